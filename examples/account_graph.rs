@@ -37,7 +37,11 @@ async fn main() -> Result<()> {
         account_type: Some(AccountNodeType::TokenMint {
             supply: 1_000_000,
             decimals: 9,
+            mint_authority: None,
+            freeze_authority: None,
         }),
+        rent_exempt: true,
+        recoverable_rent: 1_461_600,
     });
 
     graph.add_node(AccountNode {
@@ -50,7 +54,10 @@ async fn main() -> Result<()> {
             mint: token_mint,
             owner: wallet,
             amount: 0, // Empty!
+            is_nft: false,
         }),
+        rent_exempt: true,
+        recoverable_rent: 2_039_280,
     });
 
     // Add edge: token account -> mint
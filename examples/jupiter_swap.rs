@@ -6,11 +6,8 @@
 //! Run with: cargo run --example jupiter_swap --features jupiter
 
 use solana_pipkit::jupiter::{JupiterClient, SwapConfig};
-use solana_sdk::{
-    native_token::LAMPORTS_PER_SOL,
-    signature::{read_keypair_file, Keypair},
-    signer::Signer,
-};
+use solana_pipkit::keys::load_keypair;
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, signature::Keypair, signer::Signer};
 use std::env;
 
 #[tokio::main]
@@ -106,19 +103,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 }
 
 fn load_wallet() -> Result<Keypair, Box<dyn std::error::Error>> {
-    // Try environment variable first
+    // Try environment variable first (JSON byte array, base58 key or file path)
     if let Ok(key) = env::var("SOLANA_PRIVATE_KEY") {
-        let bytes: Vec<u8> = serde_json::from_str(&key)?;
-        return Ok(Keypair::from_bytes(&bytes)?);
+        return Ok(load_keypair(&key)?);
     }
 
     // Try default Solana CLI keypair location
-    let home = env::var("HOME").or_else(|_| env::var("USERPROFILE"))?;
-    let keypair_path = format!("{}/.config/solana/id.json", home);
-    
-    if std::path::Path::new(&keypair_path).exists() {
-        return Ok(read_keypair_file(&keypair_path)
-            .map_err(|e| format!("Failed to read keypair: {}", e))?);
+    let keypair_path = "~/.config/solana/id.json";
+    if let Ok(keypair) = load_keypair(keypair_path) {
+        return Ok(keypair);
     }
 
     // Generate a new keypair for testing (no funds)
@@ -96,11 +96,13 @@ async fn main() -> Result<()> {
         successful: vec![solana_sdk::signature::Signature::default()],
         failed: vec![(1, "Simulated error".to_string())],
         instructions_processed: 3,
+        batch_items: vec![vec![0, 1, 2], vec![3, 4]],
     };
 
     println!("All succeeded: {}", result.all_succeeded());
     println!("Success rate: {:.1}%", result.success_rate());
     println!("Instructions processed: {}", result.instructions_processed);
+    println!("Failed items to retry: {:?}", result.failed_items());
 
     println!("\n=== Example Complete ===");
     println!("Note: Actual transactions require a funded account");
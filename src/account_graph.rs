@@ -7,10 +7,20 @@ use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::{
     account::Account,
     commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    program_pack::Pack,
     pubkey::Pubkey,
+    rent::Rent,
+    transaction::Transaction,
 };
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::Duration;
 
+use crate::rpc::{
+    fetch_token_accounts, Network, SolanaRpc, TimeoutRpc, TokenAccountScan, DEFAULT_RPC_TIMEOUT,
+    TOKEN_ACCOUNT_SLICE_LEN,
+};
+use crate::token_utils::decode_token_account;
 use crate::{Result, ToolkitError};
 
 /// Represents a node in the account graph.
@@ -28,6 +38,20 @@ pub struct AccountNode {
     pub is_program: bool,
     /// Optional: parsed account type.
     pub account_type: Option<AccountNodeType>,
+    /// Whether the balance covers the rent-exempt minimum for `data_len`.
+    /// Accounts below it are at risk of being purged.
+    pub rent_exempt: bool,
+    /// Rent part of the balance, up to the rent-exempt minimum, that
+    /// closing the account would return.
+    pub recoverable_rent: u64,
+}
+
+impl AccountNode {
+    /// Human name from [`labels`] if the account is well known or was
+    /// registered, otherwise the shortened pubkey.
+    pub fn display_label(&self) -> String {
+        labels::display(&self.pubkey)
+    }
 }
 
 /// Type classification for account nodes.
@@ -40,25 +64,68 @@ pub enum AccountNodeType {
         mint: Pubkey,
         owner: Pubkey,
         amount: u64,
+        /// Whether the linked mint is an NFT (supply 1, 0 decimals).
+        is_nft: bool,
     },
     /// SPL Token mint.
     TokenMint {
         supply: u64,
         decimals: u8,
+        /// Account allowed to mint new tokens, if any.
+        mint_authority: Option<Pubkey>,
+        /// Account allowed to freeze token accounts, if any.
+        freeze_authority: Option<Pubkey>,
     },
     /// Metaplex metadata account.
     Metadata { mint: Pubkey },
     /// Program account.
     Program,
+    /// Data account owned by a program other than System, SPL Token or
+    /// Token Metadata, such as a PDA.
+    ProgramData {
+        /// Owning program.
+        program: Pubkey,
+    },
     /// Associated token account.
     AssociatedTokenAccount {
         wallet: Pubkey,
         mint: Pubkey,
     },
-    /// Unknown account type.
+    /// Token or metadata program account whose layout was not recognized.
     Unknown,
 }
 
+impl AccountNodeType {
+    /// Check if this is an NFT mint or a token account holding an NFT.
+    ///
+    /// A mint is treated as an NFT when `supply == 1 && decimals == 0`.
+    /// Token accounts are marked by [`AccountGraph::mark_nfts`] once their
+    /// mint is known.
+    pub fn is_nft(&self) -> bool {
+        match self {
+            AccountNodeType::TokenMint { supply, decimals, .. } => {
+                *supply == 1 && *decimals == 0
+            }
+            AccountNodeType::TokenAccount { is_nft, .. } => *is_nft,
+            _ => false,
+        }
+    }
+
+    /// The variant name, e.g. `"TokenAccount"`, without its fields.
+    pub fn name(&self) -> &'static str {
+        match self {
+            AccountNodeType::SystemAccount => "SystemAccount",
+            AccountNodeType::TokenAccount { .. } => "TokenAccount",
+            AccountNodeType::TokenMint { .. } => "TokenMint",
+            AccountNodeType::Metadata { .. } => "Metadata",
+            AccountNodeType::Program => "Program",
+            AccountNodeType::ProgramData { .. } => "ProgramData",
+            AccountNodeType::AssociatedTokenAccount { .. } => "AssociatedTokenAccount",
+            AccountNodeType::Unknown => "Unknown",
+        }
+    }
+}
+
 /// Edge type in the account graph.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum EdgeType {
@@ -87,6 +154,29 @@ pub struct AccountEdge {
     pub edge_type: EdgeType,
 }
 
+/// Structural metrics of an account graph, from [`AccountGraph::metrics`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GraphMetrics {
+    /// Number of nodes.
+    pub node_count: usize,
+    /// Number of edges, including edges to accounts that are not nodes.
+    pub edge_count: usize,
+    /// Mean number of edges leaving a node.
+    pub avg_out_degree: f64,
+    /// Most edges leaving any one node.
+    pub max_out_degree: usize,
+    /// Mean number of edges entering a node.
+    pub avg_in_degree: f64,
+    /// Most edges entering any one node.
+    pub max_in_degree: usize,
+    /// Nodes with no edges in either direction.
+    pub orphan_count: usize,
+    /// Node counts keyed by [`AccountNodeType::name`].
+    pub nodes_by_type: HashMap<&'static str, usize>,
+    /// Nodes without a parsed account type.
+    pub untyped_nodes: usize,
+}
+
 /// Account graph for tracking relationships.
 #[derive(Debug, Default)]
 pub struct AccountGraph {
@@ -148,6 +238,30 @@ impl AccountGraph {
         self.edges_from.values().map(|v| v.len()).sum()
     }
 
+    /// Check if the graph has no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Check if a node exists in the graph.
+    pub fn contains(&self, pubkey: &Pubkey) -> bool {
+        self.nodes.contains_key(pubkey)
+    }
+
+    /// Iterate over all edges.
+    pub fn all_edges(&self) -> impl Iterator<Item = &AccountEdge> {
+        self.edges_from.values().flatten()
+    }
+
+    /// Iterate over the nodes targeted by edges from a node.
+    ///
+    /// Edge targets that are not present as nodes are skipped.
+    pub fn neighbors<'a>(&'a self, pubkey: &Pubkey) -> impl Iterator<Item = &'a AccountNode> {
+        self.edges_from(pubkey)
+            .iter()
+            .filter_map(|edge| self.nodes.get(&edge.to))
+    }
+
     /// Find all accounts reachable from a starting node.
     pub fn find_reachable(&self, start: &Pubkey) -> HashSet<Pubkey> {
         let mut visited = HashSet::new();
@@ -167,6 +281,42 @@ impl AccountGraph {
         visited
     }
 
+    /// Find accounts reachable from a starting node within `max_depth` hops.
+    ///
+    /// Only edges whose type is in `edge_types` are followed; an empty slice
+    /// follows every type. A neighbor is visited only if it is a node in the
+    /// graph and passes `node_filter`. The start node is always included.
+    pub fn find_reachable_filtered(
+        &self,
+        start: &Pubkey,
+        max_depth: usize,
+        edge_types: &[EdgeType],
+        node_filter: impl Fn(&AccountNode) -> bool,
+    ) -> HashSet<Pubkey> {
+        let mut visited = HashSet::from([*start]);
+        let mut queue = VecDeque::from([(*start, 0)]);
+
+        while let Some((current, depth)) = queue.pop_front() {
+            if depth == max_depth {
+                continue;
+            }
+            for edge in self.edges_from(&current) {
+                if !edge_types.is_empty() && !edge_types.contains(&edge.edge_type) {
+                    continue;
+                }
+                if visited.contains(&edge.to)
+                    || !self.get_node(&edge.to).is_some_and(&node_filter)
+                {
+                    continue;
+                }
+                visited.insert(edge.to);
+                queue.push_back((edge.to, depth + 1));
+            }
+        }
+
+        visited
+    }
+
     /// Find all accounts that can reach a target node.
     pub fn find_reaching(&self, target: &Pubkey) -> HashSet<Pubkey> {
         let mut visited = HashSet::new();
@@ -186,6 +336,49 @@ impl AccountGraph {
         visited
     }
 
+    /// Find elementary cycles that follow only edges of `edge_types`.
+    ///
+    /// Each cycle is listed once, starting from its smallest pubkey; an edge
+    /// from an account to itself is a cycle of one. Enumeration is
+    /// exhaustive, so this is meant for wallet-sized graphs.
+    pub fn find_cycles(&self, edge_types: &[EdgeType]) -> Vec<Vec<Pubkey>> {
+        let mut starts: Vec<Pubkey> = self.edges_from.keys().copied().collect();
+        starts.sort();
+
+        let mut cycles = Vec::new();
+        for start in starts {
+            let mut path = vec![start];
+            self.extend_cycles(&mut path, edge_types, &mut cycles);
+        }
+        cycles
+    }
+
+    /// Depth-first step of [`find_cycles`](Self::find_cycles), only visiting
+    /// accounts greater than the start so each cycle is found from its
+    /// smallest member.
+    fn extend_cycles(
+        &self,
+        path: &mut Vec<Pubkey>,
+        edge_types: &[EdgeType],
+        cycles: &mut Vec<Vec<Pubkey>>,
+    ) {
+        let (start, current) = (path[0], path[path.len() - 1]);
+        for edge in self.edges_from(&current) {
+            if !edge_types.contains(&edge.edge_type) {
+                continue;
+            }
+            if edge.to == start {
+                if !cycles.contains(path) {
+                    cycles.push(path.clone());
+                }
+            } else if edge.to > start && !path.contains(&edge.to) {
+                path.push(edge.to);
+                self.extend_cycles(path, edge_types, cycles);
+                path.pop();
+            }
+        }
+    }
+
     /// Get all nodes of a specific type.
     pub fn nodes_of_type(&self, node_type: &AccountNodeType) -> Vec<&AccountNode> {
         self.nodes
@@ -194,6 +387,18 @@ impl AccountGraph {
             .collect()
     }
 
+    /// Find all accounts owned by `program`, of any type.
+    pub fn accounts_owned_by(&self, program: &Pubkey) -> Vec<&AccountNode> {
+        self.nodes.values().filter(|n| n.owner == *program).collect()
+    }
+
+    /// Iterate over edges whose target is not a node in the graph, such as
+    /// the `OwnedBy` edge of a [`AccountNodeType::ProgramData`] account
+    /// whose program was not fetched.
+    pub fn dangling_edges(&self) -> impl Iterator<Item = &AccountEdge> {
+        self.all_edges().filter(|edge| !self.nodes.contains_key(&edge.to))
+    }
+
     /// Find all token accounts for a specific mint.
     pub fn token_accounts_for_mint(&self, mint: &Pubkey) -> Vec<&AccountNode> {
         self.nodes
@@ -219,33 +424,182 @@ impl AccountGraph {
         self.nodes.values().map(|n| n.lamports).sum()
     }
 
+    /// Mark token accounts whose mint in the graph is an NFT.
+    ///
+    /// Token accounts whose mint is not present in the graph are left as-is.
+    pub fn mark_nfts(&mut self) {
+        let nft_mints: HashSet<Pubkey> = self
+            .nodes
+            .values()
+            .filter(|n| {
+                matches!(&n.account_type, Some(t @ AccountNodeType::TokenMint { .. }) if t.is_nft())
+            })
+            .map(|n| n.pubkey)
+            .collect();
+
+        for node in self.nodes.values_mut() {
+            if let Some(AccountNodeType::TokenAccount { mint, is_nft, .. }) = &mut node.account_type {
+                if nft_mints.contains(mint) {
+                    *is_nft = true;
+                }
+            }
+        }
+    }
+
+    /// Compute node and edge counts, degree statistics, orphan nodes and
+    /// node counts per type.
+    ///
+    /// Degrees are counted per node, so edges to or from accounts that are
+    /// not nodes count toward `edge_count` but not toward the averages of
+    /// the other end.
+    pub fn metrics(&self) -> GraphMetrics {
+        let mut metrics = GraphMetrics {
+            node_count: self.node_count(),
+            edge_count: self.edge_count(),
+            ..Default::default()
+        };
+        let (mut total_out, mut total_in) = (0, 0);
+
+        for node in self.nodes.values() {
+            let out_degree = self.edges_from(&node.pubkey).len();
+            let in_degree = self.edges_to(&node.pubkey).len();
+            total_out += out_degree;
+            total_in += in_degree;
+            metrics.max_out_degree = metrics.max_out_degree.max(out_degree);
+            metrics.max_in_degree = metrics.max_in_degree.max(in_degree);
+            if out_degree == 0 && in_degree == 0 {
+                metrics.orphan_count += 1;
+            }
+            match &node.account_type {
+                Some(account_type) => {
+                    *metrics.nodes_by_type.entry(account_type.name()).or_default() += 1
+                }
+                None => metrics.untyped_nodes += 1,
+            }
+        }
+
+        if metrics.node_count > 0 {
+            metrics.avg_out_degree = total_out as f64 / metrics.node_count as f64;
+            metrics.avg_in_degree = total_in as f64 / metrics.node_count as f64;
+        }
+        metrics
+    }
+
     /// Get nodes sorted by lamports (descending).
     pub fn nodes_by_lamports(&self) -> Vec<&AccountNode> {
         let mut nodes: Vec<_> = self.nodes.values().collect();
         nodes.sort_by(|a, b| b.lamports.cmp(&a.lamports));
         nodes
     }
+
+    /// Render the graph in Graphviz DOT format.
+    ///
+    /// Nodes, including dangling edge targets, are labelled with
+    /// [`labels::display`] and edges with their type.
+    pub fn to_dot(&self) -> String {
+        let mut pubkeys: Vec<Pubkey> = self
+            .nodes
+            .keys()
+            .copied()
+            .chain(self.all_edges().flat_map(|edge| [edge.from, edge.to]))
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        pubkeys.sort();
+
+        let mut edges: Vec<&AccountEdge> = self.all_edges().collect();
+        edges.sort_by_key(|edge| (edge.from, edge.to));
+
+        let mut lines = vec!["digraph accounts {".to_string()];
+        for pubkey in &pubkeys {
+            lines.push(format!("    \"{}\" [label=\"{}\"];", pubkey, labels::display(pubkey)));
+        }
+        for edge in edges {
+            lines.push(format!(
+                "    \"{}\" -> \"{}\" [label=\"{:?}\"];",
+                edge.from, edge.to, edge.edge_type
+            ));
+        }
+        lines.push("}".to_string());
+        lines.join("\n")
+    }
+
+    /// Render the accounts reachable from `root` as an indented tree, one
+    /// line per edge. An account already shown is not expanded again.
+    pub fn format_tree(&self, root: &Pubkey) -> String {
+        let mut lines = vec![labels::display(root)];
+        let mut visited = HashSet::from([*root]);
+        self.extend_tree(root, 1, &mut visited, &mut lines);
+        lines.join("\n")
+    }
+
+    /// Print [`format_tree`](Self::format_tree) to stdout.
+    pub fn print_tree(&self, root: &Pubkey) {
+        println!("{}", self.format_tree(root));
+    }
+
+    fn extend_tree(
+        &self,
+        pubkey: &Pubkey,
+        depth: usize,
+        visited: &mut HashSet<Pubkey>,
+        lines: &mut Vec<String>,
+    ) {
+        for edge in self.edges_from(pubkey) {
+            lines.push(format!(
+                "{}{:?} -> {}",
+                "  ".repeat(depth),
+                edge.edge_type,
+                labels::display(&edge.to)
+            ));
+            if visited.insert(edge.to) {
+                self.extend_tree(&edge.to, depth + 1, visited, lines);
+            }
+        }
+    }
 }
 
 /// Account graph builder for constructing graphs from on-chain data.
-pub struct AccountGraphBuilder {
-    client: RpcClient,
+pub struct AccountGraphBuilder<R = RpcClient> {
+    client: TimeoutRpc<R>,
+    token_account_scan: TokenAccountScan,
 }
 
 impl AccountGraphBuilder {
     /// Create a new graph builder.
     pub fn new(rpc_url: &str) -> Self {
+        Self::from_client(RpcClient::new_with_commitment(
+            rpc_url.to_string(),
+            CommitmentConfig::confirmed(),
+        ))
+    }
+
+    /// Create for a well-known network.
+    pub fn new_on(network: Network) -> Self {
+        Self::new(network.rpc_url())
+    }
+}
+
+impl<R: SolanaRpc> AccountGraphBuilder<R> {
+    /// Create from an existing RPC client.
+    pub fn from_client(client: R) -> Self {
         Self {
-            client: RpcClient::new_with_commitment(
-                rpc_url.to_string(),
-                CommitmentConfig::confirmed(),
-            ),
+            client: TimeoutRpc::new(client, DEFAULT_RPC_TIMEOUT),
+            token_account_scan: TokenAccountScan::ByOwner,
         }
     }
 
-    /// Create from an existing RPC client.
-    pub fn from_client(client: RpcClient) -> Self {
-        Self { client }
+    /// Set how token accounts are fetched for wallet graphs.
+    pub fn with_token_account_scan(mut self, scan: TokenAccountScan) -> Self {
+        self.token_account_scan = scan;
+        self
+    }
+
+    /// Fail RPC calls that take longer than `timeout` (30s by default)
+    /// with [`ToolkitError::Timeout`].
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client.set_timeout(timeout);
+        self
     }
 
     /// Build a graph from a list of account pubkeys.
@@ -257,7 +611,7 @@ impl AccountGraphBuilder {
 
         for (pubkey, maybe_account) in pubkeys.iter().zip(accounts.iter()) {
             if let Some(account) = maybe_account {
-                let node = self.create_node(*pubkey, account);
+                let node = self.create_node(*pubkey, account, account.data.len());
                 graph.add_node(node);
             }
         }
@@ -268,47 +622,32 @@ impl AccountGraphBuilder {
         Ok(graph)
     }
 
+    /// Build a graph of every account a transaction references, including
+    /// the programs it invokes, for inspection before signing.
+    ///
+    /// Accounts that do not exist yet are left out.
+    pub async fn build_from_transaction(&self, tx: &Transaction) -> Result<AccountGraph> {
+        self.build_from_accounts(&tx.message.account_keys).await
+    }
+
     /// Build a graph of all token accounts for a wallet.
     pub async fn build_token_account_graph(&self, wallet: &Pubkey) -> Result<AccountGraph> {
         let mut graph = AccountGraph::new();
-        let token_program = spl_token::id();
 
         // Get all token accounts
-        let accounts = self
-            .client
-            .get_token_accounts_by_owner(
-                wallet,
-                solana_client::rpc_request::TokenAccountsFilter::ProgramId(token_program),
-            )
-            .await?;
-
-        let mut pubkeys = Vec::new();
-        let mut mints = HashSet::new();
+        let accounts = fetch_token_accounts(&self.client, wallet, self.token_account_scan).await?;
 
-        for keyed_account in &accounts {
-            let pubkey = keyed_account.pubkey.parse::<Pubkey>().map_err(|e| {
-                ToolkitError::Custom(format!("Failed to parse pubkey: {}", e))
-            })?;
-            pubkeys.push(pubkey);
+        let mut mints = HashSet::new();
 
+        for (pubkey, account) in &accounts {
             // Parse token account to get mint
-            if let Some(account) = keyed_account.account.decode::<Account>() {
-                if account.data.len() >= 32 {
-                    let mint = Pubkey::try_from(&account.data[0..32])
-                        .map_err(|_| ToolkitError::InvalidAccountData("Invalid mint".to_string()))?;
-                    mints.insert(mint);
-                }
+            if let Some(token) = decode_token_account(account) {
+                mints.insert(token.mint);
             }
-        }
-
-        // Fetch token accounts
-        let token_accounts = self.client.get_multiple_accounts(&pubkeys).await?;
 
-        for (pubkey, maybe_account) in pubkeys.iter().zip(token_accounts.iter()) {
-            if let Some(account) = maybe_account {
-                let node = self.create_node(*pubkey, account);
-                graph.add_node(node);
-            }
+            // Sliced fetches only return the leading bytes
+            let data_len = account.data.len().max(spl_token::state::Account::LEN);
+            graph.add_node(self.create_node(*pubkey, account, data_len));
         }
 
         // Fetch and add mints
@@ -317,7 +656,7 @@ impl AccountGraphBuilder {
 
         for (pubkey, maybe_account) in mint_pubkeys.iter().zip(mint_accounts.iter()) {
             if let Some(account) = maybe_account {
-                let node = self.create_node(*pubkey, account);
+                let node = self.create_node(*pubkey, account, account.data.len());
                 graph.add_node(node);
             }
         }
@@ -328,17 +667,23 @@ impl AccountGraphBuilder {
         Ok(graph)
     }
 
-    /// Create a node from account data.
-    fn create_node(&self, pubkey: Pubkey, account: &Account) -> AccountNode {
+    /// Create a node from account data, sized `data_len` on chain.
+    ///
+    /// Rent exemption is judged against the default rent parameters, which
+    /// every public cluster uses.
+    fn create_node(&self, pubkey: Pubkey, account: &Account, data_len: usize) -> AccountNode {
         let account_type = self.classify_account(account);
+        let minimum = Rent::default().minimum_balance(data_len);
 
         AccountNode {
             pubkey,
             owner: account.owner,
             lamports: account.lamports,
-            data_len: account.data.len(),
+            data_len,
             is_program: account.executable,
             account_type: Some(account_type),
+            rent_exempt: account.lamports >= minimum,
+            recoverable_rent: account.lamports.min(minimum),
         }
     }
 
@@ -353,10 +698,10 @@ impl AccountGraphBuilder {
 
         // Token program accounts
         if owner == spl_token::id() {
-            // Token account (165 bytes) vs Mint (82 bytes)
-            if account.data.len() == 165 {
+            // Token account (165 bytes, or a sliced fetch) vs Mint (82 bytes)
+            if account.data.len() == 165 || account.data.len() == TOKEN_ACCOUNT_SLICE_LEN {
                 // Parse token account
-                if let Ok(token_account) = self.parse_token_account(&account.data) {
+                if let Ok(token_account) = self.parse_token_account(account) {
                     return token_account;
                 }
             } else if account.data.len() == 82 {
@@ -382,49 +727,76 @@ impl AccountGraphBuilder {
             return AccountNodeType::Program;
         }
 
+        // Data account of some other program
+        if owner != spl_token::id() && owner != mpl_token_metadata::ID {
+            return AccountNodeType::ProgramData { program: owner };
+        }
+
         AccountNodeType::Unknown
     }
 
     /// Parse SPL token account data.
-    fn parse_token_account(&self, data: &[u8]) -> Result<AccountNodeType> {
-        if data.len() < 72 {
-            return Err(ToolkitError::InvalidAccountData("Data too short".to_string()));
-        }
-
-        let mint = Pubkey::try_from(&data[0..32])
-            .map_err(|_| ToolkitError::InvalidAccountData("Invalid mint".to_string()))?;
-        let owner = Pubkey::try_from(&data[32..64])
-            .map_err(|_| ToolkitError::InvalidAccountData("Invalid owner".to_string()))?;
-        let amount = u64::from_le_bytes(
-            data[64..72].try_into().map_err(|_| ToolkitError::InvalidAccountData("Invalid amount".to_string()))?
-        );
-
-        Ok(AccountNodeType::TokenAccount { mint, owner, amount })
+    fn parse_token_account(&self, account: &Account) -> Result<AccountNodeType> {
+        let token = decode_token_account(account).ok_or_else(|| {
+            ToolkitError::InvalidAccountData("Invalid token account data".to_string())
+        })?;
+
+        Ok(AccountNodeType::TokenAccount {
+            mint: token.mint,
+            owner: token.owner,
+            amount: token.amount,
+            is_nft: false,
+        })
     }
 
     /// Parse SPL mint data.
     fn parse_mint(&self, data: &[u8]) -> Result<AccountNodeType> {
-        if data.len() < 45 {
+        if data.len() < 82 {
             return Err(ToolkitError::InvalidAccountData("Data too short".to_string()));
         }
 
+        // Mint authority is a COption<Pubkey> at offset 0-36
+        let mint_authority = Self::parse_coption_pubkey(&data[0..36])?;
         // Supply is at offset 36-44
         let supply = u64::from_le_bytes(
             data[36..44].try_into().map_err(|_| ToolkitError::InvalidAccountData("Invalid supply".to_string()))?
         );
         // Decimals at offset 44
         let decimals = data[44];
+        // Freeze authority is a COption<Pubkey> at offset 46-82
+        let freeze_authority = Self::parse_coption_pubkey(&data[46..82])?;
+
+        Ok(AccountNodeType::TokenMint {
+            supply,
+            decimals,
+            mint_authority,
+            freeze_authority,
+        })
+    }
 
-        Ok(AccountNodeType::TokenMint { supply, decimals })
+    /// Parse a 36-byte `COption<Pubkey>` (4-byte tag followed by the key).
+    fn parse_coption_pubkey(data: &[u8]) -> Result<Option<Pubkey>> {
+        match data[0..4] {
+            [0, 0, 0, 0] => Ok(None),
+            [1, 0, 0, 0] => Pubkey::try_from(&data[4..36])
+                .map(Some)
+                .map_err(|_| ToolkitError::InvalidAccountData("Invalid authority".to_string())),
+            _ => Err(ToolkitError::InvalidAccountData("Invalid option tag".to_string())),
+        }
     }
 
     /// Build edges based on account relationships.
     fn build_edges(&self, graph: &mut AccountGraph) {
+        graph.mark_nfts();
+
         let nodes: Vec<_> = graph.nodes.values().cloned().collect();
 
         for node in &nodes {
-            // Add owner edge
-            if graph.nodes.contains_key(&node.owner) {
+            // Add owner edge, kept dangling for program data so the link to
+            // the program survives when the program itself was not fetched
+            let program_data =
+                matches!(node.account_type, Some(AccountNodeType::ProgramData { .. }));
+            if program_data || graph.nodes.contains_key(&node.owner) {
                 graph.add_edge(AccountEdge {
                     from: node.pubkey,
                     to: node.owner,
@@ -453,6 +825,24 @@ impl AccountGraphBuilder {
                 }
             }
 
+            // Add mint authority edges
+            if let Some(AccountNodeType::TokenMint {
+                mint_authority,
+                freeze_authority,
+                ..
+            }) = &node.account_type
+            {
+                for authority in [mint_authority, freeze_authority].into_iter().flatten() {
+                    if graph.nodes.contains_key(authority) {
+                        graph.add_edge(AccountEdge {
+                            from: node.pubkey,
+                            to: *authority,
+                            edge_type: EdgeType::Authority,
+                        });
+                    }
+                }
+            }
+
             // Add metadata edges
             if let Some(AccountNodeType::Metadata { mint }) = &node.account_type {
                 if graph.nodes.contains_key(mint) {
@@ -467,6 +857,70 @@ impl AccountGraphBuilder {
     }
 }
 
+/// Human names for well-known programs, sysvars and mints.
+///
+/// Seeded with the System, Token, Token-2022, Associated Token, Token
+/// Metadata, Compute Budget, Memo and upgradeable BPF loader programs, the
+/// rent and clock sysvars, and the mints in
+/// [`WELL_KNOWN_MINTS`](crate::token_utils::WELL_KNOWN_MINTS) by symbol.
+/// [`register`](labels::register) adds names for the whole process.
+pub mod labels {
+    use super::*;
+    use std::str::FromStr;
+    use std::sync::{OnceLock, RwLock};
+
+    use crate::safety::AddressVerifier;
+    use crate::token_utils::WELL_KNOWN_MINTS;
+
+    /// SPL Memo program.
+    const MEMO_PROGRAM: &str = "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr";
+
+    static LABELS: OnceLock<RwLock<HashMap<Pubkey, String>>> = OnceLock::new();
+
+    fn labels() -> &'static RwLock<HashMap<Pubkey, String>> {
+        LABELS.get_or_init(|| RwLock::new(well_known()))
+    }
+
+    fn well_known() -> HashMap<Pubkey, String> {
+        let programs = [
+            (solana_sdk::system_program::id(), "System Program"),
+            (spl_token::id(), "Token Program"),
+            (spl_token_2022::id(), "Token-2022 Program"),
+            (spl_associated_token_account::id(), "Associated Token Program"),
+            (mpl_token_metadata::ID, "Token Metadata Program"),
+            (solana_sdk::compute_budget::id(), "Compute Budget Program"),
+            (Pubkey::from_str(MEMO_PROGRAM).expect("valid memo program id"), "Memo Program"),
+            (solana_sdk::bpf_loader_upgradeable::id(), "BPF Upgradeable Loader"),
+            (solana_sdk::sysvar::rent::id(), "Rent Sysvar"),
+            (solana_sdk::sysvar::clock::id(), "Clock Sysvar"),
+        ];
+        let mints = WELL_KNOWN_MINTS.iter().map(|(address, _, symbol)| {
+            (Pubkey::from_str(address).expect("valid well-known mint"), *symbol)
+        });
+        programs
+            .into_iter()
+            .chain(mints)
+            .map(|(pubkey, name)| (pubkey, name.to_string()))
+            .collect()
+    }
+
+    /// Name `pubkey` in every graph rendered by this process, replacing any
+    /// existing label.
+    pub fn register(pubkey: Pubkey, name: impl Into<String>) {
+        labels().write().unwrap().insert(pubkey, name.into());
+    }
+
+    /// Label for `pubkey`, if it has one.
+    pub fn get(pubkey: &Pubkey) -> Option<String> {
+        labels().read().unwrap().get(pubkey).cloned()
+    }
+
+    /// Label for `pubkey`, or its shortened form such as `7xKX...8AsU`.
+    pub fn display(pubkey: &Pubkey) -> String {
+        get(pubkey).unwrap_or_else(|| AddressVerifier::format_address_short(pubkey))
+    }
+}
+
 /// Utility functions for account graph operations.
 pub mod utils {
     use super::*;
@@ -484,6 +938,41 @@ pub mod utils {
             .collect()
     }
 
+    /// Build close instructions for every closeable account `authority`
+    /// owns, returning the rent to `authority`.
+    ///
+    /// Each instruction targets the node's own token program, so SPL Token
+    /// and Token-2022 accounts can be mixed. Accounts owned by another
+    /// wallet are skipped since `authority` can't sign for them. The
+    /// instructions are ordered by account and can be batched with
+    /// [`BatchExecutor`](crate::transaction::BatchExecutor).
+    pub fn build_close_instructions(graph: &AccountGraph, authority: &Pubkey) -> Vec<Instruction> {
+        let mut closeable: Vec<&AccountNode> = find_closeable_accounts(graph)
+            .into_iter()
+            .filter(|n| {
+                matches!(
+                    &n.account_type,
+                    Some(AccountNodeType::TokenAccount { owner, .. }) if owner == authority
+                )
+            })
+            .collect();
+        closeable.sort_by_key(|n| n.pubkey);
+
+        closeable
+            .into_iter()
+            .filter_map(|node| {
+                spl_token_2022::instruction::close_account(
+                    &node.owner,
+                    &node.pubkey,
+                    authority,
+                    authority,
+                    &[],
+                )
+                .ok()
+            })
+            .collect()
+    }
+
     /// Calculate total recoverable rent from closeable accounts.
     pub fn total_recoverable_rent(graph: &AccountGraph) -> u64 {
         find_closeable_accounts(graph)
@@ -492,6 +981,11 @@ pub mod utils {
             .sum()
     }
 
+    /// Find all accounts holding at least the rent-exempt minimum.
+    pub fn rent_exempt_nodes(graph: &AccountGraph) -> Vec<&AccountNode> {
+        graph.nodes().filter(|n| n.rent_exempt).collect()
+    }
+
     /// Group accounts by their owner program.
     pub fn group_by_owner(graph: &AccountGraph) -> HashMap<Pubkey, Vec<&AccountNode>> {
         let mut groups: HashMap<Pubkey, Vec<&AccountNode>> = HashMap::new();
@@ -501,6 +995,156 @@ pub mod utils {
         groups
     }
 
+    /// Find all token accounts holding NFTs (mint supply 1, 0 decimals).
+    pub fn nft_accounts(graph: &AccountGraph) -> Vec<&AccountNode> {
+        graph
+            .nodes()
+            .filter(|n| {
+                matches!(&n.account_type, Some(t @ AccountNodeType::TokenAccount { .. }) if t.is_nft())
+            })
+            .collect()
+    }
+
+    /// Summary of the token holdings in a graph.
+    #[derive(Debug, Clone, Default, PartialEq, Eq)]
+    pub struct PortfolioSummary {
+        /// Total token accounts.
+        pub token_accounts: usize,
+        /// Token accounts holding fungible tokens.
+        pub fungible_accounts: usize,
+        /// Token accounts holding NFTs (collectibles).
+        pub nft_accounts: usize,
+        /// Token accounts with a zero balance.
+        pub empty_accounts: usize,
+        /// Total lamports across all nodes.
+        pub total_lamports: u64,
+    }
+
+    /// Summarize token holdings, counting NFTs separately from fungible balances.
+    pub fn portfolio_summary(graph: &AccountGraph) -> PortfolioSummary {
+        let mut summary = PortfolioSummary {
+            total_lamports: graph.total_lamports(),
+            ..Default::default()
+        };
+
+        for node in graph.nodes() {
+            if let Some(t @ AccountNodeType::TokenAccount { amount, .. }) = &node.account_type {
+                summary.token_accounts += 1;
+                if t.is_nft() {
+                    summary.nft_accounts += 1;
+                } else {
+                    summary.fungible_accounts += 1;
+                }
+                if *amount == 0 {
+                    summary.empty_accounts += 1;
+                }
+            }
+        }
+
+        summary
+    }
+
+    /// Kind of structure reported by [`flag_suspicious_structures`].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum SuspicionKind {
+        /// Accounts that own or have authority over each other in a loop.
+        OwnershipCycle,
+        /// A mint whose mint or freeze authority is a token account of
+        /// that same mint.
+        SelfReferentialMint,
+        /// Metadata pointing at a mint that is missing from the graph or is
+        /// not a token mint.
+        DanglingMetadata,
+    }
+
+    /// A suspicious structure found in an account graph.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct Suspicion {
+        /// What was found.
+        pub kind: SuspicionKind,
+        /// Accounts involved, in cycle order for cycles.
+        pub accounts: Vec<Pubkey>,
+        /// Human-readable explanation.
+        pub reason: String,
+    }
+
+    /// Flag account structures that are commonly used to confuse analysis.
+    ///
+    /// Reports:
+    /// - Cycles in [`EdgeType::OwnedBy`] and [`EdgeType::Authority`] edges,
+    ///   including accounts that are their own authority
+    /// - Mints whose mint or freeze authority is a token account of the
+    ///   same mint
+    /// - Metadata whose mint is missing from the graph or is not a mint
+    pub fn flag_suspicious_structures(graph: &AccountGraph) -> Vec<Suspicion> {
+        let mut suspicions = Vec::new();
+
+        for cycle in graph.find_cycles(&[EdgeType::OwnedBy, EdgeType::Authority]) {
+            let mut names: Vec<String> = cycle.iter().map(|p| p.to_string()).collect();
+            names.push(cycle[0].to_string());
+            let reason = if cycle.len() == 1 {
+                format!("Ownership cycle: {} is its own owner or authority", cycle[0])
+            } else {
+                format!("Ownership cycle: {}", names.join(" -> "))
+            };
+            suspicions.push(Suspicion {
+                kind: SuspicionKind::OwnershipCycle,
+                accounts: cycle,
+                reason,
+            });
+        }
+
+        let mut nodes: Vec<&AccountNode> = graph.nodes().collect();
+        nodes.sort_by_key(|n| n.pubkey);
+        for node in nodes {
+            match &node.account_type {
+                Some(AccountNodeType::TokenMint { mint_authority, freeze_authority, .. }) => {
+                    let authorities = [("mint", mint_authority), ("freeze", freeze_authority)];
+                    for (role, authority) in authorities {
+                        let Some(authority) = authority else { continue };
+                        let holds_own_mint = matches!(
+                            graph.get_node(authority).and_then(|a| a.account_type.as_ref()),
+                            Some(AccountNodeType::TokenAccount { mint, .. }) if *mint == node.pubkey
+                        );
+                        if holds_own_mint {
+                            suspicions.push(Suspicion {
+                                kind: SuspicionKind::SelfReferentialMint,
+                                accounts: vec![node.pubkey, *authority],
+                                reason: format!(
+                                    "Mint {}'s {} authority is {}, a token account of the \
+                                     same mint",
+                                    node.pubkey, role, authority
+                                ),
+                            });
+                        }
+                    }
+                }
+                Some(AccountNodeType::Metadata { mint }) => {
+                    let problem = match graph.get_node(mint) {
+                        None => Some("is not in the graph"),
+                        Some(target) => match target.account_type {
+                            Some(AccountNodeType::TokenMint { .. }) => None,
+                            _ => Some("is not a token mint"),
+                        },
+                    };
+                    if let Some(problem) = problem {
+                        suspicions.push(Suspicion {
+                            kind: SuspicionKind::DanglingMetadata,
+                            accounts: vec![node.pubkey, *mint],
+                            reason: format!(
+                                "Metadata {} points at mint {}, which {}",
+                                node.pubkey, mint, problem
+                            ),
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        suspicions
+    }
+
     /// Find accounts with the largest balances.
     pub fn top_accounts_by_balance(graph: &AccountGraph, limit: usize) -> Vec<&AccountNode> {
         let mut nodes = graph.nodes_by_lamports();
@@ -532,12 +1176,124 @@ mod tests {
             data_len: 0,
             is_program: false,
             account_type: Some(AccountNodeType::SystemAccount),
+            rent_exempt: false,
+            recoverable_rent: 1000,
         });
 
         assert_eq!(graph.node_count(), 1);
         assert!(graph.get_node(&pubkey).is_some());
     }
 
+    #[test]
+    fn test_labels_in_rendering() {
+        let system = solana_sdk::system_program::id();
+        let wallet = Pubkey::new_unique();
+        let vault = Pubkey::new_unique();
+        let mut graph = AccountGraph::new();
+        for (pubkey, owner) in [(system, Pubkey::default()), (wallet, system), (vault, system)] {
+            graph.add_node(AccountNode {
+                pubkey,
+                owner,
+                lamports: 1000,
+                data_len: 0,
+                is_program: pubkey == system,
+                account_type: Some(AccountNodeType::SystemAccount),
+                rent_exempt: false,
+                recoverable_rent: 1000,
+            });
+        }
+        for from in [wallet, vault] {
+            graph.add_edge(AccountEdge { from, to: system, edge_type: EdgeType::OwnedBy });
+        }
+        graph.add_edge(AccountEdge { from: wallet, to: vault, edge_type: EdgeType::Related });
+
+        assert_eq!(graph.get_node(&system).unwrap().display_label(), "System Program");
+        let short = crate::safety::AddressVerifier::format_address_short(&wallet);
+        assert_eq!(graph.get_node(&wallet).unwrap().display_label(), short);
+        assert_eq!(labels::display(&spl_token::id()), "Token Program");
+        assert_eq!(
+            labels::get(&"EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".parse().unwrap()),
+            Some("USDC".to_string())
+        );
+
+        labels::register(vault, "Treasury");
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph accounts {"));
+        assert!(dot.contains(&format!("\"{}\" [label=\"System Program\"];", system)));
+        assert!(dot.contains(&format!("\"{}\" [label=\"Treasury\"];", vault)));
+        assert!(dot.contains(&format!("\"{}\" -> \"{}\" [label=\"OwnedBy\"];", wallet, system)));
+
+        // System Program is shown again but not expanded
+        let tree = graph.format_tree(&wallet);
+        let expected = format!(
+            "{}\n  OwnedBy -> System Program\n  Related -> Treasury\n    OwnedBy -> System Program",
+            short
+        );
+        assert_eq!(tree, expected);
+    }
+
+    #[test]
+    fn test_graph_metrics() {
+        assert_eq!(AccountGraph::new().metrics(), GraphMetrics::default());
+
+        let mut graph = AccountGraph::new();
+        let (wallet, mint, token_account, orphan, untyped) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let token = AccountNodeType::TokenAccount { mint, owner: wallet, amount: 5, is_nft: false };
+        let mint_type = AccountNodeType::TokenMint {
+            supply: 5,
+            decimals: 0,
+            mint_authority: None,
+            freeze_authority: None,
+        };
+        for (pubkey, account_type) in [
+            (wallet, Some(AccountNodeType::SystemAccount)),
+            (orphan, Some(AccountNodeType::SystemAccount)),
+            (mint, Some(mint_type)),
+            (token_account, Some(token)),
+            (untyped, None),
+        ] {
+            graph.add_node(AccountNode {
+                pubkey,
+                owner: solana_sdk::system_program::id(),
+                lamports: 1,
+                data_len: 0,
+                is_program: false,
+                account_type,
+                rent_exempt: false,
+                recoverable_rent: 1,
+            });
+        }
+        for (from, to, edge_type) in [
+            (token_account, mint, EdgeType::TokenAccountOf),
+            (wallet, token_account, EdgeType::Authority),
+            (untyped, mint, EdgeType::Related),
+            // Target is not a node
+            (token_account, spl_token::id(), EdgeType::OwnedBy),
+        ] {
+            graph.add_edge(AccountEdge { from, to, edge_type });
+        }
+
+        let metrics = graph.metrics();
+        assert_eq!(metrics.node_count, 5);
+        assert_eq!(metrics.edge_count, 4);
+        assert_eq!(metrics.avg_out_degree, 0.8);
+        assert_eq!(metrics.max_out_degree, 2);
+        assert_eq!(metrics.avg_in_degree, 0.6);
+        assert_eq!(metrics.max_in_degree, 2);
+        assert_eq!(metrics.orphan_count, 1);
+        assert_eq!(metrics.nodes_by_type["SystemAccount"], 2);
+        assert_eq!(metrics.nodes_by_type["TokenMint"], 1);
+        assert_eq!(metrics.nodes_by_type["TokenAccount"], 1);
+        assert_eq!(metrics.nodes_by_type.len(), 3);
+        assert_eq!(metrics.untyped_nodes, 1);
+    }
+
     #[test]
     fn test_add_edge() {
         let mut graph = AccountGraph::new();
@@ -579,6 +1335,52 @@ mod tests {
         assert!(reachable.contains(&c));
     }
 
+    #[test]
+    fn test_find_reachable_filtered() {
+        let mut graph = AccountGraph::new();
+        let wallet = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let token_account = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let far = Pubkey::new_unique();
+        for pubkey in [wallet, mint, token_account, authority, far] {
+            graph.add_node(AccountNode {
+                pubkey,
+                owner: solana_sdk::system_program::id(),
+                lamports: 1,
+                data_len: 0,
+                is_program: false,
+                account_type: None,
+                rent_exempt: false,
+                recoverable_rent: 1,
+            });
+        }
+        for (from, to, edge_type) in [
+            (wallet, token_account, EdgeType::Related),
+            (wallet, authority, EdgeType::Authority),
+            (token_account, mint, EdgeType::TokenAccountOf),
+            (mint, far, EdgeType::Related),
+        ] {
+            graph.add_edge(AccountEdge { from, to, edge_type });
+        }
+
+        let direct = graph.find_reachable_filtered(&wallet, 1, &[], |_| true);
+        assert_eq!(direct, HashSet::from([wallet, token_account, authority]));
+
+        let everything = graph.find_reachable_filtered(&wallet, usize::MAX, &[], |_| true);
+        assert_eq!(everything, graph.find_reachable(&wallet));
+        assert_eq!(graph.find_reachable_filtered(&wallet, 0, &[], |_| true).len(), 1);
+
+        let token_edges = [EdgeType::TokenAccountOf];
+        let token_only = graph.find_reachable_filtered(&token_account, 10, &token_edges, |_| true);
+        assert_eq!(token_only, HashSet::from([token_account, mint]));
+        let from_wallet = graph.find_reachable_filtered(&wallet, 10, &token_edges, |_| true);
+        assert_eq!(from_wallet, HashSet::from([wallet]));
+
+        let without_mint = graph.find_reachable_filtered(&wallet, 10, &[], |n| n.pubkey != mint);
+        assert_eq!(without_mint, HashSet::from([wallet, token_account, authority]));
+    }
+
     #[test]
     fn test_total_lamports() {
         let mut graph = AccountGraph::new();
@@ -591,9 +1393,392 @@ mod tests {
                 data_len: 0,
                 is_program: false,
                 account_type: None,
+                rent_exempt: false,
+                recoverable_rent: 1000 * (i + 1) as u64,
             });
         }
 
         assert_eq!(graph.total_lamports(), 6000);
     }
+
+    fn token_node(pubkey: Pubkey, account_type: AccountNodeType) -> AccountNode {
+        AccountNode {
+            pubkey,
+            owner: spl_token::id(),
+            lamports: 2_039_280,
+            data_len: 165,
+            is_program: false,
+            account_type: Some(account_type),
+            rent_exempt: true,
+            recoverable_rent: 2_039_280,
+        }
+    }
+
+    #[test]
+    fn test_flag_suspicious_structures() {
+        use utils::{flag_suspicious_structures, SuspicionKind};
+
+        let mut graph = AccountGraph::new();
+        let (mint, wallet) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let [a, b, c, held] = [(); 4].map(|_| Pubkey::new_unique());
+        let token_account = |owner| AccountNodeType::TokenAccount {
+            mint,
+            owner,
+            amount: 0,
+            is_nft: false,
+        };
+
+        graph.add_node(token_node(mint, AccountNodeType::TokenMint {
+            supply: 100,
+            decimals: 6,
+            mint_authority: Some(held),
+            freeze_authority: None,
+        }));
+        // a and b own each other, c owns itself, held is an ordinary account
+        graph.add_node(token_node(a, token_account(b)));
+        graph.add_node(token_node(b, token_account(a)));
+        graph.add_node(token_node(c, token_account(c)));
+        graph.add_node(token_node(held, token_account(wallet)));
+        for (from, to, edge_type) in [
+            (a, b, EdgeType::Authority),
+            (b, a, EdgeType::Authority),
+            (c, c, EdgeType::Authority),
+            (mint, held, EdgeType::Authority),
+            (held, mint, EdgeType::TokenAccountOf),
+        ] {
+            graph.add_edge(AccountEdge { from, to, edge_type });
+        }
+        let metadata = Pubkey::new_unique();
+        graph.add_node(AccountNode {
+            pubkey: metadata,
+            owner: Pubkey::new_unique(),
+            lamports: 5_616_720,
+            data_len: 679,
+            is_program: false,
+            account_type: Some(AccountNodeType::Metadata { mint: Pubkey::new_unique() }),
+            rent_exempt: true,
+            recoverable_rent: 5_616_720,
+        });
+
+        let suspicions = flag_suspicious_structures(&graph);
+        let of_kind = |kind| suspicions.iter().filter(move |s| s.kind == kind);
+
+        let cycles: Vec<_> = of_kind(SuspicionKind::OwnershipCycle).collect();
+        assert_eq!(cycles.len(), 2, "{:?}", suspicions);
+        let self_owned = cycles.iter().find(|s| s.accounts == vec![c]).unwrap();
+        assert!(self_owned.reason.contains("is its own owner or authority"));
+        let pair = cycles.iter().find(|s| s.accounts.len() == 2).unwrap();
+        assert_eq!(pair.accounts, vec![a.min(b), a.max(b)]);
+        assert!(pair.reason.starts_with("Ownership cycle:"));
+
+        let minted: Vec<_> = of_kind(SuspicionKind::SelfReferentialMint).collect();
+        assert_eq!(minted.len(), 1);
+        assert_eq!(minted[0].accounts, vec![mint, held]);
+        assert!(minted[0].reason.contains("mint authority"));
+
+        let dangling: Vec<_> = of_kind(SuspicionKind::DanglingMetadata).collect();
+        assert_eq!(dangling.len(), 1);
+        assert!(dangling[0].reason.ends_with("is not in the graph"));
+    }
+
+    #[test]
+    fn test_nft_vs_fungible_classification() {
+        let mut graph = AccountGraph::new();
+        let wallet = Pubkey::new_unique();
+        let nft_mint = Pubkey::new_unique();
+        let fungible_mint = Pubkey::new_unique();
+        let nft_account = Pubkey::new_unique();
+        let fungible_account = Pubkey::new_unique();
+
+        graph.add_node(token_node(nft_mint, AccountNodeType::TokenMint {
+            supply: 1,
+            decimals: 0,
+            mint_authority: None,
+            freeze_authority: None,
+        }));
+        graph.add_node(token_node(
+            fungible_mint,
+            AccountNodeType::TokenMint {
+                supply: 1_000_000,
+                decimals: 6,
+                mint_authority: None,
+                freeze_authority: None,
+            },
+        ));
+        graph.add_node(token_node(
+            nft_account,
+            AccountNodeType::TokenAccount { mint: nft_mint, owner: wallet, amount: 1, is_nft: false },
+        ));
+        graph.add_node(token_node(
+            fungible_account,
+            AccountNodeType::TokenAccount {
+                mint: fungible_mint,
+                owner: wallet,
+                amount: 500,
+                is_nft: false,
+            },
+        ));
+
+        graph.mark_nfts();
+
+        assert!(graph.get_node(&nft_mint).unwrap().account_type.as_ref().unwrap().is_nft());
+        assert!(!graph.get_node(&fungible_mint).unwrap().account_type.as_ref().unwrap().is_nft());
+
+        let nfts = utils::nft_accounts(&graph);
+        assert_eq!(nfts.len(), 1);
+        assert_eq!(nfts[0].pubkey, nft_account);
+
+        let summary = utils::portfolio_summary(&graph);
+        assert_eq!(summary.token_accounts, 2);
+        assert_eq!(summary.nft_accounts, 1);
+        assert_eq!(summary.fungible_accounts, 1);
+    }
+
+    #[test]
+    fn test_build_close_instructions() {
+        let mut graph = AccountGraph::new();
+        let (wallet, stranger, mint) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let account = |owner, amount| AccountNodeType::TokenAccount {
+            mint,
+            owner,
+            amount,
+            is_nft: false,
+        };
+
+        let legacy = Pubkey::new_unique();
+        let token_2022 = Pubkey::new_unique();
+        graph.add_node(token_node(legacy, account(wallet, 0)));
+        graph.add_node(AccountNode {
+            owner: spl_token_2022::id(),
+            ..token_node(token_2022, account(wallet, 0))
+        });
+        // Not closeable, or not the wallet's to close
+        graph.add_node(token_node(Pubkey::new_unique(), account(wallet, 5)));
+        graph.add_node(token_node(Pubkey::new_unique(), account(stranger, 0)));
+
+        let instructions = utils::build_close_instructions(&graph, &wallet);
+        let mut expected = vec![(legacy, spl_token::id()), (token_2022, spl_token_2022::id())];
+        expected.sort();
+        let targets: Vec<(Pubkey, Pubkey)> =
+            instructions.iter().map(|ix| (ix.accounts[0].pubkey, ix.program_id)).collect();
+        assert_eq!(targets, expected);
+        for ix in &instructions {
+            // Rent goes back to the wallet, which signs as the owner
+            assert_eq!(ix.accounts[1].pubkey, wallet);
+            assert!(ix.accounts[2].is_signer && ix.accounts[2].pubkey == wallet);
+        }
+    }
+
+    #[test]
+    fn test_contains_and_neighbors() {
+        let mut graph = AccountGraph::new();
+        assert!(graph.is_empty());
+
+        let wallet = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let account = Pubkey::new_unique();
+        let missing = Pubkey::new_unique();
+
+        graph.add_node(token_node(mint, AccountNodeType::TokenMint {
+            supply: 100,
+            decimals: 6,
+            mint_authority: None,
+            freeze_authority: None,
+        }));
+        graph.add_node(token_node(
+            account,
+            AccountNodeType::TokenAccount { mint, owner: wallet, amount: 5, is_nft: false },
+        ));
+        graph.add_edge(AccountEdge { from: account, to: mint, edge_type: EdgeType::TokenAccountOf });
+        graph.add_edge(AccountEdge { from: account, to: missing, edge_type: EdgeType::Related });
+
+        assert!(!graph.is_empty());
+        assert!(graph.contains(&mint));
+        assert!(!graph.contains(&missing));
+        assert_eq!(graph.all_edges().count(), 2);
+
+        let neighbors: Vec<_> = graph.neighbors(&account).map(|n| n.pubkey).collect();
+        assert_eq!(neighbors, vec![mint]);
+        assert_eq!(graph.neighbors(&mint).count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_mint_authority_edge() {
+        use crate::testing::MockRpc;
+        use solana_sdk::program_option::COption;
+        use solana_sdk::program_pack::Pack;
+        use spl_token::state::Mint;
+
+        let mint = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let freeze_authority = Pubkey::new_unique();
+
+        let mut mint_account = MockRpc::mint_account(6, 1_000);
+        Mint::pack(
+            Mint {
+                mint_authority: COption::Some(authority),
+                supply: 1_000,
+                decimals: 6,
+                is_initialized: true,
+                freeze_authority: COption::Some(freeze_authority),
+            },
+            &mut mint_account.data,
+        )
+        .unwrap();
+
+        let rpc = MockRpc::new()
+            .with_account(mint, mint_account)
+            .with_balance(authority, 1_000_000);
+        let builder = AccountGraphBuilder::from_client(rpc);
+
+        let graph = builder.build_from_accounts(&[mint, authority]).await.unwrap();
+
+        assert_eq!(
+            graph.get_node(&mint).unwrap().account_type,
+            Some(AccountNodeType::TokenMint {
+                supply: 1_000,
+                decimals: 6,
+                mint_authority: Some(authority),
+                freeze_authority: Some(freeze_authority),
+            })
+        );
+        // Freeze authority is not in the graph, so only one edge
+        let edges = graph.edges_from(&mint);
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0].to, authority);
+        assert_eq!(edges[0].edge_type, EdgeType::Authority);
+    }
+
+    #[tokio::test]
+    async fn test_program_data_is_linked_to_program() {
+        use crate::testing::MockRpc;
+
+        let program = Pubkey::new_unique();
+        let (state, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut program_account = Account::new(1_000_000, 36, &solana_sdk::bpf_loader::id());
+        program_account.executable = true;
+        let rpc = MockRpc::new()
+            .with_account(state, Account::new(2_000_000, 64, &program))
+            .with_account(other, Account::new(1_500_000, 8, &program))
+            .with_account(program, program_account);
+        let builder = AccountGraphBuilder::from_client(rpc);
+
+        // Without the program node the owner edge is kept as a dangling link
+        let graph = builder.build_from_accounts(&[state]).await.unwrap();
+        assert_eq!(
+            graph.get_node(&state).unwrap().account_type,
+            Some(AccountNodeType::ProgramData { program })
+        );
+        let edges = graph.edges_from(&state);
+        assert_eq!(edges.len(), 1);
+        assert_eq!((edges[0].to, &edges[0].edge_type), (program, &EdgeType::OwnedBy));
+        assert_eq!(graph.dangling_edges().count(), 1);
+        assert_eq!(graph.neighbors(&state).count(), 0);
+
+        let graph = builder.build_from_accounts(&[state, other, program]).await.unwrap();
+        assert_eq!(graph.get_node(&program).unwrap().account_type, Some(AccountNodeType::Program));
+        assert_eq!(graph.dangling_edges().count(), 0);
+        let mut owned: Vec<Pubkey> =
+            graph.accounts_owned_by(&program).iter().map(|n| n.pubkey).collect();
+        owned.sort();
+        let mut expected = vec![state, other];
+        expected.sort();
+        assert_eq!(owned, expected);
+        assert_eq!(graph.find_reaching(&program).len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_build_from_transaction() {
+        use crate::testing::MockRpc;
+        use solana_sdk::{hash::Hash, message::Message, system_instruction, system_program};
+
+        let (payer, recipient) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut program = Account::new(1, 0, &solana_sdk::native_loader::id());
+        program.executable = true;
+        let rpc = MockRpc::new()
+            .with_balance(payer, 1_000_000_000)
+            .with_balance(recipient, 1_000_000)
+            .with_account(system_program::id(), program);
+        let builder = AccountGraphBuilder::from_client(rpc);
+
+        let ix = system_instruction::transfer(&payer, &recipient, 5_000);
+        let mut tx = Transaction::new_unsigned(Message::new(&[ix], Some(&payer)));
+        tx.message.recent_blockhash = Hash::new_unique();
+
+        let graph = builder.build_from_transaction(&tx).await.unwrap();
+
+        assert_eq!(graph.node_count(), 3);
+        for wallet in [payer, recipient] {
+            let node = graph.get_node(&wallet).unwrap();
+            assert_eq!(node.account_type, Some(AccountNodeType::SystemAccount));
+            let edges = graph.edges_from(&wallet);
+            assert_eq!(edges.len(), 1);
+            assert_eq!(edges[0].to, system_program::id());
+            assert_eq!(edges[0].edge_type, EdgeType::OwnedBy);
+        }
+        assert_eq!(
+            graph.get_node(&system_program::id()).unwrap().account_type,
+            Some(AccountNodeType::Program)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_nodes_record_rent_exemption() {
+        use crate::testing::MockRpc;
+
+        let mint = Pubkey::new_unique();
+        let (funded, underfunded) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut short = MockRpc::token_account(&mint, &Pubkey::new_unique(), 0);
+        short.lamports = 1_000_000;
+        let rpc = MockRpc::new()
+            .with_account(funded, MockRpc::token_account(&mint, &Pubkey::new_unique(), 0))
+            .with_account(underfunded, short);
+        let builder = AccountGraphBuilder::from_client(rpc);
+
+        let graph = builder.build_from_accounts(&[funded, underfunded]).await.unwrap();
+
+        let node = graph.get_node(&funded).unwrap();
+        assert_eq!(node.data_len, 165);
+        assert!(node.rent_exempt);
+        assert_eq!(node.recoverable_rent, 2_039_280);
+
+        let node = graph.get_node(&underfunded).unwrap();
+        assert!(!node.rent_exempt);
+        assert_eq!(node.recoverable_rent, 1_000_000);
+
+        let exempt: Vec<Pubkey> =
+            utils::rent_exempt_nodes(&graph).iter().map(|n| n.pubkey).collect();
+        assert_eq!(exempt, vec![funded]);
+    }
+
+    #[tokio::test]
+    async fn test_token_graph_with_sliced_scan() {
+        use crate::testing::MockRpc;
+
+        let wallet = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let token_account = Pubkey::new_unique();
+        let rpc = MockRpc::new()
+            .with_account(mint, MockRpc::mint_account(0, 1))
+            .with_account(token_account, MockRpc::token_account(&mint, &wallet, 1))
+            .with_account(
+                Pubkey::new_unique(),
+                MockRpc::token_account(&mint, &Pubkey::new_unique(), 1),
+            );
+        let builder = AccountGraphBuilder::from_client(rpc)
+            .with_token_account_scan(TokenAccountScan::ProgramAccounts);
+
+        let graph = builder.build_token_account_graph(&wallet).await.unwrap();
+
+        assert_eq!(graph.node_count(), 2);
+        let node = graph.get_node(&token_account).unwrap();
+        assert_eq!(node.data_len, 165);
+        // Judged by the full account size, not the slice
+        assert!(node.rent_exempt);
+        assert_eq!(
+            node.account_type,
+            Some(AccountNodeType::TokenAccount { mint, owner: wallet, amount: 1, is_nft: true })
+        );
+    }
 }
@@ -1,65 +1,226 @@
 //! Account utilities for validation and parsing.
 
-use solana_client::nonblocking::rpc_client::RpcClient;
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
+use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
+use solana_client::{
+    nonblocking::rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::RpcFilterType,
+};
 use solana_sdk::{
     account::Account,
     commitment_config::CommitmentConfig,
+    program_pack::Pack,
     pubkey::Pubkey,
     rent::Rent,
     sysvar,
+    transaction::Transaction,
 };
+use spl_token::state::Account as TokenAccount;
 
+use crate::retry::{retry, RetryConfig};
+use crate::rpc::{Network, SolanaRpc, TimeoutRpc, DEFAULT_RPC_TIMEOUT};
+use crate::transaction::simulation_error;
 use crate::{Result, ToolkitError};
 
 /// Account validation utilities.
-pub struct AccountUtils {
-    client: RpcClient,
+pub struct AccountUtils<R = RpcClient> {
+    client: TimeoutRpc<R>,
+    read_retries: RetryConfig,
+    cache: Option<AccountCache>,
+}
+
+/// Short-lived cache of `get_account` results, including missing accounts.
+struct AccountCache {
+    ttl: Duration,
+    entries: RwLock<HashMap<Pubkey, (Instant, Option<Account>)>>,
+}
+
+impl AccountCache {
+    fn get(&self, pubkey: &Pubkey) -> Option<Option<Account>> {
+        let entries = self.entries.read().unwrap();
+        let (fetched_at, account) = entries.get(pubkey)?;
+        (fetched_at.elapsed() < self.ttl).then(|| account.clone())
+    }
+
+    fn insert(&self, pubkey: Pubkey, account: Option<Account>) {
+        self.entries
+            .write()
+            .unwrap()
+            .insert(pubkey, (Instant::now(), account));
+    }
 }
 
 impl AccountUtils {
     pub fn new(rpc_url: &str) -> Self {
+        Self::from_client(RpcClient::new_with_commitment(
+            rpc_url.to_string(),
+            CommitmentConfig::confirmed(),
+        ))
+    }
+
+    /// Create for a well-known network.
+    pub fn new_on(network: Network) -> Self {
+        Self::new(network.rpc_url())
+    }
+}
+
+impl<R: SolanaRpc> AccountUtils<R> {
+    /// Create from an existing RPC backend.
+    pub fn from_client(client: R) -> Self {
         Self {
-            client: RpcClient::new_with_commitment(
-                rpc_url.to_string(),
-                CommitmentConfig::confirmed(),
-            ),
+            client: TimeoutRpc::new(client, DEFAULT_RPC_TIMEOUT),
+            read_retries: RetryConfig::none(),
+            cache: None,
+        }
+    }
+
+    /// Cache `get_account` results, including missing accounts, for `ttl`.
+    ///
+    /// `exists`, `is_owned_by` and the other single-account reads within
+    /// the TTL are served from memory. Call [`invalidate`](Self::invalidate)
+    /// after changing an account.
+    pub fn with_cache(mut self, ttl: Duration) -> Self {
+        self.cache = Some(AccountCache {
+            ttl,
+            entries: RwLock::new(HashMap::new()),
+        });
+        self
+    }
+
+    /// Drop the cached state of `pubkey` so the next read refetches it.
+    pub fn invalidate(&self, pubkey: &Pubkey) {
+        if let Some(cache) = &self.cache {
+            cache.entries.write().unwrap().remove(pubkey);
         }
     }
 
+    /// Drop every cached account.
+    pub fn clear_cache(&self) {
+        if let Some(cache) = &self.cache {
+            cache.entries.write().unwrap().clear();
+        }
+    }
+
+    /// Retry transient read failures up to `retries` times with backoff.
+    pub fn with_read_retries(mut self, retries: u32) -> Self {
+        self.read_retries = RetryConfig::new(retries);
+        self
+    }
+
+    /// Set the full retry policy used for reads.
+    pub fn with_retry_config(mut self, config: RetryConfig) -> Self {
+        self.read_retries = config;
+        self
+    }
+
+    /// Fail RPC calls that take longer than `timeout` (30s by default)
+    /// with [`ToolkitError::Timeout`]. Timeouts count as retryable.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client.set_timeout(timeout);
+        self
+    }
+
     /// Check if an account exists.
     pub async fn exists(&self, pubkey: &Pubkey) -> bool {
-        self.client.get_account(pubkey).await.is_ok()
+        self.get_account(pubkey).await.is_ok()
     }
 
     /// Get account data or return error if not found.
+    ///
+    /// Only a missing account is [`ToolkitError::AccountNotFound`]; RPC
+    /// failures are returned as their own error (e.g. [`ToolkitError::RpcError`])
+    /// instead of being reported as a missing account.
     pub async fn get_account(&self, pubkey: &Pubkey) -> Result<Account> {
-        self.client
-            .get_account(pubkey)
-            .await
-            .map_err(|_| ToolkitError::AccountNotFound(pubkey.to_string()))
+        let cached = self.cache.as_ref().and_then(|cache| cache.get(pubkey));
+        let account = match cached {
+            Some(account) => account,
+            None => {
+                let account = retry(&self.read_retries, || self.client.get_account(pubkey)).await?;
+                if let Some(cache) = &self.cache {
+                    cache.insert(*pubkey, account.clone());
+                }
+                account
+            }
+        };
+        account.ok_or_else(|| ToolkitError::AccountNotFound(pubkey.to_string()))
+    }
+
+    /// Fetch `length` bytes of account data starting at `offset`.
+    ///
+    /// Uses the RPC `dataSlice` option so only the requested bytes are
+    /// transferred. Fewer bytes are returned if the data ends early.
+    pub async fn get_account_slice(
+        &self,
+        pubkey: &Pubkey,
+        offset: usize,
+        length: usize,
+    ) -> Result<Vec<u8>> {
+        let slice = UiDataSliceConfig { offset, length };
+        retry(&self.read_retries, || self.client.get_account_slice(pubkey, slice))
+            .await?
+            .map(|account| account.data)
+            .ok_or_else(|| ToolkitError::AccountNotFound(pubkey.to_string()))
+    }
+
+    /// Read a fixed-size little-endian field at `offset` in account data.
+    ///
+    /// ```rust,ignore
+    /// // Token amount of an SPL token account
+    /// let amount: u64 = utils.get_field(&token_account, 64).await?;
+    /// ```
+    pub async fn get_field<T: LeField>(&self, pubkey: &Pubkey, offset: usize) -> Result<T> {
+        let bytes = self.get_account_slice(pubkey, offset, T::SIZE).await?;
+        if bytes.len() != T::SIZE {
+            return Err(ToolkitError::InvalidAccountData(format!(
+                "{} has {} bytes at offset {}, need {}",
+                pubkey,
+                bytes.len(),
+                offset,
+                T::SIZE
+            )));
+        }
+        Ok(T::from_le_slice(&bytes))
+    }
+
+    /// Whether `pubkey` no longer matches `previous`, a fingerprint from
+    /// [`account_fingerprint`].
+    ///
+    /// Always reads the cluster, bypassing the cache. A closed account
+    /// counts as changed.
+    pub async fn has_changed(&self, pubkey: &Pubkey, previous: &[u8; 32]) -> Result<bool> {
+        let account = retry(&self.read_retries, || self.client.get_account(pubkey)).await?;
+        Ok(account.map_or(true, |account| account_fingerprint(&account) != *previous))
     }
 
     /// Get account lamports balance.
     pub async fn get_balance(&self, pubkey: &Pubkey) -> Result<u64> {
-        Ok(self.client.get_balance(pubkey).await?)
+        retry(&self.read_retries, || self.client.get_balance(pubkey)).await
     }
 
     /// Check if account is rent exempt.
     pub async fn is_rent_exempt(&self, pubkey: &Pubkey) -> Result<bool> {
         let account = self.get_account(pubkey).await?;
-        let rent = self.client.get_account(&sysvar::rent::id()).await?;
-        let rent: Rent = bincode::deserialize(&rent.data)
-            .map_err(|e| ToolkitError::InvalidAccountData(e.to_string()))?;
+        let rent = self.get_rent().await?;
 
         Ok(rent.is_exempt(account.lamports, account.data.len()))
     }
 
     /// Get minimum balance for rent exemption.
     pub async fn minimum_balance_for_rent_exemption(&self, data_len: usize) -> Result<u64> {
-        Ok(self
-            .client
-            .get_minimum_balance_for_rent_exemption(data_len)
-            .await?)
+        let rent = self.get_rent().await?;
+        Ok(rent.minimum_balance(data_len))
+    }
+
+    /// Fetch the cluster's rent sysvar.
+    async fn get_rent(&self) -> Result<Rent> {
+        let rent = self.get_account(&sysvar::rent::id()).await?;
+        bincode::deserialize(&rent.data)
+            .map_err(|e| ToolkitError::InvalidAccountData(e.to_string()))
     }
 
     /// Check if account is owned by a program.
@@ -73,10 +234,217 @@ impl AccountUtils {
         &self,
         pubkeys: &[Pubkey],
     ) -> Result<Vec<Option<Account>>> {
-        Ok(self.client.get_multiple_accounts(pubkeys).await?)
+        retry(&self.read_retries, || self.client.get_multiple_accounts(pubkeys)).await
+    }
+
+    /// Simulate `tx` and report how it changes each writable account.
+    ///
+    /// Pre-state is read from the cluster, post-state comes from the
+    /// simulation. The transaction does not need to be signed, so this can
+    /// be used to audit third-party transactions before signing them.
+    pub async fn simulate_balance_changes(&self, tx: &Transaction) -> Result<Vec<BalanceChange>> {
+        let writable: Vec<Pubkey> = tx
+            .message
+            .account_keys
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| tx.message.is_writable(*i))
+            .map(|(_, key)| *key)
+            .collect();
+
+        let pre = self.get_multiple_accounts(&writable).await?;
+        let simulation = self
+            .client
+            .simulate_transaction_with_accounts(tx, &writable)
+            .await?;
+        if let Some(err) = simulation.err {
+            return Err(simulation_error(tx, &err));
+        }
+        let post = simulation.accounts.ok_or_else(|| {
+            ToolkitError::TransactionError("Simulation returned no account states".to_string())
+        })?;
+        if post.len() != writable.len() {
+            return Err(ToolkitError::TransactionError(format!(
+                "Simulation returned {} account states for {} writable accounts",
+                post.len(),
+                writable.len()
+            )));
+        }
+
+        writable
+            .into_iter()
+            .zip(pre)
+            .zip(post)
+            .map(|((pubkey, pre), post)| {
+                let post = match post {
+                    Some(ui_account) => Some(ui_account.decode::<Account>().ok_or_else(|| {
+                        ToolkitError::InvalidAccountData(format!(
+                            "Could not decode simulated state of {}",
+                            pubkey
+                        ))
+                    })?),
+                    None => None,
+                };
+                Ok(BalanceChange {
+                    pubkey,
+                    pre_lamports: pre.as_ref().map_or(0, |a| a.lamports),
+                    post_lamports: post.as_ref().map_or(0, |a| a.lamports),
+                    pre_token_amount: pre.as_ref().and_then(token_amount),
+                    post_token_amount: post.as_ref().and_then(token_amount),
+                })
+            })
+            .collect()
+    }
+}
+
+/// A fixed-size field stored little-endian in account data.
+pub trait LeField: Sized {
+    /// Encoded size in bytes.
+    const SIZE: usize;
+
+    /// Decode from exactly `SIZE` bytes.
+    fn from_le_slice(bytes: &[u8]) -> Self;
+}
+
+macro_rules! impl_le_field {
+    ($($ty:ty),+) => {
+        $(
+            impl LeField for $ty {
+                const SIZE: usize = std::mem::size_of::<$ty>();
+
+                fn from_le_slice(bytes: &[u8]) -> Self {
+                    <$ty>::from_le_bytes(bytes.try_into().expect("field length checked"))
+                }
+            }
+        )+
+    };
+}
+
+impl_le_field!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl LeField for bool {
+    const SIZE: usize = 1;
+
+    fn from_le_slice(bytes: &[u8]) -> Self {
+        bytes[0] != 0
+    }
+}
+
+impl LeField for Pubkey {
+    const SIZE: usize = 32;
+
+    fn from_le_slice(bytes: &[u8]) -> Self {
+        Pubkey::new_from_array(bytes.try_into().expect("field length checked"))
     }
 }
 
+/// Amount held by an SPL token account, if `account` is one.
+fn token_amount(account: &Account) -> Option<u64> {
+    if account.owner != spl_token::id() || account.data.len() != TokenAccount::LEN {
+        return None;
+    }
+    TokenAccount::unpack(&account.data).ok().map(|t| t.amount)
+}
+
+/// Balance change of one writable account, from [`AccountUtils::simulate_balance_changes`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalanceChange {
+    pub pubkey: Pubkey,
+    /// Lamports before the transaction (0 if the account does not exist).
+    pub pre_lamports: u64,
+    /// Lamports after the transaction (0 if the account is closed).
+    pub post_lamports: u64,
+    /// Token amount before, if the account is an SPL token account.
+    pub pre_token_amount: Option<u64>,
+    /// Token amount after, if the account is an SPL token account.
+    pub post_token_amount: Option<u64>,
+}
+
+impl BalanceChange {
+    /// Net lamport change (negative when the account pays out).
+    pub fn lamports_delta(&self) -> i128 {
+        self.post_lamports as i128 - self.pre_lamports as i128
+    }
+
+    /// Net token change, if the account is a token account before or after.
+    pub fn token_delta(&self) -> Option<i128> {
+        if self.pre_token_amount.is_none() && self.post_token_amount.is_none() {
+            return None;
+        }
+        Some(
+            self.post_token_amount.unwrap_or(0) as i128
+                - self.pre_token_amount.unwrap_or(0) as i128,
+        )
+    }
+}
+
+/// SHA-256 of an account's lamports, owner and data.
+///
+/// Store it instead of the account to later check, e.g. with
+/// [`AccountUtils::has_changed`], whether anything relevant changed.
+pub fn account_fingerprint(account: &Account) -> [u8; 32] {
+    solana_sdk::hash::hashv(&[
+        &account.lamports.to_le_bytes(),
+        account.owner.as_ref(),
+        &account.data,
+    ])
+    .to_bytes()
+}
+
+/// Stream the accounts of `program_id` matching `filters`, `chunk` at a
+/// time, in pubkey order.
+///
+/// Matching pubkeys are listed first with a zero-length data slice, then
+/// full accounts are fetched `chunk` per `getMultipleAccounts` call as the
+/// stream is polled, so only one page of account data is held at a time.
+/// Accounts closed between the two steps are skipped; filters are not
+/// re-checked against the fetched data.
+pub fn program_accounts_stream<'a, R: SolanaRpc + ?Sized>(
+    rpc: &'a R,
+    program_id: Pubkey,
+    filters: Vec<RpcFilterType>,
+    chunk: usize,
+) -> impl Stream<Item = Result<(Pubkey, Account)>> + 'a {
+    let config = RpcProgramAccountsConfig {
+        filters: Some(filters),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            data_slice: Some(UiDataSliceConfig { offset: 0, length: 0 }),
+            commitment: None,
+            min_context_slot: None,
+        },
+        with_context: None,
+    };
+    let list_keys = async move {
+        let mut keys: Vec<Pubkey> = rpc
+            .get_program_accounts(&program_id, config)
+            .await?
+            .into_iter()
+            .map(|(pubkey, _)| pubkey)
+            .collect();
+        keys.sort();
+        Ok::<_, ToolkitError>(keys)
+    };
+
+    stream::once(list_keys)
+        .map_ok(move |keys| {
+            let pages: Vec<Vec<Pubkey>> =
+                keys.chunks(chunk.max(1)).map(<[Pubkey]>::to_vec).collect();
+            stream::iter(pages)
+                .then(move |page| async move {
+                    let accounts = rpc.get_multiple_accounts(&page).await?;
+                    let found: Vec<Result<(Pubkey, Account)>> = page
+                        .into_iter()
+                        .zip(accounts)
+                        .filter_map(|(pubkey, account)| Some(Ok((pubkey, account?))))
+                        .collect();
+                    Ok::<_, ToolkitError>(stream::iter(found))
+                })
+                .try_flatten()
+        })
+        .try_flatten()
+}
+
 /// Account data parser helpers.
 pub mod parser {
     use super::*;
@@ -154,6 +522,44 @@ impl From<(Pubkey, Account)> for AccountInfo {
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn test_program_accounts_stream_pages_in_order() {
+        use crate::testing::MockRpc;
+
+        let program = Pubkey::new_unique();
+        let rpc = MockRpc::new();
+        let mut expected = Vec::new();
+        for i in 0..7u8 {
+            let pubkey = Pubkey::new_unique();
+            let mut account = Account::new(1_000 + i as u64, 16, &program);
+            account.data[0] = i;
+            rpc.set_account(pubkey, account.clone());
+            expected.push((pubkey, account));
+        }
+        expected.sort_by_key(|(pubkey, _)| *pubkey);
+        // Excluded by the size filter and by owner
+        rpc.set_account(Pubkey::new_unique(), Account::new(1, 8, &program));
+        rpc.set_account(Pubkey::new_unique(), Account::new(1, 16, &Pubkey::new_unique()));
+
+        let filters = vec![RpcFilterType::DataSize(16)];
+        let streamed: Vec<(Pubkey, Account)> = program_accounts_stream(&rpc, program, filters, 3)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(streamed, expected);
+
+        // Keys are listed without data, then fetched a page at a time
+        let listing = &rpc.program_account_requests()[0];
+        assert_eq!(listing.account_config.data_slice.unwrap().length, 0);
+        let pages: Vec<usize> = rpc.account_requests().iter().map(Vec::len).collect();
+        assert_eq!(pages, vec![3, 3, 1]);
+
+        // Pages are fetched lazily
+        let mut stream = Box::pin(program_accounts_stream(&rpc, program, vec![], 4));
+        stream.next().await.unwrap().unwrap();
+        assert_eq!(rpc.account_requests().len(), 4);
+    }
+
     #[test]
     fn test_account_info_from() {
         let pubkey = Pubkey::new_unique();
@@ -170,4 +576,266 @@ mod tests {
         assert_eq!(info.lamports, 1000);
         assert_eq!(info.data_len, 100);
     }
+
+    #[tokio::test]
+    async fn test_account_fingerprint_detects_changes() {
+        use crate::testing::MockRpc;
+
+        let owner = Pubkey::new_unique();
+        let account = Account::new(1_000, 8, &owner);
+        let fingerprint = account_fingerprint(&account);
+        assert_eq!(fingerprint, account_fingerprint(&account.clone()));
+
+        let mut edited = account.clone();
+        edited.data[7] = 1;
+        assert_ne!(account_fingerprint(&edited), fingerprint);
+        let mut funded = account.clone();
+        funded.lamports += 1;
+        assert_ne!(account_fingerprint(&funded), fingerprint);
+        let reassigned = Account::new(1_000, 8, &Pubkey::new_unique());
+        assert_ne!(account_fingerprint(&reassigned), fingerprint);
+
+        let pubkey = Pubkey::new_unique();
+        let utils = AccountUtils::from_client(MockRpc::new().with_account(pubkey, account))
+            .with_cache(Duration::from_secs(60));
+        utils.get_account(&pubkey).await.unwrap();
+        assert!(!utils.has_changed(&pubkey, &fingerprint).await.unwrap());
+
+        // Changes are seen even though the cache still holds the old account
+        utils.client.set_account(pubkey, edited);
+        assert_eq!(account_fingerprint(&utils.get_account(&pubkey).await.unwrap()), fingerprint);
+        assert!(utils.has_changed(&pubkey, &fingerprint).await.unwrap());
+        utils.client.remove_account(&pubkey);
+        assert!(utils.has_changed(&pubkey, &fingerprint).await.unwrap());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_reads_retry_transient_failures() {
+        use crate::testing::MockRpc;
+
+        let pubkey = Pubkey::new_unique();
+        let utils =
+            AccountUtils::from_client(MockRpc::new().with_balance(pubkey, 42)).with_read_retries(2);
+
+        utils.client.fail_next_calls(2);
+        assert_eq!(utils.get_balance(&pubkey).await.unwrap(), 42);
+        assert_eq!(utils.client.call_count(), 3);
+
+        utils.client.fail_next_calls(2);
+        assert_eq!(utils.get_account(&pubkey).await.unwrap().lamports, 42);
+        assert_eq!(utils.client.call_count(), 6);
+
+        // A third failure in a row exhausts the retries
+        utils.client.fail_next_calls(3);
+        let err = utils.get_balance(&pubkey).await.unwrap_err();
+        assert!(matches!(err, ToolkitError::NetworkError(_)), "{}", err);
+        assert_eq!(utils.client.call_count(), 9);
+    }
+
+    #[tokio::test]
+    async fn test_account_cache() {
+        use crate::testing::MockRpc;
+
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<AccountUtils<MockRpc>>();
+
+        let (account, missing) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let rpc = MockRpc::new().with_balance(account, 1_000);
+        let utils = AccountUtils::from_client(rpc).with_cache(Duration::from_secs(60));
+
+        assert!(utils.exists(&account).await);
+        assert!(!utils.exists(&missing).await);
+
+        // Within the TTL the cached state is served without asking the RPC
+        utils.client.remove_account(&account);
+        utils.client.set_account(missing, Account::new(1, 0, &Pubkey::default()));
+        assert!(utils.exists(&account).await);
+        assert!(utils.is_owned_by(&account, &solana_sdk::system_program::id()).await.unwrap());
+        assert!(!utils.exists(&missing).await);
+
+        utils.invalidate(&account);
+        assert!(!utils.exists(&account).await);
+        assert!(!utils.exists(&missing).await);
+        utils.clear_cache();
+        assert!(utils.exists(&missing).await);
+
+        // Entries expire after the TTL
+        let utils = AccountUtils::from_client(utils.client).with_cache(Duration::ZERO);
+        assert!(utils.exists(&missing).await);
+        utils.client.remove_account(&missing);
+        assert!(!utils.exists(&missing).await);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_shared_cache_across_tasks() {
+        use crate::testing::MockRpc;
+        use std::sync::Arc;
+
+        let accounts: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let rpc = accounts
+            .iter()
+            .fold(MockRpc::new(), |rpc, pubkey| rpc.with_balance(*pubkey, 1_000));
+        let utils = Arc::new(AccountUtils::from_client(rpc).with_cache(Duration::from_secs(60)));
+        for pubkey in &accounts {
+            assert!(utils.exists(pubkey).await);
+        }
+        let warm_reads = utils.client.account_requests().len();
+
+        let tasks: Vec<_> = (0..32)
+            .map(|i| {
+                let (utils, pubkey) = (Arc::clone(&utils), accounts[i % accounts.len()]);
+                tokio::spawn(async move {
+                    // Every eighth task lands on accounts[0]
+                    if i % 8 == 0 {
+                        utils.invalidate(&pubkey);
+                    }
+                    utils.get_account(&pubkey).await.unwrap().lamports
+                })
+            })
+            .collect();
+        for task in tasks {
+            assert_eq!(task.await.unwrap(), 1_000);
+        }
+
+        // Only the invalidated account was refetched
+        let requests = utils.client.account_requests();
+        let refetched: Vec<&Pubkey> = requests[warm_reads..].iter().flatten().collect();
+        assert!(!refetched.is_empty());
+        assert!(refetched.iter().all(|pubkey| **pubkey == accounts[0]), "{:?}", refetched);
+    }
+
+    #[tokio::test]
+    async fn test_get_field_reads_slice() {
+        use crate::testing::MockRpc;
+
+        let owner = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let token_account = Pubkey::new_unique();
+        let rpc = MockRpc::new()
+            .with_account(token_account, MockRpc::token_account(&mint, &owner, 1_234_567));
+        let utils = AccountUtils::from_client(rpc);
+
+        let amount: u64 = utils.get_field(&token_account, 64).await.unwrap();
+        assert_eq!(amount, 1_234_567);
+        let field_owner: Pubkey = utils.get_field(&token_account, 32).await.unwrap();
+        assert_eq!(field_owner, owner);
+        let bytes = utils.get_account_slice(&token_account, 0, 32).await.unwrap();
+        assert_eq!(bytes, mint.to_bytes());
+
+        let requests = utils.client.slice_requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].0, token_account);
+        assert_eq!((requests[0].1.offset, requests[0].1.length), (64, 8));
+        assert_eq!((requests[1].1.offset, requests[1].1.length), (32, 32));
+        assert_eq!((requests[2].1.offset, requests[2].1.length), (0, 32));
+    }
+
+    #[tokio::test]
+    async fn test_get_field_past_end_of_data() {
+        use crate::testing::MockRpc;
+
+        let token_account = Pubkey::new_unique();
+        let rpc = MockRpc::new().with_account(
+            token_account,
+            MockRpc::token_account(&Pubkey::new_unique(), &Pubkey::new_unique(), 0),
+        );
+        let utils = AccountUtils::from_client(rpc);
+
+        let err = utils.get_field::<u64>(&token_account, 160).await.unwrap_err();
+        assert!(matches!(err, ToolkitError::InvalidAccountData(_)));
+        let missing = utils.get_field::<u64>(&Pubkey::new_unique(), 0).await.unwrap_err();
+        assert!(matches!(missing, ToolkitError::AccountNotFound(_)));
+    }
+
+    #[tokio::test]
+    async fn test_simulate_balance_changes() {
+        use crate::testing::{MockRpc, TOKEN_ACCOUNT_RENT};
+        use solana_sdk::{message::Message, native_token::LAMPORTS_PER_SOL, system_instruction};
+
+        let payer = Pubkey::new_unique();
+        let recipient = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let source = Pubkey::new_unique();
+        let destination = Pubkey::new_unique();
+
+        let rpc = MockRpc::new()
+            .with_balance(payer, 5 * LAMPORTS_PER_SOL)
+            .with_account(source, MockRpc::token_account(&mint, &payer, 100))
+            .with_account(destination, MockRpc::token_account(&mint, &recipient, 0));
+        rpc.set_simulated_account(
+            payer,
+            Some(Account::new(4 * LAMPORTS_PER_SOL - 5_000, 0, &solana_sdk::system_program::id())),
+        );
+        rpc.set_simulated_account(
+            recipient,
+            Some(Account::new(LAMPORTS_PER_SOL, 0, &solana_sdk::system_program::id())),
+        );
+        rpc.set_simulated_account(source, Some(MockRpc::token_account(&mint, &payer, 40)));
+        rpc.set_simulated_account(
+            destination,
+            Some(MockRpc::token_account(&mint, &recipient, 60)),
+        );
+
+        let instructions = [
+            system_instruction::transfer(&payer, &recipient, LAMPORTS_PER_SOL),
+            spl_token::instruction::transfer(
+                &spl_token::id(),
+                &source,
+                &destination,
+                &payer,
+                &[],
+                60,
+            )
+            .unwrap(),
+        ];
+        let tx = Transaction::new_unsigned(Message::new(&instructions, Some(&payer)));
+
+        let utils = AccountUtils::from_client(rpc);
+        let changes = utils.simulate_balance_changes(&tx).await.unwrap();
+
+        // Program ids are read-only and not reported
+        assert_eq!(changes.len(), 4);
+        let change = |pubkey: Pubkey| changes.iter().find(|c| c.pubkey == pubkey).unwrap();
+
+        assert_eq!(change(payer).lamports_delta(), -(LAMPORTS_PER_SOL as i128) - 5_000);
+        assert_eq!(change(payer).token_delta(), None);
+        assert_eq!(change(recipient).pre_lamports, 0);
+        assert_eq!(change(recipient).lamports_delta(), LAMPORTS_PER_SOL as i128);
+        assert_eq!(change(source).token_delta(), Some(-60));
+        assert_eq!(change(source).lamports_delta(), 0);
+        assert_eq!(change(destination).token_delta(), Some(60));
+        assert_eq!(change(destination).post_lamports, TOKEN_ACCOUNT_RENT);
+    }
+
+    #[tokio::test]
+    async fn test_simulate_balance_changes_reports_closed_accounts() {
+        use crate::testing::{MockRpc, TOKEN_ACCOUNT_RENT};
+        use solana_sdk::message::Message;
+
+        let owner = Pubkey::new_unique();
+        let token_account = Pubkey::new_unique();
+        let rpc = MockRpc::new()
+            .with_balance(owner, 1_000_000)
+            .with_account(token_account, MockRpc::token_account(&Pubkey::new_unique(), &owner, 0));
+        rpc.set_simulated_account(token_account, None);
+
+        let close = spl_token::instruction::close_account(
+            &spl_token::id(),
+            &token_account,
+            &owner,
+            &owner,
+            &[],
+        )
+        .unwrap();
+        let tx = Transaction::new_unsigned(Message::new(&[close], Some(&owner)));
+
+        let changes = AccountUtils::from_client(rpc)
+            .simulate_balance_changes(&tx)
+            .await
+            .unwrap();
+        let closed = changes.iter().find(|c| c.pubkey == token_account).unwrap();
+        assert_eq!(closed.lamports_delta(), -(TOKEN_ACCOUNT_RENT as i128));
+        assert_eq!(closed.token_delta(), Some(0));
+        assert_eq!(closed.post_token_amount, None);
+    }
 }
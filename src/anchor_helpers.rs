@@ -100,6 +100,7 @@ pub struct CpiInstructionBuilder {
     program_id: Option<Pubkey>,
     accounts: Vec<AccountMeta>,
     data: Vec<u8>,
+    expected_instruction: Option<String>,
 }
 
 impl CpiInstructionBuilder {
@@ -165,6 +166,57 @@ impl CpiInstructionBuilder {
         self
     }
 
+    /// Require the data to start with the Anchor discriminator of
+    /// `instruction_name`, checked by [`validate`](Self::validate).
+    pub fn expect_anchor_instruction(mut self, instruction_name: &str) -> Self {
+        self.expected_instruction = Some(instruction_name.to_string());
+        self
+    }
+
+    /// Check the builder describes a plausible instruction.
+    ///
+    /// Fails if the program ID is not set, there are no accounts or no
+    /// signer, or the data doesn't start with the discriminator set by
+    /// [`expect_anchor_instruction`](Self::expect_anchor_instruction).
+    pub fn validate(&self) -> Result<()> {
+        if self.program_id.is_none() {
+            return Err(ToolkitError::Custom("Program ID not set".to_string()));
+        }
+        if self.accounts.is_empty() {
+            return Err(ToolkitError::Custom("Instruction has no accounts".to_string()));
+        }
+        if !self.accounts.iter().any(|meta| meta.is_signer) {
+            return Err(ToolkitError::Custom(format!(
+                "None of the instruction's {} accounts is a signer",
+                self.accounts.len()
+            )));
+        }
+        if let Some(name) = &self.expected_instruction {
+            if !self.data.starts_with(&instruction_discriminator(name)) {
+                return Err(ToolkitError::Custom(format!(
+                    "Instruction data does not start with the discriminator of '{}'; \
+                     set it with anchor_data",
+                    name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Build the instruction after [`validate`](Self::validate), also
+    /// requiring at least `expected_min_accounts` accounts.
+    pub fn build_validated(self, expected_min_accounts: usize) -> Result<Instruction> {
+        if self.accounts.len() < expected_min_accounts {
+            return Err(ToolkitError::Custom(format!(
+                "Instruction has {} accounts, expected at least {}",
+                self.accounts.len(),
+                expected_min_accounts
+            )));
+        }
+        self.validate()?;
+        self.build()
+    }
+
     /// Build the instruction.
     pub fn build(self) -> Result<Instruction> {
         let program_id = self.program_id.ok_or_else(|| {
@@ -216,9 +268,213 @@ pub mod system_cpi {
 }
 
 /// Helpers for SPL Token Program CPIs.
+///
+/// The free functions target the legacy SPL Token program. Use [`TokenCpi`]
+/// to build the same instructions for Token-2022 or another program id.
+///
+/// [`TokenCpi`]: token_cpi::TokenCpi
 pub mod token_cpi {
     use super::*;
-    use spl_token::instruction as token_instruction;
+    use spl_token::instruction::AuthorityType;
+    use spl_token_2022::instruction as token_instruction;
+
+    fn token_error(e: impl ToString) -> ToolkitError {
+        ToolkitError::TokenError(e.to_string())
+    }
+
+    /// Token instruction builder for a specific token program.
+    ///
+    /// Token-2022 encodes these instructions the same way as the legacy
+    /// program, so both are built with the Token-2022 instruction module.
+    /// Any other program id fails with [`ToolkitError::TokenError`].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct TokenCpi {
+        program_id: Pubkey,
+    }
+
+    impl Default for TokenCpi {
+        fn default() -> Self {
+            Self::legacy()
+        }
+    }
+
+    impl TokenCpi {
+        /// Build instructions for `program_id`.
+        pub fn new(program_id: Pubkey) -> Self {
+            Self { program_id }
+        }
+
+        /// Build instructions for the legacy SPL Token program.
+        pub fn legacy() -> Self {
+            Self::new(spl_token::id())
+        }
+
+        /// Build instructions for the Token-2022 program.
+        pub fn token_2022() -> Self {
+            Self::new(spl_token_2022::id())
+        }
+
+        /// Token program the instructions invoke.
+        pub fn program_id(&self) -> Pubkey {
+            self.program_id
+        }
+
+        /// Create a transfer instruction.
+        ///
+        /// Token-2022 mints with a transfer fee or hook require
+        /// [`transfer_checked`](Self::transfer_checked) instead.
+        pub fn transfer(
+            &self,
+            source: &Pubkey,
+            destination: &Pubkey,
+            authority: &Pubkey,
+            amount: u64,
+        ) -> Result<Instruction> {
+            #[allow(deprecated)]
+            token_instruction::transfer(
+                &self.program_id,
+                source,
+                destination,
+                authority,
+                &[],
+                amount,
+            )
+            .map_err(token_error)
+        }
+
+        /// Create a transfer_checked instruction.
+        pub fn transfer_checked(
+            &self,
+            source: &Pubkey,
+            mint: &Pubkey,
+            destination: &Pubkey,
+            authority: &Pubkey,
+            amount: u64,
+            decimals: u8,
+        ) -> Result<Instruction> {
+            token_instruction::transfer_checked(
+                &self.program_id,
+                source,
+                mint,
+                destination,
+                authority,
+                &[],
+                amount,
+                decimals,
+            )
+            .map_err(token_error)
+        }
+
+        /// Create a mint_to instruction.
+        pub fn mint_to(
+            &self,
+            mint: &Pubkey,
+            destination: &Pubkey,
+            authority: &Pubkey,
+            amount: u64,
+        ) -> Result<Instruction> {
+            token_instruction::mint_to(&self.program_id, mint, destination, authority, &[], amount)
+                .map_err(token_error)
+        }
+
+        /// Create a burn instruction.
+        pub fn burn(
+            &self,
+            account: &Pubkey,
+            mint: &Pubkey,
+            authority: &Pubkey,
+            amount: u64,
+        ) -> Result<Instruction> {
+            token_instruction::burn(&self.program_id, account, mint, authority, &[], amount)
+                .map_err(token_error)
+        }
+
+        /// Create a close_account instruction.
+        pub fn close_account(
+            &self,
+            account: &Pubkey,
+            destination: &Pubkey,
+            authority: &Pubkey,
+        ) -> Result<Instruction> {
+            token_instruction::close_account(&self.program_id, account, destination, authority, &[])
+                .map_err(token_error)
+        }
+
+        /// Create an approve instruction.
+        pub fn approve(
+            &self,
+            source: &Pubkey,
+            delegate: &Pubkey,
+            authority: &Pubkey,
+            amount: u64,
+        ) -> Result<Instruction> {
+            token_instruction::approve(&self.program_id, source, delegate, authority, &[], amount)
+                .map_err(token_error)
+        }
+
+        /// Create a revoke instruction.
+        pub fn revoke(&self, source: &Pubkey, authority: &Pubkey) -> Result<Instruction> {
+            token_instruction::revoke(&self.program_id, source, authority, &[]).map_err(token_error)
+        }
+
+        /// Create an initialize_account instruction.
+        pub fn initialize_account(
+            &self,
+            account: &Pubkey,
+            mint: &Pubkey,
+            owner: &Pubkey,
+        ) -> Result<Instruction> {
+            token_instruction::initialize_account(&self.program_id, account, mint, owner)
+                .map_err(token_error)
+        }
+
+        /// Create a set_authority instruction.
+        pub fn set_authority(
+            &self,
+            account: &Pubkey,
+            current_authority: &Pubkey,
+            authority_type: AuthorityType,
+            new_authority: Option<&Pubkey>,
+        ) -> Result<Instruction> {
+            let authority_type = match authority_type {
+                AuthorityType::MintTokens => token_instruction::AuthorityType::MintTokens,
+                AuthorityType::FreezeAccount => token_instruction::AuthorityType::FreezeAccount,
+                AuthorityType::AccountOwner => token_instruction::AuthorityType::AccountOwner,
+                AuthorityType::CloseAccount => token_instruction::AuthorityType::CloseAccount,
+            };
+            token_instruction::set_authority(
+                &self.program_id,
+                account,
+                new_authority,
+                authority_type,
+                current_authority,
+                &[],
+            )
+            .map_err(token_error)
+        }
+
+        /// Create a freeze_account instruction.
+        pub fn freeze_account(
+            &self,
+            account: &Pubkey,
+            mint: &Pubkey,
+            authority: &Pubkey,
+        ) -> Result<Instruction> {
+            token_instruction::freeze_account(&self.program_id, account, mint, authority, &[])
+                .map_err(token_error)
+        }
+
+        /// Create a thaw_account instruction.
+        pub fn thaw_account(
+            &self,
+            account: &Pubkey,
+            mint: &Pubkey,
+            authority: &Pubkey,
+        ) -> Result<Instruction> {
+            token_instruction::thaw_account(&self.program_id, account, mint, authority, &[])
+                .map_err(token_error)
+        }
+    }
 
     /// Create a transfer instruction.
     pub fn transfer(
@@ -227,15 +483,7 @@ pub mod token_cpi {
         authority: &Pubkey,
         amount: u64,
     ) -> Result<Instruction> {
-        token_instruction::transfer(
-            &spl_token::id(),
-            source,
-            destination,
-            authority,
-            &[],
-            amount,
-        )
-        .map_err(|e| ToolkitError::TokenError(e.to_string()))
+        TokenCpi::legacy().transfer(source, destination, authority, amount)
     }
 
     /// Create a mint_to instruction.
@@ -245,15 +493,7 @@ pub mod token_cpi {
         authority: &Pubkey,
         amount: u64,
     ) -> Result<Instruction> {
-        token_instruction::mint_to(
-            &spl_token::id(),
-            mint,
-            destination,
-            authority,
-            &[],
-            amount,
-        )
-        .map_err(|e| ToolkitError::TokenError(e.to_string()))
+        TokenCpi::legacy().mint_to(mint, destination, authority, amount)
     }
 
     /// Create a burn instruction.
@@ -263,15 +503,7 @@ pub mod token_cpi {
         authority: &Pubkey,
         amount: u64,
     ) -> Result<Instruction> {
-        token_instruction::burn(
-            &spl_token::id(),
-            account,
-            mint,
-            authority,
-            &[],
-            amount,
-        )
-        .map_err(|e| ToolkitError::TokenError(e.to_string()))
+        TokenCpi::legacy().burn(account, mint, authority, amount)
     }
 
     /// Create a close_account instruction.
@@ -280,14 +512,7 @@ pub mod token_cpi {
         destination: &Pubkey,
         authority: &Pubkey,
     ) -> Result<Instruction> {
-        token_instruction::close_account(
-            &spl_token::id(),
-            account,
-            destination,
-            authority,
-            &[],
-        )
-        .map_err(|e| ToolkitError::TokenError(e.to_string()))
+        TokenCpi::legacy().close_account(account, destination, authority)
     }
 
     /// Create an approve instruction.
@@ -297,21 +522,12 @@ pub mod token_cpi {
         authority: &Pubkey,
         amount: u64,
     ) -> Result<Instruction> {
-        token_instruction::approve(
-            &spl_token::id(),
-            source,
-            delegate,
-            authority,
-            &[],
-            amount,
-        )
-        .map_err(|e| ToolkitError::TokenError(e.to_string()))
+        TokenCpi::legacy().approve(source, delegate, authority, amount)
     }
 
     /// Create a revoke instruction.
     pub fn revoke(source: &Pubkey, authority: &Pubkey) -> Result<Instruction> {
-        token_instruction::revoke(&spl_token::id(), source, authority, &[])
-            .map_err(|e| ToolkitError::TokenError(e.to_string()))
+        TokenCpi::legacy().revoke(source, authority)
     }
 
     /// Create an initialize_account instruction.
@@ -320,13 +536,7 @@ pub mod token_cpi {
         mint: &Pubkey,
         owner: &Pubkey,
     ) -> Result<Instruction> {
-        token_instruction::initialize_account(
-            &spl_token::id(),
-            account,
-            mint,
-            owner,
-        )
-        .map_err(|e| ToolkitError::TokenError(e.to_string()))
+        TokenCpi::legacy().initialize_account(account, mint, owner)
     }
 
     /// Create a set_authority instruction.
@@ -336,15 +546,7 @@ pub mod token_cpi {
         authority_type: spl_token::instruction::AuthorityType,
         new_authority: Option<&Pubkey>,
     ) -> Result<Instruction> {
-        token_instruction::set_authority(
-            &spl_token::id(),
-            account,
-            new_authority,
-            authority_type,
-            current_authority,
-            &[],
-        )
-        .map_err(|e| ToolkitError::TokenError(e.to_string()))
+        TokenCpi::legacy().set_authority(account, current_authority, authority_type, new_authority)
     }
 
     /// Create a freeze_account instruction.
@@ -353,14 +555,7 @@ pub mod token_cpi {
         mint: &Pubkey,
         authority: &Pubkey,
     ) -> Result<Instruction> {
-        token_instruction::freeze_account(
-            &spl_token::id(),
-            account,
-            mint,
-            authority,
-            &[],
-        )
-        .map_err(|e| ToolkitError::TokenError(e.to_string()))
+        TokenCpi::legacy().freeze_account(account, mint, authority)
     }
 
     /// Create a thaw_account instruction.
@@ -369,14 +564,7 @@ pub mod token_cpi {
         mint: &Pubkey,
         authority: &Pubkey,
     ) -> Result<Instruction> {
-        token_instruction::thaw_account(
-            &spl_token::id(),
-            account,
-            mint,
-            authority,
-            &[],
-        )
-        .map_err(|e| ToolkitError::TokenError(e.to_string()))
+        TokenCpi::legacy().thaw_account(account, mint, authority)
     }
 }
 
@@ -426,7 +614,7 @@ pub mod ata_cpi {
 /// Validation utilities for Anchor accounts.
 pub mod validation {
     use super::*;
-    use solana_sdk::account::Account;
+    use solana_sdk::{account::Account, rent::Rent};
 
     /// Validate that an account has the expected discriminator.
     pub fn validate_discriminator(
@@ -476,8 +664,26 @@ pub mod validation {
         Ok(())
     }
 
+    /// Rent parameters of mainnet-beta, devnet and a default test validator.
+    pub fn default_rent() -> Rent {
+        Rent::default()
+    }
+
+    /// Rent parameters for a cluster started with custom rent (e.g. a
+    /// localnet genesis), keeping the default burn percentage.
+    pub fn custom_rent(lamports_per_byte_year: u64, exemption_threshold: f64) -> Rent {
+        Rent {
+            lamports_per_byte_year,
+            exemption_threshold,
+            ..Rent::default()
+        }
+    }
+
     /// Validate that an account is rent exempt.
-    pub fn validate_rent_exempt(account: &Account, rent: &solana_sdk::rent::Rent) -> Result<()> {
+    ///
+    /// Use [`default_rent`] or [`custom_rent`] to check without fetching the
+    /// rent sysvar.
+    pub fn validate_rent_exempt(account: &Account, rent: &Rent) -> Result<()> {
         if !rent.is_exempt(account.lamports, account.data.len()) {
             return Err(ToolkitError::InsufficientBalance {
                 needed: rent.minimum_balance(account.data.len()),
@@ -487,6 +693,11 @@ pub mod validation {
         Ok(())
     }
 
+    /// Validate that an account is rent exempt under [`default_rent`].
+    pub fn validate_rent_exempt_default(account: &Account) -> Result<()> {
+        validate_rent_exempt(account, &default_rent())
+    }
+
     /// Validate that an account is writable (not a program).
     pub fn validate_writable(account: &Account) -> Result<()> {
         if account.executable {
@@ -553,6 +764,185 @@ pub mod serialization {
     }
 }
 
+// ============================================================================
+// Instruction Data Sizing
+// ============================================================================
+
+/// Serialized size of an Anchor instruction's data: the 8-byte
+/// discriminator plus the Borsh encoding of `args`.
+pub fn serialized_size<T: borsh::BorshSerialize>(args: &T) -> Result<usize> {
+    let args_len = borsh::object_length(args)
+        .map_err(|e| ToolkitError::Custom(format!("Serialization error: {}", e)))?;
+    Ok(sizes::DISCRIMINATOR + args_len)
+}
+
+/// Serialized size of the data of instruction `name` described by an Anchor
+/// IDL, for the argument values in `args_json` (an object keyed by argument
+/// name).
+///
+/// Both the legacy (`publicKey`, `{"defined": "Name"}`) and the 0.30
+/// (`pubkey`, `{"defined": {"name": "Name"}}`) IDL formats are understood.
+/// Struct values are objects keyed by field name; enum values are the
+/// variant name, or an object mapping the variant name to its fields.
+///
+/// Fails with [`ToolkitError::ParseError`] if the instruction, an argument
+/// or a referenced type is missing, or a value doesn't match its type.
+pub fn instruction_data_size(
+    idl: &serde_json::Value,
+    name: &str,
+    args_json: &serde_json::Value,
+) -> Result<usize> {
+    let instruction = idl
+        .get("instructions")
+        .and_then(|ixs| ixs.as_array())
+        .and_then(|ixs| ixs.iter().find(|ix| ix.get("name").and_then(|n| n.as_str()) == Some(name)))
+        .ok_or_else(|| ToolkitError::ParseError(format!("IDL has no instruction '{}'", name)))?;
+    let types = idl.get("types").and_then(|t| t.as_array()).map(Vec::as_slice).unwrap_or(&[]);
+    let sizer = IdlSizer { types };
+
+    let mut size = sizes::DISCRIMINATOR;
+    for arg in instruction.get("args").and_then(|a| a.as_array()).into_iter().flatten() {
+        let (arg_name, ty) = named_field(arg)?;
+        let value = args_json.get(arg_name).ok_or_else(|| {
+            ToolkitError::ParseError(format!("Missing argument '{}' for '{}'", arg_name, name))
+        })?;
+        size += sizer.size(ty, value)?;
+    }
+    Ok(size)
+}
+
+/// The `name` and `type` of an IDL argument or struct field.
+fn named_field(field: &serde_json::Value) -> Result<(&str, &serde_json::Value)> {
+    let name = field.get("name").and_then(|n| n.as_str());
+    match (name, field.get("type")) {
+        (Some(name), Some(ty)) => Ok((name, ty)),
+        _ => Err(ToolkitError::ParseError(format!("Malformed IDL field: {}", field))),
+    }
+}
+
+/// Borsh sizes of IDL-typed JSON values.
+struct IdlSizer<'a> {
+    types: &'a [serde_json::Value],
+}
+
+impl IdlSizer<'_> {
+    fn size(&self, ty: &serde_json::Value, value: &serde_json::Value) -> Result<usize> {
+        use serde_json::Value;
+
+        let mismatch = || ToolkitError::ParseError(format!("Value {} is not a {}", value, ty));
+        match ty {
+            Value::String(primitive) => match primitive.as_str() {
+                "bool" | "u8" | "i8" => Ok(1),
+                "u16" | "i16" => Ok(2),
+                "u32" | "i32" | "f32" => Ok(4),
+                "u64" | "i64" | "f64" => Ok(8),
+                "u128" | "i128" => Ok(16),
+                "publicKey" | "pubkey" => Ok(32),
+                "string" => Ok(4 + value.as_str().ok_or_else(mismatch)?.len()),
+                "bytes" => Ok(4 + value.as_array().ok_or_else(mismatch)?.len()),
+                other => Err(ToolkitError::ParseError(format!("Unknown IDL type '{}'", other))),
+            },
+            Value::Object(compound) => {
+                if let Some(inner) = compound.get("option") {
+                    match value {
+                        Value::Null => Ok(1),
+                        value => Ok(1 + self.size(inner, value)?),
+                    }
+                } else if let Some(inner) = compound.get("vec") {
+                    let items = value.as_array().ok_or_else(mismatch)?;
+                    items.iter().try_fold(4, |size, item| Ok(size + self.size(inner, item)?))
+                } else if let Some(array) = compound.get("array") {
+                    let inner = array.get(0).ok_or_else(mismatch)?;
+                    let len = array.get(1).and_then(|l| l.as_u64()).ok_or_else(mismatch)?;
+                    let items = value.as_array().ok_or_else(mismatch)?;
+                    if items.len() as u64 != len {
+                        return Err(mismatch());
+                    }
+                    items.iter().try_fold(0, |size, item| Ok(size + self.size(inner, item)?))
+                } else if let Some(defined) = compound.get("defined") {
+                    let name = defined
+                        .as_str()
+                        .or_else(|| defined.get("name").and_then(|n| n.as_str()))
+                        .ok_or_else(mismatch)?;
+                    self.defined_size(name, value)
+                } else {
+                    Err(mismatch())
+                }
+            }
+            _ => Err(mismatch()),
+        }
+    }
+
+    /// Size of a value of the struct or enum `name` from the IDL's types.
+    fn defined_size(&self, name: &str, value: &serde_json::Value) -> Result<usize> {
+        let definition = self
+            .types
+            .iter()
+            .find(|t| t.get("name").and_then(|n| n.as_str()) == Some(name))
+            .and_then(|t| t.get("type"))
+            .ok_or_else(|| ToolkitError::ParseError(format!("IDL has no type '{}'", name)))?;
+        let mismatch = || ToolkitError::ParseError(format!("Value {} is not a {}", value, name));
+
+        match definition.get("kind").and_then(|k| k.as_str()) {
+            Some("struct") => {
+                let fields = definition.get("fields").and_then(|f| f.as_array());
+                self.fields_size(fields.map(Vec::as_slice).unwrap_or(&[]), value)
+            }
+            Some("enum") => {
+                let (variant_name, fields_value) = match value {
+                    serde_json::Value::String(variant) => (variant.as_str(), None),
+                    serde_json::Value::Object(map) if map.len() == 1 => {
+                        let (variant, fields) = map.iter().next().ok_or_else(mismatch)?;
+                        (variant.as_str(), Some(fields))
+                    }
+                    _ => return Err(mismatch()),
+                };
+                let variant = definition
+                    .get("variants")
+                    .and_then(|v| v.as_array())
+                    .and_then(|variants| {
+                        variants
+                            .iter()
+                            .find(|v| v.get("name").and_then(|n| n.as_str()) == Some(variant_name))
+                    })
+                    .ok_or_else(mismatch)?;
+                let fields = variant.get("fields").and_then(|f| f.as_array());
+                let fields_size = match (fields, fields_value) {
+                    (None, _) => 0,
+                    (Some(fields), Some(value)) => self.fields_size(fields, value)?,
+                    (Some(_), None) => return Err(mismatch()),
+                };
+                Ok(1 + fields_size)
+            }
+            _ => Err(ToolkitError::ParseError(format!("Unsupported IDL type '{}'", name))),
+        }
+    }
+
+    /// Size of named fields (an object) or tuple fields (an array).
+    fn fields_size(
+        &self,
+        fields: &[serde_json::Value],
+        value: &serde_json::Value,
+    ) -> Result<usize> {
+        let mut size = 0;
+        for (index, field) in fields.iter().enumerate() {
+            if field.get("name").is_some() {
+                let (name, ty) = named_field(field)?;
+                let field_value = value.get(name).ok_or_else(|| {
+                    ToolkitError::ParseError(format!("Missing field '{}' in {}", name, value))
+                })?;
+                size += self.size(ty, field_value)?;
+            } else {
+                let field_value = value.get(index).ok_or_else(|| {
+                    ToolkitError::ParseError(format!("Missing field {} in {}", index, value))
+                })?;
+                size += self.size(field, field_value)?;
+            }
+        }
+        Ok(size)
+    }
+}
+
 // ============================================================================
 // Remaining Accounts Builder
 // ============================================================================
@@ -701,6 +1091,151 @@ mod tests {
         assert_eq!(ix.data, vec![1, 2, 3]);
     }
 
+    #[test]
+    fn test_cpi_builder_validation() {
+        let program_id = Pubkey::new_unique();
+        let (authority, vault) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let builder = || {
+            CpiInstructionBuilder::new()
+                .program(program_id)
+                .signer(authority)
+                .writable(vault)
+                .expect_anchor_instruction("deposit")
+        };
+
+        let ix = builder().anchor_data("deposit", &[1, 2]).build_validated(2).unwrap();
+        assert_eq!(ix.accounts.len(), 2);
+        assert_eq!(&ix.data[..8], &instruction_discriminator("deposit"));
+
+        // Too few accounts
+        let err = builder().anchor_data("deposit", &[]).build_validated(3).unwrap_err();
+        assert!(err.to_string().contains("expected at least 3"), "{}", err);
+
+        // Discriminator expected but data missing or for another instruction
+        let err = builder().build_validated(2).unwrap_err();
+        assert!(err.to_string().contains("discriminator of 'deposit'"), "{}", err);
+        assert!(builder().anchor_data("withdraw", &[]).validate().is_err());
+
+        // No signer, no accounts
+        let unsigned = CpiInstructionBuilder::new().program(program_id).readonly(vault);
+        assert!(unsigned.validate().unwrap_err().to_string().contains("signer"));
+        assert!(CpiInstructionBuilder::new().program(program_id).validate().is_err());
+
+        // Plain build stays permissive
+        assert!(CpiInstructionBuilder::new().program(program_id).build().is_ok());
+    }
+
+    #[test]
+    fn test_instruction_data_size_matches_serialized_length() {
+        use borsh::BorshSerialize;
+        use serde_json::json;
+
+        #[derive(BorshSerialize)]
+        struct DepositArgs {
+            amount: u64,
+            memo: String,
+            recipient: Option<[u8; 32]>,
+        }
+
+        #[derive(BorshSerialize)]
+        enum Side {
+            Bid,
+            Ask { price: u64 },
+        }
+
+        #[derive(BorshSerialize)]
+        struct Order {
+            side: Side,
+            sizes: Vec<u32>,
+        }
+
+        #[derive(BorshSerialize)]
+        struct PlaceArgs {
+            orders: Vec<Order>,
+            flags: [bool; 3],
+        }
+
+        let idl = json!({
+            "instructions": [
+                {
+                    "name": "deposit",
+                    "args": [
+                        { "name": "amount", "type": "u64" },
+                        { "name": "memo", "type": "string" },
+                        { "name": "recipient", "type": { "option": "publicKey" } }
+                    ]
+                },
+                {
+                    "name": "place",
+                    "args": [
+                        { "name": "orders", "type": { "vec": { "defined": "Order" } } },
+                        { "name": "flags", "type": { "array": ["bool", 3] } }
+                    ]
+                }
+            ],
+            "types": [
+                {
+                    "name": "Order",
+                    "type": {
+                        "kind": "struct",
+                        "fields": [
+                            { "name": "side", "type": { "defined": { "name": "Side" } } },
+                            { "name": "sizes", "type": { "vec": "u32" } }
+                        ]
+                    }
+                },
+                {
+                    "name": "Side",
+                    "type": {
+                        "kind": "enum",
+                        "variants": [
+                            { "name": "Bid" },
+                            { "name": "Ask", "fields": [{ "name": "price", "type": "u64" }] }
+                        ]
+                    }
+                }
+            ]
+        });
+
+        let deposit =
+            DepositArgs { amount: 5, memo: "hello".to_string(), recipient: Some([7; 32]) };
+        let data = serialization::serialize_anchor_ix("deposit", &deposit).unwrap();
+        assert_eq!(serialized_size(&deposit).unwrap(), data.len());
+        let recipient = Pubkey::default().to_string();
+        let args = json!({ "amount": 5, "memo": "hello", "recipient": recipient });
+        assert_eq!(instruction_data_size(&idl, "deposit", &args).unwrap(), data.len());
+        let args = json!({ "amount": 5, "memo": "", "recipient": null });
+        assert_eq!(instruction_data_size(&idl, "deposit", &args).unwrap(), 8 + 8 + 4 + 1);
+
+        let place = PlaceArgs {
+            orders: vec![
+                Order { side: Side::Bid, sizes: vec![1, 2, 3] },
+                Order { side: Side::Ask { price: 9 }, sizes: vec![] },
+            ],
+            flags: [true, false, true],
+        };
+        let data = serialization::serialize_anchor_ix("place", &place).unwrap();
+        assert_eq!(serialized_size(&place).unwrap(), data.len());
+        let args = json!({
+            "orders": [
+                { "side": "Bid", "sizes": [1, 2, 3] },
+                { "side": { "Ask": { "price": 9 } }, "sizes": [] }
+            ],
+            "flags": [true, false, true]
+        });
+        assert_eq!(instruction_data_size(&idl, "place", &args).unwrap(), data.len());
+
+        // Unknown instructions, missing arguments and mistyped values fail
+        assert!(instruction_data_size(&idl, "withdraw", &json!({})).is_err());
+        assert!(instruction_data_size(&idl, "deposit", &json!({ "amount": 5 })).is_err());
+        let args = json!({ "amount": 5, "memo": 7, "recipient": null });
+        assert!(instruction_data_size(&idl, "deposit", &args).is_err());
+        for flags in [json!([true, false]), json!([true, false, true, false])] {
+            let args = json!({ "orders": [], "flags": flags });
+            assert!(instruction_data_size(&idl, "place", &args).is_err());
+        }
+    }
+
     #[test]
     fn test_remaining_accounts_builder() {
         let pubkey1 = Pubkey::new_unique();
@@ -716,6 +1251,87 @@ mod tests {
         assert!(!accounts[1].is_writable);
     }
 
+    #[test]
+    fn test_validate_rent_exempt_boundary() {
+        use solana_sdk::account::Account;
+
+        let owner = Pubkey::new_unique();
+
+        // Default rent: (128 + 0) bytes * 3480 lamports * 2 years
+        let minimum = validation::default_rent().minimum_balance(0);
+        assert_eq!(minimum, 890_880);
+        let exempt = Account::new(minimum, 0, &owner);
+        assert!(validation::validate_rent_exempt_default(&exempt).is_ok());
+        assert!(matches!(
+            validation::validate_rent_exempt_default(&Account::new(minimum - 1, 0, &owner)),
+            Err(ToolkitError::InsufficientBalance { needed: 890_880, available: 890_879 })
+        ));
+
+        // Custom rent: (128 + 165) bytes * 1000 lamports * 1 year
+        let rent = validation::custom_rent(1_000, 1.0);
+        let exempt = Account::new(293_000, 165, &owner);
+        assert!(validation::validate_rent_exempt(&exempt, &rent).is_ok());
+        assert!(validation::validate_rent_exempt_default(&exempt).is_err());
+        let short = Account::new(292_999, 165, &owner);
+        assert!(validation::validate_rent_exempt(&short, &rent).is_err());
+    }
+
+    #[test]
+    fn test_token_cpi_uses_requested_program() {
+        use token_cpi::TokenCpi;
+
+        let (account, mint, wallet) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let build_all = |cpi: TokenCpi| {
+            vec![
+                cpi.transfer(&account, &wallet, &wallet, 5).unwrap(),
+                cpi.transfer_checked(&account, &mint, &wallet, &wallet, 5, 6).unwrap(),
+                cpi.mint_to(&mint, &account, &wallet, 5).unwrap(),
+                cpi.burn(&account, &mint, &wallet, 5).unwrap(),
+                cpi.close_account(&account, &wallet, &wallet).unwrap(),
+                cpi.approve(&account, &wallet, &wallet, 5).unwrap(),
+                cpi.revoke(&account, &wallet).unwrap(),
+                cpi.initialize_account(&account, &mint, &wallet).unwrap(),
+                cpi.set_authority(
+                    &mint,
+                    &wallet,
+                    spl_token::instruction::AuthorityType::MintTokens,
+                    None,
+                )
+                .unwrap(),
+                cpi.freeze_account(&account, &mint, &wallet).unwrap(),
+                cpi.thaw_account(&account, &mint, &wallet).unwrap(),
+            ]
+        };
+
+        let legacy = build_all(TokenCpi::legacy());
+        let token_2022 = build_all(TokenCpi::token_2022());
+        assert!(legacy.iter().all(|ix| ix.program_id == spl_token::id()));
+        assert!(token_2022.iter().all(|ix| ix.program_id == spl_token_2022::id()));
+        for (legacy_ix, token_2022_ix) in legacy.iter().zip(&token_2022) {
+            assert_eq!(legacy_ix.data, token_2022_ix.data);
+            assert_eq!(legacy_ix.accounts, token_2022_ix.accounts);
+        }
+
+        // Convenience functions stay on the legacy program
+        let expected = spl_token::instruction::close_account(
+            &spl_token::id(),
+            &account,
+            &wallet,
+            &wallet,
+            &[],
+        )
+        .unwrap();
+        assert_eq!(token_cpi::close_account(&account, &wallet, &wallet).unwrap(), expected);
+        assert_eq!(token_cpi::burn(&account, &mint, &wallet, 5).unwrap(), legacy[3]);
+
+        let unknown = TokenCpi::new(Pubkey::new_unique());
+        assert!(matches!(
+            unknown.burn(&account, &mint, &wallet, 5),
+            Err(ToolkitError::TokenError(_))
+        ));
+    }
+
     #[test]
     fn test_sizes() {
         assert_eq!(sizes::DISCRIMINATOR, 8);
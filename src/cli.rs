@@ -0,0 +1,334 @@
+//! Subcommand handlers for command-line tools.
+//!
+//! Each handler takes an argument struct that the binary fills from its own
+//! parser (clap, argh, ...) and returns a result the binary prints. No
+//! parsing or printing happens here, so the handlers can be tested and
+//! reused from any frontend:
+//!
+//! ```rust,ignore
+//! let wallet = Wallet::new_on(Network::Devnet, load_keypair(&path)?);
+//! let args = CleanArgs { dry_run: true, ..Default::default() };
+//! println!("{}", cli::run_clean(&wallet, &args).await?);
+//! ```
+
+use solana_sdk::pubkey::Pubkey;
+
+use crate::rent_cleaner::{AdvancedCleanupConfig, CleanupResult, CleanupStrategy};
+use crate::rpc::SolanaRpc;
+use crate::safety::{AddressComparison, AddressVerification, AddressVerifier, AmountValidator};
+use crate::wallet::Wallet;
+use crate::Result;
+
+#[cfg(feature = "jupiter")]
+use crate::jupiter::{SafeSwap, SafeSwapOutcome, SwapProvider};
+#[cfg(feature = "jupiter")]
+use solana_sdk::signature::Keypair;
+
+/// Arguments for [`run_clean`].
+#[derive(Debug, Clone)]
+pub struct CleanArgs {
+    /// Which accounts to close.
+    pub strategy: CleanupStrategy,
+    /// Plan without sending anything.
+    pub dry_run: bool,
+    /// Accounts closed per transaction, if not the default.
+    pub batch_size: Option<usize>,
+    /// Where recovered rent goes, if not the wallet.
+    pub rent_destination: Option<Pubkey>,
+    /// Mints whose accounts are left alone.
+    pub excluded_mints: Vec<Pubkey>,
+}
+
+impl Default for CleanArgs {
+    fn default() -> Self {
+        Self {
+            strategy: CleanupStrategy::EmptyOnly,
+            dry_run: false,
+            batch_size: None,
+            rent_destination: None,
+            excluded_mints: Vec::new(),
+        }
+    }
+}
+
+/// Close the wallet's cleanable token accounts.
+///
+/// Runs the wallet's rent cleaner with `args` applied over the default
+/// [`AdvancedCleanupConfig`]. The result's `Display` is a printable summary.
+pub async fn run_clean<R: SolanaRpc>(
+    wallet: &Wallet<R>,
+    args: &CleanArgs,
+) -> Result<CleanupResult> {
+    let mut config = AdvancedCleanupConfig {
+        strategy: args.strategy,
+        ..Default::default()
+    }
+    .exclude_mints(args.excluded_mints.clone());
+    config.base.dry_run = args.dry_run;
+    config.base.rent_destination = args.rent_destination;
+    if let Some(size) = args.batch_size {
+        config = config.with_batch_size(size);
+    }
+
+    wallet.rent_with_config(config).execute_cleanup().await
+}
+
+/// Arguments for [`run_verify`].
+#[derive(Debug, Clone, Default)]
+pub struct VerifyArgs {
+    /// Address to check.
+    pub address: String,
+    /// Address it is supposed to be, e.g. as copied from elsewhere.
+    pub expected: Option<String>,
+}
+
+/// Result of [`run_verify`].
+#[derive(Debug, Clone)]
+pub struct VerifyReport {
+    /// The parsed address.
+    pub verification: AddressVerification,
+    /// Comparison with the expected address, if one was given.
+    pub comparison: Option<AddressComparison>,
+    /// Balance and owning program, if the account exists.
+    pub account: Option<(u64, Pubkey)>,
+}
+
+impl VerifyReport {
+    /// Whether the address parsed and matches the expected one, if any.
+    pub fn is_ok(&self) -> bool {
+        self.comparison.as_ref().map_or(true, |c| c.matches)
+    }
+}
+
+impl std::fmt::Display for VerifyReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Address:  {} ({})",
+            self.verification.pubkey, self.verification.short_display
+        )?;
+        if let Some(comparison) = &self.comparison {
+            if comparison.matches {
+                writeln!(f, "Expected: matches")?;
+            } else {
+                let typo = if comparison.likely_typo { ", likely a typo" } else { "" };
+                writeln!(
+                    f,
+                    "Expected: MISMATCH ({} characters differ{})",
+                    comparison.difference_count, typo
+                )?;
+            }
+        }
+        match &self.account {
+            Some((lamports, owner)) => write!(
+                f,
+                "On chain: {}, owned by {}",
+                AmountValidator::format_amount_with_symbol(*lamports, 9, "SOL"),
+                owner
+            ),
+            None => write!(f, "On chain: no account"),
+        }
+    }
+}
+
+/// Parse an address, compare it with the expected one and look it up.
+///
+/// An unparseable address is an error; a mismatch is reported in the
+/// result, see [`VerifyReport::is_ok`].
+pub async fn run_verify<R: SolanaRpc + ?Sized>(
+    client: &R,
+    args: &VerifyArgs,
+) -> Result<VerifyReport> {
+    let verification = AddressVerifier::verify_full(&args.address)?;
+    let comparison = args
+        .expected
+        .as_deref()
+        .map(|expected| AddressVerifier::compare_addresses(&args.address, expected));
+    let account = client
+        .get_account(&verification.pubkey)
+        .await?
+        .map(|account| (account.lamports, account.owner));
+
+    Ok(VerifyReport {
+        verification,
+        comparison,
+        account,
+    })
+}
+
+/// Arguments for [`run_swap`].
+#[cfg(feature = "jupiter")]
+#[derive(Debug, Clone, Default)]
+pub struct SwapArgs {
+    /// Mint to sell.
+    pub input_mint: String,
+    /// Mint to buy.
+    pub output_mint: String,
+    /// Raw amount of the input mint.
+    pub amount: u64,
+    /// Slippage tolerance in basis points.
+    pub slippage_bps: u16,
+    /// Send swaps whose safety report asks for confirmation, e.g. `--yes`.
+    pub confirmed: bool,
+}
+
+/// Quote, check and execute a swap through `swaps`.
+///
+/// Blocked swaps are never sent; swaps needing confirmation are sent only
+/// with `confirmed` set. Check [`SafeSwapOutcome::was_sent`].
+#[cfg(feature = "jupiter")]
+pub async fn run_swap<S: SwapProvider, R: SolanaRpc>(
+    swaps: &SafeSwap<S, R>,
+    wallet: &Keypair,
+    args: &SwapArgs,
+) -> Result<SafeSwapOutcome> {
+    swaps
+        .swap_with_confirmation(
+            wallet,
+            &args.input_mint,
+            &args.output_mint,
+            args.amount,
+            args.slippage_bps,
+            |_| args.confirmed,
+        )
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{MockRpc, TOKEN_ACCOUNT_RENT};
+    use solana_sdk::signature::{Keypair, Signer};
+
+    #[tokio::test]
+    async fn test_run_clean_applies_args() {
+        let payer = Keypair::new();
+        let owner = payer.pubkey();
+        let (mint, excluded) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let rpc = MockRpc::new()
+            .with_account(Pubkey::new_unique(), MockRpc::token_account(&mint, &owner, 0))
+            .with_account(Pubkey::new_unique(), MockRpc::token_account(&mint, &owner, 0))
+            .with_account(Pubkey::new_unique(), MockRpc::token_account(&excluded, &owner, 0));
+        let wallet = Wallet::from_client(rpc, payer);
+
+        let args = CleanArgs {
+            dry_run: true,
+            excluded_mints: vec![excluded],
+            ..Default::default()
+        };
+        let result = run_clean(&wallet, &args).await.unwrap();
+        assert_eq!(result.accounts_closed, 2);
+        assert!(result.dry_run_plan.is_some());
+        assert!(wallet.client().sent_transactions().is_empty());
+
+        let args = CleanArgs {
+            dry_run: false,
+            batch_size: Some(1),
+            ..args
+        };
+        let result = run_clean(&wallet, &args).await.unwrap();
+        assert_eq!(result.lamports_recovered, 2 * TOKEN_ACCOUNT_RENT);
+        assert_eq!(wallet.client().sent_transactions().len(), 2);
+        assert!(result.to_string().contains("Accounts closed: 2"));
+    }
+
+    #[tokio::test]
+    async fn test_run_verify_reports_mismatch_and_account() {
+        let address = Pubkey::new_unique();
+        let rpc = MockRpc::new().with_balance(address, 1_500_000_000);
+        let args = VerifyArgs {
+            address: address.to_string(),
+            expected: Some(address.to_string()),
+        };
+
+        let report = run_verify(&rpc, &args).await.unwrap();
+        assert!(report.is_ok());
+        assert_eq!(report.account.unwrap().0, 1_500_000_000);
+        assert!(report.to_string().contains("1.5 SOL"), "{}", report);
+
+        let other = Pubkey::new_unique();
+        let args = VerifyArgs {
+            address: other.to_string(),
+            ..args
+        };
+        let report = run_verify(&rpc, &args).await.unwrap();
+        assert!(!report.is_ok());
+        assert!(report.account.is_none());
+        assert!(report.to_string().contains("MISMATCH"), "{}", report);
+
+        let args = VerifyArgs {
+            address: "not-an-address".to_string(),
+            expected: None,
+        };
+        assert!(run_verify(&rpc, &args).await.is_err());
+    }
+
+    #[cfg(feature = "jupiter")]
+    #[tokio::test]
+    async fn test_run_swap_sends_only_when_confirmed() {
+        use crate::jupiter::{JupiterClient, QuoteProvider, QuoteResponse};
+        use crate::safety::SafetyProtocol;
+        use async_trait::async_trait;
+        use solana_sdk::signature::Signature;
+
+        /// Quotes 2:1 with 1% price impact.
+        struct MockSwaps;
+
+        #[async_trait]
+        impl QuoteProvider for MockSwaps {
+            async fn quote(
+                &self,
+                input_mint: &str,
+                output_mint: &str,
+                amount: u64,
+                slippage_bps: u16,
+            ) -> Result<QuoteResponse> {
+                Ok(serde_json::from_value(serde_json::json!({
+                    "inputMint": input_mint,
+                    "inAmount": amount.to_string(),
+                    "outputMint": output_mint,
+                    "outAmount": (2 * amount).to_string(),
+                    "otherAmountThreshold": (2 * amount).to_string(),
+                    "swapMode": "ExactIn",
+                    "slippageBps": slippage_bps,
+                    "priceImpactPct": "1.0",
+                    "routePlan": []
+                }))
+                .unwrap())
+            }
+        }
+
+        #[async_trait]
+        impl SwapProvider for MockSwaps {
+            async fn execute_swap(&self, _: &Keypair, _: QuoteResponse) -> Result<Signature> {
+                Ok(Signature::new_unique())
+            }
+        }
+
+        let token = Pubkey::new_unique();
+        let rpc = MockRpc::new().with_account(token, MockRpc::mint_account(6, 1_000));
+        // 1% impact is over half the limit, so the report asks for confirmation
+        let swaps = SafeSwap::from_parts(MockSwaps, rpc, SafetyProtocol::new())
+            .with_max_price_impact_pct(1.5);
+        let wallet = Keypair::new();
+        let args = SwapArgs {
+            input_mint: JupiterClient::USDC_MINT.to_string(),
+            output_mint: token.to_string(),
+            amount: 1_000_000,
+            slippage_bps: 50,
+            confirmed: false,
+        };
+
+        let outcome = run_swap(&swaps, &wallet, &args).await.unwrap();
+        assert!(outcome.report.requires_confirmation);
+        assert!(!outcome.was_sent());
+
+        let args = SwapArgs {
+            confirmed: true,
+            ..args
+        };
+        let outcome = run_swap(&swaps, &wallet, &args).await.unwrap();
+        assert!(outcome.was_sent());
+        assert_eq!(outcome.quote.out_amount, 2_000_000);
+    }
+}
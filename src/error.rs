@@ -92,6 +92,33 @@ pub enum ToolkitError {
     #[error("Operation timed out: {0}")]
     Timeout(String),
 
+    /// A transaction's blockhash expired before it was confirmed, so it can
+    /// no longer land. Rebuild it with a fresh blockhash to retry.
+    #[error(
+        "Transaction expired: {signature} was not confirmed by block height \
+         {last_valid_block_height}"
+    )]
+    TransactionExpired {
+        /// Signature of the expired transaction.
+        signature: String,
+        /// Last block height at which the transaction could have landed.
+        last_valid_block_height: u64,
+    },
+
+    /// A transaction seen on chain disappeared before it was settled, as
+    /// when the fork it landed on is abandoned. Treat its effects as undone.
+    #[error(
+        "Transaction dropped: {signature} was seen at {seen} but is no longer found at {expected}"
+    )]
+    TransactionDropped {
+        /// Signature of the dropped transaction.
+        signature: String,
+        /// Highest commitment the transaction was seen at.
+        seen: String,
+        /// Commitment it was expected to hold.
+        expected: String,
+    },
+
     /// Custom error with message.
     #[error("Custom error: {0}")]
     Custom(String),
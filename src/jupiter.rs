@@ -40,21 +40,51 @@
 //! }
 //! ```
 
+use crate::retry::{retry, RetryConfig};
+use crate::rpc::{with_timeout, Network, SolanaRpc, TimeoutRpc, DEFAULT_RPC_TIMEOUT};
+use crate::safety::{SafetyProtocol, SafetyReport};
+use crate::token_utils::{decode_token_account, parse_ui_amount, MintRegistry};
+use crate::transaction::{
+    confirm_until_block_height, deserialize_base64, SendPolicy, DEFAULT_CONFIRM_POLL_INTERVAL,
+    MAX_TRANSACTION_SIZE,
+};
 use crate::{Result, ToolkitError};
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::{nonblocking::rpc_client::RpcClient, rpc_config::RpcSendTransactionConfig};
 use solana_sdk::{
+    account::Account,
     commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    message::Message,
     pubkey::Pubkey,
     signature::{Keypair, Signature},
     signer::Signer,
-    transaction::VersionedTransaction,
+    transaction::{Transaction, VersionedTransaction},
 };
-use std::str::FromStr;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
 
 /// Default Jupiter API endpoint
 pub const JUPITER_API_URL: &str = "https://quote-api.jup.ag/v6";
 
+/// Default timeout for Jupiter API requests
+pub const DEFAULT_JUPITER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Accounts Jupiter lets a route use when `maxAccounts` isn't set
+pub const DEFAULT_JUPITER_MAX_ACCOUNTS: usize = 64;
+
+/// Bytes of a swap instruction besides its account indices and route:
+/// program id key and index, length prefixes, discriminator, amounts,
+/// slippage and platform fee
+const SWAP_INSTRUCTION_OVERHEAD_BYTES: usize = 68;
+
+/// Encoded bytes per route plan step (swap kind, percent, input and output
+/// indices)
+const SWAP_BYTES_PER_HOP: usize = 6;
+
 /// Common token mints for convenience
 pub mod mints {
     use solana_sdk::pubkey::Pubkey;
@@ -78,9 +108,66 @@ pub mod mints {
 
 /// Jupiter swap client for interacting with Jupiter's V6 API
 pub struct JupiterClient {
-    rpc_client: RpcClient,
+    rpc_client: Arc<RpcClient>,
     api_url: String,
     http_client: reqwest::Client,
+    request_retries: RetryConfig,
+    slippage_policy: SlippagePolicy,
+    max_round_trip_loss_bps: u64,
+    mints: MintRegistry,
+    timeout: Duration,
+    max_accounts: Option<usize>,
+    send_policy: SendPolicy,
+}
+
+/// HTTP settings applied to every Jupiter API request
+#[derive(Debug, Clone)]
+pub struct JupiterConfig {
+    /// Per-request timeout (30s by default)
+    pub timeout: Duration,
+    /// `User-Agent` header sent with each request
+    pub user_agent: String,
+    /// Retries after a failed or timed-out request (none by default)
+    pub max_retries: u32,
+}
+
+impl Default for JupiterConfig {
+    fn default() -> Self {
+        Self {
+            timeout: DEFAULT_JUPITER_TIMEOUT,
+            user_agent: format!("solana-pipkit/{}", env!("CARGO_PKG_VERSION")),
+            max_retries: 0,
+        }
+    }
+}
+
+impl JupiterConfig {
+    /// Set the per-request timeout
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the `User-Agent` header
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// Retry failed requests up to `retries` times with backoff
+    pub fn with_max_retries(mut self, retries: u32) -> Self {
+        self.max_retries = retries;
+        self
+    }
+
+    /// Build an HTTP client with these settings
+    pub fn build_http_client(&self) -> Result<reqwest::Client> {
+        reqwest::Client::builder()
+            .timeout(self.timeout)
+            .user_agent(self.user_agent.as_str())
+            .build()
+            .map_err(|e| ToolkitError::ConfigError(format!("Invalid HTTP client config: {}", e)))
+    }
 }
 
 /// Quote response from Jupiter API
@@ -151,6 +238,96 @@ pub struct SwapInfo {
     pub fee_mint: String,
 }
 
+/// One hop of a swap route, flattened for display
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteHop {
+    /// DEX label (e.g., "Raydium", "Orca"), if Jupiter reported one
+    pub label: Option<String>,
+    /// Percentage of input routed through this hop
+    pub percent: u8,
+    /// Input amount in smallest units
+    pub in_amount: u64,
+    /// Output amount in smallest units
+    pub out_amount: u64,
+    /// Fee amount in smallest units of `fee_mint`
+    pub fee_amount: u64,
+    /// Mint the fee is charged in
+    pub fee_mint: String,
+    /// Input mint
+    pub input_mint: String,
+    /// Output mint
+    pub output_mint: String,
+}
+
+impl From<&RoutePlanStep> for RouteHop {
+    fn from(step: &RoutePlanStep) -> Self {
+        let info = &step.swap_info;
+        Self {
+            label: info.label.clone(),
+            percent: step.percent,
+            in_amount: info.in_amount,
+            out_amount: info.out_amount,
+            fee_amount: info.fee_amount,
+            fee_mint: info.fee_mint.clone(),
+            input_mint: info.input_mint.clone(),
+            output_mint: info.output_mint.clone(),
+        }
+    }
+}
+
+impl QuoteResponse {
+    /// The route plan as one [`RouteHop`] per step, in order
+    pub fn route_hops(&self) -> Vec<RouteHop> {
+        self.route_plan.iter().map(RouteHop::from).collect()
+    }
+
+    /// DEX fees across every route step, summed per fee mint
+    pub fn total_fees(&self) -> HashMap<String, u64> {
+        let mut fees = HashMap::new();
+        for step in &self.route_plan {
+            let total: &mut u64 = fees.entry(step.swap_info.fee_mint.clone()).or_default();
+            *total = total.saturating_add(step.swap_info.fee_amount);
+        }
+        fees
+    }
+
+    /// Output per unit of input after fees, in raw units
+    ///
+    /// Fees charged in the output mint are deducted from `out_amount`; fees
+    /// in other mints are already reflected in the amounts passed between
+    /// hops. `None` when the quote has no input.
+    pub fn effective_rate(&self) -> Option<f64> {
+        if self.in_amount == 0 {
+            return None;
+        }
+        let output_fees = self.total_fees().get(&self.output_mint).copied().unwrap_or(0);
+        let net_out = self.out_amount.saturating_sub(output_fees);
+        Some(net_out as f64 / self.in_amount as f64)
+    }
+}
+
+/// A quote with its route fees, from [`JupiterClient::get_quote_with_fees`]
+#[derive(Debug, Clone)]
+pub struct QuoteWithFees {
+    /// The quote itself
+    pub quote: QuoteResponse,
+    /// Route fees per fee mint, see [`QuoteResponse::total_fees`]
+    pub total_fees: HashMap<String, u64>,
+    /// See [`QuoteResponse::effective_rate`]
+    pub effective_rate: Option<f64>,
+}
+
+impl From<QuoteResponse> for QuoteWithFees {
+    fn from(quote: QuoteResponse) -> Self {
+        Self {
+            total_fees: quote.total_fees(),
+            effective_rate: quote.effective_rate(),
+            quote,
+        }
+    }
+}
+
 /// Swap request to Jupiter API
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -191,7 +368,9 @@ pub struct SwapRequest {
 pub struct SwapResponse {
     /// Base64-encoded serialized transaction
     pub swap_transaction: String,
-    /// Last valid block height
+    /// Last valid block height of the returned transaction's blockhash.
+    /// [`JupiterClient::swap_with_config`] re-signs with a fresh blockhash
+    /// and waits on that one's limit instead.
     pub last_valid_block_height: u64,
     /// Priority fee type used
     #[serde(default)]
@@ -211,8 +390,29 @@ pub struct SwapConfig {
     pub use_shared_accounts: bool,
     /// Dynamic compute unit limit
     pub dynamic_compute_unit_limit: bool,
+    /// Absolute floor on output tokens, independent of slippage (optional)
+    pub min_out_amount: Option<u64>,
+    /// Re-quote if the quote's context slot is older than this (optional)
+    pub max_quote_age_slots: Option<u64>,
+    /// How often, and for how long, to poll a swap's signature after its
+    /// confirmation is lost
+    pub confirmation_recovery: RetryConfig,
+    /// Token account to receive the output instead of the wallet's ATA
+    /// (optional)
+    pub destination_token_account: Option<Pubkey>,
+    /// Check that `destination_token_account` holds the output mint before
+    /// swapping
+    pub verify_destination: bool,
 }
 
+/// Default polling of a swap whose confirmation was lost: 10 checks over
+/// about 15 seconds
+pub const DEFAULT_CONFIRMATION_RECOVERY: RetryConfig = RetryConfig {
+    max_retries: 10,
+    initial_backoff: Duration::from_millis(500),
+    max_backoff: Duration::from_secs(2),
+};
+
 impl Default for SwapConfig {
     fn default() -> Self {
         Self {
@@ -221,6 +421,11 @@ impl Default for SwapConfig {
             wrap_unwrap_sol: true,
             use_shared_accounts: true,
             dynamic_compute_unit_limit: true,
+            min_out_amount: None,
+            max_quote_age_slots: None,
+            confirmation_recovery: DEFAULT_CONFIRMATION_RECOVERY,
+            destination_token_account: None,
+            verify_destination: true,
         }
     }
 }
@@ -239,248 +444,1364 @@ impl SwapConfig {
         self.priority_fee_micro_lamports = Some(micro_lamports);
         self
     }
-}
 
-impl JupiterClient {
-    /// Native SOL mint (wrapped)
-    pub const SOL_MINT: &'static str = "So11111111111111111111111111111111111111112";
-    /// USDC mint
-    pub const USDC_MINT: &'static str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
-    /// USDT mint
-    pub const USDT_MINT: &'static str = "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB";
+    /// Never accept fewer than `amount` output tokens
+    pub fn with_min_out_amount(mut self, amount: u64) -> Self {
+        self.min_out_amount = Some(amount);
+        self
+    }
 
-    /// Create a new Jupiter client with the default API endpoint
-    pub fn new(rpc_url: &str) -> Self {
-        Self::with_api_url(rpc_url, JUPITER_API_URL)
+    /// Re-quote before swapping if the quote is more than `slots` old
+    pub fn with_max_quote_age_slots(mut self, slots: u64) -> Self {
+        self.max_quote_age_slots = Some(slots);
+        self
     }
 
-    /// Create a new Jupiter client with a custom API endpoint
-    pub fn with_api_url(rpc_url: &str, api_url: &str) -> Self {
-        Self {
-            rpc_client: RpcClient::new_with_commitment(
-                rpc_url.to_string(),
-                CommitmentConfig::confirmed(),
-            ),
-            api_url: api_url.to_string(),
-            http_client: reqwest::Client::new(),
-        }
+    /// Poll a swap's signature per `recovery` when its confirmation is lost
+    pub fn with_confirmation_recovery(mut self, recovery: RetryConfig) -> Self {
+        self.confirmation_recovery = recovery;
+        self
     }
 
-    /// Get a quote for swapping tokens
-    ///
-    /// # Arguments
-    /// * `input_mint` - The mint address of the input token
-    /// * `output_mint` - The mint address of the output token
-    /// * `amount` - The amount to swap in smallest units (e.g., lamports for SOL)
-    /// * `slippage_bps` - Slippage tolerance in basis points (50 = 0.5%)
-    ///
-    /// # Example
-    /// ```rust,no_run
-    /// let quote = jupiter.get_quote(
-    ///     JupiterClient::USDC_MINT,
-    ///     JupiterClient::SOL_MINT,
-    ///     1_000_000, // 1 USDC
-    ///     50, // 0.5% slippage
-    /// ).await?;
-    /// ```
-    pub async fn get_quote(
-        &self,
-        input_mint: &str,
-        output_mint: &str,
-        amount: u64,
-        slippage_bps: u16,
-    ) -> Result<QuoteResponse> {
-        let url = format!(
-            "{}/quote?inputMint={}&outputMint={}&amount={}&slippageBps={}",
-            self.api_url, input_mint, output_mint, amount, slippage_bps
-        );
+    /// Send the output to `account` instead of the wallet's ATA
+    pub fn with_destination_token_account(mut self, account: Pubkey) -> Self {
+        self.destination_token_account = Some(account);
+        self
+    }
 
-        let response = self
-            .http_client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| ToolkitError::NetworkError(e.to_string()))?;
+    /// Enable or disable the destination token account check
+    pub fn with_verify_destination(mut self, enabled: bool) -> Self {
+        self.verify_destination = enabled;
+        self
+    }
 
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            return Err(ToolkitError::JupiterError(format!(
-                "Quote request failed: {}",
-                error_text
-            )));
+    /// Build the `/swap` request for `user` swapping `quote`
+    pub fn swap_request(&self, user: &Pubkey, quote: QuoteResponse) -> SwapRequest {
+        SwapRequest {
+            user_public_key: user.to_string(),
+            quote_response: quote,
+            wrap_and_unwrap_sol: Some(self.wrap_unwrap_sol),
+            use_shared_accounts: Some(self.use_shared_accounts),
+            fee_account: None,
+            compute_unit_price_micro_lamports: self.priority_fee_micro_lamports,
+            use_token_ledger: None,
+            destination_token_account: self.destination_token_account.map(|a| a.to_string()),
+            dynamic_compute_unit_limit: Some(self.dynamic_compute_unit_limit),
+            skip_user_accounts_rpc_calls: None,
         }
-
-        let quote: QuoteResponse = response
-            .json()
-            .await
-            .map_err(|e| ToolkitError::ParseError(e.to_string()))?;
-
-        Ok(quote)
     }
 
-    /// Get a quote for an exact output amount (reverse quote)
+    /// Check `account`, the fetched `destination_token_account`, against
+    /// the quote's output mint
     ///
-    /// Use this when you know how much output you want to receive.
-    pub async fn get_quote_exact_out(
+    /// Passes when no destination is set or verification is disabled, and
+    /// otherwise rejects a missing account or one that isn't an SPL Token
+    /// or Token-2022 account of the output mint.
+    pub fn check_destination(
         &self,
-        input_mint: &str,
-        output_mint: &str,
-        amount: u64,
-        slippage_bps: u16,
-    ) -> Result<QuoteResponse> {
-        let url = format!(
-            "{}/quote?inputMint={}&outputMint={}&amount={}&slippageBps={}&swapMode=ExactOut",
-            self.api_url, input_mint, output_mint, amount, slippage_bps
-        );
-
-        let response = self
-            .http_client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| ToolkitError::NetworkError(e.to_string()))?;
+        quote: &QuoteResponse,
+        account: Option<&Account>,
+    ) -> Result<()> {
+        let Some(destination) = self.destination_token_account else {
+            return Ok(());
+        };
+        if !self.verify_destination {
+            return Ok(());
+        }
 
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
+        let state = account
+            .filter(|a| a.owner == spl_token::id() || a.owner == spl_token_2022::id())
+            .and_then(decode_token_account)
+            .ok_or_else(|| {
+                ToolkitError::JupiterError(format!(
+                    "Destination {} is not a token account",
+                    destination
+                ))
+            })?;
+        if state.mint.to_string() != quote.output_mint {
             return Err(ToolkitError::JupiterError(format!(
-                "Quote request failed: {}",
-                error_text
+                "Destination {} holds mint {}, not the output mint {}",
+                destination, state.mint, quote.output_mint
             )));
         }
 
-        let quote: QuoteResponse = response
-            .json()
-            .await
-            .map_err(|e| ToolkitError::ParseError(e.to_string()))?;
-
-        Ok(quote)
+        Ok(())
     }
 
-    /// Execute a swap with a previously fetched quote
-    ///
-    /// # Arguments
-    /// * `wallet` - The wallet keypair to sign the transaction
-    /// * `quote` - The quote response from `get_quote`
+    /// Check a quote against `min_out_amount`
     ///
-    /// # Returns
-    /// The transaction signature on success
-    pub async fn swap(&self, wallet: &Keypair, quote: QuoteResponse) -> Result<Signature> {
-        self.swap_with_config(wallet, quote, SwapConfig::default())
-            .await
-    }
-
-    /// Execute a swap with custom configuration
-    pub async fn swap_with_config(
-        &self,
-        wallet: &Keypair,
-        quote: QuoteResponse,
-        config: SwapConfig,
-    ) -> Result<Signature> {
-        // Build swap request
-        let swap_request = SwapRequest {
-            user_public_key: wallet.pubkey().to_string(),
-            quote_response: quote,
-            wrap_and_unwrap_sol: Some(config.wrap_unwrap_sol),
-            use_shared_accounts: Some(config.use_shared_accounts),
-            fee_account: None,
-            compute_unit_price_micro_lamports: config.priority_fee_micro_lamports,
-            use_token_ledger: None,
-            destination_token_account: None,
-            dynamic_compute_unit_limit: Some(config.dynamic_compute_unit_limit),
-            skip_user_accounts_rpc_calls: None,
+    /// Rejects the quote if its expected output is below the floor, or if
+    /// its slippage threshold (for ExactIn swaps) would allow receiving less.
+    pub fn check_min_out(&self, quote: &QuoteResponse) -> Result<()> {
+        let Some(min_out) = self.min_out_amount else {
+            return Ok(());
         };
 
-        // Get swap transaction from Jupiter
-        let url = format!("{}/swap", self.api_url);
-        let response = self
-            .http_client
-            .post(&url)
-            .json(&swap_request)
-            .send()
-            .await
-            .map_err(|e| ToolkitError::NetworkError(e.to_string()))?;
+        if quote.out_amount < min_out {
+            return Err(ToolkitError::JupiterError(format!(
+                "Quote output {} is below the minimum output {}",
+                quote.out_amount, min_out
+            )));
+        }
 
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
+        if quote.swap_mode == "ExactIn" && quote.other_amount_threshold < min_out {
             return Err(ToolkitError::JupiterError(format!(
-                "Swap request failed: {}",
-                error_text
+                "Slippage threshold {} would allow less than the minimum output {}",
+                quote.other_amount_threshold, min_out
             )));
         }
 
-        let swap_response: SwapResponse = response
-            .json()
-            .await
-            .map_err(|e| ToolkitError::ParseError(e.to_string()))?;
+        Ok(())
+    }
+}
 
-        // Decode and sign transaction
-        let tx_bytes = base64::Engine::decode(
-            &base64::engine::general_purpose::STANDARD,
-            &swap_response.swap_transaction,
-        )
-        .map_err(|e| ToolkitError::ParseError(format!("Failed to decode transaction: {}", e)))?;
+/// Policy for deriving slippage from a quote's pool conditions.
+///
+/// The recommendation is `min_bps + 2 × price impact (bps) + per_hop_bps ×
+/// (hops - 1)`, clamped to `[min_bps, max_bps]`. Doubling the observed
+/// impact leaves room for the pool to move between quote and landing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlippagePolicy {
+    /// Floor for the recommendation in basis points
+    pub min_bps: u16,
+    /// Ceiling for the recommendation in basis points
+    pub max_bps: u16,
+    /// Extra slippage per additional route hop in basis points
+    pub per_hop_bps: u16,
+}
 
-        let mut versioned_tx: VersionedTransaction = bincode::deserialize(&tx_bytes)
-            .map_err(|e| ToolkitError::ParseError(format!("Failed to deserialize tx: {}", e)))?;
+impl Default for SlippagePolicy {
+    fn default() -> Self {
+        Self {
+            min_bps: 30,
+            max_bps: 500,
+            per_hop_bps: 15,
+        }
+    }
+}
 
-        // Sign the transaction
-        let recent_blockhash = self
-            .rpc_client
-            .get_latest_blockhash()
-            .await
-            .map_err(|e| ToolkitError::RpcError(e.to_string()))?;
+impl SlippagePolicy {
+    /// Create a policy clamped to the given bounds
+    pub fn new(min_bps: u16, max_bps: u16) -> Self {
+        Self {
+            min_bps,
+            max_bps,
+            ..Default::default()
+        }
+    }
 
-        versioned_tx
-            .message
-            .set_recent_blockhash(recent_blockhash);
+    /// Set the extra slippage per additional hop
+    pub fn with_per_hop_bps(mut self, per_hop_bps: u16) -> Self {
+        self.per_hop_bps = per_hop_bps;
+        self
+    }
 
-        let signed_tx = VersionedTransaction::try_new(versioned_tx.message, &[wallet])
-            .map_err(|e| ToolkitError::SigningError(e.to_string()))?;
+    /// Recommend a slippage in basis points for the given quote
+    ///
+    /// An unparseable price impact yields `max_bps`.
+    pub fn recommend(&self, quote: &QuoteResponse) -> u16 {
+        let impact_pct = match quote.price_impact_pct.trim().parse::<f64>() {
+            Ok(pct) if pct.is_finite() => pct.abs(),
+            _ => return self.max_bps,
+        };
 
-        // Send and confirm transaction
-        let signature = self
-            .rpc_client
-            .send_and_confirm_transaction(&signed_tx)
-            .await
-            .map_err(|e| ToolkitError::TransactionError(e.to_string()))?;
+        let impact_bps = (impact_pct * 100.0 * 2.0).ceil();
+        let extra_hops = quote.route_plan.len().saturating_sub(1) as f64;
+        let recommended =
+            self.min_bps as f64 + impact_bps + self.per_hop_bps as f64 * extra_hops;
 
-        Ok(signature)
+        recommended.clamp(self.min_bps as f64, self.max_bps as f64) as u16
     }
+}
 
-    /// Simple swap helper - swap tokens with default config
-    ///
-    /// # Arguments
-    /// * `wallet` - The wallet keypair
-    /// * `input_mint` - Input token mint address
-    /// * `output_mint` - Output token mint address  
-    /// * `amount` - Amount to swap in smallest units
-    /// * `slippage_bps` - Slippage tolerance in basis points
-    ///
-    /// # Example
-    /// ```rust,no_run
-    /// // Swap 1 USDC for SOL with 0.5% slippage
-    /// let sig = jupiter.simple_swap(
-    ///     &wallet,
-    ///     JupiterClient::USDC_MINT,
-    ///     JupiterClient::SOL_MINT,
-    ///     1_000_000,
-    ///     50,
-    /// ).await?;
-    /// ```
-    pub async fn simple_swap(
+/// Recommend a slippage in basis points using the default [`SlippagePolicy`]
+pub fn recommend_slippage_bps(quote: &QuoteResponse) -> u16 {
+    SlippagePolicy::default().recommend(quote)
+}
+
+/// Source of swap quotes.
+///
+/// Implemented by [`JupiterClient`]; lets quote-driven checks such as
+/// [`check_sellability`] run against canned quotes in tests.
+#[async_trait]
+pub trait QuoteProvider: Send + Sync {
+    /// Get an exact-in quote
+    async fn quote(
         &self,
-        wallet: &Keypair,
         input_mint: &str,
         output_mint: &str,
         amount: u64,
         slippage_bps: u16,
-    ) -> Result<Signature> {
+    ) -> Result<QuoteResponse>;
+}
+
+/// Executor for quoted swaps.
+///
+/// Implemented by [`JupiterClient`]; lets flows that swap as one of several
+/// steps, such as dust consolidation, run against a mock in tests.
+#[async_trait]
+pub trait SwapProvider: QuoteProvider {
+    /// Execute a swap for `quote`, signed by `wallet`
+    async fn execute_swap(&self, wallet: &Keypair, quote: QuoteResponse) -> Result<Signature>;
+}
+
+/// Return `quote`, or a fresh quote for the same swap if it is stale
+///
+/// A quote is stale when `current_slot` is more than `max_age_slots` past its
+/// `context_slot`, or when it carries no context slot. Only ExactIn quotes
+/// can be refreshed; a stale ExactOut quote is an error.
+pub async fn refresh_if_stale<Q: QuoteProvider + ?Sized>(
+    quotes: &Q,
+    quote: QuoteResponse,
+    current_slot: u64,
+    max_age_slots: u64,
+) -> Result<QuoteResponse> {
+    let is_fresh = quote
+        .context_slot
+        .is_some_and(|slot| current_slot.saturating_sub(slot) <= max_age_slots);
+    if is_fresh {
+        return Ok(quote);
+    }
+
+    if quote.swap_mode != "ExactIn" {
+        return Err(ToolkitError::JupiterError(format!(
+            "Stale {} quote (context slot {:?}, current slot {}) must be re-quoted",
+            quote.swap_mode, quote.context_slot, current_slot
+        )));
+    }
+
+    quotes
+        .quote(&quote.input_mint, &quote.output_mint, quote.in_amount, quote.slippage_bps)
+        .await
+}
+
+/// Wait for `send_and_confirm` and, if its confirmation is lost, look up
+/// `signature` itself before giving up
+///
+/// A confirmation timeout doesn't mean the swap failed; it may have landed
+/// or still land, and building a fresh swap would then swap twice. The
+/// signature's status is polled per `recovery`, each check bounded by
+/// `timeout`: a confirmed swap returns `signature`, a failed one a
+/// [`ToolkitError::TransactionError`], and one never seen a
+/// [`ToolkitError::Timeout`] saying so. Other errors are returned as is.
+pub async fn confirm_swap<R, F>(
+    rpc: &R,
+    signature: Signature,
+    send_and_confirm: F,
+    recovery: &RetryConfig,
+    timeout: Duration,
+) -> Result<Signature>
+where
+    R: SolanaRpc + ?Sized,
+    F: std::future::Future<Output = Result<Signature>>,
+{
+    let error = match send_and_confirm.await {
+        Err(e) if is_confirmation_drop(&e) => e,
+        result => return result,
+    };
+    warn!(signature = %signature, error = %error, "swap confirmation lost, checking signature");
+
+    let status = retry(recovery, || async {
+        let statuses = with_timeout(timeout, "get_signature_statuses", async {
+            rpc.get_signature_statuses(&[signature]).await
+        })
+        .await?;
+        let status = statuses.into_iter().next().flatten();
+        match status {
+            Some(status) if status.err.is_some() => Err(ToolkitError::TransactionError(format!(
+                "Swap {} failed: {:?}",
+                signature, status.err
+            ))),
+            Some(status) if status.satisfies_commitment(CommitmentConfig::confirmed()) => Ok(()),
+            _ => Err(ToolkitError::Timeout(format!(
+                "Swap {} not confirmed after {}; it may still land until its blockhash \
+                 expires, so check it before swapping again",
+                signature, error
+            ))),
+        }
+    })
+    .await;
+
+    status.map(|()| {
+        info!(signature = %signature, "swap confirmed late");
+        signature
+    })
+}
+
+/// What a confirmed swap actually delivered, from [`report_swap`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapOutcome {
+    /// Signature of the confirmed swap
+    pub signature: Signature,
+    /// Output the quote expected
+    pub expected_out: u64,
+    /// Output that arrived in the destination account
+    pub actual_out: u64,
+    /// Whether `actual_out` fell below the quote's `other_amount_threshold`
+    pub below_threshold: bool,
+}
+
+impl SwapOutcome {
+    /// Expected output that didn't arrive, or 0 if at least as much did
+    pub fn shortfall(&self) -> u64 {
+        self.expected_out.saturating_sub(self.actual_out)
+    }
+}
+
+/// Run `swap` and measure what it delivered to `destination`
+///
+/// `destination`'s balance is read before and after `swap`: the token
+/// amount of a token account, or the lamports of any other account, and
+/// zero for an account that doesn't exist yet (such as an ATA the swap
+/// creates). A balance that drops reports zero output.
+///
+/// A lamport `destination` is taken to be the swap's fee payer, so the
+/// transaction's fee and the rent of accounts it created (see
+/// [`SolanaRpc::get_transaction_costs`]) are added back to its delta.
+pub async fn report_swap<R, F>(
+    rpc: &R,
+    destination: &Pubkey,
+    quote: &QuoteResponse,
+    swap: F,
+) -> Result<SwapOutcome>
+where
+    R: SolanaRpc + ?Sized,
+    F: std::future::Future<Output = Result<Signature>>,
+{
+    let balance = |account: Option<Account>| {
+        account.map_or(0, |account| {
+            decode_token_account(&account).map_or(account.lamports, |token| token.amount)
+        })
+    };
+
+    let before = balance(rpc.get_account(destination).await?);
+    let signature = swap.await?;
+    let after = rpc.get_account(destination).await?;
+
+    let in_lamports = after.as_ref().is_some_and(|a| decode_token_account(a).is_none());
+    let costs = if in_lamports {
+        rpc.get_transaction_costs(&signature).await?.unwrap_or(0)
+    } else {
+        0
+    };
+    let actual_out = (balance(after) + costs).saturating_sub(before);
+    let below_threshold = actual_out < quote.other_amount_threshold;
+    if below_threshold {
+        warn!(
+            signature = %signature,
+            actual_out,
+            threshold = quote.other_amount_threshold,
+            "swap delivered less than its slippage threshold"
+        );
+    }
+    Ok(SwapOutcome {
+        signature,
+        expected_out: quote.out_amount,
+        actual_out,
+        below_threshold,
+    })
+}
+
+/// Whether `error` means a sent transaction's outcome is unknown rather
+/// than failed
+fn is_confirmation_drop(error: &ToolkitError) -> bool {
+    match error {
+        ToolkitError::Timeout(_) => true,
+        ToolkitError::TransactionError(message) => message.contains("unable to confirm"),
+        _ => false,
+    }
+}
+
+/// Default maximum round-trip loss before a token is flagged (10%)
+pub const DEFAULT_MAX_ROUND_TRIP_LOSS_BPS: u64 = 1_000;
+
+/// Outcome of a buy-then-sell round-trip probe
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Sellability {
+    /// The token can be sold back within the loss threshold
+    Sellable {
+        /// Tokens received for the probe amount
+        tokens_out: u64,
+        /// Lamports returned when selling those tokens
+        lamports_back: u64,
+        /// Round-trip loss in basis points
+        round_trip_loss_bps: u64,
+    },
+    /// A sell route exists but loses more than the threshold
+    ExcessiveLoss {
+        /// Tokens received for the probe amount
+        tokens_out: u64,
+        /// Lamports returned when selling those tokens
+        lamports_back: u64,
+        /// Round-trip loss in basis points
+        round_trip_loss_bps: u64,
+    },
+    /// No route exists to sell the token back to SOL
+    NoSellRoute {
+        /// Tokens received for the probe amount
+        tokens_out: u64,
+        /// Why the sell quote failed
+        reason: String,
+    },
+}
+
+impl Sellability {
+    /// Returns true if the token passed the round-trip check
+    pub fn is_sellable(&self) -> bool {
+        matches!(self, Sellability::Sellable { .. })
+    }
+}
+
+/// Probe whether `mint` can be bought with SOL and sold straight back
+///
+/// Quotes SOL→token for `probe_lamports`, then token→SOL for the tokens
+/// received. Tokens with no sell route, or whose round trip loses more
+/// than `max_loss_bps`, are flagged; this catches many honeypots.
+pub async fn check_sellability<Q: QuoteProvider + ?Sized>(
+    quotes: &Q,
+    mint: &str,
+    probe_lamports: u64,
+    max_loss_bps: u64,
+) -> Result<Sellability> {
+    let buy = quotes
+        .quote(JupiterClient::SOL_MINT, mint, probe_lamports, 50)
+        .await?;
+    let tokens_out = buy.out_amount;
+
+    let sell = match quotes.quote(mint, JupiterClient::SOL_MINT, tokens_out, 50).await {
+        Ok(sell) if sell.route_plan.is_empty() || sell.out_amount == 0 => {
+            return Ok(Sellability::NoSellRoute {
+                tokens_out,
+                reason: "Sell quote returned no route".to_string(),
+            });
+        }
+        Ok(sell) => sell,
+        Err(ToolkitError::JupiterError(reason)) if is_no_route_error(&reason) => {
+            return Ok(Sellability::NoSellRoute { tokens_out, reason });
+        }
+        Err(e) => return Err(e),
+    };
+
+    let lamports_back = sell.out_amount;
+    let round_trip_loss_bps = if probe_lamports == 0 {
+        0
+    } else {
+        (probe_lamports.saturating_sub(lamports_back) as u128 * 10_000
+            / probe_lamports as u128) as u64
+    };
+
+    if round_trip_loss_bps > max_loss_bps {
+        Ok(Sellability::ExcessiveLoss {
+            tokens_out,
+            lamports_back,
+            round_trip_loss_bps,
+        })
+    } else {
+        Ok(Sellability::Sellable {
+            tokens_out,
+            lamports_back,
+            round_trip_loss_bps,
+        })
+    }
+}
+
+/// Error codes Jupiter answers a quote with when no route exists
+const NO_ROUTE_ERROR_CODES: &[&str] = &["COULD_NOT_FIND_ANY_ROUTE", "NO_ROUTES_FOUND"];
+
+/// Whether a quote error is Jupiter saying no route exists, as opposed to
+/// a rate limit, server error or network failure
+fn is_no_route_error(reason: &str) -> bool {
+    NO_ROUTE_ERROR_CODES.iter().any(|code| reason.contains(code))
+}
+
+/// Settings for [`rebalance`]
+#[derive(Debug, Clone)]
+pub struct RebalanceConfig {
+    /// Slippage tolerance for each swap in basis points
+    pub slippage_bps: u16,
+    /// Holdings quoted below this output amount are skipped as dust
+    pub min_output: u64,
+    /// Pause between swaps, to stay under API rate limits
+    pub swap_delay: Duration,
+}
+
+impl Default for RebalanceConfig {
+    fn default() -> Self {
+        Self {
+            slippage_bps: 100,
+            min_output: crate::rent_cleaner::DEFAULT_MIN_SWAP_OUTPUT,
+            swap_delay: Duration::ZERO,
+        }
+    }
+}
+
+impl RebalanceConfig {
+    /// Set the slippage tolerance in basis points
+    pub fn with_slippage_bps(mut self, slippage_bps: u16) -> Self {
+        self.slippage_bps = slippage_bps;
+        self
+    }
+
+    /// Set the minimum quoted output below which a holding is skipped
+    pub fn with_min_output(mut self, min_output: u64) -> Self {
+        self.min_output = min_output;
+        self
+    }
+
+    /// Set the pause between swaps
+    pub fn with_swap_delay(mut self, delay: Duration) -> Self {
+        self.swap_delay = delay;
+        self
+    }
+}
+
+/// A swap executed by [`rebalance`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RebalanceSwap {
+    /// Mint that was sold
+    pub mint: Pubkey,
+    /// Raw amount of `mint` sold
+    pub in_amount: u64,
+    /// Quoted raw amount of the target mint received
+    pub out_amount: u64,
+    /// Swap transaction signature
+    pub signature: Signature,
+}
+
+/// Result of [`rebalance`]
+#[derive(Debug, Clone, Default)]
+pub struct RebalanceResult {
+    /// Swaps that were executed
+    pub swaps: Vec<RebalanceSwap>,
+    /// Holdings skipped because their quoted output was below the minimum
+    pub dust: Vec<Pubkey>,
+    /// Holdings whose quote or swap failed, with the error
+    pub failed: Vec<(Pubkey, String)>,
+}
+
+impl RebalanceResult {
+    /// Signatures of the executed swaps, in order
+    pub fn signatures(&self) -> Vec<Signature> {
+        self.swaps.iter().map(|swap| swap.signature).collect()
+    }
+
+    /// Total quoted output of the target mint
+    pub fn total_output(&self) -> u64 {
+        self.swaps.iter().map(|swap| swap.out_amount).sum()
+    }
+}
+
+/// Swap each of `holdings` into `target_mint`
+///
+/// Every holding is quoted and swapped independently: dust and failures
+/// are recorded and the remaining holdings still swap. Holdings of the
+/// target mint and zero amounts are ignored.
+pub async fn rebalance<S: SwapProvider + ?Sized>(
+    swaps: &S,
+    wallet: &Keypair,
+    holdings: &[(Pubkey, u64)],
+    target_mint: &str,
+    config: &RebalanceConfig,
+) -> Result<RebalanceResult> {
+    let target = target_mint
+        .parse::<Pubkey>()
+        .map_err(|e| ToolkitError::ParseError(format!("Invalid target mint: {}", e)))?;
+    let mut result = RebalanceResult::default();
+
+    for &(mint, amount) in holdings {
+        if mint == target || amount == 0 {
+            continue;
+        }
+
+        let quote = match swaps
+            .quote(&mint.to_string(), target_mint, amount, config.slippage_bps)
+            .await
+        {
+            Ok(quote) => quote,
+            Err(e) => {
+                result.failed.push((mint, e.to_string()));
+                continue;
+            }
+        };
+        if quote.out_amount < config.min_output {
+            result.dust.push(mint);
+            continue;
+        }
+
+        if !result.swaps.is_empty() && !config.swap_delay.is_zero() {
+            tokio::time::sleep(config.swap_delay).await;
+        }
+        let out_amount = quote.out_amount;
+        match swaps.execute_swap(wallet, quote).await {
+            Ok(signature) => result.swaps.push(RebalanceSwap {
+                mint,
+                in_amount: amount,
+                out_amount,
+                signature,
+            }),
+            Err(e) => result.failed.push((mint, e.to_string())),
+        }
+    }
+
+    Ok(result)
+}
+
+/// Result of [`JupiterClient::swap_ui`]
+#[derive(Debug, Clone)]
+pub struct UiSwapOutcome {
+    /// Signature of the swap
+    pub signature: Signature,
+    /// Quoted output in display units, e.g. `"0.0069 SOL"`
+    pub quoted_output: String,
+}
+
+/// Default price impact that blocks a [`SafeSwap`] (5%)
+pub const DEFAULT_MAX_PRICE_IMPACT_PCT: f64 = 5.0;
+
+/// Result of [`SafeSwap::swap`]
+#[derive(Debug, Clone)]
+pub struct SafeSwapOutcome {
+    /// Quote the swap was validated against
+    pub quote: QuoteResponse,
+    /// Combined quote and output-mint safety report
+    pub report: SafetyReport,
+    /// Signature, if the swap was sent
+    pub signature: Option<Signature>,
+}
+
+impl SafeSwapOutcome {
+    /// Whether the swap was sent
+    pub fn was_sent(&self) -> bool {
+        self.signature.is_some()
+    }
+}
+
+/// Swaps gated by [`SafetyProtocol::validate_swap`], and by
+/// [`SafetyProtocol::check_token_authenticity`] and
+/// [`SafetyProtocol::check_swap_output`] on the output mint
+pub struct SafeSwap<S = JupiterClient, R = RpcClient> {
+    swaps: S,
+    client: TimeoutRpc<R>,
+    protocol: SafetyProtocol,
+    max_impact_pct: f64,
+}
+
+impl SafeSwap {
+    /// Create with Jupiter's default API and the default safety protocol
+    pub fn new(rpc_url: &str) -> Self {
+        Self::from_parts(
+            JupiterClient::new(rpc_url),
+            RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed()),
+            SafetyProtocol::new(),
+        )
+    }
+
+    /// Create for a well-known network with the default safety protocol
+    pub fn new_on(network: Network) -> Self {
+        Self::new(network.rpc_url())
+    }
+}
+
+impl<S: SwapProvider, R: SolanaRpc> SafeSwap<S, R> {
+    /// Create from a swap provider, an RPC backend and a safety protocol
+    pub fn from_parts(swaps: S, client: R, protocol: SafetyProtocol) -> Self {
+        Self {
+            swaps,
+            client: TimeoutRpc::new(client, DEFAULT_RPC_TIMEOUT),
+            protocol,
+            max_impact_pct: DEFAULT_MAX_PRICE_IMPACT_PCT,
+        }
+    }
+
+    /// Set the price impact (in percent) that blocks a swap
+    pub fn with_max_price_impact_pct(mut self, max_impact_pct: f64) -> Self {
+        self.max_impact_pct = max_impact_pct;
+        self
+    }
+
+    /// Fail quotes, swaps and RPC calls that take longer than `timeout`
+    /// (30s by default) with [`ToolkitError::Timeout`]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client.set_timeout(timeout);
+        self
+    }
+
+    /// Quote, validate and execute an exact-in swap
+    ///
+    /// Sends only if the report is approved and does not require
+    /// confirmation; otherwise returns the report without sending.
+    pub async fn swap(
+        &self,
+        wallet: &Keypair,
+        input_mint: &str,
+        output_mint: &str,
+        amount: u64,
+        slippage_bps: u16,
+    ) -> Result<SafeSwapOutcome> {
+        let refuse = |_: &SafetyReport| false;
+        self.swap_with_confirmation(wallet, input_mint, output_mint, amount, slippage_bps, refuse)
+            .await
+    }
+
+    /// Quote, validate and execute an exact-in swap, asking `confirm` when
+    /// the report requires confirmation
+    ///
+    /// Blocked swaps are never sent, regardless of `confirm`.
+    pub async fn swap_with_confirmation<F>(
+        &self,
+        wallet: &Keypair,
+        input_mint: &str,
+        output_mint: &str,
+        amount: u64,
+        slippage_bps: u16,
+        confirm: F,
+    ) -> Result<SafeSwapOutcome>
+    where
+        F: FnOnce(&SafetyReport) -> bool,
+    {
+        let output = output_mint
+            .parse::<Pubkey>()
+            .map_err(|e| ToolkitError::ParseError(format!("Invalid output mint: {}", e)))?;
+
+        let timeout = self.client.timeout();
+        let quote = self.swaps.quote(input_mint, output_mint, amount, slippage_bps);
+        let quote = with_timeout(timeout, "quote", quote).await?;
+        let mut report = self.protocol.validate_swap(&quote, self.max_impact_pct);
+        let authenticity = self
+            .protocol
+            .check_token_authenticity(&self.client, &output)
+            .await?;
+        report.merge(&authenticity);
+        let output_fee = self
+            .protocol
+            .check_swap_output(&self.client, &output, quote.out_amount)
+            .await?;
+        report.merge(&output_fee);
+
+        let proceed = report.approved && (!report.requires_confirmation || confirm(&report));
+        if !proceed {
+            return Ok(SafeSwapOutcome {
+                quote,
+                report,
+                signature: None,
+            });
+        }
+
+        let execute = self.swaps.execute_swap(wallet, quote.clone());
+        let signature = with_timeout(timeout, "execute_swap", execute).await?;
+        Ok(SafeSwapOutcome {
+            quote,
+            report,
+            signature: Some(signature),
+        })
+    }
+}
+
+#[async_trait]
+impl QuoteProvider for JupiterClient {
+    async fn quote(
+        &self,
+        input_mint: &str,
+        output_mint: &str,
+        amount: u64,
+        slippage_bps: u16,
+    ) -> Result<QuoteResponse> {
+        self.get_quote(input_mint, output_mint, amount, slippage_bps)
+            .await
+    }
+}
+
+#[async_trait]
+impl SwapProvider for JupiterClient {
+    async fn execute_swap(&self, wallet: &Keypair, quote: QuoteResponse) -> Result<Signature> {
+        self.swap(wallet, quote).await
+    }
+}
+
+impl JupiterClient {
+    /// Native SOL mint (wrapped)
+    pub const SOL_MINT: &'static str = "So11111111111111111111111111111111111111112";
+    /// USDC mint
+    pub const USDC_MINT: &'static str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+    /// USDT mint
+    pub const USDT_MINT: &'static str = "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB";
+
+    /// Create a new Jupiter client with the default API endpoint
+    pub fn new(rpc_url: &str) -> Self {
+        Self::with_api_url(rpc_url, JUPITER_API_URL)
+    }
+
+    /// Create a new Jupiter client with a custom API endpoint
+    pub fn with_api_url(rpc_url: &str, api_url: &str) -> Self {
+        Self::with_config(rpc_url, api_url, JupiterConfig::default())
+            .expect("default Jupiter HTTP client config is valid")
+    }
+
+    /// Create a Jupiter client whose API requests use `config`
+    pub fn with_config(rpc_url: &str, api_url: &str, config: JupiterConfig) -> Result<Self> {
+        let mut client = Self::with_http_client(rpc_url, api_url, config.build_http_client()?);
+        client.request_retries = RetryConfig::new(config.max_retries);
+        client.timeout = config.timeout;
+        Ok(client)
+    }
+
+    /// Create a Jupiter client that sends API requests through `http_client`
+    ///
+    /// Timeouts, proxies and headers come from `http_client`; requests are
+    /// not retried.
+    pub fn with_http_client(rpc_url: &str, api_url: &str, http_client: reqwest::Client) -> Self {
+        Self {
+            rpc_client: Arc::new(RpcClient::new_with_commitment(
+                rpc_url.to_string(),
+                CommitmentConfig::confirmed(),
+            )),
+            api_url: api_url.to_string(),
+            http_client,
+            request_retries: RetryConfig::none(),
+            slippage_policy: SlippagePolicy::default(),
+            max_round_trip_loss_bps: DEFAULT_MAX_ROUND_TRIP_LOSS_BPS,
+            mints: MintRegistry::new(),
+            timeout: DEFAULT_JUPITER_TIMEOUT,
+            max_accounts: None,
+            send_policy: SendPolicy::default(),
+        }
+    }
+
+    /// Send an API request, retrying per the client's config
+    async fn send_request<F>(&self, build: F) -> Result<reqwest::Response>
+    where
+        F: Fn() -> reqwest::RequestBuilder,
+    {
+        retry(&self.request_retries, || {
+            with_timeout(self.timeout, "Jupiter request", async {
+                build().send().await.map_err(|e| {
+                    if e.is_timeout() {
+                        ToolkitError::Timeout(format!("Jupiter request timed out: {}", e))
+                    } else {
+                        ToolkitError::NetworkError(e.to_string())
+                    }
+                })
+            })
+        })
+        .await
+    }
+
+    /// Set the round-trip loss above which [`check_sellability`](Self::check_sellability)
+    /// flags a token
+    pub fn with_max_round_trip_loss_bps(mut self, max_loss_bps: u64) -> Self {
+        self.max_round_trip_loss_bps = max_loss_bps;
+        self
+    }
+
+    /// Set the policy used by [`simple_swap_auto_slippage`](Self::simple_swap_auto_slippage)
+    pub fn with_slippage_policy(mut self, policy: SlippagePolicy) -> Self {
+        self.slippage_policy = policy;
+        self
+    }
+
+    /// Send transactions and read chain state through `client`, which may be
+    /// shared with other helpers
+    pub fn with_rpc_client(mut self, client: Arc<RpcClient>) -> Self {
+        self.rpc_client = client;
+        self
+    }
+
+    /// Resolve mint decimals and symbols through `registry`, e.g. one shared
+    /// with a [`SafetyProtocol`]
+    pub fn with_mint_registry(mut self, registry: MintRegistry) -> Self {
+        self.mints = registry;
+        self
+    }
+
+    /// Fail API requests and RPC calls that take longer than `timeout`
+    /// (30s by default) with [`ToolkitError::Timeout`]
+    ///
+    /// Bounds each request even when the HTTP client passed to
+    /// [`with_http_client`](Self::with_http_client) has no timeout of its own
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Mint decimals and symbols cached by this client
+    pub fn mint_registry(&self) -> &MintRegistry {
+        &self.mints
+    }
+
+    /// Ask for routes using at most `max_accounts` accounts (Jupiter's
+    /// `maxAccounts`, 64 by default)
+    ///
+    /// Lower it when the swap has to share a transaction with other
+    /// instructions, e.g. when CPI-ing Jupiter from your own program; see
+    /// [`validate_swap_fits`](Self::validate_swap_fits)
+    pub fn with_max_accounts(mut self, max_accounts: usize) -> Self {
+        self.max_accounts = Some(max_accounts);
+        self
+    }
+
+    /// Send swaps with `policy`'s preflight settings
+    ///
+    /// Swaps are fetched, preflighted and confirmed at the RPC client's
+    /// commitment. They are versioned transactions, so instead of
+    /// [`send_and_confirm`](crate::transaction::send_and_confirm) they are
+    /// confirmed until their blockhash expires, and a lost confirmation is
+    /// rechecked per [`SwapConfig::confirmation_recovery`] rather than resent
+    pub fn with_send_policy(mut self, policy: SendPolicy) -> Self {
+        self.send_policy = policy;
+        self
+    }
+
+    /// Query string options shared by every quote request
+    fn quote_options(&self) -> String {
+        match self.max_accounts {
+            Some(max_accounts) => format!("&maxAccounts={}", max_accounts),
+            None => String::new(),
+        }
+    }
+
+    /// Estimate the size of a transaction holding `base_instructions` and
+    /// the swap for `quote`, failing if it may exceed
+    /// [`MAX_TRANSACTION_SIZE`]
+    ///
+    /// Returns the estimate in bytes. The swap is assumed to use all the
+    /// accounts its route may ([`with_max_accounts`](Self::with_max_accounts),
+    /// or Jupiter's default of 64), none of them shared with the base
+    /// instructions and without address lookup tables, so an estimate that
+    /// fits is a safe bound
+    pub fn validate_swap_fits(
+        &self,
+        quote: &QuoteResponse,
+        base_instructions: &[Instruction],
+    ) -> Result<usize> {
+        let message = Message::new(base_instructions, None);
+        let mut size = bincode::serialized_size(&Transaction::new_unsigned(message.clone()))
+            .map_err(|e| ToolkitError::Custom(format!("Failed to size transaction: {}", e)))?
+            as usize;
+        if message.header.num_required_signatures == 0 {
+            // The fee payer's key and signature
+            size += 32 + 64;
+        }
+
+        let max_accounts = self.max_accounts.unwrap_or(DEFAULT_JUPITER_MAX_ACCOUNTS);
+        // One key and one index per account
+        size += max_accounts * 33
+            + SWAP_INSTRUCTION_OVERHEAD_BYTES
+            + quote.route_plan.len() * SWAP_BYTES_PER_HOP;
+
+        if size > MAX_TRANSACTION_SIZE {
+            return Err(ToolkitError::TransactionError(format!(
+                "Swap may not fit in one transaction: ~{} bytes with up to {} route accounts \
+                 exceeds the {} byte limit; lower max_accounts",
+                size, max_accounts, MAX_TRANSACTION_SIZE
+            )));
+        }
+        Ok(size)
+    }
+
+    /// Describe a quote in display units, e.g.
+    /// `"1 USDC → 0.00694 SOL (min 0.0069 SOL)"`
+    ///
+    /// Mints that aren't well known are fetched once and cached
+    pub async fn describe_quote(&self, quote: &QuoteResponse) -> Result<String> {
+        let input = quote
+            .input_mint
+            .parse::<Pubkey>()
+            .map_err(|e| ToolkitError::ParseError(format!("Invalid input mint: {}", e)))?;
+        let output = quote
+            .output_mint
+            .parse::<Pubkey>()
+            .map_err(|e| ToolkitError::ParseError(format!("Invalid output mint: {}", e)))?;
+        let mints = [input, output];
+        let prefetch = self.mints.prefetch(&self.rpc_client, &mints);
+        with_timeout(self.timeout, "get_multiple_accounts", prefetch).await?;
+
+        let format = |mint: &Pubkey, amount: u64| {
+            self.mints
+                .format_amount(mint, amount)
+                .ok_or_else(|| ToolkitError::InvalidAccountData(format!("{} is not a mint", mint)))
+        };
+        Ok(format!(
+            "{} → {} (min {})",
+            format(&input, quote.in_amount)?,
+            format(&output, quote.out_amount)?,
+            format(&output, quote.other_amount_threshold)?
+        ))
+    }
+
+    /// Get a quote for swapping tokens
+    ///
+    /// # Arguments
+    /// * `input_mint` - The mint address of the input token
+    /// * `output_mint` - The mint address of the output token
+    /// * `amount` - The amount to swap in smallest units (e.g., lamports for SOL)
+    /// * `slippage_bps` - Slippage tolerance in basis points (50 = 0.5%)
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// let quote = jupiter.get_quote(
+    ///     JupiterClient::USDC_MINT,
+    ///     JupiterClient::SOL_MINT,
+    ///     1_000_000, // 1 USDC
+    ///     50, // 0.5% slippage
+    /// ).await?;
+    /// ```
+    pub async fn get_quote(
+        &self,
+        input_mint: &str,
+        output_mint: &str,
+        amount: u64,
+        slippage_bps: u16,
+    ) -> Result<QuoteResponse> {
+        let url = format!(
+            "{}/quote?inputMint={}&outputMint={}&amount={}&slippageBps={}{}",
+            self.api_url,
+            input_mint,
+            output_mint,
+            amount,
+            slippage_bps,
+            self.quote_options()
+        );
+
+        let response = self.send_request(|| self.http_client.get(&url)).await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(ToolkitError::JupiterError(format!(
+                "Quote request failed: {}",
+                error_text
+            )));
+        }
+
+        let quote: QuoteResponse = response
+            .json()
+            .await
+            .map_err(|e| ToolkitError::ParseError(e.to_string()))?;
+
+        Ok(quote)
+    }
+
+    /// Get a quote for an exact output amount (reverse quote)
+    ///
+    /// Use this when you know how much output you want to receive.
+    pub async fn get_quote_exact_out(
+        &self,
+        input_mint: &str,
+        output_mint: &str,
+        amount: u64,
+        slippage_bps: u16,
+    ) -> Result<QuoteResponse> {
+        let url = format!(
+            "{}/quote?inputMint={}&outputMint={}&amount={}&slippageBps={}&swapMode=ExactOut{}",
+            self.api_url,
+            input_mint,
+            output_mint,
+            amount,
+            slippage_bps,
+            self.quote_options()
+        );
+
+        let response = self.send_request(|| self.http_client.get(&url)).await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(ToolkitError::JupiterError(format!(
+                "Quote request failed: {}",
+                error_text
+            )));
+        }
+
+        let quote: QuoteResponse = response
+            .json()
+            .await
+            .map_err(|e| ToolkitError::ParseError(e.to_string()))?;
+
+        Ok(quote)
+    }
+
+    /// Execute a swap with a previously fetched quote
+    ///
+    /// # Arguments
+    /// * `wallet` - The wallet keypair to sign the transaction
+    /// * `quote` - The quote response from `get_quote`
+    ///
+    /// # Returns
+    /// The transaction signature on success
+    pub async fn swap(&self, wallet: &Keypair, quote: QuoteResponse) -> Result<Signature> {
+        self.swap_with_config(wallet, quote, SwapConfig::default())
+            .await
+    }
+
+    /// Execute a swap with custom configuration
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(
+                input_mint = %quote.input_mint,
+                output_mint = %quote.output_mint,
+                in_amount = quote.in_amount,
+                out_amount = quote.out_amount,
+            )
+        )
+    )]
+    pub async fn swap_with_config(
+        &self,
+        wallet: &Keypair,
+        quote: QuoteResponse,
+        config: SwapConfig,
+    ) -> Result<Signature> {
+        let quote = match config.max_quote_age_slots {
+            Some(max_age_slots) => {
+                let current_slot = with_timeout(self.timeout, "get_slot", async {
+                    Ok(self.rpc_client.get_slot().await?)
+                })
+                .await?;
+                refresh_if_stale(self, quote, current_slot, max_age_slots).await?
+            }
+            None => quote,
+        };
+        config.check_min_out(&quote)?;
+        if let Some(destination) =
+            config.destination_token_account.filter(|_| config.verify_destination)
+        {
+            let rpc = TimeoutRpc::new(Arc::clone(&self.rpc_client), self.timeout);
+            let account = rpc.get_account(&destination).await?;
+            config.check_destination(&quote, account.as_ref())?;
+        }
+
+        // Build swap request
+        let swap_request = config.swap_request(&wallet.pubkey(), quote);
+
+        // Get swap transaction from Jupiter
+        let url = format!("{}/swap", self.api_url);
+        let response = self
+            .send_request(|| self.http_client.post(&url).json(&swap_request))
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(ToolkitError::JupiterError(format!(
+                "Swap request failed: {}",
+                error_text
+            )));
+        }
+
+        let swap_response: SwapResponse = response
+            .json()
+            .await
+            .map_err(|e| ToolkitError::ParseError(e.to_string()))?;
+
+        // Decode and sign transaction
+        let mut versioned_tx: VersionedTransaction =
+            deserialize_base64(&swap_response.swap_transaction)?;
+
+        // Sign the transaction
+        let commitment = self.rpc_client.commitment();
+        let (recent_blockhash, last_valid_block_height) =
+            with_timeout(self.timeout, "get_latest_blockhash", async {
+                Ok(self.rpc_client.get_latest_blockhash_with_commitment(commitment).await?)
+            })
+            .await?;
+
+        versioned_tx
+            .message
+            .set_recent_blockhash(recent_blockhash);
+
+        let signed_tx = VersionedTransaction::try_new(versioned_tx.message, &[wallet])
+            .map_err(|e| ToolkitError::SigningError(e.to_string()))?;
+
+        // Send and wait until confirmed or the blockhash expires, checking
+        // the signature itself if confirmation is lost
+        let send = async {
+            let signature = with_timeout(self.timeout, "send_transaction", async {
+                let config = RpcSendTransactionConfig {
+                    preflight_commitment: Some(commitment.commitment),
+                    ..self.send_policy.send_config()
+                };
+                RpcClient::send_transaction_with_config(&self.rpc_client, &signed_tx, config)
+                    .await
+                    .map_err(|e| ToolkitError::TransactionError(e.to_string()))
+            })
+            .await?;
+            let rpc = TimeoutRpc::new(Arc::clone(&self.rpc_client), self.timeout);
+            confirm_until_block_height(
+                &rpc,
+                &signature,
+                last_valid_block_height,
+                commitment,
+                DEFAULT_CONFIRM_POLL_INTERVAL,
+            )
+            .await?;
+            Ok(signature)
+        };
+        let signature = confirm_swap(
+            &self.rpc_client,
+            signed_tx.signatures[0],
+            send,
+            &config.confirmation_recovery,
+            self.timeout,
+        )
+        .await?;
+
+        info!(signature = %signature, "swap confirmed");
+        Ok(signature)
+    }
+
+    /// Execute a swap with custom configuration and report the output
+    /// actually received
+    ///
+    /// The output is measured on `config.destination_token_account` if
+    /// set, otherwise on the wallet's associated token account for the
+    /// output mint under the mint's token program. SOL that is unwrapped
+    /// into the wallet is measured on its lamports, with the transaction
+    /// fee and rent added back. See [`report_swap`].
+    pub async fn swap_and_report(
+        &self,
+        wallet: &Keypair,
+        quote: QuoteResponse,
+        config: SwapConfig,
+    ) -> Result<SwapOutcome> {
+        let owner = wallet.pubkey();
+        let rpc = TimeoutRpc::new(Arc::clone(&self.rpc_client), self.timeout);
+        let destination = match config.destination_token_account {
+            Some(account) => account,
+            None if quote.output_mint == Self::SOL_MINT && config.wrap_unwrap_sol => owner,
+            None => {
+                let mint = quote.output_mint.parse::<Pubkey>().map_err(|e| {
+                    ToolkitError::ParseError(format!("Invalid output mint: {}", e))
+                })?;
+                let token_program = rpc
+                    .get_account(&mint)
+                    .await?
+                    .ok_or_else(|| {
+                        ToolkitError::InvalidAccountData(format!("{} is not a mint", mint))
+                    })?
+                    .owner;
+                get_associated_token_address_with_program_id(&owner, &mint, &token_program)
+            }
+        };
+
+        let expected = quote.clone();
+        report_swap(&rpc, &destination, &expected, self.swap_with_config(wallet, quote, config))
+            .await
+    }
+
+    /// Simple swap helper - swap tokens with default config
+    ///
+    /// # Arguments
+    /// * `wallet` - The wallet keypair
+    /// * `input_mint` - Input token mint address
+    /// * `output_mint` - Output token mint address  
+    /// * `amount` - Amount to swap in smallest units
+    /// * `slippage_bps` - Slippage tolerance in basis points
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// // Swap 1 USDC for SOL with 0.5% slippage
+    /// let sig = jupiter.simple_swap(
+    ///     &wallet,
+    ///     JupiterClient::USDC_MINT,
+    ///     JupiterClient::SOL_MINT,
+    ///     1_000_000,
+    ///     50,
+    /// ).await?;
+    /// ```
+    pub async fn simple_swap(
+        &self,
+        wallet: &Keypair,
+        input_mint: &str,
+        output_mint: &str,
+        amount: u64,
+        slippage_bps: u16,
+    ) -> Result<Signature> {
         let quote = self
             .get_quote(input_mint, output_mint, amount, slippage_bps)
             .await?;
         self.swap(wallet, quote).await
     }
 
+    /// Swap tokens with slippage derived from the pool conditions
+    ///
+    /// Fetches a probe quote, derives slippage from its price impact and
+    /// route length using the client's [`SlippagePolicy`], then re-quotes
+    /// with that slippage and executes the swap.
+    pub async fn simple_swap_auto_slippage(
+        &self,
+        wallet: &Keypair,
+        input_mint: &str,
+        output_mint: &str,
+        amount: u64,
+    ) -> Result<Signature> {
+        let probe = self
+            .get_quote(input_mint, output_mint, amount, self.slippage_policy.min_bps)
+            .await?;
+        let slippage_bps = self.slippage_policy.recommend(&probe);
+
+        self.simple_swap(wallet, input_mint, output_mint, amount, slippage_bps)
+            .await
+    }
+
+    /// Swap an amount given in display units, e.g. `1.5` USDC
+    ///
+    /// The input mint's decimals are resolved through the client's
+    /// [`MintRegistry`] and `ui_amount` is converted to smallest units with
+    /// integer math. Amounts with more decimal places than the mint
+    /// supports are rejected rather than rounded
+    pub async fn swap_ui(
+        &self,
+        wallet: &Keypair,
+        input_mint: &str,
+        output_mint: &str,
+        ui_amount: f64,
+        slippage_bps: u16,
+    ) -> Result<UiSwapOutcome> {
+        let input = input_mint
+            .parse::<Pubkey>()
+            .map_err(|e| ToolkitError::ParseError(format!("Invalid input mint: {}", e)))?;
+        let output = output_mint
+            .parse::<Pubkey>()
+            .map_err(|e| ToolkitError::ParseError(format!("Invalid output mint: {}", e)))?;
+        let mints = [input, output];
+        let resolve = self.mints.resolve_many(&self.rpc_client, &mints);
+        let resolved = with_timeout(self.timeout, "get_multiple_accounts", resolve).await?;
+        let decimals = resolved
+            .get(&input)
+            .ok_or_else(|| ToolkitError::InvalidAccountData(format!("{} is not a mint", input)))?
+            .decimals;
+
+        let amount = parse_ui_amount(&ui_amount.to_string(), decimals)
+            .map_err(|message| ToolkitError::AmountValidation { message })?;
+        let quote = self
+            .get_quote(input_mint, output_mint, amount, slippage_bps)
+            .await?;
+        let quoted_output = self
+            .mints
+            .format_amount(&output, quote.out_amount)
+            .ok_or_else(|| ToolkitError::InvalidAccountData(format!("{} is not a mint", output)))?;
+
+        let signature = self.swap(wallet, quote).await?;
+        Ok(UiSwapOutcome { signature, quoted_output })
+    }
+
+    /// Check that a token can be sold back to SOL before buying it
+    ///
+    /// See [`check_sellability`](crate::jupiter::check_sellability) for details.
+    pub async fn check_sellability(&self, mint: &str, probe_lamports: u64) -> Result<Sellability> {
+        check_sellability(self, mint, probe_lamports, self.max_round_trip_loss_bps).await
+    }
+
+    /// Swap each of `holdings` into `target_mint` with default settings
+    ///
+    /// See [`rebalance`](crate::jupiter::rebalance) for details.
+    pub async fn rebalance(
+        &self,
+        wallet: &Keypair,
+        holdings: &[(Pubkey, u64)],
+        target_mint: &str,
+    ) -> Result<RebalanceResult> {
+        rebalance(self, wallet, holdings, target_mint, &RebalanceConfig::default()).await
+    }
+
     /// Get the best price for a token pair without executing
     ///
     /// Returns the expected output amount for the given input
@@ -507,6 +1828,19 @@ impl JupiterClient {
         Ok(quote.price_impact_pct)
     }
 
+    /// Get a quote along with its summed route fees and effective rate,
+    /// for comparing multi-hop routes
+    pub async fn get_quote_with_fees(
+        &self,
+        input_mint: &str,
+        output_mint: &str,
+        amount: u64,
+        slippage_bps: u16,
+    ) -> Result<QuoteWithFees> {
+        let quote = self.get_quote(input_mint, output_mint, amount, slippage_bps).await?;
+        Ok(quote.into())
+    }
+
     /// Get the route labels for a swap (which DEXs will be used)
     pub async fn get_route_labels(
         &self,
@@ -521,6 +1855,20 @@ impl JupiterClient {
             .filter_map(|step| step.swap_info.label.clone())
             .collect())
     }
+
+    /// Get the structured route for a swap, one [`RouteHop`] per step
+    ///
+    /// Unlike [`get_route_labels`](Self::get_route_labels) this keeps each
+    /// hop's amounts and fees, for rendering a route diagram
+    pub async fn get_route(
+        &self,
+        input_mint: &str,
+        output_mint: &str,
+        amount: u64,
+    ) -> Result<Vec<RouteHop>> {
+        let quote = self.get_quote(input_mint, output_mint, amount, 50).await?;
+        Ok(quote.route_hops())
+    }
 }
 
 /// Helper function to deserialize string numbers to u64
@@ -566,10 +1914,772 @@ mod tests {
         assert!(config.use_shared_accounts);
     }
 
+    fn quote_with(price_impact_pct: &str, hops: usize) -> QuoteResponse {
+        let step = RoutePlanStep {
+            swap_info: SwapInfo {
+                amm_key: "amm".to_string(),
+                label: Some("Orca".to_string()),
+                input_mint: JupiterClient::USDC_MINT.to_string(),
+                output_mint: JupiterClient::SOL_MINT.to_string(),
+                in_amount: 1_000_000,
+                out_amount: 5_000_000,
+                fee_amount: 100,
+                fee_mint: JupiterClient::USDC_MINT.to_string(),
+            },
+            percent: 100,
+        };
+
+        QuoteResponse {
+            input_mint: JupiterClient::USDC_MINT.to_string(),
+            in_amount: 1_000_000,
+            output_mint: JupiterClient::SOL_MINT.to_string(),
+            out_amount: 5_000_000,
+            other_amount_threshold: 4_975_000,
+            swap_mode: "ExactIn".to_string(),
+            slippage_bps: 50,
+            price_impact_pct: price_impact_pct.to_string(),
+            route_plan: vec![step; hops],
+            context_slot: None,
+            time_taken: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_describe_quote_uses_well_known_mints() {
+        // Both mints are seeded in the registry, so the RPC is never hit
+        let client = JupiterClient::new("http://127.0.0.1:1");
+
+        let description = client.describe_quote(&quote_with("0.1", 1)).await.unwrap();
+
+        assert_eq!(description, "1 USDC → 0.005 SOL (min 0.004975 SOL)");
+    }
+
+    fn multi_hop_quote() -> QuoteResponse {
+        let json = r#"{
+            "inputMint": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+            "inAmount": "1000000",
+            "outputMint": "So11111111111111111111111111111111111111112",
+            "outAmount": "5000000",
+            "otherAmountThreshold": "4975000",
+            "swapMode": "ExactIn",
+            "slippageBps": 50,
+            "priceImpactPct": "0.01",
+            "routePlan": [
+                {
+                    "swapInfo": {
+                        "ammKey": "amm1",
+                        "label": "Orca",
+                        "inputMint": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+                        "outputMint": "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB",
+                        "inAmount": "1000000",
+                        "outAmount": "999000",
+                        "feeAmount": "100",
+                        "feeMint": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"
+                    },
+                    "percent": 100
+                },
+                {
+                    "swapInfo": {
+                        "ammKey": "amm2",
+                        "inputMint": "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB",
+                        "outputMint": "So11111111111111111111111111111111111111112",
+                        "inAmount": "999000",
+                        "outAmount": "5000000",
+                        "feeAmount": "2500",
+                        "feeMint": "So11111111111111111111111111111111111111112"
+                    },
+                    "percent": 100
+                }
+            ]
+        }"#;
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_route_hops_from_quote() {
+        let quote = multi_hop_quote();
+
+        let hops = quote.route_hops();
+        assert_eq!(hops.len(), 2);
+        assert_eq!(
+            hops[0],
+            RouteHop {
+                label: Some("Orca".to_string()),
+                percent: 100,
+                in_amount: 1_000_000,
+                out_amount: 999_000,
+                fee_amount: 100,
+                fee_mint: JupiterClient::USDC_MINT.to_string(),
+                input_mint: JupiterClient::USDC_MINT.to_string(),
+                output_mint: JupiterClient::USDT_MINT.to_string(),
+            }
+        );
+        assert_eq!(hops[1].label, None);
+        assert_eq!(hops[1].input_mint, hops[0].output_mint);
+        assert_eq!(hops[1].out_amount, quote.out_amount);
+        assert_eq!(hops[1].fee_amount, 2_500);
+        assert_eq!(hops[1].fee_mint, JupiterClient::SOL_MINT);
+    }
+
+    #[test]
+    fn test_total_fees_and_effective_rate() {
+        let mut quote = multi_hop_quote();
+        // A second USDC-fee step, so fees of the same mint add up
+        quote.route_plan.push(quote.route_plan[0].clone());
+
+        let fees = QuoteWithFees::from(quote.clone());
+        assert_eq!(fees.total_fees.len(), 2);
+        assert_eq!(fees.total_fees[JupiterClient::USDC_MINT], 200);
+        assert_eq!(fees.total_fees[JupiterClient::SOL_MINT], 2_500);
+        // 5_000_000 out less the 2_500 SOL fee, per 1_000_000 in
+        assert_eq!(fees.effective_rate, Some(4.9975));
+
+        quote.in_amount = 0;
+        assert_eq!(quote.effective_rate(), None);
+    }
+
+    #[test]
+    fn test_min_out_amount_satisfied() {
+        let quote = quote_with("0.1", 1);
+
+        assert!(SwapConfig::default().check_min_out(&quote).is_ok());
+        assert!(SwapConfig::default()
+            .with_min_out_amount(4_975_000)
+            .check_min_out(&quote)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_min_out_amount_rejects_quote() {
+        let quote = quote_with("0.1", 1);
+
+        // Expected output itself is below the floor
+        let err = SwapConfig::default()
+            .with_min_out_amount(6_000_000)
+            .check_min_out(&quote)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("5000000") && err.contains("6000000"));
+
+        // Expected output clears the floor but the slippage threshold does not
+        let err = SwapConfig::default()
+            .with_min_out_amount(4_990_000)
+            .check_min_out(&quote)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("4975000") && err.contains("4990000"));
+    }
+
+    #[test]
+    fn test_recommend_slippage_from_price_impact() {
+        // Deep pool, direct route: stays at the floor
+        assert_eq!(recommend_slippage_bps(&quote_with("0", 1)), 30);
+        // 0.01% impact -> 2 bps on top of the floor
+        assert_eq!(recommend_slippage_bps(&quote_with("0.01", 1)), 32);
+        // 0.5% impact over two hops
+        assert_eq!(recommend_slippage_bps(&quote_with("0.5", 2)), 145);
+        // Illiquid pair is capped at the ceiling
+        assert_eq!(recommend_slippage_bps(&quote_with("5.0", 3)), 500);
+        // Unknown impact is treated conservatively
+        assert_eq!(recommend_slippage_bps(&quote_with("n/a", 1)), 500);
+    }
+
+    #[test]
+    fn test_slippage_policy_bounds() {
+        let policy = SlippagePolicy::new(50, 200).with_per_hop_bps(25);
+
+        assert_eq!(policy.recommend(&quote_with("0", 1)), 50);
+        assert_eq!(policy.recommend(&quote_with("0.1", 3)), 120);
+        assert_eq!(policy.recommend(&quote_with("2.0", 1)), 200);
+    }
+
+    /// Canned quotes keyed by (input, output) with a fixed output ratio.
+    struct MockQuotes {
+        routes: std::collections::HashMap<(String, String), f64>,
+        executed: std::sync::Mutex<Vec<QuoteResponse>>,
+    }
+
+    #[async_trait]
+    impl QuoteProvider for MockQuotes {
+        async fn quote(
+            &self,
+            input_mint: &str,
+            output_mint: &str,
+            amount: u64,
+            _slippage_bps: u16,
+        ) -> Result<QuoteResponse> {
+            let ratio = self
+                .routes
+                .get(&(input_mint.to_string(), output_mint.to_string()))
+                .ok_or_else(|| {
+                    ToolkitError::JupiterError("COULD_NOT_FIND_ANY_ROUTE".to_string())
+                })?;
+
+            let mut quote = quote_with("0.1", 1);
+            quote.input_mint = input_mint.to_string();
+            quote.output_mint = output_mint.to_string();
+            quote.in_amount = amount;
+            quote.out_amount = (amount as f64 * ratio) as u64;
+            Ok(quote)
+        }
+    }
+
+    /// Executes quoted swaps by recording them.
+    #[async_trait]
+    impl SwapProvider for MockQuotes {
+        async fn execute_swap(&self, _wallet: &Keypair, quote: QuoteResponse) -> Result<Signature> {
+            if quote.input_mint == JupiterClient::USDT_MINT {
+                return Err(ToolkitError::TransactionError("swap reverted".to_string()));
+            }
+            self.executed.lock().unwrap().push(quote);
+            Ok(Signature::new_unique())
+        }
+    }
+
+    const TOKEN: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+
+    #[tokio::test]
+    async fn test_rebalance_swaps_holdings_and_skips_dust() {
+        let (usdc, token) = (
+            JupiterClient::USDC_MINT.parse::<Pubkey>().unwrap(),
+            TOKEN.parse::<Pubkey>().unwrap(),
+        );
+        let usdt = JupiterClient::USDT_MINT.parse::<Pubkey>().unwrap();
+        let sol = JupiterClient::SOL_MINT.parse::<Pubkey>().unwrap();
+        let no_route = Pubkey::new_unique();
+        let quotes = mock_quotes(&[
+            (JupiterClient::USDC_MINT, JupiterClient::SOL_MINT, 5.0),
+            (TOKEN, JupiterClient::SOL_MINT, 0.001),
+            (JupiterClient::USDT_MINT, JupiterClient::SOL_MINT, 5.0),
+        ]);
+        let holdings = [
+            (usdc, 2_000_000),
+            (token, 1_000_000),
+            (sol, 50_000_000),
+            (no_route, 10),
+            (usdc, 0),
+            (usdt, 1_000_000),
+        ];
+        let config = RebalanceConfig::default().with_min_output(10_000);
+        let wallet = Keypair::new();
+
+        let result = rebalance(&quotes, &wallet, &holdings, JupiterClient::SOL_MINT, &config)
+            .await
+            .unwrap();
+
+        // One swap per non-dust holding; 1_000 quoted lamports is dust
+        assert_eq!(result.swaps.len(), 1);
+        assert_eq!(result.swaps[0].mint, usdc);
+        assert_eq!(result.swaps[0].out_amount, 10_000_000);
+        assert_eq!(result.signatures().len(), 1);
+        assert_eq!(result.total_output(), 10_000_000);
+        assert_eq!(result.dust, vec![token]);
+        assert_eq!(quotes.executed.lock().unwrap().len(), 1);
+
+        // Quote and swap failures are collected instead of aborting
+        let failed: Vec<Pubkey> = result.failed.iter().map(|(mint, _)| *mint).collect();
+        assert_eq!(failed, vec![no_route, usdt]);
+        assert!(result.failed[1].1.contains("swap reverted"));
+
+        let config = config.with_min_output(0);
+        let result = rebalance(&quotes, &wallet, &holdings, JupiterClient::SOL_MINT, &config)
+            .await
+            .unwrap();
+        assert_eq!(result.swaps.len(), 2);
+        assert!(result.dust.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_report_swap_measures_realized_output() {
+        use crate::testing::MockRpc;
+
+        let (owner, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let destination = Pubkey::new_unique();
+        let rpc = MockRpc::new()
+            .with_account(destination, MockRpc::token_account(&mint, &owner, 100));
+        // Expects 5_000_000 out, at least 4_975_000 after slippage
+        let quote = quote_with("0.1", 1);
+        let deliver = |amount: u64| {
+            let rpc = &rpc;
+            async move {
+                let account = rpc.get_account(&destination).await?.unwrap();
+                let balance = decode_token_account(&account).unwrap().amount;
+                let delivered = MockRpc::token_account(&mint, &owner, balance + amount);
+                rpc.set_account(destination, delivered);
+                Ok(Signature::new_unique())
+            }
+        };
+
+        let outcome = report_swap(&rpc, &destination, &quote, deliver(4_990_000)).await.unwrap();
+        assert_eq!(outcome.expected_out, 5_000_000);
+        assert_eq!(outcome.actual_out, 4_990_000);
+        assert_eq!(outcome.shortfall(), 10_000);
+        assert!(!outcome.below_threshold);
+
+        let outcome = report_swap(&rpc, &destination, &quote, deliver(4_000_000)).await.unwrap();
+        assert_eq!(outcome.actual_out, 4_000_000);
+        assert!(outcome.below_threshold);
+
+        // A destination the swap creates starts from zero
+        let created = Pubkey::new_unique();
+        let swap = async {
+            rpc.set_account(created, MockRpc::token_account(&mint, &owner, 5_000_000));
+            Ok(Signature::new_unique())
+        };
+        let outcome = report_swap(&rpc, &created, &quote, swap).await.unwrap();
+        assert_eq!(outcome.actual_out, 5_000_000);
+        assert_eq!(outcome.shortfall(), 0);
+
+        // A failed swap is reported as an error
+        let failed = async { Err(ToolkitError::TransactionError("reverted".to_string())) };
+        assert!(report_swap(&rpc, &destination, &quote, failed).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_report_swap_adds_fee_and_rent_back_to_sol_output() {
+        use crate::testing::MockRpc;
+
+        let wallet = Pubkey::new_unique();
+        let rpc = MockRpc::new().with_balance(wallet, 1_000_000_000);
+        // Expects 5_000_000 out, at least 4_975_000 after slippage
+        let quote = quote_with("0.1", 1);
+        let signature = Signature::new_unique();
+        // The fee and a created account's rent come out of the unwrapped SOL
+        let costs = 5_000 + 2_039_280;
+        rpc.set_transaction_costs(signature, costs);
+        let swap = async {
+            let lamports = 1_000_000_000 + 4_990_000 - costs;
+            rpc.set_account(wallet, Account::new(lamports, 0, &Pubkey::default()));
+            Ok(signature)
+        };
+
+        let outcome = report_swap(&rpc, &wallet, &quote, swap).await.unwrap();
+        assert_eq!(outcome.actual_out, 4_990_000);
+        assert!(!outcome.below_threshold);
+    }
+
+    #[tokio::test]
+    async fn test_stale_quote_is_refreshed() {
+        let quotes = mock_quotes(&[(JupiterClient::USDC_MINT, JupiterClient::SOL_MINT, 4.0)]);
+        let mut quote = quote_with("0.1", 1);
+        quote.context_slot = Some(100);
+
+        // Within the age limit: kept as is
+        let kept = refresh_if_stale(&quotes, quote.clone(), 120, 50).await.unwrap();
+        assert_eq!(kept.out_amount, 5_000_000);
+        assert_eq!(kept.context_slot, Some(100));
+
+        // Too old: re-quoted for the same input
+        let refreshed = refresh_if_stale(&quotes, quote.clone(), 200, 50).await.unwrap();
+        assert_eq!(refreshed.in_amount, 1_000_000);
+        assert_eq!(refreshed.out_amount, 4_000_000);
+
+        // No context slot: treated as stale
+        quote.context_slot = None;
+        let refreshed = refresh_if_stale(&quotes, quote.clone(), 200, 50).await.unwrap();
+        assert_eq!(refreshed.out_amount, 4_000_000);
+
+        // ExactOut quotes cannot be refreshed with an ExactIn quote
+        quote.swap_mode = "ExactOut".to_string();
+        assert!(refresh_if_stale(&quotes, quote, 200, 50).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_confirm_swap_finds_late_confirmation() {
+        use crate::testing::MockRpc;
+        use solana_sdk::transaction::TransactionError;
+        use solana_transaction_status::{TransactionConfirmationStatus, TransactionStatus};
+
+        let status = |err: Option<TransactionError>| TransactionStatus {
+            slot: 1,
+            confirmations: None,
+            status: err.clone().map_or(Ok(()), Err),
+            err,
+            confirmation_status: Some(TransactionConfirmationStatus::Confirmed),
+        };
+        let recovery = RetryConfig::new(5).with_backoff(Duration::from_millis(20));
+        let timeout = Duration::from_secs(1);
+        let dropped = || async {
+            Err(ToolkitError::Timeout("send_and_confirm_transaction timed out".to_string()))
+        };
+
+        // The swap lands after confirmation was given up on
+        let rpc = MockRpc::new();
+        let landed = Signature::new_unique();
+        let set_later = async {
+            tokio::time::sleep(Duration::from_millis(30)).await;
+            rpc.set_signature_status(landed, status(None));
+        };
+        let (result, ()) = tokio::join!(
+            confirm_swap(&rpc, landed, dropped(), &recovery, timeout),
+            set_later
+        );
+        assert_eq!(result.unwrap(), landed);
+
+        // The swap landed but failed
+        let failed = Signature::new_unique();
+        rpc.set_signature_status(failed, status(Some(TransactionError::AccountNotFound)));
+        let err = confirm_swap(&rpc, failed, dropped(), &recovery, timeout)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolkitError::TransactionError(_)), "{}", err);
+
+        // Never seen: reported as unknown, not as safe to resend
+        let unseen = Signature::new_unique();
+        let err = confirm_swap(&rpc, unseen, dropped(), &recovery, timeout)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolkitError::Timeout(_)), "{}", err);
+        assert!(err.to_string().contains("check it before swapping again"), "{}", err);
+
+        // Other send errors are returned without polling, even for a
+        // signature that confirmed
+        let rejected = async { Err(ToolkitError::TransactionError("blockhash not found".into())) };
+        let err = confirm_swap(&rpc, landed, rejected, &recovery, timeout).await.unwrap_err();
+        assert!(err.to_string().contains("blockhash not found"), "{}", err);
+    }
+
+    fn mock_quotes(routes: &[(&str, &str, f64)]) -> MockQuotes {
+        MockQuotes {
+            routes: routes
+                .iter()
+                .map(|(i, o, r)| ((i.to_string(), o.to_string()), *r))
+                .collect(),
+            executed: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_sellability_normal_token() {
+        let quotes = mock_quotes(&[
+            (JupiterClient::SOL_MINT, TOKEN, 1_000.0),
+            (TOKEN, JupiterClient::SOL_MINT, 0.000_99),
+        ]);
+
+        let result = check_sellability(&quotes, TOKEN, 1_000_000_000, 500).await.unwrap();
+
+        assert!(result.is_sellable());
+        assert_eq!(
+            result,
+            Sellability::Sellable {
+                tokens_out: 1_000_000_000_000,
+                lamports_back: 990_000_000,
+                round_trip_loss_bps: 100,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_sellability_no_return_route() {
+        let quotes = mock_quotes(&[(JupiterClient::SOL_MINT, TOKEN, 1_000.0)]);
+
+        let result = check_sellability(&quotes, TOKEN, 1_000_000_000, 500).await.unwrap();
+
+        assert!(!result.is_sellable());
+        assert!(matches!(result, Sellability::NoSellRoute { tokens_out, .. } if tokens_out > 0));
+    }
+
+    #[tokio::test]
+    async fn test_check_sellability_passes_up_api_failures() {
+        /// Buys at 1:1000 and fails every sell quote with `error`.
+        struct FailingSells(&'static str);
+
+        #[async_trait]
+        impl QuoteProvider for FailingSells {
+            async fn quote(
+                &self,
+                input_mint: &str,
+                output_mint: &str,
+                amount: u64,
+                slippage_bps: u16,
+            ) -> Result<QuoteResponse> {
+                if input_mint != JupiterClient::SOL_MINT {
+                    return Err(ToolkitError::JupiterError(self.0.to_string()));
+                }
+                let buy = mock_quotes(&[(JupiterClient::SOL_MINT, TOKEN, 1_000.0)]);
+                buy.quote(input_mint, output_mint, amount, slippage_bps).await
+            }
+        }
+
+        // A rate limit or outage says nothing about the token
+        for error in [
+            "Quote request failed: 429 Too Many Requests",
+            "Quote request failed: 502 Bad Gateway",
+        ] {
+            let result = check_sellability(&FailingSells(error), TOKEN, 1_000_000_000, 500).await;
+            assert!(matches!(result, Err(ToolkitError::JupiterError(_))), "{:?}", result);
+        }
+
+        // Jupiter's explicit answer that no route exists
+        let no_route = FailingSells(concat!(
+            r#"Quote request failed: {"error":"Could not find any route","#,
+            r#""errorCode":"COULD_NOT_FIND_ANY_ROUTE"}"#
+        ));
+        let result = check_sellability(&no_route, TOKEN, 1_000_000_000, 500).await.unwrap();
+        assert!(matches!(result, Sellability::NoSellRoute { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_check_sellability_excessive_loss() {
+        let quotes = mock_quotes(&[
+            (JupiterClient::SOL_MINT, TOKEN, 1_000.0),
+            (TOKEN, JupiterClient::SOL_MINT, 0.000_5),
+        ]);
+
+        let result = check_sellability(&quotes, TOKEN, 1_000_000_000, 500).await.unwrap();
+
+        assert!(matches!(
+            result,
+            Sellability::ExcessiveLoss { round_trip_loss_bps: 5_000, .. }
+        ));
+    }
+
+    #[test]
+    fn test_destination_token_account_in_swap_request() {
+        use crate::testing::MockRpc;
+
+        let quote = quote_with("0.1", 1);
+        let (wallet, owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let sol = JupiterClient::SOL_MINT.parse::<Pubkey>().unwrap();
+        let usdc = JupiterClient::USDC_MINT.parse::<Pubkey>().unwrap();
+
+        let request = SwapConfig::default().swap_request(&wallet, quote.clone());
+        let request = serde_json::to_value(request).unwrap();
+        assert!(request.get("destinationTokenAccount").is_none());
+
+        let vault = Pubkey::new_unique();
+        let config = SwapConfig::default().with_destination_token_account(vault);
+        let request = serde_json::to_value(config.swap_request(&wallet, quote.clone())).unwrap();
+        assert_eq!(request["destinationTokenAccount"], vault.to_string());
+
+        // The destination must be a token account of the output mint
+        let output_account = MockRpc::token_account(&sol, &owner, 0);
+        assert!(config.check_destination(&quote, Some(&output_account)).is_ok());
+        let input_account = MockRpc::token_account(&usdc, &owner, 0);
+        let err = config.check_destination(&quote, Some(&input_account)).unwrap_err();
+        assert!(err.to_string().contains("not the output mint"), "{}", err);
+        assert!(config.check_destination(&quote, None).is_err());
+
+        let unverified = config.with_verify_destination(false);
+        assert!(unverified.check_destination(&quote, Some(&input_account)).is_ok());
+    }
+
     #[test]
     fn test_swap_config_builder() {
         let config = SwapConfig::with_slippage(100).with_priority_fee(5000);
         assert_eq!(config.slippage_bps, 100);
         assert_eq!(config.priority_fee_micro_lamports, Some(5000));
     }
+
+    #[tokio::test]
+    async fn test_configured_timeout_is_respected() {
+        use tokio::io::AsyncReadExt;
+        use tokio::net::TcpListener;
+
+        // Accepts each request and never answers it
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let api_url = format!("http://{}", listener.local_addr().unwrap());
+        let (request_tx, mut request_rx) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let mut held = Vec::new();
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buf = vec![0u8; 4096];
+                let n = socket.read(&mut buf).await.unwrap();
+                request_tx.send(String::from_utf8_lossy(&buf[..n]).to_string()).unwrap();
+                held.push(socket);
+            }
+        });
+
+        let config = JupiterConfig::default()
+            .with_timeout(Duration::from_millis(200))
+            .with_user_agent("pipkit-test")
+            .with_max_retries(1);
+        let jupiter =
+            JupiterClient::with_config("http://127.0.0.1:8899", &api_url, config).unwrap();
+        let jupiter = JupiterClient {
+            request_retries: jupiter.request_retries.with_backoff(Duration::from_millis(10)),
+            ..jupiter
+        };
+
+        let started = std::time::Instant::now();
+        let result = jupiter
+            .get_quote(JupiterClient::USDC_MINT, JupiterClient::SOL_MINT, 1_000_000, 50)
+            .await;
+
+        assert!(matches!(result, Err(ToolkitError::Timeout(_))));
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        // One attempt plus one retry, both carrying the user agent
+        for _ in 0..2 {
+            let request = request_rx.recv().await.unwrap();
+            assert!(request.to_lowercase().contains("user-agent: pipkit-test"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_swap_ui_converts_with_mint_decimals() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::TcpListener;
+
+        // Records each request and answers that there is no route
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let api_url = format!("http://{}", listener.local_addr().unwrap());
+        let (request_tx, mut request_rx) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buf = vec![0u8; 4096];
+                let n = socket.read(&mut buf).await.unwrap();
+                request_tx.send(String::from_utf8_lossy(&buf[..n]).to_string()).unwrap();
+                let response = "HTTP/1.1 400 Bad Request\r\ncontent-length: 8\r\n\
+                                connection: close\r\n\r\nno route";
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        let token = Pubkey::new_unique();
+        let registry = MintRegistry::new();
+        registry.insert(token, 6, Some("TKN".to_string()));
+        let jupiter = JupiterClient::with_config("http://127.0.0.1:1", &api_url, Default::default())
+            .unwrap()
+            .with_mint_registry(registry);
+        let wallet = Keypair::new();
+        let (input, output) = (token.to_string(), JupiterClient::SOL_MINT);
+
+        let result = jupiter.swap_ui(&wallet, &input, output, 1.5, 50).await;
+        assert!(matches!(result, Err(ToolkitError::JupiterError(_))));
+        let request = request_rx.recv().await.unwrap();
+        assert!(request.contains("amount=1500000&"), "{}", request);
+
+        // Too precise for 6 decimals: rejected before any request
+        let result = jupiter.swap_ui(&wallet, &input, output, 1.0000001, 50).await;
+        assert!(matches!(result, Err(ToolkitError::AmountValidation { .. })));
+        let result = jupiter.swap_ui(&wallet, &input, output, -1.0, 50).await;
+        assert!(matches!(result, Err(ToolkitError::AmountValidation { .. })));
+        assert!(request_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_validate_swap_fits_flags_high_max_accounts() {
+        let payer = Pubkey::new_unique();
+        let base = vec![
+            solana_sdk::system_instruction::transfer(&payer, &Pubkey::new_unique(), 1),
+            Instruction::new_with_bytes(Pubkey::new_unique(), &[0; 32], vec![]),
+        ];
+        let quote = quote_with("0.1", 2);
+
+        // Jupiter's default of 64 accounts can't fit alongside anything
+        let jupiter = JupiterClient::new("http://127.0.0.1:1");
+        assert_eq!(jupiter.quote_options(), "");
+        let err = jupiter.validate_swap_fits(&quote, &base).unwrap_err();
+        assert!(err.to_string().contains("max_accounts"), "{}", err);
+        let err = jupiter.with_max_accounts(40).validate_swap_fits(&quote, &[]).unwrap_err();
+        assert!(matches!(err, ToolkitError::TransactionError(_)), "{}", err);
+
+        // A capped route leaves room for the base instructions
+        let jupiter = JupiterClient::new("http://127.0.0.1:1").with_max_accounts(20);
+        assert_eq!(jupiter.quote_options(), "&maxAccounts=20");
+        let size = jupiter.validate_swap_fits(&quote, &base).unwrap();
+        assert!(size <= MAX_TRANSACTION_SIZE);
+        // More hops and more base instructions only grow the estimate
+        assert!(jupiter.validate_swap_fits(&quote_with("0.1", 4), &base).unwrap() > size);
+        assert!(jupiter.validate_swap_fits(&quote, &base[..1]).unwrap() < size);
+    }
+
+    #[test]
+    fn test_jupiter_config_defaults() {
+        let config = JupiterConfig::default();
+        assert_eq!(config.timeout, DEFAULT_JUPITER_TIMEOUT);
+        assert_eq!(config.max_retries, 0);
+        assert!(config.user_agent.starts_with("solana-pipkit/"));
+        assert!(config.build_http_client().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_safe_swap_blocks_scam_output_mint() {
+        use crate::testing::MockRpc;
+        use solana_sdk::account::Account;
+
+        let (token, scam) = (TOKEN.parse::<Pubkey>().unwrap(), Pubkey::new_unique());
+        let quotes = MockQuotes {
+            routes: [
+                ((JupiterClient::USDC_MINT.to_string(), TOKEN.to_string()), 2.0),
+                ((JupiterClient::USDC_MINT.to_string(), scam.to_string()), 2.0),
+            ]
+            .into_iter()
+            .collect(),
+            executed: Default::default(),
+        };
+        // The "scam" mint is not owned by the token program
+        let rpc = MockRpc::new()
+            .with_account(token, MockRpc::mint_account(6, 1_000))
+            .with_account(scam, Account::new(1_000_000, 82, &Pubkey::new_unique()));
+        let safe = SafeSwap::from_parts(quotes, rpc, SafetyProtocol::new());
+        let wallet = Keypair::new();
+
+        let blocked = safe
+            .swap(&wallet, JupiterClient::USDC_MINT, &scam.to_string(), 1_000_000, 50)
+            .await
+            .unwrap();
+        assert!(!blocked.was_sent());
+        assert!(!blocked.report.approved);
+        assert!(blocked.report.blockers[0].contains("not an SPL token mint"));
+        assert!(safe.swaps.executed.lock().unwrap().is_empty());
+
+        let sent = safe
+            .swap(&wallet, JupiterClient::USDC_MINT, TOKEN, 1_000_000, 50)
+            .await
+            .unwrap();
+        assert!(sent.was_sent());
+        assert!(sent.report.approved);
+        assert_eq!(sent.quote.out_amount, 2_000_000);
+        assert_eq!(safe.swaps.executed.lock().unwrap().len(), 1);
+
+        // High impact needs confirmation, which the callback can refuse
+        let safe = safe.with_max_price_impact_pct(0.15);
+        let declined = safe
+            .swap_with_confirmation(&wallet, JupiterClient::USDC_MINT, TOKEN, 1_000_000, 50, |r| {
+                !r.requires_confirmation
+            })
+            .await
+            .unwrap();
+        assert!(declined.report.requires_confirmation);
+        assert!(!declined.was_sent());
+    }
+
+    #[tokio::test]
+    async fn test_safe_swap_into_token_2022_mint() {
+        use crate::testing::MockRpc;
+        use solana_sdk::clock::Clock;
+
+        let (plain, fee) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut plain_account = MockRpc::mint_account(6, 1_000);
+        plain_account.owner = spl_token_2022::id();
+        let quotes = mock_quotes(&[
+            (JupiterClient::USDC_MINT, &plain.to_string(), 2.0),
+            (JupiterClient::USDC_MINT, &fee.to_string(), 2.0),
+        ]);
+        let rpc = MockRpc::new()
+            .with_account(plain, plain_account)
+            .with_account(fee, MockRpc::transfer_fee_mint_account(6, 100, u64::MAX))
+            .with_clock(&Clock::default());
+        let safe = SafeSwap::from_parts(quotes, rpc, SafetyProtocol::new());
+        let wallet = Keypair::new();
+
+        let sent = safe
+            .swap(&wallet, JupiterClient::USDC_MINT, &plain.to_string(), 1_000_000, 50)
+            .await
+            .unwrap();
+        assert!(sent.was_sent(), "{}", sent.report.summary());
+        assert!(sent.report.warnings.is_empty(), "{:?}", sent.report.warnings);
+
+        // A transfer fee is flagged against the quoted output
+        let sent = safe
+            .swap(&wallet, JupiterClient::USDC_MINT, &fee.to_string(), 1_000_000, 50)
+            .await
+            .unwrap();
+        assert!(sent.was_sent(), "{}", sent.report.summary());
+        let warning = "you receive 1.98 of the quoted 2";
+        assert!(sent.report.warnings.iter().any(|w| w.contains(warning)));
+        assert_eq!(safe.swaps.executed.lock().unwrap().len(), 2);
+    }
 }
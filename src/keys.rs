@@ -0,0 +1,203 @@
+//! Keypair loading.
+//!
+//! Accepts the formats wallets and tools export: the Solana CLI's JSON byte
+//! array, a base58 secret key (as exported by Phantom or Solflare), a file
+//! containing either, or a seed phrase.
+
+use std::path::PathBuf;
+
+use solana_sdk::{
+    derivation_path::DerivationPath,
+    signature::{
+        generate_seed_from_seed_phrase_and_passphrase, keypair_from_seed,
+        keypair_from_seed_and_derivation_path, Keypair,
+    },
+};
+
+use crate::{Result, ToolkitError};
+
+/// Derivation path used by Phantom, Solflare and Backpack for the first
+/// account of a seed phrase.
+pub const WALLET_DERIVATION_PATH: &str = "m/44'/501'/0'/0'";
+
+/// Word counts a BIP39 seed phrase can have.
+const SEED_PHRASE_WORD_COUNTS: [usize; 5] = [12, 15, 18, 21, 24];
+
+/// Load a keypair, detecting the format of `input`.
+///
+/// `input` may be:
+/// - A JSON byte array, as written by `solana-keygen`
+/// - A base58-encoded 64-byte secret key
+/// - A path to a file containing either (a leading `~/` is expanded)
+///
+/// An existing file wins, so a key file named e.g. `id` is loaded rather
+/// than decoded as base58.
+pub fn load_keypair(input: &str) -> Result<Keypair> {
+    let input = input.trim();
+    let path = expand_home(input);
+    if path.is_file() {
+        let contents = std::fs::read_to_string(&path).map_err(|e| {
+            ToolkitError::ParseError(format!("Failed to read {}: {}", path.display(), e))
+        })?;
+        return parse_keypair(contents.trim()).unwrap_or_else(|| {
+            Err(ToolkitError::ParseError(format!(
+                "{} contains neither a JSON byte array nor a base58 secret key",
+                path.display()
+            )))
+        });
+    }
+
+    if let Some(keypair) = parse_keypair(input) {
+        return keypair;
+    }
+
+    Err(ToolkitError::ParseError(
+        "Keypair is not a JSON byte array, a base58 secret key or an existing file".to_string(),
+    ))
+}
+
+/// Derive a keypair from a BIP39 seed phrase.
+///
+/// With `derivation_path` (e.g. [`WALLET_DERIVATION_PATH`]) the key is
+/// derived like browser wallets do; without one it matches
+/// `solana-keygen recover` with no derivation. The phrase's words are not
+/// checked against the BIP39 word list, only counted.
+pub fn load_keypair_from_seed_phrase(
+    phrase: &str,
+    passphrase: &str,
+    derivation_path: Option<&str>,
+) -> Result<Keypair> {
+    let words: Vec<&str> = phrase.split_whitespace().collect();
+    if !SEED_PHRASE_WORD_COUNTS.contains(&words.len()) {
+        return Err(ToolkitError::ParseError(format!(
+            "Seed phrase has {} words, expected 12, 15, 18, 21 or 24",
+            words.len()
+        )));
+    }
+
+    let seed = generate_seed_from_seed_phrase_and_passphrase(&words.join(" "), passphrase);
+    let keypair = match derivation_path {
+        Some(path) => {
+            let path = DerivationPath::from_absolute_path_str(path).map_err(|e| {
+                ToolkitError::ParseError(format!("Invalid derivation path '{}': {}", path, e))
+            })?;
+            keypair_from_seed_and_derivation_path(&seed, Some(path))
+        }
+        None => keypair_from_seed(&seed),
+    };
+    keypair.map_err(|e| ToolkitError::SigningError(format!("Key derivation failed: {}", e)))
+}
+
+/// Parse a JSON byte array or base58 secret key, or `None` if `input` is
+/// neither format.
+fn parse_keypair(input: &str) -> Option<Result<Keypair>> {
+    if input.starts_with('[') {
+        let bytes: Vec<u8> = match serde_json::from_str(input) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                return Some(Err(ToolkitError::ParseError(format!(
+                    "Invalid keypair byte array: {}",
+                    e
+                ))))
+            }
+        };
+        return Some(keypair_from_bytes(&bytes));
+    }
+
+    let bytes = bs58::decode(input).into_vec().ok()?;
+    Some(keypair_from_bytes(&bytes))
+}
+
+/// Build a keypair from 64 secret-then-public bytes. `Keypair::from_bytes`
+/// rejects a public half that doesn't match the secret half.
+fn keypair_from_bytes(bytes: &[u8]) -> Result<Keypair> {
+    if bytes.len() != 64 {
+        return Err(ToolkitError::ParseError(format!(
+            "Keypair must be 64 bytes, got {}",
+            bytes.len()
+        )));
+    }
+    Keypair::from_bytes(bytes)
+        .map_err(|e| ToolkitError::ParseError(format!("Invalid keypair bytes: {}", e)))
+}
+
+/// Expand a leading `~/` to the home directory.
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signer::Signer;
+
+    #[test]
+    fn test_load_keypair_round_trips() {
+        let keypair = Keypair::new();
+
+        let base58 = keypair.to_base58_string();
+        assert_eq!(load_keypair(&base58).unwrap().pubkey(), keypair.pubkey());
+
+        let json = serde_json::to_string(&keypair.to_bytes().to_vec()).unwrap();
+        assert_eq!(load_keypair(&json).unwrap().pubkey(), keypair.pubkey());
+        let padded = format!("  {}\n", json);
+        assert_eq!(load_keypair(&padded).unwrap().pubkey(), keypair.pubkey());
+
+        /// Removes the scratch directory even if an assertion fails.
+        struct TempDir(std::path::PathBuf);
+
+        impl Drop for TempDir {
+            fn drop(&mut self) {
+                let _ = std::fs::remove_dir_all(&self.0);
+            }
+        }
+
+        let dir = TempDir(std::env::temp_dir().join(format!("pipkit-keys-{}", keypair.pubkey())));
+        std::fs::create_dir_all(&dir.0).unwrap();
+
+        let path = dir.0.join("id.json");
+        std::fs::write(&path, &json).unwrap();
+        assert_eq!(load_keypair(path.to_str().unwrap()).unwrap().pubkey(), keypair.pubkey());
+
+        // A file whose name is also valid base58 is still read as a file
+        let path = dir.0.join("keypair");
+        assert!(bs58::decode("keypair").into_vec().is_ok());
+        std::fs::write(&path, &json).unwrap();
+        assert_eq!(load_keypair(path.to_str().unwrap()).unwrap().pubkey(), keypair.pubkey());
+    }
+
+    #[test]
+    fn test_load_keypair_rejects_bad_input() {
+        assert!(load_keypair("[1, 2, 3]").is_err());
+        assert!(load_keypair("not/a/keypair.json").is_err());
+        assert!(load_keypair(&bs58::encode([7u8; 32]).into_string()).is_err());
+
+        // Public half of another keypair
+        let mut bytes = Keypair::new().to_bytes();
+        bytes[32..].copy_from_slice(&Keypair::new().pubkey().to_bytes());
+        let err = load_keypair(&bs58::encode(bytes).into_string()).unwrap_err();
+        assert!(err.to_string().contains("Invalid keypair bytes"), "{}", err);
+    }
+
+    #[test]
+    fn test_load_keypair_from_seed_phrase() {
+        let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+                      abandon abandon about";
+
+        let wallet = load_keypair_from_seed_phrase(phrase, "", Some(WALLET_DERIVATION_PATH));
+        assert_eq!(
+            wallet.unwrap().pubkey().to_string(),
+            "HAgk14JpMQLgt6rVgv7cBQFJWFto5Dqxi472uT3DKpqk"
+        );
+
+        let plain = load_keypair_from_seed_phrase(phrase, "", None).unwrap();
+        let salted = load_keypair_from_seed_phrase(phrase, "secret", None).unwrap();
+        assert_ne!(plain.pubkey(), salted.pubkey());
+
+        assert!(load_keypair_from_seed_phrase("abandon about", "", None).is_err());
+        assert!(load_keypair_from_seed_phrase(phrase, "", Some("44/501")).is_err());
+    }
+}
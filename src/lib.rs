@@ -13,11 +13,17 @@
 //! - **Anchor Helpers**: CPI builders, discriminators, and validation (optional)
 //! - **Jupiter Integration**: DEX aggregator for token swaps (optional)
 //! - **Safety Protocol**: Client-side safety checks to prevent common mistakes
+//! - **Mockable RPC**: `SolanaRpc` trait with an in-memory `MockRpc` for unit tests
+//! - **Custom RPC Headers**: `ClientConfig` for providers that require API key headers
+//! - **Keypair Loading**: JSON byte arrays, base58 secret keys, files and seed phrases
+//! - **Wallet Facade**: One keypair and shared client wired into every helper
 //!
 //! ## Feature Flags
 //!
 //! - `anchor` - Enable Anchor framework helpers and CPI utilities
 //! - `jupiter` - Enable Jupiter DEX integration for token swaps
+//! - `tracing` - Emit `tracing` spans and events for cleanup, batches and swaps
+//! - `cli` - Subcommand handlers for building command-line tools
 //! - `all` - Enable all optional features
 //!
 //! ## Quick Start
@@ -43,22 +49,72 @@
 //! // Validate amount
 //! let validation = AmountValidator::validate_amount(amount, 9, balance);
 //! ```
+//!
+//! ## Offline Building
+//!
+//! Every client that sends transactions also exposes builders that take
+//! explicit inputs and perform no network I/O, for deterministic tests and
+//! offline signing:
+//!
+//! - `*_ix` methods return a single [`Instruction`](solana_sdk::instruction::Instruction)
+//! - `build_*` methods return a `Vec<Instruction>` or a signed `Transaction`
+//!   for a caller-supplied recent blockhash
+//!
+//! ```rust,ignore
+//! let cleaner = RentCleaner::new(rpc_url, payer);
+//! let tx = cleaner.build_close_transaction(&accounts, recent_blockhash)?;
+//! ```
+//!
+//! ## Concurrency
+//!
+//! Clients such as [`AccountUtils`](account_utils::AccountUtils),
+//! [`TokenClient`](token_utils::TokenClient) and `JupiterClient` are
+//! `Send + Sync` and take `&self`, so one instance can be shared across
+//! tasks and threads behind an `Arc`. Their caches (accounts, rent minimums,
+//! mint details, compute usage) sit behind a `RwLock`: concurrent reads
+//! proceed in parallel and a cache miss may be fetched by more than one
+//! task, with the last write winning. The locks are never held across an
+//! `.await`.
+
+#[macro_use]
+mod logging;
+#[macro_use]
+mod macros;
 
 pub mod account_graph;
 pub mod account_utils;
 pub mod anchor_helpers;
 pub mod error;
+pub mod keys;
 pub mod pda;
 pub mod rent_cleaner;
+pub mod retry;
+pub mod rpc;
 pub mod safety;
+mod state_file;
+pub mod testing;
 pub mod token_utils;
 pub mod transaction;
+pub mod wallet;
 
+#[cfg(feature = "cli")]
+pub mod cli;
 #[cfg(feature = "jupiter")]
 pub mod jupiter;
 
 pub use error::{Result, ToolkitError};
 
+// Shared clients must stay usable from multi-threaded runtimes
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<account_utils::AccountUtils>();
+    assert_send_sync::<token_utils::TokenClient>();
+    assert_send_sync::<token_utils::MintRegistry>();
+    assert_send_sync::<transaction::BatchExecutor>();
+    #[cfg(feature = "jupiter")]
+    assert_send_sync::<jupiter::JupiterClient>();
+};
+
 /// Common imports for convenient use.
 ///
 /// Import everything you need with:
@@ -68,26 +124,38 @@ pub use error::{Result, ToolkitError};
 pub mod prelude {
     // Core utilities
     pub use crate::account_utils::*;
+    pub use crate::keys::{load_keypair, load_keypair_from_seed_phrase};
     pub use crate::pda::*;
     pub use crate::token_utils::*;
+    pub use crate::retry::RetryConfig;
+    pub use crate::rpc::{ClientConfig, Network, SolanaRpc, TimeoutRpc, TokenAccountScan};
     pub use crate::{Result, ToolkitError};
 
     // Rent recovery
     pub use crate::rent_cleaner::{
         AccountType, AdvancedCleanupConfig, AdvancedRentCleaner, CleanableAccount,
-        CleanupPriority, CleanupResult, CleanupStrategy, RentCleaner, RentCleanerConfig,
+        CleanupCheckpoint, CleanupPlan, CleanupPriority, CleanupResult, CleanupStrategy,
+        DryRunPlan, MultiWalletCleaner, MultiWalletResult, PlannedAccount, PlannedAction,
+        PlannedBatch, RentCleaner, RentCleanerConfig, StrategyEstimate,
     };
 
     // Transaction utilities
     pub use crate::transaction::{
-        BatchExecutor, BatchResult, ParallelBatchExecutor, TransactionBuilder,
-        TransactionConfig,
+        confirm_stable, confirm_until_block_height, decode_instruction, deserialize_base64,
+        estimate_fee, fee_paid_transaction, fee_tiers, priority_fee_for_budget, serialize_base64,
+        serialize_versioned_base64, send_and_confirm, transfer_sol, BatchExecutor, BatchResult,
+        ConfirmStrategy, DecodedInstruction, ExecutionPolicy, FeeTier, FeeTiers,
+        ParallelBatchExecutor, SafeTransfer, SafeTransferOutcome, SendPolicy, SimulationReport,
+        TransactionBuilder, TransactionConfig, WsConfirmation,
     };
 
+    // Wallet facade
+    pub use crate::wallet::Wallet;
+
     // Account graph
     pub use crate::account_graph::{
         AccountEdge, AccountGraph, AccountGraphBuilder, AccountNode, AccountNodeType,
-        EdgeType,
+        EdgeType, GraphMetrics,
     };
 
     // Anchor helpers
@@ -98,12 +166,14 @@ pub mod prelude {
 
     // Safety protocol
     pub use crate::safety::{
-        AddressComparison, AddressVerification, AddressVerifier,
-        AmountValidation, AmountValidator, AmountWarning, MagnitudeCheck,
-        RiskLevel, SafetyProtocol, SafetyReport, WarningSeverity,
+        AddressComparison, AddressVerification, AddressVerifier, AmountValidation, AmountValidator,
+        AmountWarning, AmountWarningKind, ConfirmationChallenge, Lamports, MagnitudeCheck,
+        RiskLevel, SafetyDecision, SafetyProtocol, SafetyReport, Sol, TokenKind, WarningSeverity,
         LAMPORTS_PER_SOL,
     };
 
     #[cfg(feature = "jupiter")]
     pub use crate::jupiter::*;
+    #[cfg(feature = "jupiter")]
+    pub use crate::rent_cleaner::{ConsolidationResult, DustSwap};
 }
@@ -0,0 +1,70 @@
+//! Internal helper macros.
+
+/// Implement `Display`, case-insensitive `FromStr`, `TryFrom<&str>`,
+/// `TryFrom<String>` and `From<T> for String` for a fieldless enum.
+///
+/// Parsing ignores case, `_` and `-`, so `"burn_and_close"`,
+/// `"BurnAndClose"` and `"burn-and-close"` are equivalent. Pair with
+/// `#[serde(try_from = "String", into = "String")]` for serde support.
+macro_rules! string_enum {
+    ($ty:ident, $what:literal, { $($variant:ident => $name:literal),+ $(,)? }) => {
+        impl $ty {
+            const NAMES: &'static [&'static str] = &[$($name),+];
+        }
+
+        impl std::fmt::Display for $ty {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    $($ty::$variant => write!(f, $name),)+
+                }
+            }
+        }
+
+        impl std::str::FromStr for $ty {
+            type Err = $crate::ToolkitError;
+
+            fn from_str(s: &str) -> $crate::Result<Self> {
+                let normalize = |v: &str| {
+                    v.chars()
+                        .filter(|c| *c != '_' && *c != '-')
+                        .collect::<String>()
+                        .to_lowercase()
+                };
+                let wanted = normalize(s.trim());
+                $(
+                    if wanted == normalize($name) {
+                        return Ok($ty::$variant);
+                    }
+                )+
+                Err($crate::ToolkitError::ParseError(format!(
+                    "Unknown {} '{}', expected one of: {}",
+                    $what,
+                    s,
+                    Self::NAMES.join(", ")
+                )))
+            }
+        }
+
+        impl TryFrom<&str> for $ty {
+            type Error = $crate::ToolkitError;
+
+            fn try_from(s: &str) -> $crate::Result<Self> {
+                s.parse()
+            }
+        }
+
+        impl TryFrom<String> for $ty {
+            type Error = $crate::ToolkitError;
+
+            fn try_from(s: String) -> $crate::Result<Self> {
+                s.parse()
+            }
+        }
+
+        impl From<$ty> for String {
+            fn from(value: $ty) -> String {
+                value.to_string()
+            }
+        }
+    };
+}
@@ -3,7 +3,9 @@
 //! Simplified derivation for common Solana PDAs including
 //! token metadata, associated token accounts, and custom PDAs.
 
-use solana_sdk::pubkey::Pubkey;
+use solana_sdk::pubkey::{Pubkey, MAX_SEEDS, MAX_SEED_LEN};
+
+use crate::{Result, ToolkitError};
 
 // Common program IDs
 pub const TOKEN_METADATA_PROGRAM_ID: Pubkey =
@@ -92,6 +94,34 @@ pub fn validate_pda(
     derived == *pda
 }
 
+/// Assert that `pda` is the canonical PDA for `seeds`, returning its bump.
+///
+/// Errors if `pda` is derived from the seeds with a non-canonical bump, or
+/// is not derived from them at all.
+pub fn assert_canonical(pda: &Pubkey, seeds: &[&[u8]], program_id: &Pubkey) -> Result<u8> {
+    let (canonical, bump) = derive_pda(seeds, program_id);
+    if canonical == *pda {
+        return Ok(bump);
+    }
+
+    let alternate = (0..bump)
+        .rev()
+        .find(|b| derive_pda_with_bump(seeds, program_id, *b) == Some(*pda));
+
+    Err(ToolkitError::InvalidPda(match alternate {
+        Some(b) => format!(
+            "{} uses non-canonical bump {} (canonical is {} at bump {})",
+            pda, b, canonical, bump
+        ),
+        None => format!("{} is not derived from the given seeds (expected {})", pda, canonical),
+    }))
+}
+
+/// Check that an address is off the ed25519 curve, i.e. can be a PDA at all.
+pub fn is_off_curve(pubkey: &Pubkey) -> bool {
+    !pubkey.is_on_curve()
+}
+
 /// PDA builder for complex seed patterns.
 #[derive(Default)]
 pub struct PdaBuilder {
@@ -123,10 +153,44 @@ impl PdaBuilder {
         self
     }
 
+    /// Derive the PDA and its canonical bump.
+    ///
+    /// Panics if the seeds break Solana's limits; use
+    /// [`try_derive`](Self::try_derive) for seeds that aren't fixed.
     pub fn derive(self, program_id: &Pubkey) -> (Pubkey, u8) {
         let seed_refs: Vec<&[u8]> = self.seeds.iter().map(|s| s.as_slice()).collect();
         derive_pda(&seed_refs, program_id)
     }
+
+    /// Derive the PDA, checking the seeds against Solana's limits first.
+    ///
+    /// Each seed may be at most 32 bytes, and there may be at most 15 seeds
+    /// since the bump takes the 16th. Errors with
+    /// [`ToolkitError::InvalidPda`] naming the offending seed.
+    pub fn try_derive(self, program_id: &Pubkey) -> Result<(Pubkey, u8)> {
+        if self.seeds.len() >= MAX_SEEDS {
+            return Err(ToolkitError::InvalidPda(format!(
+                "{} seeds given, but at most {} fit alongside the bump",
+                self.seeds.len(),
+                MAX_SEEDS - 1
+            )));
+        }
+        if let Some((index, seed)) =
+            self.seeds.iter().enumerate().find(|(_, seed)| seed.len() > MAX_SEED_LEN)
+        {
+            return Err(ToolkitError::InvalidPda(format!(
+                "Seed {} is {} bytes, over the {}-byte limit",
+                index,
+                seed.len(),
+                MAX_SEED_LEN
+            )));
+        }
+
+        let seed_refs: Vec<&[u8]> = self.seeds.iter().map(|s| s.as_slice()).collect();
+        Pubkey::try_find_program_address(&seed_refs, program_id).ok_or_else(|| {
+            ToolkitError::InvalidPda("No viable bump found for the seeds".to_string())
+        })
+    }
 }
 
 #[cfg(test)]
@@ -149,6 +213,28 @@ mod tests {
         assert_eq!(bump1, bump2);
     }
 
+    #[test]
+    fn test_pda_builder_checks_seed_limits() {
+        let program_id = Pubkey::new_unique();
+
+        let err = PdaBuilder::new()
+            .add_seed(b"vault")
+            .add_seed(&[7; 33])
+            .try_derive(&program_id)
+            .unwrap_err();
+        assert!(matches!(err, ToolkitError::InvalidPda(_)));
+        assert!(err.to_string().contains("Seed 1 is 33 bytes"), "{}", err);
+
+        let too_many = (0..17).fold(PdaBuilder::new(), |builder, i| builder.add_u64(i));
+        let err = too_many.try_derive(&program_id).unwrap_err();
+        assert!(matches!(err, ToolkitError::InvalidPda(_)));
+        assert!(err.to_string().contains("17 seeds"), "{}", err);
+
+        let most = (0..15).fold(PdaBuilder::new(), |builder, i| builder.add_u64(i));
+        let expected = (0..15).fold(PdaBuilder::new(), |builder, i| builder.add_u64(i));
+        assert_eq!(most.try_derive(&program_id).unwrap(), expected.derive(&program_id));
+    }
+
     #[test]
     fn test_validate_pda() {
         let program_id = Pubkey::new_unique();
@@ -158,4 +244,34 @@ mod tests {
         assert!(validate_pda(&pda, seeds, &program_id));
         assert!(!validate_pda(&Pubkey::new_unique(), seeds, &program_id));
     }
+
+    #[test]
+    fn test_assert_canonical() {
+        let program_id = Pubkey::new_unique();
+        let seeds: &[&[u8]] = &[b"vault"];
+        let (pda, bump) = derive_pda(seeds, &program_id);
+
+        assert_eq!(assert_canonical(&pda, seeds, &program_id).unwrap(), bump);
+        assert!(is_off_curve(&pda));
+
+        // A valid PDA for the same seeds, but with a lower bump
+        let alternate = (0..bump)
+            .rev()
+            .find_map(|b| derive_pda_with_bump(seeds, &program_id, b))
+            .unwrap();
+        let err = assert_canonical(&alternate, seeds, &program_id).unwrap_err();
+        assert!(matches!(err, ToolkitError::InvalidPda(_)));
+        assert!(err.to_string().contains("non-canonical"));
+        assert!(is_off_curve(&alternate));
+
+        let unrelated = Pubkey::new_unique();
+        assert!(assert_canonical(&unrelated, seeds, &program_id).is_err());
+    }
+
+    #[test]
+    fn test_is_off_curve_rejects_wallet() {
+        use solana_sdk::signature::{Keypair, Signer};
+
+        assert!(!is_off_curve(&Keypair::new().pubkey()));
+    }
 }
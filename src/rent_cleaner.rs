@@ -4,19 +4,40 @@
 //! that can be closed to recover rent-exempt SOL. Includes advanced
 //! recovery strategies for different account types and batched operations.
 
-use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::{
+    nonblocking::rpc_client::RpcClient,
+    rpc_config::RpcProgramAccountsConfig,
+    rpc_filter::{Memcmp, RpcFilterType},
+};
 use solana_sdk::{
     commitment_config::CommitmentConfig,
+    compute_budget::ComputeBudgetInstruction,
+    hash::Hash,
     instruction::Instruction,
     message::Message,
     pubkey::Pubkey,
     signature::{Keypair, Signature, Signer},
     transaction::Transaction,
 };
-use spl_token::instruction as token_instruction;
+use serde::{Deserialize, Serialize};
+use spl_token::{instruction as token_instruction, solana_program::program_pack::Pack, state::Mint};
 use std::collections::HashMap;
-
-use crate::{Result, ToolkitError};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+#[cfg(feature = "jupiter")]
+use crate::jupiter::{JupiterClient, SwapProvider};
+use crate::rpc::{
+    empty_token_accounts_by_owner_config, fetch_token_accounts, token_accounts_by_owner_config,
+    zero_amount_filter, Network, SolanaRpc, TimeoutRpc, TokenAccountScan, DEFAULT_RPC_TIMEOUT,
+};
+use crate::safety::{AmountValidator, Lamports};
+use crate::token_utils::{check_rent_destination, decode_token_account};
+use crate::transaction::{
+    ensure_instructions, fee_paid_transaction, send_and_confirm, ExecutionPolicy, SendPolicy,
+};
+use crate::{state_file, Result, ToolkitError};
 
 /// Configuration for rent cleaning operations.
 #[derive(Debug, Clone)]
@@ -29,6 +50,17 @@ pub struct RentCleanerConfig {
     pub close_system_accounts: bool,
     /// Dry run mode (don't actually close accounts)
     pub dry_run: bool,
+    /// Emit progress `info`/`debug` events (off by default). Failures are
+    /// logged as warnings either way
+    pub verbose: bool,
+    /// How token accounts are fetched
+    pub token_account_scan: TokenAccountScan,
+    /// Refuse to close accounts unless the cleaner is known to target a
+    /// test network (devnet, testnet or localnet)
+    pub test_networks_only: bool,
+    /// Account receiving recovered rent instead of the payer, which stays
+    /// the close authority. Must be a system account or not exist yet
+    pub rent_destination: Option<Pubkey>,
 }
 
 impl Default for RentCleanerConfig {
@@ -38,10 +70,30 @@ impl Default for RentCleanerConfig {
             close_token_accounts: true,
             close_system_accounts: true,
             dry_run: false,
+            verbose: false,
+            token_account_scan: TokenAccountScan::ByOwner,
+            test_networks_only: false,
+            rent_destination: None,
         }
     }
 }
 
+/// Fail if `test_networks_only` is set and `network` is not a known test network.
+fn check_test_network(test_networks_only: bool, network: Option<Network>) -> Result<()> {
+    if !test_networks_only || network.is_some_and(|n| n.is_test_network()) {
+        return Ok(());
+    }
+    Err(ToolkitError::ConfigError(match network {
+        Some(network) => format!(
+            "Refusing to close accounts on {}: test_networks_only is set",
+            network
+        ),
+        None => "Refusing to close accounts on an unknown network: test_networks_only is set; \
+                 declare the network with with_network"
+            .to_string(),
+    }))
+}
+
 /// Account information for potential cleanup.
 #[derive(Debug, Clone)]
 pub struct CleanableAccount {
@@ -50,6 +102,61 @@ pub struct CleanableAccount {
     pub account_type: AccountType,
 }
 
+/// An action a cleanup would take, reported by dry runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlannedAction {
+    /// Burn the remaining token balance of an account.
+    Burn {
+        address: Pubkey,
+        mint: Pubkey,
+        amount: u64,
+    },
+    /// Close an account and recover its rent.
+    Close { address: Pubkey, lamports: u64 },
+}
+
+/// Structured description of what a cleanup would do.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DryRunPlan {
+    /// Actions in execution order.
+    pub actions: Vec<PlannedAction>,
+}
+
+impl DryRunPlan {
+    /// Total lamports that would be recovered.
+    pub fn total_lamports(&self) -> u64 {
+        self.actions
+            .iter()
+            .map(|a| match a {
+                PlannedAction::Close { lamports, .. } => *lamports,
+                PlannedAction::Burn { .. } => 0,
+            })
+            .sum()
+    }
+
+    /// Addresses of accounts that would be closed.
+    pub fn accounts_to_close(&self) -> Vec<Pubkey> {
+        self.actions
+            .iter()
+            .filter_map(|a| match a {
+                PlannedAction::Close { address, .. } => Some(*address),
+                PlannedAction::Burn { .. } => None,
+            })
+            .collect()
+    }
+
+    /// Tokens that would be burned (mint -> amount).
+    pub fn tokens_to_burn(&self) -> HashMap<Pubkey, u64> {
+        let mut burned = HashMap::new();
+        for action in &self.actions {
+            if let PlannedAction::Burn { mint, amount, .. } = action {
+                *burned.entry(*mint).or_insert(0) += amount;
+            }
+        }
+        burned
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum AccountType {
     TokenAccount,
@@ -58,140 +165,351 @@ pub enum AccountType {
 }
 
 /// Rent cleaner for recovering SOL from empty accounts.
-pub struct RentCleaner {
-    client: RpcClient,
+pub struct RentCleaner<R = RpcClient> {
+    client: TimeoutRpc<R>,
     payer: Keypair,
     config: RentCleanerConfig,
+    network: Option<Network>,
+    send_policy: SendPolicy,
+    mock_accounts: Option<Vec<ExtendedCleanableAccount>>,
 }
 
 impl RentCleaner {
     /// Create a new RentCleaner instance.
     pub fn new(rpc_url: &str, payer: Keypair) -> Self {
+        Self::with_config(rpc_url, payer, RentCleanerConfig::default())
+    }
+
+    /// Create with custom configuration.
+    pub fn with_config(rpc_url: &str, payer: Keypair, config: RentCleanerConfig) -> Self {
+        let client =
+            RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed());
         Self {
-            client: RpcClient::new_with_commitment(
-                rpc_url.to_string(),
-                CommitmentConfig::confirmed(),
-            ),
+            client: TimeoutRpc::new(client, DEFAULT_RPC_TIMEOUT),
             payer,
-            config: RentCleanerConfig::default(),
+            config,
+            network: Network::from_rpc_url(rpc_url),
+            send_policy: SendPolicy::default(),
+            mock_accounts: None,
         }
     }
 
-    /// Create with custom configuration.
-    pub fn with_config(rpc_url: &str, payer: Keypair, config: RentCleanerConfig) -> Self {
+    /// Create for a well-known network.
+    pub fn new_on(network: Network, payer: Keypair) -> Self {
+        Self::new(network.rpc_url(), payer).with_network(network)
+    }
+}
+
+impl<R: SolanaRpc> RentCleaner<R> {
+    /// Create from an existing RPC backend.
+    pub fn from_client(client: R, payer: Keypair) -> Self {
+        Self::from_client_with_config(client, payer, RentCleanerConfig::default())
+    }
+
+    /// Create from an existing RPC backend with custom configuration.
+    pub fn from_client_with_config(client: R, payer: Keypair, config: RentCleanerConfig) -> Self {
         Self {
-            client: RpcClient::new_with_commitment(
-                rpc_url.to_string(),
-                CommitmentConfig::confirmed(),
-            ),
+            client: TimeoutRpc::new(client, DEFAULT_RPC_TIMEOUT),
             payer,
             config,
+            network: None,
+            send_policy: SendPolicy::default(),
+            mock_accounts: None,
         }
     }
 
+    /// Declare the network the client targets, for `test_networks_only`.
+    ///
+    /// Set automatically by `new_on`, and guessed from the URL by `new`.
+    pub fn with_network(mut self, network: Network) -> Self {
+        self.network = Some(network);
+        self
+    }
+
+    /// Fail RPC calls that take longer than `timeout` (30s by default)
+    /// with [`ToolkitError::Timeout`].
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client.set_timeout(timeout);
+        self
+    }
+
+    /// Send and confirm transactions per `policy`.
+    pub fn with_send_policy(mut self, policy: SendPolicy) -> Self {
+        self.send_policy = policy;
+        self
+    }
+
+    /// Scan `accounts` instead of querying the RPC node.
+    ///
+    /// A test hook: accounts with a zero token balance are the empty ones,
+    /// and a dry run then makes no network calls at all. Sends still go
+    /// through the client.
+    pub fn with_mock_accounts(mut self, accounts: Vec<ExtendedCleanableAccount>) -> Self {
+        self.mock_accounts = Some(accounts);
+        self
+    }
+
     /// Scan for empty token accounts owned by the payer.
+    ///
+    /// With [`TokenAccountScan::ProgramAccounts`] the zero-balance filter
+    /// runs server-side (see [`empty_token_accounts_by_owner_config`]), so
+    /// funded accounts are never downloaded.
     pub async fn find_empty_token_accounts(&self) -> Result<Vec<CleanableAccount>> {
-        let owner = self.payer.pubkey();
-        let token_program = spl_token::id();
+        if let Some(accounts) = &self.mock_accounts {
+            return Ok(accounts
+                .iter()
+                .filter(|a| a.token_balance == 0)
+                .map(|a| a.base.clone())
+                .collect());
+        }
 
-        let accounts = self
-            .client
-            .get_token_accounts_by_owner(&owner, solana_client::rpc_request::TokenAccountsFilter::ProgramId(token_program))
-            .await?;
+        let owner = self.payer.pubkey();
+        let accounts = match self.config.token_account_scan {
+            TokenAccountScan::ProgramAccounts => {
+                self.client
+                    .get_program_accounts(
+                        &spl_token::id(),
+                        empty_token_accounts_by_owner_config(&owner),
+                    )
+                    .await?
+            }
+            scan => fetch_token_accounts(&self.client, &owner, scan).await?,
+        };
 
         let mut cleanable = Vec::new();
 
-        for keyed_account in accounts {
-            let pubkey = keyed_account.pubkey.parse::<Pubkey>().map_err(|e| {
-                ToolkitError::Custom(format!("Failed to parse pubkey: {}", e))
-            })?;
-
+        for (pubkey, account) in accounts {
             // Check if token account has zero balance
-            if let Some(account) = keyed_account.account.decode::<solana_sdk::account::Account>() {
-                // Parse token account data
-                if account.data.len() >= 64 {
-                    let amount = u64::from_le_bytes(
-                        account.data[64..72].try_into().unwrap_or([0; 8])
-                    );
-                    
-                    if amount == 0 {
-                        cleanable.push(CleanableAccount {
-                            address: pubkey,
-                            lamports: account.lamports,
-                            account_type: AccountType::TokenAccount,
-                        });
-                    }
-                }
+            if decode_token_account(&account).is_some_and(|parsed| parsed.amount == 0) {
+                cleanable.push(CleanableAccount {
+                    address: pubkey,
+                    lamports: account.lamports,
+                    account_type: AccountType::TokenAccount,
+                });
             }
         }
 
         Ok(cleanable)
     }
 
-    /// Close empty token accounts and recover rent.
-    pub async fn close_empty_token_accounts(&self) -> Result<u64> {
+    /// Plan closing empty token accounts without sending anything.
+    pub async fn plan_close_empty_token_accounts(&self) -> Result<DryRunPlan> {
         let accounts = self.find_empty_token_accounts().await?;
-        let mut total_recovered: u64 = 0;
 
+        Ok(DryRunPlan {
+            actions: accounts
+                .iter()
+                .map(|account| PlannedAction::Close {
+                    address: account.address,
+                    lamports: account.lamports,
+                })
+                .collect(),
+        })
+    }
+
+    /// Close empty token accounts and recover rent.
+    ///
+    /// This is the main entry point. Each account is closed in its own
+    /// transaction; accounts that fail to close are listed in
+    /// `failed_accounts` and don't stop the rest. In dry-run mode nothing
+    /// is sent and the result carries the plan, as
+    /// [`plan_close_empty_token_accounts`](Self::plan_close_empty_token_accounts)
+    /// returns it.
+    pub async fn clean_empty_accounts(&self) -> Result<CleanupResult> {
+        self.clean_empty_accounts_inner(None).await
+    }
+
+    /// Close empty token accounts with a separate fee payer.
+    ///
+    /// Like [`clean_empty_accounts`](Self::clean_empty_accounts), but
+    /// `fee_payer` (e.g. a relayer) pays every transaction fee while the
+    /// cleaner's payer signs each close as the account owner.
+    pub async fn clean_empty_accounts_with_fee_payer(
+        &self,
+        fee_payer: &Keypair,
+    ) -> Result<CleanupResult> {
+        self.clean_empty_accounts_inner(Some(fee_payer)).await
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(dry_run = self.config.dry_run))
+    )]
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    async fn clean_empty_accounts_inner(
+        &self,
+        fee_payer: Option<&Keypair>,
+    ) -> Result<CleanupResult> {
+        let mut result = CleanupResult::new();
         if self.config.dry_run {
-            for account in &accounts {
-                println!(
-                    "[DRY RUN] Would close token account {} and recover {} lamports",
-                    account.address, account.lamports
+            let plan = self.plan_close_empty_token_accounts().await?;
+            if self.config.verbose {
+                info!(
+                    accounts = plan.actions.len(),
+                    lamports = plan.total_lamports(),
+                    "dry run planned"
                 );
-                total_recovered += account.lamports;
             }
-            return Ok(total_recovered);
+            result.lamports_recovered = plan.total_lamports();
+            result.accounts_closed = plan.accounts_to_close().len();
+            result.dry_run_plan = Some(plan);
+            return Ok(result);
+        }
+        check_test_network(self.config.test_networks_only, self.network)?;
+        if let Some(destination) = &self.config.rent_destination {
+            check_rent_destination(&self.client, destination).await?;
         }
 
+        let accounts = self.find_empty_token_accounts().await?;
+
         for account in accounts {
-            match self.close_token_account(&account.address).await {
-                Ok(lamports) => {
-                    total_recovered += lamports;
-                    println!(
-                        "Closed token account {} - recovered {} lamports",
-                        account.address, lamports
-                    );
+            match self.close_token_account(&account.address, fee_payer).await {
+                Ok((lamports, signature)) => {
+                    result.lamports_recovered += lamports;
+                    result.accounts_closed += 1;
+                    result.signatures.push(signature);
+                    if self.config.verbose {
+                        debug!(account = %account.address, lamports, "closed token account");
+                    }
                 }
                 Err(e) => {
-                    eprintln!("Failed to close {}: {}", account.address, e);
+                    warn!(account = %account.address, error = %e, "failed to close token account");
+                    result.failed_accounts.push((account.address, e.to_string()));
                 }
             }
         }
 
-        Ok(total_recovered)
+        if self.config.verbose {
+            info!(lamports = result.lamports_recovered, "cleanup finished");
+        }
+        Ok(result)
+    }
+
+    /// Close empty token accounts and return the lamports recovered.
+    ///
+    /// Shorthand for [`clean_empty_accounts`](Self::clean_empty_accounts)
+    /// when only the total matters.
+    pub async fn close_empty_token_accounts(&self) -> Result<u64> {
+        Ok(self.clean_empty_accounts().await?.lamports_recovered)
     }
 
-    /// Close a single token account.
-    async fn close_token_account(&self, token_account: &Pubkey) -> Result<u64> {
-        let account_info = self.client.get_account(token_account).await?;
+    /// Close a single token account, returning its lamports and the
+    /// signature of the close.
+    async fn close_token_account(
+        &self,
+        token_account: &Pubkey,
+        fee_payer: Option<&Keypair>,
+    ) -> Result<(u64, Signature)> {
+        let account_info = self
+            .client
+            .get_account(token_account)
+            .await?
+            .ok_or_else(|| ToolkitError::account_not_found(token_account.to_string()))?;
         let lamports = account_info.lamports;
 
-        let instruction = token_instruction::close_account(
+        let instruction = self.close_account_ix(token_account)?;
+        let signature = self.send_transaction(vec![instruction], fee_payer).await?;
+
+        Ok((lamports, signature))
+    }
+
+    /// Build a close instruction for a token account owned by the payer.
+    ///
+    /// Rent goes to `rent_destination` if set, otherwise the payer.
+    /// Performs no network I/O.
+    pub fn close_account_ix(&self, token_account: &Pubkey) -> Result<Instruction> {
+        let payer = self.payer.pubkey();
+        Ok(token_instruction::close_account(
             &spl_token::id(),
             token_account,
-            &self.payer.pubkey(),
-            &self.payer.pubkey(),
+            &self.config.rent_destination.unwrap_or(payer),
+            &payer,
             &[],
-        )?;
+        )?)
+    }
+
+    /// Build close instructions for a set of previously scanned accounts.
+    ///
+    /// Performs no network I/O.
+    pub fn build_close_instructions(
+        &self,
+        accounts: &[CleanableAccount],
+    ) -> Result<Vec<Instruction>> {
+        accounts
+            .iter()
+            .map(|account| self.close_account_ix(&account.address))
+            .collect()
+    }
 
-        self.send_transaction(vec![instruction]).await?;
+    /// Build a signed close transaction for the given accounts.
+    ///
+    /// Performs no network I/O; the caller supplies the recent blockhash.
+    pub fn build_close_transaction(
+        &self,
+        accounts: &[CleanableAccount],
+        recent_blockhash: Hash,
+    ) -> Result<Transaction> {
+        let instructions = self.build_close_instructions(accounts)?;
+        Ok(self.build_transaction(&instructions, recent_blockhash))
+    }
 
-        Ok(lamports)
+    /// Build a close transaction whose fee is paid by `fee_payer`.
+    ///
+    /// Performs no network I/O; the caller supplies the recent blockhash.
+    pub fn build_close_transaction_with_fee_payer(
+        &self,
+        accounts: &[CleanableAccount],
+        fee_payer: &Keypair,
+        recent_blockhash: Hash,
+    ) -> Result<Transaction> {
+        let instructions = self.build_close_instructions(accounts)?;
+        Ok(self.build_transaction_with_fee_payer(&instructions, fee_payer, recent_blockhash))
     }
 
-    /// Send a transaction with the given instructions.
-    async fn send_transaction(&self, instructions: Vec<Instruction>) -> Result<()> {
-        let recent_blockhash = self.client.get_latest_blockhash().await?;
-        
-        let message = Message::new(&instructions, Some(&self.payer.pubkey()));
-        let transaction = Transaction::new(&[&self.payer], message, recent_blockhash);
+    /// Build a transaction signed by the payer.
+    ///
+    /// Performs no network I/O; the caller supplies the recent blockhash.
+    pub fn build_transaction(
+        &self,
+        instructions: &[Instruction],
+        recent_blockhash: Hash,
+    ) -> Transaction {
+        let message = Message::new(instructions, Some(&self.payer.pubkey()));
+        Transaction::new(&[&self.payer], message, recent_blockhash)
+    }
 
-        self.client
-            .send_and_confirm_transaction(&transaction)
-            .await?;
+    /// Build a transaction whose fee is paid by `fee_payer`.
+    ///
+    /// `fee_payer` is the message's fee payer; the cleaner's payer also
+    /// signs, as the owner of the accounts being closed. Performs no
+    /// network I/O; the caller supplies the recent blockhash.
+    pub fn build_transaction_with_fee_payer(
+        &self,
+        instructions: &[Instruction],
+        fee_payer: &Keypair,
+        recent_blockhash: Hash,
+    ) -> Transaction {
+        fee_paid_transaction(instructions, fee_payer, &self.payer, recent_blockhash)
+    }
 
-        Ok(())
+    /// Send a transaction with the given instructions, paid for by
+    /// `fee_payer` if given, otherwise the payer.
+    async fn send_transaction(
+        &self,
+        instructions: Vec<Instruction>,
+        fee_payer: Option<&Keypair>,
+    ) -> Result<Signature> {
+        ensure_instructions(&instructions)?;
+        check_test_network(self.config.test_networks_only, self.network)?;
+        let recent_blockhash = self.client.get_latest_blockhash().await?;
+        let transaction = match fee_payer {
+            Some(fee_payer) => {
+                self.build_transaction_with_fee_payer(&instructions, fee_payer, recent_blockhash)
+            }
+            None => self.build_transaction(&instructions, recent_blockhash),
+        };
+
+        send_and_confirm(&self.client, &transaction, &[], &self.send_policy).await
     }
 
     /// Get total recoverable lamports from empty accounts.
@@ -207,12 +525,49 @@ pub async fn clean_all_empty_accounts(rpc_url: &str, payer: Keypair) -> Result<u
     cleaner.close_empty_token_accounts().await
 }
 
+/// Estimate recoverable lamports for many wallets at once.
+///
+/// Scans each owner's empty token accounts with the server-side zero-balance
+/// filter (see [`empty_token_accounts_by_owner_config`]), running at most
+/// `concurrency` scans at a time. Every owner appears in the result, with 0
+/// if nothing is recoverable. Fails if any scan fails.
+pub async fn estimate_many<R: SolanaRpc + ?Sized>(
+    rpc: &R,
+    owners: &[Pubkey],
+    concurrency: usize,
+) -> Result<HashMap<Pubkey, u64>> {
+    use futures::stream::{self, StreamExt, TryStreamExt};
+
+    stream::iter(owners)
+        .map(|owner| async move {
+            let accounts = rpc
+                .get_program_accounts(&spl_token::id(), empty_token_accounts_by_owner_config(owner))
+                .await?;
+            // Providers that ignore filters return funded accounts too
+            let lamports = accounts
+                .iter()
+                .filter(|(_, account)| {
+                    decode_token_account(account).is_some_and(|token| token.amount == 0)
+                })
+                .map(|(_, account)| account.lamports)
+                .sum();
+            Ok((*owner, lamports))
+        })
+        .buffer_unordered(concurrency.max(1))
+        .try_collect()
+        .await
+}
+
 // ============================================================================
 // Advanced Rent Recovery Strategies
 // ============================================================================
 
 /// Strategy for handling different account types during cleanup.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// Parses from and serializes to snake_case names such as `"burn_and_close"`;
+/// `BurnDustThenClose` carries its threshold as `"burn_dust_then_close:1000"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
 pub enum CleanupStrategy {
     /// Close only completely empty token accounts (balance = 0).
     EmptyOnly,
@@ -222,32 +577,142 @@ pub enum CleanupStrategy {
     BurnAndClose,
     /// Aggregate small balances before closing.
     AggregateAndClose,
+    /// Burn balances below `keep` raw units and close those accounts,
+    /// leaving accounts that hold at least `keep` open.
+    BurnDustThenClose {
+        /// Smallest raw balance worth keeping.
+        keep: u64,
+    },
+}
+
+impl CleanupStrategy {
+    const NAMES: &'static [&'static str] = &[
+        "empty_only",
+        "below_dust_threshold",
+        "burn_and_close",
+        "aggregate_and_close",
+        "burn_dust_then_close:<keep>",
+    ];
+
+    /// Whether accounts closed under this strategy have their balance
+    /// burned first.
+    pub fn burns_balances(&self) -> bool {
+        matches!(self, Self::BurnAndClose | Self::BurnDustThenClose { .. })
+    }
+}
+
+impl std::fmt::Display for CleanupStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EmptyOnly => write!(f, "empty_only"),
+            Self::BelowDustThreshold => write!(f, "below_dust_threshold"),
+            Self::BurnAndClose => write!(f, "burn_and_close"),
+            Self::AggregateAndClose => write!(f, "aggregate_and_close"),
+            Self::BurnDustThenClose { keep } => write!(f, "burn_dust_then_close:{}", keep),
+        }
+    }
+}
+
+impl std::str::FromStr for CleanupStrategy {
+    type Err = ToolkitError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let normalize = |v: &str| {
+            v.chars()
+                .filter(|c| *c != '_' && *c != '-')
+                .collect::<String>()
+                .to_lowercase()
+        };
+        let unknown = || {
+            ToolkitError::ParseError(format!(
+                "Unknown cleanup strategy '{}', expected one of: {}",
+                s,
+                Self::NAMES.join(", ")
+            ))
+        };
+        let (name, keep) = match s.trim().split_once(':') {
+            Some((name, keep)) => (normalize(name), Some(keep.trim())),
+            None => (normalize(s.trim()), None),
+        };
+
+        match (name.as_str(), keep) {
+            ("emptyonly", None) => Ok(Self::EmptyOnly),
+            ("belowdustthreshold", None) => Ok(Self::BelowDustThreshold),
+            ("burnandclose", None) => Ok(Self::BurnAndClose),
+            ("aggregateandclose", None) => Ok(Self::AggregateAndClose),
+            ("burndustthenclose", Some(keep)) => keep
+                .parse()
+                .map(|keep| Self::BurnDustThenClose { keep })
+                .map_err(|_| unknown()),
+            _ => Err(unknown()),
+        }
+    }
+}
+
+impl TryFrom<&str> for CleanupStrategy {
+    type Error = ToolkitError;
+
+    fn try_from(s: &str) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl TryFrom<String> for CleanupStrategy {
+    type Error = ToolkitError;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<CleanupStrategy> for String {
+    fn from(value: CleanupStrategy) -> String {
+        value.to_string()
+    }
 }
 
 /// Priority level for cleanup operations.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+///
+/// Parses from and serializes to snake_case names such as `"high_value"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
 pub enum CleanupPriority {
     /// High value recovery (largest rent amounts first).
     HighValue,
     /// Quick wins (easiest to close first).
     QuickWins,
-    /// By mint (group token accounts by their mint).
+    /// By mint: group token accounts by their mint so that every batch
+    /// holds a single mint, splitting large groups by batch size.
     ByMint,
     /// Oldest first (by rent epoch).
     OldestFirst,
 }
 
+string_enum!(CleanupPriority, "cleanup priority", {
+    HighValue => "high_value",
+    QuickWins => "quick_wins",
+    ByMint => "by_mint",
+    OldestFirst => "oldest_first",
+});
+
 /// Advanced configuration for rent recovery.
 #[derive(Debug, Clone)]
 pub struct AdvancedCleanupConfig {
-    /// Base configuration.
+    /// Base configuration. `base.verbose` also gates the advanced cleanup's
+    /// progress events.
     pub base: RentCleanerConfig,
     /// Cleanup strategy to use.
     pub strategy: CleanupStrategy,
     /// Priority ordering.
     pub priority: CleanupPriority,
-    /// Dust threshold in token smallest units (for BelowDustThreshold).
-    pub dust_threshold: u64,
+    /// Default dust threshold in UI units (for BelowDustThreshold).
+    ///
+    /// A balance is dust when `raw / 10^decimals <= threshold`. Defaults to
+    /// `0.0`, so only empty balances count as dust until a threshold is
+    /// set; a balance of one raw unit does not.
+    pub dust_threshold_ui: f64,
+    /// Per-mint dust thresholds in UI units, overriding `dust_threshold_ui`.
+    pub mint_dust_thresholds: HashMap<Pubkey, f64>,
     /// Maximum accounts to process per batch.
     pub batch_size: usize,
     /// Whether to skip accounts that fail.
@@ -256,19 +721,35 @@ pub struct AdvancedCleanupConfig {
     pub excluded_mints: Vec<Pubkey>,
     /// Only process these mints (if empty, process all).
     pub included_mints: Vec<Pubkey>,
+    /// Priority fee in micro-lamports per compute unit (none by default).
+    pub priority_fee_micro_lamports: Option<u64>,
+    /// Compute unit limit (none by default).
+    pub compute_unit_limit: Option<u32>,
+    /// Slippage for dust swaps in basis points.
+    pub swap_slippage_bps: u16,
+    /// Minimum quoted output for a dust swap to be worth sending.
+    pub min_swap_output: u64,
 }
 
+/// Default minimum quoted output for a dust swap: a couple of base fees.
+pub const DEFAULT_MIN_SWAP_OUTPUT: u64 = 10_000;
+
 impl Default for AdvancedCleanupConfig {
     fn default() -> Self {
         Self {
             base: RentCleanerConfig::default(),
             strategy: CleanupStrategy::EmptyOnly,
             priority: CleanupPriority::HighValue,
-            dust_threshold: 1,
+            dust_threshold_ui: 0.0,
+            mint_dust_thresholds: HashMap::new(),
             batch_size: 10,
             skip_failures: true,
             excluded_mints: Vec::new(),
             included_mints: Vec::new(),
+            priority_fee_micro_lamports: None,
+            compute_unit_limit: None,
+            swap_slippage_bps: 100,
+            min_swap_output: DEFAULT_MIN_SWAP_OUTPUT,
         }
     }
 }
@@ -292,9 +773,34 @@ impl AdvancedCleanupConfig {
         }
     }
 
-    /// Set the dust threshold.
-    pub fn with_dust_threshold(mut self, threshold: u64) -> Self {
-        self.dust_threshold = threshold;
+    /// Set the default dust threshold in UI units.
+    pub fn with_dust_threshold_ui(mut self, threshold: f64) -> Self {
+        self.dust_threshold_ui = threshold;
+        self
+    }
+
+    /// Set the dust threshold in UI units for a specific mint.
+    pub fn with_mint_dust_threshold(mut self, mint: Pubkey, threshold: f64) -> Self {
+        self.mint_dust_thresholds.insert(mint, threshold);
+        self
+    }
+
+    /// Get the dust threshold in UI units for a mint.
+    pub fn dust_threshold_for(&self, mint: Option<&Pubkey>) -> f64 {
+        mint.and_then(|m| self.mint_dust_thresholds.get(m))
+            .copied()
+            .unwrap_or(self.dust_threshold_ui)
+    }
+
+    /// Check whether a raw token balance is dust for the given mint.
+    pub fn is_dust(&self, mint: Option<&Pubkey>, balance: u64, decimals: u8) -> bool {
+        let ui_amount = balance as f64 / 10f64.powi(decimals as i32);
+        ui_amount <= self.dust_threshold_for(mint)
+    }
+
+    /// Emit progress `info`/`debug` events while cleaning up.
+    pub fn with_verbose(mut self, verbose: bool) -> Self {
+        self.base.verbose = verbose;
         self
     }
 
@@ -315,6 +821,30 @@ impl AdvancedCleanupConfig {
         self.included_mints = mints;
         self
     }
+
+    /// Set the priority fee in micro-lamports per compute unit.
+    pub fn with_priority_fee(mut self, micro_lamports: u64) -> Self {
+        self.priority_fee_micro_lamports = Some(micro_lamports);
+        self
+    }
+
+    /// Set the compute unit limit.
+    pub fn with_compute_unit_limit(mut self, units: u32) -> Self {
+        self.compute_unit_limit = Some(units);
+        self
+    }
+
+    /// Set the slippage for dust swaps in basis points.
+    pub fn with_swap_slippage_bps(mut self, bps: u16) -> Self {
+        self.swap_slippage_bps = bps;
+        self
+    }
+
+    /// Skip dust swaps whose quoted output is below `amount`.
+    pub fn with_min_swap_output(mut self, amount: u64) -> Self {
+        self.min_swap_output = amount;
+        self
+    }
 }
 
 /// Result of an advanced cleanup operation.
@@ -326,10 +856,17 @@ pub struct CleanupResult {
     pub accounts_closed: usize,
     /// Accounts that failed to close.
     pub failed_accounts: Vec<(Pubkey, String)>,
+    /// Accounts left open because they changed since the scan, with the
+    /// reason.
+    pub skipped_accounts: Vec<(Pubkey, String)>,
     /// Tokens burned (mint -> amount).
     pub tokens_burned: HashMap<Pubkey, u64>,
     /// Transaction signatures.
     pub signatures: Vec<Signature>,
+    /// Accounts left open under `BurnDustThenClose` (account -> balance).
+    pub kept_balances: HashMap<Pubkey, u64>,
+    /// Planned actions, set only for dry runs.
+    pub dry_run_plan: Option<DryRunPlan>,
 }
 
 impl CleanupResult {
@@ -339,8 +876,11 @@ impl CleanupResult {
             lamports_recovered: 0,
             accounts_closed: 0,
             failed_accounts: Vec::new(),
+            skipped_accounts: Vec::new(),
             tokens_burned: HashMap::new(),
             signatures: Vec::new(),
+            kept_balances: HashMap::new(),
+            dry_run_plan: None,
         }
     }
 
@@ -351,7 +891,27 @@ impl CleanupResult {
 
     /// Get the SOL equivalent recovered (assuming 1 SOL = 1e9 lamports).
     pub fn sol_recovered(&self) -> f64 {
-        self.lamports_recovered as f64 / 1_000_000_000.0
+        Lamports(self.lamports_recovered).as_sol().0
+    }
+
+    /// Add another result's totals, failures, burns and signatures to this one.
+    pub fn merge(&mut self, other: &CleanupResult) {
+        self.lamports_recovered += other.lamports_recovered;
+        self.accounts_closed += other.accounts_closed;
+        self.failed_accounts.extend(other.failed_accounts.iter().cloned());
+        self.skipped_accounts.extend(other.skipped_accounts.iter().cloned());
+        for (mint, amount) in &other.tokens_burned {
+            *self.tokens_burned.entry(*mint).or_insert(0) += amount;
+        }
+        self.signatures.extend(other.signatures.iter().copied());
+        self.kept_balances.extend(other.kept_balances.iter().map(|(k, v)| (*k, *v)));
+
+        if let Some(plan) = &other.dry_run_plan {
+            self.dry_run_plan
+                .get_or_insert_with(DryRunPlan::default)
+                .actions
+                .extend(plan.actions.iter().cloned());
+        }
     }
 }
 
@@ -361,6 +921,66 @@ impl Default for CleanupResult {
     }
 }
 
+impl std::fmt::Display for CleanupResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let heading = if self.dry_run_plan.is_some() {
+            "Cleanup (dry run)"
+        } else {
+            "Cleanup"
+        };
+        writeln!(f, "{}", heading)?;
+        writeln!(f, "  Recovered:       {}", format_sol(self.lamports_recovered))?;
+        writeln!(f, "  Accounts closed: {}", self.accounts_closed)?;
+        writeln!(f, "  Transactions:    {}", self.signatures.len())?;
+
+        if !self.tokens_burned.is_empty() {
+            let mut burned: Vec<_> = self.tokens_burned.iter().collect();
+            burned.sort_by(|a, b| b.1.cmp(a.1));
+            writeln!(f, "  Tokens burned:")?;
+            for (mint, amount) in burned {
+                writeln!(f, "    {}  {}", mint, amount)?;
+            }
+        }
+
+        if !self.kept_balances.is_empty() {
+            writeln!(f, "  Kept open:       {}", self.kept_balances.len())?;
+        }
+
+        if !self.skipped_accounts.is_empty() {
+            writeln!(f, "  Skipped:         {}", self.skipped_accounts.len())?;
+            for (account, reason) in &self.skipped_accounts {
+                writeln!(f, "    {}  {}", account, reason)?;
+            }
+        }
+
+        write!(f, "  Failures:        {}", self.failed_accounts.len())?;
+        for (account, reason) in &self.failed_accounts {
+            write!(f, "\n    {}  {}", account, reason)?;
+        }
+        Ok(())
+    }
+}
+
+/// Lamports as trimmed SOL, e.g. `0.00203928 SOL`.
+fn format_sol(lamports: u64) -> String {
+    AmountValidator::format_amount_with_symbol(lamports, 9, "SOL")
+}
+
+/// Sort accounts by recoverable lamports, highest first.
+fn sort_high_value(accounts: &mut [ExtendedCleanableAccount]) {
+    accounts.sort_by(|a, b| b.base.lamports.cmp(&a.base.lamports));
+}
+
+/// What closing does with a batch that changed on-chain since it was
+/// planned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OnChange {
+    /// Leave out the accounts that can no longer be closed.
+    Skip,
+    /// Send nothing more; the approved plan no longer holds.
+    Abort,
+}
+
 /// Extended cleanable account with additional metadata.
 #[derive(Debug, Clone)]
 pub struct ExtendedCleanableAccount {
@@ -370,131 +990,345 @@ pub struct ExtendedCleanableAccount {
     pub mint: Option<Pubkey>,
     /// Token balance (if token account).
     pub token_balance: u64,
+    /// Decimals of the token mint.
+    pub decimals: u8,
     /// Whether this account can be burned and closed.
     pub can_burn: bool,
 }
 
 /// Advanced rent cleaner with multiple recovery strategies.
-pub struct AdvancedRentCleaner {
-    client: RpcClient,
+pub struct AdvancedRentCleaner<R = RpcClient> {
+    client: TimeoutRpc<R>,
     payer: Keypair,
     config: AdvancedCleanupConfig,
+    network: Option<Network>,
+    policy: ExecutionPolicy,
+    send_policy: SendPolicy,
+    mock_accounts: Option<Vec<ExtendedCleanableAccount>>,
+    expected_network: Option<Network>,
+    genesis_verified: AtomicBool,
 }
 
 impl AdvancedRentCleaner {
     /// Create a new advanced rent cleaner.
     pub fn new(rpc_url: &str, payer: Keypair) -> Self {
+        Self::with_config(rpc_url, payer, AdvancedCleanupConfig::default())
+    }
+
+    /// Create with custom configuration.
+    pub fn with_config(rpc_url: &str, payer: Keypair, config: AdvancedCleanupConfig) -> Self {
+        let client =
+            RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed());
         Self {
-            client: RpcClient::new_with_commitment(
-                rpc_url.to_string(),
-                CommitmentConfig::confirmed(),
-            ),
+            client: TimeoutRpc::new(client, DEFAULT_RPC_TIMEOUT),
             payer,
-            config: AdvancedCleanupConfig::default(),
+            config,
+            network: Network::from_rpc_url(rpc_url),
+            policy: ExecutionPolicy::default(),
+            send_policy: SendPolicy::default(),
+            mock_accounts: None,
+            expected_network: None,
+            genesis_verified: AtomicBool::new(false),
         }
     }
 
-    /// Create with custom configuration.
-    pub fn with_config(rpc_url: &str, payer: Keypair, config: AdvancedCleanupConfig) -> Self {
+    /// Create for a well-known network with custom configuration.
+    pub fn new_on(network: Network, payer: Keypair, config: AdvancedCleanupConfig) -> Self {
+        Self::with_config(network.rpc_url(), payer, config).with_network(network)
+    }
+}
+
+impl<R: SolanaRpc> AdvancedRentCleaner<R> {
+    /// Create from an existing RPC backend with custom configuration.
+    pub fn from_client(client: R, payer: Keypair, config: AdvancedCleanupConfig) -> Self {
         Self {
-            client: RpcClient::new_with_commitment(
-                rpc_url.to_string(),
-                CommitmentConfig::confirmed(),
-            ),
+            client: TimeoutRpc::new(client, DEFAULT_RPC_TIMEOUT),
             payer,
             config,
+            network: None,
+            policy: ExecutionPolicy::default(),
+            send_policy: SendPolicy::default(),
+            mock_accounts: None,
+            expected_network: None,
+            genesis_verified: AtomicBool::new(false),
         }
     }
 
+    /// Declare the network the client targets, for `test_networks_only`.
+    ///
+    /// Set automatically by `new_on`, and guessed from the URL by `new`.
+    pub fn with_network(mut self, network: Network) -> Self {
+        self.network = Some(network);
+        self
+    }
+
+    /// Enforce `policy` before every send. A dry-run policy behaves like
+    /// `dry_run` in the config.
+    pub fn with_execution_policy(mut self, policy: ExecutionPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Send and confirm transactions per `policy`.
+    pub fn with_send_policy(mut self, policy: SendPolicy) -> Self {
+        self.send_policy = policy;
+        self
+    }
+
+    /// Fail RPC calls that take longer than `timeout` (30s by default)
+    /// with [`ToolkitError::Timeout`].
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client.set_timeout(timeout);
+        self
+    }
+
+    /// Refuse to close anything unless the RPC node is on `network`.
+    ///
+    /// Before the first send the node's genesis hash is fetched and
+    /// compared with the cluster's known one, so a mainnet URL pasted in
+    /// place of a devnet one fails before any account is touched.
+    /// `Localnet` accepts any cluster except the public ones.
+    pub fn expected_network(mut self, network: Network) -> Self {
+        self.expected_network = Some(network);
+        self.genesis_verified = AtomicBool::new(false);
+        self
+    }
+
+    /// Scan `accounts` instead of querying the RPC node.
+    ///
+    /// A test hook: the strategy, mint filters and priority still apply to
+    /// the injected accounts, and a dry run then makes no network calls at
+    /// all. Sends still go through the client.
+    pub fn with_mock_accounts(mut self, accounts: Vec<ExtendedCleanableAccount>) -> Self {
+        self.mock_accounts = Some(accounts);
+        self
+    }
+
+    fn is_dry_run(&self) -> bool {
+        self.config.base.dry_run || self.policy.dry_run
+    }
+
     /// Scan for all cleanable accounts with extended information.
+    ///
+    /// With [`TokenAccountScan::ProgramAccounts`] the RPC node applies the
+    /// filters of [`scan_config`](Self::scan_config), so accounts the
+    /// strategy would skip are never downloaded.
     pub async fn scan_accounts(&self) -> Result<Vec<ExtendedCleanableAccount>> {
-        let owner = self.payer.pubkey();
-        let token_program = spl_token::id();
+        Ok(self.scan_and_kept().await?.0)
+    }
 
-        let accounts = self
-            .client
-            .get_token_accounts_by_owner(
-                &owner,
-                solana_client::rpc_request::TokenAccountsFilter::ProgramId(token_program),
-            )
-            .await?;
+    /// Cleanable accounts, plus the balances of accounts left open because
+    /// they hold at least the `BurnDustThenClose` threshold.
+    async fn scan_and_kept(
+        &self,
+    ) -> Result<(Vec<ExtendedCleanableAccount>, HashMap<Pubkey, u64>)> {
+        let (mut cleanable, skipped): (Vec<_>, Vec<_>) = self
+            .scan_token_accounts(true)
+            .await?
+            .into_iter()
+            .partition(|a| self.closes_under(self.config.strategy, a));
 
-        let mut cleanable = Vec::new();
+        // Sort by priority
+        self.sort_by_priority(&mut cleanable);
 
-        for keyed_account in accounts {
-            let pubkey = keyed_account.pubkey.parse::<Pubkey>().map_err(|e| {
-                ToolkitError::Custom(format!("Failed to parse pubkey: {}", e))
-            })?;
-
-            if let Some(account) = keyed_account.account.decode::<solana_sdk::account::Account>() {
-                if account.data.len() >= 72 {
-                    // Parse token account data
-                    let mint = Pubkey::try_from(&account.data[0..32]).ok();
-                    let token_balance = u64::from_le_bytes(
-                        account.data[64..72].try_into().unwrap_or([0; 8]),
-                    );
+        let kept = match self.config.strategy {
+            CleanupStrategy::BurnDustThenClose { keep } => skipped
+                .into_iter()
+                .filter(|a| a.token_balance >= keep)
+                .map(|a| (a.base.address, a.token_balance))
+                .collect(),
+            _ => HashMap::new(),
+        };
 
-                    // Check if we should include this account
-                    if !self.should_include_account(mint.as_ref(), token_balance) {
-                        continue;
-                    }
+        Ok((cleanable, kept))
+    }
 
-                    let can_close = match self.config.strategy {
-                        CleanupStrategy::EmptyOnly => token_balance == 0,
-                        CleanupStrategy::BelowDustThreshold => {
-                            token_balance <= self.config.dust_threshold
-                        }
-                        CleanupStrategy::BurnAndClose => true,
-                        CleanupStrategy::AggregateAndClose => true,
-                    };
-
-                    if can_close {
-                        cleanable.push(ExtendedCleanableAccount {
-                            base: CleanableAccount {
-                                address: pubkey,
-                                lamports: account.lamports,
-                                account_type: AccountType::TokenAccount,
-                            },
-                            mint,
-                            token_balance,
-                            can_burn: token_balance > 0,
-                        });
-                    }
+    /// Estimate the outcome of each cleanup strategy from a single scan.
+    ///
+    /// Covers `EmptyOnly`, `BelowDustThreshold` and `BurnAndClose`, with the
+    /// configured dust thresholds and mint filters applied in memory.
+    pub async fn compare_strategies(&self) -> Result<HashMap<CleanupStrategy, StrategyEstimate>> {
+        let accounts = self.scan_token_accounts(false).await?;
+
+        Ok([
+            CleanupStrategy::EmptyOnly,
+            CleanupStrategy::BelowDustThreshold,
+            CleanupStrategy::BurnAndClose,
+        ]
+        .into_iter()
+        .map(|strategy| {
+            let mut estimate = StrategyEstimate::default();
+            for account in accounts.iter().filter(|a| self.closes_under(strategy, a)) {
+                estimate.accounts_to_close += 1;
+                estimate.lamports_recoverable += account.base.lamports;
+                if account.token_balance > 0 {
+                    estimate.accounts_to_burn += 1;
                 }
             }
-        }
+            (strategy, estimate)
+        })
+        .collect())
+    }
 
-        // Sort by priority
-        self.sort_by_priority(&mut cleanable);
+    /// `getProgramAccounts` config used by [`scan_accounts`](Self::scan_accounts).
+    ///
+    /// Extends [`token_accounts_by_owner_config`] (token account size and
+    /// owner) with server-side filters for the configured strategy: a zero
+    /// amount under `EmptyOnly`, and the mint when exactly one mint is
+    /// included. Other mint and dust filters are applied after the fetch.
+    pub fn scan_config(&self) -> RpcProgramAccountsConfig {
+        let mut config = token_accounts_by_owner_config(&self.payer.pubkey());
+        let filters = config.filters.get_or_insert_with(Vec::new);
+
+        if self.config.strategy == CleanupStrategy::EmptyOnly {
+            filters.push(zero_amount_filter());
+        }
+        if let [mint] = self.config.included_mints.as_slice() {
+            filters.push(RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, mint.as_ref())));
+        }
 
-        Ok(cleanable)
+        config
     }
 
-    /// Check if an account should be included based on config.
-    fn should_include_account(&self, mint: Option<&Pubkey>, _balance: u64) -> bool {
-        if let Some(mint) = mint {
-            // Check excluded mints
-            if self.config.excluded_mints.contains(mint) {
-                return false;
-            }
+    /// Token accounts of the payer, narrowed server-side by the strategy
+    /// when `narrow` is set and the scan uses `getProgramAccounts`.
+    async fn scan_token_accounts(&self, narrow: bool) -> Result<Vec<ExtendedCleanableAccount>> {
+        if let Some(accounts) = &self.mock_accounts {
+            return Ok(accounts.clone());
+        }
 
-            // Check included mints (if specified)
-            if !self.config.included_mints.is_empty()
-                && !self.config.included_mints.contains(mint)
-            {
-                return false;
+        let owner = self.payer.pubkey();
+        let scan = self.config.base.token_account_scan;
+        let accounts = if narrow && scan == TokenAccountScan::ProgramAccounts {
+            self.client
+                .get_program_accounts(&spl_token::id(), self.scan_config())
+                .await?
+        } else {
+            fetch_token_accounts(&self.client, &owner, scan).await?
+        };
+
+        let mut parsed = Vec::new();
+
+        for (pubkey, account) in accounts {
+            if let Some(token) = decode_token_account(&account) {
+                parsed.push((pubkey, account.lamports, Some(token.mint), token.amount));
             }
         }
 
-        true
+        let mints: Vec<Pubkey> = parsed.iter().filter_map(|(_, _, mint, _)| *mint).collect();
+        let decimals = self.fetch_mint_decimals(&mints).await?;
+
+        Ok(parsed
+            .into_iter()
+            .filter_map(|(pubkey, lamports, mint, token_balance)| {
+                let decimals = match mint.and_then(|m| decimals.get(&m).copied()) {
+                    Some(decimals) => decimals,
+                    // Decimals only matter for a balance, which can't be
+                    // measured without them
+                    None if token_balance > 0 => {
+                        warn!(account = %pubkey, "skipping account with unknown mint decimals");
+                        return None;
+                    }
+                    None => 0,
+                };
+                Some(ExtendedCleanableAccount {
+                    base: CleanableAccount {
+                        address: pubkey,
+                        lamports,
+                        account_type: AccountType::TokenAccount,
+                    },
+                    mint,
+                    token_balance,
+                    decimals,
+                    can_burn: token_balance > 0,
+                })
+            })
+            .collect())
     }
 
-    /// Sort accounts by priority.
-    fn sort_by_priority(&self, accounts: &mut [ExtendedCleanableAccount]) {
-        match self.config.priority {
-            CleanupPriority::HighValue => {
-                accounts.sort_by(|a, b| b.base.lamports.cmp(&a.base.lamports));
+    /// Whether `account` would be closed under `strategy`.
+    fn closes_under(&self, strategy: CleanupStrategy, account: &ExtendedCleanableAccount) -> bool {
+        let included = self.should_include_account(
+            strategy,
+            account.mint.as_ref(),
+            account.token_balance,
+            account.decimals,
+        );
+
+        match strategy {
+            CleanupStrategy::EmptyOnly => included && account.token_balance == 0,
+            // Dust filtering already happened in should_include_account
+            CleanupStrategy::BelowDustThreshold => included,
+            CleanupStrategy::BurnAndClose => included,
+            CleanupStrategy::AggregateAndClose => included,
+            // Kept balances already excluded in should_include_account
+            CleanupStrategy::BurnDustThenClose { .. } => included,
+        }
+    }
+
+    /// Fetch decimals for each unique mint.
+    async fn fetch_mint_decimals(&self, mints: &[Pubkey]) -> Result<HashMap<Pubkey, u8>> {
+        let mut unique = mints.to_vec();
+        unique.sort();
+        unique.dedup();
+
+        let mut decimals = HashMap::new();
+        for chunk in unique.chunks(100) {
+            let accounts = self.client.get_multiple_accounts(chunk).await?;
+            for (mint, account) in chunk.iter().zip(accounts) {
+                if let Some(mint_data) = account.and_then(|a| Mint::unpack(&a.data).ok()) {
+                    decimals.insert(*mint, mint_data.decimals);
+                }
+            }
+        }
+
+        Ok(decimals)
+    }
+
+    /// Check if an account should be included under `strategy` based on config.
+    ///
+    /// Under `BelowDustThreshold`, accounts above the mint's dust threshold
+    /// (in UI units, scaled by `decimals`) are excluded, as are accounts
+    /// holding at least `keep` under `BurnDustThenClose`.
+    fn should_include_account(
+        &self,
+        strategy: CleanupStrategy,
+        mint: Option<&Pubkey>,
+        balance: u64,
+        decimals: u8,
+    ) -> bool {
+        if strategy == CleanupStrategy::BelowDustThreshold
+            && !self.config.is_dust(mint, balance, decimals)
+        {
+            return false;
+        }
+        if let CleanupStrategy::BurnDustThenClose { keep } = strategy {
+            if balance >= keep {
+                return false;
+            }
+        }
+
+        if let Some(mint) = mint {
+            // Check excluded mints
+            if self.config.excluded_mints.contains(mint) {
+                return false;
             }
+
+            // Check included mints (if specified)
+            if !self.config.included_mints.is_empty()
+                && !self.config.included_mints.contains(mint)
+            {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Sort accounts by priority.
+    fn sort_by_priority(&self, accounts: &mut [ExtendedCleanableAccount]) {
+        match self.config.priority {
+            CleanupPriority::HighValue => sort_high_value(accounts),
             CleanupPriority::QuickWins => {
                 // Empty accounts first, then by lamports
                 accounts.sort_by(|a, b| {
@@ -515,31 +1349,158 @@ impl AdvancedRentCleaner {
         }
     }
 
+    /// Split `accounts` into transaction batches of at most `batch_size`.
+    ///
+    /// Under [`CleanupPriority::ByMint`] a batch never spans two mints:
+    /// each run of consecutive accounts with the same mint is chunked on
+    /// its own.
+    fn batches<'a>(
+        &self,
+        accounts: &'a [ExtendedCleanableAccount],
+    ) -> Vec<&'a [ExtendedCleanableAccount]> {
+        if self.config.priority != CleanupPriority::ByMint {
+            return accounts.chunks(self.config.batch_size).collect();
+        }
+
+        let mut batches = Vec::new();
+        let mut rest = accounts;
+        while let Some(first) = rest.first() {
+            let run = rest.iter().take_while(|a| a.mint == first.mint).count();
+            let (group, tail) = rest.split_at(run);
+            batches.extend(group.chunks(self.config.batch_size));
+            rest = tail;
+        }
+        batches
+    }
+
     /// Execute the cleanup with the configured strategy.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(wallet = %self.payer.pubkey()))
+    )]
     pub async fn execute_cleanup(&self) -> Result<CleanupResult> {
-        let accounts = self.scan_accounts().await?;
+        let (accounts, kept) = self.scan_and_kept().await?;
+        let mut result = self.cleanup_accounts(&accounts, OnChange::Skip, |_, _| Ok(())).await?;
+        result.kept_balances = kept;
+        Ok(result)
+    }
+
+    /// Close just enough accounts to recover `target_lamports`.
+    ///
+    /// Accounts the configured strategy would close are taken highest value
+    /// first, whatever the configured priority, until their rent meets the
+    /// target; the rest are left open. If every account together falls short
+    /// of the target, all of them are closed. Failed closes are not replaced
+    /// by further accounts, so check `lamports_recovered` in the result.
+    pub async fn recover_until(&self, target_lamports: u64) -> Result<CleanupResult> {
+        let mut accounts = self.scan_accounts().await?;
+        sort_high_value(&mut accounts);
+
+        let mut selected = 0u64;
+        let needed = accounts
+            .iter()
+            .take_while(|account| {
+                let below_target = selected < target_lamports;
+                selected = selected.saturating_add(account.base.lamports);
+                below_target
+            })
+            .count();
+        if self.config.base.verbose {
+            debug!(needed, target_lamports, "selected accounts for partial cleanup");
+        }
+
+        self.cleanup_accounts(&accounts[..needed], OnChange::Skip, |_, _| Ok(())).await
+    }
+
+    /// Execute the cleanup, recording closed accounts in a checkpoint file.
+    ///
+    /// The checkpoint is JSON mapping each closed account to the signature
+    /// that closed it, and is rewritten after every confirmed transaction.
+    /// If `checkpoint_path` already exists, accounts it lists are skipped,
+    /// so a run that died part-way resumes where it stopped. The file is
+    /// kept after a complete run; delete it to start fresh.
+    pub async fn execute_cleanup_with_checkpoint(
+        &self,
+        checkpoint_path: impl AsRef<Path>,
+    ) -> Result<CleanupResult> {
+        let path = checkpoint_path.as_ref();
+        let wallet = self.payer.pubkey();
+        let mut checkpoint = CleanupCheckpoint::load_or_new(path, &wallet)?;
+
+        let accounts: Vec<ExtendedCleanableAccount> = self
+            .scan_accounts()
+            .await?
+            .into_iter()
+            .filter(|a| !checkpoint.is_closed(&a.base.address))
+            .collect();
+        if self.config.base.verbose {
+            info!(
+                already_closed = checkpoint.closed.len(),
+                remaining = accounts.len(),
+                "resuming cleanup from checkpoint"
+            );
+        }
+
+        self.cleanup_accounts(&accounts, OnChange::Skip, |closed, signature| {
+            checkpoint.record(closed, signature);
+            checkpoint.save(path)
+        })
+        .await
+    }
+
+    /// Close `accounts` in batches, reporting each confirmed transaction to
+    /// `on_closed` with the accounts it closed.
+    ///
+    /// Each batch is rechecked just before it is sent; `on_change` decides
+    /// what happens when that changes it.
+    async fn cleanup_accounts<F>(
+        &self,
+        accounts: &[ExtendedCleanableAccount],
+        on_change: OnChange,
+        mut on_closed: F,
+    ) -> Result<CleanupResult>
+    where
+        F: FnMut(&[Pubkey], &Signature) -> Result<()>,
+    {
         let mut result = CleanupResult::new();
 
-        if self.config.base.dry_run {
-            for account in &accounts {
-                println!(
-                    "[DRY RUN] Would close {} (mint: {:?}, balance: {}, rent: {} lamports)",
-                    account.base.address,
-                    account.mint,
-                    account.token_balance,
-                    account.base.lamports
-                );
-                result.lamports_recovered += account.base.lamports;
-                result.accounts_closed += 1;
+        if self.is_dry_run() {
+            let plan = self.build_plan(accounts);
+            if self.config.base.verbose {
+                info!(actions = plan.actions.len(), "dry run planned");
             }
+            result.lamports_recovered = plan.total_lamports();
+            result.accounts_closed = plan.accounts_to_close().len();
+            result.tokens_burned = plan.tokens_to_burn();
+            result.dry_run_plan = Some(plan);
             return Ok(result);
         }
+        check_test_network(self.config.base.test_networks_only, self.network)?;
+        self.check_expected_network().await?;
+        if let Some(destination) = &self.config.base.rent_destination {
+            check_rent_destination(&self.client, destination).await?;
+        }
 
         // Process in batches
-        for batch in accounts.chunks(self.config.batch_size) {
+        for (index, planned) in self.batches(accounts).into_iter().enumerate() {
+            let batch = self.recheck_balances(planned, &mut result).await?;
+            let changed = batch.len() != planned.len()
+                || batch.iter().zip(planned).any(|(a, b)| a.token_balance != b.token_balance);
+            if changed && on_change == OnChange::Abort {
+                return Err(ToolkitError::ConfigError(format!(
+                    "Batch {} changed on-chain since the plan was approved",
+                    index
+                )));
+            }
+            if batch.is_empty() {
+                continue;
+            }
+            let batch = batch.as_slice();
             let batch_result = self.process_batch(batch).await;
             match batch_result {
                 Ok(sig) => {
+                    let closed: Vec<Pubkey> = batch.iter().map(|a| a.base.address).collect();
+                    on_closed(&closed, &sig)?;
                     for account in batch {
                         result.lamports_recovered += account.base.lamports;
                         result.accounts_closed += 1;
@@ -550,16 +1511,21 @@ impl AdvancedRentCleaner {
                             }
                         }
                     }
+                    if self.config.base.verbose {
+                        debug!(signature = %sig, accounts = batch.len(), "cleanup batch sent");
+                    }
                     result.signatures.push(sig);
                 }
                 Err(e) => {
                     if !self.config.skip_failures {
                         return Err(e);
                     }
+                    warn!(error = %e, "cleanup batch failed, closing accounts individually");
                     // Process individually on batch failure
                     for account in batch {
                         match self.close_single_account(account).await {
                             Ok(sig) => {
+                                on_closed(&[account.base.address], &sig)?;
                                 result.lamports_recovered += account.base.lamports;
                                 result.accounts_closed += 1;
                                 if account.token_balance > 0 {
@@ -571,6 +1537,11 @@ impl AdvancedRentCleaner {
                                 result.signatures.push(sig);
                             }
                             Err(e) => {
+                                warn!(
+                                    account = %account.base.address,
+                                    error = %e,
+                                    "failed to close account"
+                                );
                                 result
                                     .failed_accounts
                                     .push((account.base.address, e.to_string()));
@@ -581,17 +1552,254 @@ impl AdvancedRentCleaner {
             }
         }
 
+        if self.config.base.verbose {
+            info!(
+                accounts_closed = result.accounts_closed,
+                lamports = result.lamports_recovered,
+                failed = result.failed_accounts.len(),
+                "cleanup finished"
+            );
+        }
         Ok(result)
     }
 
+    /// Re-read the balances of `batch` just before closing it.
+    ///
+    /// Strategies that don't burn can only close accounts whose balance is
+    /// still what the scan saw, so an account that gained tokens or was
+    /// closed in the meantime is recorded in `skipped_accounts` instead of
+    /// being sent in a doomed close. An account whose balance dropped is
+    /// kept with the balance it has now. Burning strategies return the batch
+    /// unchanged.
+    async fn recheck_balances(
+        &self,
+        batch: &[ExtendedCleanableAccount],
+        result: &mut CleanupResult,
+    ) -> Result<Vec<ExtendedCleanableAccount>> {
+        if self.config.strategy.burns_balances() {
+            return Ok(batch.to_vec());
+        }
+
+        let addresses: Vec<Pubkey> = batch.iter().map(|a| a.base.address).collect();
+        let current = self.client.get_multiple_accounts(&addresses).await?;
+        let mut still_closeable = Vec::with_capacity(batch.len());
+        for (account, current) in batch.iter().zip(current) {
+            let balance = current.as_ref().and_then(decode_token_account).map(|t| t.amount);
+            let reason = match balance {
+                None => "account no longer exists".to_string(),
+                Some(balance) if balance > account.token_balance => format!(
+                    "account no longer empty: balance is {}, scanned {}",
+                    balance, account.token_balance
+                ),
+                Some(balance) => {
+                    still_closeable.push(ExtendedCleanableAccount {
+                        token_balance: balance,
+                        ..account.clone()
+                    });
+                    continue;
+                }
+            };
+            warn!(account = %account.base.address, reason = %reason, "skipping close");
+            result.skipped_accounts.push((account.base.address, reason));
+        }
+
+        Ok(still_closeable)
+    }
+
+    /// Plan the cleanup without sending anything.
+    pub async fn plan_cleanup(&self) -> Result<DryRunPlan> {
+        let accounts = self.scan_accounts().await?;
+        Ok(self.build_plan(&accounts))
+    }
+
+    /// Build the plan for a set of scanned accounts.
+    ///
+    /// Performs no network I/O.
+    pub fn build_plan(&self, accounts: &[ExtendedCleanableAccount]) -> DryRunPlan {
+        let mut actions = Vec::new();
+
+        for account in accounts {
+            if account.token_balance > 0 && self.config.strategy.burns_balances() {
+                if let Some(mint) = account.mint {
+                    actions.push(PlannedAction::Burn {
+                        address: account.base.address,
+                        mint,
+                        amount: account.token_balance,
+                    });
+                }
+            }
+
+            actions.push(PlannedAction::Close {
+                address: account.base.address,
+                lamports: account.base.lamports,
+            });
+        }
+
+        DryRunPlan { actions }
+    }
+
+    /// Scan and describe the cleanup as a serializable [`CleanupPlan`].
+    ///
+    /// The plan can be stored, reviewed and approved out of band, then run
+    /// with [`execute_plan`](Self::execute_plan).
+    pub async fn build_cleanup_plan(&self) -> Result<CleanupPlan> {
+        let accounts = self.scan_accounts().await?;
+
+        let batches = self
+            .batches(&accounts)
+            .into_iter()
+            .map(|batch| {
+                Ok(PlannedBatch {
+                    accounts: batch.iter().map(|a| a.base.address.to_string()).collect(),
+                    instruction_hash: self.instruction_hash(batch)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(CleanupPlan {
+            wallet: self.payer.pubkey().to_string(),
+            strategy: self.config.strategy,
+            estimated_recovery: accounts.iter().map(|a| a.base.lamports).sum(),
+            accounts: accounts
+                .iter()
+                .map(|a| PlannedAccount {
+                    address: a.base.address.to_string(),
+                    mint: a.mint.map(|m| m.to_string()),
+                    lamports: a.base.lamports,
+                    token_balance: a.token_balance,
+                    decimals: a.decimals,
+                })
+                .collect(),
+            batches,
+        })
+    }
+
+    /// Execute an approved [`CleanupPlan`].
+    ///
+    /// Fails without sending anything if the plan is for another wallet, if
+    /// any planned account changed on-chain (closed, new balance, lamports,
+    /// mint or owner), or if the cleaner's configuration would no longer
+    /// produce the planned instructions.
+    ///
+    /// Each batch is rechecked before it is sent, and execution stops with
+    /// an error at the first one that changed since.
+    pub async fn execute_plan(&self, plan: &CleanupPlan) -> Result<CleanupResult> {
+        let wallet = self.payer.pubkey();
+        if plan.wallet != wallet.to_string() {
+            return Err(ToolkitError::ConfigError(format!(
+                "Plan is for wallet {}, not {}",
+                plan.wallet, wallet
+            )));
+        }
+
+        let accounts = plan
+            .accounts
+            .iter()
+            .map(PlannedAccount::to_cleanable)
+            .collect::<Result<Vec<_>>>()?;
+        self.verify_on_chain(&accounts).await?;
+
+        let batches = self.batches(&accounts);
+        if batches.len() != plan.batches.len() {
+            return Err(ToolkitError::ConfigError(format!(
+                "Plan has {} batches but the cleaner would send {}",
+                plan.batches.len(),
+                batches.len()
+            )));
+        }
+        for (index, (batch, planned)) in batches.iter().zip(&plan.batches).enumerate() {
+            if self.instruction_hash(batch)? != planned.instruction_hash {
+                return Err(ToolkitError::ConfigError(format!(
+                    "Batch {} instructions differ from the approved plan",
+                    index
+                )));
+            }
+        }
+
+        if self.config.base.verbose {
+            info!(accounts = accounts.len(), "executing approved cleanup plan");
+        }
+        self.cleanup_accounts(&accounts, OnChange::Abort, |_, _| Ok(())).await
+    }
+
+    /// Hash of the cleanup instructions for one batch.
+    fn instruction_hash(&self, batch: &[ExtendedCleanableAccount]) -> Result<String> {
+        let instructions = self.build_cleanup_instructions(batch)?;
+        let bytes = bincode::serialize(&instructions)
+            .map_err(|e| ToolkitError::Custom(format!("Failed to encode instructions: {}", e)))?;
+        Ok(solana_sdk::hash::hash(&bytes).to_string())
+    }
+
+    /// Check that every account still matches its planned state.
+    async fn verify_on_chain(&self, accounts: &[ExtendedCleanableAccount]) -> Result<()> {
+        let wallet = self.payer.pubkey();
+
+        for chunk in accounts.chunks(100) {
+            let addresses: Vec<Pubkey> = chunk.iter().map(|a| a.base.address).collect();
+            let current = self.client.get_multiple_accounts(&addresses).await?;
+
+            for (planned, account) in chunk.iter().zip(current) {
+                let address = planned.base.address;
+                let account = account.ok_or_else(|| {
+                    ToolkitError::InvalidAccountData(format!("{} no longer exists", address))
+                })?;
+                let matches = account.owner == spl_token::id()
+                    && account.lamports == planned.base.lamports
+                    && decode_token_account(&account).is_some_and(|token| {
+                        Some(token.mint) == planned.mint
+                            && token.owner == wallet
+                            && token.amount == planned.token_balance
+                    });
+                if !matches {
+                    return Err(ToolkitError::InvalidAccountData(format!(
+                        "{} changed since the plan was built",
+                        address
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     /// Process a batch of accounts.
     async fn process_batch(&self, accounts: &[ExtendedCleanableAccount]) -> Result<Signature> {
+        let instructions = self.build_cleanup_instructions(accounts)?;
+        self.send_transaction(instructions).await
+    }
+
+    /// Close a single account.
+    async fn close_single_account(
+        &self,
+        account: &ExtendedCleanableAccount,
+    ) -> Result<Signature> {
+        let instructions = self.build_cleanup_instructions(std::slice::from_ref(account))?;
+        self.send_transaction(instructions).await
+    }
+
+    /// Build burn (if the strategy requires it) and close instructions for
+    /// a set of scanned accounts, preceded by any configured compute-budget
+    /// instructions.
+    ///
+    /// Performs no network I/O.
+    pub fn build_cleanup_instructions(
+        &self,
+        accounts: &[ExtendedCleanableAccount],
+    ) -> Result<Vec<Instruction>> {
         let mut instructions = Vec::new();
         let payer_pubkey = self.payer.pubkey();
 
+        if let Some(fee) = self.config.priority_fee_micro_lamports {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_price(fee));
+        }
+
+        if let Some(units) = self.config.compute_unit_limit {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(units));
+        }
+
         for account in accounts {
             // Burn tokens if needed
-            if account.token_balance > 0 && self.config.strategy == CleanupStrategy::BurnAndClose {
+            if account.token_balance > 0 && self.config.strategy.burns_balances() {
                 if let Some(mint) = account.mint {
                     instructions.push(token_instruction::burn(
                         &spl_token::id(),
@@ -608,59 +1816,83 @@ impl AdvancedRentCleaner {
             instructions.push(token_instruction::close_account(
                 &spl_token::id(),
                 &account.base.address,
-                &payer_pubkey,
+                &self.config.base.rent_destination.unwrap_or(payer_pubkey),
                 &payer_pubkey,
                 &[],
             )?);
         }
 
-        self.send_transaction(instructions).await
+        Ok(instructions)
     }
 
-    /// Close a single account.
-    async fn close_single_account(
+    /// Build a signed cleanup transaction for the given accounts.
+    ///
+    /// Performs no network I/O; the caller supplies the recent blockhash.
+    pub fn build_cleanup_transaction(
         &self,
-        account: &ExtendedCleanableAccount,
-    ) -> Result<Signature> {
-        let mut instructions = Vec::new();
-        let payer_pubkey = self.payer.pubkey();
-
-        // Burn tokens if needed
-        if account.token_balance > 0 && self.config.strategy == CleanupStrategy::BurnAndClose {
-            if let Some(mint) = account.mint {
-                instructions.push(token_instruction::burn(
-                    &spl_token::id(),
-                    &account.base.address,
-                    &mint,
-                    &payer_pubkey,
-                    &[],
-                    account.token_balance,
-                )?);
-            }
-        }
-
-        // Close the account
-        instructions.push(token_instruction::close_account(
-            &spl_token::id(),
-            &account.base.address,
-            &payer_pubkey,
-            &payer_pubkey,
-            &[],
-        )?);
+        accounts: &[ExtendedCleanableAccount],
+        recent_blockhash: Hash,
+    ) -> Result<Transaction> {
+        let instructions = self.build_cleanup_instructions(accounts)?;
+        let message = Message::new(&instructions, Some(&self.payer.pubkey()));
+        Ok(Transaction::new(&[&self.payer], message, recent_blockhash))
+    }
 
-        self.send_transaction(instructions).await
+    /// Build a cleanup transaction whose fee is paid by `fee_payer`, with
+    /// the cleaner's payer signing as the account owner.
+    ///
+    /// Performs no network I/O; the caller supplies the recent blockhash.
+    pub fn build_cleanup_transaction_with_fee_payer(
+        &self,
+        accounts: &[ExtendedCleanableAccount],
+        fee_payer: &Keypair,
+        recent_blockhash: Hash,
+    ) -> Result<Transaction> {
+        let instructions = self.build_cleanup_instructions(accounts)?;
+        Ok(fee_paid_transaction(&instructions, fee_payer, &self.payer, recent_blockhash))
     }
 
     /// Send a transaction with the given instructions.
     async fn send_transaction(&self, instructions: Vec<Instruction>) -> Result<Signature> {
+        ensure_instructions(&instructions)?;
+        check_test_network(self.config.base.test_networks_only, self.network)?;
+        self.check_expected_network().await?;
         let recent_blockhash = self.client.get_latest_blockhash().await?;
         let message = Message::new(&instructions, Some(&self.payer.pubkey()));
         let transaction = Transaction::new(&[&self.payer], message, recent_blockhash);
 
-        self.client
-            .send_and_confirm_transaction(&transaction)
-            .await
-            .map_err(|e| ToolkitError::TransactionError(e.to_string()))
+        self.policy.enforce(&self.client, &transaction).await?;
+        send_and_confirm(&self.client, &transaction, &[], &self.send_policy).await
+    }
+
+    /// Check the node's genesis hash against the expected network, once.
+    async fn check_expected_network(&self) -> Result<()> {
+        let Some(expected) = self.expected_network else {
+            return Ok(());
+        };
+        if self.genesis_verified.load(Ordering::Acquire) {
+            return Ok(());
+        }
+
+        let genesis = self.client.get_genesis_hash().await?;
+        let actual = Network::from_genesis_hash(&genesis);
+        let matches = match expected.genesis_hash() {
+            Some(hash) => genesis == hash,
+            None => actual.is_none(),
+        };
+        if !matches {
+            let actual = match actual {
+                Some(network) => network.to_string(),
+                None => format!("an unknown cluster (genesis {})", genesis),
+            };
+            return Err(ToolkitError::ConfigError(format!(
+                "Refusing to close accounts: expected {} but the RPC node is on {}",
+                expected, actual
+            )));
+        }
+
+        self.genesis_verified.store(true, Ordering::Release);
+        Ok(())
     }
 
     /// Estimate total recoverable lamports.
@@ -687,7 +1919,11 @@ impl AdvancedRentCleaner {
 
             if account.token_balance == 0 {
                 breakdown.empty_accounts += 1;
-            } else if account.token_balance <= self.config.dust_threshold {
+            } else if self.config.is_dust(
+                account.mint.as_ref(),
+                account.token_balance,
+                account.decimals,
+            ) {
                 breakdown.dust_accounts += 1;
             } else {
                 breakdown.accounts_with_balance += 1;
@@ -710,6 +1946,24 @@ impl AdvancedRentCleaner {
     }
 }
 
+/// Projected outcome of one strategy, from [`AdvancedRentCleaner::compare_strategies`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StrategyEstimate {
+    /// Number of accounts that would be closed.
+    pub accounts_to_close: usize,
+    /// Of those, accounts holding tokens that would be burned first.
+    pub accounts_to_burn: usize,
+    /// Total lamports that would be recovered.
+    pub lamports_recoverable: u64,
+}
+
+impl StrategyEstimate {
+    /// Get total SOL recoverable.
+    pub fn sol_recoverable(&self) -> f64 {
+        Lamports(self.lamports_recoverable).as_sol().0
+    }
+}
+
 /// Breakdown of recoverable rent by category.
 #[derive(Debug, Clone)]
 pub struct RecoveryBreakdown {
@@ -730,7 +1984,39 @@ pub struct RecoveryBreakdown {
 impl RecoveryBreakdown {
     /// Get total SOL recoverable.
     pub fn sol_recoverable(&self) -> f64 {
-        self.total_lamports as f64 / 1_000_000_000.0
+        Lamports(self.total_lamports).as_sol().0
+    }
+}
+
+impl std::fmt::Display for RecoveryBreakdown {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Recoverable rent")?;
+        writeln!(f, "  Accounts:        {}", self.total_accounts)?;
+        writeln!(f, "  Recoverable:     {}", format_sol(self.total_lamports))?;
+        writeln!(f, "  Empty:           {}", self.empty_accounts)?;
+        writeln!(f, "  Dust:            {}", self.dust_accounts)?;
+        write!(f, "  With balance:    {}", self.accounts_with_balance)?;
+
+        let mut mints: Vec<&MintBreakdown> = self.by_mint.values().collect();
+        mints.sort_by(|a, b| b.total_lamports.cmp(&a.total_lamports).then(a.mint.cmp(&b.mint)));
+        if !mints.is_empty() {
+            write!(
+                f,
+                "\n  {:<44}  {:>8}  {:>20}  {:>20}",
+                "Mint", "Accounts", "Recoverable", "Tokens"
+            )?;
+        }
+        for mint in mints {
+            write!(
+                f,
+                "\n  {:<44}  {:>8}  {:>20}  {:>20}",
+                mint.mint.to_string(),
+                mint.account_count,
+                format_sol(mint.total_lamports),
+                mint.total_tokens
+            )?;
+        }
+        Ok(())
     }
 }
 
@@ -747,10 +2033,449 @@ pub struct MintBreakdown {
     pub total_tokens: u64,
 }
 
+/// Cleanup across several wallets.
+///
+/// Each wallet is cleaned by its own [`AdvancedRentCleaner`], since closing a
+/// token account requires the owner's signature.
+pub struct MultiWalletCleaner<R = RpcClient> {
+    cleaners: Vec<AdvancedRentCleaner<R>>,
+}
+
+impl MultiWalletCleaner {
+    /// Create a cleaner for each payer with a shared configuration.
+    pub fn new(rpc_url: &str, payers: Vec<Keypair>, config: AdvancedCleanupConfig) -> Self {
+        Self {
+            cleaners: payers
+                .into_iter()
+                .map(|payer| AdvancedRentCleaner::with_config(rpc_url, payer, config.clone()))
+                .collect(),
+        }
+    }
+
+    /// Create a cleaner for each payer on a well-known network.
+    pub fn new_on(network: Network, payers: Vec<Keypair>, config: AdvancedCleanupConfig) -> Self {
+        Self {
+            cleaners: payers
+                .into_iter()
+                .map(|payer| AdvancedRentCleaner::new_on(network, payer, config.clone()))
+                .collect(),
+        }
+    }
+}
+
+impl<R: SolanaRpc> MultiWalletCleaner<R> {
+    /// Create from per-wallet cleaners.
+    pub fn from_cleaners(cleaners: Vec<AdvancedRentCleaner<R>>) -> Self {
+        Self { cleaners }
+    }
+
+    /// Apply [`AdvancedRentCleaner::with_timeout`] to every wallet's cleaner.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        for cleaner in &mut self.cleaners {
+            cleaner.client.set_timeout(timeout);
+        }
+        self
+    }
+
+    /// Wallets covered by this cleaner.
+    pub fn wallets(&self) -> Vec<Pubkey> {
+        self.cleaners.iter().map(|c| c.payer.pubkey()).collect()
+    }
+
+    /// Run cleanup for every wallet in turn.
+    ///
+    /// A wallet whose cleanup fails is recorded in
+    /// [`MultiWalletResult::failed_wallets`] and does not stop the others.
+    pub async fn execute_cleanup(&self) -> MultiWalletResult {
+        let mut result = MultiWalletResult::default();
+
+        for cleaner in &self.cleaners {
+            let wallet = cleaner.payer.pubkey();
+            match cleaner.execute_cleanup().await {
+                Ok(wallet_result) => {
+                    result.combined.merge(&wallet_result);
+                    result.per_wallet.push((wallet, wallet_result));
+                }
+                Err(e) => result.failed_wallets.push((wallet, e.to_string())),
+            }
+        }
+
+        result
+    }
+}
+
+/// Progress of a checkpointed cleanup, persisted as JSON.
+///
+/// Written by [`AdvancedRentCleaner::execute_cleanup_with_checkpoint`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CleanupCheckpoint {
+    /// Wallet whose accounts are being closed.
+    pub wallet: String,
+    /// Closed account address -> signature of the transaction that closed it.
+    pub closed: HashMap<String, String>,
+}
+
+impl CleanupCheckpoint {
+    /// Create an empty checkpoint for `wallet`.
+    pub fn new(wallet: &Pubkey) -> Self {
+        Self {
+            wallet: wallet.to_string(),
+            closed: HashMap::new(),
+        }
+    }
+
+    /// Load the checkpoint at `path`, or start a new one if it does not exist.
+    ///
+    /// Fails if the file belongs to a different wallet.
+    pub fn load_or_new(path: &Path, wallet: &Pubkey) -> Result<Self> {
+        let Some(checkpoint) = state_file::load::<Self>(path, "checkpoint")? else {
+            return Ok(Self::new(wallet));
+        };
+
+        if checkpoint.wallet != wallet.to_string() {
+            return Err(ToolkitError::ConfigError(format!(
+                "Checkpoint {} is for wallet {}, not {}",
+                path.display(),
+                checkpoint.wallet,
+                wallet
+            )));
+        }
+        Ok(checkpoint)
+    }
+
+    /// Whether `address` was closed by an earlier run.
+    pub fn is_closed(&self, address: &Pubkey) -> bool {
+        self.closed.contains_key(&address.to_string())
+    }
+
+    /// Record `addresses` as closed by `signature`.
+    pub fn record(&mut self, addresses: &[Pubkey], signature: &Signature) {
+        for address in addresses {
+            self.closed.insert(address.to_string(), signature.to_string());
+        }
+    }
+
+    /// Write the checkpoint to `path`, replacing it atomically.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        state_file::save(self, path, "checkpoint")
+    }
+}
+
+/// Serializable description of a cleanup, for approval workflows.
+///
+/// Built by [`AdvancedRentCleaner::build_cleanup_plan`] and run with
+/// [`AdvancedRentCleaner::execute_plan`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CleanupPlan {
+    /// Wallet whose accounts are closed.
+    pub wallet: String,
+    /// Strategy the plan was built under.
+    pub strategy: CleanupStrategy,
+    /// Accounts to close, in execution order.
+    pub accounts: Vec<PlannedAccount>,
+    /// Lamports recovered if every account is closed.
+    pub estimated_recovery: u64,
+    /// Transactions to send, in order.
+    pub batches: Vec<PlannedBatch>,
+}
+
+impl CleanupPlan {
+    /// Distinct mints of the planned accounts.
+    pub fn mints(&self) -> Vec<String> {
+        let mut mints: Vec<String> =
+            self.accounts.iter().filter_map(|a| a.mint.clone()).collect();
+        mints.sort();
+        mints.dedup();
+        mints
+    }
+}
+
+/// One account in a [`CleanupPlan`], as scanned.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlannedAccount {
+    /// Token account address.
+    pub address: String,
+    /// Token mint.
+    pub mint: Option<String>,
+    /// Lamports held by the account.
+    pub lamports: u64,
+    /// Token balance, burned first under `BurnAndClose`.
+    pub token_balance: u64,
+    /// Decimals of the token mint.
+    pub decimals: u8,
+}
+
+impl PlannedAccount {
+    fn to_cleanable(&self) -> Result<ExtendedCleanableAccount> {
+        let parse = |s: &str| {
+            s.parse::<Pubkey>()
+                .map_err(|e| ToolkitError::ParseError(format!("Invalid address {}: {}", s, e)))
+        };
+        Ok(ExtendedCleanableAccount {
+            base: CleanableAccount {
+                address: parse(&self.address)?,
+                lamports: self.lamports,
+                account_type: AccountType::TokenAccount,
+            },
+            mint: self.mint.as_deref().map(parse).transpose()?,
+            token_balance: self.token_balance,
+            decimals: self.decimals,
+            can_burn: self.token_balance > 0,
+        })
+    }
+}
+
+/// One transaction in a [`CleanupPlan`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlannedBatch {
+    /// Accounts closed by the transaction.
+    pub accounts: Vec<String>,
+    /// Base58 SHA-256 of the bincode-encoded instructions.
+    pub instruction_hash: String,
+}
+
+/// Combined result of a multi-wallet cleanup.
+#[derive(Debug, Clone, Default)]
+pub struct MultiWalletResult {
+    /// Totals across all wallets.
+    pub combined: CleanupResult,
+    /// Result for each wallet, in order.
+    pub per_wallet: Vec<(Pubkey, CleanupResult)>,
+    /// Wallets whose cleanup could not run.
+    pub failed_wallets: Vec<(Pubkey, String)>,
+}
+
+#[cfg(feature = "jupiter")]
+impl<R: SolanaRpc> AdvancedRentCleaner<R> {
+    /// Reclaim rent, then swap leftover token dust into `output_mint`.
+    ///
+    /// Runs [`execute_cleanup`](Self::execute_cleanup) first, then quotes
+    /// every remaining token account whose balance is dust under the
+    /// configured thresholds and swaps those quoting at least
+    /// `min_swap_output`. Swapped accounts are left empty for the next
+    /// cleanup run. Quote or swap failures are recorded per account and do
+    /// not stop the others. Dry runs report the cleanup plan only.
+    pub async fn reclaim_and_consolidate<J: SwapProvider + ?Sized>(
+        &self,
+        jupiter: &J,
+        output_mint: &str,
+    ) -> Result<ConsolidationResult> {
+        let cleanup = self.execute_cleanup().await?;
+        let mut result = ConsolidationResult {
+            cleanup,
+            output_mint: output_mint.to_string(),
+            swaps: Vec::new(),
+            below_threshold: Vec::new(),
+            failed_swaps: Vec::new(),
+        };
+
+        if self.is_dry_run() {
+            return Ok(result);
+        }
+
+        for account in self.scan_dust_balances(output_mint).await? {
+            let Some(mint) = account.mint else { continue };
+            let address = account.base.address;
+
+            let quote = match jupiter
+                .quote(
+                    &mint.to_string(),
+                    output_mint,
+                    account.token_balance,
+                    self.config.swap_slippage_bps,
+                )
+                .await
+            {
+                Ok(quote) => quote,
+                Err(e) => {
+                    warn!(account = %address, error = %e, "dust quote failed");
+                    result.failed_swaps.push((address, e.to_string()));
+                    continue;
+                }
+            };
+
+            if quote.out_amount < self.config.min_swap_output {
+                if self.config.base.verbose {
+                    debug!(
+                        account = %address,
+                        out_amount = quote.out_amount,
+                        "dust below threshold"
+                    );
+                }
+                result.below_threshold.push(address);
+                continue;
+            }
+
+            let out_amount = quote.out_amount;
+            match jupiter.execute_swap(&self.payer, quote).await {
+                Ok(signature) => result.swaps.push(DustSwap {
+                    account: address,
+                    mint,
+                    in_amount: account.token_balance,
+                    out_amount,
+                    signature,
+                }),
+                Err(e) => {
+                    warn!(account = %address, error = %e, "dust swap failed");
+                    result.failed_swaps.push((address, e.to_string()));
+                }
+            }
+        }
+
+        if self.config.base.verbose {
+            info!(
+                lamports = result.cleanup.lamports_recovered,
+                swaps = result.swaps.len(),
+                output = result.total_output(),
+                "consolidation finished"
+            );
+        }
+        Ok(result)
+    }
+
+    /// Non-empty token accounts whose balance is dust, excluding
+    /// `output_mint` and mints filtered out by the config.
+    async fn scan_dust_balances(&self, output_mint: &str) -> Result<Vec<ExtendedCleanableAccount>> {
+        let owner = self.payer.pubkey();
+        let accounts =
+            fetch_token_accounts(&self.client, &owner, self.config.base.token_account_scan)
+                .await?;
+
+        let parsed: Vec<(Pubkey, u64, Pubkey, u64)> = accounts
+            .into_iter()
+            .filter_map(|(pubkey, account)| {
+                let token = decode_token_account(&account)?;
+                Some((pubkey, account.lamports, token.mint, token.amount))
+            })
+            .filter(|(_, _, mint, balance)| *balance > 0 && mint.to_string() != output_mint)
+            .collect();
+
+        let mints: Vec<Pubkey> = parsed.iter().map(|(_, _, mint, _)| *mint).collect();
+        let decimals = self.fetch_mint_decimals(&mints).await?;
+
+        Ok(parsed
+            .into_iter()
+            .filter_map(|(address, lamports, mint, balance)| {
+                let Some(decimals) = decimals.get(&mint).copied() else {
+                    warn!(account = %address, "skipping dust with unknown mint decimals");
+                    return None;
+                };
+                let include = self
+                    .should_include_account(self.config.strategy, Some(&mint), balance, decimals)
+                    && self.config.is_dust(Some(&mint), balance, decimals);
+                include.then_some(ExtendedCleanableAccount {
+                    base: CleanableAccount {
+                        address,
+                        lamports,
+                        account_type: AccountType::TokenAccount,
+                    },
+                    mint: Some(mint),
+                    token_balance: balance,
+                    decimals,
+                    can_burn: false,
+                })
+            })
+            .collect())
+    }
+}
+
+/// A dust balance swapped by [`AdvancedRentCleaner::reclaim_and_consolidate`].
+#[cfg(feature = "jupiter")]
+#[derive(Debug, Clone)]
+pub struct DustSwap {
+    /// Token account the dust was swapped out of.
+    pub account: Pubkey,
+    /// Mint of the swapped dust.
+    pub mint: Pubkey,
+    /// Raw amount swapped.
+    pub in_amount: u64,
+    /// Quoted output in the output mint's base units.
+    pub out_amount: u64,
+    /// Swap transaction signature.
+    pub signature: Signature,
+}
+
+/// Result of [`AdvancedRentCleaner::reclaim_and_consolidate`].
+#[cfg(feature = "jupiter")]
+#[derive(Debug, Clone)]
+pub struct ConsolidationResult {
+    /// Result of the rent reclaim that ran before any swap.
+    pub cleanup: CleanupResult,
+    /// Mint the dust was swapped into.
+    pub output_mint: String,
+    /// Swaps that were sent.
+    pub swaps: Vec<DustSwap>,
+    /// Dust accounts whose quote was below `min_swap_output`.
+    pub below_threshold: Vec<Pubkey>,
+    /// Dust accounts whose quote or swap failed.
+    pub failed_swaps: Vec<(Pubkey, String)>,
+}
+
+#[cfg(feature = "jupiter")]
+impl ConsolidationResult {
+    /// Total quoted output across all swaps, in the output mint's base units.
+    pub fn total_output(&self) -> u64 {
+        self.swaps.iter().map(|s| s.out_amount).sum()
+    }
+
+    /// Reclaimed rent plus swap output, when the output mint is wrapped SOL.
+    pub fn net_lamports(&self) -> Option<u64> {
+        (self.output_mint == JupiterClient::SOL_MINT)
+            .then(|| self.cleanup.lamports_recovered + self.total_output())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_result_display() {
+        let failed = Pubkey::new_unique();
+        let (usdc, bonk) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut result = CleanupResult::new();
+        result.lamports_recovered = 4_078_560;
+        result.accounts_closed = 2;
+        result.signatures.push(Signature::default());
+        result.failed_accounts.push((failed, "account frozen".to_string()));
+
+        let rendered = result.to_string();
+        assert!(rendered.contains("0.00407856 SOL"));
+        assert!(rendered.contains("Accounts closed: 2"));
+        assert!(rendered.contains("Transactions:    1"));
+        assert!(rendered.contains(&failed.to_string()));
+        assert!(rendered.contains("account frozen"));
+
+        let mint = |mint, account_count, total_lamports| MintBreakdown {
+            mint,
+            account_count,
+            total_lamports,
+            total_tokens: 0,
+        };
+        let breakdown = RecoveryBreakdown {
+            total_accounts: 3,
+            total_lamports: 6_117_840,
+            empty_accounts: 2,
+            dust_accounts: 1,
+            accounts_with_balance: 0,
+            by_mint: HashMap::from([
+                (usdc, mint(usdc, 1, 2_039_280)),
+                (bonk, mint(bonk, 2, 4_078_560)),
+            ]),
+        };
+
+        let rendered = breakdown.to_string();
+        assert!(rendered.contains("0.00611784 SOL"));
+        assert!(rendered.contains("Empty:           2"));
+        assert!(rendered.contains("Dust:            1"));
+        // Mints are listed by recoverable lamports, highest first
+        let (bonk_at, usdc_at) = (
+            rendered.find(&bonk.to_string()).unwrap(),
+            rendered.find(&usdc.to_string()).unwrap(),
+        );
+        assert!(bonk_at < usdc_at);
+    }
+
     #[test]
     fn test_config_default() {
         let config = RentCleanerConfig::default();
@@ -758,4 +2483,1357 @@ mod tests {
         assert!(config.close_system_accounts);
         assert!(!config.dry_run);
     }
+
+    fn offline_rpc_url() -> &'static str {
+        "http://127.0.0.1:8899"
+    }
+
+    #[test]
+    fn test_build_close_transaction_offline() {
+        let payer = Keypair::new();
+        let payer_pubkey = payer.pubkey();
+        let cleaner = RentCleaner::new(offline_rpc_url(), payer);
+
+        let accounts: Vec<CleanableAccount> = (0..2)
+            .map(|_| CleanableAccount {
+                address: Pubkey::new_unique(),
+                lamports: 2_039_280,
+                account_type: AccountType::TokenAccount,
+            })
+            .collect();
+        let blockhash = Hash::new_unique();
+
+        let tx = cleaner.build_close_transaction(&accounts, blockhash).unwrap();
+
+        assert_eq!(tx.message.recent_blockhash, blockhash);
+        assert_eq!(tx.message.account_keys[0], payer_pubkey);
+        assert_eq!(tx.message.instructions.len(), 2);
+        for (ix, account) in tx.message.instructions.iter().zip(&accounts) {
+            assert_eq!(tx.message.account_keys[ix.program_id_index as usize], spl_token::id());
+            assert_eq!(tx.message.account_keys[ix.accounts[0] as usize], account.address);
+        }
+        assert!(tx.verify().is_ok());
+    }
+
+    #[test]
+    fn test_build_cleanup_instructions_burns_under_burn_and_close() {
+        let payer = Keypair::new();
+        let cleaner = AdvancedRentCleaner::with_config(
+            offline_rpc_url(),
+            payer,
+            AdvancedCleanupConfig::aggressive(),
+        );
+
+        let account = ExtendedCleanableAccount {
+            base: CleanableAccount {
+                address: Pubkey::new_unique(),
+                lamports: 2_039_280,
+                account_type: AccountType::TokenAccount,
+            },
+            mint: Some(Pubkey::new_unique()),
+            token_balance: 5,
+            decimals: 6,
+            can_burn: true,
+        };
+
+        let instructions = cleaner.build_cleanup_instructions(&[account]).unwrap();
+        assert_eq!(instructions.len(), 2);
+        assert_eq!(instructions[0].data[0], 8); // Burn
+        assert_eq!(instructions[1].data[0], 9); // CloseAccount
+    }
+
+    #[test]
+    fn test_cleanup_transaction_includes_priority_fee() {
+        let config = AdvancedCleanupConfig::default()
+            .with_priority_fee(10_000)
+            .with_compute_unit_limit(50_000);
+        let cleaner = AdvancedRentCleaner::with_config(offline_rpc_url(), Keypair::new(), config);
+
+        let account = ExtendedCleanableAccount {
+            base: CleanableAccount {
+                address: Pubkey::new_unique(),
+                lamports: 2_039_280,
+                account_type: AccountType::TokenAccount,
+            },
+            mint: Some(Pubkey::new_unique()),
+            token_balance: 0,
+            decimals: 6,
+            can_burn: false,
+        };
+
+        let tx = cleaner
+            .build_cleanup_transaction(&[account], Hash::new_unique())
+            .unwrap();
+        let instructions = &tx.message.instructions;
+
+        assert_eq!(instructions.len(), 3);
+        let first = &instructions[0];
+        assert_eq!(
+            tx.message.account_keys[first.program_id_index as usize],
+            solana_sdk::compute_budget::id()
+        );
+        assert_eq!(
+            first.data,
+            ComputeBudgetInstruction::set_compute_unit_price(10_000).data
+        );
+        assert_eq!(
+            instructions[1].data,
+            ComputeBudgetInstruction::set_compute_unit_limit(50_000).data
+        );
+    }
+
+    #[test]
+    fn test_per_mint_dust_threshold_scales_with_decimals() {
+        let usdc = Pubkey::new_unique();
+        let meme = Pubkey::new_unique();
+        let config = AdvancedCleanupConfig {
+            strategy: CleanupStrategy::BelowDustThreshold,
+            ..Default::default()
+        }
+        .with_dust_threshold_ui(0.01)
+        .with_mint_dust_threshold(usdc, 0.5);
+
+        let cleaner = AdvancedRentCleaner::with_config(offline_rpc_url(), Keypair::new(), config);
+
+        // 1_000_000 raw units: 1.0 USDC (6 decimals) vs 0.001 of a 9-decimal token
+        let raw = 1_000_000;
+        assert!(!cleaner.config.is_dust(Some(&usdc), raw, 6));
+        assert!(cleaner.config.is_dust(Some(&meme), raw, 9));
+        let strategy = cleaner.config.strategy;
+        assert!(!cleaner.should_include_account(strategy, Some(&usdc), raw, 6));
+        assert!(cleaner.should_include_account(strategy, Some(&meme), raw, 9));
+
+        // Below the USDC override
+        assert!(cleaner.should_include_account(strategy, Some(&usdc), 400_000, 6));
+    }
+
+    #[test]
+    fn test_dust_threshold_falls_back_to_default() {
+        let mint = Pubkey::new_unique();
+        let config = AdvancedCleanupConfig::default().with_dust_threshold_ui(2.0);
+
+        assert_eq!(config.dust_threshold_for(Some(&mint)), 2.0);
+        assert_eq!(config.dust_threshold_for(None), 2.0);
+        assert!(config.is_dust(Some(&mint), 2_000, 3));
+        assert!(!config.is_dust(Some(&mint), 2_001, 3));
+    }
+
+    #[test]
+    fn test_default_dust_threshold_counts_only_empty_balances() {
+        let config = AdvancedCleanupConfig::default();
+
+        assert_eq!(config.dust_threshold_ui, 0.0);
+        assert!(config.is_dust(None, 0, 6));
+        assert!(!config.is_dust(None, 1, 6));
+        assert!(!config.is_dust(None, 1, 0));
+    }
+
+    #[tokio::test]
+    async fn test_rent_cleaner_with_mock_rpc() {
+        use crate::testing::{MockRpc, TOKEN_ACCOUNT_RENT};
+
+        let payer = Keypair::new();
+        let owner = payer.pubkey();
+        let mint = Pubkey::new_unique();
+        let empty = Pubkey::new_unique();
+        let funded = Pubkey::new_unique();
+        let rpc = MockRpc::new()
+            .with_account(empty, MockRpc::token_account(&mint, &owner, 0))
+            .with_account(funded, MockRpc::token_account(&mint, &owner, 10));
+
+        let cleaner = RentCleaner::from_client(rpc, payer);
+
+        let accounts = cleaner.find_empty_token_accounts().await.unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].address, empty);
+
+        let recovered = cleaner.close_empty_token_accounts().await.unwrap();
+        assert_eq!(recovered, TOKEN_ACCOUNT_RENT);
+
+        let sent = cleaner.client.sent_transactions();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].message.recent_blockhash, cleaner.client.blockhash());
+        assert!(sent[0].message.account_keys.contains(&empty));
+        assert!(!sent[0].message.account_keys.contains(&funded));
+    }
+
+    #[tokio::test]
+    async fn test_clean_empty_accounts_matches_close_empty_token_accounts() {
+        use crate::testing::{MockRpc, TOKEN_ACCOUNT_RENT};
+
+        let payer = Keypair::new();
+        let owner = payer.pubkey();
+        let mint = Pubkey::new_unique();
+        let closable = Pubkey::new_unique();
+        let stuck = Pubkey::new_unique();
+        let setup = |dry_run: bool| {
+            let rpc = MockRpc::new()
+                .with_account(closable, MockRpc::token_account(&mint, &owner, 0))
+                .with_account(stuck, MockRpc::token_account(&mint, &owner, 0))
+                .with_account(Pubkey::new_unique(), MockRpc::token_account(&mint, &owner, 3));
+            rpc.fail_sends_touching(stuck, "account frozen");
+            let config = RentCleanerConfig { dry_run, ..Default::default() };
+            RentCleaner::from_client_with_config(rpc, payer.insecure_clone(), config)
+        };
+
+        let result = setup(false).clean_empty_accounts().await.unwrap();
+        assert_eq!(result.lamports_recovered, TOKEN_ACCOUNT_RENT);
+        assert_eq!(result.accounts_closed, 1);
+        assert_eq!(result.signatures.len(), 1);
+        assert_eq!(result.failed_accounts.len(), 1);
+        assert_eq!(result.failed_accounts[0].0, stuck);
+        assert!(result.dry_run_plan.is_none());
+
+        let recovered = setup(false).close_empty_token_accounts().await.unwrap();
+        assert_eq!(recovered, result.lamports_recovered);
+
+        // Dry runs agree too, and carry the plan
+        let result = setup(true).clean_empty_accounts().await.unwrap();
+        assert_eq!(result.lamports_recovered, 2 * TOKEN_ACCOUNT_RENT);
+        assert_eq!(result.accounts_closed, 2);
+        assert!(result.signatures.is_empty());
+        assert_eq!(result.dry_run_plan.unwrap().actions.len(), 2);
+        let recovered = setup(true).close_empty_token_accounts().await.unwrap();
+        assert_eq!(recovered, 2 * TOKEN_ACCOUNT_RENT);
+    }
+
+    #[tokio::test]
+    async fn test_relayer_pays_cleanup_fees() {
+        use crate::testing::{MockRpc, TOKEN_ACCOUNT_RENT};
+
+        let owner_keypair = Keypair::new();
+        let owner = owner_keypair.pubkey();
+        let relayer = Keypair::new();
+        let empty = Pubkey::new_unique();
+        let rpc = MockRpc::new()
+            .with_account(empty, MockRpc::token_account(&Pubkey::new_unique(), &owner, 0));
+        let cleaner = RentCleaner::from_client(rpc, owner_keypair);
+
+        let result = cleaner.clean_empty_accounts_with_fee_payer(&relayer).await.unwrap();
+        assert_eq!(result.accounts_closed, 1);
+        assert_eq!(result.lamports_recovered, TOKEN_ACCOUNT_RENT);
+
+        let sent = cleaner.client.sent_transactions();
+        assert_eq!(sent.len(), 1);
+        let tx = &sent[0];
+        // The relayer pays the fee; the owner signs the close and gets the rent
+        assert_eq!(tx.message.account_keys[0], relayer.pubkey());
+        assert_eq!(tx.message.header.num_required_signatures, 2);
+        assert_eq!(tx.message.account_keys[1], owner);
+        let ix = &tx.message.instructions[0];
+        assert_eq!(tx.message.account_keys[ix.accounts[1] as usize], owner);
+        assert_eq!(tx.message.account_keys[ix.accounts[2] as usize], owner);
+        assert!(tx.message.is_signer(ix.accounts[2] as usize));
+        tx.verify().unwrap();
+
+        // The offline builder agrees
+        let accounts = [CleanableAccount {
+            address: empty,
+            lamports: TOKEN_ACCOUNT_RENT,
+            account_type: AccountType::TokenAccount,
+        }];
+        let blockhash = Hash::new_unique();
+        let tx = cleaner
+            .build_close_transaction_with_fee_payer(&accounts, &relayer, blockhash)
+            .unwrap();
+        assert_eq!(tx.message.account_keys[0], relayer.pubkey());
+        assert_eq!(tx.signatures.len(), 2);
+        tx.verify().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_estimate_many_bounds_concurrency() {
+        use crate::testing::{MockRpc, TOKEN_ACCOUNT_RENT};
+
+        let owners = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        let mint = Pubkey::new_unique();
+        let rpc = MockRpc::new()
+            .with_account(Pubkey::new_unique(), MockRpc::token_account(&mint, &owners[0], 0))
+            .with_account(Pubkey::new_unique(), MockRpc::token_account(&mint, &owners[0], 0))
+            .with_account(Pubkey::new_unique(), MockRpc::token_account(&mint, &owners[1], 0))
+            .with_account(Pubkey::new_unique(), MockRpc::token_account(&mint, &owners[1], 5))
+            .with_account(Pubkey::new_unique(), MockRpc::token_account(&mint, &owners[2], 7));
+        rpc.set_latency(Duration::from_millis(50));
+
+        let estimates = estimate_many(&rpc, &owners, 2).await.unwrap();
+        assert_eq!(estimates.len(), 3);
+        assert_eq!(estimates[&owners[0]], 2 * TOKEN_ACCOUNT_RENT);
+        assert_eq!(estimates[&owners[1]], TOKEN_ACCOUNT_RENT);
+        assert_eq!(estimates[&owners[2]], 0);
+        assert_eq!(rpc.peak_concurrency(), 2);
+
+        let requests = rpc.program_account_requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests.iter().all(|config| {
+            config.filters.as_ref().unwrap().contains(&zero_amount_filter())
+        }));
+    }
+
+    #[tokio::test]
+    async fn test_advanced_scan_uses_mint_decimals_from_mock_rpc() {
+        use crate::testing::MockRpc;
+
+        let payer = Keypair::new();
+        let owner = payer.pubkey();
+        let usdc = Pubkey::new_unique();
+        let meme = Pubkey::new_unique();
+        let usdc_account = Pubkey::new_unique();
+        let meme_account = Pubkey::new_unique();
+        let rpc = MockRpc::new()
+            .with_account(usdc, MockRpc::mint_account(6, 1_000_000_000))
+            .with_account(meme, MockRpc::mint_account(9, 1_000_000_000))
+            .with_account(usdc_account, MockRpc::token_account(&usdc, &owner, 1_000_000))
+            .with_account(meme_account, MockRpc::token_account(&meme, &owner, 1_000_000));
+
+        let config = AdvancedCleanupConfig {
+            strategy: CleanupStrategy::BelowDustThreshold,
+            ..Default::default()
+        }
+        .with_dust_threshold_ui(0.01);
+        let cleaner = AdvancedRentCleaner::from_client(rpc, payer, config);
+
+        let accounts = cleaner.scan_accounts().await.unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].base.address, meme_account);
+        assert_eq!(accounts[0].decimals, 9);
+    }
+
+    #[tokio::test]
+    async fn test_advanced_scan_skips_balances_with_unknown_decimals() {
+        use crate::testing::MockRpc;
+
+        let payer = Keypair::new();
+        let owner = payer.pubkey();
+        // No mint account, so its decimals can't be fetched
+        let unknown = Pubkey::new_unique();
+        let (empty_account, held_account) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let rpc = MockRpc::new()
+            .with_account(empty_account, MockRpc::token_account(&unknown, &owner, 0))
+            .with_account(held_account, MockRpc::token_account(&unknown, &owner, 5));
+
+        let config = AdvancedCleanupConfig::aggressive();
+        let cleaner = AdvancedRentCleaner::from_client(rpc, payer, config);
+
+        let accounts = cleaner.scan_accounts().await.unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].base.address, empty_account);
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_plan_lists_empty_accounts() {
+        use crate::testing::{MockRpc, TOKEN_ACCOUNT_RENT};
+
+        let payer = Keypair::new();
+        let owner = payer.pubkey();
+        let mint = Pubkey::new_unique();
+        let empty_a = Pubkey::new_unique();
+        let empty_b = Pubkey::new_unique();
+        let rpc = MockRpc::new()
+            .with_account(empty_a, MockRpc::token_account(&mint, &owner, 0))
+            .with_account(empty_b, MockRpc::token_account(&mint, &owner, 0))
+            .with_account(Pubkey::new_unique(), MockRpc::token_account(&mint, &owner, 7));
+        let config = RentCleanerConfig {
+            dry_run: true,
+            ..Default::default()
+        };
+        let cleaner = RentCleaner::from_client_with_config(rpc, payer, config);
+
+        let plan = cleaner.plan_close_empty_token_accounts().await.unwrap();
+        let mut closed = plan.accounts_to_close();
+        closed.sort();
+        let mut expected = vec![empty_a, empty_b];
+        expected.sort();
+
+        assert_eq!(closed, expected);
+        assert_eq!(plan.total_lamports(), 2 * TOKEN_ACCOUNT_RENT);
+        assert_eq!(
+            cleaner.close_empty_token_accounts().await.unwrap(),
+            2 * TOKEN_ACCOUNT_RENT
+        );
+        assert!(cleaner.client.sent_transactions().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_genesis_mismatch_aborts_cleanup() {
+        use crate::testing::{MockRpc, TOKEN_ACCOUNT_RENT};
+
+        let payer = Keypair::new();
+        let owner = payer.pubkey();
+        let account = Pubkey::new_unique();
+        let setup = |genesis: Hash, network: Network| {
+            let rpc = MockRpc::new()
+                .with_genesis_hash(genesis)
+                .with_account(account, MockRpc::token_account(&Pubkey::new_unique(), &owner, 0));
+            AdvancedRentCleaner::from_client(
+                rpc,
+                payer.insecure_clone(),
+                AdvancedCleanupConfig::default(),
+            )
+            .expected_network(network)
+        };
+        let mainnet = Network::Mainnet.genesis_hash().unwrap();
+        let devnet = Network::Devnet.genesis_hash().unwrap();
+
+        // A mainnet node behind what should be devnet sends nothing
+        let cleaner = setup(mainnet, Network::Devnet);
+        let err = cleaner.execute_cleanup().await.unwrap_err();
+        assert!(matches!(err, ToolkitError::ConfigError(_)));
+        assert!(
+            err.to_string().contains("expected devnet but the RPC node is on mainnet"),
+            "{}",
+            err
+        );
+        assert!(cleaner.client.sent_transactions().is_empty());
+
+        // Localnet accepts its own genesis, but not a public cluster's
+        let err = setup(devnet, Network::Localnet).execute_cleanup().await.unwrap_err();
+        assert!(err.to_string().contains("is on devnet"), "{}", err);
+        let result = setup(Hash::new_unique(), Network::Localnet).execute_cleanup().await.unwrap();
+        assert_eq!(result.accounts_closed, 1);
+
+        let cleaner = setup(devnet, Network::Devnet);
+        let result = cleaner.execute_cleanup().await.unwrap();
+        assert_eq!(result.lamports_recovered, TOKEN_ACCOUNT_RENT);
+        assert_eq!(cleaner.client.sent_transactions().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_cleaners_refuse_empty_instruction_lists() {
+        use crate::testing::MockRpc;
+
+        let cleaner = RentCleaner::from_client(MockRpc::new(), Keypair::new());
+        let err = cleaner.send_transaction(vec![], None).await.unwrap_err();
+        assert_eq!(err.to_string(), "Custom error: no instructions to execute");
+        assert!(cleaner.client.sent_transactions().is_empty());
+
+        let config = AdvancedCleanupConfig::default();
+        let cleaner = AdvancedRentCleaner::from_client(MockRpc::new(), Keypair::new(), config);
+        let err = cleaner.send_transaction(vec![]).await.unwrap_err();
+        assert!(matches!(err, ToolkitError::Custom(_)), "{}", err);
+        assert!(cleaner.client.sent_transactions().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_advanced_dry_run_reports_burns_and_closes() {
+        use crate::testing::{MockRpc, TOKEN_ACCOUNT_RENT};
+
+        let payer = Keypair::new();
+        let owner = payer.pubkey();
+        let mint = Pubkey::new_unique();
+        let account = Pubkey::new_unique();
+        let rpc = MockRpc::new()
+            .with_account(mint, MockRpc::mint_account(0, 100))
+            .with_account(account, MockRpc::token_account(&mint, &owner, 3));
+        let mut config = AdvancedCleanupConfig::aggressive();
+        config.base.dry_run = true;
+        let cleaner = AdvancedRentCleaner::from_client(rpc, payer, config);
+
+        let result = cleaner.execute_cleanup().await.unwrap();
+        let plan = result.dry_run_plan.clone().unwrap();
+
+        assert_eq!(
+            plan.actions,
+            vec![
+                PlannedAction::Burn { address: account, mint, amount: 3 },
+                PlannedAction::Close { address: account, lamports: TOKEN_ACCOUNT_RENT },
+            ]
+        );
+        assert_eq!(result.lamports_recovered, TOKEN_ACCOUNT_RENT);
+        assert_eq!(result.accounts_closed, 1);
+        assert_eq!(result.tokens_burned.get(&mint), Some(&3));
+        assert!(result.signatures.is_empty());
+        assert!(cleaner.client.sent_transactions().is_empty());
+    }
+
+    #[cfg(feature = "tracing")]
+    #[tokio::test]
+    async fn test_verbose_controls_progress_events() {
+        use crate::testing::MockRpc;
+        use std::sync::{Arc, Mutex};
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata, Subscriber};
+
+        /// Records event messages.
+        #[derive(Clone, Default)]
+        struct Capture(Arc<Mutex<Vec<String>>>);
+
+        struct MessageVisitor(Option<String>);
+
+        impl Visit for MessageVisitor {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                if field.name() == "message" {
+                    self.0 = Some(format!("{:?}", value));
+                }
+            }
+        }
+
+        impl Subscriber for Capture {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+
+            fn new_span(&self, _: &Attributes<'_>) -> Id {
+                Id::from_u64(1)
+            }
+
+            fn record(&self, _: &Id, _: &Record<'_>) {}
+
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+            fn event(&self, event: &Event<'_>) {
+                let mut visitor = MessageVisitor(None);
+                event.record(&mut visitor);
+                if let Some(message) = visitor.0 {
+                    self.0.lock().unwrap().push(message);
+                }
+            }
+
+            fn enter(&self, _: &Id) {}
+
+            fn exit(&self, _: &Id) {}
+        }
+
+        let payer = Keypair::new();
+        let owner = payer.pubkey();
+        let mint = Pubkey::new_unique();
+        let rpc = || {
+            MockRpc::new()
+                .with_account(mint, MockRpc::mint_account(6, 0))
+                .with_account(Pubkey::new_unique(), MockRpc::token_account(&mint, &owner, 0))
+        };
+
+        for verbose in [false, true] {
+            let capture = Capture::default();
+            let _guard = tracing::subscriber::set_default(capture.clone());
+
+            let config = RentCleanerConfig { verbose, ..Default::default() };
+            let cleaner =
+                RentCleaner::from_client_with_config(rpc(), payer.insecure_clone(), config);
+            assert_eq!(cleaner.clean_empty_accounts().await.unwrap().accounts_closed, 1);
+            let config = AdvancedCleanupConfig::default().with_verbose(verbose);
+            let cleaner = AdvancedRentCleaner::from_client(rpc(), payer.insecure_clone(), config);
+            assert_eq!(cleaner.execute_cleanup().await.unwrap().accounts_closed, 1);
+
+            let events = capture.0.lock().unwrap().clone();
+            for message in ["closed token account", "cleanup batch sent", "cleanup finished"] {
+                assert_eq!(events.iter().any(|e| e == message), verbose, "{}", message);
+            }
+        }
+    }
+
+    fn injected_account(lamports: u64, token_balance: u64) -> ExtendedCleanableAccount {
+        ExtendedCleanableAccount {
+            base: CleanableAccount {
+                address: Pubkey::new_unique(),
+                lamports,
+                account_type: AccountType::TokenAccount,
+            },
+            mint: Some(Pubkey::new_unique()),
+            token_balance,
+            decimals: 6,
+            can_burn: token_balance > 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_from_mock_accounts_needs_no_rpc() {
+        // Nothing listens on the offline URL, so any RPC call would fail
+        let accounts = vec![
+            injected_account(2_039_280, 0),
+            injected_account(1_000_000, 0),
+            injected_account(3_000_000, 42),
+        ];
+
+        let config = RentCleanerConfig { dry_run: true, ..Default::default() };
+        let cleaner = RentCleaner::with_config(offline_rpc_url(), Keypair::new(), config)
+            .with_mock_accounts(accounts.clone());
+        let result = cleaner.clean_empty_accounts().await.unwrap();
+        assert_eq!(result.lamports_recovered, 3_039_280);
+        assert_eq!(result.accounts_closed, 2);
+        assert!(result.signatures.is_empty());
+        assert_eq!(cleaner.estimate_recoverable().await.unwrap(), 3_039_280);
+
+        // The advanced cleaner applies its strategy to the injected accounts
+        let mut config = AdvancedCleanupConfig::default();
+        config.base.dry_run = true;
+        let cleaner = AdvancedRentCleaner::with_config(offline_rpc_url(), Keypair::new(), config)
+            .with_mock_accounts(accounts.clone());
+        let result = cleaner.execute_cleanup().await.unwrap();
+        assert_eq!(result.lamports_recovered, 3_039_280);
+        assert_eq!(result.accounts_closed, 2);
+        assert!(result.tokens_burned.is_empty());
+        assert!(result.signatures.is_empty());
+
+        let mut config = AdvancedCleanupConfig::aggressive();
+        config.base.dry_run = true;
+        let cleaner = AdvancedRentCleaner::with_config(offline_rpc_url(), Keypair::new(), config)
+            .with_mock_accounts(accounts.clone());
+        let result = cleaner.execute_cleanup().await.unwrap();
+        let total: u64 = accounts.iter().map(|a| a.base.lamports).sum();
+        assert_eq!(result.lamports_recovered, total);
+        assert_eq!(result.accounts_closed, 3);
+        assert_eq!(result.tokens_burned.get(&accounts[2].mint.unwrap()), Some(&42));
+        assert!(result.signatures.is_empty());
+        assert_eq!(result.dry_run_plan.unwrap().total_lamports(), total);
+    }
+
+    #[tokio::test]
+    async fn test_burn_dust_then_close_keeps_balances_above_threshold() {
+        use crate::testing::{MockRpc, TOKEN_ACCOUNT_RENT};
+        use spl_token::instruction::TokenInstruction;
+
+        let payer = Keypair::new();
+        let owner = payer.pubkey();
+        let mint = Pubkey::new_unique();
+        let kept = Pubkey::new_unique();
+        let dust = Pubkey::new_unique();
+        let empty = Pubkey::new_unique();
+        let rpc = MockRpc::new()
+            .with_account(mint, MockRpc::mint_account(6, 1_000_000))
+            .with_account(kept, MockRpc::token_account(&mint, &owner, 100))
+            .with_account(dust, MockRpc::token_account(&mint, &owner, 99))
+            .with_account(empty, MockRpc::token_account(&mint, &owner, 0));
+        let config = AdvancedCleanupConfig {
+            strategy: CleanupStrategy::BurnDustThenClose { keep: 100 },
+            ..Default::default()
+        };
+        let cleaner = AdvancedRentCleaner::from_client(rpc, payer, config);
+
+        let result = cleaner.execute_cleanup().await.unwrap();
+        assert_eq!(result.accounts_closed, 2);
+        assert_eq!(result.lamports_recovered, 2 * TOKEN_ACCOUNT_RENT);
+        assert_eq!(result.tokens_burned.get(&mint), Some(&99));
+        assert_eq!(result.kept_balances, HashMap::from([(kept, 100)]));
+
+        let sent = cleaner.client.sent_transactions();
+        assert_eq!(sent.len(), 1);
+        let keys = &sent[0].message.account_keys;
+        assert!(keys.contains(&dust) && keys.contains(&empty));
+        assert!(!keys.contains(&kept));
+        let burns: Vec<_> = sent[0]
+            .message
+            .instructions
+            .iter()
+            .filter_map(|ix| match TokenInstruction::unpack(&ix.data) {
+                Ok(TokenInstruction::Burn { amount }) => {
+                    Some((keys[ix.accounts[0] as usize], amount))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(burns, vec![(dust, 99)]);
+    }
+
+    #[tokio::test]
+    async fn test_multi_wallet_cleanup_aggregates() {
+        use crate::testing::{MockRpc, TOKEN_ACCOUNT_RENT};
+
+        let mut cleaners = Vec::new();
+        let mut wallets = Vec::new();
+        for empty_accounts in [1, 2] {
+            let payer = Keypair::new();
+            let owner = payer.pubkey();
+            let mint = Pubkey::new_unique();
+            let mut rpc = MockRpc::new().with_account(mint, MockRpc::mint_account(6, 0));
+            for _ in 0..empty_accounts {
+                rpc = rpc.with_account(
+                    Pubkey::new_unique(),
+                    MockRpc::token_account(&mint, &owner, 0),
+                );
+            }
+            wallets.push(owner);
+            cleaners.push(AdvancedRentCleaner::from_client(
+                rpc,
+                payer,
+                AdvancedCleanupConfig::default(),
+            ));
+        }
+
+        let multi = MultiWalletCleaner::from_cleaners(cleaners);
+        assert_eq!(multi.wallets(), wallets);
+
+        let result = multi.execute_cleanup().await;
+
+        assert!(result.failed_wallets.is_empty());
+        assert_eq!(result.per_wallet.len(), 2);
+        assert_eq!(result.per_wallet[0].0, wallets[0]);
+        assert_eq!(result.per_wallet[0].1.accounts_closed, 1);
+        assert_eq!(result.per_wallet[1].1.accounts_closed, 2);
+        assert_eq!(result.combined.accounts_closed, 3);
+        assert_eq!(result.combined.lamports_recovered, 3 * TOKEN_ACCOUNT_RENT);
+        assert_eq!(result.combined.signatures.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_program_accounts_scan_uses_data_slice() {
+        use crate::rpc::TOKEN_ACCOUNT_SLICE_LEN;
+        use crate::testing::{MockRpc, TOKEN_ACCOUNT_RENT};
+        use solana_account_decoder::UiDataSliceConfig;
+
+        let payer = Keypair::new();
+        let owner = payer.pubkey();
+        let mint = Pubkey::new_unique();
+        let empty = Pubkey::new_unique();
+        let rpc = MockRpc::new()
+            .with_account(mint, MockRpc::mint_account(6, 10))
+            .with_account(empty, MockRpc::token_account(&mint, &owner, 0))
+            .with_account(Pubkey::new_unique(), MockRpc::token_account(&mint, &owner, 10));
+        let config = RentCleanerConfig {
+            token_account_scan: TokenAccountScan::ProgramAccounts,
+            ..Default::default()
+        };
+        let cleaner = RentCleaner::from_client_with_config(rpc, payer, config);
+
+        let accounts = cleaner.find_empty_token_accounts().await.unwrap();
+
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].address, empty);
+        assert_eq!(accounts[0].lamports, TOKEN_ACCOUNT_RENT);
+
+        let requests = cleaner.client.program_account_requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0].account_config.data_slice,
+            Some(UiDataSliceConfig { offset: 0, length: TOKEN_ACCOUNT_SLICE_LEN })
+        );
+        // Funded accounts are filtered out by the server
+        let filters = requests[0].filters.as_ref().unwrap();
+        assert_eq!(filters.len(), 3);
+        assert_eq!(filters[2], zero_amount_filter());
+    }
+
+    #[test]
+    fn test_cleanup_enums_parse_and_serde_round_trip() {
+        for strategy in [
+            CleanupStrategy::EmptyOnly,
+            CleanupStrategy::BelowDustThreshold,
+            CleanupStrategy::BurnAndClose,
+            CleanupStrategy::AggregateAndClose,
+            CleanupStrategy::BurnDustThenClose { keep: 1_000 },
+        ] {
+            assert_eq!(strategy.to_string().parse::<CleanupStrategy>().unwrap(), strategy);
+            let json = serde_json::to_string(&strategy).unwrap();
+            assert_eq!(serde_json::from_str::<CleanupStrategy>(&json).unwrap(), strategy);
+        }
+
+        for priority in [
+            CleanupPriority::HighValue,
+            CleanupPriority::QuickWins,
+            CleanupPriority::ByMint,
+            CleanupPriority::OldestFirst,
+        ] {
+            assert_eq!(priority.to_string().parse::<CleanupPriority>().unwrap(), priority);
+            let json = serde_json::to_string(&priority).unwrap();
+            assert_eq!(serde_json::from_str::<CleanupPriority>(&json).unwrap(), priority);
+        }
+
+        assert_eq!(
+            serde_json::to_string(&CleanupStrategy::BurnAndClose).unwrap(),
+            "\"burn_and_close\""
+        );
+        assert_eq!(
+            CleanupStrategy::try_from("BurnAndClose").unwrap(),
+            CleanupStrategy::BurnAndClose
+        );
+        assert_eq!("Quick-Wins".parse::<CleanupPriority>().unwrap(), CleanupPriority::QuickWins);
+        assert_eq!(
+            "Burn-Dust-Then-Close: 50".parse::<CleanupStrategy>().unwrap(),
+            CleanupStrategy::BurnDustThenClose { keep: 50 }
+        );
+        assert!("burn_dust_then_close".parse::<CleanupStrategy>().is_err());
+        assert!("burn_and_close:50".parse::<CleanupStrategy>().is_err());
+
+        let err = "nuke_everything".parse::<CleanupStrategy>().unwrap_err();
+        assert!(err.to_string().contains("empty_only"));
+        assert!(serde_json::from_str::<CleanupPriority>("\"soonest\"").is_err());
+    }
+
+    /// Swaps at a fixed 100x ratio and records how many cleanup
+    /// transactions had been sent when each swap ran.
+    #[cfg(feature = "jupiter")]
+    struct MockSwapper<'a> {
+        rpc: &'a crate::testing::MockRpc,
+        no_route: Pubkey,
+        sends_before_swap: std::sync::Mutex<Vec<usize>>,
+    }
+
+    #[cfg(feature = "jupiter")]
+    #[async_trait::async_trait]
+    impl crate::jupiter::QuoteProvider for MockSwapper<'_> {
+        async fn quote(
+            &self,
+            input_mint: &str,
+            output_mint: &str,
+            amount: u64,
+            slippage_bps: u16,
+        ) -> Result<crate::jupiter::QuoteResponse> {
+            if input_mint == self.no_route.to_string() {
+                return Err(ToolkitError::JupiterError("COULD_NOT_FIND_ANY_ROUTE".to_string()));
+            }
+            Ok(crate::jupiter::QuoteResponse {
+                input_mint: input_mint.to_string(),
+                in_amount: amount,
+                output_mint: output_mint.to_string(),
+                out_amount: amount * 100,
+                other_amount_threshold: amount * 99,
+                swap_mode: "ExactIn".to_string(),
+                slippage_bps,
+                price_impact_pct: "0".to_string(),
+                route_plan: Vec::new(),
+                context_slot: None,
+                time_taken: None,
+            })
+        }
+    }
+
+    #[cfg(feature = "jupiter")]
+    #[async_trait::async_trait]
+    impl SwapProvider for MockSwapper<'_> {
+        async fn execute_swap(
+            &self,
+            _wallet: &Keypair,
+            _quote: crate::jupiter::QuoteResponse,
+        ) -> Result<Signature> {
+            let sent = self.rpc.sent_transactions().len();
+            self.sends_before_swap.lock().unwrap().push(sent);
+            Ok(Signature::new_unique())
+        }
+    }
+
+    #[cfg(feature = "jupiter")]
+    #[tokio::test]
+    async fn test_reclaim_and_consolidate_cleans_before_swapping() {
+        use crate::testing::{MockRpc, TOKEN_ACCOUNT_RENT};
+        use std::str::FromStr;
+
+        let payer = Keypair::new();
+        let owner = payer.pubkey();
+        let sol_mint = Pubkey::from_str(JupiterClient::SOL_MINT).unwrap();
+        let (mint_a, mint_b, mint_c) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (empty, dust_a, dust_b, dust_c, large, wsol) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+
+        let rpc = MockRpc::new()
+            .with_account(mint_a, MockRpc::mint_account(6, 0))
+            .with_account(mint_b, MockRpc::mint_account(6, 0))
+            .with_account(mint_c, MockRpc::mint_account(6, 0))
+            .with_account(empty, MockRpc::token_account(&mint_a, &owner, 0))
+            .with_account(dust_a, MockRpc::token_account(&mint_a, &owner, 500))
+            .with_account(dust_b, MockRpc::token_account(&mint_b, &owner, 20))
+            .with_account(dust_c, MockRpc::token_account(&mint_c, &owner, 300))
+            .with_account(large, MockRpc::token_account(&mint_a, &owner, 5_000_000))
+            .with_account(wsol, MockRpc::token_account(&sol_mint, &owner, 700));
+        let cleaner = AdvancedRentCleaner::from_client(
+            rpc,
+            payer,
+            AdvancedCleanupConfig::default().with_dust_threshold_ui(0.01),
+        );
+        let swapper = MockSwapper {
+            rpc: &cleaner.client,
+            no_route: mint_c,
+            sends_before_swap: std::sync::Mutex::new(Vec::new()),
+        };
+
+        let result = cleaner
+            .reclaim_and_consolidate(&swapper, JupiterClient::SOL_MINT)
+            .await
+            .unwrap();
+
+        // The empty account was closed in one transaction before the swap
+        assert_eq!(result.cleanup.accounts_closed, 1);
+        assert_eq!(*swapper.sends_before_swap.lock().unwrap(), vec![1]);
+
+        assert_eq!(result.swaps.len(), 1);
+        assert_eq!(result.swaps[0].account, dust_a);
+        assert_eq!(result.swaps[0].mint, mint_a);
+        assert_eq!(result.swaps[0].out_amount, 50_000);
+        assert_eq!(result.below_threshold, vec![dust_b]);
+        assert_eq!(result.failed_swaps.len(), 1);
+        assert_eq!(result.failed_swaps[0].0, dust_c);
+
+        assert_eq!(result.total_output(), 50_000);
+        assert_eq!(result.net_lamports(), Some(TOKEN_ACCOUNT_RENT + 50_000));
+    }
+
+    #[tokio::test]
+    async fn test_checkpointed_cleanup_resumes_after_crash() {
+        use crate::testing::MockRpc;
+
+        let path = std::env::temp_dir()
+            .join(format!("pipkit-checkpoint-{}.json", Pubkey::new_unique()));
+        let payer = Keypair::new();
+        let owner = payer.pubkey();
+        let mint = Pubkey::new_unique();
+        let accounts: Vec<Pubkey> = (0..5).map(|_| Pubkey::new_unique()).collect();
+        let rpc = || {
+            accounts.iter().fold(
+                MockRpc::new().with_account(mint, MockRpc::mint_account(6, 0)),
+                |rpc, address| {
+                    rpc.with_account(*address, MockRpc::token_account(&mint, &owner, 0))
+                },
+            )
+        };
+        let config = || AdvancedCleanupConfig::conservative().with_batch_size(2);
+
+        // First run: the second batch fails and the run aborts
+        let first = AdvancedRentCleaner::from_client(rpc(), payer.insecure_clone(), config());
+        let scanned = first.scan_accounts().await.unwrap();
+        let first_batch: Vec<Pubkey> = scanned[..2].iter().map(|a| a.base.address).collect();
+        first.client.fail_sends_touching(scanned[2].base.address, "node crashed");
+        assert!(first.execute_cleanup_with_checkpoint(&path).await.is_err());
+
+        let checkpoint = CleanupCheckpoint::load_or_new(&path, &owner).unwrap();
+        assert_eq!(checkpoint.closed.len(), 2);
+        assert!(first_batch.iter().all(|a| checkpoint.is_closed(a)));
+
+        // Second run: only the three remaining accounts are closed
+        let second = AdvancedRentCleaner::from_client(rpc(), payer, config());
+        let result = second.execute_cleanup_with_checkpoint(&path).await.unwrap();
+        assert_eq!(result.accounts_closed, 3);
+
+        let sent = second.client.sent_transactions();
+        assert_eq!(sent.len(), 2);
+        for tx in &sent {
+            assert!(first_batch.iter().all(|a| !tx.message.account_keys.contains(a)));
+        }
+
+        let checkpoint = CleanupCheckpoint::load_or_new(&path, &owner).unwrap();
+        assert_eq!(checkpoint.closed.len(), 5);
+        assert!(accounts.iter().all(|a| checkpoint.is_closed(a)));
+
+        // A checkpoint for another wallet is rejected
+        assert!(CleanupCheckpoint::load_or_new(&path, &Pubkey::new_unique()).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_by_mint_batches_never_mix_mints() {
+        use crate::testing::MockRpc;
+        use std::collections::HashSet;
+
+        let payer = Keypair::new();
+        let owner = payer.pubkey();
+        let mints: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        // 3, 1 and 4 accounts per mint
+        let accounts: Vec<(Pubkey, Pubkey)> = [(0, 3), (1, 1), (2, 4)]
+            .into_iter()
+            .flat_map(|(i, count)| (0..count).map(move |_| (Pubkey::new_unique(), i)))
+            .map(|(address, i)| (address, mints[i]))
+            .collect();
+        let rpc = mints.iter().fold(MockRpc::new(), |rpc, mint| {
+            rpc.with_account(*mint, MockRpc::mint_account(6, 0))
+        });
+        let rpc = accounts.iter().fold(rpc, |rpc, (address, mint)| {
+            rpc.with_account(*address, MockRpc::token_account(mint, &owner, 0))
+        });
+        let mut config = AdvancedCleanupConfig::conservative().with_batch_size(2);
+        config.priority = CleanupPriority::ByMint;
+        let cleaner = AdvancedRentCleaner::from_client(rpc, payer, config);
+        let mint_of = |address: &str| {
+            let address: Pubkey = address.parse().unwrap();
+            accounts.iter().find(|(a, _)| *a == address).unwrap().1
+        };
+
+        // 2 + 1, 1 and 2 + 2
+        let plan = cleaner.build_cleanup_plan().await.unwrap();
+        assert_eq!(plan.batches.len(), 5);
+        for batch in &plan.batches {
+            let first = mint_of(&batch.accounts[0]);
+            assert!(batch.accounts.iter().all(|a| mint_of(a) == first), "{:?}", batch);
+        }
+
+        let result = cleaner.execute_cleanup().await.unwrap();
+        assert_eq!(result.accounts_closed, 8);
+        let sent = cleaner.client.sent_transactions();
+        assert_eq!(sent.len(), 5);
+        for tx in &sent {
+            let touched: HashSet<Pubkey> = accounts
+                .iter()
+                .filter(|(address, _)| tx.message.account_keys.contains(address))
+                .map(|(_, mint)| *mint)
+                .collect();
+            assert_eq!(touched.len(), 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_account_funded_after_scan_is_skipped() {
+        use crate::testing::MockRpc;
+
+        let payer = Keypair::new();
+        let owner = payer.pubkey();
+        let mint = Pubkey::new_unique();
+        let accounts: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let rpc = accounts.iter().fold(
+            MockRpc::new().with_account(mint, MockRpc::mint_account(6, 0)),
+            |rpc, address| rpc.with_account(*address, MockRpc::token_account(&mint, &owner, 0)),
+        );
+        let config = AdvancedCleanupConfig::conservative().with_batch_size(3);
+        let cleaner = AdvancedRentCleaner::from_client(rpc, payer, config);
+        let scanned = cleaner.scan_accounts().await.unwrap();
+        assert_eq!(scanned.len(), 3);
+
+        // Between scan and execute one account receives tokens, one is closed
+        let (funded, closed) = (scanned[0].base.address, scanned[1].base.address);
+        cleaner.client.set_account(funded, MockRpc::token_account(&mint, &owner, 42));
+        cleaner.client.remove_account(&closed);
+
+        let result = cleaner.cleanup_accounts(&scanned, OnChange::Skip, |_, _| Ok(())).await;
+        let result = result.unwrap();
+        assert_eq!(result.accounts_closed, 1);
+        assert!(result.failed_accounts.is_empty());
+        assert_eq!(result.skipped_accounts.len(), 2);
+        let reason = |address| {
+            let (_, reason) = result.skipped_accounts.iter().find(|(a, _)| *a == address).unwrap();
+            reason.clone()
+        };
+        assert!(reason(funded).starts_with("account no longer empty: balance is 42"));
+        assert_eq!(reason(closed), "account no longer exists");
+
+        // Only the unchanged account was sent
+        let sent = cleaner.client.sent_transactions();
+        assert_eq!(sent.len(), 1);
+        assert!(sent[0].message.account_keys.contains(&scanned[2].base.address));
+        assert!(!sent[0].message.account_keys.contains(&funded));
+    }
+
+    #[tokio::test]
+    async fn test_recover_until_closes_fewest_accounts() {
+        use crate::testing::MockRpc;
+
+        let payer = Keypair::new();
+        let owner = payer.pubkey();
+        let mint = Pubkey::new_unique();
+        let accounts: Vec<(Pubkey, u64)> =
+            (1..=5).map(|i| (Pubkey::new_unique(), i * 1_000_000)).collect();
+        let rpc = accounts.iter().fold(
+            MockRpc::new().with_account(mint, MockRpc::mint_account(6, 0)),
+            |rpc, &(address, lamports)| {
+                let mut account = MockRpc::token_account(&mint, &owner, 0);
+                account.lamports = lamports;
+                rpc.with_account(address, account)
+            },
+        );
+        let mut config = AdvancedCleanupConfig::conservative().with_batch_size(1);
+        config.priority = CleanupPriority::OldestFirst;
+        let cleaner = AdvancedRentCleaner::from_client(rpc, payer, config);
+
+        // 5M + 4M meets 8M; the three smaller accounts stay open
+        let result = cleaner.recover_until(8_000_000).await.unwrap();
+        assert_eq!(result.accounts_closed, 2);
+        assert_eq!(result.lamports_recovered, 9_000_000);
+        let sent = cleaner.client.sent_transactions();
+        assert_eq!(sent.len(), 2);
+        for (address, lamports) in &accounts {
+            let closed = sent.iter().any(|tx| tx.message.account_keys.contains(address));
+            assert_eq!(closed, *lamports >= 4_000_000, "{} lamports", lamports);
+        }
+
+        let result = cleaner.recover_until(0).await.unwrap();
+        assert_eq!(result.accounts_closed, 0);
+    }
+
+    #[tokio::test]
+    async fn test_recheck_uses_current_balances_and_aborts_approved_plans() {
+        use crate::testing::MockRpc;
+
+        let payer = Keypair::new();
+        let owner = payer.pubkey();
+        let mint = Pubkey::new_unique();
+        let (drained, unchanged) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let rpc = MockRpc::new()
+            .with_account(mint, MockRpc::mint_account(6, 0))
+            .with_account(drained, MockRpc::token_account(&mint, &owner, 10))
+            .with_account(unchanged, MockRpc::token_account(&mint, &owner, 10));
+        let config = AdvancedCleanupConfig {
+            strategy: CleanupStrategy::AggregateAndClose,
+            ..Default::default()
+        };
+        let cleaner = AdvancedRentCleaner::from_client(rpc, payer, config);
+        let scanned = cleaner.scan_accounts().await.unwrap();
+        assert_eq!(scanned.len(), 2);
+
+        // Part of one balance leaves between scan and send
+        cleaner.client.set_account(drained, MockRpc::token_account(&mint, &owner, 4));
+
+        let approved = cleaner.cleanup_accounts(&scanned, OnChange::Abort, |_, _| Ok(())).await;
+        assert!(approved.unwrap_err().to_string().contains("changed on-chain"));
+        assert!(cleaner.client.sent_transactions().is_empty());
+
+        let result = cleaner.cleanup_accounts(&scanned, OnChange::Skip, |_, _| Ok(())).await;
+        let result = result.unwrap();
+        assert_eq!(result.accounts_closed, 2);
+        assert_eq!(result.tokens_burned[&mint], 14);
+    }
+
+    #[tokio::test]
+    async fn test_execute_plan_rejects_changed_accounts() {
+        use crate::testing::MockRpc;
+
+        let payer = Keypair::new();
+        let owner = payer.pubkey();
+        let mint = Pubkey::new_unique();
+        let accounts: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let rpc = accounts.iter().fold(
+            MockRpc::new().with_account(mint, MockRpc::mint_account(6, 0)),
+            |rpc, address| rpc.with_account(*address, MockRpc::token_account(&mint, &owner, 0)),
+        );
+        let config = AdvancedCleanupConfig::conservative().with_batch_size(2);
+        let cleaner = AdvancedRentCleaner::from_client(rpc, payer, config);
+
+        let plan = cleaner.build_cleanup_plan().await.unwrap();
+        assert_eq!(plan.accounts.len(), 3);
+        assert_eq!(plan.batches.len(), 2);
+        assert_eq!(plan.mints(), vec![mint.to_string()]);
+        let json = serde_json::to_string(&plan).unwrap();
+        let approved: CleanupPlan = serde_json::from_str(&json).unwrap();
+        assert_eq!(approved, plan);
+
+        // A token arrives in one of the accounts after approval
+        cleaner
+            .client
+            .set_account(accounts[1], MockRpc::token_account(&mint, &owner, 7));
+        let err = cleaner.execute_plan(&approved).await.unwrap_err();
+        assert!(err.to_string().contains(&accounts[1].to_string()));
+
+        // An account closed after approval
+        cleaner
+            .client
+            .set_account(accounts[1], MockRpc::token_account(&mint, &owner, 0));
+        cleaner.client.remove_account(&accounts[2]);
+        assert!(cleaner.execute_plan(&approved).await.is_err());
+        assert!(cleaner.client.sent_transactions().is_empty());
+
+        cleaner
+            .client
+            .set_account(accounts[2], MockRpc::token_account(&mint, &owner, 0));
+        let result = cleaner.execute_plan(&approved).await.unwrap();
+        assert_eq!(result.accounts_closed, 3);
+        assert_eq!(result.lamports_recovered, plan.estimated_recovery);
+        assert_eq!(cleaner.client.sent_transactions().len(), 2);
+
+        // A plan whose instructions no longer match the configuration
+        let mut tampered = approved.clone();
+        tampered.batches[0].instruction_hash = Hash::new_unique().to_string();
+        assert!(cleaner.execute_plan(&tampered).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_scan_accounts_filters_server_side() {
+        use crate::testing::MockRpc;
+
+        let payer = Keypair::new();
+        let owner = payer.pubkey();
+        let (mint, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let empty = Pubkey::new_unique();
+        let rpc = MockRpc::new()
+            .with_account(mint, MockRpc::mint_account(6, 10))
+            .with_account(other, MockRpc::mint_account(6, 10))
+            .with_account(empty, MockRpc::token_account(&mint, &owner, 0))
+            .with_account(Pubkey::new_unique(), MockRpc::token_account(&mint, &owner, 10))
+            .with_account(Pubkey::new_unique(), MockRpc::token_account(&other, &owner, 0));
+        let mut config = AdvancedCleanupConfig::default().include_mints(vec![mint]);
+        config.strategy = CleanupStrategy::EmptyOnly;
+        config.base.token_account_scan = TokenAccountScan::ProgramAccounts;
+        let cleaner = AdvancedRentCleaner::from_client(rpc, payer, config);
+
+        let filters = cleaner.scan_config().filters.unwrap();
+        assert_eq!(
+            filters,
+            vec![
+                RpcFilterType::DataSize(spl_token::state::Account::LEN as u64),
+                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(32, owner.as_ref())),
+                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(64, &[0; 8])),
+                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, mint.as_ref())),
+            ]
+        );
+
+        let accounts = cleaner.scan_accounts().await.unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].base.address, empty);
+        let requests = cleaner.client.program_account_requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].filters.as_ref(), Some(&filters));
+
+        // Strategy comparison needs every account, so it is not narrowed
+        cleaner.compare_strategies().await.unwrap();
+        let requests = cleaner.client.program_account_requests();
+        assert_eq!(requests[1].filters.as_ref().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_compare_strategies() {
+        use crate::testing::{MockRpc, TOKEN_ACCOUNT_RENT};
+
+        let payer = Keypair::new();
+        let owner = payer.pubkey();
+        let mint = Pubkey::new_unique();
+        let rpc = MockRpc::new()
+            .with_account(mint, MockRpc::mint_account(6, 0))
+            .with_account(Pubkey::new_unique(), MockRpc::token_account(&mint, &owner, 0))
+            .with_account(Pubkey::new_unique(), MockRpc::token_account(&mint, &owner, 500))
+            .with_account(Pubkey::new_unique(), MockRpc::token_account(&mint, &owner, 5_000_000));
+        let cleaner = AdvancedRentCleaner::from_client(
+            rpc,
+            payer,
+            AdvancedCleanupConfig::default().with_dust_threshold_ui(0.01),
+        );
+
+        let estimates = cleaner.compare_strategies().await.unwrap();
+        let empty_only = estimates[&CleanupStrategy::EmptyOnly];
+        let dust = estimates[&CleanupStrategy::BelowDustThreshold];
+        let burn = estimates[&CleanupStrategy::BurnAndClose];
+
+        assert_eq!(estimates.len(), 3);
+        assert_eq!(empty_only.accounts_to_close, 1);
+        assert_eq!(empty_only.lamports_recoverable, TOKEN_ACCOUNT_RENT);
+        assert_eq!((dust.accounts_to_close, dust.accounts_to_burn), (2, 1));
+        assert_eq!((burn.accounts_to_close, burn.accounts_to_burn), (3, 2));
+        assert!(burn.lamports_recoverable >= empty_only.lamports_recoverable);
+        assert!(dust.lamports_recoverable >= empty_only.lamports_recoverable);
+
+        // The estimate for the configured strategy matches a real scan
+        let scanned = cleaner.scan_accounts().await.unwrap();
+        assert_eq!(scanned.len(), empty_only.accounts_to_close);
+    }
+
+    #[tokio::test]
+    async fn test_execution_policy_blocks_failed_simulation() {
+        use crate::testing::MockRpc;
+        use solana_sdk::transaction::TransactionError;
+
+        let payer = Keypair::new();
+        let owner = payer.pubkey();
+        let mint = Pubkey::new_unique();
+        let rpc = MockRpc::new()
+            .with_account(mint, MockRpc::mint_account(6, 0))
+            .with_account(Pubkey::new_unique(), MockRpc::token_account(&mint, &owner, 0))
+            .with_account(Pubkey::new_unique(), MockRpc::token_account(&mint, &owner, 0));
+        rpc.fail_simulations(TransactionError::AccountInUse);
+
+        let cleaner = AdvancedRentCleaner::from_client(rpc, payer, Default::default())
+            .with_execution_policy(ExecutionPolicy::strict());
+        let result = cleaner.execute_cleanup().await.unwrap();
+        assert_eq!(result.accounts_closed, 0);
+        assert_eq!(result.failed_accounts.len(), 2);
+        assert!(cleaner.client.sent_transactions().is_empty());
+
+        // A dry-run policy plans without sending
+        let cleaner = cleaner.with_execution_policy(ExecutionPolicy::default().with_dry_run(true));
+        let result = cleaner.execute_cleanup().await.unwrap();
+        assert_eq!(result.dry_run_plan.unwrap().accounts_to_close().len(), 2);
+        assert!(cleaner.client.sent_transactions().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rent_destination_override() {
+        use crate::testing::MockRpc;
+
+        let payer = Keypair::new();
+        let owner = payer.pubkey();
+        let mint = Pubkey::new_unique();
+        let (empty, treasury) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let rpc = || {
+            MockRpc::new()
+                .with_account(mint, MockRpc::mint_account(6, 0))
+                .with_account(empty, MockRpc::token_account(&mint, &owner, 0))
+        };
+        let base = RentCleanerConfig {
+            rent_destination: Some(treasury),
+            ..Default::default()
+        };
+
+        let simple =
+            RentCleaner::from_client_with_config(rpc(), payer.insecure_clone(), base.clone());
+        let ix = simple.close_account_ix(&empty).unwrap();
+        assert_eq!(ix.accounts[1].pubkey, treasury);
+        assert_eq!(ix.accounts[2].pubkey, owner);
+
+        let config = AdvancedCleanupConfig { base, ..Default::default() };
+        let cleaner = AdvancedRentCleaner::from_client(rpc(), payer.insecure_clone(), config);
+        let accounts = cleaner.scan_accounts().await.unwrap();
+        let instructions = cleaner.build_cleanup_instructions(&accounts).unwrap();
+        assert_eq!(instructions[0].accounts[1].pubkey, treasury);
+        assert_eq!(instructions[0].accounts[2].pubkey, owner);
+        assert_eq!(cleaner.execute_cleanup().await.unwrap().accounts_closed, 1);
+
+        // Rent can't be sent to a token account
+        let config = AdvancedCleanupConfig {
+            base: RentCleanerConfig {
+                rent_destination: Some(empty),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let cleaner = AdvancedRentCleaner::from_client(rpc(), payer, config);
+        let err = cleaner.execute_cleanup().await.unwrap_err();
+        assert!(err.to_string().contains("is not a system account"), "{}", err);
+        assert!(cleaner.client.sent_transactions().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_test_networks_only_blocks_mainnet() {
+        use crate::testing::MockRpc;
+
+        let payer = Keypair::new();
+        let owner = payer.pubkey();
+        let mint = Pubkey::new_unique();
+        let rpc = || {
+            MockRpc::new()
+                .with_account(mint, MockRpc::mint_account(6, 0))
+                .with_account(Pubkey::new_unique(), MockRpc::token_account(&mint, &owner, 0))
+        };
+        let guarded = || {
+            let mut config = AdvancedCleanupConfig::default();
+            config.base.test_networks_only = true;
+            config
+        };
+
+        let mainnet = AdvancedRentCleaner::from_client(rpc(), payer.insecure_clone(), guarded())
+            .with_network(Network::Mainnet);
+        let err = mainnet.execute_cleanup().await.unwrap_err();
+        assert!(matches!(err, ToolkitError::ConfigError(_)));
+        assert!(mainnet.client.sent_transactions().is_empty());
+
+        // An undeclared network is treated as possibly mainnet
+        let unknown = AdvancedRentCleaner::from_client(rpc(), payer.insecure_clone(), guarded());
+        assert!(unknown.execute_cleanup().await.is_err());
+
+        let devnet = AdvancedRentCleaner::from_client(rpc(), payer.insecure_clone(), guarded())
+            .with_network(Network::Devnet);
+        assert_eq!(devnet.execute_cleanup().await.unwrap().accounts_closed, 1);
+
+        // The flag is off by default
+        let unguarded =
+            AdvancedRentCleaner::from_client(rpc(), payer.insecure_clone(), Default::default())
+                .with_network(Network::Mainnet);
+        assert_eq!(unguarded.execute_cleanup().await.unwrap().accounts_closed, 1);
+
+        let config = RentCleanerConfig {
+            test_networks_only: true,
+            ..Default::default()
+        };
+        let simple = RentCleaner::from_client_with_config(rpc(), payer, config)
+            .with_network(Network::Mainnet);
+        assert!(simple.close_empty_token_accounts().await.is_err());
+        assert!(simple.client.sent_transactions().is_empty());
+    }
+
+    #[test]
+    fn test_new_on_detects_network() {
+        let cleaner = RentCleaner::new_on(Network::Devnet, Keypair::new());
+        assert_eq!(cleaner.network, Some(Network::Devnet));
+        let cleaner = RentCleaner::new("https://api.mainnet-beta.solana.com", Keypair::new());
+        assert_eq!(cleaner.network, Some(Network::Mainnet));
+        let cleaner = AdvancedRentCleaner::new("https://rpc.example.com", Keypair::new());
+        assert_eq!(cleaner.network, None);
+    }
 }
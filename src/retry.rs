@@ -0,0 +1,157 @@
+//! Retry helpers for transient failures.
+//!
+//! Wraps fallible async operations with a bounded number of retries and
+//! exponential backoff. Only errors reported as retryable by
+//! [`ToolkitError::is_retryable`](crate::ToolkitError::is_retryable) are retried.
+
+use std::future::Future;
+use std::time::Duration;
+
+use crate::Result;
+
+/// Retry policy with exponential backoff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryConfig {
+    /// Number of retries after the first attempt.
+    pub max_retries: u32,
+    /// Delay before the first retry.
+    pub initial_backoff: Duration,
+    /// Upper bound for the delay between retries.
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(2),
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Create a policy with the given number of retries and default backoff.
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            ..Default::default()
+        }
+    }
+
+    /// Create a policy that never retries.
+    pub fn none() -> Self {
+        Self::new(0)
+    }
+
+    /// Set the initial backoff delay.
+    pub fn with_backoff(mut self, initial_backoff: Duration) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    /// Set the maximum backoff delay.
+    pub fn with_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Delay before the given retry (0-based), doubling each time.
+    pub fn backoff_for(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry);
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+/// Run an async operation, retrying retryable errors according to `config`.
+///
+/// # Example
+/// ```ignore
+/// let balance = retry(&RetryConfig::new(3), || client.get_balance(&pubkey)).await?;
+/// ```
+pub async fn retry<T, F, Fut>(config: &RetryConfig, mut operation: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempt = 0;
+    loop {
+        match operation().await {
+            Ok(value) => return Ok(value),
+            Err(e) if e.is_retryable() && attempt < config.max_retries => {
+                tokio::time::sleep(config.backoff_for(attempt)).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ToolkitError;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn fast_config(max_retries: u32) -> RetryConfig {
+        RetryConfig::new(max_retries).with_backoff(Duration::from_millis(1))
+    }
+
+    #[tokio::test]
+    async fn test_retry_succeeds_after_transient_failures() {
+        let calls = AtomicU32::new(0);
+
+        let result = retry(&fast_config(3), || async {
+            if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                Err(ToolkitError::NetworkError("connection reset".to_string()))
+            } else {
+                Ok(42u64)
+            }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_does_not_retry_permanent_errors() {
+        let calls = AtomicU32::new(0);
+
+        let result: Result<u64> = retry(&fast_config(3), || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(ToolkitError::account_not_found("missing"))
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retry_gives_up_after_max_retries() {
+        let calls = AtomicU32::new(0);
+
+        let result: Result<u64> = retry(&fast_config(2), || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(ToolkitError::Timeout("get_balance".to_string()))
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_backoff_is_capped() {
+        let config = RetryConfig::new(10)
+            .with_backoff(Duration::from_millis(100))
+            .with_max_backoff(Duration::from_millis(500));
+
+        assert_eq!(config.backoff_for(0), Duration::from_millis(100));
+        assert_eq!(config.backoff_for(1), Duration::from_millis(200));
+        assert_eq!(config.backoff_for(5), Duration::from_millis(500));
+    }
+}
@@ -0,0 +1,1213 @@
+//! RPC abstraction.
+//!
+//! [`SolanaRpc`] covers the handful of RPC methods the crate relies on, so
+//! helper types can be driven by a real [`RpcClient`] or by
+//! [`MockRpc`](crate::testing::MockRpc) in unit tests.
+//!
+//! [`ClientConfig`] builds an [`RpcClient`] that sends custom headers, for
+//! providers that authenticate with an API key header:
+//!
+//! ```rust,ignore
+//! let client = ClientConfig::new("https://rpc.example.com")
+//!     .with_header("x-api-key", api_key)
+//!     .build()?;
+//! let cleaner = RentCleaner::from_client(client, payer);
+//! ```
+//!
+//! [`Network`] names the public clusters, for the `new_on` constructors:
+//!
+//! ```rust,ignore
+//! let cleaner = RentCleaner::new_on(Network::Devnet, payer);
+//! ```
+//!
+//! Helpers bound every RPC call with [`TimeoutRpc`], failing a stalled call
+//! with [`ToolkitError::Timeout`] after [`DEFAULT_RPC_TIMEOUT`]:
+//!
+//! ```rust,ignore
+//! let cleaner = RentCleaner::new_on(Network::Devnet, payer).with_timeout(Duration::from_secs(10));
+//! ```
+
+use std::collections::{hash_map::Entry, HashMap};
+use std::future::Future;
+use std::ops::Deref;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::header::{HeaderName, HeaderValue};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use solana_account_decoder::{UiAccount, UiAccountEncoding, UiDataSliceConfig};
+use solana_client::{
+    nonblocking::rpc_client::RpcClient,
+    rpc_client::RpcClientConfig,
+    rpc_config::{
+        RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSendTransactionConfig,
+        RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig, RpcTransactionConfig,
+    },
+    rpc_filter::{Memcmp, RpcFilterType},
+    rpc_request::RpcRequest,
+    rpc_client::GetConfirmedSignaturesForAddress2Config,
+    rpc_response::{
+        Response, RpcConfirmedTransactionStatusWithSignature, RpcKeyedAccount,
+        RpcPrioritizationFee, RpcSimulateTransactionResult,
+    },
+};
+use solana_rpc_client::http_sender::HttpSender;
+use solana_sdk::{
+    account::Account, commitment_config::CommitmentConfig, hash::Hash, program_pack::Pack,
+    pubkey::Pubkey, signature::Signature, transaction::Transaction,
+};
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, EncodedTransactionWithStatusMeta,
+    TransactionStatus, UiLoadedAddresses, UiTransactionEncoding,
+};
+
+use crate::token_utils::decode_token_account;
+use crate::{Result, ToolkitError};
+
+/// A Solana cluster with a well-known public RPC endpoint.
+///
+/// Parses from and serializes to `"mainnet"`, `"devnet"`, `"testnet"` and
+/// `"localnet"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Network {
+    /// Mainnet beta.
+    Mainnet,
+    /// Devnet.
+    Devnet,
+    /// Testnet.
+    Testnet,
+    /// A local `solana-test-validator`.
+    Localnet,
+}
+
+string_enum!(Network, "network", {
+    Mainnet => "mainnet",
+    Devnet => "devnet",
+    Testnet => "testnet",
+    Localnet => "localnet",
+});
+
+impl Network {
+    /// Public JSON-RPC endpoint.
+    pub fn rpc_url(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "https://api.mainnet-beta.solana.com",
+            Network::Devnet => "https://api.devnet.solana.com",
+            Network::Testnet => "https://api.testnet.solana.com",
+            Network::Localnet => "http://127.0.0.1:8899",
+        }
+    }
+
+    /// Public websocket endpoint.
+    pub fn ws_url(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "wss://api.mainnet-beta.solana.com",
+            Network::Devnet => "wss://api.devnet.solana.com",
+            Network::Testnet => "wss://api.testnet.solana.com",
+            Network::Localnet => "ws://127.0.0.1:8900",
+        }
+    }
+
+    /// Genesis hash identifying the cluster, `None` for `Localnet`, whose
+    /// genesis is created fresh by each validator.
+    pub fn genesis_hash(&self) -> Option<Hash> {
+        let hash = match self {
+            Network::Mainnet => "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d",
+            Network::Devnet => "EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG",
+            Network::Testnet => "4uhcVJyU9pJkvQyS88uRDiswHXSCkY3zQawwpjk2NsNY",
+            Network::Localnet => return None,
+        };
+        Some(hash.parse().expect("known genesis hashes are valid"))
+    }
+
+    /// The public cluster with genesis hash `hash`, if any.
+    pub fn from_genesis_hash(hash: &Hash) -> Option<Network> {
+        [Network::Mainnet, Network::Devnet, Network::Testnet]
+            .into_iter()
+            .find(|network| network.genesis_hash().as_ref() == Some(hash))
+    }
+
+    /// Whether funds on this network have no real value.
+    pub fn is_test_network(&self) -> bool {
+        !matches!(self, Network::Mainnet)
+    }
+
+    /// Best-effort guess of the network an RPC URL points at.
+    ///
+    /// Local hosts map to `Localnet`; otherwise the host name is checked for
+    /// `devnet`, `testnet` or `mainnet`, which covers the public endpoints
+    /// and most provider URLs. Returns `None` when the URL gives no hint.
+    pub fn from_rpc_url(url: &str) -> Option<Network> {
+        let url = reqwest::Url::parse(url).ok()?;
+        let host = url.host_str()?.to_ascii_lowercase();
+
+        if host == "localhost" || host == "127.0.0.1" || host == "0.0.0.0" || host == "[::1]" {
+            Some(Network::Localnet)
+        } else if host.contains("devnet") {
+            Some(Network::Devnet)
+        } else if host.contains("testnet") {
+            Some(Network::Testnet)
+        } else if host.contains("mainnet") {
+            Some(Network::Mainnet)
+        } else {
+            None
+        }
+    }
+}
+
+/// Bytes of a token account needed for balance parsing: mint, owner, amount.
+pub const TOKEN_ACCOUNT_SLICE_LEN: usize = 72;
+
+/// How to fetch a wallet's token accounts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TokenAccountScan {
+    /// `getTokenAccountsByOwner`, returning full account data.
+    #[default]
+    ByOwner,
+    /// `getProgramAccounts` with size and owner filters, returning only the
+    /// first [`TOKEN_ACCOUNT_SLICE_LEN`] bytes of each account. Much smaller
+    /// responses for wallets with thousands of token accounts.
+    ProgramAccounts,
+}
+
+/// `getProgramAccounts` config selecting `owner`'s SPL token accounts and
+/// slicing their data down to mint, owner and amount.
+pub fn token_accounts_by_owner_config(owner: &Pubkey) -> RpcProgramAccountsConfig {
+    RpcProgramAccountsConfig {
+        filters: Some(vec![
+            RpcFilterType::DataSize(spl_token::state::Account::LEN as u64),
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(32, owner.as_ref())),
+        ]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            data_slice: Some(UiDataSliceConfig {
+                offset: 0,
+                length: TOKEN_ACCOUNT_SLICE_LEN,
+            }),
+            commitment: None,
+            min_context_slot: None,
+        },
+        with_context: None,
+    }
+}
+
+/// `Memcmp` filter matching SPL token accounts whose amount is zero.
+pub fn zero_amount_filter() -> RpcFilterType {
+    RpcFilterType::Memcmp(Memcmp::new_base58_encoded(64, &0u64.to_le_bytes()))
+}
+
+/// [`token_accounts_by_owner_config`] narrowed to `owner`'s empty token
+/// accounts by [`zero_amount_filter`], so only closeable accounts are
+/// returned.
+pub fn empty_token_accounts_by_owner_config(owner: &Pubkey) -> RpcProgramAccountsConfig {
+    let mut config = token_accounts_by_owner_config(owner);
+    config.filters.get_or_insert_with(Vec::new).push(zero_amount_filter());
+    config
+}
+
+/// Fetch `owner`'s SPL token accounts using the given scan strategy.
+///
+/// With [`TokenAccountScan::ProgramAccounts`] the returned account data is
+/// truncated to [`TOKEN_ACCOUNT_SLICE_LEN`] bytes.
+pub async fn fetch_token_accounts<R: SolanaRpc + ?Sized>(
+    client: &R,
+    owner: &Pubkey,
+    scan: TokenAccountScan,
+) -> Result<Vec<(Pubkey, Account)>> {
+    match scan {
+        TokenAccountScan::ByOwner => {
+            client.get_token_accounts_by_owner(owner, &spl_token::id()).await
+        }
+        TokenAccountScan::ProgramAccounts => {
+            client
+                .get_program_accounts(&spl_token::id(), token_accounts_by_owner_config(owner))
+                .await
+        }
+    }
+}
+
+/// Convert an RPC token account into raw [`Account`] form.
+///
+/// Binary encodings are decoded directly. A `jsonParsed` SPL token account is
+/// repacked from the fields [`decode_token_account`] reads; delegate and
+/// native fields are not reconstructed.
+pub(crate) fn token_account_from_ui(account: &UiAccount) -> Option<Account> {
+    account.decode::<Account>().or_else(|| {
+        let parsed = decode_token_account(account)?;
+        let token_account = spl_token::state::Account {
+            mint: parsed.mint,
+            owner: parsed.owner,
+            amount: parsed.amount,
+            state: parsed.state,
+            ..Default::default()
+        };
+
+        let mut data = vec![0; spl_token::state::Account::LEN];
+        spl_token::state::Account::pack_into_slice(&token_account, &mut data);
+        Some(Account {
+            lamports: account.lamports,
+            data,
+            owner: account.owner.parse().ok()?,
+            executable: account.executable,
+            rent_epoch: account.rent_epoch,
+        })
+    })
+}
+
+/// Connection settings for building an [`RpcClient`].
+///
+/// Keys embedded in the URL path work with the URL alone; header-based keys
+/// go in `headers`.
+#[derive(Debug, Clone)]
+pub struct ClientConfig {
+    /// RPC endpoint URL.
+    pub url: String,
+    /// Extra headers sent with every request.
+    pub headers: HashMap<String, String>,
+    /// Commitment level.
+    pub commitment: CommitmentConfig,
+    /// Request timeout.
+    pub timeout: Duration,
+}
+
+impl ClientConfig {
+    /// Create a config for `url` with confirmed commitment and a 30s timeout.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            headers: HashMap::new(),
+            commitment: CommitmentConfig::confirmed(),
+            timeout: Duration::from_secs(30),
+        }
+    }
+
+    /// Add a header sent with every request.
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.insert(name.into(), value.into());
+        self
+    }
+
+    /// Set the commitment level.
+    pub fn with_commitment(mut self, commitment: CommitmentConfig) -> Self {
+        self.commitment = commitment;
+        self
+    }
+
+    /// Set the request timeout.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Build the client.
+    ///
+    /// Fails with [`ToolkitError::ConfigError`] if a header name or value is
+    /// not valid HTTP.
+    pub fn build(&self) -> Result<RpcClient> {
+        let mut headers = HttpSender::default_headers();
+        for (name, value) in &self.headers {
+            let name = HeaderName::from_bytes(name.as_bytes()).map_err(|e| {
+                ToolkitError::ConfigError(format!("Invalid header name {}: {}", name, e))
+            })?;
+            let value = HeaderValue::from_str(value).map_err(|e| {
+                ToolkitError::ConfigError(format!("Invalid value for header {}: {}", name, e))
+            })?;
+            headers.insert(name, value);
+        }
+
+        let http = reqwest::Client::builder()
+            .default_headers(headers)
+            .timeout(self.timeout)
+            .pool_idle_timeout(self.timeout)
+            .build()
+            .map_err(|e| ToolkitError::ConfigError(format!("Failed to build HTTP client: {}", e)))?;
+
+        Ok(RpcClient::new_sender(
+            HttpSender::new_with_client(&self.url, http),
+            RpcClientConfig::with_commitment(self.commitment),
+        ))
+    }
+}
+
+/// RPC methods used by the crate's helper types.
+#[async_trait]
+pub trait SolanaRpc: Send + Sync {
+    /// Get the lamport balance of an account.
+    async fn get_balance(&self, pubkey: &Pubkey) -> Result<u64>;
+
+    /// Get an account, or `None` if it does not exist.
+    async fn get_account(&self, pubkey: &Pubkey) -> Result<Option<Account>>;
+
+    /// Get an account with only the bytes selected by `slice` in its data.
+    async fn get_account_slice(
+        &self,
+        pubkey: &Pubkey,
+        slice: UiDataSliceConfig,
+    ) -> Result<Option<Account>>;
+
+    /// Get multiple accounts in a single call.
+    async fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> Result<Vec<Option<Account>>>;
+
+    /// Get the latest blockhash.
+    async fn get_latest_blockhash(&self) -> Result<Hash>;
+
+    /// Get the current block height, against which blockhash expiry
+    /// (`lastValidBlockHeight`) is measured.
+    async fn get_block_height(&self) -> Result<u64>;
+
+    /// Get the genesis hash, which identifies the cluster.
+    async fn get_genesis_hash(&self) -> Result<Hash>;
+
+    /// Send a transaction and wait for confirmation.
+    async fn send_and_confirm_transaction(&self, transaction: &Transaction) -> Result<Signature>;
+
+    /// Send a transaction without waiting for confirmation.
+    async fn send_transaction(&self, transaction: &Transaction) -> Result<Signature>;
+
+    /// Send a transaction without waiting for confirmation, using `config`
+    /// (e.g. to skip preflight).
+    async fn send_transaction_with_config(
+        &self,
+        transaction: &Transaction,
+        config: RpcSendTransactionConfig,
+    ) -> Result<Signature>;
+
+    /// Get the status of each signature, `None` for signatures the node
+    /// has not seen.
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<Option<TransactionStatus>>>;
+
+    /// Get all token accounts owned by `owner` under the given token program.
+    async fn get_token_accounts_by_owner(
+        &self,
+        owner: &Pubkey,
+        program_id: &Pubkey,
+    ) -> Result<Vec<(Pubkey, Account)>>;
+
+    /// Simulate a transaction without sending it.
+    async fn simulate_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<RpcSimulateTransactionResult>;
+
+    /// Simulate a transaction and return the post-simulation state of
+    /// `addresses` in the result's `accounts` field.
+    ///
+    /// Signatures are not verified and the blockhash is replaced, so
+    /// unsigned transactions can be inspected.
+    async fn simulate_transaction_with_accounts(
+        &self,
+        transaction: &Transaction,
+        addresses: &[Pubkey],
+    ) -> Result<RpcSimulateTransactionResult>;
+
+    /// Get accounts owned by a program, filtered and sliced per `config`.
+    async fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> Result<Vec<(Pubkey, Account)>>;
+
+    /// Compute units a confirmed transaction consumed, from its
+    /// `getTransaction` meta. `None` if the transaction is not found or the
+    /// node does not report usage.
+    async fn get_transaction_units_consumed(&self, signature: &Signature) -> Result<Option<u64>>;
+
+    /// Lamports a confirmed transaction cost its fee payer on top of what it
+    /// moved: the fee plus the rent of every account it created and left
+    /// open. Only new accounts holding data and funded to exactly their
+    /// rent-exempt minimum count as rent, so lamports sent to a new plain
+    /// wallet are not. `None` if the transaction is not found.
+    async fn get_transaction_costs(&self, signature: &Signature) -> Result<Option<u64>>;
+
+    /// Up to `limit` of the most recent signatures involving `address`,
+    /// newest first.
+    async fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        limit: usize,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>>;
+
+    /// Priority fees paid in recent slots by transactions that lock any of
+    /// `addresses` as writable, one entry per slot.
+    async fn get_recent_prioritization_fees(
+        &self,
+        addresses: &[Pubkey],
+    ) -> Result<Vec<RpcPrioritizationFee>>;
+}
+
+#[async_trait]
+impl SolanaRpc for RpcClient {
+    async fn get_balance(&self, pubkey: &Pubkey) -> Result<u64> {
+        Ok(RpcClient::get_balance(self, pubkey).await?)
+    }
+
+    async fn get_account(&self, pubkey: &Pubkey) -> Result<Option<Account>> {
+        Ok(self
+            .get_account_with_commitment(pubkey, self.commitment())
+            .await?
+            .value)
+    }
+
+    async fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        Ok(RpcClient::get_multiple_accounts(self, pubkeys).await?)
+    }
+
+    async fn get_latest_blockhash(&self) -> Result<Hash> {
+        Ok(RpcClient::get_latest_blockhash(self).await?)
+    }
+
+    async fn get_block_height(&self) -> Result<u64> {
+        Ok(RpcClient::get_block_height(self).await?)
+    }
+
+    async fn get_genesis_hash(&self) -> Result<Hash> {
+        Ok(RpcClient::get_genesis_hash(self).await?)
+    }
+
+    async fn send_and_confirm_transaction(&self, transaction: &Transaction) -> Result<Signature> {
+        Ok(RpcClient::send_and_confirm_transaction(self, transaction).await?)
+    }
+
+    async fn send_transaction(&self, transaction: &Transaction) -> Result<Signature> {
+        Ok(RpcClient::send_transaction(self, transaction).await?)
+    }
+
+    async fn send_transaction_with_config(
+        &self,
+        transaction: &Transaction,
+        config: RpcSendTransactionConfig,
+    ) -> Result<Signature> {
+        Ok(RpcClient::send_transaction_with_config(self, transaction, config).await?)
+    }
+
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<Option<TransactionStatus>>> {
+        Ok(RpcClient::get_signature_statuses(self, signatures).await?.value)
+    }
+
+    async fn get_account_slice(
+        &self,
+        pubkey: &Pubkey,
+        slice: UiDataSliceConfig,
+    ) -> Result<Option<Account>> {
+        let config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            data_slice: Some(slice),
+            commitment: Some(self.commitment()),
+            min_context_slot: None,
+        };
+        Ok(self.get_account_with_config(pubkey, config).await?.value)
+    }
+
+    async fn get_token_accounts_by_owner(
+        &self,
+        owner: &Pubkey,
+        program_id: &Pubkey,
+    ) -> Result<Vec<(Pubkey, Account)>> {
+        // Request base64 so raw account data can be decoded; the client's
+        // own helper asks for jsonParsed. Some providers ignore the requested
+        // encoding, so decoding falls back to the parsed form.
+        let config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(self.commitment()),
+            data_slice: None,
+            min_context_slot: None,
+        };
+
+        let response: Response<Vec<RpcKeyedAccount>> = self
+            .send(
+                RpcRequest::GetTokenAccountsByOwner,
+                json!([
+                    owner.to_string(),
+                    { "programId": program_id.to_string() },
+                    config
+                ]),
+            )
+            .await?;
+
+        response
+            .value
+            .into_iter()
+            .map(|keyed| {
+                let pubkey = keyed.pubkey.parse::<Pubkey>().map_err(|e| {
+                    ToolkitError::Custom(format!("Failed to parse pubkey: {}", e))
+                })?;
+                let account = token_account_from_ui(&keyed.account).ok_or_else(|| {
+                    ToolkitError::invalid_data(format!("Failed to decode account {}", pubkey))
+                })?;
+                Ok((pubkey, account))
+            })
+            .collect()
+    }
+
+    async fn simulate_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<RpcSimulateTransactionResult> {
+        Ok(RpcClient::simulate_transaction(self, transaction).await?.value)
+    }
+
+    async fn simulate_transaction_with_accounts(
+        &self,
+        transaction: &Transaction,
+        addresses: &[Pubkey],
+    ) -> Result<RpcSimulateTransactionResult> {
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            commitment: Some(self.commitment()),
+            accounts: Some(RpcSimulateTransactionAccountsConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                addresses: addresses.iter().map(|a| a.to_string()).collect(),
+            }),
+            ..RpcSimulateTransactionConfig::default()
+        };
+        Ok(self
+            .simulate_transaction_with_config(transaction, config)
+            .await?
+            .value)
+    }
+
+    async fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> Result<Vec<(Pubkey, Account)>> {
+        Ok(self.get_program_accounts_with_config(program_id, config).await?)
+    }
+
+    async fn get_transaction_units_consumed(&self, signature: &Signature) -> Result<Option<u64>> {
+        Ok(confirmed_transaction(self, signature)
+            .await?
+            .and_then(|tx| tx.meta)
+            .and_then(|meta| meta.compute_units_consumed.into()))
+    }
+
+    async fn get_transaction_costs(&self, signature: &Signature) -> Result<Option<u64>> {
+        let Some(tx) = confirmed_transaction(self, signature).await? else {
+            return Ok(None);
+        };
+        let (Some(meta), Some(decoded)) = (tx.meta, tx.transaction.decode()) else {
+            return Ok(None);
+        };
+
+        // Loaded addresses follow the static keys, writable first
+        let loaded: Option<UiLoadedAddresses> = meta.loaded_addresses.into();
+        let loaded_keys = loaded
+            .iter()
+            .flat_map(|loaded| loaded.writable.iter().chain(&loaded.readonly))
+            .filter_map(|key| key.parse::<Pubkey>().ok());
+        let (created, post_balances): (Vec<Pubkey>, Vec<u64>) = decoded
+            .message
+            .static_account_keys()
+            .iter()
+            .copied()
+            .chain(loaded_keys)
+            .zip(meta.pre_balances.iter().zip(&meta.post_balances))
+            .filter(|(_, (pre, post))| **pre == 0 && **post > 0)
+            .map(|(key, (_, post))| (key, *post))
+            .unzip();
+        if created.is_empty() {
+            return Ok(Some(meta.fee));
+        }
+
+        let accounts = RpcClient::get_multiple_accounts(self, &created).await?;
+        let mut minimums = HashMap::new();
+        for account in accounts.iter().flatten() {
+            let len = account.data.len();
+            if let Entry::Vacant(entry) = minimums.entry(len) {
+                entry.insert(self.get_minimum_balance_for_rent_exemption(len).await?);
+            }
+        }
+        let created: Vec<_> = post_balances.into_iter().zip(accounts).collect();
+        Ok(Some(meta.fee + created_account_rent(&created, |len| minimums[&len])))
+    }
+
+    async fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        limit: usize,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+        let config = GetConfirmedSignaturesForAddress2Config {
+            limit: Some(limit),
+            commitment: Some(self.commitment()),
+            ..Default::default()
+        };
+        Ok(self.get_signatures_for_address_with_config(address, config).await?)
+    }
+
+    async fn get_recent_prioritization_fees(
+        &self,
+        addresses: &[Pubkey],
+    ) -> Result<Vec<RpcPrioritizationFee>> {
+        Ok(RpcClient::get_recent_prioritization_fees(self, addresses).await?)
+    }
+}
+
+/// A confirmed transaction with its meta, or `None` if the node doesn't
+/// know it.
+async fn confirmed_transaction(
+    client: &RpcClient,
+    signature: &Signature,
+) -> Result<Option<EncodedTransactionWithStatusMeta>> {
+    let config = RpcTransactionConfig {
+        encoding: Some(UiTransactionEncoding::Base64),
+        commitment: Some(client.commitment()),
+        max_supported_transaction_version: Some(0),
+    };
+    // The client's own helper fails on a null result instead of
+    // returning `None`.
+    let transaction: Option<EncodedConfirmedTransactionWithStatusMeta> = client
+        .send(RpcRequest::GetTransaction, json!([signature.to_string(), config]))
+        .await?;
+
+    Ok(transaction.map(|tx| tx.transaction))
+}
+
+/// Rent paid into accounts a transaction created, given each one's post
+/// balance and current state.
+///
+/// An account counts only if it holds data and was funded to exactly its
+/// rent-exempt minimum; anything else, like a plain transfer to a new
+/// wallet, is value moved rather than a cost.
+fn created_account_rent(
+    created: &[(u64, Option<Account>)],
+    minimum_balance: impl Fn(usize) -> u64,
+) -> u64 {
+    created
+        .iter()
+        .filter_map(|(post, account)| Some((*post, account.as_ref()?.data.len())))
+        .filter(|(post, len)| *len > 0 && *post == minimum_balance(*len))
+        .map(|(post, _)| post)
+        .sum()
+}
+
+/// Timeout applied to each RPC call made by the crate's helper types.
+pub const DEFAULT_RPC_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Await `future`, failing with [`ToolkitError::Timeout`] naming `operation`
+/// if it doesn't finish within `timeout`.
+///
+/// # Example
+/// ```ignore
+/// let timeout = Duration::from_secs(5);
+/// let balance = with_timeout(timeout, "get_balance", client.get_balance(&key)).await?;
+/// ```
+pub async fn with_timeout<T>(
+    timeout: Duration,
+    operation: &str,
+    future: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    tokio::time::timeout(timeout, future).await.unwrap_or_else(|_| {
+        Err(ToolkitError::Timeout(format!(
+            "{} timed out after {:?}",
+            operation, timeout
+        )))
+    })
+}
+
+/// [`SolanaRpc`] backend that bounds every call of the wrapped backend with
+/// [`with_timeout`].
+///
+/// Helper types wrap their client in one, so a stalled node fails the
+/// operation instead of hanging it; set the bound with the helper's
+/// `with_timeout`. Derefs to the wrapped backend.
+///
+/// `send_and_confirm_transaction` is exempt: it waits for the transaction
+/// to land, which can outlast any per-call bound, and giving up early
+/// would report a transaction as failed that may still confirm.
+pub struct TimeoutRpc<R> {
+    inner: R,
+    timeout: Duration,
+}
+
+impl<R> TimeoutRpc<R> {
+    /// Wrap `inner`, bounding each call by `timeout`.
+    pub fn new(inner: R, timeout: Duration) -> Self {
+        Self { inner, timeout }
+    }
+
+    /// Timeout applied to each call.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Change the timeout applied to each call.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Unwrap the backend.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R> Deref for TimeoutRpc<R> {
+    type Target = R;
+
+    fn deref(&self) -> &R {
+        &self.inner
+    }
+}
+
+#[async_trait]
+impl<R: SolanaRpc> SolanaRpc for TimeoutRpc<R> {
+    async fn get_balance(&self, pubkey: &Pubkey) -> Result<u64> {
+        with_timeout(self.timeout, "get_balance", self.inner.get_balance(pubkey)).await
+    }
+
+    async fn get_account(&self, pubkey: &Pubkey) -> Result<Option<Account>> {
+        with_timeout(self.timeout, "get_account", self.inner.get_account(pubkey)).await
+    }
+
+    async fn get_account_slice(
+        &self,
+        pubkey: &Pubkey,
+        slice: UiDataSliceConfig,
+    ) -> Result<Option<Account>> {
+        let call = self.inner.get_account_slice(pubkey, slice);
+        with_timeout(self.timeout, "get_account_slice", call).await
+    }
+
+    async fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        let call = self.inner.get_multiple_accounts(pubkeys);
+        with_timeout(self.timeout, "get_multiple_accounts", call).await
+    }
+
+    async fn get_latest_blockhash(&self) -> Result<Hash> {
+        let call = self.inner.get_latest_blockhash();
+        with_timeout(self.timeout, "get_latest_blockhash", call).await
+    }
+
+    async fn get_block_height(&self) -> Result<u64> {
+        with_timeout(self.timeout, "get_block_height", self.inner.get_block_height()).await
+    }
+
+    async fn get_genesis_hash(&self) -> Result<Hash> {
+        with_timeout(self.timeout, "get_genesis_hash", self.inner.get_genesis_hash()).await
+    }
+
+    async fn send_and_confirm_transaction(&self, transaction: &Transaction) -> Result<Signature> {
+        // Bounded by the blockhash's expiry instead; see the type docs
+        self.inner.send_and_confirm_transaction(transaction).await
+    }
+
+    async fn send_transaction(&self, transaction: &Transaction) -> Result<Signature> {
+        let call = self.inner.send_transaction(transaction);
+        with_timeout(self.timeout, "send_transaction", call).await
+    }
+
+    async fn send_transaction_with_config(
+        &self,
+        transaction: &Transaction,
+        config: RpcSendTransactionConfig,
+    ) -> Result<Signature> {
+        let call = self.inner.send_transaction_with_config(transaction, config);
+        with_timeout(self.timeout, "send_transaction", call).await
+    }
+
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<Option<TransactionStatus>>> {
+        let call = self.inner.get_signature_statuses(signatures);
+        with_timeout(self.timeout, "get_signature_statuses", call).await
+    }
+
+    async fn get_token_accounts_by_owner(
+        &self,
+        owner: &Pubkey,
+        program_id: &Pubkey,
+    ) -> Result<Vec<(Pubkey, Account)>> {
+        let call = self.inner.get_token_accounts_by_owner(owner, program_id);
+        with_timeout(self.timeout, "get_token_accounts_by_owner", call).await
+    }
+
+    async fn simulate_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<RpcSimulateTransactionResult> {
+        let call = self.inner.simulate_transaction(transaction);
+        with_timeout(self.timeout, "simulate_transaction", call).await
+    }
+
+    async fn simulate_transaction_with_accounts(
+        &self,
+        transaction: &Transaction,
+        addresses: &[Pubkey],
+    ) -> Result<RpcSimulateTransactionResult> {
+        let call = self.inner.simulate_transaction_with_accounts(transaction, addresses);
+        with_timeout(self.timeout, "simulate_transaction", call).await
+    }
+
+    async fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> Result<Vec<(Pubkey, Account)>> {
+        let call = self.inner.get_program_accounts(program_id, config);
+        with_timeout(self.timeout, "get_program_accounts", call).await
+    }
+
+    async fn get_transaction_units_consumed(&self, signature: &Signature) -> Result<Option<u64>> {
+        let call = self.inner.get_transaction_units_consumed(signature);
+        with_timeout(self.timeout, "get_transaction", call).await
+    }
+
+    async fn get_transaction_costs(&self, signature: &Signature) -> Result<Option<u64>> {
+        let call = self.inner.get_transaction_costs(signature);
+        with_timeout(self.timeout, "get_transaction", call).await
+    }
+
+    async fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        limit: usize,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+        let call = self.inner.get_signatures_for_address(address, limit);
+        with_timeout(self.timeout, "get_signatures_for_address", call).await
+    }
+
+    async fn get_recent_prioritization_fees(
+        &self,
+        addresses: &[Pubkey],
+    ) -> Result<Vec<RpcPrioritizationFee>> {
+        let call = self.inner.get_recent_prioritization_fees(addresses);
+        with_timeout(self.timeout, "get_recent_prioritization_fees", call).await
+    }
+}
+
+/// Shares one backend between several helpers, e.g. through a
+/// [`Wallet`](crate::wallet::Wallet).
+#[async_trait]
+impl<R: SolanaRpc + ?Sized> SolanaRpc for Arc<R> {
+    async fn get_balance(&self, pubkey: &Pubkey) -> Result<u64> {
+        (**self).get_balance(pubkey).await
+    }
+
+    async fn get_account(&self, pubkey: &Pubkey) -> Result<Option<Account>> {
+        (**self).get_account(pubkey).await
+    }
+
+    async fn get_account_slice(
+        &self,
+        pubkey: &Pubkey,
+        slice: UiDataSliceConfig,
+    ) -> Result<Option<Account>> {
+        (**self).get_account_slice(pubkey, slice).await
+    }
+
+    async fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        (**self).get_multiple_accounts(pubkeys).await
+    }
+
+    async fn get_latest_blockhash(&self) -> Result<Hash> {
+        (**self).get_latest_blockhash().await
+    }
+
+    async fn get_block_height(&self) -> Result<u64> {
+        (**self).get_block_height().await
+    }
+
+    async fn get_genesis_hash(&self) -> Result<Hash> {
+        (**self).get_genesis_hash().await
+    }
+
+    async fn send_and_confirm_transaction(&self, transaction: &Transaction) -> Result<Signature> {
+        (**self).send_and_confirm_transaction(transaction).await
+    }
+
+    async fn send_transaction(&self, transaction: &Transaction) -> Result<Signature> {
+        (**self).send_transaction(transaction).await
+    }
+
+    async fn send_transaction_with_config(
+        &self,
+        transaction: &Transaction,
+        config: RpcSendTransactionConfig,
+    ) -> Result<Signature> {
+        (**self).send_transaction_with_config(transaction, config).await
+    }
+
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<Option<TransactionStatus>>> {
+        (**self).get_signature_statuses(signatures).await
+    }
+
+    async fn get_token_accounts_by_owner(
+        &self,
+        owner: &Pubkey,
+        program_id: &Pubkey,
+    ) -> Result<Vec<(Pubkey, Account)>> {
+        (**self).get_token_accounts_by_owner(owner, program_id).await
+    }
+
+    async fn simulate_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<RpcSimulateTransactionResult> {
+        (**self).simulate_transaction(transaction).await
+    }
+
+    async fn simulate_transaction_with_accounts(
+        &self,
+        transaction: &Transaction,
+        addresses: &[Pubkey],
+    ) -> Result<RpcSimulateTransactionResult> {
+        (**self).simulate_transaction_with_accounts(transaction, addresses).await
+    }
+
+    async fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> Result<Vec<(Pubkey, Account)>> {
+        (**self).get_program_accounts(program_id, config).await
+    }
+
+    async fn get_transaction_units_consumed(&self, signature: &Signature) -> Result<Option<u64>> {
+        (**self).get_transaction_units_consumed(signature).await
+    }
+
+    async fn get_transaction_costs(&self, signature: &Signature) -> Result<Option<u64>> {
+        (**self).get_transaction_costs(signature).await
+    }
+
+    async fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        limit: usize,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+        (**self).get_signatures_for_address(address, limit).await
+    }
+
+    async fn get_recent_prioritization_fees(
+        &self,
+        addresses: &[Pubkey],
+    ) -> Result<Vec<RpcPrioritizationFee>> {
+        (**self).get_recent_prioritization_fees(addresses).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_empty_token_accounts_config() {
+        let owner = Pubkey::new_unique();
+        let RpcFilterType::Memcmp(zero) = zero_amount_filter() else {
+            panic!("expected a memcmp filter");
+        };
+        let mut data = vec![1u8; 165];
+        data[64..72].copy_from_slice(&0u64.to_le_bytes());
+        assert!(zero.bytes_match(&data));
+        data[64] = 5;
+        assert!(!zero.bytes_match(&data));
+
+        let config = empty_token_accounts_by_owner_config(&owner);
+        assert_eq!(
+            config.filters.unwrap(),
+            vec![
+                RpcFilterType::DataSize(165),
+                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(32, owner.as_ref())),
+                zero_amount_filter(),
+            ]
+        );
+        assert_eq!(
+            config.account_config.data_slice,
+            token_accounts_by_owner_config(&owner).account_config.data_slice
+        );
+    }
+
+    #[test]
+    fn test_created_account_rent_ignores_transfers_to_new_wallets() {
+        use solana_sdk::rent::Rent;
+
+        let rent = Rent::default();
+        let token_rent = rent.minimum_balance(165);
+        let wallet_rent = rent.minimum_balance(0);
+        let token_account = Account::new(token_rent, 165, &spl_token::id());
+        let created = [
+            // A new token account funded to its minimum is rent
+            (token_rent, Some(token_account)),
+            // Transfers to new wallets are not, even of exactly the minimum
+            (5_000_000, Some(Account::new(5_000_000, 0, &Pubkey::default()))),
+            (wallet_rent, Some(Account::new(wallet_rent, 0, &Pubkey::default()))),
+            // Nor are funded data accounts or accounts closed since
+            (token_rent + 1, Some(Account::new(token_rent + 1, 165, &spl_token::id()))),
+            (token_rent, None),
+        ];
+
+        assert_eq!(created_account_rent(&created, |len| rent.minimum_balance(len)), token_rent);
+        assert_eq!(created_account_rent(&created[1..3], |len| rent.minimum_balance(len)), 0);
+    }
+
+    #[test]
+    fn test_network_urls() {
+        assert_eq!(Network::Mainnet.rpc_url(), "https://api.mainnet-beta.solana.com");
+        assert_eq!(Network::Devnet.rpc_url(), "https://api.devnet.solana.com");
+        assert_eq!(Network::Testnet.rpc_url(), "https://api.testnet.solana.com");
+        assert_eq!(Network::Localnet.rpc_url(), "http://127.0.0.1:8899");
+        assert_eq!(Network::Devnet.ws_url(), "wss://api.devnet.solana.com");
+        assert_eq!(Network::Localnet.ws_url(), "ws://127.0.0.1:8900");
+
+        for network in [Network::Mainnet, Network::Devnet, Network::Testnet, Network::Localnet] {
+            assert_eq!(Network::from_rpc_url(network.rpc_url()), Some(network));
+            assert_eq!(network.to_string().parse::<Network>().unwrap(), network);
+        }
+        assert!(!Network::Mainnet.is_test_network());
+        assert!(Network::Devnet.is_test_network());
+
+        assert_eq!(
+            Network::from_rpc_url("https://devnet.helius-rpc.com/?api-key=x"),
+            Some(Network::Devnet)
+        );
+        assert_eq!(Network::from_rpc_url("http://localhost:8899"), Some(Network::Localnet));
+        assert_eq!(Network::from_rpc_url("https://rpc.example.com"), None);
+        assert_eq!(Network::from_rpc_url("not a url"), None);
+    }
+
+    #[tokio::test]
+    async fn test_json_parsed_token_account_is_detected() {
+        use crate::rent_cleaner::RentCleaner;
+        use crate::testing::MockRpc;
+        use solana_sdk::signature::{Keypair, Signer};
+
+        let payer = Keypair::new();
+        let (address, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let response = json!({
+            "pubkey": address.to_string(),
+            "account": {
+                "lamports": 2_039_280,
+                "owner": spl_token::id().to_string(),
+                "executable": false,
+                "rentEpoch": 0,
+                "space": 165,
+                "data": {
+                    "program": "spl-token",
+                    "space": 165,
+                    "parsed": {
+                        "type": "account",
+                        "info": {
+                            "mint": mint.to_string(),
+                            "owner": payer.pubkey().to_string(),
+                            "state": "initialized",
+                            "isNative": false,
+                            "tokenAmount": {
+                                "amount": "0",
+                                "decimals": 6,
+                                "uiAmount": 0.0,
+                                "uiAmountString": "0"
+                            }
+                        }
+                    }
+                }
+            }
+        });
+        let keyed: RpcKeyedAccount = serde_json::from_value(response).unwrap();
+        assert!(keyed.account.decode::<Account>().is_none());
+
+        let account = token_account_from_ui(&keyed.account).unwrap();
+        let token = spl_token::state::Account::unpack(&account.data).unwrap();
+        assert_eq!((token.mint, token.owner, token.amount), (mint, payer.pubkey(), 0));
+        assert_eq!(account.owner, spl_token::id());
+
+        let rpc = MockRpc::new().with_account(address, account);
+        let cleaner = RentCleaner::from_client(rpc, payer);
+        let empty = cleaner.find_empty_token_accounts().await.unwrap();
+        assert_eq!(empty.len(), 1);
+        assert_eq!(empty[0].address, address);
+        assert_eq!(empty[0].lamports, 2_039_280);
+    }
+
+    #[tokio::test]
+    async fn test_client_config_sends_custom_headers() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                let n = socket.read(&mut buf).await.unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+
+            let body = r#"{"jsonrpc":"2.0","result":42,"id":1}"#;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\n\
+                 content-length: {}\r\nconnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&request).to_lowercase()
+        });
+
+        let client = ClientConfig::new(url)
+            .with_header("x-api-key", "secret")
+            .build()
+            .unwrap();
+        let slot: u64 = client.send(RpcRequest::GetSlot, json!([])).await.unwrap();
+        let request = server.await.unwrap();
+
+        assert_eq!(slot, 42);
+        assert!(request.contains("x-api-key: secret"));
+        assert!(request.contains("solana-client: rust/"));
+    }
+
+    #[test]
+    fn test_client_config_rejects_invalid_header() {
+        let result = ClientConfig::new("http://localhost:8899")
+            .with_header("bad header", "value")
+            .build();
+
+        assert!(matches!(result, Err(ToolkitError::ConfigError(_))));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_timeout_rpc_fails_stalled_calls() {
+        use crate::account_utils::AccountUtils;
+        use crate::testing::MockRpc;
+        use std::time::Instant;
+
+        let wallet = Pubkey::new_unique();
+        let rpc = MockRpc::new().with_balance(wallet, 1_000);
+        rpc.set_latency(Duration::from_secs(5));
+
+        let started = Instant::now();
+        let timed = TimeoutRpc::new(rpc, Duration::from_millis(50));
+        let err = timed.get_balance(&wallet).await.unwrap_err();
+        assert!(matches!(err, ToolkitError::Timeout(_)), "{}", err);
+        assert!(err.to_string().contains("get_balance"), "{}", err);
+        assert!(started.elapsed() < Duration::from_secs(1));
+
+        // Confirming a send is not cut short
+        let transaction = Transaction::new_with_payer(&[], Some(&wallet));
+        assert!(timed.send_and_confirm_transaction(&transaction).await.is_ok());
+
+        // Helpers apply their own bound to every call
+        let utils = AccountUtils::from_client(timed.into_inner())
+            .with_timeout(Duration::from_millis(50));
+        let err = utils.get_balance(&wallet).await.unwrap_err();
+        assert!(matches!(err, ToolkitError::Timeout(_)), "{}", err);
+    }
+}
@@ -146,6 +146,75 @@ impl AddressVerifier {
             likely_typo,
         }
     }
+
+    /// Group addresses that look like the same address typed twice.
+    ///
+    /// Returns clusters of indices into `addresses` whose edit distance
+    /// (insertions, deletions and substitutions) is at most `max_distance`,
+    /// after trimming whitespace. Clusters are linked transitively, each
+    /// is sorted, and they are ordered by their first index. Addresses with
+    /// no near neighbor are left out, so an empty result means nothing
+    /// looks duplicated. A `max_distance` of 0 finds exact duplicates only.
+    ///
+    /// The addresses are not validated; run [`verify_address`](Self::verify_address)
+    /// on each row as well.
+    pub fn cluster_similar(addresses: &[String], max_distance: usize) -> Vec<Vec<usize>> {
+        let trimmed: Vec<Vec<char>> =
+            addresses.iter().map(|a| a.trim().chars().collect()).collect();
+
+        // Union-find over the indices, each root being the smallest index
+        let mut parent: Vec<usize> = (0..addresses.len()).collect();
+
+        for i in 0..trimmed.len() {
+            for j in i + 1..trimmed.len() {
+                if within_edit_distance(&trimmed[i], &trimmed[j], max_distance) {
+                    let (a, b) = (root(&mut parent, i), root(&mut parent, j));
+                    parent[a.max(b)] = a.min(b);
+                }
+            }
+        }
+
+        let mut clusters: Vec<Vec<usize>> = vec![Vec::new(); addresses.len()];
+        for i in 0..addresses.len() {
+            let r = root(&mut parent, i);
+            clusters[r].push(i);
+        }
+        clusters.retain(|cluster| cluster.len() > 1);
+        clusters
+    }
+}
+
+/// Root of `i` in a union-find parent array, compressing the path.
+fn root(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+/// Whether the Levenshtein distance between `a` and `b` is at most `max`.
+///
+/// Stops as soon as every cell of a row exceeds `max`.
+fn within_edit_distance(a: &[char], b: &[char], max: usize) -> bool {
+    if a.len().abs_diff(b.len()) > max {
+        return false;
+    }
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        if current.iter().all(|&d| d > max) {
+            return false;
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()] <= max
 }
 
 /// Result of comparing two addresses.
@@ -218,6 +287,29 @@ mod tests {
         assert!(comparison.likely_typo);
     }
 
+    #[test]
+    fn test_cluster_similar_addresses() {
+        let typo = VALID_ADDRESS.replacen("Xtg", "Xtq", 1);
+        let addresses: Vec<String> = vec![
+            Pubkey::new_unique().to_string(),
+            VALID_ADDRESS.to_string(),
+            Pubkey::new_unique().to_string(),
+            Pubkey::new_unique().to_string(),
+            format!(" {} ", typo),
+            Pubkey::new_unique().to_string(),
+        ];
+
+        assert_eq!(AddressVerifier::cluster_similar(&addresses, 2), vec![vec![1, 4]]);
+        assert!(AddressVerifier::cluster_similar(&addresses, 0).is_empty());
+
+        // A dropped character and an exact repeat join the same cluster
+        let mut addresses = addresses;
+        addresses.push(VALID_ADDRESS[1..].to_string());
+        addresses.push(VALID_ADDRESS.to_string());
+        assert_eq!(AddressVerifier::cluster_similar(&addresses, 1), vec![vec![1, 4, 6, 7]]);
+        assert_eq!(AddressVerifier::cluster_similar(&addresses, 0), vec![vec![1, 7]]);
+    }
+
     #[test]
     fn test_format_address_short() {
         let pubkey = Pubkey::from_str(VALID_ADDRESS).unwrap();
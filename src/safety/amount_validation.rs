@@ -4,9 +4,25 @@
 //! entire balances accidentally or adding too many zeros.
 
 use crate::{Result, ToolkitError};
+use solana_sdk::pubkey::Pubkey;
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Mul, Sub, SubAssign};
 
-/// Lamports per SOL constant.
-pub const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+/// Lamports per SOL, the SDK's own constant.
+pub use solana_sdk::native_token::LAMPORTS_PER_SOL;
+
+/// Share of the balance (in percent) an ExactOut maximum input may reach
+/// before [`AmountValidator::validate_max_input`] warns.
+pub const MAX_INPUT_WARNING_PCT: f64 = 50.0;
+
+/// Lamports [`AmountValidator::amount_from_percentage`] keeps back when
+/// reserving for fees: the rent-exempt minimum of a system account
+/// (890,880) plus one signature's base fee (5,000).
+pub const FEE_RESERVE_LAMPORTS: u64 = 895_880;
+
+/// Significant fractional digits shown in safety reports.
+pub const DEFAULT_DISPLAY_SIG_DIGITS: u8 = 6;
 
 /// Result of amount validation.
 #[derive(Debug, Clone)]
@@ -14,7 +30,7 @@ pub struct AmountValidation {
     /// Whether the amount is valid.
     pub is_valid: bool,
     /// Warnings about the amount (non-blocking).
-    pub warnings: Vec<String>,
+    pub warnings: Vec<AmountWarning>,
     /// Whether this amount requires explicit user confirmation.
     pub requires_confirmation: bool,
     /// Human-readable representation of the amount.
@@ -24,12 +40,71 @@ pub struct AmountValidation {
 }
 
 /// Warning about a potential amount issue.
-#[derive(Debug, Clone)]
+///
+/// Classify warnings by `kind`; `message` is for display only and its
+/// wording may change.
+#[derive(Debug, Clone, PartialEq)]
 pub struct AmountWarning {
-    /// Warning message.
-    pub message: String,
+    /// What the warning is about.
+    pub kind: AmountWarningKind,
     /// Severity level (low, medium, high).
     pub severity: WarningSeverity,
+    /// Warning message.
+    pub message: String,
+}
+
+impl AmountWarning {
+    fn new(kind: AmountWarningKind, severity: WarningSeverity, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            severity,
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for AmountWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Kind of an [`AmountWarning`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AmountWarningKind {
+    /// The amount is zero.
+    ZeroAmount,
+    /// The amount is more than the balance.
+    ExceedsBalance,
+    /// The amount is all, or all but a rounding error, of the balance.
+    EntireBalance,
+    /// The amount is a large share of the balance, but not all of it.
+    MostOfBalance,
+}
+
+/// What an amount is denominated in, and so which balance backs it.
+///
+/// Native SOL is a lamport balance on a wallet. Wrapped SOL (WSOL) is an
+/// SPL token of the [native mint](spl_token::native_mint) held in a token
+/// account; the two are not interchangeable in a transfer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    /// Lamports, moved with a System Program transfer.
+    NativeSol,
+    /// An SPL token of this mint, moved between token accounts.
+    Spl(Pubkey),
+}
+
+impl TokenKind {
+    /// Wrapped SOL.
+    pub fn wrapped_sol() -> Self {
+        TokenKind::Spl(spl_token::native_mint::id())
+    }
+
+    /// Whether this is wrapped SOL.
+    pub fn is_wrapped_sol(&self) -> bool {
+        *self == Self::wrapped_sol()
+    }
 }
 
 /// Severity of an amount warning.
@@ -53,6 +128,186 @@ pub struct MagnitudeCheck {
     pub explanation: String,
 }
 
+/// An amount of native SOL in lamports.
+///
+/// The `+`, `-` and `*` operators saturate at zero and `u64::MAX` rather
+/// than overflowing; use the `checked_*` methods to detect that instead.
+///
+/// Arithmetic is only defined between `Lamports`, so mixing in a [`Sol`]
+/// amount without converting it does not compile:
+///
+/// ```compile_fail
+/// use solana_pipkit::safety::{Lamports, Sol};
+///
+/// let total = Lamports(5_000) + Sol(1.5);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Lamports(pub u64);
+
+impl Lamports {
+    /// Zero lamports.
+    pub const ZERO: Self = Self(0);
+
+    /// The amount in SOL.
+    pub fn as_sol(self) -> Sol {
+        Sol(AmountValidator::token_to_human_amount(self.0, 9))
+    }
+
+    /// Add, returning `None` on overflow.
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        self.0.checked_add(other.0).map(Self)
+    }
+
+    /// Multiply, returning `None` on overflow.
+    pub fn checked_mul(self, count: u64) -> Option<Self> {
+        self.0.checked_mul(count).map(Self)
+    }
+
+    /// Subtract, returning `None` if `other` is larger.
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        self.0.checked_sub(other.0).map(Self)
+    }
+
+    /// Subtract, stopping at zero.
+    pub fn saturating_sub(self, other: Self) -> Self {
+        Self(self.0.saturating_sub(other.0))
+    }
+}
+
+impl From<u64> for Lamports {
+    fn from(lamports: u64) -> Self {
+        Self(lamports)
+    }
+}
+
+impl From<Lamports> for u64 {
+    fn from(lamports: Lamports) -> Self {
+        lamports.0
+    }
+}
+
+impl TryFrom<Sol> for Lamports {
+    type Error = ToolkitError;
+
+    /// Rejects negative, NaN and overflowing amounts; see
+    /// [`AmountValidator::as_lamports`].
+    fn try_from(sol: Sol) -> Result<Self> {
+        AmountValidator::as_lamports(sol)
+    }
+}
+
+impl Add for Lamports {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self(self.0.saturating_add(other.0))
+    }
+}
+
+impl Sub for Lamports {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self.saturating_sub(other)
+    }
+}
+
+impl AddAssign for Lamports {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Lamports {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl Mul<u64> for Lamports {
+    type Output = Self;
+
+    fn mul(self, count: u64) -> Self {
+        Self(self.0.saturating_mul(count))
+    }
+}
+
+impl Sum for Lamports {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, Add::add)
+    }
+}
+
+impl fmt::Display for Lamports {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} lamports", self.0)
+    }
+}
+
+/// An amount of native SOL, for display and user input.
+///
+/// Convert to [`Lamports`] before doing exact arithmetic or sending.
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+pub struct Sol(pub f64);
+
+impl Sol {
+    /// The amount in lamports, rounded to the nearest lamport.
+    ///
+    /// Negative and NaN amounts become zero and overly large ones saturate;
+    /// use `Lamports::try_from` to reject them instead.
+    pub fn as_lamports(self) -> Lamports {
+        Lamports((self.0 * LAMPORTS_PER_SOL as f64).round() as u64)
+    }
+}
+
+impl From<f64> for Sol {
+    fn from(sol: f64) -> Self {
+        Self(sol)
+    }
+}
+
+impl From<Sol> for f64 {
+    fn from(sol: Sol) -> Self {
+        sol.0
+    }
+}
+
+impl From<Lamports> for Sol {
+    fn from(lamports: Lamports) -> Self {
+        lamports.as_sol()
+    }
+}
+
+impl Add for Sol {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self(self.0 + other.0)
+    }
+}
+
+impl Sub for Sol {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self(self.0 - other.0)
+    }
+}
+
+impl Mul<f64> for Sol {
+    type Output = Self;
+
+    fn mul(self, factor: f64) -> Self {
+        Self(self.0 * factor)
+    }
+}
+
+impl fmt::Display for Sol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} SOL", self.0)
+    }
+}
+
 /// Amount validator for transaction safety.
 pub struct AmountValidator;
 
@@ -79,15 +334,21 @@ impl AmountValidator {
             let percentage = (amount as f64 / balance as f64) * 100.0;
 
             if percentage > 99.0 {
-                warnings.push(
-                    "Sending entire balance. No funds will remain for fees.".to_string()
-                );
+                warnings.push(AmountWarning::new(
+                    AmountWarningKind::EntireBalance,
+                    WarningSeverity::High,
+                    "Sending entire balance. No funds will remain for fees.",
+                ));
                 requires_confirmation = true;
             } else if percentage > 90.0 {
-                warnings.push(format!(
-                    "Sending {:.1}% of balance. Only {:.6} will remain.",
-                    percentage,
-                    Self::format_amount(balance - amount, decimals)
+                warnings.push(AmountWarning::new(
+                    AmountWarningKind::MostOfBalance,
+                    WarningSeverity::Medium,
+                    format!(
+                        "Sending {:.1}% of balance. Only {:.6} will remain.",
+                        percentage,
+                        Self::format_amount(balance - amount, decimals)
+                    ),
                 ));
                 requires_confirmation = true;
             }
@@ -95,15 +356,23 @@ impl AmountValidator {
 
         // Check for zero amount
         if amount == 0 {
-            warnings.push("Amount is zero.".to_string());
+            warnings.push(AmountWarning::new(
+                AmountWarningKind::ZeroAmount,
+                WarningSeverity::High,
+                "Amount is zero.",
+            ));
         }
 
         // Check if amount exceeds balance
         if amount > balance {
-            warnings.push(format!(
-                "Amount ({}) exceeds balance ({}).",
-                Self::format_amount(amount, decimals),
-                Self::format_amount(balance, decimals)
+            warnings.push(AmountWarning::new(
+                AmountWarningKind::ExceedsBalance,
+                WarningSeverity::High,
+                format!(
+                    "Amount ({}) exceeds balance ({}).",
+                    Self::format_amount(amount, decimals),
+                    Self::format_amount(balance, decimals)
+                ),
             ));
         }
 
@@ -118,6 +387,103 @@ impl AmountValidator {
         }
     }
 
+    /// Validate the maximum input of an ExactOut swap.
+    ///
+    /// The expected input may be small, but up to `max_input` can be spent.
+    /// Warns when that is more than [`MAX_INPUT_WARNING_PCT`] of `balance`,
+    /// requires confirmation above 90%, and is invalid when it exceeds the
+    /// balance.
+    pub fn validate_max_input(max_input: u64, balance: u64, decimals: u8) -> AmountValidation {
+        let mut warnings = Vec::new();
+        let mut requires_confirmation = false;
+
+        if max_input == 0 {
+            warnings.push(AmountWarning::new(
+                AmountWarningKind::ZeroAmount,
+                WarningSeverity::High,
+                "Maximum input is zero.",
+            ));
+        } else if max_input > balance {
+            warnings.push(AmountWarning::new(
+                AmountWarningKind::ExceedsBalance,
+                WarningSeverity::High,
+                format!(
+                    "Maximum input ({}) exceeds balance ({}).",
+                    Self::format_amount(max_input, decimals),
+                    Self::format_amount(balance, decimals)
+                ),
+            ));
+            requires_confirmation = true;
+        } else {
+            let percentage = (max_input as f64 / balance as f64) * 100.0;
+            if percentage > MAX_INPUT_WARNING_PCT {
+                requires_confirmation = percentage > 90.0;
+                let (kind, severity) = if requires_confirmation {
+                    (AmountWarningKind::EntireBalance, WarningSeverity::High)
+                } else {
+                    (AmountWarningKind::MostOfBalance, WarningSeverity::Medium)
+                };
+                warnings.push(AmountWarning::new(
+                    kind,
+                    severity,
+                    format!(
+                        "Up to {:.1}% of balance may be spent. Only {} is guaranteed to remain.",
+                        percentage,
+                        Self::format_amount(balance - max_input, decimals)
+                    ),
+                ));
+            }
+        }
+
+        AmountValidation {
+            is_valid: max_input <= balance && max_input > 0,
+            warnings,
+            requires_confirmation,
+            human_readable: Self::format_amount(max_input, decimals),
+            amount: max_input,
+        }
+    }
+
+    /// Compute `percent` of a balance, e.g. "send 25% of my balance".
+    ///
+    /// For [`TokenKind::NativeSol`] the percentage applies to the balance
+    /// minus [`FEE_RESERVE_LAMPORTS`], so even 100% leaves the account rent
+    /// exempt with enough for the fee. Token balances are used in full, as
+    /// their fees are paid in SOL. Rounds down.
+    ///
+    /// Fails when `percent` is not between 0 and 100, or a SOL balance
+    /// doesn't cover the reserve.
+    pub fn amount_from_percentage(
+        balance: u64,
+        percent: f64,
+        decimals: u8,
+        kind: TokenKind,
+    ) -> Result<u64> {
+        if !(0.0..=100.0).contains(&percent) {
+            return Err(ToolkitError::AmountValidation {
+                message: format!("Percentage must be between 0 and 100, got {}", percent),
+            });
+        }
+
+        let spendable = if kind == TokenKind::NativeSol {
+            balance.checked_sub(FEE_RESERVE_LAMPORTS).ok_or_else(|| {
+                ToolkitError::AmountValidation {
+                    message: format!(
+                        "Balance ({}) doesn't cover the fee and rent reserve ({})",
+                        Self::format_amount(balance, decimals),
+                        Self::format_amount(FEE_RESERVE_LAMPORTS, decimals)
+                    ),
+                }
+            })?
+        } else {
+            balance
+        };
+
+        // Hundredths of a basis point keep the math in integers
+        let parts = (percent * 10_000.0).round() as u128;
+        Ok((spendable as u128 * parts / 1_000_000) as u64)
+    }
+
     /// Convert a human-readable amount to token units safely.
     ///
     /// # Arguments
@@ -145,8 +511,7 @@ impl AmountValidator {
             });
         }
 
-        let multiplier = 10u64.pow(decimals as u32);
-        let token_amount = human_amount * (multiplier as f64);
+        let token_amount = human_amount * 10f64.powi(decimals as i32);
 
         // Check for overflow
         if token_amount > u64::MAX as f64 {
@@ -161,6 +526,17 @@ impl AmountValidator {
         Ok(result)
     }
 
+    /// Convert a SOL amount to [`Lamports`], rejecting negative, NaN and
+    /// overflowing amounts like [`human_to_token_amount`](Self::human_to_token_amount).
+    pub fn as_lamports(sol: impl Into<Sol>) -> Result<Lamports> {
+        Self::human_to_token_amount(sol.into().0, 9).map(Lamports)
+    }
+
+    /// Convert lamports to [`Sol`].
+    pub fn as_sol(lamports: impl Into<Lamports>) -> Sol {
+        lamports.into().as_sol()
+    }
+
     /// Convert token units to human-readable amount.
     ///
     /// # Arguments
@@ -168,10 +544,12 @@ impl AmountValidator {
     /// * `decimals` - Token decimals
     ///
     /// # Returns
-    /// Human-readable amount as f64
+    /// Human-readable amount as f64, rounded once from the exact decimal
+    /// value
     pub fn token_to_human_amount(token_amount: u64, decimals: u8) -> f64 {
-        let divisor = 10u64.pow(decimals as u32);
-        token_amount as f64 / divisor as f64
+        Self::format_amount(token_amount, decimals)
+            .parse()
+            .unwrap_or(0.0)
     }
 
     /// Format an amount for display.
@@ -181,10 +559,17 @@ impl AmountValidator {
     /// * `decimals` - Token decimals
     ///
     /// # Returns
-    /// Formatted string (e.g., "1.500000000" for 1.5 SOL)
+    /// Formatted string (e.g., "1.500000000" for 1.5 SOL, "3" for three
+    /// units of a 0-decimal token). Exact for any number of decimals.
     pub fn format_amount(amount: u64, decimals: u8) -> String {
-        let human = Self::token_to_human_amount(amount, decimals);
-        format!("{:.width$}", human, width = decimals as usize)
+        let decimals = decimals as usize;
+        if decimals == 0 {
+            return amount.to_string();
+        }
+
+        let digits = format!("{:0>width$}", amount, width = decimals + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - decimals);
+        format!("{}.{}", whole, fraction)
     }
 
     /// Format an amount with symbol for display.
@@ -197,13 +582,78 @@ impl AmountValidator {
     /// # Returns
     /// Formatted string (e.g., "1.5 SOL")
     pub fn format_amount_with_symbol(amount: u64, decimals: u8, symbol: &str) -> String {
-        let human = Self::token_to_human_amount(amount, decimals);
-        // Trim trailing zeros for cleaner display
-        let formatted = format!("{:.width$}", human, width = decimals as usize);
-        let trimmed = formatted.trim_end_matches('0').trim_end_matches('.');
+        let formatted = Self::format_amount(amount, decimals);
+        // Trim trailing fractional zeros for cleaner display
+        let trimmed = if formatted.contains('.') {
+            formatted.trim_end_matches('0').trim_end_matches('.')
+        } else {
+            &formatted
+        };
         format!("{} {}", trimmed, symbol)
     }
 
+    /// Format an amount compactly, keeping at most `max_sig_digits`
+    /// significant fractional digits and trimming trailing zeros.
+    ///
+    /// Fractional digits are counted from the first non-zero digit when the
+    /// whole part is zero, so dust amounts stay visible. Extra digits are
+    /// truncated, never rounded up.
+    ///
+    /// # Example
+    /// ```ignore
+    /// assert_eq!(AmountValidator::format_amount_trimmed(1_234_567_890, 9, 3), "1.234");
+    /// assert_eq!(AmountValidator::format_amount_trimmed(1, 9, 3), "0.000000001");
+    /// ```
+    pub fn format_amount_trimmed(amount: u64, decimals: u8, max_sig_digits: u8) -> String {
+        let divisor = 10u128.pow(decimals as u32);
+        let whole = amount as u128 / divisor;
+        let fraction = format!(
+            "{:0width$}",
+            amount as u128 % divisor,
+            width = decimals as usize
+        );
+
+        let first_significant = if whole == 0 {
+            fraction.find(|c| c != '0').unwrap_or(0)
+        } else {
+            0
+        };
+        let end = (first_significant + max_sig_digits as usize).min(fraction.len());
+        let fraction = fraction[..end].trim_end_matches('0');
+
+        if fraction.is_empty() {
+            whole.to_string()
+        } else {
+            format!("{}.{}", whole, fraction)
+        }
+    }
+
+    /// Insert thousands separators into the whole part of a formatted amount.
+    ///
+    /// # Example
+    /// ```ignore
+    /// assert_eq!(AmountValidator::group_thousands("1234567.89"), "1,234,567.89");
+    /// ```
+    pub fn group_thousands(formatted: &str) -> String {
+        let (whole, fraction) = match formatted.split_once('.') {
+            Some((whole, fraction)) => (whole, Some(fraction)),
+            None => (formatted, None),
+        };
+
+        let mut grouped = String::with_capacity(formatted.len() + whole.len() / 3);
+        for (i, c) in whole.chars().enumerate() {
+            if i > 0 && (whole.len() - i) % 3 == 0 {
+                grouped.push(',');
+            }
+            grouped.push(c);
+        }
+        if let Some(fraction) = fraction {
+            grouped.push('.');
+            grouped.push_str(fraction);
+        }
+        grouped
+    }
+
     /// Check if an amount requires explicit user confirmation.
     ///
     /// # Arguments
@@ -357,7 +807,8 @@ mod tests {
         // Sending 100% should definitely trigger warning
         let result = AmountValidator::validate_amount(balance, 9, balance);
         assert!(result.requires_confirmation);
-        assert!(result.warnings.iter().any(|w| w.contains("entire balance")));
+        assert!(result.warnings.iter().any(|w| w.kind == AmountWarningKind::EntireBalance));
+        assert!(result.warnings.iter().any(|w| w.message.contains("entire balance")));
     }
 
     #[test]
@@ -431,6 +882,26 @@ mod tests {
         assert_eq!(formatted, "0.000000001 SOL");
     }
 
+    #[test]
+    fn test_trimmed_formatting() {
+        assert_eq!(AmountValidator::format_amount_trimmed(1_500_000_000, 9, 6), "1.5");
+        assert_eq!(AmountValidator::format_amount_trimmed(1, 9, 6), "0.000000001");
+        assert_eq!(AmountValidator::format_amount_trimmed(1_234_567_000, 6, 6), "1234.567");
+        assert_eq!(AmountValidator::format_amount_trimmed(2_000_000_000, 9, 6), "2");
+        assert_eq!(AmountValidator::format_amount_trimmed(0, 9, 6), "0");
+
+        // Capped and truncated, counting from the first significant digit
+        assert_eq!(AmountValidator::format_amount_trimmed(1_999_999_999, 9, 2), "1.99");
+        assert_eq!(AmountValidator::format_amount_trimmed(123_456, 9, 2), "0.00012");
+        assert_eq!(AmountValidator::format_amount_trimmed(1_500_000_000, 9, 0), "1");
+        assert_eq!(AmountValidator::format_amount_trimmed(u64::MAX, 0, 6), u64::MAX.to_string());
+
+        assert_eq!(AmountValidator::group_thousands("1234.567"), "1,234.567");
+        assert_eq!(AmountValidator::group_thousands("1234567"), "1,234,567");
+        assert_eq!(AmountValidator::group_thousands("123.4567"), "123.4567");
+        assert_eq!(AmountValidator::group_thousands("0.000000001"), "0.000000001");
+    }
+
     #[test]
     fn test_negative_amount_rejected() {
         let result = AmountValidator::human_to_token_amount(-1.0, 9);
@@ -450,7 +921,90 @@ mod tests {
 
         let result = AmountValidator::validate_amount(amount, 9, balance);
         assert!(!result.is_valid);
-        assert!(result.warnings.iter().any(|w| w.contains("exceeds balance")));
+        assert!(result.warnings.iter().any(|w| w.kind == AmountWarningKind::ExceedsBalance));
+        assert!(result.warnings.iter().any(|w| w.message.contains("exceeds balance")));
+    }
+
+    #[test]
+    fn test_amount_from_percentage() {
+        let balance = 10 * LAMPORTS_PER_SOL;
+        let token = TokenKind::Spl(Pubkey::new_unique());
+        let sol = TokenKind::NativeSol;
+
+        let quarter = AmountValidator::amount_from_percentage(balance, 25.0, 9, token).unwrap();
+        assert_eq!(quarter, 2_500_000_000);
+        let tokens = AmountValidator::amount_from_percentage(1_001, 50.0, 0, token).unwrap();
+        assert_eq!(tokens, 500);
+
+        // Everything but rent and the fee for SOL, and every token unit
+        let all = AmountValidator::amount_from_percentage(balance, 100.0, 9, sol).unwrap();
+        assert_eq!(all, balance - FEE_RESERVE_LAMPORTS);
+        assert!(all < balance);
+        let all = AmountValidator::amount_from_percentage(balance, 100.0, 9, token).unwrap();
+        assert_eq!(all, balance);
+        let wsol = TokenKind::wrapped_sol();
+        let all = AmountValidator::amount_from_percentage(balance, 100.0, 9, wsol).unwrap();
+        assert_eq!(all, balance);
+
+        assert!(AmountValidator::amount_from_percentage(balance, 150.0, 9, token).is_err());
+        assert!(AmountValidator::amount_from_percentage(balance, -1.0, 9, token).is_err());
+        assert!(AmountValidator::amount_from_percentage(balance, f64::NAN, 9, token).is_err());
+        assert!(AmountValidator::amount_from_percentage(800_000, 10.0, 9, sol).is_err());
+        assert!(AmountValidator::amount_from_percentage(800_000, 10.0, 9, token).is_ok());
+    }
+
+    #[test]
+    fn test_max_input_threatens_balance() {
+        let balance = 10 * LAMPORTS_PER_SOL;
+
+        let result = AmountValidator::validate_max_input(LAMPORTS_PER_SOL, balance, 9);
+        assert!(result.is_valid);
+        assert!(result.warnings.is_empty());
+
+        let result = AmountValidator::validate_max_input(7 * LAMPORTS_PER_SOL, balance, 9);
+        assert!(result.is_valid);
+        assert!(result.warnings[0].message.contains("70.0%"));
+        assert_eq!(result.warnings[0].kind, AmountWarningKind::MostOfBalance);
+        assert!(!result.requires_confirmation);
+
+        let result = AmountValidator::validate_max_input(95 * LAMPORTS_PER_SOL / 10, balance, 9);
+        assert!(result.requires_confirmation);
+        assert_eq!(result.warnings[0].severity, WarningSeverity::High);
+
+        let result = AmountValidator::validate_max_input(11 * LAMPORTS_PER_SOL, balance, 9);
+        assert!(!result.is_valid);
+        assert!(result.warnings[0].message.contains("exceeds balance"));
+    }
+
+    #[test]
+    fn test_zero_decimal_amounts() {
+        assert_eq!(AmountValidator::format_amount(1, 0), "1");
+        assert_eq!(AmountValidator::format_amount(100, 0), "100");
+        assert_eq!(AmountValidator::format_amount_with_symbol(100, 0, "NFT"), "100 NFT");
+        assert_eq!(AmountValidator::token_to_human_amount(100, 0), 100.0);
+        assert_eq!(AmountValidator::human_to_token_amount(3.0, 0).unwrap(), 3);
+    }
+
+    #[test]
+    fn test_high_decimal_amounts() {
+        // 1.234567890123456789 of an 18-decimal token
+        let amount = 1_234_567_890_123_456_789;
+        assert_eq!(AmountValidator::format_amount(amount, 18), "1.234567890123456789");
+        assert_eq!(
+            AmountValidator::format_amount_with_symbol(1_500_000_000_000_000_000, 18, "WETH"),
+            "1.5 WETH"
+        );
+        assert_eq!(AmountValidator::format_amount(1, 18), "0.000000000000000001");
+        let exact: f64 = "1.234567890123456789".parse().unwrap();
+        assert_eq!(AmountValidator::token_to_human_amount(amount, 18), exact);
+        assert_eq!(
+            AmountValidator::format_amount(u64::MAX, 24),
+            "0.000018446744073709551615"
+        );
+        assert_eq!(
+            AmountValidator::human_to_token_amount(1.5, 18).unwrap(),
+            1_500_000_000_000_000_000
+        );
     }
 
     #[test]
@@ -459,6 +1013,54 @@ mod tests {
 
         let result = AmountValidator::validate_amount(0, 9, balance);
         assert!(!result.is_valid);
-        assert!(result.warnings.iter().any(|w| w.contains("zero")));
+        assert!(result.warnings.iter().any(|w| w.kind == AmountWarningKind::ZeroAmount));
+        assert!(result.warnings.iter().any(|w| w.message.contains("zero")));
+    }
+
+    #[test]
+    fn test_lamports_and_sol_conversions() {
+        assert_eq!(Sol(1.5).as_lamports(), Lamports(1_500_000_000));
+        assert_eq!(Lamports(1_500_000_000).as_sol(), Sol(1.5));
+        assert_eq!(Lamports::try_from(Sol(0.000000001)).unwrap(), Lamports(1));
+        assert!(Lamports::try_from(Sol(-1.0)).is_err());
+        assert!(Lamports::try_from(Sol(f64::NAN)).is_err());
+        assert!(Lamports::try_from(Sol(f64::MAX)).is_err());
+        assert_eq!(Sol::from(Lamports(LAMPORTS_PER_SOL)), Sol(1.0));
+        assert_eq!(u64::from(Lamports(42)), 42);
+        assert_eq!(Sol(-1.0).as_lamports(), Lamports::ZERO);
+
+        assert_eq!(AmountValidator::as_lamports(2.25).unwrap(), Lamports(2_250_000_000));
+        assert!(AmountValidator::as_lamports(-1.0).is_err());
+        assert!(AmountValidator::as_lamports(f64::NAN).is_err());
+        assert_eq!(AmountValidator::as_sol(250_000_000), Sol(0.25));
+
+        assert_eq!(Lamports(1_500).to_string(), "1500 lamports");
+        assert_eq!(Sol(1.5).to_string(), "1.5 SOL");
+        assert_eq!(Lamports(1).as_sol().to_string(), "0.000000001 SOL");
+    }
+
+    #[test]
+    fn test_lamports_arithmetic_stays_in_lamports() {
+        let fee = Lamports(5_000);
+        let mut total = Lamports(1_000) + fee * 2;
+        total -= Lamports(1_000);
+        assert_eq!(total, Lamports(10_000));
+        assert_eq!([fee, fee, fee].into_iter().sum::<Lamports>(), Lamports(15_000));
+        assert_eq!(fee.checked_sub(total), None);
+        assert_eq!(fee.saturating_sub(total), Lamports::ZERO);
+        assert_eq!(Lamports(u64::MAX).checked_add(fee), None);
+        assert_eq!(Lamports(u64::MAX).checked_mul(2), None);
+
+        // The operators saturate instead of overflowing
+        assert_eq!(Lamports(u64::MAX) + fee, Lamports(u64::MAX));
+        assert_eq!(fee - total, Lamports::ZERO);
+        assert_eq!(Lamports(u64::MAX) * 2, Lamports(u64::MAX));
+        assert_eq!([Lamports(u64::MAX), fee].into_iter().sum::<Lamports>(), Lamports(u64::MAX));
+
+        // SOL must be converted before it can be added to lamports
+        let with_tip = total + Sol(0.5).as_lamports();
+        assert_eq!(with_tip, Lamports(500_010_000));
+        assert_eq!(Sol(0.5) + Sol(0.25), Sol(0.75));
+        assert_eq!(Sol(1.0) * 3.0 - Sol(0.5), Sol(2.5));
     }
 }
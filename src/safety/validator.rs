@@ -3,15 +3,54 @@
 //! Combines address verification and amount validation into a unified
 //! safety check for transfers.
 
+use crate::retry::{retry, RetryConfig};
+use crate::rpc::{with_timeout, SolanaRpc, DEFAULT_RPC_TIMEOUT};
+use crate::token_utils::{
+    assess_freeze_risk, transfer_amount_after_fee, transfer_fee_config, unpack_mint, FreezeRisk,
+    MintRegistry,
+};
+use crate::transaction::{message_instruction, send_and_confirm, SendPolicy};
 use crate::{Result, ToolkitError};
-use solana_client::rpc_client::RpcClient;
-use solana_sdk::pubkey::Pubkey;
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    account::Account,
+    compute_budget,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    system_instruction::{self, SystemInstruction},
+    system_program,
+    transaction::Transaction,
+};
+use spl_token::instruction::TokenInstruction;
+use spl_token::state::Account as TokenAccount;
+use spl_token_2022::extension::StateWithExtensions;
+use spl_token_2022::state::Account as Token2022Account;
+use solana_client::rpc_response::RpcConfirmedTransactionStatusWithSignature;
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use super::address_verify::AddressVerifier;
-use super::amount_validation::AmountValidator;
+use super::amount_validation::{
+    AmountValidation, AmountValidator, AmountWarningKind, TokenKind, DEFAULT_DISPLAY_SIG_DIGITS,
+};
+
+#[cfg(feature = "jupiter")]
+use crate::jupiter::QuoteResponse;
+
+/// Price impact (percent) above which a single-route swap is flagged as
+/// low liquidity.
+#[cfg(feature = "jupiter")]
+const SINGLE_ROUTE_IMPACT_PCT: f64 = 1.0;
 
 /// Risk level for a transaction.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+///
+/// Parses case-insensitively from and serializes to `"LOW"`, `"MEDIUM"`,
+/// `"HIGH"` and `"CRITICAL"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
 pub enum RiskLevel {
     /// No concerns detected.
     Low,
@@ -33,19 +72,31 @@ impl RiskLevel {
     pub fn requires_confirmation(&self) -> bool {
         matches!(self, RiskLevel::High | RiskLevel::Critical)
     }
-}
 
-impl std::fmt::Display for RiskLevel {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    /// Base contribution of this level to [`SafetyReport::risk_score`]:
+    /// 0, 20, 45 and 80 from `Low` to `Critical`.
+    pub fn weight(&self) -> u8 {
         match self {
-            RiskLevel::Low => write!(f, "LOW"),
-            RiskLevel::Medium => write!(f, "MEDIUM"),
-            RiskLevel::High => write!(f, "HIGH"),
-            RiskLevel::Critical => write!(f, "CRITICAL"),
+            RiskLevel::Low => 0,
+            RiskLevel::Medium => 20,
+            RiskLevel::High => 45,
+            RiskLevel::Critical => 80,
         }
     }
 }
 
+/// Points added to [`SafetyReport::risk_score`] per warning.
+const SCORE_PER_WARNING: u8 = 5;
+/// Most points warnings can add to [`SafetyReport::risk_score`].
+const MAX_WARNING_SCORE: u8 = 25;
+
+string_enum!(RiskLevel, "risk level", {
+    Low => "LOW",
+    Medium => "MEDIUM",
+    High => "HIGH",
+    Critical => "CRITICAL",
+});
+
 /// Complete safety report for a transaction.
 #[derive(Debug, Clone)]
 pub struct SafetyReport {
@@ -61,7 +112,7 @@ pub struct SafetyReport {
     pub from_display: String,
     /// Recipient address in short format.
     pub to_display: String,
-    /// Human-readable amount.
+    /// Human-readable amount, trimmed and with thousands separators.
     pub amount_display: String,
     /// Whether user confirmation is required.
     pub requires_confirmation: bool,
@@ -100,6 +151,35 @@ impl SafetyReport {
         self.risk_level = RiskLevel::Critical;
     }
 
+    /// Single 0-100 risk score for sorting and thresholds.
+    ///
+    /// Any blocker scores 100. Otherwise the score is the risk level's
+    /// [`weight`](RiskLevel::weight) plus 5 per warning (at most 25),
+    /// capped at 99. A clean transfer scores 0 and a single large-amount
+    /// warning 50, so "auto-approve below 20" passes only reports without
+    /// Medium or higher warnings.
+    pub fn risk_score(&self) -> u8 {
+        if !self.blockers.is_empty() {
+            return 100;
+        }
+
+        let warnings =
+            (self.warnings.len() * SCORE_PER_WARNING as usize).min(MAX_WARNING_SCORE as usize);
+        (self.risk_level.weight() + warnings as u8).min(99)
+    }
+
+    /// Fold the findings of `other` into this report.
+    ///
+    /// Warnings and blockers are appended; the result keeps the higher risk
+    /// level and is approved only if both reports are.
+    pub fn merge(&mut self, other: &SafetyReport) {
+        self.warnings.extend(other.warnings.iter().cloned());
+        self.blockers.extend(other.blockers.iter().cloned());
+        self.approved &= other.approved;
+        self.risk_level = self.risk_level.max(other.risk_level);
+        self.requires_confirmation |= other.requires_confirmation;
+    }
+
     /// Format report for display.
     pub fn summary(&self) -> String {
         let status = if self.approved { "APPROVED" } else { "BLOCKED" };
@@ -127,6 +207,174 @@ impl SafetyReport {
     }
 }
 
+/// What to do with a transfer, from [`SafetyProtocol::decide`].
+///
+/// Unlike checking [`SafetyReport::requires_confirmation`], matching on a
+/// decision makes the caller handle the confirmation case.
+#[derive(Debug, Clone)]
+#[must_use]
+pub enum SafetyDecision {
+    /// No blockers and nothing that needs the user's confirmation.
+    Approved,
+    /// Allowed, but only after the user confirms (see
+    /// [`SafetyProtocol::require_typed_confirmation`]).
+    NeedsConfirmation(SafetyReport),
+    /// Must not be sent.
+    Blocked(SafetyReport),
+}
+
+impl SafetyDecision {
+    /// Whether the transfer may be sent without asking the user.
+    pub fn is_approved(&self) -> bool {
+        matches!(self, SafetyDecision::Approved)
+    }
+
+    /// The report behind a confirmation or block, `None` when approved.
+    pub fn report(&self) -> Option<&SafetyReport> {
+        match self {
+            SafetyDecision::Approved => None,
+            SafetyDecision::NeedsConfirmation(report) | SafetyDecision::Blocked(report) => {
+                Some(report)
+            }
+        }
+    }
+}
+
+impl From<SafetyReport> for SafetyDecision {
+    fn from(report: SafetyReport) -> Self {
+        if !report.approved {
+            SafetyDecision::Blocked(report)
+        } else if report.requires_confirmation {
+            SafetyDecision::NeedsConfirmation(report)
+        } else {
+            SafetyDecision::Approved
+        }
+    }
+}
+
+/// A request for the user to retype a transfer's amount and the last four
+/// characters of the recipient, from
+/// [`SafetyProtocol::require_typed_confirmation`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfirmationChallenge {
+    /// Amount the user must type, as shown in the report.
+    pub expected_amount: String,
+    /// Last four characters of the recipient address.
+    pub expected_suffix: String,
+}
+
+impl ConfirmationChallenge {
+    /// Instructions to show the user.
+    pub fn prompt(&self) -> String {
+        format!(
+            "Type the amount ({}) and the last 4 characters of the recipient address to confirm",
+            self.expected_amount
+        )
+    }
+}
+
+/// Normalize a typed amount: drop whitespace, thousands separators and
+/// trailing fractional zeros.
+fn normalize_typed_amount(amount: &str) -> String {
+    let amount: String = amount.chars().filter(|c| !c.is_whitespace() && *c != ',').collect();
+    match amount.split_once('.') {
+        Some((whole, fraction)) => {
+            let fraction = fraction.trim_end_matches('0');
+            if fraction.is_empty() {
+                whole.to_string()
+            } else {
+                format!("{}.{}", whole, fraction)
+            }
+        }
+        None => amount,
+    }
+}
+
+/// Compact amount for [`SafetyReport::amount_display`].
+fn display_amount(amount: u64, decimals: u8) -> String {
+    AmountValidator::group_thousands(&AmountValidator::format_amount_trimmed(
+        amount,
+        decimals,
+        DEFAULT_DISPLAY_SIG_DIGITS,
+    ))
+}
+
+/// The SPL Token or Token-2022 account state stored in `account`, if it
+/// is one. Token-2022 extensions are ignored.
+fn token_account_state(account: &Account) -> Option<TokenAccount> {
+    if account.owner == spl_token::id() {
+        return TokenAccount::unpack(&account.data).ok();
+    }
+    if account.owner == spl_token_2022::id() {
+        StateWithExtensions::<Token2022Account>::unpack(&account.data).ok()?;
+        return TokenAccount::unpack(&account.data[..TokenAccount::LEN]).ok();
+    }
+    None
+}
+
+/// Decimals of the SPL Token or Token-2022 mint stored in `account`.
+fn mint_decimals(account: &Account) -> Option<u8> {
+    unpack_mint(account).map(|mint| mint.decimals)
+}
+
+/// Whether `account` is an SPL Token or Token-2022 mint.
+fn is_mint_account(account: &Account) -> bool {
+    unpack_mint(account).is_some()
+}
+
+/// Block invalid addresses and warn about sending to yourself.
+fn check_addresses(report: &mut SafetyReport, from: &Pubkey, to: &Pubkey) {
+    for (role, address) in [("sender", from), ("recipient", to)] {
+        if let Err(e) = AddressVerifier::verify_address(&address.to_string()) {
+            report.add_blocker(format!("Invalid {} address: {}", role, e));
+        }
+    }
+
+    if from == to {
+        report.add_warning("Sending to yourself".to_string(), RiskLevel::Medium);
+    }
+}
+
+/// Signatures fetched for the recipient age check.
+pub const RECIPIENT_HISTORY_LIMIT: usize = 1_000;
+
+/// Seconds since the oldest signature in `history` (newest first), or
+/// `None` if it has no block time.
+fn history_age_secs(history: &[RpcConfirmedTransactionStatusWithSignature]) -> Option<u64> {
+    let first = history.last()?.block_time?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs() as i64;
+    Some(now.saturating_sub(first).max(0) as u64)
+}
+
+/// Rough age for warnings: minutes below two hours, hours below two days,
+/// days beyond.
+fn describe_age(secs: u64) -> String {
+    match secs {
+        0..=7_199 => format!("{} minutes", secs / 60),
+        7_200..=172_799 => format!("{} hours", secs / 3_600),
+        _ => format!("{} days", secs / 86_400),
+    }
+}
+
+/// Add the warnings of an amount validation to `report`, prefixed with
+/// `context`. An amount of zero or above the balance blocks; spending all
+/// of the balance is high risk and most of it medium risk.
+fn add_amount_warnings(report: &mut SafetyReport, validation: &AmountValidation, context: &str) {
+    for warning in &validation.warnings {
+        let message = format!("{}{}", context, warning);
+        match warning.kind {
+            AmountWarningKind::ZeroAmount | AmountWarningKind::ExceedsBalance => {
+                report.add_blocker(message)
+            }
+            AmountWarningKind::EntireBalance => report.add_warning(message, RiskLevel::High),
+            AmountWarningKind::MostOfBalance => report.add_warning(message, RiskLevel::Medium),
+        }
+    }
+}
+
+/// Default estimated fee: one signature at the base fee.
+pub const DEFAULT_ESTIMATED_FEE_LAMPORTS: u64 = 5_000;
+
 /// Safety protocol for validating transactions.
 pub struct SafetyProtocol {
     /// Whether to use strict mode (block on any warning).
@@ -135,6 +383,22 @@ pub struct SafetyProtocol {
     large_amount_threshold_usd: f64,
     /// Estimated token price in USD (for large amount checks).
     token_price_usd: Option<f64>,
+    /// SOL price in USD, for fee cost checks on non-SOL transfers.
+    sol_price_usd: Option<f64>,
+    /// Estimated transaction fee in lamports.
+    estimated_fee_lamports: u64,
+    /// Retry policy for RPC reads.
+    read_retries: RetryConfig,
+    /// Whether to look up the recipient account for SOL transfers.
+    verify_recipient: bool,
+    /// Whether token checks look for already-frozen holders.
+    sample_frozen_holders: bool,
+    /// Cached mint decimals for token amounts.
+    mints: MintRegistry,
+    /// Bound on each RPC read.
+    rpc_timeout: Duration,
+    /// Warn when the recipient's first transaction is more recent than this.
+    min_recipient_age: Option<Duration>,
 }
 
 impl Default for SafetyProtocol {
@@ -150,6 +414,14 @@ impl SafetyProtocol {
             strict_mode: false,
             large_amount_threshold_usd: 1000.0,
             token_price_usd: None,
+            sol_price_usd: None,
+            estimated_fee_lamports: DEFAULT_ESTIMATED_FEE_LAMPORTS,
+            read_retries: RetryConfig::none(),
+            verify_recipient: true,
+            sample_frozen_holders: false,
+            mints: MintRegistry::new(),
+            rpc_timeout: DEFAULT_RPC_TIMEOUT,
+            min_recipient_age: None,
         }
     }
 
@@ -171,6 +443,211 @@ impl SafetyProtocol {
         self
     }
 
+    /// Set the SOL price for fee cost checks on non-SOL transfers.
+    ///
+    /// For SOL transfers (9 decimals) the token price is used instead.
+    pub fn sol_price(mut self, price: f64) -> Self {
+        self.sol_price_usd = Some(price);
+        self
+    }
+
+    /// Set the estimated transaction fee in lamports (5,000 by default).
+    pub fn estimated_fee(mut self, lamports: u64) -> Self {
+        self.estimated_fee_lamports = lamports;
+        self
+    }
+
+    /// Retry transient RPC read failures up to `retries` times.
+    pub fn read_retries(mut self, retries: u32) -> Self {
+        self.read_retries = RetryConfig::new(retries);
+        self
+    }
+
+    /// Fail RPC reads that take longer than `timeout` (30s by default)
+    /// with [`ToolkitError::Timeout`]. Each retry gets its own timeout.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.rpc_timeout = timeout;
+        self
+    }
+
+    /// Enable or disable the on-chain recipient check, which blocks sends
+    /// to a token mint and flags SOL sent to non-system accounts.
+    ///
+    /// Enabled by default. Disable it for offline mode or when the RPC
+    /// endpoint should only be used for the balance lookup.
+    pub fn verify_recipient(mut self, enabled: bool) -> Self {
+        self.verify_recipient = enabled;
+        self
+    }
+
+    /// Make [`check_token_authenticity`](Self::check_token_authenticity)
+    /// query for frozen holders of the mint.
+    ///
+    /// Disabled by default; the `getProgramAccounts` query it needs is
+    /// restricted by some RPC providers.
+    pub fn sample_frozen_holders(mut self, enabled: bool) -> Self {
+        self.sample_frozen_holders = enabled;
+        self
+    }
+
+    /// Make [`validate_transfer`](Self::validate_transfer) warn (Medium)
+    /// when the recipient's first transaction is less than `min_age` old,
+    /// or when it has no transactions at all.
+    ///
+    /// Disabled by default; it costs a `getSignaturesForAddress` call.
+    /// Recipients with more than [`RECIPIENT_HISTORY_LIMIT`] signatures are
+    /// treated as old without paging further.
+    pub fn min_recipient_age(mut self, min_age: Duration) -> Self {
+        self.min_recipient_age = Some(min_age);
+        self
+    }
+
+    /// Resolve mint decimals through `registry`, e.g. one shared with the
+    /// Jupiter client or prefetched for the mints about to be checked.
+    pub fn mint_registry(mut self, registry: MintRegistry) -> Self {
+        self.mints = registry;
+        self
+    }
+
+    /// Check a token mint for signs of a scam token.
+    ///
+    /// Performs the following checks:
+    /// 1. The mint exists and is owned by the SPL Token or Token-2022 program
+    /// 2. Freeze authority risk (see [`assess_freeze_risk`]): an active
+    ///    freeze authority is a medium risk, frozen holders a high one
+    ///
+    /// The report's "from" and "to" displays are both the mint and its
+    /// `amount_display` is empty.
+    pub async fn check_token_authenticity<R: SolanaRpc + ?Sized>(
+        &self,
+        client: &R,
+        mint: &Pubkey,
+    ) -> Result<SafetyReport> {
+        let mut report = SafetyReport::approved(mint, mint, String::new());
+
+        // 1. Mint exists and is an SPL token mint
+        let account = retry(&self.read_retries, || {
+            with_timeout(self.rpc_timeout, "get_account", client.get_account(mint))
+        })
+        .await?;
+        match account {
+            None => report.add_blocker(format!("Mint {} does not exist", mint)),
+            Some(account) if !is_mint_account(&account) => report.add_blocker(format!(
+                "{} is not an SPL token mint (owner {})",
+                mint, account.owner
+            )),
+            Some(_) => {
+                // 2. Freeze authority
+                let risk = retry(&self.read_retries, || {
+                    let assess = assess_freeze_risk(client, mint, self.sample_frozen_holders);
+                    with_timeout(self.rpc_timeout, "assess_freeze_risk", assess)
+                })
+                .await?;
+                match risk {
+                    FreezeRisk::Low => {}
+                    FreezeRisk::Medium => report.add_warning(
+                        "Mint has an active freeze authority that can freeze your tokens"
+                            .to_string(),
+                        RiskLevel::Medium,
+                    ),
+                    FreezeRisk::High => report.add_warning(
+                        "Mint's freeze authority has frozen holders' accounts".to_string(),
+                        RiskLevel::High,
+                    ),
+                }
+            }
+        }
+
+        self.apply_strict_mode(&mut report);
+        Ok(report)
+    }
+
+    /// Check the output of a swap into `mint` for a Token-2022 transfer fee.
+    ///
+    /// When the mint charges one, the report carries a Medium warning
+    /// stating how much of the quoted `out_amount` actually arrives;
+    /// otherwise it is empty and approved. Its "from" and "to" displays
+    /// are both the mint.
+    pub async fn check_swap_output<R: SolanaRpc + ?Sized>(
+        &self,
+        client: &R,
+        mint: &Pubkey,
+        out_amount: u64,
+    ) -> Result<SafetyReport> {
+        let mut report = SafetyReport::approved(mint, mint, String::new());
+        let account = retry(&self.read_retries, || {
+            with_timeout(self.rpc_timeout, "get_account", client.get_account(mint))
+        })
+        .await?;
+
+        if let Some((account, decimals)) =
+            account.and_then(|a| mint_decimals(&a).map(|decimals| (a, decimals)))
+        {
+            report.amount_display = display_amount(out_amount, decimals);
+            if let Some(received) = self.received_after_fee(client, &account, out_amount).await? {
+                report.add_warning(
+                    format!(
+                        "Mint {} charges a transfer fee; you receive {} of the quoted {}",
+                        mint,
+                        display_amount(received, decimals),
+                        report.amount_display
+                    ),
+                    RiskLevel::Medium,
+                );
+            }
+        }
+
+        self.apply_strict_mode(&mut report);
+        Ok(report)
+    }
+
+    /// What arrives of `amount` after the Token-2022 transfer fee of
+    /// `mint_account`, or `None` when the mint charges no fee.
+    async fn received_after_fee<R: SolanaRpc + ?Sized>(
+        &self,
+        client: &R,
+        mint_account: &Account,
+        amount: u64,
+    ) -> Result<Option<u64>> {
+        if transfer_fee_config(mint_account).is_none() {
+            return Ok(None);
+        }
+        let received = retry(&self.read_retries, || {
+            with_timeout(self.rpc_timeout, "transfer_amount_after_fee", async {
+                transfer_amount_after_fee(client, mint_account, amount).await
+            })
+        })
+        .await?;
+        Ok(Some(received))
+    }
+
+    /// Build a challenge that makes the user retype the amount and the last
+    /// four characters of the recipient before a send.
+    ///
+    /// Use it to gate reports with `requires_confirmation` set.
+    pub fn require_typed_confirmation(report: &SafetyReport) -> ConfirmationChallenge {
+        let to = &report.to_display;
+        let suffix_start = to.char_indices().rev().nth(3).map_or(0, |(i, _)| i);
+        ConfirmationChallenge {
+            expected_amount: report.amount_display.clone(),
+            expected_suffix: to[suffix_start..].to_string(),
+        }
+    }
+
+    /// Check the user's answers to a [`ConfirmationChallenge`].
+    ///
+    /// The amount ignores whitespace, thousands separators and trailing
+    /// zeros; the suffix must match exactly, since addresses are
+    /// case-sensitive.
+    pub fn verify_confirmation(
+        challenge: &ConfirmationChallenge,
+        typed_amount: &str,
+        typed_suffix: &str,
+    ) -> bool {
+        normalize_typed_amount(typed_amount) == normalize_typed_amount(&challenge.expected_amount)
+            && typed_suffix.trim() == challenge.expected_suffix
+    }
+
     /// Validate a transfer for safety issues.
     ///
     /// Performs the following checks:
@@ -178,7 +655,16 @@ impl SafetyProtocol {
     /// 2. Check sender has sufficient balance
     /// 3. Validate amount (not zero, not exceeding balance)
     /// 4. Check for full balance sends
-    /// 5. Check for large amounts requiring confirmation
+    /// 5. Check for large amounts requiring confirmation, and for amounts
+    ///    worth less than the estimated fee
+    /// 6. Block sends to a token mint or program (see
+    ///    [`SafetyProtocol::verify_recipient`])
+    /// 7. Optionally, flag recipients with little history (see
+    ///    [`SafetyProtocol::min_recipient_age`])
+    ///
+    /// Checks that only apply to native SOL run when the transfer is
+    /// validated as [`TokenKind::NativeSol`] with
+    /// [`validate_transfer_of`](Self::validate_transfer_of).
     ///
     /// # Arguments
     /// * `client` - RPC client for balance queries
@@ -189,101 +675,801 @@ impl SafetyProtocol {
     ///
     /// # Returns
     /// SafetyReport with approval status and any warnings/blockers
-    pub async fn validate_transfer(
+    pub async fn validate_transfer<R: SolanaRpc + ?Sized>(
+        &self,
+        client: &R,
+        from: &Pubkey,
+        to: &Pubkey,
+        amount: u64,
+        decimals: u8,
+    ) -> Result<SafetyReport> {
+        self.validate_wallet_transfer(client, from, to, amount, decimals, false).await
+    }
+
+    /// [`validate_transfer`](Self::validate_transfer), adding the native
+    /// SOL checks when `native_sol` is set.
+    async fn validate_wallet_transfer<R: SolanaRpc + ?Sized>(
         &self,
-        client: &RpcClient,
+        client: &R,
         from: &Pubkey,
         to: &Pubkey,
         amount: u64,
         decimals: u8,
+        native_sol: bool,
     ) -> Result<SafetyReport> {
-        let amount_display = AmountValidator::format_amount(amount, decimals);
-        let mut report = SafetyReport::approved(from, to, amount_display.clone());
+        let amount_display = display_amount(amount, decimals);
+        let mut report = SafetyReport::approved(from, to, amount_display);
 
         // 1. Verify addresses are valid
-        if let Err(e) = AddressVerifier::verify_address(&from.to_string()) {
-            report.add_blocker(format!("Invalid sender address: {}", e));
+        check_addresses(&mut report, from, to);
+
+        // 2. Fetch the sender's balance, and the recipient with it
+        let mut accounts = self.fetch_accounts(client, &[*from, *to]).await?.into_iter();
+        let (sender, recipient) = (accounts.next().flatten(), accounts.next().flatten());
+        let balance = sender.as_ref().map_or(0, |account| account.lamports);
+
+        // 3-5. Validate amount against balance, then check for large amounts
+        self.check_spend(&mut report, amount, decimals, balance, "", true);
+        if native_sol {
+            self.check_sol_reserve(&mut report, amount, balance);
         }
+        self.check_fee_exceeds_value(&mut report, amount, decimals, native_sol);
+
+        // 6-7. What the recipient is, and how long it has existed
+        self.check_recipient(client, &mut report, to, recipient.as_ref(), native_sol).await?;
+
+        if native_sol {
+            let is_wsol = |account: &Option<Account>| {
+                account
+                    .as_ref()
+                    .and_then(token_account_state)
+                    .is_some_and(|state| state.mint == spl_token::native_mint::id())
+            };
+            if is_wsol(&sender) {
+                report.add_warning(
+                    "Sender is a WSOL token account; wrapped SOL must be sent as an SPL token \
+                     transfer or unwrapped first"
+                        .to_string(),
+                    RiskLevel::High,
+                );
+            }
+            if is_wsol(&recipient) {
+                report.add_warning(
+                    "Recipient is a WSOL token account; native SOL sent there is not counted \
+                     as WSOL until it is synced, and may be unrecoverable"
+                        .to_string(),
+                    RiskLevel::High,
+                );
+            }
+        }
+
+        self.apply_strict_mode(&mut report);
+        Ok(report)
+    }
 
-        if let Err(e) = AddressVerifier::verify_address(&to.to_string()) {
-            report.add_blocker(format!("Invalid recipient address: {}", e));
+    /// Checks 6-7 of [`validate_transfer`](Self::validate_transfer) on the
+    /// recipient `to`, fetched as `recipient`.
+    ///
+    /// Mints and programs are blocked as recipients whatever is sent; for
+    /// `native_sol` a recipient that is not a system account is also
+    /// flagged.
+    async fn check_recipient<R: SolanaRpc + ?Sized>(
+        &self,
+        client: &R,
+        report: &mut SafetyReport,
+        to: &Pubkey,
+        recipient: Option<&Account>,
+        native_sol: bool,
+    ) -> Result<()> {
+        // Funds sent to a mint, program or token account are usually stranded
+        if let Some(account) = recipient.filter(|_| self.verify_recipient) {
+            if is_mint_account(account) {
+                report.add_blocker(
+                    "Recipient is a token mint, not a wallet; funds sent there are lost"
+                        .to_string(),
+                );
+            } else if account.executable {
+                report.add_blocker(format!(
+                    "Recipient is an executable program (owner {})",
+                    account.owner
+                ));
+            } else if !native_sol {
+                // Token recipients may be wallets or token accounts
+            } else if account.owner != system_program::id() {
+                let kind = if account.owner == spl_token::id() {
+                    "a token account"
+                } else {
+                    "not a system account"
+                };
+                report.add_warning(
+                    format!(
+                        "Recipient is {} (owner {}); SOL sent there may be unrecoverable",
+                        kind, account.owner
+                    ),
+                    RiskLevel::High,
+                );
+            }
         }
 
-        // Check for self-transfer
-        if from == to {
+        // Freshly created recipients are a common scam lure
+        let Some(min_age) = self.min_recipient_age else {
+            return Ok(());
+        };
+        let history = retry(&self.read_retries, || {
+            with_timeout(self.rpc_timeout, "get_signatures_for_address", async {
+                client.get_signatures_for_address(to, RECIPIENT_HISTORY_LIMIT).await.map_err(
+                    |e| ToolkitError::NetworkError(format!("Failed to fetch history: {}", e)),
+                )
+            })
+        })
+        .await?;
+
+        if history.is_empty() {
             report.add_warning(
-                "Sending to yourself".to_string(),
+                "Recipient has no transaction history".to_string(),
                 RiskLevel::Medium,
             );
+        } else if history.len() < RECIPIENT_HISTORY_LIMIT {
+            if let Some(age) = history_age_secs(&history).filter(|age| *age < min_age.as_secs()) {
+                report.add_warning(
+                    format!(
+                        "Recipient's first transaction was only {} ago; new accounts are \
+                         common in scams",
+                        describe_age(age)
+                    ),
+                    RiskLevel::Medium,
+                );
+            }
         }
+        Ok(())
+    }
 
-        // 2. Fetch balance and validate amount
-        let balance = client.get_balance(from).map_err(|e| {
-            ToolkitError::NetworkError(format!("Failed to fetch balance: {}", e))
-        })?;
+    /// Fetch `keys` in one call, with the protocol's retries and timeout.
+    async fn fetch_accounts<R: SolanaRpc + ?Sized>(
+        &self,
+        client: &R,
+        keys: &[Pubkey],
+    ) -> Result<Vec<Option<Account>>> {
+        retry(&self.read_retries, || {
+            with_timeout(self.rpc_timeout, "get_multiple_accounts", async {
+                client.get_multiple_accounts(keys).await.map_err(|e| {
+                    ToolkitError::NetworkError(format!("Failed to fetch transfer accounts: {}", e))
+                })
+            })
+        })
+        .await
+    }
 
-        // 3. Validate amount against balance
-        let validation = AmountValidator::validate_amount(amount, decimals, balance);
+    /// Run [`validate_transfer`](Self::validate_transfer) and turn the
+    /// report into a [`SafetyDecision`].
+    ///
+    /// Blocked reports become [`SafetyDecision::Blocked`], approved reports
+    /// with `requires_confirmation` become
+    /// [`SafetyDecision::NeedsConfirmation`], and the rest
+    /// [`SafetyDecision::Approved`].
+    pub async fn decide<R: SolanaRpc + ?Sized>(
+        &self,
+        client: &R,
+        from: &Pubkey,
+        to: &Pubkey,
+        amount: u64,
+        decimals: u8,
+    ) -> Result<SafetyDecision> {
+        let report = self.validate_transfer(client, from, to, amount, decimals).await?;
+        Ok(report.into())
+    }
 
-        if !validation.is_valid {
-            for warning in &validation.warnings {
-                if warning.contains("exceeds balance") || warning.contains("zero") {
-                    report.add_blocker(warning.clone());
-                }
+    /// Validate a SOL transfer and send it only if it passes.
+    ///
+    /// Runs [`validate_transfer_of`](Self::validate_transfer_of) with
+    /// [`TokenKind::NativeSol`] from `from`'s pubkey. Blocked transfers are
+    /// never sent; when the report requires confirmation, `confirm` is
+    /// asked and the transfer is sent only if it returns `true`. Returns the
+    /// report with the signature, or `None` when nothing was sent.
+    /// `decimals` must be 9, since this sends SOL.
+    pub async fn validate_and_send<R: SolanaRpc + ?Sized>(
+        &self,
+        client: &R,
+        from: &Keypair,
+        to: &Pubkey,
+        amount: u64,
+        decimals: u8,
+        confirm: impl Fn(&SafetyReport) -> bool,
+    ) -> Result<(SafetyReport, Option<Signature>)> {
+        if decimals != 9 {
+            return Err(ToolkitError::ConfigError(format!(
+                "validate_and_send sends SOL, which has 9 decimals, not {}",
+                decimals
+            )));
+        }
+
+        let from_pubkey = from.pubkey();
+        let report =
+            self.validate_wallet_transfer(client, &from_pubkey, to, amount, 9, true).await?;
+        let send = match SafetyDecision::from(report.clone()) {
+            SafetyDecision::Approved => true,
+            SafetyDecision::NeedsConfirmation(report) => confirm(&report),
+            SafetyDecision::Blocked(_) => false,
+        };
+        if !send {
+            return Ok((report, None));
+        }
+
+        let instruction = system_instruction::transfer(&from_pubkey, to, amount);
+        let recent_blockhash = client.get_latest_blockhash().await?;
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&from_pubkey),
+            &[from],
+            recent_blockhash,
+        );
+        let signature =
+            send_and_confirm(client, &transaction, &[], &SendPolicy::default()).await?;
+        Ok((report, Some(signature)))
+    }
+
+    /// Validate a transfer of `kind`, checking the balance that actually
+    /// backs it.
+    ///
+    /// For [`TokenKind::NativeSol`] this is
+    /// [`validate_transfer`](Self::validate_transfer) with 9 decimals, also
+    /// blocking amounts that leave too little for the estimated fee or
+    /// leave the sender below the rent-exempt minimum without emptying it,
+    /// and warning when the recipient is not a system account. For
+    /// [`TokenKind::Spl`] `from` and `to` are token accounts: `from` must
+    /// hold the mint and its token balance is checked with the mint's
+    /// decimals, and a recipient token account of another mint is blocked.
+    /// The self-transfer, fee, recipient and recipient age checks of
+    /// `validate_transfer` apply either way.
+    /// Token-2022 mints with a transfer fee add a Medium warning stating
+    /// the amount the recipient actually receives.
+    ///
+    /// Native SOL and wrapped SOL (WSOL) are easily confused, so either
+    /// way a High warning is added when the sender or recipient is the
+    /// other kind: a WSOL token account in a native transfer, or a wallet
+    /// in a WSOL transfer.
+    pub async fn validate_transfer_of<R: SolanaRpc + ?Sized>(
+        &self,
+        client: &R,
+        from: &Pubkey,
+        to: &Pubkey,
+        amount: u64,
+        kind: TokenKind,
+    ) -> Result<SafetyReport> {
+        let mint = match kind {
+            TokenKind::NativeSol => {
+                return self.validate_wallet_transfer(client, from, to, amount, 9, true).await
             }
+            TokenKind::Spl(mint) => mint,
+        };
+
+        let mut report = SafetyReport::approved(from, to, String::new());
+        check_addresses(&mut report, from, to);
+
+        let mut accounts = self.fetch_accounts(client, &[*from, *to, mint]).await?.into_iter();
+        let (source, recipient, mint_account) =
+            (accounts.next().flatten(), accounts.next().flatten(), accounts.next().flatten());
+
+        let Some((mint_account, decimals)) =
+            mint_account.and_then(|a| mint_decimals(&a).map(|decimals| (a, decimals)))
+        else {
+            report.add_blocker(format!("{} is not an SPL token mint", mint));
+            self.apply_strict_mode(&mut report);
+            return Ok(report);
+        };
+        report.amount_display = display_amount(amount, decimals);
+
+        // Token-2022 transfer fees are withheld from what the recipient gets
+        if let Some(received) = self.received_after_fee(client, &mint_account, amount).await? {
+            report.add_warning(
+                format!(
+                    "Mint {} charges a transfer fee; the recipient receives {} of {}",
+                    mint,
+                    display_amount(received, decimals),
+                    report.amount_display
+                ),
+                RiskLevel::Medium,
+            );
         }
 
-        // 4. Add non-blocking warnings
-        for warning in &validation.warnings {
-            if !warning.contains("exceeds balance") && !warning.contains("zero") {
-                let level = if warning.contains("entire balance") {
-                    RiskLevel::High
-                } else if warning.contains("%") {
-                    RiskLevel::Medium
-                } else {
-                    RiskLevel::Low
-                };
-                report.add_warning(warning.clone(), level);
+        // Source: a token account of this mint, whose token balance is spent
+        match source.as_ref().and_then(token_account_state) {
+            Some(state) if state.mint == mint => {
+                self.check_spend(&mut report, amount, decimals, state.amount, "", true);
+            }
+            Some(state) => report.add_blocker(format!(
+                "Sender token account holds mint {}, not {}",
+                state.mint, mint
+            )),
+            None => {
+                if kind.is_wrapped_sol() {
+                    report.add_warning(
+                        "Sender is a wallet, not a WSOL token account; its native SOL must be \
+                         sent as native SOL or wrapped first"
+                            .to_string(),
+                        RiskLevel::High,
+                    );
+                }
+                report.add_blocker(format!("Sender is not a token account for {}", mint));
             }
         }
 
-        // 5. Check for large amounts requiring confirmation
-        if let Some(price) = self.token_price_usd {
-            let human_amount = AmountValidator::token_to_human_amount(amount, decimals);
-            let usd_value = human_amount * price;
+        self.check_fee_exceeds_value(&mut report, amount, decimals, false);
+
+        // Recipient: SPL transfers need a token account of the same mint
+        match recipient.as_ref().and_then(token_account_state) {
+            Some(state) if state.mint != mint => report.add_blocker(format!(
+                "Recipient token account holds mint {}, not {}",
+                state.mint, mint
+            )),
+            Some(_) => {}
+            None if kind.is_wrapped_sol() => report.add_warning(
+                "Recipient is a wallet, not a WSOL token account; send native SOL instead, or \
+                 to its associated WSOL account"
+                    .to_string(),
+                RiskLevel::High,
+            ),
+            None => report.add_warning(
+                "Recipient is not a token account; send to its associated token account"
+                    .to_string(),
+                RiskLevel::High,
+            ),
+        }
+        self.check_recipient(client, &mut report, to, recipient.as_ref(), false).await?;
+
+        self.apply_strict_mode(&mut report);
+        Ok(report)
+    }
 
-            if AmountValidator::requires_confirmation(usd_value, self.large_amount_threshold_usd) {
+    /// Validate SOL routed through intermediate accounts as one chain.
+    ///
+    /// `hops` lists every account from sender to final recipient and
+    /// `amounts[i]` is the lamports sent from `hops[i]` to `hops[i + 1]`.
+    /// Performs the following checks:
+    /// 1. Verify every hop address
+    /// 2. The sender's balance covers the first leg
+    /// 3. Each intermediate exists, is not a program, and its balance plus
+    ///    the incoming leg covers the outgoing leg
+    /// 4. The final recipient is not a program
+    ///
+    /// The report's "from" and "to" displays are the first and last hops and
+    /// `amount_display` lists each leg.
+    ///
+    /// Fails with [`ToolkitError::ConfigError`] unless there are at least two
+    /// hops and exactly one amount per leg.
+    pub async fn validate_transfer_chain<R: SolanaRpc + ?Sized>(
+        &self,
+        client: &R,
+        hops: &[Pubkey],
+        amounts: &[u64],
+    ) -> Result<SafetyReport> {
+        if hops.len() < 2 || amounts.len() != hops.len() - 1 {
+            return Err(ToolkitError::ConfigError(format!(
+                "A transfer chain needs at least two hops and one amount per leg, got {} hops \
+                 and {} amounts",
+                hops.len(),
+                amounts.len()
+            )));
+        }
+
+        let legs: Vec<String> = amounts.iter().map(|a| display_amount(*a, 9)).collect();
+        let mut report =
+            SafetyReport::approved(&hops[0], &hops[hops.len() - 1], legs.join(" -> "));
+
+        // 1. Verify addresses
+        for (i, hop) in hops.iter().enumerate() {
+            if let Err(e) = AddressVerifier::verify_address(&hop.to_string()) {
+                report.add_blocker(format!("Invalid address at hop {}: {}", i, e));
+            }
+        }
+
+        let accounts = retry(&self.read_retries, || {
+            with_timeout(self.rpc_timeout, "get_multiple_accounts", async {
+                client.get_multiple_accounts(hops).await.map_err(|e| {
+                    ToolkitError::NetworkError(format!("Failed to fetch hop accounts: {}", e))
+                })
+            })
+        })
+        .await?;
+
+        for (i, (hop, account)) in hops.iter().zip(&accounts).enumerate() {
+            let incoming = if i == 0 { 0 } else { amounts[i - 1] };
+            let outgoing = amounts.get(i).copied().unwrap_or(0);
+            let balance = account.as_ref().map_or(0, |a| a.lamports);
+
+            // 3-4. Programs can't pass funds on, and nonexistent hops are
+            // usually a mistyped address
+            if let Some(account) = account.as_ref().filter(|a| a.executable) {
+                report.add_blocker(format!(
+                    "Hop {} ({}) is an executable program (owner {})",
+                    i,
+                    AddressVerifier::format_address_short(hop),
+                    account.owner
+                ));
+                continue;
+            }
+            if account.is_none() && i > 0 && i < hops.len() - 1 {
                 report.add_warning(
                     format!(
-                        "Large transfer: ~${:.2} USD exceeds ${:.0} threshold",
-                        usd_value, self.large_amount_threshold_usd
+                        "Intermediate hop {} ({}) does not exist",
+                        i,
+                        AddressVerifier::format_address_short(hop)
                     ),
                     RiskLevel::High,
                 );
             }
+
+            // 2-3. Each sending hop covers its outgoing leg
+            if outgoing > 0 && balance.saturating_add(incoming) < outgoing {
+                report.add_blocker(format!(
+                    "Hop {} ({}) can't cover its outgoing {}: balance {} plus incoming {}",
+                    i,
+                    AddressVerifier::format_address_short(hop),
+                    display_amount(outgoing, 9),
+                    display_amount(balance, 9),
+                    display_amount(incoming, 9)
+                ));
+            }
         }
 
-        // In strict mode, any warning becomes a blocker
-        if self.strict_mode && !report.warnings.is_empty() {
-            let warnings: Vec<String> = report.warnings.drain(..).collect();
-            for warning in warnings {
-                report.add_blocker(format!("STRICT: {}", warning));
+        self.apply_strict_mode(&mut report);
+        Ok(report)
+    }
+
+    /// Validate every transfer in a signed or unsigned transaction as one
+    /// spend.
+    ///
+    /// Sums what each account sends through System `Transfer`,
+    /// `TransferWithSeed` and `CreateAccount` instructions (lamports) and
+    /// what each token account sends through SPL Token `Transfer` and
+    /// `TransferChecked` (base units), then runs checks 3-5 of
+    /// [`SafetyProtocol::validate_transfer`] against each source's total, so
+    /// several transfers that each look fine can't together drain a
+    /// balance. USD checks only apply to lamport totals. Also:
+    /// - Blocks when `signer` is not a required signer of the transaction
+    /// - Warns (High) on every SPL Token `Approve` and `ApproveChecked`,
+    ///   naming the delegate, and blocks unlimited (`u64::MAX`) approvals
+    /// - Flags each instruction for a program other than System, SPL Token,
+    ///   Associated Token Account or Compute Budget, since its effect on
+    ///   balances is unknown
+    ///
+    /// The report's "to" display counts the distinct destinations and
+    /// `amount_display` is the total SOL sent. Findings for a source are
+    /// prefixed with its short address.
+    pub async fn validate_transaction<R: SolanaRpc + ?Sized>(
+        &self,
+        client: &R,
+        tx: &Transaction,
+        signer: &Pubkey,
+    ) -> Result<SafetyReport> {
+        let message = &tx.message;
+        let mut lamports_out: Vec<(Pubkey, u64)> = Vec::new();
+        let mut tokens_out: Vec<(Pubkey, u64)> = Vec::new();
+        let mut token_decimals: HashMap<Pubkey, u8> = HashMap::new();
+        let mut destinations: Vec<Pubkey> = Vec::new();
+        let mut unknown_programs: Vec<(usize, Pubkey)> = Vec::new();
+        // (instruction, source, delegate, amount, decimals if stated)
+        let mut approvals: Vec<(usize, Pubkey, Pubkey, u64, Option<u8>)> = Vec::new();
+
+        for index in 0..message.instructions.len() {
+            let ix = message_instruction(message, index).ok_or_else(|| {
+                ToolkitError::TransactionError(format!(
+                    "Instruction {} references an account outside the message",
+                    index
+                ))
+            })?;
+            let key = |i: usize| ix.accounts.get(i).map(|meta| meta.pubkey);
+
+            let spend = if ix.program_id == system_program::id() {
+                match bincode::deserialize::<SystemInstruction>(&ix.data) {
+                    Ok(SystemInstruction::Transfer { lamports })
+                    | Ok(SystemInstruction::CreateAccount { lamports, .. }) => {
+                        key(0).zip(key(1)).map(|(from, to)| (from, to, lamports, false))
+                    }
+                    Ok(SystemInstruction::TransferWithSeed { lamports, .. }) => {
+                        key(0).zip(key(2)).map(|(from, to)| (from, to, lamports, false))
+                    }
+                    _ => None,
+                }
+            } else if ix.program_id == spl_token::id() {
+                match TokenInstruction::unpack(&ix.data) {
+                    Ok(TokenInstruction::Transfer { amount }) => {
+                        key(0).zip(key(1)).map(|(from, to)| (from, to, amount, true))
+                    }
+                    Ok(TokenInstruction::TransferChecked { amount, decimals }) => {
+                        key(0).zip(key(2)).map(|(from, to)| {
+                            token_decimals.insert(from, decimals);
+                            (from, to, amount, true)
+                        })
+                    }
+                    Ok(TokenInstruction::Approve { amount }) => {
+                        if let Some((source, delegate)) = key(0).zip(key(1)) {
+                            approvals.push((index, source, delegate, amount, None));
+                        }
+                        None
+                    }
+                    Ok(TokenInstruction::ApproveChecked { amount, decimals }) => {
+                        if let Some((source, delegate)) = key(0).zip(key(2)) {
+                            approvals.push((index, source, delegate, amount, Some(decimals)));
+                        }
+                        None
+                    }
+                    _ => None,
+                }
+            } else {
+                if ix.program_id != spl_associated_token_account::id()
+                    && ix.program_id != compute_budget::id()
+                {
+                    unknown_programs.push((index, ix.program_id));
+                }
+                None
+            };
+
+            if let Some((from, to, amount, is_token)) = spend {
+                let totals = if is_token { &mut tokens_out } else { &mut lamports_out };
+                match totals.iter_mut().find(|(source, _)| *source == from) {
+                    Some((_, total)) => *total = total.saturating_add(amount),
+                    None => totals.push((from, amount)),
+                }
+                if !destinations.contains(&to) {
+                    destinations.push(to);
+                }
+            }
+        }
+
+        let total_lamports = lamports_out.iter().fold(0u64, |sum, (_, a)| sum.saturating_add(*a));
+        let mut report = SafetyReport::approved(signer, signer, display_amount(total_lamports, 9));
+        report.to_display = match destinations.len() {
+            1 => AddressVerifier::format_address_short(&destinations[0]),
+            n => format!("{} destinations", n),
+        };
+
+        let signs = message
+            .account_keys
+            .iter()
+            .take(message.header.num_required_signatures as usize)
+            .any(|key| key == signer);
+        if !signs {
+            report.add_blocker(format!(
+                "{} is not a signer of this transaction",
+                AddressVerifier::format_address_short(signer)
+            ));
+        }
+
+        // Delegates can move the approved amount without asking again
+        for (index, source, delegate, amount, decimals) in &approvals {
+            let source = AddressVerifier::format_address_short(source);
+            if *amount == u64::MAX {
+                report.add_blocker(format!(
+                    "Instruction {} grants {} an unlimited approval over {}; the delegate could \
+                     drain it at any time",
+                    index, delegate, source
+                ));
+                continue;
             }
+            let amount = match decimals {
+                Some(decimals) => display_amount(*amount, *decimals),
+                None => format!("{} base units", amount),
+            };
+            report.add_warning(
+                format!(
+                    "Instruction {} approves {} to spend {} from {}",
+                    index, delegate, amount, source
+                ),
+                RiskLevel::High,
+            );
+        }
+
+        for (index, program) in &unknown_programs {
+            report.add_warning(
+                format!(
+                    "Instruction {} calls unrecognized program {}; its transfers can't be checked",
+                    index, program
+                ),
+                RiskLevel::Medium,
+            );
         }
 
+        // Fetch every source in one request
+        let sources: Vec<Pubkey> =
+            lamports_out.iter().chain(&tokens_out).map(|(source, _)| *source).collect();
+        let accounts = retry(&self.read_retries, || {
+            with_timeout(self.rpc_timeout, "get_multiple_accounts", async {
+                client.get_multiple_accounts(&sources).await.map_err(|e| {
+                    ToolkitError::NetworkError(format!("Failed to fetch transfer sources: {}", e))
+                })
+            })
+        })
+        .await?;
+        let (sol_accounts, token_accounts) = accounts.split_at(lamports_out.len());
+
+        for ((source, total), account) in lamports_out.iter().zip(sol_accounts) {
+            let balance = account.as_ref().map_or(0, |a| a.lamports);
+            let context = format!("{}: ", AddressVerifier::format_address_short(source));
+            self.check_spend(&mut report, *total, 9, balance, &context, true);
+        }
+
+        let mut token_states = Vec::new();
+        for ((source, total), account) in tokens_out.iter().zip(token_accounts) {
+            match account.as_ref().and_then(|a| TokenAccount::unpack(&a.data).ok()) {
+                Some(state) => token_states.push((*source, *total, state)),
+                None => report.add_blocker(format!(
+                    "{} is not a token account",
+                    AddressVerifier::format_address_short(source)
+                )),
+            }
+        }
+
+        // Token amounts need their mint's decimals unless TransferChecked
+        // already stated them
+        let mut mints: Vec<Pubkey> = token_states
+            .iter()
+            .filter(|(source, _, _)| !token_decimals.contains_key(source))
+            .map(|(_, _, state)| state.mint)
+            .collect();
+        mints.sort();
+        mints.dedup();
+        let mint_decimals: HashMap<Pubkey, u8> = if mints.is_empty() {
+            HashMap::new()
+        } else {
+            retry(&self.read_retries, || {
+                with_timeout(self.rpc_timeout, "get_multiple_accounts", async {
+                    self.mints.resolve_many(client, &mints).await.map_err(|e| {
+                        ToolkitError::NetworkError(format!("Failed to fetch mints: {}", e))
+                    })
+                })
+            })
+            .await?
+            .into_iter()
+            .map(|(mint, details)| (mint, details.decimals))
+            .collect()
+        };
+
+        for (source, total, state) in &token_states {
+            let decimals = token_decimals
+                .get(source)
+                .or_else(|| mint_decimals.get(&state.mint))
+                .copied()
+                .unwrap_or(0);
+            let context = format!("{}: ", AddressVerifier::format_address_short(source));
+            self.check_spend(&mut report, *total, decimals, state.amount, &context, false);
+        }
+
+        self.apply_strict_mode(&mut report);
+
         Ok(report)
     }
 
+    /// Checks 3-5 of [`SafetyProtocol::validate_transfer`]: `amount` against
+    /// `balance`, then (when `usd_priced`) the large-amount threshold. Each
+    /// finding is prefixed with `context`.
+    fn check_spend(
+        &self,
+        report: &mut SafetyReport,
+        amount: u64,
+        decimals: u8,
+        balance: u64,
+        context: &str,
+        usd_priced: bool,
+    ) {
+        let validation = AmountValidator::validate_amount(amount, decimals, balance);
+        add_amount_warnings(report, &validation, context);
+
+        let Some(price) = self.token_price_usd.filter(|_| usd_priced) else {
+            return;
+        };
+        let usd_value = AmountValidator::token_to_human_amount(amount, decimals) * price;
+        if AmountValidator::requires_confirmation(usd_value, self.large_amount_threshold_usd) {
+            report.add_warning(
+                format!(
+                    "{}Large transfer: ~${:.2} USD exceeds ${:.0} threshold",
+                    context, usd_value, self.large_amount_threshold_usd
+                ),
+                RiskLevel::High,
+            );
+        }
+    }
+
+    /// Block SOL sends that leave the sender unable to pay the fee, or with
+    /// a balance the runtime rejects: above zero but not rent exempt.
+    ///
+    /// Sending exactly `balance - fee` empties the account and is allowed.
+    fn check_sol_reserve(&self, report: &mut SafetyReport, amount: u64, balance: u64) {
+        // Zero and over-balance amounts are reported by the amount checks
+        if amount == 0 || amount > balance {
+            return;
+        }
+        let fee = self.estimated_fee_lamports;
+        let Some(remaining) = (balance - amount).checked_sub(fee) else {
+            report.add_blocker(format!(
+                "Transaction cannot pay its own fee: sending {} SOL of {} SOL leaves less than \
+                 the ~{} SOL fee; send at most {} SOL",
+                display_amount(amount, 9),
+                display_amount(balance, 9),
+                display_amount(fee, 9),
+                display_amount(balance.saturating_sub(fee), 9)
+            ));
+            return;
+        };
+
+        let rent_exempt = Rent::default().minimum_balance(0);
+        if remaining > 0 && remaining < rent_exempt {
+            report.add_blocker(format!(
+                "Transfer leaves {} SOL after the fee, below the {} SOL rent-exempt minimum; \
+                 send at most {} SOL, or exactly {} SOL to empty the account",
+                display_amount(remaining, 9),
+                display_amount(rent_exempt, 9),
+                display_amount((balance - fee).saturating_sub(rent_exempt), 9),
+                display_amount(balance - fee, 9)
+            ));
+        }
+    }
+
+    /// Warn when the transfer is worth less than the fee paid to send it.
+    ///
+    /// Needs the token price, and unless `native_sol` is set (the token
+    /// price is then the SOL price) the SOL price.
+    fn check_fee_exceeds_value(
+        &self,
+        report: &mut SafetyReport,
+        amount: u64,
+        decimals: u8,
+        native_sol: bool,
+    ) {
+        let Some(price) = self.token_price_usd else {
+            return;
+        };
+        let sol_price = if native_sol {
+            Some(self.sol_price_usd.unwrap_or(price))
+        } else {
+            self.sol_price_usd
+        };
+        let Some(sol_price) = sol_price else {
+            return;
+        };
+
+        let usd_value = AmountValidator::token_to_human_amount(amount, decimals) * price;
+        let fee_usd = AmountValidator::token_to_human_amount(self.estimated_fee_lamports, 9)
+            * sol_price;
+
+        if amount > 0 && usd_value < fee_usd {
+            report.add_warning(
+                format!(
+                    "Tiny transfer: ~${:.6} USD is less than the ~${:.6} fee; \
+                     you'll pay more in fees than you're sending",
+                    usd_value, fee_usd
+                ),
+                RiskLevel::Medium,
+            );
+        }
+    }
+
     /// Validate a transfer synchronously (blocking).
-    pub fn validate_transfer_sync(
+    ///
+    /// Runs [`validate_transfer`](Self::validate_transfer) on a private
+    /// current-thread runtime, so `client` is an async [`SolanaRpc`] such as
+    /// the nonblocking `RpcClient`; the blocking client is not accepted.
+    ///
+    /// # Errors
+    /// Fails with [`ToolkitError::ConfigError`] when called from inside a
+    /// Tokio runtime, where blocking on the private runtime would panic;
+    /// await [`validate_transfer`](Self::validate_transfer) there instead.
+    pub fn validate_transfer_sync<R: SolanaRpc + ?Sized>(
         &self,
-        client: &RpcClient,
+        client: &R,
         from: &Pubkey,
         to: &Pubkey,
         amount: u64,
         decimals: u8,
     ) -> Result<SafetyReport> {
-        // Use tokio runtime if available, otherwise create one
+        if tokio::runtime::Handle::try_current().is_ok() {
+            return Err(ToolkitError::ConfigError(
+                "validate_transfer_sync cannot run inside a Tokio runtime; \
+                 await validate_transfer instead"
+                    .to_string(),
+            ));
+        }
+
         let rt = tokio::runtime::Builder::new_current_thread()
             .enable_all()
             .build()
@@ -303,72 +1489,164 @@ impl SafetyProtocol {
         decimals: u8,
         balance: u64,
     ) -> SafetyReport {
-        let amount_display = AmountValidator::format_amount(amount, decimals);
+        let amount_display = display_amount(amount, decimals);
         let mut report = SafetyReport::approved(from, to, amount_display);
 
         // Verify addresses
-        if let Err(e) = AddressVerifier::verify_address(&from.to_string()) {
-            report.add_blocker(format!("Invalid sender address: {}", e));
-        }
+        check_addresses(&mut report, from, to);
 
-        if let Err(e) = AddressVerifier::verify_address(&to.to_string()) {
-            report.add_blocker(format!("Invalid recipient address: {}", e));
-        }
+        // Validate amount and check for large amounts
+        self.check_spend(&mut report, amount, decimals, balance, "", true);
+        self.check_fee_exceeds_value(&mut report, amount, decimals, false);
 
-        // Check for self-transfer
-        if from == to {
-            report.add_warning("Sending to yourself".to_string(), RiskLevel::Medium);
-        }
+        // Strict mode
+        self.apply_strict_mode(&mut report);
 
-        // Validate amount
-        let validation = AmountValidator::validate_amount(amount, decimals, balance);
+        report
+    }
 
-        if !validation.is_valid {
-            for warning in &validation.warnings {
-                if warning.contains("exceeds balance") || warning.contains("zero") {
-                    report.add_blocker(warning.clone());
-                }
-            }
-        }
+    /// Validate a swap quote for price impact and liquidity risks.
+    ///
+    /// Performs the following checks:
+    /// 1. Price impact at or above `max_impact_pct` blocks the swap
+    /// 2. Price impact above half of `max_impact_pct` requires confirmation
+    /// 3. A single-route swap with noticeable impact is flagged as low liquidity
+    ///
+    /// The report's `amount_display` notes the expected and minimum output
+    /// (for ExactOut quotes, the expected and maximum input). The "from" and
+    /// "to" displays are the input and output mints.
+    #[cfg(feature = "jupiter")]
+    pub fn validate_swap(&self, quote: &QuoteResponse, max_impact_pct: f64) -> SafetyReport {
+        let mut report = self.swap_report(quote, max_impact_pct);
+        self.apply_strict_mode(&mut report);
+        report
+    }
 
-        for warning in &validation.warnings {
-            if !warning.contains("exceeds balance") && !warning.contains("zero") {
-                let level = if warning.contains("entire balance") {
-                    RiskLevel::High
-                } else if warning.contains("%") {
-                    RiskLevel::Medium
-                } else {
-                    RiskLevel::Low
-                };
-                report.add_warning(warning.clone(), level);
+    /// Validate a swap quote like [`validate_swap`](Self::validate_swap) and
+    /// check what it can spend against the input token `balance`.
+    ///
+    /// ExactIn quotes check the input amount like a transfer. ExactOut
+    /// quotes check the maximum input (`other_amount_threshold`) with
+    /// [`AmountValidator::validate_max_input`], so a quote expecting a small
+    /// input still warns when slippage lets it spend most of the balance.
+    /// Only an expected input the balance cannot cover blocks the swap.
+    #[cfg(feature = "jupiter")]
+    pub fn validate_swap_with_balance(
+        &self,
+        quote: &QuoteResponse,
+        max_impact_pct: f64,
+        balance: u64,
+        decimals: u8,
+    ) -> SafetyReport {
+        let mut report = self.swap_report(quote, max_impact_pct);
+
+        if quote.in_amount > balance {
+            report.add_blocker(format!(
+                "Expected input {} exceeds balance {}",
+                AmountValidator::format_amount(quote.in_amount, decimals),
+                AmountValidator::format_amount(balance, decimals)
+            ));
+        } else {
+            let validation = if quote.swap_mode == "ExactOut" {
+                AmountValidator::validate_max_input(quote.other_amount_threshold, balance, decimals)
+            } else {
+                AmountValidator::validate_amount(quote.in_amount, decimals, balance)
+            };
+            let level = if validation.requires_confirmation {
+                RiskLevel::High
+            } else {
+                RiskLevel::Medium
+            };
+            for warning in validation.warnings {
+                report.add_warning(warning.message, level);
             }
         }
 
-        // Check for large amounts
-        if let Some(price) = self.token_price_usd {
-            let human_amount = AmountValidator::token_to_human_amount(amount, decimals);
-            let usd_value = human_amount * price;
+        self.apply_strict_mode(&mut report);
+        report
+    }
 
-            if AmountValidator::requires_confirmation(usd_value, self.large_amount_threshold_usd) {
-                report.add_warning(
-                    format!(
-                        "Large transfer: ~${:.2} USD exceeds ${:.0} threshold",
-                        usd_value, self.large_amount_threshold_usd
-                    ),
-                    RiskLevel::High,
-                );
+    /// Price impact and liquidity checks shared by the swap validators.
+    #[cfg(feature = "jupiter")]
+    fn swap_report(&self, quote: &QuoteResponse, max_impact_pct: f64) -> SafetyReport {
+        let short = |mint: &str| {
+            mint.parse::<Pubkey>()
+                .map(|pk| AddressVerifier::format_address_short(&pk))
+                .unwrap_or_else(|_| mint.to_string())
+        };
+        let mut report = SafetyReport {
+            approved: true,
+            risk_level: RiskLevel::Low,
+            warnings: Vec::new(),
+            blockers: Vec::new(),
+            from_display: short(&quote.input_mint),
+            to_display: short(&quote.output_mint),
+            amount_display: if quote.swap_mode == "ExactOut" {
+                format!(
+                    "expected {} in, maximum {} in, {} out ({} bps slippage)",
+                    quote.in_amount,
+                    quote.other_amount_threshold,
+                    quote.out_amount,
+                    quote.slippage_bps
+                )
+            } else {
+                format!(
+                    "{} in, expected {} out, minimum {} out ({} bps slippage)",
+                    quote.in_amount,
+                    quote.out_amount,
+                    quote.other_amount_threshold,
+                    quote.slippage_bps
+                )
+            },
+            requires_confirmation: false,
+        };
+
+        match quote.price_impact_pct.parse::<f64>() {
+            Ok(impact) => {
+                // 1-2. Price impact
+                if impact >= max_impact_pct {
+                    report.add_blocker(format!(
+                        "Price impact {:.2}% exceeds maximum {:.2}%",
+                        impact, max_impact_pct
+                    ));
+                } else if impact > max_impact_pct / 2.0 {
+                    report.add_warning(
+                        format!(
+                            "High price impact: {:.2}% (maximum {:.2}%)",
+                            impact, max_impact_pct
+                        ),
+                        RiskLevel::High,
+                    );
+                }
+
+                // 3. Thin single-pool routes
+                if quote.route_plan.len() == 1 && impact >= SINGLE_ROUTE_IMPACT_PCT {
+                    report.add_warning(
+                        format!(
+                            "Single-route swap with {:.2}% impact suggests low liquidity",
+                            impact
+                        ),
+                        RiskLevel::Medium,
+                    );
+                }
             }
+            Err(_) => report.add_warning(
+                format!("Unknown price impact: {:?}", quote.price_impact_pct),
+                RiskLevel::High,
+            ),
         }
 
-        // Strict mode
+        report
+    }
+
+    /// In strict mode, turn every warning into a blocker.
+    fn apply_strict_mode(&self, report: &mut SafetyReport) {
         if self.strict_mode && !report.warnings.is_empty() {
             let warnings: Vec<String> = report.warnings.drain(..).collect();
             for warning in warnings {
                 report.add_blocker(format!("STRICT: {}", warning));
             }
         }
-
-        report
     }
 }
 
@@ -415,6 +1693,19 @@ mod tests {
         assert!(RiskLevel::High < RiskLevel::Critical);
     }
 
+    #[test]
+    fn test_risk_level_parse_and_serde_round_trip() {
+        for level in [RiskLevel::Low, RiskLevel::Medium, RiskLevel::High, RiskLevel::Critical] {
+            assert_eq!(level.to_string().parse::<RiskLevel>().unwrap(), level);
+            let json = serde_json::to_string(&level).unwrap();
+            assert_eq!(serde_json::from_str::<RiskLevel>(&json).unwrap(), level);
+        }
+
+        assert_eq!("high".parse::<RiskLevel>().unwrap(), RiskLevel::High);
+        assert_eq!(serde_json::to_string(&RiskLevel::Critical).unwrap(), "\"CRITICAL\"");
+        assert!("severe".parse::<RiskLevel>().is_err());
+    }
+
     #[test]
     fn test_risk_level_blocking() {
         assert!(!RiskLevel::Low.is_blocking());
@@ -496,6 +1787,47 @@ mod tests {
         assert!(report.requires_confirmation);
     }
 
+    #[test]
+    fn test_amount_warnings_classified_by_kind() {
+        use crate::safety::{AmountWarning, WarningSeverity};
+
+        let warning = |kind, message: &str| AmountWarning {
+            kind,
+            severity: WarningSeverity::Low,
+            message: message.to_string(),
+        };
+        let validation = |warnings| AmountValidation {
+            is_valid: true,
+            warnings,
+            requires_confirmation: false,
+            human_readable: "1".to_string(),
+            amount: 1,
+        };
+        let classify = |warnings| {
+            let (from, to) = (test_pubkey_1(), test_pubkey_2());
+            let mut report = SafetyReport::approved(&from, &to, "1".to_string());
+            add_amount_warnings(&mut report, &validation(warnings), "");
+            report
+        };
+
+        // Reworded messages, including ones the old substring checks keyed on
+        let report = classify(vec![warning(AmountWarningKind::EntireBalance, "All of it")]);
+        assert_eq!(report.risk_level, RiskLevel::High);
+        assert!(report.approved);
+
+        let report = classify(vec![warning(AmountWarningKind::MostOfBalance, "entire balance")]);
+        assert_eq!(report.risk_level, RiskLevel::Medium);
+        assert_eq!(report.warnings, vec!["entire balance".to_string()]);
+
+        let report = classify(vec![warning(AmountWarningKind::ExceedsBalance, "Too much")]);
+        assert!(!report.approved);
+        assert_eq!(report.blockers, vec!["Too much".to_string()]);
+
+        let report = classify(vec![warning(AmountWarningKind::ZeroAmount, "95% of nothing")]);
+        assert!(!report.approved);
+        assert!(report.warnings.is_empty());
+    }
+
     #[test]
     fn test_large_amount_warning() {
         let protocol = SafetyProtocol::new()
@@ -516,6 +1848,65 @@ mod tests {
         assert!(report.requires_confirmation);
     }
 
+    #[test]
+    fn test_risk_score() {
+        let (from, to) = (test_pubkey_1(), test_pubkey_2());
+        let balance = 100 * LAMPORTS_PER_SOL;
+
+        let clean =
+            SafetyProtocol::new().validate_offline(&from, &to, LAMPORTS_PER_SOL, 9, balance);
+        assert_eq!(clean.risk_score(), 0);
+
+        let large = SafetyProtocol::new()
+            .token_price(100.0)
+            .validate_offline(&from, &to, 15 * LAMPORTS_PER_SOL, 9, balance);
+        assert_eq!(large.warnings.len(), 1);
+        assert_eq!(large.risk_score(), RiskLevel::High.weight() + 5);
+        assert!((20..80).contains(&large.risk_score()));
+
+        let blocked = SafetyProtocol::new().validate_offline(&from, &to, 2 * balance, 9, balance);
+        assert_eq!(blocked.risk_score(), 100);
+
+        // Warnings add at most 25 and never reach the blocked score
+        let mut noisy = clean.clone();
+        noisy.risk_level = RiskLevel::Critical;
+        noisy.warnings = vec!["warning".to_string(); 10];
+        assert_eq!(noisy.risk_score(), 99);
+
+        assert!(RiskLevel::Low.weight() < RiskLevel::Medium.weight());
+        assert!(RiskLevel::High.weight() < RiskLevel::Critical.weight());
+    }
+
+    #[test]
+    fn test_tiny_transfer_fee_warning() {
+        let from = test_pubkey_1();
+        let to = test_pubkey_2();
+        let balance = 10 * LAMPORTS_PER_SOL;
+
+        // 1,000 lamports of SOL is worth less than a 5,000-lamport fee
+        let protocol = SafetyProtocol::new().token_price(150.0).sol_price(150.0);
+        let report = protocol.validate_offline(&from, &to, 1_000, 9, balance);
+        assert!(report.approved);
+        assert_eq!(report.risk_level, RiskLevel::Medium);
+        assert!(report.warnings.iter().any(|w| w.contains("more in fees")));
+
+        let report = protocol.validate_offline(&from, &to, LAMPORTS_PER_SOL, 9, balance);
+        assert!(!report.warnings.iter().any(|w| w.contains("more in fees")));
+
+        // 0.0001 of a $0.50 token vs. a 10,000-lamport fee at $150/SOL
+        let protocol = SafetyProtocol::new()
+            .token_price(0.5)
+            .sol_price(150.0)
+            .estimated_fee(10_000);
+        let report = protocol.validate_offline(&from, &to, 100, 6, 1_000_000_000);
+        assert!(report.warnings.iter().any(|w| w.contains("more in fees")));
+
+        // Without a SOL price the check is skipped for non-SOL tokens
+        let protocol = SafetyProtocol::new().token_price(0.5);
+        let report = protocol.validate_offline(&from, &to, 100, 6, 1_000_000_000);
+        assert!(!report.warnings.iter().any(|w| w.contains("more in fees")));
+    }
+
     #[test]
     fn test_self_transfer_warning() {
         let protocol = SafetyProtocol::new();
@@ -576,7 +1967,10 @@ mod tests {
 
         let report = protocol.validate_offline(&from, &to, amount, 9, balance);
 
-        assert!(report.amount_display.contains("1.5"));
+        assert_eq!(report.amount_display, "1.5");
+
+        let report = protocol.validate_offline(&from, &to, 1_234_567_000_000, 9, u64::MAX);
+        assert_eq!(report.amount_display, "1,234.567");
     }
 
     #[test]
@@ -597,4 +1991,851 @@ mod tests {
         // Risk level should be highest of all warnings
         assert!(report.risk_level >= RiskLevel::High);
     }
+
+    #[tokio::test]
+    async fn test_validate_transfer_with_mock_rpc() {
+        use crate::testing::MockRpc;
+
+        let protocol = SafetyProtocol::new();
+        let from = test_pubkey_1();
+        let to = test_pubkey_2();
+        let rpc = MockRpc::new().with_balance(from, 2 * LAMPORTS_PER_SOL);
+
+        let report = protocol
+            .validate_transfer(&rpc, &from, &to, LAMPORTS_PER_SOL / 2, 9)
+            .await
+            .unwrap();
+        assert!(report.approved);
+
+        let report = protocol
+            .validate_transfer(&rpc, &from, &to, 5 * LAMPORTS_PER_SOL, 9)
+            .await
+            .unwrap();
+        assert!(!report.approved);
+        assert!(report.blockers.iter().any(|b| b.contains("exceeds balance")));
+    }
+
+    #[test]
+    fn test_validate_transfer_sync_outside_runtime_only() {
+        use crate::testing::MockRpc;
+
+        let protocol = SafetyProtocol::new();
+        let from = test_pubkey_1();
+        let to = test_pubkey_2();
+        let rpc = MockRpc::new().with_balance(from, 2 * LAMPORTS_PER_SOL);
+
+        let report = protocol
+            .validate_transfer_sync(&rpc, &from, &to, LAMPORTS_PER_SOL / 2, 9)
+            .unwrap();
+        assert!(report.approved);
+
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let err = runtime
+            .block_on(async {
+                protocol.validate_transfer_sync(&rpc, &from, &to, LAMPORTS_PER_SOL / 2, 9)
+            })
+            .unwrap_err();
+        assert!(matches!(err, ToolkitError::ConfigError(_)), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_validate_transfer_reserves_fee_and_rent() {
+        use crate::testing::MockRpc;
+
+        let protocol = SafetyProtocol::new();
+        let from = test_pubkey_1();
+        let to = test_pubkey_2();
+        let balance = 2 * LAMPORTS_PER_SOL;
+        let fee = DEFAULT_ESTIMATED_FEE_LAMPORTS;
+        let rent_exempt = Rent::default().minimum_balance(0);
+        let rpc = MockRpc::new().with_balance(from, balance);
+        let blockers = |amount: u64, decimals: u8| {
+            let (rpc, protocol) = (&rpc, &protocol);
+            async move {
+                let report = if decimals == 9 {
+                    let kind = TokenKind::NativeSol;
+                    protocol.validate_transfer_of(rpc, &from, &to, amount, kind).await
+                } else {
+                    protocol.validate_transfer(rpc, &from, &to, amount, decimals).await
+                };
+                let report = report.unwrap();
+                assert_eq!(report.approved, report.blockers.is_empty());
+                report.blockers
+            }
+        };
+
+        // Sending everything, or anything past balance - fee, can't pay the fee
+        for amount in [balance, balance - fee + 1] {
+            let blockers = blockers(amount, 9).await;
+            let unpaid = blockers.iter().any(|b| b.contains("cannot pay its own fee"));
+            assert!(unpaid, "{:?}", blockers);
+        }
+
+        // Exactly balance - fee empties the account
+        assert!(blockers(balance - fee, 9).await.is_empty());
+
+        // Leaving dust below the rent-exempt minimum is rejected by the runtime
+        let blockers_left = blockers(balance - fee - 1, 9).await;
+        assert!(blockers_left.iter().any(|b| b.contains("rent-exempt")), "{:?}", blockers_left);
+        assert!(blockers(balance - fee - rent_exempt, 9).await.is_empty());
+
+        // Token amounts don't pay the SOL fee, whatever their decimals
+        assert!(blockers(balance, 6).await.is_empty());
+        let report = protocol.validate_transfer(&rpc, &from, &to, balance, 9).await.unwrap();
+        assert!(report.approved, "{:?}", report.blockers);
+    }
+
+    #[tokio::test]
+    async fn test_validate_transfer_of_flags_sol_wsol_mixups() {
+        use crate::testing::MockRpc;
+
+        let wsol = spl_token::native_mint::id();
+        let (wallet, other_wallet) = (test_pubkey_1(), test_pubkey_2());
+        let (wsol_account, other_wsol_account) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let wrapped_balance = 5 * LAMPORTS_PER_SOL;
+        let rpc = MockRpc::new()
+            .with_balance(wallet, 10 * LAMPORTS_PER_SOL)
+            .with_balance(other_wallet, LAMPORTS_PER_SOL)
+            .with_account(wsol, MockRpc::mint_account(9, 0))
+            .with_account(wsol_account, MockRpc::token_account(&wsol, &wallet, wrapped_balance))
+            .with_account(other_wsol_account, MockRpc::token_account(&wsol, &other_wallet, 0));
+        let protocol = SafetyProtocol::new();
+        let amount = LAMPORTS_PER_SOL;
+        let validate = |from, to, kind| protocol.validate_transfer_of(&rpc, from, to, amount, kind);
+        let has_warning = |report: &SafetyReport, text: &str| {
+            report.risk_level >= RiskLevel::High
+                && report.warnings.iter().any(|w| w.contains(text))
+        };
+
+        // Matching kinds pass cleanly
+        let report = validate(&wallet, &other_wallet, TokenKind::NativeSol).await.unwrap();
+        assert!(report.approved && report.warnings.is_empty(), "{}", report.summary());
+        let wrapped = TokenKind::wrapped_sol();
+        let report = validate(&wsol_account, &other_wsol_account, wrapped).await.unwrap();
+        assert!(report.approved && report.warnings.is_empty(), "{}", report.summary());
+        assert_eq!(report.amount_display, "1");
+
+        // Native SOL into a WSOL account
+        let report = validate(&wallet, &other_wsol_account, TokenKind::NativeSol).await.unwrap();
+        assert!(has_warning(&report, "Recipient is a WSOL token account"), "{:?}", report.warnings);
+
+        // Native SOL out of a WSOL account
+        let report = validate(&wsol_account, &other_wallet, TokenKind::NativeSol).await.unwrap();
+        assert!(has_warning(&report, "Sender is a WSOL token account"), "{:?}", report.warnings);
+
+        // WSOL to a wallet
+        let report = validate(&wsol_account, &other_wallet, wrapped).await.unwrap();
+        let warning = "Recipient is a wallet, not a WSOL token account";
+        assert!(has_warning(&report, warning), "{:?}", report.warnings);
+
+        // WSOL from a wallet holding only native SOL
+        let report = validate(&wallet, &other_wsol_account, wrapped).await.unwrap();
+        let warning = "Sender is a wallet, not a WSOL token account";
+        assert!(has_warning(&report, warning), "{:?}", report.warnings);
+        assert!(!report.approved);
+
+        // SPL amounts are checked against the token balance, not lamports
+        let report = protocol
+            .validate_transfer_of(&rpc, &wsol_account, &other_wsol_account, 6 * amount, wrapped)
+            .await
+            .unwrap();
+        assert!(report.blockers.iter().any(|b| b.contains("exceeds balance")));
+    }
+
+    #[tokio::test]
+    async fn test_validate_transfer_of_warns_on_transfer_fee() {
+        use crate::testing::MockRpc;
+        use solana_sdk::clock::Clock;
+
+        let mint = Pubkey::new_unique();
+        let (from, to) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let token_2022_account = |owner: &Pubkey, amount| {
+            let mut account = MockRpc::token_account(&mint, owner, amount);
+            account.owner = spl_token_2022::id();
+            account
+        };
+        let rpc = MockRpc::new()
+            .with_account(mint, MockRpc::transfer_fee_mint_account(6, 100, u64::MAX))
+            .with_account(from, token_2022_account(&test_pubkey_1(), 10_000_000))
+            .with_account(to, token_2022_account(&test_pubkey_2(), 0))
+            .with_clock(&Clock::default());
+
+        let report = SafetyProtocol::new()
+            .validate_transfer_of(&rpc, &from, &to, 1_000_000, TokenKind::Spl(mint))
+            .await
+            .unwrap();
+        assert!(report.approved, "{}", report.summary());
+        assert_eq!(report.risk_level, RiskLevel::Medium);
+        let warning = "transfer fee; the recipient receives 0.99 of 1";
+        assert!(report.warnings.iter().any(|w| w.contains(warning)), "{:?}", report.warnings);
+    }
+
+    #[tokio::test]
+    async fn test_check_swap_output_warns_on_transfer_fee() {
+        use crate::testing::MockRpc;
+        use solana_sdk::clock::Clock;
+
+        let (fee_mint, plain_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let rpc = MockRpc::new()
+            .with_account(fee_mint, MockRpc::transfer_fee_mint_account(6, 100, u64::MAX))
+            .with_account(plain_mint, MockRpc::mint_account(6, 0))
+            .with_clock(&Clock::default());
+        let protocol = SafetyProtocol::new();
+
+        let report = protocol.check_swap_output(&rpc, &fee_mint, 2_000_000).await.unwrap();
+        assert!(report.approved, "{}", report.summary());
+        assert_eq!(report.risk_level, RiskLevel::Medium);
+        let warning = "transfer fee; you receive 1.98 of the quoted 2";
+        assert!(report.warnings.iter().any(|w| w.contains(warning)), "{:?}", report.warnings);
+
+        let report = protocol.check_swap_output(&rpc, &plain_mint, 2_000_000).await.unwrap();
+        assert!(report.approved && report.warnings.is_empty(), "{}", report.summary());
+    }
+
+    #[tokio::test]
+    async fn test_validate_transfer_of_checks_spl_recipient() {
+        use crate::testing::MockRpc;
+        use solana_sdk::account::Account;
+
+        let mint = Pubkey::new_unique();
+        let (from, to) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let program = Pubkey::new_unique();
+        let rpc = MockRpc::new()
+            .with_account(mint, MockRpc::mint_account(6, 1_000_000_000))
+            .with_account(from, MockRpc::token_account(&mint, &test_pubkey_1(), 10_000_000))
+            .with_account(to, MockRpc::token_account(&mint, &test_pubkey_2(), 0))
+            .with_account(
+                program,
+                Account {
+                    lamports: LAMPORTS_PER_SOL,
+                    data: vec![],
+                    owner: solana_sdk::bpf_loader_upgradeable::id(),
+                    executable: true,
+                    rent_epoch: 0,
+                },
+            );
+        let protocol = SafetyProtocol::new();
+        let kind = TokenKind::Spl(mint);
+        let validate = |to| protocol.validate_transfer_of(&rpc, &from, to, 1_000_000, kind);
+
+        assert!(validate(&to).await.unwrap().approved);
+
+        // Mints and programs can't hold tokens
+        for recipient in [&mint, &program] {
+            let report = validate(recipient).await.unwrap();
+            assert!(!report.approved);
+            assert_eq!(report.risk_level, RiskLevel::Critical);
+        }
+        let report = validate(&mint).await.unwrap();
+        assert!(report.blockers.iter().any(|b| b.contains("token mint, not a wallet")));
+
+        let report = validate(&from).await.unwrap();
+        assert!(report.warnings.iter().any(|w| w.contains("yourself")), "{:?}", report.warnings);
+
+        let report = SafetyProtocol::new()
+            .min_recipient_age(Duration::from_secs(86_400))
+            .validate_transfer_of(&rpc, &from, &to, 1_000_000, kind)
+            .await
+            .unwrap();
+        assert!(report.warnings.iter().any(|w| w.contains("no transaction history")));
+
+        // 0.0001 of a $0.50 token is worth less than the fee at $150/SOL
+        let report = SafetyProtocol::new()
+            .token_price(0.5)
+            .sol_price(150.0)
+            .validate_transfer_of(&rpc, &from, &to, 100, kind)
+            .await
+            .unwrap();
+        assert!(report.warnings.iter().any(|w| w.contains("more in fees")));
+    }
+
+    #[tokio::test]
+    async fn test_validate_native_transfer_fetches_accounts_once() {
+        use crate::testing::MockRpc;
+
+        let (from, to) = (test_pubkey_1(), test_pubkey_2());
+        let rpc = MockRpc::new()
+            .with_balance(from, 2 * LAMPORTS_PER_SOL)
+            .with_balance(to, LAMPORTS_PER_SOL);
+
+        let report = SafetyProtocol::new()
+            .validate_transfer_of(&rpc, &from, &to, LAMPORTS_PER_SOL, TokenKind::NativeSol)
+            .await
+            .unwrap();
+        assert!(report.approved);
+        assert_eq!(rpc.account_requests(), vec![vec![from, to]]);
+    }
+
+    #[tokio::test]
+    async fn test_validate_and_send_only_sends_approved_transfers() {
+        use crate::testing::MockRpc;
+        use std::cell::Cell;
+
+        let protocol = SafetyProtocol::new().token_price(100.0).large_amount_threshold(1000.0);
+        let from = Keypair::new();
+        let to = test_pubkey_2();
+        let rpc = MockRpc::new().with_balance(from.pubkey(), 100 * LAMPORTS_PER_SOL);
+        let asked = Cell::new(0);
+        let deny = |_: &SafetyReport| {
+            asked.set(asked.get() + 1);
+            false
+        };
+
+        // Blocked: more than the balance, and confirm is never asked
+        let (report, signature) = protocol
+            .validate_and_send(&rpc, &from, &to, 500 * LAMPORTS_PER_SOL, 9, deny)
+            .await
+            .unwrap();
+        assert!(!report.approved);
+        assert!(signature.is_none());
+        assert_eq!(asked.get(), 0);
+
+        // Needs confirmation ($1500 is over the threshold), and it's denied
+        let (report, signature) = protocol
+            .validate_and_send(&rpc, &from, &to, 15 * LAMPORTS_PER_SOL, 9, deny)
+            .await
+            .unwrap();
+        assert!(report.requires_confirmation);
+        assert!(signature.is_none());
+        assert_eq!(asked.get(), 1);
+        assert!(rpc.sent_transactions().is_empty());
+
+        // Confirmed
+        let (_, signature) = protocol
+            .validate_and_send(&rpc, &from, &to, 15 * LAMPORTS_PER_SOL, 9, |_| true)
+            .await
+            .unwrap();
+        assert!(signature.is_some());
+        assert_eq!(rpc.sent_transactions().len(), 1);
+
+        // Approved without asking
+        let (report, signature) = protocol
+            .validate_and_send(&rpc, &from, &to, LAMPORTS_PER_SOL, 9, deny)
+            .await
+            .unwrap();
+        assert!(report.approved && !report.requires_confirmation);
+        assert_eq!(asked.get(), 1);
+        let sent = rpc.sent_transactions();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(Some(sent[1].signatures[0]), signature);
+        assert_eq!(sent[1].message.account_keys[0], from.pubkey());
+        assert!(sent[1].message.account_keys.contains(&to));
+
+        let err = protocol
+            .validate_and_send(&rpc, &from, &to, LAMPORTS_PER_SOL, 6, |_| true)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolkitError::ConfigError(_)));
+        assert_eq!(rpc.sent_transactions().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_decide_maps_reports_to_decisions() {
+        use crate::testing::MockRpc;
+
+        let protocol = SafetyProtocol::new().token_price(100.0).large_amount_threshold(1000.0);
+        let from = test_pubkey_1();
+        let to = test_pubkey_2();
+        let rpc = MockRpc::new().with_balance(from, 100 * LAMPORTS_PER_SOL);
+
+        let decision = protocol.decide(&rpc, &from, &to, LAMPORTS_PER_SOL, 9).await.unwrap();
+        assert!(decision.is_approved());
+        assert!(decision.report().is_none());
+
+        // $1500 is over the threshold
+        let decision = protocol.decide(&rpc, &from, &to, 15 * LAMPORTS_PER_SOL, 9).await.unwrap();
+        match decision {
+            SafetyDecision::NeedsConfirmation(report) => {
+                assert!(report.approved);
+                assert!(report.warnings.iter().any(|w| w.contains("Large transfer")));
+            }
+            other => panic!("expected NeedsConfirmation, got {:?}", other),
+        }
+
+        let decision =
+            protocol.decide(&rpc, &from, &to, 500 * LAMPORTS_PER_SOL, 9).await.unwrap();
+        match decision {
+            SafetyDecision::Blocked(report) => {
+                assert!(report.blockers.iter().any(|b| b.contains("exceeds balance")));
+            }
+            other => panic!("expected Blocked, got {:?}", other),
+        }
+
+        // Strict mode turns the confirmation into a block
+        let decision = protocol
+            .strict()
+            .decide(&rpc, &from, &to, 15 * LAMPORTS_PER_SOL, 9)
+            .await
+            .unwrap();
+        assert!(matches!(decision, SafetyDecision::Blocked(_)));
+    }
+
+    #[tokio::test]
+    async fn test_recipient_age_check() {
+        use crate::testing::MockRpc;
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+        let entry = |seconds_ago: i64| RpcConfirmedTransactionStatusWithSignature {
+            signature: solana_sdk::signature::Signature::new_unique().to_string(),
+            slot: 1,
+            err: None,
+            memo: None,
+            block_time: Some(now - seconds_ago),
+            confirmation_status: None,
+        };
+        let from = test_pubkey_1();
+        let (young, old, unused) = (Pubkey::new_unique(), Pubkey::new_unique(), test_pubkey_2());
+        let rpc = MockRpc::new().with_balance(from, 2 * LAMPORTS_PER_SOL);
+        rpc.set_signature_history(young, vec![entry(60), entry(600)]);
+        rpc.set_signature_history(old, vec![entry(60), entry(90 * 86_400)]);
+
+        let protocol = SafetyProtocol::new().min_recipient_age(Duration::from_secs(86_400));
+        let check = |to| protocol.validate_transfer(&rpc, &from, to, 1_000_000, 9);
+
+        let report = check(&young).await.unwrap();
+        assert!(report.approved);
+        assert_eq!(report.risk_level, RiskLevel::Medium);
+        assert!(
+            report.warnings.iter().any(|w| w.contains("only 10 minutes ago")),
+            "{:?}",
+            report.warnings
+        );
+
+        let report = check(&old).await.unwrap();
+        assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+
+        let report = check(&unused).await.unwrap();
+        assert!(report.warnings.iter().any(|w| w.contains("no transaction history")));
+
+        // Off by default
+        let report = SafetyProtocol::new()
+            .validate_transfer(&rpc, &from, &young, 1_000_000, 9)
+            .await
+            .unwrap();
+        assert!(report.warnings.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_validate_transfer_times_out_stalled_reads() {
+        use crate::testing::MockRpc;
+        use std::time::{Duration, Instant};
+
+        let from = test_pubkey_1();
+        let rpc = MockRpc::new().with_balance(from, 2 * LAMPORTS_PER_SOL);
+        rpc.set_latency(Duration::from_secs(5));
+
+        let started = Instant::now();
+        let err = SafetyProtocol::new()
+            .timeout(Duration::from_millis(50))
+            .validate_transfer(&rpc, &from, &test_pubkey_2(), LAMPORTS_PER_SOL, 9)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, ToolkitError::Timeout(_)), "{}", err);
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_validate_transfer_blocks_mint_recipient() {
+        use crate::testing::MockRpc;
+
+        let protocol = SafetyProtocol::new();
+        let from = test_pubkey_1();
+        let wallet = test_pubkey_2();
+        let mint = Pubkey::new_unique();
+        let rpc = MockRpc::new()
+            .with_balance(from, 2 * LAMPORTS_PER_SOL)
+            .with_balance(wallet, LAMPORTS_PER_SOL)
+            .with_account(mint, MockRpc::mint_account(6, 1_000_000));
+
+        for decimals in [9, 6] {
+            let report = protocol
+                .validate_transfer(&rpc, &from, &mint, LAMPORTS_PER_SOL / 2, decimals)
+                .await
+                .unwrap();
+            assert!(!report.approved);
+            assert_eq!(report.risk_level, RiskLevel::Critical);
+            assert!(report.blockers.iter().any(|b| b.contains("token mint, not a wallet")));
+        }
+
+        let report = protocol
+            .validate_transfer(&rpc, &from, &wallet, LAMPORTS_PER_SOL / 2, 9)
+            .await
+            .unwrap();
+        assert!(report.approved);
+        assert!(report.blockers.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_validate_transfer_recipient_owner() {
+        use crate::testing::MockRpc;
+        use solana_sdk::account::Account;
+
+        let protocol = SafetyProtocol::new();
+        let from = test_pubkey_1();
+        let system_recipient = test_pubkey_2();
+        let token_recipient = Pubkey::new_unique();
+        let program_recipient = Pubkey::new_unique();
+        let program = Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: vec![],
+            owner: solana_sdk::bpf_loader_upgradeable::id(),
+            executable: true,
+            rent_epoch: 0,
+        };
+        let rpc = MockRpc::new()
+            .with_balance(from, 2 * LAMPORTS_PER_SOL)
+            .with_balance(system_recipient, LAMPORTS_PER_SOL)
+            .with_account(
+                token_recipient,
+                MockRpc::token_account(&Pubkey::new_unique(), &from, 0),
+            )
+            .with_account(program_recipient, program);
+        let half = LAMPORTS_PER_SOL / 2;
+
+        let report = protocol
+            .validate_transfer_of(&rpc, &from, &system_recipient, half, TokenKind::NativeSol)
+            .await
+            .unwrap();
+        assert!(report.approved);
+        assert!(report.warnings.is_empty());
+
+        let report = protocol
+            .validate_transfer_of(&rpc, &from, &token_recipient, half, TokenKind::NativeSol)
+            .await
+            .unwrap();
+        assert!(report.approved);
+        assert_eq!(report.risk_level, RiskLevel::High);
+        assert!(report.warnings.iter().any(|w| w.contains("token account")));
+
+        // A token with 9 decimals is not SOL; token accounts receive it
+        let report = protocol
+            .validate_transfer(&rpc, &from, &token_recipient, LAMPORTS_PER_SOL / 2, 9)
+            .await
+            .unwrap();
+        assert!(report.approved);
+        assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+
+        for native_sol in [true, false] {
+            let report = if native_sol {
+                let kind = TokenKind::NativeSol;
+                protocol.validate_transfer_of(&rpc, &from, &program_recipient, half, kind).await
+            } else {
+                protocol.validate_transfer(&rpc, &from, &program_recipient, half, 6).await
+            };
+            let report = report.unwrap();
+            assert!(!report.approved);
+            assert_eq!(report.risk_level, RiskLevel::Critical);
+            assert!(report.blockers.iter().any(|b| b.contains("executable")));
+        }
+
+        // Offline mode skips the lookup entirely
+        let report = SafetyProtocol::new()
+            .verify_recipient(false)
+            .validate_transfer_of(&rpc, &from, &program_recipient, half, TokenKind::NativeSol)
+            .await
+            .unwrap();
+        assert!(report.approved);
+    }
+
+    #[test]
+    fn test_typed_confirmation() {
+        let protocol = SafetyProtocol::new().token_price(100.0);
+        let report = protocol.validate_offline(
+            &test_pubkey_1(),
+            &test_pubkey_2(),
+            1_234_500_000_000,
+            9,
+            2_000 * LAMPORTS_PER_SOL,
+        );
+        assert!(report.requires_confirmation);
+
+        let challenge = SafetyProtocol::require_typed_confirmation(&report);
+        assert_eq!(challenge.expected_amount, "1,234.5");
+        assert_eq!(challenge.expected_suffix, &TEST_ADDR_2[TEST_ADDR_2.len() - 4..]);
+        assert!(challenge.prompt().contains("1,234.5"));
+
+        assert!(SafetyProtocol::verify_confirmation(&challenge, "1,234.5", "AWWM"));
+        assert!(SafetyProtocol::verify_confirmation(&challenge, " 1234.50 ", "AWWM "));
+        assert!(!SafetyProtocol::verify_confirmation(&challenge, "1234.5", "AWWN"));
+        assert!(!SafetyProtocol::verify_confirmation(&challenge, "1234.5", "awwm"));
+        assert!(!SafetyProtocol::verify_confirmation(&challenge, "12345", "AWWM"));
+    }
+
+    #[tokio::test]
+    async fn test_validate_transfer_chain() {
+        use crate::testing::MockRpc;
+
+        let (sender, vault, recipient) = (test_pubkey_1(), test_pubkey_2(), Pubkey::new_unique());
+        let rpc = MockRpc::new()
+            .with_balance(sender, 2 * LAMPORTS_PER_SOL)
+            .with_balance(vault, LAMPORTS_PER_SOL / 10);
+        let protocol = SafetyProtocol::new();
+        let hops = [sender, vault, recipient];
+
+        // The vault forwards what it receives plus some of its own balance
+        let amounts = [LAMPORTS_PER_SOL, LAMPORTS_PER_SOL + LAMPORTS_PER_SOL / 20];
+        let report = protocol.validate_transfer_chain(&rpc, &hops, &amounts).await.unwrap();
+        assert!(report.approved, "{:?}", report.blockers);
+        assert_eq!(report.amount_display, "1 -> 1.05");
+        assert_eq!(report.to_display, AddressVerifier::format_address_short(&recipient));
+
+        let amounts = [LAMPORTS_PER_SOL, 2 * LAMPORTS_PER_SOL];
+        let report = protocol.validate_transfer_chain(&rpc, &hops, &amounts).await.unwrap();
+        assert!(!report.approved);
+        assert_eq!(report.blockers.len(), 1);
+        assert!(report.blockers[0].starts_with("Hop 1"));
+
+        // Missing intermediates are flagged, programs block
+        let missing = Pubkey::new_unique();
+        let amounts = [1_000, 1_000];
+        let report = protocol
+            .validate_transfer_chain(&rpc, &[sender, missing, recipient], &amounts)
+            .await
+            .unwrap();
+        assert!(report.approved);
+        assert_eq!(report.risk_level, RiskLevel::High);
+
+        let program = Pubkey::new_unique();
+        let mut program_account = solana_sdk::account::Account::new(1, 0, &Pubkey::new_unique());
+        program_account.executable = true;
+        rpc.set_account(program, program_account);
+        let report = protocol
+            .validate_transfer_chain(&rpc, &[sender, program, recipient], &amounts)
+            .await
+            .unwrap();
+        assert!(report.blockers[0].contains("executable program"));
+
+        assert!(protocol.validate_transfer_chain(&rpc, &hops, &[1]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_validate_transaction_aggregates_transfers() {
+        use crate::testing::MockRpc;
+        use solana_sdk::{instruction::Instruction, message::Message, system_instruction};
+
+        let signer = test_pubkey_1();
+        let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let rpc = MockRpc::new().with_balance(signer, 10 * LAMPORTS_PER_SOL);
+        let protocol = SafetyProtocol::new();
+        let send = |to: &Pubkey, lamports: u64| system_instruction::transfer(&signer, to, lamports);
+
+        // Each transfer is 46% of the balance; together they are 92%
+        let half = 46 * LAMPORTS_PER_SOL / 10;
+        let single = protocol.validate_offline(&signer, &alice, half, 9, 10 * LAMPORTS_PER_SOL);
+        assert!(single.warnings.is_empty());
+
+        let tx = Transaction::new_unsigned(Message::new(
+            &[send(&alice, half), send(&bob, half)],
+            Some(&signer),
+        ));
+        let report = protocol.validate_transaction(&rpc, &tx, &signer).await.unwrap();
+        assert!(report.approved, "{:?}", report.blockers);
+        assert_eq!(report.risk_level, RiskLevel::Medium);
+        assert_eq!(report.warnings.len(), 1);
+        assert!(report.warnings[0].contains("92.0%"), "{}", report.warnings[0]);
+        assert_eq!(report.amount_display, "9.2");
+        assert_eq!(report.to_display, "2 destinations");
+
+        // Together they exceed the balance
+        let tx = Transaction::new_unsigned(Message::new(
+            &[send(&alice, 6 * LAMPORTS_PER_SOL), send(&bob, 6 * LAMPORTS_PER_SOL)],
+            Some(&signer),
+        ));
+        let report = protocol.validate_transaction(&rpc, &tx, &signer).await.unwrap();
+        assert!(!report.approved);
+        assert!(report.blockers[0].contains("exceeds balance"));
+
+        // Token transfers sum per source account; unknown programs are flagged
+        let mint = Pubkey::new_unique();
+        let source = Pubkey::new_unique();
+        rpc.set_account(mint, MockRpc::mint_account(6, 1_000_000));
+        rpc.set_account(source, MockRpc::token_account(&mint, &signer, 1_000_000));
+        let token_send = |amount: u64| {
+            let token = spl_token::id();
+            spl_token::instruction::transfer(&token, &source, &alice, &signer, &[], amount).unwrap()
+        };
+        let unknown = Instruction::new_with_bytes(Pubkey::new_unique(), &[], vec![]);
+        let tx = Transaction::new_unsigned(Message::new(
+            &[token_send(600_000), token_send(600_000), unknown],
+            Some(&signer),
+        ));
+        let report = protocol.validate_transaction(&rpc, &tx, &signer).await.unwrap();
+        assert_eq!(report.blockers.len(), 1, "{:?}", report.blockers);
+        let blocker = &report.blockers[0];
+        assert!(blocker.contains("(1.200000) exceeds balance"), "{}", blocker);
+        assert!(report.warnings.iter().any(|w| w.contains("unrecognized program")));
+
+        // The wallet being protected must actually sign
+        let report = protocol.validate_transaction(&rpc, &tx, &bob).await.unwrap();
+        assert!(report.blockers.iter().any(|b| b.contains("not a signer")));
+    }
+
+    #[tokio::test]
+    async fn test_validate_transaction_flags_approvals() {
+        use crate::testing::MockRpc;
+        use solana_sdk::message::Message;
+        use spl_token::instruction::{approve, approve_checked};
+
+        let signer = test_pubkey_1();
+        let (source, mint, delegate) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let rpc = MockRpc::new();
+        let protocol = SafetyProtocol::new();
+        let token = spl_token::id();
+
+        // A bounded approval is allowed but warned about, naming the delegate
+        let bounded =
+            approve_checked(&token, &source, &mint, &delegate, &signer, &[], 2_500_000, 6).unwrap();
+        let tx = Transaction::new_unsigned(Message::new(&[bounded], Some(&signer)));
+        let report = protocol.validate_transaction(&rpc, &tx, &signer).await.unwrap();
+        assert!(report.approved, "{:?}", report.blockers);
+        assert_eq!(report.risk_level, RiskLevel::High);
+        assert_eq!(report.warnings.len(), 1);
+        assert!(report.warnings[0].contains(&delegate.to_string()), "{}", report.warnings[0]);
+        assert!(report.warnings[0].contains("2.5"), "{}", report.warnings[0]);
+
+        // An unlimited one is blocked
+        let unlimited = approve(&token, &source, &delegate, &signer, &[], u64::MAX).unwrap();
+        let tx = Transaction::new_unsigned(Message::new(&[unlimited], Some(&signer)));
+        let report = protocol.validate_transaction(&rpc, &tx, &signer).await.unwrap();
+        assert!(!report.approved);
+        assert_eq!(report.risk_level, RiskLevel::Critical);
+        assert!(report.blockers[0].contains("unlimited approval"), "{}", report.blockers[0]);
+        assert!(report.blockers[0].contains(&delegate.to_string()), "{}", report.blockers[0]);
+    }
+
+    #[tokio::test]
+    async fn test_check_token_authenticity_freeze_authority() {
+        use crate::testing::MockRpc;
+        use solana_sdk::program_pack::Pack;
+        use spl_token::state::Mint;
+
+        let (plain_mint, freezable_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut freezable = MockRpc::mint_account(6, 1_000);
+        let mut state = Mint::unpack(&freezable.data).unwrap();
+        state.freeze_authority = Some(Pubkey::new_unique()).into();
+        Mint::pack(state, &mut freezable.data).unwrap();
+        let rpc = MockRpc::new()
+            .with_account(plain_mint, MockRpc::mint_account(6, 1_000))
+            .with_account(freezable_mint, freezable)
+            .with_balance(test_pubkey_1(), LAMPORTS_PER_SOL);
+        let protocol = SafetyProtocol::new().sample_frozen_holders(true);
+
+        let report = protocol.check_token_authenticity(&rpc, &plain_mint).await.unwrap();
+        assert!(report.approved);
+        assert_eq!(report.risk_level, RiskLevel::Low);
+
+        let report = protocol.check_token_authenticity(&rpc, &freezable_mint).await.unwrap();
+        assert!(report.approved);
+        assert_eq!(report.risk_level, RiskLevel::Medium);
+        assert!(report.warnings[0].contains("freeze authority"));
+
+        // Token-2022 mints are checked the same way
+        let token_2022_mint = Pubkey::new_unique();
+        rpc.set_account(token_2022_mint, MockRpc::transfer_fee_mint_account(6, 100, 1_000));
+        let report = protocol.check_token_authenticity(&rpc, &token_2022_mint).await.unwrap();
+        assert!(report.approved, "{:?}", report.blockers);
+        assert_eq!(report.risk_level, RiskLevel::Low);
+
+        let report = protocol.check_token_authenticity(&rpc, &test_pubkey_1()).await.unwrap();
+        assert!(!report.approved);
+        let missing = Pubkey::new_unique();
+        let report = protocol.check_token_authenticity(&rpc, &missing).await.unwrap();
+        assert!(report.blockers[0].contains("does not exist"));
+    }
+
+    #[cfg(feature = "jupiter")]
+    fn swap_quote(price_impact_pct: &str, hops: usize) -> QuoteResponse {
+        use crate::jupiter::{RoutePlanStep, SwapInfo};
+
+        let step = RoutePlanStep {
+            swap_info: SwapInfo {
+                amm_key: "amm".to_string(),
+                label: None,
+                input_mint: TEST_ADDR_1.to_string(),
+                output_mint: TEST_ADDR_2.to_string(),
+                in_amount: 1_000_000,
+                out_amount: 5_000_000,
+                fee_amount: 0,
+                fee_mint: TEST_ADDR_1.to_string(),
+            },
+            percent: 100,
+        };
+
+        QuoteResponse {
+            input_mint: TEST_ADDR_1.to_string(),
+            in_amount: 1_000_000,
+            output_mint: TEST_ADDR_2.to_string(),
+            out_amount: 5_000_000,
+            other_amount_threshold: 4_975_000,
+            swap_mode: "ExactIn".to_string(),
+            slippage_bps: 50,
+            price_impact_pct: price_impact_pct.to_string(),
+            route_plan: vec![step; hops],
+            context_slot: None,
+            time_taken: None,
+        }
+    }
+
+    #[cfg(feature = "jupiter")]
+    #[test]
+    fn test_validate_swap_low_impact_approved() {
+        let report = SafetyProtocol::new().validate_swap(&swap_quote("0.1", 1), 5.0);
+
+        assert!(report.approved);
+        assert_eq!(report.risk_level, RiskLevel::Low);
+        assert!(report.warnings.is_empty());
+        assert!(report.amount_display.contains("minimum 4975000"));
+    }
+
+    #[cfg(feature = "jupiter")]
+    #[test]
+    fn test_validate_swap_high_impact_blocked() {
+        let report = SafetyProtocol::new().validate_swap(&swap_quote("30", 1), 5.0);
+
+        assert!(!report.approved);
+        assert_eq!(report.risk_level, RiskLevel::Critical);
+        assert!(report.blockers[0].contains("30.00%"));
+        assert!(report.warnings.iter().any(|w| w.contains("low liquidity")));
+
+        // Moderate impact over several hops needs confirmation but is allowed
+        let report = SafetyProtocol::new().validate_swap(&swap_quote("3", 3), 5.0);
+        assert!(report.approved);
+        assert!(report.requires_confirmation);
+    }
+
+    #[cfg(feature = "jupiter")]
+    #[test]
+    fn test_validate_exact_out_swap_max_input() {
+        let balance = 10_000_000;
+        let mut quote = swap_quote("0.1", 2);
+        quote.swap_mode = "ExactOut".to_string();
+        // Expects to spend 10% of the balance but may spend up to 95%
+        quote.in_amount = 1_000_000;
+        quote.other_amount_threshold = 9_500_000;
+
+        let report = SafetyProtocol::new().validate_swap_with_balance(&quote, 5.0, balance, 6);
+        assert!(report.approved);
+        assert_eq!(report.risk_level, RiskLevel::High);
+        assert!(report.warnings[0].contains("Up to 95.0% of balance"));
+        assert!(report.amount_display.contains("maximum 9500000 in"));
+
+        quote.other_amount_threshold = 6_000_000;
+        let report = SafetyProtocol::new().validate_swap_with_balance(&quote, 5.0, balance, 6);
+        assert_eq!(report.risk_level, RiskLevel::Medium);
+
+        quote.other_amount_threshold = 1_100_000;
+        let report = SafetyProtocol::new().validate_swap_with_balance(&quote, 5.0, balance, 6);
+        assert!(report.warnings.is_empty());
+
+        // The same spend on an ExactIn quote is judged by its input amount
+        let report = SafetyProtocol::new()
+            .validate_swap_with_balance(&swap_quote("0.1", 2), 5.0, balance, 6);
+        assert!(report.warnings.is_empty());
+
+        quote.in_amount = 2 * balance;
+        let report = SafetyProtocol::new().validate_swap_with_balance(&quote, 5.0, balance, 6);
+        assert!(!report.approved);
+    }
 }
@@ -0,0 +1,35 @@
+//! JSON state files that let long-running jobs resume where they stopped.
+
+use std::path::Path;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::error::{Result, ToolkitError};
+
+/// Read the state file at `path`, or `None` if it does not exist.
+///
+/// `kind` names the file in errors, e.g. "checkpoint".
+pub(crate) fn load<T: DeserializeOwned>(path: &Path, kind: &str) -> Result<Option<T>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let contents = std::fs::read_to_string(path).map_err(|e| {
+        ToolkitError::ConfigError(format!("Failed to read {} {}: {}", kind, path.display(), e))
+    })?;
+    serde_json::from_str(&contents).map(Some).map_err(|e| {
+        ToolkitError::ParseError(format!("Invalid {} {}: {}", kind, path.display(), e))
+    })
+}
+
+/// Write `state` to `path`, replacing it atomically.
+pub(crate) fn save<T: Serialize>(state: &T, path: &Path, kind: &str) -> Result<()> {
+    let json = serde_json::to_string_pretty(state)
+        .map_err(|e| ToolkitError::Custom(format!("Failed to encode {}: {}", kind, e)))?;
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, json)
+        .and_then(|_| std::fs::rename(&tmp, path))
+        .map_err(|e| {
+            ToolkitError::ConfigError(format!("Failed to write {} {}: {}", kind, path.display(), e))
+        })
+}
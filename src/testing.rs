@@ -0,0 +1,731 @@
+//! Test utilities.
+//!
+//! [`MockRpc`] is an in-memory [`SolanaRpc`] implementation for driving the
+//! crate's helper types in unit tests without a validator.
+//!
+//! # Example
+//! ```ignore
+//! let rpc = MockRpc::new()
+//!     .with_account(token_account, MockRpc::token_account(&mint, &owner, 0));
+//! let cleaner = RentCleaner::from_client(rpc, owner_keypair);
+//! let empty = cleaner.find_empty_token_accounts().await?;
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use solana_account_decoder::{UiAccount, UiAccountEncoding, UiDataSliceConfig};
+use solana_client::{
+    rpc_config::{RpcProgramAccountsConfig, RpcSendTransactionConfig},
+    rpc_response::{
+        RpcConfirmedTransactionStatusWithSignature, RpcPrioritizationFee,
+        RpcSimulateTransactionResult,
+    },
+};
+use solana_sdk::{
+    account::{Account, AccountSharedData}, clock::Clock, hash::Hash, program_option::COption,
+    program_pack::Pack, pubkey::Pubkey, rent::Rent, signature::Signature, system_program, sysvar,
+    transaction::{Transaction, TransactionError},
+};
+use solana_transaction_status::{TransactionConfirmationStatus, TransactionStatus};
+use spl_token::state::{Account as TokenAccount, AccountState, Mint};
+
+use crate::rpc::SolanaRpc;
+use crate::{Result, ToolkitError};
+
+/// Rent-exempt lamports for a 165-byte token account.
+pub const TOKEN_ACCOUNT_RENT: u64 = 2_039_280;
+
+/// Rent-exempt lamports for an 82-byte mint account.
+pub const MINT_ACCOUNT_RENT: u64 = 1_461_600;
+
+/// In-memory RPC backend.
+///
+/// Accounts are served from a map, sent transactions are recorded, and
+/// failures can be injected for sends and simulations.
+pub struct MockRpc {
+    accounts: Mutex<HashMap<Pubkey, Account>>,
+    blockhash: Hash,
+    genesis_hash: Mutex<Hash>,
+    sent: Mutex<Vec<Transaction>>,
+    send_configs: Mutex<Vec<RpcSendTransactionConfig>>,
+    send_error: Mutex<Option<String>>,
+    failing_accounts: Mutex<HashMap<Pubkey, String>>,
+    simulation_error: Mutex<Option<TransactionError>>,
+    failing_simulations: Mutex<HashMap<Pubkey, TransactionError>>,
+    simulated_accounts: Mutex<HashMap<Pubkey, Option<Account>>>,
+    program_account_requests: Mutex<Vec<RpcProgramAccountsConfig>>,
+    slice_requests: Mutex<Vec<(Pubkey, UiDataSliceConfig)>>,
+    account_requests: Mutex<Vec<Vec<Pubkey>>>,
+    units_consumed: Mutex<Option<u64>>,
+    transaction_costs: Mutex<HashMap<Signature, u64>>,
+    sends_to_land: Mutex<usize>,
+    signature_statuses: Mutex<HashMap<Signature, TransactionStatus>>,
+    status_sequences: Mutex<HashMap<Signature, VecDeque<Option<TransactionStatus>>>>,
+    signature_histories: Mutex<HashMap<Pubkey, Vec<RpcConfirmedTransactionStatusWithSignature>>>,
+    latency: Mutex<Duration>,
+    jitter: Mutex<Duration>,
+    calls: AtomicU64,
+    call_count: AtomicUsize,
+    failing_calls: AtomicUsize,
+    prioritization_fees: Mutex<Vec<u64>>,
+    block_height: AtomicU64,
+    in_flight: AtomicUsize,
+    peak_in_flight: AtomicUsize,
+}
+
+impl Default for MockRpc {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockRpc {
+    /// Create an empty mock with a fixed blockhash.
+    pub fn new() -> Self {
+        Self {
+            accounts: Mutex::new(HashMap::new()),
+            blockhash: Hash::new_unique(),
+            genesis_hash: Mutex::new(Hash::new_unique()),
+            sent: Mutex::new(Vec::new()),
+            send_configs: Mutex::new(Vec::new()),
+            send_error: Mutex::new(None),
+            failing_accounts: Mutex::new(HashMap::new()),
+            simulation_error: Mutex::new(None),
+            failing_simulations: Mutex::new(HashMap::new()),
+            simulated_accounts: Mutex::new(HashMap::new()),
+            program_account_requests: Mutex::new(Vec::new()),
+            slice_requests: Mutex::new(Vec::new()),
+            account_requests: Mutex::new(Vec::new()),
+            units_consumed: Mutex::new(None),
+            transaction_costs: Mutex::new(HashMap::new()),
+            sends_to_land: Mutex::new(1),
+            signature_statuses: Mutex::new(HashMap::new()),
+            status_sequences: Mutex::new(HashMap::new()),
+            signature_histories: Mutex::new(HashMap::new()),
+            latency: Mutex::new(Duration::ZERO),
+            jitter: Mutex::new(Duration::ZERO),
+            calls: AtomicU64::new(0),
+            call_count: AtomicUsize::new(0),
+            failing_calls: AtomicUsize::new(0),
+            prioritization_fees: Mutex::new(Vec::new()),
+            block_height: AtomicU64::new(0),
+            in_flight: AtomicUsize::new(0),
+            peak_in_flight: AtomicUsize::new(0),
+        }
+    }
+
+    /// Add an account.
+    pub fn with_account(self, pubkey: Pubkey, account: Account) -> Self {
+        self.set_account(pubkey, account);
+        self
+    }
+
+    /// Add a system account holding `lamports`.
+    pub fn with_balance(self, pubkey: Pubkey, lamports: u64) -> Self {
+        self.with_account(pubkey, Account::new(lamports, 0, &system_program::id()))
+    }
+
+    /// Add the rent sysvar holding `rent`.
+    pub fn with_rent(self, rent: &Rent) -> Self {
+        let data = bincode::serialize(rent).expect("rent serializes");
+        let mut account = Account::new(1, data.len(), &sysvar::id());
+        account.data = data;
+        self.with_account(sysvar::rent::id(), account)
+    }
+
+    /// Add the clock sysvar holding `clock`.
+    pub fn with_clock(self, clock: &Clock) -> Self {
+        let data = bincode::serialize(clock).expect("clock serializes");
+        let mut account = Account::new(1, data.len(), &sysvar::id());
+        account.data = data;
+        self.with_account(sysvar::clock::id(), account)
+    }
+
+    /// Report `hash` from `get_genesis_hash`, e.g. a known cluster's
+    /// [`Network::genesis_hash`](crate::rpc::Network::genesis_hash). A
+    /// unique hash by default.
+    pub fn with_genesis_hash(self, hash: Hash) -> Self {
+        *self.genesis_hash.lock().unwrap() = hash;
+        self
+    }
+
+    /// Insert or replace an account.
+    pub fn set_account(&self, pubkey: Pubkey, account: Account) {
+        self.accounts.lock().unwrap().insert(pubkey, account);
+    }
+
+    /// Remove an account.
+    pub fn remove_account(&self, pubkey: &Pubkey) {
+        self.accounts.lock().unwrap().remove(pubkey);
+    }
+
+    /// Blockhash returned by `get_latest_blockhash`.
+    pub fn blockhash(&self) -> Hash {
+        self.blockhash
+    }
+
+    /// Make every subsequent send fail with the given message.
+    pub fn fail_sends(&self, message: impl Into<String>) {
+        *self.send_error.lock().unwrap() = Some(message.into());
+    }
+
+    /// Make subsequent sends fail if the transaction references `pubkey`.
+    pub fn fail_sends_touching(&self, pubkey: Pubkey, message: impl Into<String>) {
+        self.failing_accounts.lock().unwrap().insert(pubkey, message.into());
+    }
+
+    /// Make every subsequent simulation report the given error.
+    pub fn fail_simulations(&self, err: TransactionError) {
+        *self.simulation_error.lock().unwrap() = Some(err);
+    }
+
+    /// Make simulations of transactions referencing `pubkey` report `err`.
+    pub fn fail_simulations_touching(&self, pubkey: Pubkey, err: TransactionError) {
+        self.failing_simulations.lock().unwrap().insert(pubkey, err);
+    }
+
+    /// Report `account` as the post-simulation state of `pubkey`, or
+    /// `None` if the simulated transaction closes it.
+    ///
+    /// Addresses without a simulated state report their current account.
+    pub fn set_simulated_account(&self, pubkey: Pubkey, account: Option<Account>) {
+        self.simulated_accounts.lock().unwrap().insert(pubkey, account);
+    }
+
+    /// Report `units` as the compute units consumed by every transaction
+    /// sent so far or later, and by every simulation.
+    pub fn set_units_consumed(&self, units: u64) {
+        *self.units_consumed.lock().unwrap() = Some(units);
+    }
+
+    /// Report `lamports` from `get_transaction_costs` for `signature`, as
+    /// if it was confirmed.
+    pub fn set_transaction_costs(&self, signature: Signature, lamports: u64) {
+        self.transaction_costs.lock().unwrap().insert(signature, lamports);
+    }
+
+    /// Report a signature as confirmed by `get_signature_statuses` only
+    /// once its transaction has been sent `sends` times, as if earlier
+    /// sends were dropped. Landed transactions report the number of sends
+    /// it took as their slot. Defaults to 1.
+    pub fn land_after_sends(&self, sends: usize) {
+        *self.sends_to_land.lock().unwrap() = sends;
+    }
+
+    /// Report `status` for `signature` from `get_signature_statuses`,
+    /// whether or not it was sent through this mock.
+    pub fn set_signature_status(&self, signature: Signature, status: TransactionStatus) {
+        self.signature_statuses.lock().unwrap().insert(signature, status);
+    }
+
+    /// Report `statuses` for `signature` from successive
+    /// `get_signature_statuses` calls, repeating the last one, as if it
+    /// landed and was then dropped by a reorg. Takes precedence over
+    /// [`set_signature_status`](Self::set_signature_status).
+    pub fn set_signature_status_sequence(
+        &self,
+        signature: Signature,
+        statuses: Vec<Option<TransactionStatus>>,
+    ) {
+        self.status_sequences.lock().unwrap().insert(signature, statuses.into());
+    }
+
+    /// Serve `history` (newest first) from `get_signatures_for_address`
+    /// for `address`. Addresses without a history have no signatures.
+    pub fn set_signature_history(
+        &self,
+        address: Pubkey,
+        history: Vec<RpcConfirmedTransactionStatusWithSignature>,
+    ) {
+        self.signature_histories.lock().unwrap().insert(address, history);
+    }
+
+    /// Serve `fees` (micro-lamports per compute unit, oldest slot first)
+    /// from `get_recent_prioritization_fees`, whatever the addresses.
+    pub fn set_prioritization_fees(&self, fees: Vec<u64>) {
+        *self.prioritization_fees.lock().unwrap() = fees;
+    }
+
+    /// Set the block height. Each `get_block_height` call returns the
+    /// current height and then advances it by one, as if a block was
+    /// produced between polls.
+    pub fn set_block_height(&self, height: u64) {
+        self.block_height.store(height, Ordering::SeqCst);
+    }
+
+    /// Delay every subsequent call by `latency`, as if the node stalled.
+    pub fn set_latency(&self, latency: Duration) {
+        *self.latency.lock().unwrap() = latency;
+    }
+
+    /// Delay each subsequent call by a further pseudo-random time below
+    /// `max`, so concurrent calls complete out of order.
+    pub fn set_latency_jitter(&self, max: Duration) {
+        *self.jitter.lock().unwrap() = max;
+    }
+
+    /// Fail the next `calls` calls of any kind with a retryable
+    /// [`ToolkitError::NetworkError`], as if the node were briefly down.
+    pub fn fail_next_calls(&self, calls: usize) {
+        self.failing_calls.store(calls, Ordering::SeqCst);
+    }
+
+    /// Calls made so far, including failed ones.
+    pub fn call_count(&self) -> usize {
+        self.call_count.load(Ordering::SeqCst)
+    }
+
+    /// Start a call: count it, apply the configured latency and fail it if
+    /// [`fail_next_calls`](Self::fail_next_calls) has failures left.
+    async fn stall(&self) -> Result<()> {
+        self.call_count.fetch_add(1, Ordering::SeqCst);
+        let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+        let mut latency = *self.latency.lock().unwrap();
+        let jitter = *self.jitter.lock().unwrap();
+        if !jitter.is_zero() {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            // Knuth's multiplicative hash spreads consecutive calls apart
+            let spread = call.wrapping_mul(2_654_435_761) % 1_000;
+            latency += jitter * spread as u32 / 1_000;
+        }
+        if !latency.is_zero() {
+            tokio::time::sleep(latency).await;
+        }
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+        let take_failure = |left: usize| left.checked_sub(1);
+        let failed =
+            self.failing_calls.fetch_update(Ordering::SeqCst, Ordering::SeqCst, take_failure);
+        if failed.is_ok() {
+            return Err(ToolkitError::NetworkError("injected failure".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Most calls stalled by [`set_latency`](Self::set_latency) at the same
+    /// time so far, to check that callers bound their concurrency.
+    pub fn peak_concurrency(&self) -> usize {
+        self.peak_in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Transactions sent so far, in order.
+    pub fn sent_transactions(&self) -> Vec<Transaction> {
+        self.sent.lock().unwrap().clone()
+    }
+
+    /// Configs passed to `send_transaction_with_config` so far, in order,
+    /// including sends that failed.
+    pub fn send_configs(&self) -> Vec<RpcSendTransactionConfig> {
+        self.send_configs.lock().unwrap().clone()
+    }
+
+    /// Configs passed to `get_program_accounts` so far, in order.
+    pub fn program_account_requests(&self) -> Vec<RpcProgramAccountsConfig> {
+        self.program_account_requests.lock().unwrap().clone()
+    }
+
+    /// Slices passed to `get_account_slice` so far, in order.
+    pub fn slice_requests(&self) -> Vec<(Pubkey, UiDataSliceConfig)> {
+        self.slice_requests.lock().unwrap().clone()
+    }
+
+    /// Addresses passed to `get_account` and `get_multiple_accounts` so
+    /// far, one entry per call.
+    pub fn account_requests(&self) -> Vec<Vec<Pubkey>> {
+        self.account_requests.lock().unwrap().clone()
+    }
+
+    /// Build an initialized SPL token account.
+    pub fn token_account(mint: &Pubkey, owner: &Pubkey, amount: u64) -> Account {
+        let state = TokenAccount {
+            mint: *mint,
+            owner: *owner,
+            amount,
+            delegate: COption::None,
+            state: AccountState::Initialized,
+            is_native: COption::None,
+            delegated_amount: 0,
+            close_authority: COption::None,
+        };
+        let mut data = vec![0u8; TokenAccount::LEN];
+        TokenAccount::pack(state, &mut data).expect("token account fits");
+        Account {
+            lamports: TOKEN_ACCOUNT_RENT,
+            data,
+            owner: spl_token::id(),
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    /// Build an initialized SPL mint.
+    pub fn mint_account(decimals: u8, supply: u64) -> Account {
+        let state = Mint {
+            mint_authority: COption::None,
+            supply,
+            decimals,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        };
+        let mut data = vec![0u8; Mint::LEN];
+        Mint::pack(state, &mut data).expect("mint fits");
+        Account {
+            lamports: MINT_ACCOUNT_RENT,
+            data,
+            owner: spl_token::id(),
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    /// Build an initialized Token-2022 mint whose transfer fee extension
+    /// charges `basis_points` of each transfer, capped at `maximum_fee`.
+    pub fn transfer_fee_mint_account(decimals: u8, basis_points: u16, maximum_fee: u64) -> Account {
+        use spl_token_2022::extension::transfer_fee::{TransferFee, TransferFeeConfig};
+        use spl_token_2022::extension::{ExtensionType, StateWithExtensionsMut};
+
+        let len = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[
+            ExtensionType::TransferFeeConfig,
+        ])
+        .expect("extension has a fixed length");
+        let mut data = vec![0u8; len];
+        let mut state =
+            StateWithExtensionsMut::<spl_token_2022::state::Mint>::unpack_uninitialized(&mut data)
+                .expect("mint fits");
+        let fee = TransferFee {
+            epoch: 0.into(),
+            maximum_fee: maximum_fee.into(),
+            transfer_fee_basis_points: basis_points.into(),
+        };
+        let config = state.init_extension::<TransferFeeConfig>(true).expect("extension fits");
+        config.older_transfer_fee = fee;
+        config.newer_transfer_fee = fee;
+        state.base = spl_token_2022::state::Mint {
+            mint_authority: COption::None,
+            supply: 0,
+            decimals,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        };
+        state.pack_base();
+        state.init_account_type().expect("account type fits");
+
+        Account {
+            lamports: Rent::default().minimum_balance(len),
+            data,
+            owner: spl_token_2022::id(),
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+}
+
+#[async_trait]
+impl SolanaRpc for MockRpc {
+    async fn get_balance(&self, pubkey: &Pubkey) -> Result<u64> {
+        self.stall().await?;
+        Ok(self
+            .accounts
+            .lock()
+            .unwrap()
+            .get(pubkey)
+            .map(|a| a.lamports)
+            .unwrap_or(0))
+    }
+
+    async fn get_account(&self, pubkey: &Pubkey) -> Result<Option<Account>> {
+        self.stall().await?;
+        self.account_requests.lock().unwrap().push(vec![*pubkey]);
+        Ok(self.accounts.lock().unwrap().get(pubkey).cloned())
+    }
+
+    async fn get_account_slice(
+        &self,
+        pubkey: &Pubkey,
+        slice: UiDataSliceConfig,
+    ) -> Result<Option<Account>> {
+        self.stall().await?;
+        self.slice_requests.lock().unwrap().push((*pubkey, slice));
+        Ok(self
+            .accounts
+            .lock()
+            .unwrap()
+            .get(pubkey)
+            .map(|a| slice_account(a, slice)))
+    }
+
+    async fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        self.stall().await?;
+        self.account_requests.lock().unwrap().push(pubkeys.to_vec());
+        let accounts = self.accounts.lock().unwrap();
+        Ok(pubkeys.iter().map(|p| accounts.get(p).cloned()).collect())
+    }
+
+    async fn get_latest_blockhash(&self) -> Result<Hash> {
+        self.stall().await?;
+        Ok(self.blockhash)
+    }
+
+    async fn get_block_height(&self) -> Result<u64> {
+        self.stall().await?;
+        Ok(self.block_height.fetch_add(1, Ordering::SeqCst))
+    }
+
+    async fn get_genesis_hash(&self) -> Result<Hash> {
+        self.stall().await?;
+        Ok(*self.genesis_hash.lock().unwrap())
+    }
+
+    async fn send_and_confirm_transaction(&self, transaction: &Transaction) -> Result<Signature> {
+        self.stall().await?;
+        if let Some(message) = self.send_error.lock().unwrap().clone() {
+            return Err(ToolkitError::TransactionError(message));
+        }
+
+        let failing = self.failing_accounts.lock().unwrap();
+        if let Some(message) = transaction
+            .message
+            .account_keys
+            .iter()
+            .find_map(|key| failing.get(key))
+        {
+            return Err(ToolkitError::TransactionError(message.clone()));
+        }
+        drop(failing);
+
+        self.sent.lock().unwrap().push(transaction.clone());
+        Ok(transaction.signatures.first().copied().unwrap_or_default())
+    }
+
+    async fn send_transaction(&self, transaction: &Transaction) -> Result<Signature> {
+        self.send_and_confirm_transaction(transaction).await
+    }
+
+    async fn send_transaction_with_config(
+        &self,
+        transaction: &Transaction,
+        config: RpcSendTransactionConfig,
+    ) -> Result<Signature> {
+        self.send_configs.lock().unwrap().push(config);
+        self.send_and_confirm_transaction(transaction).await
+    }
+
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<Option<TransactionStatus>>> {
+        self.stall().await?;
+        let sent = self.sent.lock().unwrap();
+        let sends_to_land = *self.sends_to_land.lock().unwrap();
+        let overrides = self.signature_statuses.lock().unwrap();
+        let mut sequences = self.status_sequences.lock().unwrap();
+        Ok(signatures
+            .iter()
+            .map(|signature| {
+                if let Some(sequence) = sequences.get_mut(signature) {
+                    if sequence.len() > 1 {
+                        return sequence.pop_front().flatten();
+                    }
+                    return sequence.front().cloned().flatten();
+                }
+                if let Some(status) = overrides.get(signature) {
+                    return Some(status.clone());
+                }
+                let sends = sent
+                    .iter()
+                    .filter(|tx| tx.signatures.first() == Some(signature))
+                    .count();
+                (sends > 0 && sends >= sends_to_land).then_some(TransactionStatus {
+                    slot: sends_to_land as u64,
+                    confirmations: None,
+                    status: Ok(()),
+                    err: None,
+                    confirmation_status: Some(TransactionConfirmationStatus::Confirmed),
+                })
+            })
+            .collect())
+    }
+
+    async fn get_token_accounts_by_owner(
+        &self,
+        owner: &Pubkey,
+        program_id: &Pubkey,
+    ) -> Result<Vec<(Pubkey, Account)>> {
+        self.stall().await?;
+        let accounts = self.accounts.lock().unwrap();
+        let mut matching: Vec<(Pubkey, Account)> = accounts
+            .iter()
+            .filter(|(_, a)| a.owner == *program_id && a.data.len() >= 64)
+            .filter(|(_, a)| a.data[32..64] == owner.to_bytes())
+            .map(|(p, a)| (*p, a.clone()))
+            .collect();
+        matching.sort_by_key(|(p, _)| *p);
+        Ok(matching)
+    }
+
+    async fn simulate_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<RpcSimulateTransactionResult> {
+        self.stall().await?;
+        let failing = self.failing_simulations.lock().unwrap();
+        let err = transaction
+            .message
+            .account_keys
+            .iter()
+            .find_map(|key| failing.get(key).cloned())
+            .or_else(|| self.simulation_error.lock().unwrap().clone());
+        let logs = transaction
+            .message
+            .instructions
+            .iter()
+            .map(|ix| ix.program_id(&transaction.message.account_keys))
+            .map(|program_id| format!("Program {} invoke [1]", program_id))
+            .collect();
+        Ok(RpcSimulateTransactionResult {
+            err,
+            logs: Some(logs),
+            accounts: None,
+            units_consumed: *self.units_consumed.lock().unwrap(),
+            return_data: None,
+            inner_instructions: None,
+        })
+    }
+
+    async fn simulate_transaction_with_accounts(
+        &self,
+        transaction: &Transaction,
+        addresses: &[Pubkey],
+    ) -> Result<RpcSimulateTransactionResult> {
+        self.stall().await?;
+        let mut result = self.simulate_transaction(transaction).await?;
+        let simulated = self.simulated_accounts.lock().unwrap();
+        let accounts = self.accounts.lock().unwrap();
+        result.accounts = Some(
+            addresses
+                .iter()
+                .map(|address| {
+                    let post = match simulated.get(address) {
+                        Some(post) => post.as_ref(),
+                        None => accounts.get(address),
+                    };
+                    post.map(|account| {
+                        UiAccount::encode(address, account, UiAccountEncoding::Base64, None, None)
+                    })
+                })
+                .collect(),
+        );
+        Ok(result)
+    }
+
+    async fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> Result<Vec<(Pubkey, Account)>> {
+        self.stall().await?;
+        self.program_account_requests.lock().unwrap().push(config.clone());
+
+        let filters = config.filters.unwrap_or_default();
+        let slice = config.account_config.data_slice;
+        let accounts = self.accounts.lock().unwrap();
+        let mut matching: Vec<(Pubkey, Account)> = accounts
+            .iter()
+            .filter(|(_, a)| a.owner == *program_id)
+            .filter(|(_, a)| {
+                let shared = AccountSharedData::from((*a).clone());
+                filters.iter().all(|f| f.allows(&shared))
+            })
+            .map(|(p, a)| match slice {
+                Some(slice) => (*p, slice_account(a, slice)),
+                None => (*p, a.clone()),
+            })
+            .collect();
+        matching.sort_by_key(|(p, _)| *p);
+        Ok(matching)
+    }
+
+    async fn get_transaction_units_consumed(&self, signature: &Signature) -> Result<Option<u64>> {
+        self.stall().await?;
+        let sent = self
+            .sent
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|tx| tx.signatures.first() == Some(signature));
+        Ok(self.units_consumed.lock().unwrap().filter(|_| sent))
+    }
+
+    async fn get_transaction_costs(&self, signature: &Signature) -> Result<Option<u64>> {
+        self.stall().await?;
+        Ok(self.transaction_costs.lock().unwrap().get(signature).copied())
+    }
+
+    async fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        limit: usize,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+        self.stall().await?;
+        let histories = self.signature_histories.lock().unwrap();
+        let history = histories.get(address).map(Vec::as_slice).unwrap_or_default();
+        Ok(history.iter().take(limit).cloned().collect())
+    }
+
+    async fn get_recent_prioritization_fees(
+        &self,
+        _addresses: &[Pubkey],
+    ) -> Result<Vec<RpcPrioritizationFee>> {
+        self.stall().await?;
+        let fees = self.prioritization_fees.lock().unwrap();
+        Ok(fees
+            .iter()
+            .enumerate()
+            .map(|(slot, fee)| RpcPrioritizationFee {
+                slot: slot as u64,
+                prioritization_fee: *fee,
+            })
+            .collect())
+    }
+}
+
+/// Copy of `account` with its data cut to `slice`, as the RPC would return.
+fn slice_account(account: &Account, slice: UiDataSliceConfig) -> Account {
+    let start = slice.offset.min(account.data.len());
+    let end = (slice.offset + slice.length).min(account.data.len());
+    Account {
+        data: account.data[start..end].to_vec(),
+        ..account.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_mock_token_accounts_by_owner() {
+        let owner = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let mine = Pubkey::new_unique();
+        let rpc = MockRpc::new()
+            .with_account(mine, MockRpc::token_account(&mint, &owner, 5))
+            .with_account(
+                Pubkey::new_unique(),
+                MockRpc::token_account(&mint, &Pubkey::new_unique(), 5),
+            )
+            .with_balance(owner, 1_000);
+
+        let accounts = rpc.get_token_accounts_by_owner(&owner, &spl_token::id()).await.unwrap();
+
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].0, mine);
+        assert_eq!(rpc.get_balance(&owner).await.unwrap(), 1_000);
+        assert!(rpc.get_account(&Pubkey::new_unique()).await.unwrap().is_none());
+    }
+}
@@ -2,40 +2,169 @@
 //!
 //! Provides helpers for token minting, burning, transfers, and account management.
 
-use solana_client::nonblocking::rpc_client::RpcClient;
+use borsh::BorshDeserialize;
+use futures::{
+    channel::{mpsc, oneshot},
+    Stream, StreamExt,
+};
+use solana_account_decoder::{UiAccount, UiAccountData, UiAccountEncoding, UiDataSliceConfig};
+use solana_client::{
+    nonblocking::{pubsub_client::PubsubClient, rpc_client::RpcClient},
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+    rpc_response::RpcKeyedAccount,
+};
 use solana_sdk::{
+    account::Account,
+    clock::{Clock, MAX_PROCESSING_AGE},
     commitment_config::CommitmentConfig,
+    compute_budget::ComputeBudgetInstruction,
+    hash::Hash,
     instruction::Instruction,
     message::Message,
     pubkey::Pubkey,
-    signature::{Keypair, Signer},
+    rent::Rent,
+    signature::{Keypair, Signature, Signer},
+    system_program, sysvar,
     transaction::Transaction,
 };
 use spl_token::{
     instruction as token_instruction,
     solana_program::program_pack::Pack,
-    state::{Account as TokenAccount, Mint},
+    state::{Account as TokenAccount, AccountState, Mint},
+};
+use spl_token_2022::extension::{
+    transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions,
+};
+use solana_transaction_status::TransactionStatus;
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::sync::watch;
+
+use crate::{
+    pda::{find_associated_token_address, find_metadata_pda, TOKEN_METADATA_PROGRAM_ID},
+    rpc::{
+        token_accounts_by_owner_config, Network, SolanaRpc, TimeoutRpc, DEFAULT_RPC_TIMEOUT,
+        TOKEN_ACCOUNT_SLICE_LEN,
+    },
+    safety::{AddressVerifier, AmountValidator, DEFAULT_ESTIMATED_FEE_LAMPORTS},
+    state_file,
+    transaction::{
+        confirm_until_block_height, fee_paid_transaction, send_and_confirm,
+        will_fit_in_transaction, ExecutionPolicy, SendPolicy, DEFAULT_CONFIRM_POLL_INTERVAL,
+    },
+    Result, ToolkitError,
 };
 
-use crate::{pda::find_associated_token_address, Result, ToolkitError};
+/// Signatures `getSignatureStatuses` accepts per call.
+const MAX_SIGNATURE_STATUSES_PER_CALL: usize = 256;
 
 /// Token client for SPL token operations.
-pub struct TokenClient {
-    client: RpcClient,
+pub struct TokenClient<R = RpcClient> {
+    client: TimeoutRpc<R>,
     payer: Keypair,
+    validate: bool,
+    policy: ExecutionPolicy,
+    send_policy: SendPolicy,
+    rent_destination: Option<Pubkey>,
+    priority_fee_micro_lamports: Option<u64>,
+    minimum_rent: RwLock<Option<u64>>,
+    mint_registry: MintRegistry,
 }
 
 impl TokenClient {
     pub fn new(rpc_url: &str, payer: Keypair) -> Self {
+        Self::from_client(
+            RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed()),
+            payer,
+        )
+    }
+
+    /// Create for a well-known network.
+    pub fn new_on(network: Network, payer: Keypair) -> Self {
+        Self::new(network.rpc_url(), payer)
+    }
+}
+
+impl<R: SolanaRpc> TokenClient<R> {
+    /// Create from an existing RPC backend.
+    pub fn from_client(client: R, payer: Keypair) -> Self {
         Self {
-            client: RpcClient::new_with_commitment(
-                rpc_url.to_string(),
-                CommitmentConfig::confirmed(),
-            ),
+            client: TimeoutRpc::new(client, DEFAULT_RPC_TIMEOUT),
             payer,
+            validate: false,
+            policy: ExecutionPolicy::default(),
+            send_policy: SendPolicy::default(),
+            rent_destination: None,
+            priority_fee_micro_lamports: None,
+            minimum_rent: RwLock::new(None),
+            mint_registry: MintRegistry::new(),
         }
     }
 
+    /// Enforce `policy` before every send. Under a dry-run policy methods
+    /// succeed without sending anything.
+    pub fn with_execution_policy(mut self, policy: ExecutionPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Send and confirm transactions per `policy`.
+    pub fn with_send_policy(mut self, policy: SendPolicy) -> Self {
+        self.send_policy = policy;
+        self
+    }
+
+    /// Verify mints and token accounts are owned by the token program
+    /// before sending burn, transfer and close transactions, and that the
+    /// payer can fund the rent of token accounts it creates.
+    pub fn with_validation(mut self, validate: bool) -> Self {
+        self.validate = validate;
+        self
+    }
+
+    /// Send rent from closed accounts to `destination` instead of the
+    /// payer. The payer stays the close authority.
+    ///
+    /// Closing fails unless `destination` is a system account or doesn't
+    /// exist yet.
+    pub fn with_rent_destination(mut self, destination: Pubkey) -> Self {
+        self.rent_destination = Some(destination);
+        self
+    }
+
+    /// Fail RPC calls that take longer than `timeout` (30s by default)
+    /// with [`ToolkitError::Timeout`].
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client.set_timeout(timeout);
+        self
+    }
+
+    /// Set the priority fee in micro-lamports per compute unit added to
+    /// every transaction this client builds.
+    pub fn with_priority_fee(mut self, micro_lamports: u64) -> Self {
+        self.priority_fee_micro_lamports = Some(micro_lamports);
+        self
+    }
+
+    /// Share `registry` as the cache [`get_mint_infos`](Self::get_mint_infos)
+    /// fills, instead of a private one.
+    pub fn with_mint_registry(mut self, registry: MintRegistry) -> Self {
+        self.mint_registry = registry;
+        self
+    }
+
+    /// Mint decimals and symbols cached by this client.
+    pub fn mint_registry(&self) -> &MintRegistry {
+        &self.mint_registry
+    }
+
     /// Burn tokens from a token account.
     pub async fn burn(
         &self,
@@ -43,15 +172,10 @@ impl TokenClient {
         token_account: &Pubkey,
         amount: u64,
     ) -> Result<()> {
-        let instruction = token_instruction::burn(
-            &spl_token::id(),
-            token_account,
-            mint,
-            &self.payer.pubkey(),
-            &[],
-            amount,
-        )?;
+        self.check_mint(mint).await?;
+        self.check_token_account(token_account).await?;
 
+        let instruction = self.burn_ix(mint, token_account, amount)?;
         self.send_transaction(vec![instruction]).await
     }
 
@@ -62,29 +186,14 @@ impl TokenClient {
         token_account: &Pubkey,
         amount: u64,
     ) -> Result<u64> {
-        let mut instructions = vec![];
-
-        // Burn instruction
-        instructions.push(token_instruction::burn(
-            &spl_token::id(),
-            token_account,
-            mint,
-            &self.payer.pubkey(),
-            &[],
-            amount,
-        )?);
+        self.check_mint(mint).await?;
+        self.check_token_account(token_account).await?;
+        self.check_rent_destination().await?;
 
-        // Close account instruction
-        instructions.push(token_instruction::close_account(
-            &spl_token::id(),
-            token_account,
-            &self.payer.pubkey(),
-            &self.payer.pubkey(),
-            &[],
-        )?);
+        let instructions = self.build_burn_and_close(mint, token_account, amount)?;
 
         // Get account balance before closing
-        let account = self.client.get_account(token_account).await?;
+        let account = self.fetch_account(token_account).await?;
         let lamports = account.lamports;
 
         self.send_transaction(instructions).await?;
@@ -93,23 +202,39 @@ impl TokenClient {
     }
 
     /// Transfer tokens between accounts.
+    ///
+    /// Both accounts are fetched first and must hold `mint`, so a transfer
+    /// between accounts of different mints fails before anything is sent.
     pub async fn transfer(
         &self,
-        _mint: &Pubkey,
+        mint: &Pubkey,
         source: &Pubkey,
         destination: &Pubkey,
         amount: u64,
     ) -> Result<()> {
-        let instruction = token_instruction::transfer(
-            &spl_token::id(),
-            source,
-            destination,
-            &self.payer.pubkey(),
-            &[],
-            amount,
-        )?;
+        self.transfer_with_fee_payer(mint, source, destination, amount, &self.payer)
+            .await
+    }
 
-        self.send_transaction(vec![instruction]).await
+    /// Transfer tokens with a separate fee payer.
+    ///
+    /// Like [`transfer`](Self::transfer), but `fee_payer` (e.g. a relayer)
+    /// pays the fee while the client's payer still signs as the transfer
+    /// authority.
+    pub async fn transfer_with_fee_payer(
+        &self,
+        mint: &Pubkey,
+        source: &Pubkey,
+        destination: &Pubkey,
+        amount: u64,
+        fee_payer: &Keypair,
+    ) -> Result<()> {
+        self.check_token_account(source).await?;
+        self.check_token_account(destination).await?;
+        self.check_transfer_mints(mint, source, destination).await?;
+
+        let instruction = self.transfer_ix(source, destination, amount)?;
+        self.send_signed_by(vec![instruction], fee_payer).await.map(|_| ())
     }
 
     /// Create an associated token account.
@@ -119,15 +244,9 @@ impl TokenClient {
         mint: &Pubkey,
     ) -> Result<Pubkey> {
         let (ata, _) = find_associated_token_address(wallet, mint);
+        self.check_payer_covers_rent().await?;
 
-        let instruction =
-            spl_associated_token_account::instruction::create_associated_token_account(
-                &self.payer.pubkey(),
-                wallet,
-                mint,
-                &spl_token::id(),
-            );
-
+        let instruction = self.create_ata_ix(wallet, mint);
         self.send_transaction(vec![instruction]).await?;
 
         Ok(ata)
@@ -143,32 +262,248 @@ impl TokenClient {
 
         // Check if account exists
         match self.client.get_account(&ata).await {
-            Ok(_) => Ok(ata),
-            Err(_) => self.create_associated_token_account(wallet, mint).await,
+            Ok(Some(_)) => Ok(ata),
+            _ => self.create_associated_token_account(wallet, mint).await,
         }
     }
 
     /// Close a token account and recover rent.
     pub async fn close_account(&self, token_account: &Pubkey) -> Result<u64> {
-        let account = self.client.get_account(token_account).await?;
+        self.check_token_account(token_account).await?;
+        self.check_rent_destination().await?;
+
+        let account = self.fetch_account(token_account).await?;
+        let lamports = account.lamports;
+
+        let instruction = self.close_account_ix(token_account)?;
+        self.send_transaction(vec![instruction]).await?;
+
+        Ok(lamports)
+    }
+
+    /// Close a token account with a separate fee payer.
+    ///
+    /// Like [`close_account`](Self::close_account), but `fee_payer` pays the
+    /// fee while the client's payer signs as the close authority. Rent still
+    /// goes to the payer (or the rent destination), not the fee payer.
+    pub async fn close_account_with_fee_payer(
+        &self,
+        token_account: &Pubkey,
+        fee_payer: &Keypair,
+    ) -> Result<u64> {
+        self.check_token_account(token_account).await?;
+        self.check_rent_destination().await?;
+
+        let account = self.fetch_account(token_account).await?;
         let lamports = account.lamports;
 
-        let instruction = token_instruction::close_account(
+        let instruction = self.close_account_ix(token_account)?;
+        self.send_signed_by(vec![instruction], fee_payer).await?;
+
+        Ok(lamports)
+    }
+
+    /// Merge all of `owner`'s token accounts for `mint` into its ATA.
+    ///
+    /// Balances are transferred into the associated token account (created
+    /// if missing) and the other accounts are closed, returning their rent
+    /// to the payer (or the rent destination). The payer must be `owner`.
+    /// Frozen accounts are left alone. Large merges are split across several
+    /// transactions.
+    pub async fn consolidate_mint(&self, mint: &Pubkey, owner: &Pubkey) -> Result<Consolidation> {
+        if *owner != self.payer.pubkey() {
+            return Err(ToolkitError::ConfigError(format!(
+                "Cannot consolidate accounts of {}: the payer {} must be the owner",
+                owner,
+                self.payer.pubkey()
+            )));
+        }
+        self.check_rent_destination().await?;
+
+        let (ata, _) = find_associated_token_address(owner, mint);
+        let mut ata_exists = false;
+        let mut sources = Vec::new();
+        for (address, account) in self
+            .client
+            .get_token_accounts_by_owner(owner, &spl_token::id())
+            .await?
+        {
+            let Ok(state) = TokenAccount::unpack(&account.data) else {
+                continue;
+            };
+            if state.mint != *mint {
+                continue;
+            }
+            if address == ata {
+                ata_exists = true;
+            } else if !state.is_frozen() {
+                sources.push((address, state.amount, account.lamports));
+            }
+        }
+
+        let mut consolidation = Consolidation {
+            ata,
+            ata_created: !ata_exists && !sources.is_empty(),
+            accounts_closed: Vec::new(),
+            lamports_recovered: 0,
+            tokens_moved: 0,
+        };
+
+        for (i, chunk) in sources.chunks(CONSOLIDATE_ACCOUNTS_PER_TX).enumerate() {
+            let balances: Vec<(Pubkey, u64)> =
+                chunk.iter().map(|(address, amount, _)| (*address, *amount)).collect();
+            let create_ata = consolidation.ata_created && i == 0;
+            let instructions = self.build_consolidation(mint, owner, &balances, create_ata)?;
+            self.send_transaction(instructions).await?;
+
+            for (address, amount, lamports) in chunk {
+                consolidation.accounts_closed.push(*address);
+                consolidation.lamports_recovered += lamports;
+                consolidation.tokens_moved += amount;
+            }
+        }
+
+        Ok(consolidation)
+    }
+
+    // ------------------------------------------------------------------------
+    // Offline instruction builders (no network I/O)
+    // ------------------------------------------------------------------------
+
+    /// Build a burn instruction with the payer as authority.
+    pub fn burn_ix(
+        &self,
+        mint: &Pubkey,
+        token_account: &Pubkey,
+        amount: u64,
+    ) -> Result<Instruction> {
+        Ok(token_instruction::burn(
             &spl_token::id(),
             token_account,
+            mint,
             &self.payer.pubkey(),
+            &[],
+            amount,
+        )?)
+    }
+
+    /// Build a transfer instruction with the payer as authority.
+    pub fn transfer_ix(
+        &self,
+        source: &Pubkey,
+        destination: &Pubkey,
+        amount: u64,
+    ) -> Result<Instruction> {
+        Ok(token_instruction::transfer(
+            &spl_token::id(),
+            source,
+            destination,
             &self.payer.pubkey(),
             &[],
-        )?;
+            amount,
+        )?)
+    }
 
-        self.send_transaction(vec![instruction]).await?;
+    /// Build a close instruction returning rent to the payer, or to the
+    /// rent destination if one is set.
+    pub fn close_account_ix(&self, token_account: &Pubkey) -> Result<Instruction> {
+        let payer = self.payer.pubkey();
+        Ok(token_instruction::close_account(
+            &spl_token::id(),
+            token_account,
+            &self.rent_destination.unwrap_or(payer),
+            &payer,
+            &[],
+        )?)
+    }
 
-        Ok(lamports)
+    /// Build an associated token account creation instruction funded by the payer.
+    pub fn create_ata_ix(&self, wallet: &Pubkey, mint: &Pubkey) -> Instruction {
+        spl_associated_token_account::instruction::create_associated_token_account(
+            &self.payer.pubkey(),
+            wallet,
+            mint,
+            &spl_token::id(),
+        )
+    }
+
+    /// Build burn + close instructions for a token account.
+    pub fn build_burn_and_close(
+        &self,
+        mint: &Pubkey,
+        token_account: &Pubkey,
+        amount: u64,
+    ) -> Result<Vec<Instruction>> {
+        Ok(vec![
+            self.burn_ix(mint, token_account, amount)?,
+            self.close_account_ix(token_account)?,
+        ])
+    }
+
+    /// Build instructions moving `sources` (address, amount) into `owner`'s
+    /// ATA for `mint` and closing them, optionally creating the ATA first.
+    pub fn build_consolidation(
+        &self,
+        mint: &Pubkey,
+        owner: &Pubkey,
+        sources: &[(Pubkey, u64)],
+        create_ata: bool,
+    ) -> Result<Vec<Instruction>> {
+        let (ata, _) = find_associated_token_address(owner, mint);
+        let mut instructions = Vec::with_capacity(sources.len() * 2 + 1);
+
+        if create_ata {
+            instructions.push(self.create_ata_ix(owner, mint));
+        }
+        for (source, amount) in sources {
+            if *amount > 0 {
+                instructions.push(self.transfer_ix(source, &ata, *amount)?);
+            }
+            instructions.push(self.close_account_ix(source)?);
+        }
+
+        Ok(instructions)
+    }
+
+    /// Build a transaction signed by the payer.
+    ///
+    /// The caller supplies the recent blockhash.
+    pub fn build_transaction(
+        &self,
+        instructions: &[Instruction],
+        recent_blockhash: Hash,
+    ) -> Transaction {
+        let instructions = self.with_compute_budget(instructions);
+        let message = Message::new(&instructions, Some(&self.payer.pubkey()));
+        Transaction::new(&[&self.payer], message, recent_blockhash)
+    }
+
+    /// Build a transaction whose fee is paid by `fee_payer`.
+    ///
+    /// `fee_payer` is the message's fee payer and signs first; the client's
+    /// payer also signs, as the authority of the token instructions. The
+    /// caller supplies the recent blockhash.
+    pub fn build_transaction_with_fee_payer(
+        &self,
+        instructions: &[Instruction],
+        fee_payer: &Keypair,
+        recent_blockhash: Hash,
+    ) -> Transaction {
+        let instructions = self.with_compute_budget(instructions);
+        fee_paid_transaction(&instructions, fee_payer, &self.payer, recent_blockhash)
+    }
+
+    /// `instructions` preceded by the priority fee instruction, if one is set.
+    fn with_compute_budget(&self, instructions: &[Instruction]) -> Vec<Instruction> {
+        let price = self
+            .priority_fee_micro_lamports
+            .map(ComputeBudgetInstruction::set_compute_unit_price);
+        price.into_iter().chain(instructions.iter().cloned()).collect()
     }
 
     /// Get token account balance.
     pub async fn get_balance(&self, token_account: &Pubkey) -> Result<u64> {
-        let account = self.client.get_account(token_account).await?;
+        let account = self.fetch_account(token_account).await?;
         let token_account = TokenAccount::unpack(&account.data)
             .map_err(|e| ToolkitError::InvalidAccountData(e.to_string()))?;
         Ok(token_account.amount)
@@ -176,32 +511,308 @@ impl TokenClient {
 
     /// Get mint info.
     pub async fn get_mint_info(&self, mint: &Pubkey) -> Result<MintInfo> {
-        let account = self.client.get_account(mint).await?;
+        let account = self.fetch_account(mint).await?;
         let mint_data = Mint::unpack(&account.data)
             .map_err(|e| ToolkitError::InvalidAccountData(e.to_string()))?;
 
-        Ok(MintInfo {
-            supply: mint_data.supply,
-            decimals: mint_data.decimals,
-            is_initialized: mint_data.is_initialized,
-            mint_authority: mint_data.mint_authority.into(),
-            freeze_authority: mint_data.freeze_authority.into(),
+        Ok(mint_data.into())
+    }
+
+    /// Get mint info for many mints, in one batched lookup per 50 mints.
+    ///
+    /// Addresses that don't exist or aren't mints are left out of the map.
+    /// The decimals and symbol of every mint found are cached in the
+    /// [mint registry](Self::mint_registry).
+    pub async fn get_mint_infos(&self, mints: &[Pubkey]) -> Result<HashMap<Pubkey, MintInfo>> {
+        let mut unique = mints.to_vec();
+        unique.sort();
+        unique.dedup();
+
+        let mut infos = HashMap::new();
+        for chunk in unique.chunks(50) {
+            for (mint, state, symbol) in fetch_mints(&self.client, chunk).await? {
+                self.mint_registry.insert(mint, state.decimals, symbol);
+                infos.insert(mint, state.into());
+            }
+        }
+        Ok(infos)
+    }
+
+    /// Assess how exposed holders of `mint` are to being frozen.
+    ///
+    /// See [`assess_freeze_risk`].
+    pub async fn assess_freeze_risk(
+        &self,
+        mint: &Pubkey,
+        sample_holders: bool,
+    ) -> Result<FreezeRisk> {
+        assess_freeze_risk(&self.client, mint, sample_holders).await
+    }
+
+    /// Rent-exempt minimum for a token account at the cluster's live rent.
+    ///
+    /// Read from the rent sysvar on first use and cached on the client;
+    /// call [`invalidate_rent_cache`](Self::invalidate_rent_cache) to fetch
+    /// it again.
+    pub async fn minimum_rent(&self) -> Result<u64> {
+        if let Some(lamports) = *self.minimum_rent.read().unwrap() {
+            return Ok(lamports);
+        }
+
+        let rent = self
+            .client
+            .get_account(&sysvar::rent::id())
+            .await?
+            .ok_or_else(|| ToolkitError::account_not_found(sysvar::rent::id().to_string()))?;
+        let rent: Rent = bincode::deserialize(&rent.data)
+            .map_err(|e| ToolkitError::InvalidAccountData(e.to_string()))?;
+        let lamports = rent.minimum_balance(TokenAccount::LEN);
+
+        *self.minimum_rent.write().unwrap() = Some(lamports);
+        Ok(lamports)
+    }
+
+    /// Forget the cached [`minimum_rent`](Self::minimum_rent).
+    pub fn invalidate_rent_cache(&self) {
+        *self.minimum_rent.write().unwrap() = None;
+    }
+
+    /// Lamports needed to create `count` associated token accounts: rent at
+    /// the cached [`minimum_rent`](Self::minimum_rent) plus the fees of the
+    /// transactions creating them.
+    pub async fn estimate_batch_ata_cost(&self, count: usize) -> Result<u64> {
+        Ok(creation_cost(count, self.minimum_rent().await?))
+    }
+
+    /// Same as [`estimate_batch_ata_cost`](Self::estimate_batch_ata_cost).
+    pub async fn estimate_creation_cost(&self, count: usize) -> Result<u64> {
+        self.estimate_batch_ata_cost(count).await
+    }
+
+    /// Check which airdrop recipients need an ATA for `mint` and whether the
+    /// payer can afford to create them.
+    ///
+    /// Sends nothing. The plan carries a warning when the payer's balance
+    /// does not cover [`estimate_batch_ata_cost`](Self::estimate_batch_ata_cost)
+    /// for the missing ATAs.
+    pub async fn plan_airdrop(
+        &self,
+        mint: &Pubkey,
+        recipients: &[(Pubkey, u64)],
+    ) -> Result<AirdropPlan> {
+        let mut seen = HashSet::new();
+        let wallets: Vec<Pubkey> = recipients
+            .iter()
+            .map(|(wallet, _)| *wallet)
+            .filter(|wallet| seen.insert(*wallet))
+            .collect();
+        let atas: Vec<Pubkey> = wallets
+            .iter()
+            .map(|wallet| find_associated_token_address(wallet, mint).0)
+            .collect();
+
+        let existing = self.client.get_multiple_accounts(&atas).await?;
+        let atas_to_create: Vec<Pubkey> = wallets
+            .into_iter()
+            .zip(existing)
+            .filter(|(_, account)| account.is_none())
+            .map(|(wallet, _)| wallet)
+            .collect();
+
+        let creation_cost = self.estimate_batch_ata_cost(atas_to_create.len()).await?;
+        let payer_balance = self.client.get_balance(&self.payer.pubkey()).await?;
+
+        let mut warnings = Vec::new();
+        if payer_balance < creation_cost {
+            warnings.push(format!(
+                "Payer has {} but creating {} token accounts needs ~{}",
+                AmountValidator::format_amount_with_symbol(payer_balance, 9, "SOL"),
+                atas_to_create.len(),
+                AmountValidator::format_amount_with_symbol(creation_cost, 9, "SOL")
+            ));
+        }
+
+        Ok(AirdropPlan {
+            recipients: recipients.len(),
+            atas_to_create,
+            creation_cost,
+            payer_balance,
+            warnings,
+        })
+    }
+
+    /// Get the Metaplex name, symbol and URI for a mint.
+    ///
+    /// Returns `Ok(None)` when the mint has no metadata account.
+    pub async fn get_token_metadata(&self, mint: &Pubkey) -> Result<Option<TokenMetadata>> {
+        let (metadata_pda, _) = find_metadata_pda(mint);
+        let account = self.client.get_account(&metadata_pda).await?;
+
+        decode_metadata_account(account.as_ref())
+    }
+
+    /// When validation is enabled, ensure `pubkey` is a token account.
+    async fn check_token_account(&self, pubkey: &Pubkey) -> Result<()> {
+        if self.validate {
+            self.check_token_program_account(pubkey, TokenAccount::LEN, "token account")
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// When validation is enabled, ensure the payer can fund a token
+    /// account's rent.
+    async fn check_payer_covers_rent(&self) -> Result<()> {
+        if self.validate {
+            let needed = self.minimum_rent().await?;
+            let available = self.client.get_balance(&self.payer.pubkey()).await?;
+            if available < needed {
+                return Err(ToolkitError::InsufficientBalance { needed, available });
+            }
+        }
+        Ok(())
+    }
+
+    /// Ensure the rent destination, if set, can receive lamports.
+    async fn check_rent_destination(&self) -> Result<()> {
+        match &self.rent_destination {
+            Some(destination) => check_rent_destination(&self.client, destination).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Ensure `source` and `destination` are token accounts of `mint`.
+    async fn check_transfer_mints(
+        &self,
+        mint: &Pubkey,
+        source: &Pubkey,
+        destination: &Pubkey,
+    ) -> Result<()> {
+        let source_mint = self.token_account_mint(source, "source").await?;
+        let destination_mint = self.token_account_mint(destination, "destination").await?;
+
+        if source_mint != destination_mint {
+            return Err(ToolkitError::TokenError(format!(
+                "source mint {} != destination mint {}",
+                source_mint, destination_mint
+            )));
+        }
+        if source_mint != *mint {
+            return Err(ToolkitError::TokenError(format!(
+                "source mint {} != expected mint {}",
+                source_mint, mint
+            )));
+        }
+        Ok(())
+    }
+
+    async fn token_account_mint(&self, pubkey: &Pubkey, role: &str) -> Result<Pubkey> {
+        let account = self.fetch_account(pubkey).await?;
+        decode_token_account(&account).map(|parsed| parsed.mint).ok_or_else(|| {
+            ToolkitError::InvalidAccountData(format!("{} {} is not a token account", role, pubkey))
         })
     }
 
+    /// When validation is enabled, ensure `pubkey` is a token mint.
+    async fn check_mint(&self, pubkey: &Pubkey) -> Result<()> {
+        if self.validate {
+            self.check_token_program_account(pubkey, Mint::LEN, "mint").await?;
+        }
+        Ok(())
+    }
+
+    async fn check_token_program_account(
+        &self,
+        pubkey: &Pubkey,
+        expected_len: usize,
+        kind: &str,
+    ) -> Result<()> {
+        let account = self.fetch_account(pubkey).await?;
+
+        if account.owner != spl_token::id() {
+            return Err(ToolkitError::InvalidAccountData(format!(
+                "{} is not a {}: owned by {}, expected token program {}",
+                pubkey,
+                kind,
+                account.owner,
+                spl_token::id()
+            )));
+        }
+
+        if account.data.len() != expected_len {
+            return Err(ToolkitError::InvalidAccountData(format!(
+                "{} is not a {}: data length {}, expected {}",
+                pubkey,
+                kind,
+                account.data.len(),
+                expected_len
+            )));
+        }
+
+        Ok(())
+    }
+
+    async fn fetch_account(&self, pubkey: &Pubkey) -> Result<Account> {
+        self.client
+            .get_account(pubkey)
+            .await?
+            .ok_or_else(|| ToolkitError::account_not_found(pubkey.to_string()))
+    }
+
     async fn send_transaction(&self, instructions: Vec<Instruction>) -> Result<()> {
+        self.send_signed(instructions).await.map(|_| ())
+    }
+
+    /// Send `instructions`, returning the signature, or `None` under a
+    /// dry-run policy.
+    async fn send_signed(&self, instructions: Vec<Instruction>) -> Result<Option<Signature>> {
         let recent_blockhash = self.client.get_latest_blockhash().await?;
-        let message = Message::new(&instructions, Some(&self.payer.pubkey()));
-        let transaction = Transaction::new(&[&self.payer], message, recent_blockhash);
+        let transaction = self.build_transaction(&instructions, recent_blockhash);
+        self.send_built(&transaction).await
+    }
 
-        self.client
-            .send_and_confirm_transaction(&transaction)
-            .await?;
+    /// Like [`send_signed`](Self::send_signed), with `fee_payer` paying the
+    /// fee.
+    async fn send_signed_by(
+        &self,
+        instructions: Vec<Instruction>,
+        fee_payer: &Keypair,
+    ) -> Result<Option<Signature>> {
+        let recent_blockhash = self.client.get_latest_blockhash().await?;
+        let transaction =
+            self.build_transaction_with_fee_payer(&instructions, fee_payer, recent_blockhash);
+        self.send_built(&transaction).await
+    }
 
-        Ok(())
+    async fn send_built(&self, transaction: &Transaction) -> Result<Option<Signature>> {
+        self.policy.enforce(&self.client, transaction).await?;
+        if self.policy.dry_run {
+            return Ok(None);
+        }
+        send_and_confirm(&self.client, transaction, &[], &self.send_policy)
+            .await
+            .map(Some)
     }
 }
 
+/// Source accounts merged per transaction by [`TokenClient::consolidate_mint`].
+const CONSOLIDATE_ACCOUNTS_PER_TX: usize = 8;
+
+/// Result of [`TokenClient::consolidate_mint`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Consolidation {
+    /// The associated token account that received the balances.
+    pub ata: Pubkey,
+    /// Whether the ATA had to be created (its rent is not netted out).
+    pub ata_created: bool,
+    /// Accounts that were emptied and closed.
+    pub accounts_closed: Vec<Pubkey>,
+    /// Rent returned by the closed accounts.
+    pub lamports_recovered: u64,
+    /// Raw token amount moved into the ATA.
+    pub tokens_moved: u64,
+}
+
 /// Mint information.
 #[derive(Debug, Clone)]
 pub struct MintInfo {
@@ -212,31 +823,1169 @@ pub struct MintInfo {
     pub freeze_authority: Option<Pubkey>,
 }
 
-/// Standalone burn function.
-pub async fn burn_tokens(
-    rpc_url: &str,
-    payer: Keypair,
-    mint: &Pubkey,
-    token_account: &Pubkey,
-    amount: u64,
-) -> Result<()> {
-    let client = TokenClient::new(rpc_url, payer);
-    client.burn(mint, token_account, amount).await
+impl From<Mint> for MintInfo {
+    fn from(mint: Mint) -> Self {
+        Self {
+            supply: mint.supply,
+            decimals: mint.decimals,
+            is_initialized: mint.is_initialized,
+            mint_authority: mint.mint_authority.into(),
+            freeze_authority: mint.freeze_authority.into(),
+        }
+    }
 }
 
-/// Standalone close account function.
-pub async fn close_token_account(
-    rpc_url: &str,
-    payer: Keypair,
-    token_account: &Pubkey,
-) -> Result<u64> {
-    let client = TokenClient::new(rpc_url, payer);
-    client.close_account(token_account).await
+/// Human-facing token metadata from the Metaplex metadata account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenMetadata {
+    pub mint: Pubkey,
+    pub update_authority: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Leading fields of a Metaplex `Metadata` account.
+#[derive(BorshDeserialize)]
+struct RawMetadata {
+    _key: u8,
+    update_authority: [u8; 32],
+    mint: [u8; 32],
+    name: String,
+    symbol: String,
+    uri: String,
+}
+
+impl TokenMetadata {
+    /// Decode metadata account data, trimming the null padding Metaplex
+    /// stores in fixed-width string fields.
+    pub fn from_account_data(data: &[u8]) -> Result<Self> {
+        let raw = RawMetadata::deserialize(&mut &data[..])
+            .map_err(|e| ToolkitError::InvalidAccountData(e.to_string()))?;
+
+        Ok(Self {
+            mint: Pubkey::new_from_array(raw.mint),
+            update_authority: Pubkey::new_from_array(raw.update_authority),
+            name: trim_nulls(&raw.name),
+            symbol: trim_nulls(&raw.symbol),
+            uri: trim_nulls(&raw.uri),
+        })
+    }
+}
+
+fn trim_nulls(s: &str) -> String {
+    s.trim_end_matches('\0').to_string()
+}
+
+fn decode_metadata_account(account: Option<&Account>) -> Result<Option<TokenMetadata>> {
+    match account {
+        Some(account) if account.owner == TOKEN_METADATA_PROGRAM_ID => {
+            TokenMetadata::from_account_data(&account.data).map(Some)
+        }
+        Some(account) => Err(ToolkitError::InvalidAccountData(format!(
+            "Metadata account owned by {}, expected {}",
+            account.owner, TOKEN_METADATA_PROGRAM_ID
+        ))),
+        None => Ok(None),
+    }
+}
+
+/// Mint, owner, amount and state of an SPL token account, as decoded by
+/// [`decode_token_account`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParsedTokenAccount {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub state: AccountState,
+}
+
+impl ParsedTokenAccount {
+    /// Decode the leading fields of raw token account data.
+    ///
+    /// Accepts data sliced to exactly [`TOKEN_ACCOUNT_SLICE_LEN`] bytes, in
+    /// which case the state is assumed to be initialized, or at least a full
+    /// token account. Other lengths (e.g. an 82-byte mint) are rejected.
+    fn from_data(data: &[u8]) -> Option<Self> {
+        if data.len() != TOKEN_ACCOUNT_SLICE_LEN && data.len() < TokenAccount::LEN {
+            return None;
+        }
+        let state = match data.get(TOKEN_ACCOUNT_STATE_OFFSET) {
+            None | Some(1) => AccountState::Initialized,
+            Some(0) => AccountState::Uninitialized,
+            Some(2) => AccountState::Frozen,
+            Some(_) => return None,
+        };
+        Some(Self {
+            mint: Pubkey::try_from(&data[0..32]).ok()?,
+            owner: Pubkey::try_from(&data[32..64]).ok()?,
+            amount: u64::from_le_bytes(data[64..72].try_into().ok()?),
+            state,
+        })
+    }
+
+    /// Decode the `info` object of a `jsonParsed` SPL token account.
+    fn from_json(parsed: &serde_json::Value) -> Option<Self> {
+        let info = parsed.get("info")?;
+        let pubkey = |field: &str| info.get(field)?.as_str()?.parse::<Pubkey>().ok();
+        let state = match info.get("state").and_then(|s| s.as_str()) {
+            Some("frozen") => AccountState::Frozen,
+            Some("uninitialized") => AccountState::Uninitialized,
+            _ => AccountState::Initialized,
+        };
+        Some(Self {
+            mint: pubkey("mint")?,
+            owner: pubkey("owner")?,
+            amount: info.get("tokenAmount")?.get("amount")?.as_str()?.parse().ok()?,
+            state,
+        })
+    }
+}
+
+/// Account representations [`decode_token_account`] accepts.
+pub trait TokenAccountData {
+    /// Decode the token account fields, or `None` if this isn't token
+    /// account data.
+    fn parse_token_account(&self) -> Option<ParsedTokenAccount>;
+}
+
+impl TokenAccountData for Account {
+    fn parse_token_account(&self) -> Option<ParsedTokenAccount> {
+        ParsedTokenAccount::from_data(&self.data)
+    }
+}
+
+impl TokenAccountData for UiAccount {
+    fn parse_token_account(&self) -> Option<ParsedTokenAccount> {
+        match &self.data {
+            UiAccountData::Json(parsed) => ParsedTokenAccount::from_json(&parsed.parsed),
+            _ => ParsedTokenAccount::from_data(&self.decode::<Account>()?.data),
+        }
+    }
+}
+
+/// Decode an SPL token account's mint, owner, amount and state.
+///
+/// Handles a raw [`Account`] (full or sliced to [`TOKEN_ACCOUNT_SLICE_LEN`]
+/// bytes) and an RPC [`UiAccount`] in base58, base64 or `jsonParsed`
+/// encoding, so scanners parse every form the same way. The owning program
+/// is not checked.
+pub fn decode_token_account(account: &impl TokenAccountData) -> Option<ParsedTokenAccount> {
+    account.parse_token_account()
+}
+
+/// Well-known mints as `(address, decimals, symbol)`, seeded into every
+/// [`MintRegistry`] so they resolve without an RPC call.
+pub const WELL_KNOWN_MINTS: &[(&str, u8, &str)] = &[
+    ("So11111111111111111111111111111111111111112", 9, "SOL"),
+    ("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v", 6, "USDC"),
+    ("Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB", 6, "USDT"),
+    ("DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263", 5, "BONK"),
+    ("JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN", 6, "JUP"),
+    ("4k3Dyjzvzp8eMZWUXbBCjEvwSkkk59S5iCNLY3QrkX6R", 6, "RAY"),
+    ("mSoLzYCxHdYgdzU16g5QSh3i5K3z3KZK7ytfqcJm7So", 9, "mSOL"),
+    ("J1toso1uCk3RLmjorhTtrVwY9HJ7X8V9yYac6Y7kGCPn", 9, "JitoSOL"),
+];
+
+/// Decimals and symbol of a mint, as cached by [`MintRegistry`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MintDetails {
+    pub decimals: u8,
+    /// Metaplex symbol, or `None` if the mint has no metadata account.
+    pub symbol: Option<String>,
+}
+
+/// Cache of mint decimals and symbols.
+///
+/// Seeded with [`WELL_KNOWN_MINTS`]; other mints are fetched once, together
+/// with their metadata account, and served from the cache afterwards.
+/// Clones share the same cache.
+#[derive(Debug, Clone)]
+pub struct MintRegistry {
+    mints: Arc<RwLock<HashMap<Pubkey, MintDetails>>>,
+}
+
+impl Default for MintRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MintRegistry {
+    /// Create a registry seeded with [`WELL_KNOWN_MINTS`].
+    pub fn new() -> Self {
+        let registry = Self::empty();
+        for (address, decimals, symbol) in WELL_KNOWN_MINTS {
+            let mint = Pubkey::from_str(address).expect("well-known mint address is valid");
+            registry.insert(mint, *decimals, Some(symbol.to_string()));
+        }
+        registry
+    }
+
+    /// Create a registry with nothing cached.
+    pub fn empty() -> Self {
+        Self {
+            mints: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Cache `decimals` and `symbol` for `mint`, replacing any entry.
+    pub fn insert(&self, mint: Pubkey, decimals: u8, symbol: Option<String>) {
+        self.mints.write().unwrap().insert(mint, MintDetails { decimals, symbol });
+    }
+
+    /// Cached details for `mint`, without any RPC call.
+    pub fn get(&self, mint: &Pubkey) -> Option<MintDetails> {
+        self.mints.read().unwrap().get(mint).cloned()
+    }
+
+    /// Details for `mint`, fetching and caching them if not yet known.
+    pub async fn resolve<R: SolanaRpc + ?Sized>(
+        &self,
+        client: &R,
+        mint: &Pubkey,
+    ) -> Result<MintDetails> {
+        self.resolve_many(client, std::slice::from_ref(mint))
+            .await?
+            .remove(mint)
+            .ok_or_else(|| ToolkitError::InvalidAccountData(format!("{} is not a mint", mint)))
+    }
+
+    /// Details for each of `mints`, fetching the uncached ones in one
+    /// batched lookup per 50 mints.
+    ///
+    /// Addresses that aren't mints are left out of the result.
+    pub async fn resolve_many<R: SolanaRpc + ?Sized>(
+        &self,
+        client: &R,
+        mints: &[Pubkey],
+    ) -> Result<HashMap<Pubkey, MintDetails>> {
+        let mut resolved = HashMap::new();
+        let mut missing = Vec::new();
+        for mint in mints {
+            match self.get(mint) {
+                Some(details) => {
+                    resolved.insert(*mint, details);
+                }
+                None => missing.push(*mint),
+            }
+        }
+        missing.sort();
+        missing.dedup();
+
+        for chunk in missing.chunks(50) {
+            for (mint, state, symbol) in fetch_mints(client, chunk).await? {
+                self.insert(mint, state.decimals, symbol.clone());
+                resolved.insert(mint, MintDetails { decimals: state.decimals, symbol });
+            }
+        }
+
+        Ok(resolved)
+    }
+
+    /// Fetch and cache any of `mints` not yet known.
+    pub async fn prefetch<R: SolanaRpc + ?Sized>(
+        &self,
+        client: &R,
+        mints: &[Pubkey],
+    ) -> Result<()> {
+        self.resolve_many(client, mints).await.map(|_| ())
+    }
+
+    /// Format `amount` of a cached mint, e.g. `"1.5 USDC"`, or `None` if
+    /// the mint hasn't been resolved.
+    ///
+    /// Mints without a symbol are labelled with their shortened address.
+    pub fn format_amount(&self, mint: &Pubkey, amount: u64) -> Option<String> {
+        let details = self.get(mint)?;
+        let symbol = details
+            .symbol
+            .unwrap_or_else(|| AddressVerifier::format_address_short(mint));
+        Some(AmountValidator::format_amount_with_symbol(amount, details.decimals, &symbol))
+    }
+}
+
+/// Fetch `mints` alongside their metadata accounts in one lookup.
+///
+/// Returns the state and Metaplex symbol of each address that is a mint.
+async fn fetch_mints<R: SolanaRpc + ?Sized>(
+    client: &R,
+    mints: &[Pubkey],
+) -> Result<Vec<(Pubkey, Mint, Option<String>)>> {
+    let mut keys = mints.to_vec();
+    keys.extend(mints.iter().map(|mint| find_metadata_pda(mint).0));
+    let accounts = client.get_multiple_accounts(&keys).await?;
+    let (mint_accounts, metadata_accounts) = accounts.split_at(mints.len());
+
+    let fetched = mints.iter().zip(mint_accounts).zip(metadata_accounts);
+    Ok(fetched
+        .filter_map(|((mint, account), metadata)| {
+            let state = account.as_ref().and_then(unpack_mint)?;
+            let symbol = decode_metadata_account(metadata.as_ref())
+                .ok()
+                .flatten()
+                .map(|metadata| metadata.symbol)
+                .filter(|symbol| !symbol.is_empty());
+            Some((*mint, state, symbol))
+        })
+        .collect())
+}
+
+/// The mint state stored in `account`, for SPL Token and Token-2022 mints
+/// alike.
+///
+/// Token-2022 extensions are ignored; the base state shares SPL Token's
+/// layout.
+pub(crate) fn unpack_mint(account: &Account) -> Option<Mint> {
+    if account.owner == spl_token::id() {
+        return Mint::unpack(&account.data).ok();
+    }
+    if account.owner == spl_token_2022::id() {
+        StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&account.data).ok()?;
+        return Mint::unpack(&account.data[..Mint::LEN]).ok();
+    }
+    None
+}
+
+/// ATA creations budgeted per transaction by
+/// [`TokenClient::estimate_batch_ata_cost`].
+pub const ATA_CREATIONS_PER_TX: usize = 8;
+
+/// Rent-exempt minimum for a token account under default rent parameters,
+/// for budgeting without an RPC call.
+pub fn estimated_ata_rent() -> u64 {
+    Rent::default().minimum_balance(TokenAccount::LEN)
+}
+
+/// Fail unless `destination` is a system account or doesn't exist yet.
+///
+/// Rent sent to a token or program-owned account is usually stranded.
+pub(crate) async fn check_rent_destination<R: SolanaRpc + ?Sized>(
+    client: &R,
+    destination: &Pubkey,
+) -> Result<()> {
+    match client.get_account(destination).await? {
+        Some(account) if account.executable || account.owner != system_program::id() => {
+            Err(ToolkitError::InvalidAccountData(format!(
+                "Rent destination {} is not a system account: owned by {}",
+                destination, account.owner
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Rent for `count` token accounts plus the fees of the transactions
+/// creating them.
+fn creation_cost(count: usize, ata_rent: u64) -> u64 {
+    let transactions = count.div_ceil(ATA_CREATIONS_PER_TX) as u64;
+    count as u64 * ata_rent + transactions * DEFAULT_ESTIMATED_FEE_LAMPORTS
+}
+
+/// Result of [`TokenClient::plan_airdrop`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AirdropPlan {
+    /// Number of recipient rows.
+    pub recipients: usize,
+    /// Recipient wallets without an ATA for the mint.
+    pub atas_to_create: Vec<Pubkey>,
+    /// Lamports needed to create the missing ATAs.
+    pub creation_cost: u64,
+    /// Payer's current balance in lamports.
+    pub payer_balance: u64,
+    /// Budget problems found while planning.
+    pub warnings: Vec<String>,
+}
+
+impl AirdropPlan {
+    /// Whether the payer can cover every ATA creation.
+    pub fn is_affordable(&self) -> bool {
+        self.payer_balance >= self.creation_cost
+    }
+}
+
+/// Transfers packed per transaction by [`AirdropRunner`] when the size
+/// limit allows.
+pub const DEFAULT_AIRDROP_TRANSFERS_PER_TX: usize = 10;
+
+/// Durable token distribution from the payer's ATA.
+///
+/// Recipients are paid in transactions packed up to the size limit, with a
+/// configurable pause between them. After every transaction an
+/// [`AirdropReceipt`] is rewritten at the receipt path; a rerun with the same
+/// path skips recipients it lists as paid and retries the rest.
+///
+/// Recipients without an ATA for the mint are skipped unless
+/// [`with_create_missing`](Self::with_create_missing) is set, so the payer
+/// never funds rent for accounts nobody asked for.
+pub struct AirdropRunner<R = RpcClient> {
+    tokens: TokenClient<R>,
+    mint: Pubkey,
+    receipt_path: PathBuf,
+    batch_delay: Duration,
+    max_transfers_per_tx: usize,
+    create_missing: bool,
+    stop: Option<watch::Receiver<bool>>,
+}
+
+impl<R: SolanaRpc> AirdropRunner<R> {
+    /// Distribute `mint` with `tokens`, recording receipts at `receipt_path`.
+    pub fn new(tokens: TokenClient<R>, mint: Pubkey, receipt_path: impl Into<PathBuf>) -> Self {
+        Self {
+            tokens,
+            mint,
+            receipt_path: receipt_path.into(),
+            batch_delay: Duration::ZERO,
+            max_transfers_per_tx: DEFAULT_AIRDROP_TRANSFERS_PER_TX,
+            create_missing: false,
+            stop: None,
+        }
+    }
+
+    /// Pause between transactions, to stay under RPC rate limits.
+    pub fn with_batch_delay(mut self, delay: Duration) -> Self {
+        self.batch_delay = delay;
+        self
+    }
+
+    /// Cap the transfers per transaction (at least one).
+    pub fn with_max_transfers_per_tx(mut self, max: usize) -> Self {
+        self.max_transfers_per_tx = max.max(1);
+        self
+    }
+
+    /// Create missing recipient ATAs, paying their rent, instead of skipping
+    /// those recipients.
+    pub fn with_create_missing(mut self, create_missing: bool) -> Self {
+        self.create_missing = create_missing;
+        self
+    }
+
+    /// Stop sending once `stop` is set to `true`.
+    ///
+    /// The signal is checked before each transaction and during the pause
+    /// between them; a transaction already sent is recorded before the run
+    /// returns its partial receipt.
+    pub fn with_stop_signal(mut self, stop: watch::Receiver<bool>) -> Self {
+        self.stop = Some(stop);
+        self
+    }
+
+    fn is_stopped(&self) -> bool {
+        self.stop.as_ref().is_some_and(|stop| *stop.borrow())
+    }
+
+    /// Sleep for the batch delay, waking early if the stop signal is set.
+    async fn pause(&self) {
+        let sleep = tokio::time::sleep(self.batch_delay);
+        match self.stop.clone() {
+            Some(mut stop) => {
+                tokio::select! {
+                    _ = sleep => {}
+                    _ = stop.wait_for(|stopped| *stopped) => {}
+                }
+            }
+            None => sleep.await,
+        }
+    }
+
+    /// Pay every recipient not already paid according to the receipt.
+    ///
+    /// Recipients must be unique. Recipients without an ATA are listed in the
+    /// receipt's `skipped`, or, with [create_missing](Self::with_create_missing),
+    /// have it created in the same transaction as their transfer.
+    ///
+    /// Each transaction's signature is saved as pending before it is sent.
+    /// If the send fails, the signature is watched until it lands or its
+    /// blockhash expires, and only then are its recipients marked failed;
+    /// the run then continues. Pending transfers left by an interrupted run
+    /// are looked up first: landed ones count as paid, expired or failed
+    /// ones are paid again, and ones that may still land are left pending.
+    /// Under a dry-run policy nothing is sent or recorded. If the
+    /// [stop signal](Self::with_stop_signal) is set, the receipt is returned
+    /// with `cancelled` set and the remaining recipients left unpaid.
+    pub async fn run(&self, recipients: &[(Pubkey, u64)]) -> Result<AirdropReceipt> {
+        let mut seen = HashSet::new();
+        if let Some((duplicate, _)) = recipients.iter().find(|(wallet, _)| !seen.insert(*wallet)) {
+            return Err(ToolkitError::ConfigError(format!(
+                "Recipient {} is listed more than once",
+                duplicate
+            )));
+        }
+
+        let mut receipt = AirdropReceipt::load_or_new(&self.receipt_path, &self.mint)?;
+        if !receipt.pending.is_empty() {
+            self.resolve_pending(&mut receipt).await?;
+            receipt.save(&self.receipt_path)?;
+        }
+        let pending: Vec<(Pubkey, u64)> = recipients
+            .iter()
+            .filter(|(wallet, _)| !receipt.is_paid(wallet) && !receipt.is_pending(wallet))
+            .copied()
+            .collect();
+        info!(
+            already_paid = receipt.paid.len(),
+            in_flight = receipt.pending.len(),
+            remaining = pending.len(),
+            "starting airdrop"
+        );
+
+        let (batches, skipped) = self.build_batches(&pending).await?;
+        if !skipped.is_empty() {
+            info!(skipped = skipped.len(), "skipping recipients without a token account");
+        }
+        receipt.skipped = skipped;
+        for (index, (wallets, instructions)) in batches.into_iter().enumerate() {
+            if index > 0 && !self.batch_delay.is_zero() {
+                self.pause().await;
+            }
+            if self.is_stopped() {
+                info!(paid = receipt.paid.len(), "airdrop cancelled");
+                receipt.cancelled = true;
+                break;
+            }
+
+            let tokens = &self.tokens;
+            let recent_blockhash = tokens.client.get_latest_blockhash().await?;
+            let last_valid_block_height =
+                tokens.client.get_block_height().await? + MAX_PROCESSING_AGE as u64;
+            let transaction = tokens.build_transaction(&instructions, recent_blockhash);
+            tokens.policy.enforce(&tokens.client, &transaction).await?;
+            if tokens.policy.dry_run {
+                continue;
+            }
+
+            let signature = transaction.signatures[0];
+            receipt.record_pending(&wallets, &signature, last_valid_block_height);
+            receipt.save(&self.receipt_path)?;
+
+            match send_and_confirm(&tokens.client, &transaction, &[], &tokens.send_policy).await {
+                Ok(_) => receipt.record_paid(&wallets, &signature),
+                Err(e) => {
+                    warn!(recipients = wallets.len(), error = %e, "airdrop batch send failed");
+                    // The send may still land; wait until it does or cannot
+                    let landed = confirm_until_block_height(
+                        &tokens.client,
+                        &signature,
+                        last_valid_block_height,
+                        tokens.send_policy.commitment,
+                        DEFAULT_CONFIRM_POLL_INTERVAL,
+                    )
+                    .await;
+                    match landed {
+                        Ok(_) => receipt.record_paid(&wallets, &signature),
+                        Err(ToolkitError::TransactionExpired { .. }) => {
+                            receipt.record_failed(&wallets, &e.to_string())
+                        }
+                        Err(ToolkitError::TransactionError(reason)) => {
+                            receipt.record_failed(&wallets, &reason)
+                        }
+                        // Unknown: leave pending for the next run to look up
+                        Err(e) => warn!(error = %e, "airdrop batch outcome unknown"),
+                    }
+                }
+            }
+            receipt.save(&self.receipt_path)?;
+        }
+
+        Ok(receipt)
+    }
+
+    /// Settle the transfers in `receipt.pending`, sent by an earlier run.
+    async fn resolve_pending(&self, receipt: &mut AirdropReceipt) -> Result<()> {
+        let client = &self.tokens.client;
+        let commitment = self.tokens.send_policy.commitment;
+        // Read the height before the statuses, so a transfer landing between
+        // the two calls is not taken as expired
+        let block_height = client.get_block_height().await?;
+
+        let signatures: Vec<String> = receipt
+            .pending
+            .values()
+            .map(|transfer| transfer.signature.clone())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        let mut statuses = HashMap::new();
+        for chunk in signatures.chunks(MAX_SIGNATURE_STATUSES_PER_CALL) {
+            let parsed = chunk
+                .iter()
+                .map(|signature| {
+                    Signature::from_str(signature).map_err(|e| {
+                        ToolkitError::ParseError(format!(
+                            "Invalid signature {} in receipt: {}",
+                            signature, e
+                        ))
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let found = client.get_signature_statuses(&parsed).await?;
+            statuses.extend(chunk.iter().cloned().zip(found));
+        }
+
+        let pending = std::mem::take(&mut receipt.pending);
+        for (recipient, transfer) in pending {
+            let status = statuses.get(&transfer.signature).cloned().flatten();
+            match status {
+                Some(TransactionStatus { err: Some(err), .. }) => {
+                    let reason = format!("Transaction {} failed: {:?}", transfer.signature, err);
+                    receipt.failed.insert(recipient, reason);
+                }
+                Some(status) if status.satisfies_commitment(commitment) => {
+                    receipt.failed.remove(&recipient);
+                    receipt.paid.insert(recipient, transfer.signature);
+                }
+                None if block_height > transfer.last_valid_block_height => {
+                    let reason = format!("Transaction {} expired", transfer.signature);
+                    receipt.failed.insert(recipient, reason);
+                }
+                // Seen but not yet at the commitment, or not seen yet
+                _ => {
+                    receipt.pending.insert(recipient, transfer);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Group transfers (and ATA creations) into transactions that fit.
+    ///
+    /// Also returns the recipients skipped for lacking an ATA.
+    async fn build_batches(
+        &self,
+        recipients: &[(Pubkey, u64)],
+    ) -> Result<(Vec<(Vec<Pubkey>, Vec<Instruction>)>, Vec<Pubkey>)> {
+        let (source, _) = find_associated_token_address(&self.tokens.payer.pubkey(), &self.mint);
+        let atas: Vec<Pubkey> = recipients
+            .iter()
+            .map(|(wallet, _)| find_associated_token_address(wallet, &self.mint).0)
+            .collect();
+
+        let mut existing = Vec::with_capacity(atas.len());
+        for chunk in atas.chunks(100) {
+            existing.extend(self.tokens.client.get_multiple_accounts(chunk).await?);
+        }
+
+        let mut batches: Vec<(Vec<Pubkey>, Vec<Instruction>)> = Vec::new();
+        let mut skipped = Vec::new();
+        let mut wallets = Vec::new();
+        let mut instructions = Vec::new();
+        for (((wallet, amount), ata), account) in recipients.iter().zip(&atas).zip(existing) {
+            let mut recipient_ixs = Vec::new();
+            if account.is_none() {
+                if !self.create_missing {
+                    skipped.push(*wallet);
+                    continue;
+                }
+                recipient_ixs.push(self.tokens.create_ata_ix(wallet, &self.mint));
+            }
+            recipient_ixs.push(self.tokens.transfer_ix(&source, ata, *amount)?);
+
+            let candidate: Vec<Instruction> =
+                instructions.iter().chain(&recipient_ixs).cloned().collect();
+            let candidate = self.tokens.with_compute_budget(&candidate);
+            if !wallets.is_empty()
+                && (wallets.len() == self.max_transfers_per_tx
+                    || !will_fit_in_transaction(&candidate, 1))
+            {
+                batches.push((std::mem::take(&mut wallets), std::mem::take(&mut instructions)));
+            }
+            wallets.push(*wallet);
+            instructions.extend(recipient_ixs);
+        }
+        if !wallets.is_empty() {
+            batches.push((wallets, instructions));
+        }
+
+        Ok((batches, skipped))
+    }
+}
+
+/// Receipt file written by [`AirdropRunner`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AirdropReceipt {
+    /// Mint being distributed.
+    pub mint: String,
+    /// Paid recipient -> signature of the transaction that paid it.
+    pub paid: HashMap<String, String>,
+    /// Unpaid recipient -> error from its last attempt.
+    pub failed: HashMap<String, String>,
+    /// Recipient -> transfer sent to it whose outcome is not yet known.
+    #[serde(default)]
+    pub pending: HashMap<String, PendingTransfer>,
+    /// Whether the run returned early on its stop signal. Not saved.
+    #[serde(skip)]
+    pub cancelled: bool,
+    /// Recipients this run skipped because they have no ATA and creation
+    /// was not enabled. Not saved.
+    #[serde(skip)]
+    pub skipped: Vec<Pubkey>,
+}
+
+impl AirdropReceipt {
+    /// Create an empty receipt for `mint`.
+    pub fn new(mint: &Pubkey) -> Self {
+        Self {
+            mint: mint.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Load the receipt at `path`, or start a new one if it does not exist.
+    ///
+    /// Fails if the file belongs to a different mint.
+    pub fn load_or_new(path: &Path, mint: &Pubkey) -> Result<Self> {
+        let Some(receipt) = state_file::load::<Self>(path, "receipt")? else {
+            return Ok(Self::new(mint));
+        };
+
+        if receipt.mint != mint.to_string() {
+            return Err(ToolkitError::ConfigError(format!(
+                "Receipt {} is for mint {}, not {}",
+                path.display(),
+                receipt.mint,
+                mint
+            )));
+        }
+        Ok(receipt)
+    }
+
+    /// Whether `recipient` was paid by an earlier transaction.
+    pub fn is_paid(&self, recipient: &Pubkey) -> bool {
+        self.paid.contains_key(&recipient.to_string())
+    }
+
+    /// Whether a transfer to `recipient` was sent and may still land.
+    pub fn is_pending(&self, recipient: &Pubkey) -> bool {
+        self.pending.contains_key(&recipient.to_string())
+    }
+
+    /// Record a transaction paying `recipients` as about to be sent.
+    pub fn record_pending(
+        &mut self,
+        recipients: &[Pubkey],
+        signature: &Signature,
+        last_valid_block_height: u64,
+    ) {
+        for recipient in recipients {
+            let transfer = PendingTransfer {
+                signature: signature.to_string(),
+                last_valid_block_height,
+            };
+            self.pending.insert(recipient.to_string(), transfer);
+        }
+    }
+
+    /// Record `recipients` as paid by `signature`.
+    pub fn record_paid(&mut self, recipients: &[Pubkey], signature: &Signature) {
+        for recipient in recipients {
+            self.failed.remove(&recipient.to_string());
+            self.pending.remove(&recipient.to_string());
+            self.paid.insert(recipient.to_string(), signature.to_string());
+        }
+    }
+
+    /// Record a failed attempt to pay `recipients`.
+    pub fn record_failed(&mut self, recipients: &[Pubkey], error: &str) {
+        for recipient in recipients {
+            self.pending.remove(&recipient.to_string());
+            self.failed.insert(recipient.to_string(), error.to_string());
+        }
+    }
+
+    /// Write the receipt to `path`, replacing it atomically.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        state_file::save(self, path, "receipt")
+    }
+}
+
+/// A transfer recorded in an [`AirdropReceipt`] before it was sent.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingTransfer {
+    /// Signature of the transaction.
+    pub signature: String,
+    /// Block height after which the transaction can no longer land.
+    pub last_valid_block_height: u64,
+}
+
+/// How exposed holders of a mint are to having their tokens frozen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FreezeRisk {
+    /// The mint has no freeze authority.
+    Low,
+    /// The mint has an active freeze authority.
+    Medium,
+    /// The freeze authority has frozen at least one holder.
+    High,
+}
+
+/// Byte offset of the `state` field in an SPL token account.
+const TOKEN_ACCOUNT_STATE_OFFSET: usize = 108;
+
+/// `getProgramAccounts` config selecting frozen token accounts of `mint`
+/// under `token_program`, with no account data.
+///
+/// Token-2022 accounts with extensions are longer than the base layout, so
+/// only SPL Token accounts are filtered by size.
+fn frozen_holders_config(mint: &Pubkey, token_program: &Pubkey) -> RpcProgramAccountsConfig {
+    let mut filters = vec![
+        RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, mint.as_ref())),
+        RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            TOKEN_ACCOUNT_STATE_OFFSET,
+            &[AccountState::Frozen as u8],
+        )),
+    ];
+    if *token_program == spl_token::id() {
+        filters.insert(0, RpcFilterType::DataSize(TokenAccount::LEN as u64));
+    }
+    RpcProgramAccountsConfig {
+        filters: Some(filters),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            data_slice: Some(UiDataSliceConfig {
+                offset: 0,
+                length: 0,
+            }),
+            commitment: None,
+            min_context_slot: None,
+        },
+        with_context: None,
+    }
+}
+
+/// Transfer fee settings of a Token-2022 mint account, if it has the
+/// transfer fee extension.
+pub fn transfer_fee_config(mint: &Account) -> Option<TransferFeeConfig> {
+    if mint.owner != spl_token_2022::id() {
+        return None;
+    }
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint.data).ok()?;
+    state.get_extension::<TransferFeeConfig>().ok().copied()
+}
+
+/// Amount the recipient receives when `amount` of the mint stored in
+/// `mint` is transferred.
+///
+/// Token-2022 mints with a transfer fee withhold the fee for the current
+/// epoch, read from the clock sysvar, from the amount received. Other
+/// mints deliver the full amount, without an RPC call.
+pub async fn transfer_amount_after_fee<R: SolanaRpc + ?Sized>(
+    client: &R,
+    mint: &Account,
+    amount: u64,
+) -> Result<u64> {
+    let Some(config) = transfer_fee_config(mint) else {
+        return Ok(amount);
+    };
+
+    let clock = client
+        .get_account(&sysvar::clock::id())
+        .await?
+        .ok_or_else(|| ToolkitError::account_not_found(sysvar::clock::id().to_string()))?;
+    let clock: Clock = bincode::deserialize(&clock.data)
+        .map_err(|e| ToolkitError::InvalidAccountData(e.to_string()))?;
+    let fee = config.calculate_epoch_fee(clock.epoch, amount).ok_or_else(|| {
+        ToolkitError::TokenError(format!("Transfer fee of {} overflows", amount))
+    })?;
+
+    Ok(amount - fee)
+}
+
+/// Assess whether `mint`, an SPL Token or Token-2022 mint, can freeze, or
+/// has frozen, holders' token accounts.
+///
+/// A mint without a freeze authority is [`FreezeRisk::Low`]. With one, the
+/// risk is [`FreezeRisk::Medium`], or [`FreezeRisk::High`] if
+/// `sample_holders` is set and a `getProgramAccounts` query finds frozen
+/// accounts for the mint. Some RPC providers restrict that query.
+pub async fn assess_freeze_risk<R: SolanaRpc + ?Sized>(
+    client: &R,
+    mint: &Pubkey,
+    sample_holders: bool,
+) -> Result<FreezeRisk> {
+    let account = client
+        .get_account(mint)
+        .await?
+        .ok_or_else(|| ToolkitError::account_not_found(mint.to_string()))?;
+    let mint_state = unpack_mint(&account).ok_or_else(|| {
+        ToolkitError::InvalidAccountData(format!("{} is not a token mint", mint))
+    })?;
+
+    if mint_state.freeze_authority.is_none() {
+        return Ok(FreezeRisk::Low);
+    }
+    if sample_holders {
+        let frozen = client
+            .get_program_accounts(&account.owner, frozen_holders_config(mint, &account.owner))
+            .await?;
+        if !frozen.is_empty() {
+            return Ok(FreezeRisk::High);
+        }
+    }
+    Ok(FreezeRisk::Medium)
+}
+
+/// Standalone burn function.
+pub async fn burn_tokens(
+    rpc_url: &str,
+    payer: Keypair,
+    mint: &Pubkey,
+    token_account: &Pubkey,
+    amount: u64,
+) -> Result<()> {
+    let client = TokenClient::new(rpc_url, payer);
+    client.burn(mint, token_account, amount).await
+}
+
+/// Standalone close account function.
+pub async fn close_token_account(
+    rpc_url: &str,
+    payer: Keypair,
+    token_account: &Pubkey,
+) -> Result<u64> {
+    let client = TokenClient::new(rpc_url, payer);
+    client.close_account(token_account).await
+}
+
+/// Parse `address,amount` rows into recipients for airdrops.
+///
+/// Amounts are UI values (e.g. `1.5`) scaled by `decimals`; more fractional
+/// digits than `decimals` is an error rather than silently rounding. Blank
+/// lines and lines starting with `#` are skipped, and a first row made of
+/// column names (e.g. `address,amount`) is treated as a header. Errors name
+/// the 1-based line number of the offending row.
+///
+/// # Example
+/// ```ignore
+/// let file = std::io::BufReader::new(std::fs::File::open("airdrop.csv")?);
+/// let recipients = parse_recipients_csv(file, 6)?;
+/// ```
+pub fn parse_recipients_csv(reader: impl BufRead, decimals: u8) -> Result<Vec<(Pubkey, u64)>> {
+    let mut recipients = Vec::new();
+    let mut seen_row = false;
+
+    for (idx, line) in reader.lines().enumerate() {
+        let line_no = idx + 1;
+        let line =
+            line.map_err(|e| ToolkitError::ParseError(format!("line {}: {}", line_no, e)))?;
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        if fields.len() != 2 {
+            return Err(ToolkitError::ParseError(format!(
+                "line {}: expected 2 columns (address,amount), got {}",
+                line_no,
+                fields.len()
+            )));
+        }
+
+        let is_first_row = !seen_row;
+        seen_row = true;
+        if is_first_row && is_header_row(&fields) {
+            continue;
+        }
+
+        let address = AddressVerifier::verify_address(fields[0])
+            .map_err(|e| ToolkitError::ParseError(format!("line {}: {}", line_no, e)))?;
+        let amount = parse_ui_amount(fields[1], decimals)
+            .map_err(|e| ToolkitError::ParseError(format!("line {}: {}", line_no, e)))?;
+
+        recipients.push((address, amount));
+    }
+
+    Ok(recipients)
+}
+
+/// Column names accepted in a recipients CSV header row.
+const RECIPIENT_CSV_COLUMNS: [&str; 6] =
+    ["address", "recipient", "wallet", "owner", "pubkey", "amount"];
+
+/// A first row is a header only if its address is not a pubkey and every
+/// column is a known column name, so a malformed first recipient is reported.
+fn is_header_row(fields: &[&str]) -> bool {
+    fields[0].parse::<Pubkey>().is_err()
+        && fields
+            .iter()
+            .all(|field| RECIPIENT_CSV_COLUMNS.iter().any(|col| field.eq_ignore_ascii_case(col)))
+}
+
+/// Parse a decimal UI amount into base units without floating point.
+pub(crate) fn parse_ui_amount(value: &str, decimals: u8) -> std::result::Result<u64, String> {
+    let (whole, fraction) = value.split_once('.').unwrap_or((value, ""));
+
+    let is_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    if (whole.is_empty() && fraction.is_empty()) || !is_digits(whole) || !is_digits(fraction) {
+        return Err(format!("invalid amount '{}'", value));
+    }
+
+    if fraction.len() > decimals as usize {
+        return Err(format!(
+            "amount '{}' has more than {} decimal places",
+            value, decimals
+        ));
+    }
+
+    let digits = format!("{}{:0<width$}", whole, fraction, width = decimals as usize);
+    let amount = digits
+        .parse::<u64>()
+        .map_err(|_| format!("amount '{}' is too large", value))?;
+
+    if amount == 0 {
+        return Err("amount must be greater than zero".to_string());
+    }
+
+    Ok(amount)
+}
+
+/// Change in a wallet's total balance of one mint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HoldingChange {
+    /// Token mint.
+    pub mint: Pubkey,
+    /// Total raw amount across the wallet's accounts for `mint` before.
+    pub old_amount: u64,
+    /// Total raw amount across the wallet's accounts for `mint` after.
+    pub new_amount: u64,
+}
+
+/// In-memory view of a wallet's token accounts, used to turn account
+/// updates into [`HoldingChange`]s.
+#[derive(Debug, Clone, Default)]
+pub struct HoldingsSnapshot {
+    /// Token account -> (mint, amount).
+    accounts: HashMap<Pubkey, (Pubkey, u64)>,
+}
+
+impl HoldingsSnapshot {
+    /// Create an empty snapshot.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Seed the snapshot with a known token account state.
+    pub fn insert(&mut self, account: Pubkey, mint: Pubkey, amount: u64) {
+        self.accounts.insert(account, (mint, amount));
+    }
+
+    /// Total raw amount of `mint` across all known accounts.
+    pub fn balance(&self, mint: &Pubkey) -> u64 {
+        self.accounts
+            .values()
+            .filter(|(m, _)| m == mint)
+            .map(|(_, amount)| amount)
+            .sum()
+    }
+
+    /// Apply the new state of `account`, where `None` means it was closed.
+    ///
+    /// Returns the change in the mint's total, or `None` if it is unchanged.
+    pub fn apply(
+        &mut self,
+        account: Pubkey,
+        state: Option<(Pubkey, u64)>,
+    ) -> Option<HoldingChange> {
+        let mint = match (state, self.accounts.get(&account)) {
+            (Some((mint, _)), _) | (None, Some(&(mint, _))) => mint,
+            (None, None) => return None,
+        };
+
+        let old_amount = self.balance(&mint);
+        match state {
+            Some(state) => self.accounts.insert(account, state),
+            None => self.accounts.remove(&account),
+        };
+        let new_amount = self.balance(&mint);
+
+        (old_amount != new_amount).then_some(HoldingChange {
+            mint,
+            old_amount,
+            new_amount,
+        })
+    }
+
+    /// Apply a `programSubscribe` notification for a token account.
+    ///
+    /// Accounts that are empty or no longer parse as token accounts are
+    /// treated as closed.
+    pub fn apply_update(&mut self, update: &RpcKeyedAccount) -> Option<HoldingChange> {
+        let account = Pubkey::from_str(&update.pubkey).ok()?;
+        let state = Some(&update.account)
+            .filter(|a| a.lamports > 0)
+            .and_then(decode_token_account)
+            .map(|parsed| (parsed.mint, parsed.amount));
+        self.apply(account, state)
+    }
+}
+
+/// Stream changes to `owner`'s token balances over a websocket.
+///
+/// Subscribes to the SPL Token program filtered to `owner`'s accounts and
+/// yields a [`HoldingChange`] whenever a mint's total changes. Diffs start
+/// from an empty snapshot, so the first update for an account reports an
+/// old amount of 0; use [`watch_holdings_from`] to seed it. The stream ends
+/// when the subscription closes.
+pub async fn watch_holdings(
+    ws_url: &str,
+    owner: &Pubkey,
+) -> Result<impl Stream<Item = HoldingChange>> {
+    watch_holdings_from(ws_url, owner, HoldingsSnapshot::new()).await
+}
+
+/// Like [`watch_holdings`], diffing against a pre-populated snapshot.
+pub async fn watch_holdings_from(
+    ws_url: &str,
+    owner: &Pubkey,
+    mut snapshot: HoldingsSnapshot,
+) -> Result<impl Stream<Item = HoldingChange>> {
+    let pubsub = PubsubClient::new(ws_url)
+        .await
+        .map_err(|e| ToolkitError::NetworkError(format!("Websocket connect failed: {}", e)))?;
+    let config = token_accounts_by_owner_config(owner);
+    let (changes_tx, changes_rx) = mpsc::unbounded();
+    let (subscribed_tx, subscribed_rx) = oneshot::channel();
+
+    // The subscription borrows the client, so both live in the task
+    tokio::spawn(async move {
+        let (mut updates, unsubscribe) =
+            match pubsub.program_subscribe(&spl_token::id(), Some(config)).await {
+                Ok(subscription) => {
+                    let _ = subscribed_tx.send(Ok(()));
+                    subscription
+                }
+                Err(e) => {
+                    let _ = subscribed_tx.send(Err(e));
+                    return;
+                }
+            };
+
+        while let Some(update) = updates.next().await {
+            if let Some(change) = snapshot.apply_update(&update.value) {
+                if changes_tx.unbounded_send(change).is_err() {
+                    break;
+                }
+            }
+        }
+
+        drop(updates);
+        unsubscribe().await;
+    });
+
+    subscribed_rx
+        .await
+        .map_err(|_| ToolkitError::NetworkError("programSubscribe task ended".to_string()))?
+        .map_err(|e| ToolkitError::NetworkError(format!("programSubscribe failed: {}", e)))?;
+
+    Ok(changes_rx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spl_token::instruction::TokenInstruction;
 
     #[test]
     fn test_mint_info_debug() {
@@ -249,4 +1998,1011 @@ mod tests {
         };
         assert_eq!(info.decimals, 9);
     }
+
+    fn padded(s: &str, width: usize) -> Vec<u8> {
+        let mut bytes = s.as_bytes().to_vec();
+        bytes.resize(width, 0);
+        let mut out = (width as u32).to_le_bytes().to_vec();
+        out.extend(bytes);
+        out
+    }
+
+    fn metadata_fixture(update_authority: &Pubkey, mint: &Pubkey) -> Vec<u8> {
+        let mut data = vec![4u8]; // Key::MetadataV1
+        data.extend_from_slice(update_authority.as_ref());
+        data.extend_from_slice(mint.as_ref());
+        data.extend(padded("Pipkit Token", 32));
+        data.extend(padded("PIPK", 10));
+        data.extend(padded("https://example.com/pipk.json", 200));
+        // seller_fee_basis_points and trailing fields are ignored
+        data.extend_from_slice(&500u16.to_le_bytes());
+        data.extend_from_slice(&[0u8; 64]);
+        data
+    }
+
+    #[test]
+    fn test_decode_token_metadata_fixture() {
+        let update_authority = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let account = Account {
+            lamports: 5_616_720,
+            data: metadata_fixture(&update_authority, &mint),
+            owner: TOKEN_METADATA_PROGRAM_ID,
+            executable: false,
+            rent_epoch: 0,
+        };
+
+        let metadata = decode_metadata_account(Some(&account)).unwrap().unwrap();
+
+        assert_eq!(metadata.mint, mint);
+        assert_eq!(metadata.update_authority, update_authority);
+        assert_eq!(metadata.name, "Pipkit Token");
+        assert_eq!(metadata.symbol, "PIPK");
+        assert_eq!(metadata.uri, "https://example.com/pipk.json");
+    }
+
+    #[test]
+    fn test_decode_missing_metadata_account() {
+        assert_eq!(decode_metadata_account(None).unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_mint_registry_resolves_well_known_mint_offline() {
+        use crate::testing::MockRpc;
+
+        let rpc = MockRpc::new();
+        let registry = MintRegistry::new();
+        let usdc = Pubkey::from_str("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").unwrap();
+
+        let details = registry.resolve(&rpc, &usdc).await.unwrap();
+
+        assert_eq!(details.decimals, 6);
+        assert_eq!(details.symbol.as_deref(), Some("USDC"));
+        assert_eq!(registry.format_amount(&usdc, 1_500_000).unwrap(), "1.5 USDC");
+        assert!(rpc.account_requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_mint_registry_caches_rpc_lookup() {
+        use crate::testing::MockRpc;
+
+        let mint = Pubkey::new_unique();
+        let metadata = Account {
+            lamports: 5_616_720,
+            data: metadata_fixture(&Pubkey::new_unique(), &mint),
+            owner: TOKEN_METADATA_PROGRAM_ID,
+            executable: false,
+            rent_epoch: 0,
+        };
+        let rpc = MockRpc::new()
+            .with_account(mint, MockRpc::mint_account(3, 1_000))
+            .with_account(find_metadata_pda(&mint).0, metadata);
+        let registry = MintRegistry::empty();
+
+        let first = registry.resolve(&rpc, &mint).await.unwrap();
+        registry.prefetch(&rpc, &[mint]).await.unwrap();
+        let second = registry.resolve(&rpc, &mint).await.unwrap();
+
+        assert_eq!(first, second);
+        assert_eq!(first.decimals, 3);
+        assert_eq!(first.symbol.as_deref(), Some("PIPK"));
+        assert_eq!(rpc.account_requests().len(), 1);
+
+        let not_a_mint = Pubkey::new_unique();
+        assert!(registry.resolve(&rpc, &not_a_mint).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_mint_infos_skips_non_mints() {
+        use crate::testing::MockRpc;
+
+        let (named, plain, token_account, missing) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let token_2022 = Pubkey::new_unique();
+        let metadata = Account {
+            lamports: 5_616_720,
+            data: metadata_fixture(&Pubkey::new_unique(), &named),
+            owner: TOKEN_METADATA_PROGRAM_ID,
+            executable: false,
+            rent_epoch: 0,
+        };
+        let rpc = MockRpc::new()
+            .with_account(named, MockRpc::mint_account(3, 1_000))
+            .with_account(find_metadata_pda(&named).0, metadata)
+            .with_account(plain, MockRpc::mint_account(9, 42))
+            .with_account(token_2022, MockRpc::transfer_fee_mint_account(4, 100, 1_000))
+            .with_account(token_account, MockRpc::token_account(&plain, &named, 5));
+        let client = TokenClient::from_client(rpc, Keypair::new());
+
+        let infos = client
+            .get_mint_infos(&[named, plain, token_2022, token_account, missing, plain])
+            .await
+            .unwrap();
+
+        assert_eq!(infos.len(), 3);
+        assert_eq!(infos[&token_2022].decimals, 4);
+        assert_eq!((infos[&named].decimals, infos[&named].supply), (3, 1_000));
+        assert_eq!((infos[&plain].decimals, infos[&plain].supply), (9, 42));
+        assert!(infos[&plain].is_initialized);
+        assert_eq!(client.client.account_requests().len(), 1);
+
+        // Both mints are now cached, with the symbol of the one with metadata
+        let registry = client.mint_registry();
+        assert_eq!(registry.get(&named).unwrap().symbol.as_deref(), Some("PIPK"));
+        assert_eq!(registry.get(&plain).unwrap().decimals, 9);
+        assert!(registry.get(&token_account).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_execution_policy_blocks_failed_simulation() {
+        use crate::testing::MockRpc;
+        use solana_sdk::transaction::TransactionError;
+
+        let payer = Keypair::new();
+        let owner = payer.pubkey();
+        let mint = Pubkey::new_unique();
+        let (source, destination) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let rpc = MockRpc::new()
+            .with_account(source, MockRpc::token_account(&mint, &owner, 10))
+            .with_account(destination, MockRpc::token_account(&mint, &owner, 0));
+        rpc.fail_simulations(TransactionError::InsufficientFundsForFee);
+
+        let client = TokenClient::from_client(rpc, payer)
+            .with_execution_policy(ExecutionPolicy::strict());
+        let err = client.transfer(&mint, &source, &destination, 5).await.unwrap_err();
+        assert!(err.to_string().contains("Simulation failed"));
+        assert!(client.close_account(&destination).await.is_err());
+        assert!(client.client.sent_transactions().is_empty());
+
+        // Without the policy nothing simulates, so the send goes through
+        let client = TokenClient::from_client(client.client, client.payer);
+        client.transfer(&mint, &source, &destination, 5).await.unwrap();
+        assert_eq!(client.client.sent_transactions().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_transfer_rejects_mismatched_mints() {
+        use crate::testing::MockRpc;
+
+        let payer = Keypair::new();
+        let owner = payer.pubkey();
+        let (usdc, bonk) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (source, usdc_destination, bonk_destination) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let rpc = MockRpc::new()
+            .with_account(source, MockRpc::token_account(&usdc, &owner, 10))
+            .with_account(usdc_destination, MockRpc::token_account(&usdc, &owner, 0))
+            .with_account(bonk_destination, MockRpc::token_account(&bonk, &owner, 0));
+        let client = TokenClient::from_client(rpc, payer);
+
+        let err = client.transfer(&usdc, &source, &bonk_destination, 5).await.unwrap_err();
+        assert!(matches!(err, ToolkitError::TokenError(_)));
+        assert!(
+            err.to_string()
+                .contains(&format!("source mint {} != destination mint {}", usdc, bonk)),
+            "{}",
+            err
+        );
+        let err = client.transfer(&bonk, &source, &usdc_destination, 5).await.unwrap_err();
+        assert!(err.to_string().contains("expected mint"), "{}", err);
+        assert!(client.client.sent_transactions().is_empty());
+
+        client.transfer(&usdc, &source, &usdc_destination, 5).await.unwrap();
+        assert_eq!(client.client.sent_transactions().len(), 1);
+    }
+
+    fn mint_with_freeze_authority(authority: Pubkey) -> Account {
+        let mut account = crate::testing::MockRpc::mint_account(6, 1_000);
+        let mut mint = Mint::unpack(&account.data).unwrap();
+        mint.freeze_authority = Some(authority).into();
+        Mint::pack(mint, &mut account.data).unwrap();
+        account
+    }
+
+    /// A Token-2022 mint with a transfer fee extension and `authority` as
+    /// its freeze authority.
+    fn token_2022_mint_with_freeze_authority(authority: Pubkey) -> Account {
+        use spl_token_2022::extension::StateWithExtensionsMut;
+
+        let mut account = crate::testing::MockRpc::transfer_fee_mint_account(6, 100, u64::MAX);
+        let mut state =
+            StateWithExtensionsMut::<spl_token_2022::state::Mint>::unpack(&mut account.data)
+                .unwrap();
+        state.base.freeze_authority = Some(authority).into();
+        state.pack_base();
+        account
+    }
+
+    #[test]
+    fn test_creation_cost_arithmetic() {
+        assert_eq!(estimated_ata_rent(), crate::testing::TOKEN_ACCOUNT_RENT);
+        assert_eq!(creation_cost(0, 2_000_000), 0);
+        assert_eq!(creation_cost(1, 2_000_000), 2_000_000 + DEFAULT_ESTIMATED_FEE_LAMPORTS);
+        // 20 creations take three transactions
+        assert_eq!(
+            creation_cost(20, 2_000_000),
+            40_000_000 + 3 * DEFAULT_ESTIMATED_FEE_LAMPORTS
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_minimum_rent_shared_across_tasks() {
+        use crate::testing::{MockRpc, TOKEN_ACCOUNT_RENT};
+        use std::sync::Arc;
+
+        let rpc = MockRpc::new().with_rent(&Rent::default());
+        let client = Arc::new(TokenClient::from_client(rpc, Keypair::new()));
+        let tasks: Vec<_> = (0..16)
+            .map(|i| {
+                let client = Arc::clone(&client);
+                tokio::spawn(async move {
+                    if i % 4 == 0 {
+                        client.invalidate_rent_cache();
+                    }
+                    client.minimum_rent().await.unwrap()
+                })
+            })
+            .collect();
+        for task in tasks {
+            assert_eq!(task.await.unwrap(), TOKEN_ACCOUNT_RENT);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_minimum_rent_is_fetched_once() {
+        use crate::testing::{MockRpc, TOKEN_ACCOUNT_RENT};
+
+        let payer = Keypair::new();
+        let mint = Pubkey::new_unique();
+        let rpc = MockRpc::new()
+            .with_rent(&Rent::default())
+            .with_balance(payer.pubkey(), TOKEN_ACCOUNT_RENT);
+        let client = TokenClient::from_client(rpc, payer).with_validation(true);
+        let rent_reads = |client: &TokenClient<MockRpc>| {
+            let requests = client.client.account_requests();
+            requests.iter().filter(|keys| keys.contains(&sysvar::rent::id())).count()
+        };
+
+        for _ in 0..3 {
+            client.create_associated_token_account(&Pubkey::new_unique(), &mint).await.unwrap();
+        }
+        assert_eq!(
+            client.estimate_batch_ata_cost(100).await.unwrap(),
+            100 * TOKEN_ACCOUNT_RENT + 13 * DEFAULT_ESTIMATED_FEE_LAMPORTS
+        );
+        assert_eq!(client.client.sent_transactions().len(), 3);
+        assert_eq!(rent_reads(&client), 1);
+
+        client.invalidate_rent_cache();
+        assert_eq!(client.minimum_rent().await.unwrap(), TOKEN_ACCOUNT_RENT);
+        assert_eq!(rent_reads(&client), 2);
+
+        // A payer that can't fund the rent is stopped before sending
+        client.client.set_account(
+            client.payer.pubkey(),
+            Account::new(TOKEN_ACCOUNT_RENT - 1, 0, &system_program::id()),
+        );
+        let err = client
+            .create_associated_token_account(&Pubkey::new_unique(), &mint)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolkitError::InsufficientBalance { .. }), "{}", err);
+        assert_eq!(client.client.sent_transactions().len(), 3);
+        assert_eq!(rent_reads(&client), 2);
+    }
+
+    #[tokio::test]
+    async fn test_plan_airdrop_warns_when_payer_is_short() {
+        use crate::testing::{MockRpc, TOKEN_ACCOUNT_RENT};
+
+        let payer = Keypair::new();
+        let mint = Pubkey::new_unique();
+        let funded = Pubkey::new_unique();
+        let (funded_ata, _) = find_associated_token_address(&funded, &mint);
+        let recipients: Vec<(Pubkey, u64)> = (0..3)
+            .map(|_| (Pubkey::new_unique(), 10))
+            .chain([(funded, 10), (funded, 5)])
+            .collect();
+        let rpc = MockRpc::new()
+            .with_rent(&Rent::default())
+            .with_balance(payer.pubkey(), TOKEN_ACCOUNT_RENT)
+            .with_account(funded_ata, MockRpc::token_account(&mint, &funded, 0));
+        let client = TokenClient::from_client(rpc, payer);
+
+        assert_eq!(
+            client.estimate_creation_cost(3).await.unwrap(),
+            3 * TOKEN_ACCOUNT_RENT + DEFAULT_ESTIMATED_FEE_LAMPORTS
+        );
+
+        let plan = client.plan_airdrop(&mint, &recipients).await.unwrap();
+        assert_eq!(plan.recipients, 5);
+        assert_eq!(plan.atas_to_create.len(), 3);
+        assert!(!plan.atas_to_create.contains(&funded));
+        assert!(!plan.is_affordable());
+        assert!(plan.warnings[0].contains("creating 3 token accounts"));
+
+        client.client.set_account(
+            client.payer.pubkey(),
+            Account::new(plan.creation_cost, 0, &solana_sdk::system_program::id()),
+        );
+        let plan = client.plan_airdrop(&mint, &recipients).await.unwrap();
+        assert!(plan.is_affordable());
+        assert!(plan.warnings.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_assess_freeze_risk() {
+        use crate::testing::MockRpc;
+
+        let owner = Pubkey::new_unique();
+        let (safe_mint, freezable_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let rpc = MockRpc::new()
+            .with_account(safe_mint, MockRpc::mint_account(6, 1_000))
+            .with_account(freezable_mint, mint_with_freeze_authority(Pubkey::new_unique()))
+            .with_account(
+                Pubkey::new_unique(),
+                MockRpc::token_account(&freezable_mint, &owner, 10),
+            );
+
+        assert_eq!(assess_freeze_risk(&rpc, &safe_mint, true).await.unwrap(), FreezeRisk::Low);
+        assert_eq!(
+            assess_freeze_risk(&rpc, &freezable_mint, true).await.unwrap(),
+            FreezeRisk::Medium
+        );
+
+        let mut frozen = MockRpc::token_account(&freezable_mint, &owner, 10);
+        let mut state = TokenAccount::unpack(&frozen.data).unwrap();
+        state.state = AccountState::Frozen;
+        TokenAccount::pack(state, &mut frozen.data).unwrap();
+        rpc.set_account(Pubkey::new_unique(), frozen);
+
+        assert_eq!(
+            assess_freeze_risk(&rpc, &freezable_mint, true).await.unwrap(),
+            FreezeRisk::High
+        );
+        // Without sampling only the authority is considered
+        assert_eq!(
+            assess_freeze_risk(&rpc, &freezable_mint, false).await.unwrap(),
+            FreezeRisk::Medium
+        );
+        assert!(assess_freeze_risk(&rpc, &owner, false).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_assess_freeze_risk_token_2022() {
+        use crate::testing::MockRpc;
+
+        let owner = Pubkey::new_unique();
+        let (safe_mint, freezable_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let rpc = MockRpc::new()
+            .with_account(safe_mint, MockRpc::transfer_fee_mint_account(6, 100, u64::MAX))
+            .with_account(
+                freezable_mint,
+                token_2022_mint_with_freeze_authority(Pubkey::new_unique()),
+            );
+
+        assert_eq!(assess_freeze_risk(&rpc, &safe_mint, true).await.unwrap(), FreezeRisk::Low);
+        assert_eq!(
+            assess_freeze_risk(&rpc, &freezable_mint, true).await.unwrap(),
+            FreezeRisk::Medium
+        );
+
+        // Frozen holders are looked up under the Token-2022 program
+        let mut frozen = MockRpc::token_account(&freezable_mint, &owner, 10);
+        let mut state = TokenAccount::unpack(&frozen.data).unwrap();
+        state.state = AccountState::Frozen;
+        TokenAccount::pack(state, &mut frozen.data).unwrap();
+        frozen.owner = spl_token_2022::id();
+        rpc.set_account(Pubkey::new_unique(), frozen);
+
+        assert_eq!(
+            assess_freeze_risk(&rpc, &freezable_mint, true).await.unwrap(),
+            FreezeRisk::High
+        );
+    }
+
+    #[tokio::test]
+    async fn test_transfer_amount_after_fee() {
+        use crate::testing::MockRpc;
+
+        let plain_mint = MockRpc::mint_account(6, 0);
+        let fee_mint = MockRpc::transfer_fee_mint_account(6, 100, 5_000);
+        let rpc = MockRpc::new().with_clock(&Clock::default());
+
+        assert_eq!(transfer_amount_after_fee(&rpc, &plain_mint, 100_000).await.unwrap(), 100_000);
+        assert!(rpc.account_requests().is_empty());
+        // 1% of the amount is withheld, up to the maximum fee
+        assert_eq!(transfer_amount_after_fee(&rpc, &fee_mint, 100_000).await.unwrap(), 99_000);
+        assert_eq!(transfer_amount_after_fee(&rpc, &fee_mint, 1_000_000).await.unwrap(), 995_000);
+        // The fee depends on the epoch, so the clock is required
+        assert!(transfer_amount_after_fee(&MockRpc::new(), &fee_mint, 1).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_validation_rejects_system_account() {
+        use crate::testing::MockRpc;
+
+        let payer = Keypair::new();
+        let owner = payer.pubkey();
+        let mint = Pubkey::new_unique();
+        let not_a_token_account = Pubkey::new_unique();
+        let token_account = Pubkey::new_unique();
+        let rpc = MockRpc::new()
+            .with_balance(not_a_token_account, 1_000_000)
+            .with_account(mint, MockRpc::mint_account(6, 1_000))
+            .with_account(token_account, MockRpc::token_account(&mint, &owner, 10));
+
+        let client = TokenClient::from_client(rpc, payer).with_validation(true);
+
+        let err = client.close_account(&not_a_token_account).await.unwrap_err();
+        assert!(err.to_string().contains("is not a token account"));
+
+        let err = client
+            .transfer(&mint, &token_account, &not_a_token_account, 1)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("is not a token account"));
+
+        let err = client.burn(&not_a_token_account, &token_account, 1).await.unwrap_err();
+        assert!(err.to_string().contains("is not a mint"));
+
+        assert!(client.client.sent_transactions().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_validation_accepts_token_accounts() {
+        use crate::testing::MockRpc;
+
+        let payer = Keypair::new();
+        let owner = payer.pubkey();
+        let mint = Pubkey::new_unique();
+        let token_account = Pubkey::new_unique();
+        let rpc = MockRpc::new()
+            .with_account(mint, MockRpc::mint_account(6, 1_000))
+            .with_account(token_account, MockRpc::token_account(&mint, &owner, 10));
+
+        let client = TokenClient::from_client(rpc, payer).with_validation(true);
+        client.burn(&mint, &token_account, 10).await.unwrap();
+
+        assert_eq!(client.client.sent_transactions().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_close_account_rent_destination() {
+        use crate::testing::MockRpc;
+
+        let payer = Keypair::new();
+        let owner = payer.pubkey();
+        let mint = Pubkey::new_unique();
+        let (token_account, treasury) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let rpc = MockRpc::new()
+            .with_balance(treasury, 1_000_000)
+            .with_account(token_account, MockRpc::token_account(&mint, &owner, 0));
+
+        // Rent goes to the treasury; the payer still signs as the owner
+        let client = TokenClient::from_client(rpc, payer).with_rent_destination(treasury);
+        let ix = client.close_account_ix(&token_account).unwrap();
+        assert_eq!(ix.accounts[1].pubkey, treasury);
+        assert_eq!(ix.accounts[2].pubkey, owner);
+        assert!(ix.accounts[2].is_signer);
+
+        client.close_account(&token_account).await.unwrap();
+        let sent = client.client.sent_transactions();
+        assert_eq!(sent.len(), 1);
+        assert!(sent[0].message.account_keys.contains(&treasury));
+
+        // A token account can't receive the rent
+        let client = client.with_rent_destination(token_account);
+        let err = client.close_account(&token_account).await.unwrap_err();
+        assert!(err.to_string().contains("is not a system account"), "{}", err);
+        assert_eq!(client.client.sent_transactions().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_relayer_pays_fees_for_authority() {
+        use crate::testing::MockRpc;
+
+        let authority = Keypair::new();
+        let relayer = Keypair::new();
+        let owner = authority.pubkey();
+        let mint = Pubkey::new_unique();
+        let (source, destination, empty) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let rpc = MockRpc::new()
+            .with_account(source, MockRpc::token_account(&mint, &owner, 10))
+            .with_account(destination, MockRpc::token_account(&mint, &owner, 0))
+            .with_account(empty, MockRpc::token_account(&mint, &owner, 0));
+        let client = TokenClient::from_client(rpc, authority);
+
+        client
+            .transfer_with_fee_payer(&mint, &source, &destination, 5, &relayer)
+            .await
+            .unwrap();
+        client.close_account_with_fee_payer(&empty, &relayer).await.unwrap();
+
+        let sent = client.client.sent_transactions();
+        assert_eq!(sent.len(), 2);
+        for tx in &sent {
+            // The relayer pays the fee; the authority still signs the token instruction
+            assert_eq!(tx.message.account_keys[0], relayer.pubkey());
+            assert_eq!(tx.message.header.num_required_signatures, 2);
+            assert!(tx.message.is_signer(1));
+            assert_eq!(tx.message.account_keys[1], owner);
+            let ix = &tx.message.instructions[0];
+            assert!(ix.accounts.contains(&1));
+            tx.verify().unwrap();
+        }
+
+        // Rent from the close returns to the authority, not the relayer
+        let ix = client.close_account_ix(&empty).unwrap();
+        assert_eq!(ix.accounts[1].pubkey, owner);
+
+        // A fee payer equal to the authority signs once
+        let tx = client.build_transaction_with_fee_payer(&[ix], &client.payer, Hash::default());
+        assert_eq!(tx.signatures.len(), 1);
+    }
+
+    const ADDR_1: &str = "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU";
+    const ADDR_2: &str = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+
+    #[test]
+    fn test_parse_recipients_csv_valid() {
+        let csv = format!("address,amount\n{},1.5\n\n# comment\n{}, 42\n", ADDR_1, ADDR_2);
+
+        let recipients = parse_recipients_csv(csv.as_bytes(), 6).unwrap();
+
+        assert_eq!(
+            recipients,
+            vec![
+                (ADDR_1.parse().unwrap(), 1_500_000),
+                (ADDR_2.parse().unwrap(), 42_000_000),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_recipients_csv_bad_address() {
+        let csv = format!("{},1\nnot-an-address,2\n", ADDR_1);
+
+        let err = parse_recipients_csv(csv.as_bytes(), 6).unwrap_err();
+
+        assert!(err.to_string().contains("line 2"));
+        assert!(err.to_string().contains("not-an-address"));
+    }
+
+    #[test]
+    fn test_parse_recipients_csv_malformed_first_row() {
+        let csv = format!("not-an-address,1\n{},2\n", ADDR_1);
+        let err = parse_recipients_csv(csv.as_bytes(), 6).unwrap_err();
+        assert!(err.to_string().contains("line 1"));
+
+        let csv = format!("{},abc\n{},2\n", ADDR_1, ADDR_2);
+        let err = parse_recipients_csv(csv.as_bytes(), 6).unwrap_err();
+        assert!(err.to_string().contains("line 1"));
+        assert!(err.to_string().contains("invalid amount"));
+
+        let csv = format!("Wallet,Amount\n{},2\n", ADDR_1);
+        let recipients = parse_recipients_csv(csv.as_bytes(), 6).unwrap();
+        assert_eq!(recipients, vec![(ADDR_1.parse().unwrap(), 2_000_000)]);
+    }
+
+    #[test]
+    fn test_parse_recipients_csv_over_precise_amount() {
+        let csv = format!("{},1\n{},0.1234567\n", ADDR_1, ADDR_2);
+
+        let err = parse_recipients_csv(csv.as_bytes(), 6).unwrap_err();
+
+        assert!(err.to_string().contains("line 2"));
+        assert!(err.to_string().contains("more than 6 decimal places"));
+    }
+
+    #[test]
+    fn test_burn_and_close_transaction_offline() {
+        let payer = Keypair::new();
+        let payer_pubkey = payer.pubkey();
+        let client = TokenClient::new("http://127.0.0.1:8899", payer);
+
+        let mint = Pubkey::new_unique();
+        let token_account = Pubkey::new_unique();
+        let blockhash = Hash::new_unique();
+
+        let instructions = client.build_burn_and_close(&mint, &token_account, 42).unwrap();
+        let tx = client.build_transaction(&instructions, blockhash);
+
+        assert_eq!(tx.message.recent_blockhash, blockhash);
+        assert_eq!(tx.message.account_keys[0], payer_pubkey);
+        assert_eq!(instructions.len(), 2);
+        assert!(instructions.iter().all(|ix| ix.program_id == spl_token::id()));
+        assert!(tx.verify().is_ok());
+    }
+
+    fn token_update(account: &Pubkey, mint: &Pubkey, amount: u64) -> RpcKeyedAccount {
+        use solana_account_decoder::{UiAccount, UiAccountEncoding};
+
+        let data = crate::testing::MockRpc::token_account(mint, &Pubkey::new_unique(), amount);
+        RpcKeyedAccount {
+            pubkey: account.to_string(),
+            account: UiAccount::encode(account, &data, UiAccountEncoding::Base64, None, None),
+        }
+    }
+
+    #[test]
+    fn test_holdings_snapshot_diffs_updates() {
+        let mint = Pubkey::new_unique();
+        let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut snapshot = HoldingsSnapshot::new();
+
+        let change = snapshot.apply_update(&token_update(&first, &mint, 100)).unwrap();
+        assert_eq!(change, HoldingChange { mint, old_amount: 0, new_amount: 100 });
+
+        let change = snapshot.apply_update(&token_update(&first, &mint, 40)).unwrap();
+        assert_eq!((change.old_amount, change.new_amount), (100, 40));
+
+        // A second account for the same mint changes the wallet total
+        let change = snapshot.apply_update(&token_update(&second, &mint, 25)).unwrap();
+        assert_eq!((change.old_amount, change.new_amount), (40, 65));
+
+        // Unchanged state produces no change
+        assert!(snapshot.apply_update(&token_update(&second, &mint, 25)).is_none());
+        assert_eq!(snapshot.balance(&mint), 65);
+    }
+
+    #[test]
+    fn test_holdings_snapshot_closed_and_seeded_accounts() {
+        let mint = Pubkey::new_unique();
+        let other_mint = Pubkey::new_unique();
+        let account = Pubkey::new_unique();
+        let mut snapshot = HoldingsSnapshot::new();
+        snapshot.insert(account, mint, 500);
+
+        let change = snapshot.apply_update(&token_update(&account, &mint, 450)).unwrap();
+        assert_eq!((change.old_amount, change.new_amount), (500, 450));
+
+        // A closed account drops out of the total
+        let change = snapshot.apply(account, None).unwrap();
+        assert_eq!(change, HoldingChange { mint, old_amount: 450, new_amount: 0 });
+        assert!(snapshot.apply(Pubkey::new_unique(), None).is_none());
+
+        // Mints are tracked independently
+        let change = snapshot.apply_update(&token_update(&account, &other_mint, 7)).unwrap();
+        assert_eq!(change.mint, other_mint);
+        assert_eq!(snapshot.balance(&mint), 0);
+    }
+
+    #[test]
+    fn test_build_consolidation_three_accounts() {
+        let payer = Keypair::new();
+        let owner = payer.pubkey();
+        let client = TokenClient::new("http://127.0.0.1:8899", payer);
+        let mint = Pubkey::new_unique();
+        let (ata, _) = find_associated_token_address(&owner, &mint);
+        let sources = [
+            (Pubkey::new_unique(), 100),
+            (Pubkey::new_unique(), 0),
+            (Pubkey::new_unique(), 7),
+        ];
+
+        let ixs = client.build_consolidation(&mint, &owner, &sources, true).unwrap();
+
+        // create ATA, transfer+close, close (empty), transfer+close
+        assert_eq!(ixs.len(), 6);
+        assert_eq!(ixs[0].program_id, spl_associated_token_account::id());
+        let expected = [
+            (1, TokenInstruction::Transfer { amount: 100 }, sources[0].0),
+            (2, TokenInstruction::CloseAccount, sources[0].0),
+            (3, TokenInstruction::CloseAccount, sources[1].0),
+            (4, TokenInstruction::Transfer { amount: 7 }, sources[2].0),
+            (5, TokenInstruction::CloseAccount, sources[2].0),
+        ];
+        for (i, instruction, source) in expected {
+            assert_eq!(ixs[i].program_id, spl_token::id());
+            assert_eq!(TokenInstruction::unpack(&ixs[i].data).unwrap(), instruction);
+            assert_eq!(ixs[i].accounts[0].pubkey, source);
+        }
+        assert_eq!(ixs[1].accounts[1].pubkey, ata);
+        assert_eq!(ixs[4].accounts[1].pubkey, ata);
+        assert_eq!(ixs[2].accounts[1].pubkey, owner);
+    }
+
+    #[tokio::test]
+    async fn test_consolidate_mint_with_mock_rpc() {
+        use crate::testing::{MockRpc, TOKEN_ACCOUNT_RENT};
+
+        let payer = Keypair::new();
+        let owner = payer.pubkey();
+        let mint = Pubkey::new_unique();
+        let (ata, _) = find_associated_token_address(&owner, &mint);
+        let (extra, other_mint_account) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let rpc = MockRpc::new()
+            .with_account(ata, MockRpc::token_account(&mint, &owner, 10))
+            .with_account(extra, MockRpc::token_account(&mint, &owner, 5))
+            .with_account(
+                other_mint_account,
+                MockRpc::token_account(&Pubkey::new_unique(), &owner, 1),
+            );
+        let client = TokenClient::from_client(rpc, payer);
+
+        let result = client.consolidate_mint(&mint, &owner).await.unwrap();
+
+        assert_eq!(result.ata, ata);
+        assert!(!result.ata_created);
+        assert_eq!(result.accounts_closed, vec![extra]);
+        assert_eq!(result.tokens_moved, 5);
+        assert_eq!(result.lamports_recovered, TOKEN_ACCOUNT_RENT);
+        let sent = client.client.sent_transactions();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].message.instructions.len(), 2);
+
+        let err = client.consolidate_mint(&mint, &Pubkey::new_unique()).await.unwrap_err();
+        assert!(matches!(err, ToolkitError::ConfigError(_)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_airdrop_rerun_pays_only_remaining_recipients() {
+        use crate::testing::MockRpc;
+
+        let path = std::env::temp_dir()
+            .join(format!("pipkit-airdrop-{}.json", Pubkey::new_unique()));
+        let payer = Keypair::new();
+        let mint = Pubkey::new_unique();
+        let recipients: Vec<(Pubkey, u64)> =
+            (1..=5).map(|amount| (Pubkey::new_unique(), amount)).collect();
+        let (existing_ata, _) = find_associated_token_address(&recipients[0].0, &mint);
+        let rpc = || {
+            MockRpc::new()
+                .with_account(existing_ata, MockRpc::token_account(&mint, &recipients[0].0, 0))
+        };
+        let runner = |rpc| {
+            let tokens = TokenClient::from_client(rpc, payer.insecure_clone());
+            AirdropRunner::new(tokens, mint, &path)
+                .with_max_transfers_per_tx(2)
+                .with_create_missing(true)
+        };
+
+        // First run: the second batch is rejected and expires without landing
+        let first = runner(rpc());
+        let (failing_ata, _) = find_associated_token_address(&recipients[2].0, &mint);
+        first.tokens.client.fail_sends_touching(failing_ata, "blockhash expired");
+        let receipt = first.run(&recipients).await.unwrap();
+        assert_eq!(first.tokens.client.sent_transactions().len(), 2);
+        assert_eq!(receipt.paid.len(), 3);
+        assert_eq!(receipt.failed.len(), 2);
+        assert!(!receipt.is_paid(&recipients[2].0) && !receipt.is_paid(&recipients[3].0));
+        // The existing ATA is not created again
+        let sent = first.tokens.client.sent_transactions();
+        assert_eq!(sent[0].message.instructions.len(), 3);
+
+        let on_disk = AirdropReceipt::load_or_new(&path, &mint).unwrap();
+        assert_eq!(on_disk, receipt);
+
+        // Second run: only the two failed recipients are paid
+        let second = runner(rpc());
+        let receipt = second.run(&recipients).await.unwrap();
+        let sent = second.tokens.client.sent_transactions();
+        assert_eq!(sent.len(), 1);
+        for (wallet, _) in [recipients[0], recipients[1], recipients[4]] {
+            let (ata, _) = find_associated_token_address(&wallet, &mint);
+            assert!(!sent[0].message.account_keys.contains(&ata));
+            assert_eq!(receipt.paid[&wallet.to_string()], on_disk.paid[&wallet.to_string()]);
+        }
+        assert_eq!(receipt.paid.len(), 5);
+        assert!(receipt.failed.is_empty());
+        assert_eq!(AirdropReceipt::load_or_new(&path, &mint).unwrap(), receipt);
+
+        // A third run has nothing left to pay
+        let third = runner(rpc());
+        third.run(&recipients).await.unwrap();
+        assert!(third.tokens.client.sent_transactions().is_empty());
+
+        assert!(AirdropReceipt::load_or_new(&path, &Pubkey::new_unique()).is_err());
+        let duplicated = [recipients[0], recipients[0]];
+        assert!(third.run(&duplicated).await.is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_airdrop_resumes_pending_transfers() {
+        use crate::testing::MockRpc;
+        use solana_transaction_status::TransactionConfirmationStatus;
+
+        let path = std::env::temp_dir()
+            .join(format!("pipkit-airdrop-{}.json", Pubkey::new_unique()));
+        let mint = Pubkey::new_unique();
+        let recipients: Vec<(Pubkey, u64)> =
+            (1..=3).map(|amount| (Pubkey::new_unique(), amount)).collect();
+        let (landed, expired, in_flight) =
+            (Signature::new_unique(), Signature::new_unique(), Signature::new_unique());
+
+        // An interrupted run sent three transfers without seeing them confirm
+        let mut receipt = AirdropReceipt::new(&mint);
+        receipt.record_pending(&[recipients[0].0], &landed, 1_000);
+        receipt.record_pending(&[recipients[1].0], &expired, 5);
+        receipt.record_pending(&[recipients[2].0], &in_flight, 1_000);
+        receipt.save(&path).unwrap();
+
+        let rpc = MockRpc::new();
+        rpc.set_block_height(10);
+        rpc.set_signature_status(
+            landed,
+            TransactionStatus {
+                slot: 9,
+                confirmations: Some(1),
+                status: Ok(()),
+                err: None,
+                confirmation_status: Some(TransactionConfirmationStatus::Confirmed),
+            },
+        );
+        let tokens = TokenClient::from_client(rpc, Keypair::new());
+        let runner = AirdropRunner::new(tokens, mint, &path).with_create_missing(true);
+        let receipt = runner.run(&recipients).await.unwrap();
+
+        // Only the expired transfer is sent again
+        let sent = runner.tokens.client.sent_transactions();
+        assert_eq!(sent.len(), 1);
+        let (expired_ata, _) = find_associated_token_address(&recipients[1].0, &mint);
+        assert!(sent[0].message.account_keys.contains(&expired_ata));
+
+        assert_eq!(receipt.paid[&recipients[0].0.to_string()], landed.to_string());
+        assert_eq!(receipt.paid[&recipients[1].0.to_string()], sent[0].signatures[0].to_string());
+        assert!(receipt.is_pending(&recipients[2].0) && !receipt.is_paid(&recipients[2].0));
+        assert_eq!(receipt.pending.len(), 1);
+        assert_eq!(AirdropReceipt::load_or_new(&path, &mint).unwrap(), receipt);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_airdrop_stop_signal_returns_partial_receipt() {
+        use crate::testing::MockRpc;
+
+        let path = std::env::temp_dir()
+            .join(format!("pipkit-airdrop-{}.json", Pubkey::new_unique()));
+        let mint = Pubkey::new_unique();
+        let recipients: Vec<(Pubkey, u64)> =
+            (1..=3).map(|amount| (Pubkey::new_unique(), amount)).collect();
+        let tokens = TokenClient::from_client(MockRpc::new(), Keypair::new());
+        let (stop_tx, stop_rx) = watch::channel(false);
+        let runner = AirdropRunner::new(tokens, mint, &path)
+            .with_max_transfers_per_tx(1)
+            .with_create_missing(true)
+            .with_batch_delay(Duration::from_secs(30))
+            .with_stop_signal(stop_rx);
+
+        // Stop during the pause after the first transaction
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            stop_tx.send(true).unwrap();
+        });
+        let started = std::time::Instant::now();
+        let receipt = runner.run(&recipients).await.unwrap();
+
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(receipt.cancelled);
+        assert_eq!(runner.tokens.client.sent_transactions().len(), 1);
+        assert_eq!(receipt.paid.len(), 1);
+        assert!(receipt.is_paid(&recipients[0].0));
+        assert!(receipt.failed.is_empty());
+        let on_disk = AirdropReceipt::load_or_new(&path, &mint).unwrap();
+        assert_eq!(on_disk.paid, receipt.paid);
+        assert!(!on_disk.cancelled);
+
+        // Already stopped: nothing is sent
+        let receipt = runner.run(&recipients).await.unwrap();
+        assert!(receipt.cancelled);
+        assert_eq!(runner.tokens.client.sent_transactions().len(), 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_airdrop_skips_recipients_without_ata() {
+        use crate::testing::MockRpc;
+
+        let path = std::env::temp_dir()
+            .join(format!("pipkit-airdrop-{}.json", Pubkey::new_unique()));
+        let mint = Pubkey::new_unique();
+        let recipients: Vec<(Pubkey, u64)> =
+            (1..=3).map(|amount| (Pubkey::new_unique(), amount)).collect();
+        let (existing_ata, _) = find_associated_token_address(&recipients[1].0, &mint);
+        let rpc = MockRpc::new()
+            .with_account(existing_ata, MockRpc::token_account(&mint, &recipients[1].0, 0));
+        let tokens = TokenClient::from_client(rpc, Keypair::new());
+        let runner = AirdropRunner::new(tokens, mint, &path).with_create_missing(false);
+
+        let receipt = runner.run(&recipients).await.unwrap();
+        assert_eq!(receipt.skipped, vec![recipients[0].0, recipients[2].0]);
+        assert_eq!(receipt.paid.len(), 1);
+        assert!(receipt.is_paid(&recipients[1].0));
+        assert!(receipt.failed.is_empty());
+
+        // Only the existing ATA is paid and nothing is created
+        let sent = runner.tokens.client.sent_transactions();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].message.instructions.len(), 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_decode_token_account_matches_across_encodings() {
+        use solana_account_decoder::UiAccountEncoding;
+        use solana_sdk::account::AccountSharedData;
+
+        let (address, mint, owner) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mut data = vec![0; TokenAccount::LEN];
+        let token = TokenAccount {
+            mint,
+            owner,
+            amount: 42_000,
+            state: AccountState::Frozen,
+            ..Default::default()
+        };
+        TokenAccount::pack(token, &mut data).unwrap();
+        let account = Account {
+            lamports: 2_039_280,
+            data,
+            owner: spl_token::id(),
+            executable: false,
+            rent_epoch: 0,
+        };
+        let expected = ParsedTokenAccount {
+            mint,
+            owner,
+            amount: 42_000,
+            state: AccountState::Frozen,
+        };
+        assert_eq!(decode_token_account(&account), Some(expected));
+
+        let encode = |account: &Account, encoding| {
+            let shared = AccountSharedData::from(account.clone());
+            UiAccount::encode(&address, &shared, encoding, None, None)
+        };
+        let base64 = encode(&account, UiAccountEncoding::Base64);
+        assert_eq!(decode_token_account(&base64), Some(expected));
+
+        let json: UiAccount = serde_json::from_value(serde_json::json!({
+            "lamports": 2_039_280,
+            "owner": spl_token::id().to_string(),
+            "executable": false,
+            "rentEpoch": 0,
+            "space": 165,
+            "data": {
+                "program": "spl-token",
+                "space": 165,
+                "parsed": {
+                    "type": "account",
+                    "info": {
+                        "mint": mint.to_string(),
+                        "owner": owner.to_string(),
+                        "state": "frozen",
+                        "isNative": false,
+                        "tokenAmount": {
+                            "amount": "42000",
+                            "decimals": 6,
+                            "uiAmount": 0.042,
+                            "uiAmountString": "0.042"
+                        }
+                    }
+                }
+            }
+        }))
+        .unwrap();
+        assert_eq!(decode_token_account(&json), Some(expected));
+
+        // Sliced scans only return the leading fields; state is assumed
+        let sliced = Account {
+            data: account.data[..TOKEN_ACCOUNT_SLICE_LEN].to_vec(),
+            ..account.clone()
+        };
+        let sliced_expected = ParsedTokenAccount { state: AccountState::Initialized, ..expected };
+        assert_eq!(decode_token_account(&sliced), Some(sliced_expected));
+        // The RPC only base58-encodes up to 128 bytes, so base58 scans are sliced
+        let base58 = encode(&sliced, UiAccountEncoding::Base58);
+        assert_eq!(decode_token_account(&base58), Some(sliced_expected));
+
+        let mint_account = Account { data: vec![0; Mint::LEN], ..account };
+        assert_eq!(decode_token_account(&mint_account), None);
+    }
 }
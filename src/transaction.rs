@@ -3,19 +3,39 @@
 //! This module provides utilities for building, batching, and executing
 //! Solana transactions efficiently with automatic size management.
 
-use solana_client::nonblocking::rpc_client::RpcClient;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use futures::StreamExt;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use solana_client::{
+    nonblocking::{pubsub_client::PubsubClient, rpc_client::RpcClient},
+    rpc_config::{RpcSendTransactionConfig, RpcSignatureSubscribeConfig},
+    rpc_response::{Response, RpcSignatureResult},
+};
 use solana_sdk::{
+    account::Account,
+    clock::MAX_PROCESSING_AGE,
     commitment_config::CommitmentConfig,
     compute_budget::ComputeBudgetInstruction,
     hash::Hash,
-    instruction::Instruction,
+    instruction::{AccountMeta, Instruction},
     message::Message,
     pubkey::Pubkey,
     signature::{Keypair, Signature, Signer},
-    transaction::Transaction,
+    system_instruction::{self, SystemInstruction},
+    transaction::{Transaction, TransactionError, VersionedTransaction},
 };
+use solana_transaction_status::TransactionConfirmationStatus;
+use spl_token::instruction::TokenInstruction;
 
-use crate::{Result, ToolkitError};
+use crate::safety::{AmountValidator, Lamports, SafetyProtocol, SafetyReport, TokenKind};
+use crate::{
+    rpc::{Network, SolanaRpc, TimeoutRpc, DEFAULT_RPC_TIMEOUT},
+    Result, ToolkitError,
+};
 
 /// Maximum transaction size in bytes (1232 bytes for legacy transactions).
 pub const MAX_TRANSACTION_SIZE: usize = 1232;
@@ -26,6 +46,219 @@ pub const MAX_ACCOUNTS_PER_TX: usize = 64;
 /// Default compute units per transaction.
 pub const DEFAULT_COMPUTE_UNITS: u32 = 200_000;
 
+/// Maximum compute units a transaction may request.
+pub const MAX_COMPUTE_UNITS: u32 = 1_400_000;
+
+/// Default headroom added to observed compute usage, in basis points.
+pub const DEFAULT_COMPUTE_UNIT_MARGIN_BPS: u16 = 1_000;
+
+/// How long websocket confirmation waits before giving up.
+pub const DEFAULT_WS_CONFIRM_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How often [`BatchExecutor::send_persistent`] resubmits a transaction.
+pub const DEFAULT_RESEND_INTERVAL: Duration = Duration::from_millis(400);
+
+/// How often [`confirm_until_block_height`] polls by default.
+pub const DEFAULT_CONFIRM_POLL_INTERVAL: Duration = Duration::from_millis(400);
+
+/// How long [`send_and_confirm`] waits for each send to confirm by default.
+pub const DEFAULT_CONFIRM_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Delay before the first resend in [`send_and_confirm`] by default.
+pub const DEFAULT_SEND_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Named priority fee levels, each a percentile of recent fees.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FeeTier {
+    /// 25th percentile; lands when the cluster is quiet.
+    Slow,
+    /// 50th percentile.
+    Normal,
+    /// 75th percentile.
+    Fast,
+    /// 95th percentile, for time-critical transactions.
+    Urgent,
+}
+
+impl FeeTier {
+    /// All tiers, cheapest first.
+    pub const ALL: [FeeTier; 4] = [FeeTier::Slow, FeeTier::Normal, FeeTier::Fast, FeeTier::Urgent];
+
+    /// Percentile of recent fees this tier pays.
+    pub fn percentile(self) -> u8 {
+        match self {
+            FeeTier::Slow => 25,
+            FeeTier::Normal => 50,
+            FeeTier::Fast => 75,
+            FeeTier::Urgent => 95,
+        }
+    }
+}
+
+/// Priority fees for each [`FeeTier`], in micro-lamports per compute unit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeTiers {
+    /// 25th percentile fee.
+    pub slow: u64,
+    /// 50th percentile fee.
+    pub normal: u64,
+    /// 75th percentile fee.
+    pub fast: u64,
+    /// 95th percentile fee.
+    pub urgent: u64,
+}
+
+impl FeeTiers {
+    /// Compute tiers from a sample of recent fees, using the nearest-rank
+    /// percentile. An empty sample gives zero for every tier.
+    pub fn from_fees(fees: &[u64]) -> Self {
+        let mut sorted = fees.to_vec();
+        sorted.sort_unstable();
+        let percentile = |tier: FeeTier| {
+            let rank = (sorted.len() * tier.percentile() as usize).div_ceil(100);
+            sorted.get(rank.saturating_sub(1)).copied().unwrap_or(0)
+        };
+        Self {
+            slow: percentile(FeeTier::Slow),
+            normal: percentile(FeeTier::Normal),
+            fast: percentile(FeeTier::Fast),
+            urgent: percentile(FeeTier::Urgent),
+        }
+    }
+
+    /// The fee for `tier`.
+    pub fn get(&self, tier: FeeTier) -> u64 {
+        match tier {
+            FeeTier::Slow => self.slow,
+            FeeTier::Normal => self.normal,
+            FeeTier::Fast => self.fast,
+            FeeTier::Urgent => self.urgent,
+        }
+    }
+}
+
+/// Fetch recent priority fees paid to lock `writable_accounts` and split
+/// them into [`FeeTiers`].
+pub async fn fee_tiers<R: SolanaRpc + ?Sized>(
+    rpc: &R,
+    writable_accounts: &[Pubkey],
+) -> Result<FeeTiers> {
+    let fees = rpc.get_recent_prioritization_fees(writable_accounts).await?;
+    let fees: Vec<u64> = fees.iter().map(|fee| fee.prioritization_fee).collect();
+    Ok(FeeTiers::from_fees(&fees))
+}
+
+/// The priority fee to send `instructions` with: the configured
+/// [`FeeTier`] resolved against the accounts they write, or the fixed fee.
+async fn resolve_priority_fee<R: SolanaRpc + ?Sized>(
+    rpc: &R,
+    config: &TransactionConfig,
+    payer: &Pubkey,
+    instructions: &[Instruction],
+) -> Result<Option<u64>> {
+    let Some(tier) = config.fee_tier else {
+        return Ok(config.priority_fee_micro_lamports);
+    };
+    let mut writable = vec![*payer];
+    for meta in instructions.iter().flat_map(|ix| &ix.accounts) {
+        if meta.is_writable && !writable.contains(&meta.pubkey) {
+            writable.push(meta.pubkey);
+        }
+    }
+    let fee = fee_tiers(rpc, &writable).await?.get(tier);
+    debug!(?tier, fee, "resolved priority fee tier");
+    Ok(Some(fee))
+}
+
+/// How sent transactions are confirmed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ConfirmStrategy {
+    /// Poll the RPC node until the transaction is confirmed.
+    #[default]
+    Rpc,
+    /// Subscribe to `signatureSubscribe` and resolve on the first notification.
+    Websocket {
+        /// Websocket endpoint, e.g. `ws://localhost:8900`.
+        ws_url: String,
+    },
+}
+
+/// Outcome of a websocket confirmation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WsConfirmation {
+    /// Slot in which the commitment was reached.
+    pub slot: u64,
+    /// Execution error, if the transaction failed.
+    pub err: Option<TransactionError>,
+}
+
+/// Interpret a `signatureSubscribe` notification.
+///
+/// Returns `None` for "received" notifications, which do not mean the
+/// transaction has reached the requested commitment.
+pub fn signature_notification_result(
+    notification: &Response<RpcSignatureResult>,
+) -> Option<WsConfirmation> {
+    match &notification.value {
+        RpcSignatureResult::ProcessedSignature(result) => Some(WsConfirmation {
+            slot: notification.context.slot,
+            err: result.err.clone(),
+        }),
+        RpcSignatureResult::ReceivedSignature(_) => None,
+    }
+}
+
+/// Subscribe to `signature`, run `before_wait`, then wait for confirmation.
+///
+/// Subscribing before sending means a fast confirmation cannot be missed.
+async fn confirm_signature_via_ws<F>(
+    ws_url: &str,
+    signature: &Signature,
+    commitment: CommitmentConfig,
+    timeout: Duration,
+    before_wait: F,
+) -> Result<WsConfirmation>
+where
+    F: Future<Output = Result<()>>,
+{
+    let pubsub = PubsubClient::new(ws_url)
+        .await
+        .map_err(|e| ToolkitError::NetworkError(format!("Websocket connect failed: {}", e)))?;
+    let config = RpcSignatureSubscribeConfig {
+        commitment: Some(commitment),
+        enable_received_notification: Some(false),
+    };
+    let (mut notifications, unsubscribe) = pubsub
+        .signature_subscribe(signature, Some(config))
+        .await
+        .map_err(|e| ToolkitError::NetworkError(format!("signatureSubscribe failed: {}", e)))?;
+
+    let outcome = match before_wait.await {
+        Ok(()) => tokio::time::timeout(timeout, async {
+            while let Some(notification) = notifications.next().await {
+                if let Some(confirmation) = signature_notification_result(&notification) {
+                    return Ok(confirmation);
+                }
+            }
+            Err(ToolkitError::NetworkError(
+                "Signature subscription closed before confirmation".to_string(),
+            ))
+        })
+        .await
+        .unwrap_or_else(|_| {
+            Err(ToolkitError::Timeout(format!(
+                "Signature {} not confirmed within {:?}",
+                signature, timeout
+            )))
+        }),
+        Err(e) => Err(e),
+    };
+
+    drop(notifications);
+    unsubscribe().await;
+    outcome
+}
+
 /// Configuration for transaction execution.
 #[derive(Debug, Clone)]
 pub struct TransactionConfig {
@@ -33,6 +266,9 @@ pub struct TransactionConfig {
     pub compute_units: Option<u32>,
     /// Priority fee in micro-lamports per compute unit.
     pub priority_fee_micro_lamports: Option<u64>,
+    /// Fee tier resolved from recent fees at send time. Overrides
+    /// `priority_fee_micro_lamports` when set.
+    pub fee_tier: Option<FeeTier>,
     /// Whether to simulate before sending.
     pub simulate_before_send: bool,
     /// Whether to skip preflight checks.
@@ -41,6 +277,16 @@ pub struct TransactionConfig {
     pub max_retries: u8,
     /// Commitment level for confirmation.
     pub commitment: CommitmentConfig,
+    /// How sent transactions are confirmed.
+    pub confirm_strategy: ConfirmStrategy,
+    /// Fetch each confirmed transaction's compute usage for
+    /// [`BatchExecutor::recommended_compute_units`]. Costs one extra RPC
+    /// call per transaction.
+    pub track_compute_units: bool,
+    /// Headroom added over the highest observed usage, in basis points.
+    pub compute_unit_margin_bps: u16,
+    /// Delay between resubmissions in [`BatchExecutor::send_persistent`].
+    pub resend_interval: Duration,
 }
 
 impl Default for TransactionConfig {
@@ -48,10 +294,15 @@ impl Default for TransactionConfig {
         Self {
             compute_units: None,
             priority_fee_micro_lamports: None,
+            fee_tier: None,
             simulate_before_send: true,
             skip_preflight: false,
             max_retries: 3,
             commitment: CommitmentConfig::confirmed(),
+            confirm_strategy: ConfirmStrategy::Rpc,
+            track_compute_units: false,
+            compute_unit_margin_bps: DEFAULT_COMPUTE_UNIT_MARGIN_BPS,
+            resend_interval: DEFAULT_RESEND_INTERVAL,
         }
     }
 }
@@ -89,6 +340,434 @@ impl TransactionConfig {
         self.priority_fee_micro_lamports = Some(micro_lamports);
         self
     }
+
+    /// Pay the `tier` percentile of recent priority fees, looked up for the
+    /// transaction's writable accounts each time it is sent. See
+    /// [`BatchExecutor::fee_tiers`].
+    pub fn with_fee_tier(mut self, tier: FeeTier) -> Self {
+        self.fee_tier = Some(tier);
+        self
+    }
+
+    /// Set the priority fee from a total budget in lamports.
+    ///
+    /// The budget is spread over [`compute_units`](Self::compute_units), or
+    /// [`DEFAULT_COMPUTE_UNITS`] if unset, so set the compute units first.
+    /// See [`priority_fee_for_budget`].
+    pub fn with_priority_budget(self, target_lamports: u64) -> Self {
+        let units = self.compute_units.unwrap_or(DEFAULT_COMPUTE_UNITS);
+        self.with_priority_fee(priority_fee_for_budget(target_lamports, units))
+    }
+
+    /// Set the delay between resubmissions in
+    /// [`BatchExecutor::send_persistent`].
+    pub fn with_resend_interval(mut self, interval: Duration) -> Self {
+        self.resend_interval = interval;
+        self
+    }
+
+    /// Set the confirmation strategy.
+    pub fn with_confirm_strategy(mut self, strategy: ConfirmStrategy) -> Self {
+        self.confirm_strategy = strategy;
+        self
+    }
+
+    /// Track compute usage of confirmed transactions.
+    pub fn with_compute_unit_tracking(mut self, track: bool) -> Self {
+        self.track_compute_units = track;
+        self
+    }
+
+    /// Set the headroom added to observed compute usage, in basis points.
+    pub fn with_compute_unit_margin_bps(mut self, margin_bps: u16) -> Self {
+        self.compute_unit_margin_bps = margin_bps;
+        self
+    }
+
+    /// The [`SendPolicy`] for sending with this config.
+    pub fn send_policy(&self) -> SendPolicy {
+        SendPolicy {
+            commitment: self.commitment,
+            skip_preflight: self.skip_preflight,
+            max_retries: self.max_retries,
+            confirm_strategy: self.confirm_strategy.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Checks applied to every transaction before a helper sends it.
+///
+/// Shared by [`BatchExecutor`], [`TokenClient`](crate::token_utils::TokenClient)
+/// and [`AdvancedRentCleaner`](crate::rent_cleaner::AdvancedRentCleaner), so
+/// one policy can be enforced uniformly across a workflow. The default
+/// policy enforces nothing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecutionPolicy {
+    /// Refuse to send anything that does not simulate cleanly.
+    pub simulate_required: bool,
+    /// Refuse to send transactions that simulate moving more than this many
+    /// lamports out of their signers.
+    pub max_lamports_out: Option<u64>,
+    /// Build, sign and check transactions but never send them.
+    pub dry_run: bool,
+}
+
+impl ExecutionPolicy {
+    /// Policy that requires a clean simulation before every send.
+    pub fn strict() -> Self {
+        Self {
+            simulate_required: true,
+            ..Default::default()
+        }
+    }
+
+    /// Require a clean simulation before every send.
+    pub fn with_simulate_required(mut self, simulate_required: bool) -> Self {
+        self.simulate_required = simulate_required;
+        self
+    }
+
+    /// Cap the lamports a transaction may move out of its signers.
+    pub fn with_max_lamports_out(mut self, lamports: u64) -> Self {
+        self.max_lamports_out = Some(lamports);
+        self
+    }
+
+    /// Never send; helpers report what they would have done.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Whether [`enforce`](Self::enforce) simulates transactions.
+    pub fn simulates(&self) -> bool {
+        self.simulate_required || self.max_lamports_out.is_some()
+    }
+
+    /// Check `transaction` against the policy without sending it.
+    ///
+    /// Fails with [`ToolkitError::TransactionError`] if the simulation fails
+    /// or the signers' combined balance would drop by more than
+    /// `max_lamports_out`.
+    pub async fn enforce<R: SolanaRpc + ?Sized>(
+        &self,
+        client: &R,
+        transaction: &Transaction,
+    ) -> Result<()> {
+        if !self.simulates() {
+            return Ok(());
+        }
+
+        let Some(max_out) = self.max_lamports_out else {
+            let simulation = client.simulate_transaction(transaction).await?;
+            return match simulation.err {
+                Some(err) => Err(simulation_error(transaction, &err)),
+                None => Ok(()),
+            };
+        };
+
+        let signers: Vec<Pubkey> = transaction.message.signer_keys().into_iter().copied().collect();
+        let pre = client.get_multiple_accounts(&signers).await?;
+        let simulation = client
+            .simulate_transaction_with_accounts(transaction, &signers)
+            .await?;
+        if let Some(err) = simulation.err {
+            return Err(simulation_error(transaction, &err));
+        }
+        let post = simulation.accounts.ok_or_else(|| {
+            ToolkitError::TransactionError("Simulation returned no account states".to_string())
+        })?;
+
+        let pre_total: u64 = pre.iter().flatten().map(|a| a.lamports).sum();
+        let post_total: u64 = post
+            .iter()
+            .flatten()
+            .filter_map(|a| a.decode::<Account>())
+            .map(|a| a.lamports)
+            .sum();
+        let lamports_out = pre_total.saturating_sub(post_total);
+        if lamports_out > max_out {
+            return Err(ToolkitError::TransactionError(format!(
+                "Transaction would move {} lamports out of its signers, policy allows {}",
+                lamports_out, max_out
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// How a signed transaction is sent and confirmed.
+///
+/// Every helper that sends transactions goes through [`send_and_confirm`]
+/// with one of these, so sending behaves the same everywhere.
+/// [`BatchExecutor`] and [`ParallelBatchExecutor`] derive theirs from
+/// [`TransactionConfig::send_policy`]; the token and cleanup clients take one
+/// with `with_send_policy`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SendPolicy {
+    /// Commitment the transaction must reach, also used for preflight.
+    pub commitment: CommitmentConfig,
+    /// Skip the node's preflight simulation.
+    pub skip_preflight: bool,
+    /// Resends after a failed or unconfirmed attempt. The same signed
+    /// transaction is resent, so it can never land twice.
+    pub max_retries: u8,
+    /// Delay before the first resend, doubling for each one after.
+    pub backoff: Duration,
+    /// How each send is confirmed.
+    pub confirm_strategy: ConfirmStrategy,
+    /// How long each send may take to confirm before it is resent. Once no
+    /// resends are left, [`ConfirmStrategy::Rpc`] keeps waiting until the
+    /// blockhash expires instead.
+    pub timeout: Duration,
+}
+
+impl Default for SendPolicy {
+    fn default() -> Self {
+        Self {
+            commitment: CommitmentConfig::confirmed(),
+            skip_preflight: false,
+            max_retries: 0,
+            backoff: DEFAULT_SEND_BACKOFF,
+            confirm_strategy: ConfirmStrategy::Rpc,
+            timeout: DEFAULT_CONFIRM_TIMEOUT,
+        }
+    }
+}
+
+impl SendPolicy {
+    /// Set the commitment to confirm at.
+    pub fn with_commitment(mut self, commitment: CommitmentConfig) -> Self {
+        self.commitment = commitment;
+        self
+    }
+
+    /// Skip the node's preflight simulation.
+    pub fn with_skip_preflight(mut self, skip_preflight: bool) -> Self {
+        self.skip_preflight = skip_preflight;
+        self
+    }
+
+    /// Set the number of resends.
+    pub fn with_max_retries(mut self, max_retries: u8) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Set the delay before the first resend.
+    pub fn with_backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Set the confirmation strategy.
+    pub fn with_confirm_strategy(mut self, strategy: ConfirmStrategy) -> Self {
+        self.confirm_strategy = strategy;
+        self
+    }
+
+    /// Set how long each send may take to confirm.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// `sendTransaction` config with the policy's preflight settings.
+    pub fn send_config(&self) -> RpcSendTransactionConfig {
+        RpcSendTransactionConfig {
+            skip_preflight: self.skip_preflight,
+            preflight_commitment: Some(self.commitment.commitment),
+            ..Default::default()
+        }
+    }
+
+    /// Delay before the given resend (0-based).
+    fn backoff_for(&self, retry: u8) -> Duration {
+        self.backoff.saturating_mul(2u32.saturating_pow(retry.into()))
+    }
+}
+
+/// Send `transaction` and wait until it reaches the policy's commitment.
+///
+/// `signers` add any signatures the transaction is still missing; pass
+/// `&[]` for a fully signed transaction. Each attempt sends with the
+/// policy's preflight settings and is confirmed per its strategy within
+/// `timeout`. Failed or unconfirmed attempts are resent up to
+/// `max_retries` times, except when the payer cannot cover the transaction
+/// or it landed with an error, which fail at once. Returns the signature.
+///
+/// With [`ConfirmStrategy::Rpc`] the last attempt is confirmed with
+/// [`confirm_until_block_height`] rather than within `timeout`, taking the
+/// blockhash to stay valid for [`MAX_PROCESSING_AGE`] blocks from the first
+/// send. A transaction still unseen after that fails with
+/// [`ToolkitError::TransactionExpired`] and is not resent.
+pub async fn send_and_confirm<R: SolanaRpc + ?Sized>(
+    client: &R,
+    transaction: &Transaction,
+    signers: &[&Keypair],
+    policy: &SendPolicy,
+) -> Result<Signature> {
+    let message = &transaction.message;
+    let budget_only = message
+        .instructions
+        .iter()
+        .all(|ix| solana_sdk::compute_budget::check_id(ix.program_id(&message.account_keys)));
+    if budget_only {
+        return Err(no_instructions());
+    }
+
+    let mut transaction = transaction.clone();
+    if !signers.is_empty() {
+        let recent_blockhash = transaction.message.recent_blockhash;
+        transaction
+            .try_partial_sign(signers, recent_blockhash)
+            .map_err(|e| ToolkitError::SigningError(e.to_string()))?;
+    }
+    if !transaction.is_signed() {
+        return Err(ToolkitError::SigningError(
+            "Transaction is missing signatures".to_string(),
+        ));
+    }
+    let signature = transaction.signatures[0];
+    let last_valid_block_height = match policy.confirm_strategy {
+        ConfirmStrategy::Rpc => client.get_block_height().await? + MAX_PROCESSING_AGE as u64,
+        ConfirmStrategy::Websocket { .. } => 0,
+    };
+
+    let mut attempt = 0;
+    loop {
+        let last_attempt = attempt == policy.max_retries;
+        let sent = send_once(client, &transaction, policy, last_valid_block_height, last_attempt);
+        let error = match sent.await {
+            Ok(()) => return Ok(signature),
+            Err(SendFailure::Final(e)) => return Err(e),
+            Err(SendFailure::Retryable(e)) => e,
+        };
+        if policy.max_retries == 0 {
+            return Err(error);
+        }
+        if attempt == policy.max_retries {
+            return Err(ToolkitError::TransactionError(format!(
+                "Failed after {} retries: {}",
+                policy.max_retries, error
+            )));
+        }
+        warn!(attempt, error = %error, "transaction send failed");
+        tokio::time::sleep(policy.backoff_for(attempt)).await;
+        attempt += 1;
+    }
+}
+
+/// Why one attempt of [`send_and_confirm`] failed.
+enum SendFailure {
+    /// Resending could still succeed.
+    Retryable(ToolkitError),
+    /// Resending cannot help.
+    Final(ToolkitError),
+}
+
+impl From<ToolkitError> for SendFailure {
+    fn from(error: ToolkitError) -> Self {
+        if is_insufficient_funds(&error) {
+            SendFailure::Final(error)
+        } else {
+            SendFailure::Retryable(error)
+        }
+    }
+}
+
+/// Send `transaction` once and wait for it per `policy`.
+///
+/// The [`ConfirmStrategy::Rpc`] wait is cut short after the policy's
+/// `timeout` unless this is the `last_attempt`.
+async fn send_once<R: SolanaRpc + ?Sized>(
+    client: &R,
+    transaction: &Transaction,
+    policy: &SendPolicy,
+    last_valid_block_height: u64,
+    last_attempt: bool,
+) -> std::result::Result<(), SendFailure> {
+    let signature = transaction.signatures[0];
+    let landed_with = |err| {
+        SendFailure::Final(ToolkitError::TransactionError(format!(
+            "Transaction {} failed: {:?}",
+            signature, err
+        )))
+    };
+
+    match &policy.confirm_strategy {
+        ConfirmStrategy::Rpc => {
+            client.send_transaction_with_config(transaction, policy.send_config()).await?;
+            let poll_interval = if last_attempt {
+                DEFAULT_CONFIRM_POLL_INTERVAL
+            } else {
+                DEFAULT_CONFIRM_POLL_INTERVAL.min(policy.timeout)
+            };
+            let confirm = confirm_until_block_height(
+                client,
+                &signature,
+                last_valid_block_height,
+                policy.commitment,
+                poll_interval,
+            );
+            let confirmed = if last_attempt {
+                confirm.await
+            } else {
+                match tokio::time::timeout(policy.timeout, confirm).await {
+                    Ok(confirmed) => confirmed,
+                    Err(_) => Err(ToolkitError::Timeout(format!(
+                        "Signature {} not confirmed within {:?}",
+                        signature, policy.timeout
+                    ))),
+                }
+            };
+            match confirmed {
+                Ok(_) => Ok(()),
+                // Resending the same blockhash can't help once it expired
+                Err(e @ ToolkitError::TransactionExpired { .. })
+                | Err(e @ ToolkitError::TransactionError(_)) => Err(SendFailure::Final(e)),
+                Err(e) => Err(SendFailure::Retryable(e)),
+            }
+        }
+        ConfirmStrategy::Websocket { ws_url } => {
+            let confirmation = confirm_signature_via_ws(
+                ws_url,
+                &signature,
+                policy.commitment,
+                policy.timeout,
+                async {
+                    client
+                        .send_transaction_with_config(transaction, policy.send_config())
+                        .await
+                        .map(|_| ())
+                },
+            )
+            .await?;
+            match confirmation.err {
+                Some(err) => Err(landed_with(err)),
+                None => Ok(()),
+            }
+        }
+    }
+}
+
+/// Outcome of simulating one batch with [`BatchExecutor::simulate_all`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimulationReport {
+    /// Why the simulation failed, or `None` if it succeeded.
+    pub error: Option<String>,
+    /// Program log messages.
+    pub logs: Vec<String>,
+    /// Compute units consumed, if the node reported them.
+    pub units_consumed: Option<u64>,
+}
+
+impl SimulationReport {
+    /// Whether the batch simulated without error.
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
 }
 
 /// Result of a batch transaction execution.
@@ -100,9 +779,49 @@ pub struct BatchResult {
     pub failed: Vec<(usize, String)>,
     /// Total instructions processed.
     pub instructions_processed: usize,
+    /// Original instruction indices contained in each batch, by batch index.
+    ///
+    /// Indices count the caller's instructions across all batches in order,
+    /// excluding compute budget instructions added by the executor.
+    pub batch_items: Vec<Vec<usize>>,
 }
 
 impl BatchResult {
+    /// Create an empty result for batches of the given sizes.
+    pub fn for_batch_sizes(sizes: impl IntoIterator<Item = usize>) -> Self {
+        let mut next = 0;
+        let batch_items = sizes
+            .into_iter()
+            .map(|size| {
+                let items = (next..next + size).collect();
+                next += size;
+                items
+            })
+            .collect();
+
+        Self {
+            successful: Vec::new(),
+            failed: Vec::new(),
+            instructions_processed: 0,
+            batch_items,
+        }
+    }
+
+    /// Original instruction indices contained in failed batches, sorted.
+    ///
+    /// Use this to retry exactly the failed items (e.g. airdrop recipients).
+    pub fn failed_items(&self) -> Vec<usize> {
+        let mut items: Vec<usize> = self
+            .failed
+            .iter()
+            .filter_map(|(batch, _)| self.batch_items.get(*batch))
+            .flatten()
+            .copied()
+            .collect();
+        items.sort_unstable();
+        items
+    }
+
     /// Check if all transactions succeeded.
     pub fn all_succeeded(&self) -> bool {
         self.failed.is_empty()
@@ -120,11 +839,15 @@ impl BatchResult {
 }
 
 /// Transaction builder for constructing complex transactions.
-#[derive(Default)]
+///
+/// Clone a builder holding shared setup, or use the `*_ref` methods, to
+/// build several transactions from the same base.
+#[derive(Debug, Clone, Default)]
 pub struct TransactionBuilder {
     instructions: Vec<Instruction>,
     signers: Vec<Pubkey>,
     config: TransactionConfig,
+    auto_compute_units: bool,
 }
 
 impl TransactionBuilder {
@@ -173,6 +896,76 @@ impl TransactionBuilder {
         self
     }
 
+    /// Size the compute unit limit from the instructions when building.
+    ///
+    /// The limit is [`estimate_compute_units`](Self::estimate_compute_units)
+    /// at build time, so instructions added afterwards are counted. An
+    /// explicit [`compute_units`](Self::compute_units) or an added
+    /// `set_compute_unit_limit` instruction overrides it.
+    pub fn auto_compute_units(mut self) -> Self {
+        self.auto_compute_units = true;
+        self
+    }
+
+    /// Estimate the compute units the instructions need.
+    ///
+    /// Sums a per-program cost for each instruction: ~150 CU for system and
+    /// compute budget instructions, ~4k for SPL Token, ~25k for creating an
+    /// associated token account, and the runtime's 200k default for any
+    /// other program. The config's `compute_unit_margin_bps` is added on top
+    /// and the result is capped at [`MAX_COMPUTE_UNITS`].
+    pub fn estimate_compute_units(&self) -> u32 {
+        let base: u64 = self
+            .instructions
+            .iter()
+            .map(|ix| program_compute_cost(&ix.program_id) as u64)
+            .sum();
+        let margin = base * self.config.compute_unit_margin_bps as u64 / 10_000;
+        (base + margin).min(MAX_COMPUTE_UNITS as u64) as u32
+    }
+
+    /// Keep only the last `set_compute_unit_limit` and `set_compute_unit_price`.
+    ///
+    /// Applies to added instructions and the builder's own settings, which are
+    /// placed first when building; an added instruction therefore wins over
+    /// [`compute_units`](Self::compute_units) / [`priority_fee`](Self::priority_fee).
+    pub fn dedup_compute_budget(mut self) -> Self {
+        for kind in [COMPUTE_UNIT_LIMIT_TAG, COMPUTE_UNIT_PRICE_TAG] {
+            let is_kind = |ix: &Instruction| compute_budget_tag(ix) == Some(kind);
+            let Some(last) = self.instructions.iter().rposition(is_kind) else {
+                continue;
+            };
+
+            let mut idx = 0;
+            self.instructions.retain(|ix| {
+                let keep = idx == last || !is_kind(ix);
+                idx += 1;
+                keep
+            });
+
+            if kind == COMPUTE_UNIT_LIMIT_TAG {
+                self.config.compute_units = None;
+                self.auto_compute_units = false;
+            } else {
+                self.config.priority_fee_micro_lamports = None;
+            }
+        }
+        self
+    }
+
+    /// Remove instructions identical to an earlier one (program id, accounts
+    /// and data), keeping the first occurrence.
+    pub fn remove_duplicate_instructions(mut self) -> Self {
+        let mut unique: Vec<Instruction> = Vec::with_capacity(self.instructions.len());
+        for ix in self.instructions {
+            if !unique.contains(&ix) {
+                unique.push(ix);
+            }
+        }
+        self.instructions = unique;
+        self
+    }
+
     /// Get the number of instructions currently in the builder.
     pub fn instruction_count(&self) -> usize {
         self.instructions.len()
@@ -197,10 +990,34 @@ impl TransactionBuilder {
 
     /// Build the final instructions with compute budget if configured.
     pub fn build_instructions(self) -> Vec<Instruction> {
+        let mut instructions = self.compute_budget_instructions();
+        instructions.extend(self.instructions);
+        instructions
+    }
+
+    /// Like [`build_instructions`](Self::build_instructions), but leaves the
+    /// builder reusable.
+    pub fn build_instructions_ref(&self) -> Vec<Instruction> {
+        let mut instructions = self.compute_budget_instructions();
+        instructions.extend(self.instructions.iter().cloned());
+        instructions
+    }
+
+    fn compute_budget_instructions(&self) -> Vec<Instruction> {
         let mut instructions = Vec::new();
+        // A budget alone would make a no-op transaction
+        if self.instructions.is_empty() {
+            return instructions;
+        }
 
         // Add compute budget instructions if configured
-        if let Some(units) = self.config.compute_units {
+        let has_limit_ix = self
+            .instructions
+            .iter()
+            .any(|ix| compute_budget_tag(ix) == Some(COMPUTE_UNIT_LIMIT_TAG));
+        let auto_units = (self.auto_compute_units && !has_limit_ix)
+            .then(|| self.estimate_compute_units());
+        if let Some(units) = self.config.compute_units.or(auto_units) {
             instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(units));
         }
 
@@ -208,45 +1025,139 @@ impl TransactionBuilder {
             instructions.push(ComputeBudgetInstruction::set_compute_unit_price(fee));
         }
 
-        instructions.extend(self.instructions);
         instructions
     }
 
     /// Build a transaction ready for signing.
-    pub fn build(self, payer: &Pubkey, _recent_blockhash: Hash) -> Transaction {
+    ///
+    /// Performs no network I/O; the caller supplies the recent blockhash.
+    pub fn build(self, payer: &Pubkey, recent_blockhash: Hash) -> Transaction {
         let instructions = self.build_instructions();
-        let message = Message::new(&instructions, Some(payer));
+        let message = Message::new_with_blockhash(&instructions, Some(payer), &recent_blockhash);
         Transaction::new_unsigned(message)
     }
+
+    /// Like [`build`](Self::build), but leaves the builder reusable.
+    pub fn build_ref(&self, payer: &Pubkey, recent_blockhash: Hash) -> Transaction {
+        let instructions = self.build_instructions_ref();
+        let message = Message::new_with_blockhash(&instructions, Some(payer), &recent_blockhash);
+        Transaction::new_unsigned(message)
+    }
+
+    /// Build and sign a transaction offline.
+    ///
+    /// The first signer is used as the fee payer. Fails if no instructions
+    /// were added.
+    pub fn build_signed(self, signers: &[&Keypair], recent_blockhash: Hash) -> Result<Transaction> {
+        ensure_instructions(&self.instructions)?;
+        let payer = signers
+            .first()
+            .ok_or_else(|| ToolkitError::SigningError("No signers provided".to_string()))?
+            .pubkey();
+
+        let mut transaction = self.build(&payer, recent_blockhash);
+        transaction.try_sign(signers, recent_blockhash)?;
+        Ok(transaction)
+    }
+}
+
+/// Compute budget instruction tags (the first data byte).
+const COMPUTE_UNIT_LIMIT_TAG: u8 = 2;
+const COMPUTE_UNIT_PRICE_TAG: u8 = 3;
+
+/// Approximate compute cost of one instruction of `program_id`, used by
+/// [`TransactionBuilder::estimate_compute_units`].
+fn program_compute_cost(program_id: &Pubkey) -> u32 {
+    if solana_sdk::system_program::check_id(program_id)
+        || solana_sdk::compute_budget::check_id(program_id)
+    {
+        150
+    } else if spl_token::check_id(program_id) {
+        4_000
+    } else if spl_associated_token_account::check_id(program_id) {
+        25_000
+    } else {
+        DEFAULT_COMPUTE_UNITS
+    }
+}
+
+/// Tag of a compute budget instruction, or `None` for other programs.
+fn compute_budget_tag(ix: &Instruction) -> Option<u8> {
+    if solana_sdk::compute_budget::check_id(&ix.program_id) {
+        ix.data.first().copied()
+    } else {
+        None
+    }
 }
 
 /// Batch executor for processing multiple transactions.
-pub struct BatchExecutor {
-    client: RpcClient,
+pub struct BatchExecutor<R = RpcClient> {
+    client: TimeoutRpc<R>,
     config: TransactionConfig,
+    policy: ExecutionPolicy,
+    /// Highest observed compute usage per instruction set.
+    compute_usage: RwLock<HashMap<Vec<Pubkey>, u64>>,
+}
+
+/// Programs invoked by `instructions`, in order, ignoring compute budget
+/// instructions. Transactions with the same key are treated as similar.
+fn instruction_set_key(instructions: &[Instruction]) -> Vec<Pubkey> {
+    instructions
+        .iter()
+        .map(|ix| ix.program_id)
+        .filter(|program_id| !solana_sdk::compute_budget::check_id(program_id))
+        .collect()
 }
 
 impl BatchExecutor {
     /// Create a new batch executor.
     pub fn new(rpc_url: &str) -> Self {
-        Self {
-            client: RpcClient::new_with_commitment(
-                rpc_url.to_string(),
-                CommitmentConfig::confirmed(),
-            ),
-            config: TransactionConfig::default(),
-        }
+        Self::with_config(rpc_url, TransactionConfig::default())
     }
 
     /// Create with custom configuration.
     pub fn with_config(rpc_url: &str, config: TransactionConfig) -> Self {
+        let client = RpcClient::new_with_commitment(rpc_url.to_string(), config.commitment);
+        Self::from_client(client, config)
+    }
+
+    /// Create for a well-known network.
+    pub fn new_on(network: Network) -> Self {
+        Self::new(network.rpc_url())
+    }
+}
+
+impl<R: SolanaRpc> BatchExecutor<R> {
+    /// Create from an existing RPC backend.
+    pub fn from_client(client: R, config: TransactionConfig) -> Self {
         Self {
-            client: RpcClient::new_with_commitment(rpc_url.to_string(), config.commitment),
+            client: TimeoutRpc::new(client, DEFAULT_RPC_TIMEOUT),
             config,
+            policy: ExecutionPolicy::default(),
+            compute_usage: RwLock::new(HashMap::new()),
         }
     }
 
+    /// Enforce `policy` before every send.
+    pub fn with_execution_policy(mut self, policy: ExecutionPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Fail RPC calls that take longer than `timeout` (30s by default)
+    /// with [`ToolkitError::Timeout`].
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client.set_timeout(timeout);
+        self
+    }
+
     /// Execute a single transaction with the configured settings.
+    ///
+    /// Under a dry-run [`ExecutionPolicy`] nothing is sent and the signature
+    /// the signed transaction would have had is returned. A simulation or
+    /// send rejected because the payer cannot cover the fee fails with
+    /// [`ToolkitError::InsufficientBalance`] without retrying. Fails
+    /// without sending if `instructions` is empty.
     pub async fn execute_transaction(
         &self,
         instructions: Vec<Instruction>,
@@ -255,7 +1166,46 @@ impl BatchExecutor {
         if signers.is_empty() {
             return Err(ToolkitError::SigningError("No signers provided".to_string()));
         }
+        ensure_instructions(&instructions)?;
+
+        let usage_key = instruction_set_key(&instructions);
+        let recent_blockhash = self.client.get_latest_blockhash().await?;
+        let transaction = self.build_transaction(instructions, signers, recent_blockhash).await?;
+
+        self.policy.enforce(&self.client, &transaction).await?;
+        if self.policy.dry_run {
+            return Ok(transaction.signatures[0]);
+        }
+
+        // Simulate if configured and the policy has not already
+        if self.config.simulate_before_send && !self.policy.simulates() {
+            let sim_result = self.client.simulate_transaction(&transaction).await?;
+            if let Some(err) = sim_result.err {
+                let error = simulation_error(&transaction, &err);
+                return Err(self.explain_funding_error(&transaction, error).await);
+            }
+        }
+
+        let policy = self.config.send_policy();
+        match send_and_confirm(&self.client, &transaction, &[], &policy).await {
+            Ok(sig) => {
+                if self.config.track_compute_units {
+                    self.track_units_consumed(usage_key, &sig).await;
+                }
+                Ok(sig)
+            }
+            Err(e) => Err(self.explain_funding_error(&transaction, e).await),
+        }
+    }
 
+    /// Sign `instructions` with the configured compute budget instructions
+    /// prepended. The first signer pays.
+    async fn build_transaction(
+        &self,
+        instructions: Vec<Instruction>,
+        signers: &[&Keypair],
+        recent_blockhash: Hash,
+    ) -> Result<Transaction> {
         let payer = signers[0];
         let mut all_instructions = Vec::new();
 
@@ -264,48 +1214,243 @@ impl BatchExecutor {
             all_instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(units));
         }
 
-        if let Some(fee) = self.config.priority_fee_micro_lamports {
+        let priority_fee =
+            resolve_priority_fee(&self.client, &self.config, &payer.pubkey(), &instructions)
+                .await?;
+        if let Some(fee) = priority_fee {
             all_instructions.push(ComputeBudgetInstruction::set_compute_unit_price(fee));
         }
 
         all_instructions.extend(instructions);
 
-        let recent_blockhash = self.client.get_latest_blockhash().await?;
         let message = Message::new(&all_instructions, Some(&payer.pubkey()));
-        let transaction = Transaction::new(signers, message, recent_blockhash);
-
-        // Simulate if configured
-        if self.config.simulate_before_send {
-            let sim_result = self.client.simulate_transaction(&transaction).await?;
-            if let Some(err) = sim_result.value.err {
-                return Err(ToolkitError::TransactionError(format!(
-                    "Simulation failed: {:?}",
-                    err
-                )));
-            }
-        }
+        Ok(Transaction::new(signers, message, recent_blockhash))
+    }
 
-        // Send with retries
-        let mut last_error = None;
-        for attempt in 0..=self.config.max_retries {
-            match self.client.send_and_confirm_transaction(&transaction).await {
-                Ok(sig) => return Ok(sig),
-                Err(e) => {
-                    last_error = Some(e);
-                    if attempt < self.config.max_retries {
-                        tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-                    }
+    /// Simulate every batch as [`execute_batches`](Self::execute_batches)
+    /// would send it, without sending anything.
+    ///
+    /// Reports are in batch order. Each batch is simulated on its own
+    /// against current state, so a batch depending on an earlier one may
+    /// fail here and still succeed when executed. Empty batches are
+    /// reported as failed without simulating. Fails if every batch is
+    /// empty, a batch cannot be built or the node cannot be reached.
+    pub async fn simulate_all(
+        &self,
+        instruction_batches: Vec<Vec<Instruction>>,
+        signers: &[&Keypair],
+    ) -> Result<Vec<SimulationReport>> {
+        if signers.is_empty() {
+            return Err(ToolkitError::SigningError("No signers provided".to_string()));
+        }
+        if instruction_batches.iter().all(Vec::is_empty) {
+            return Err(no_instructions());
+        }
+
+        let recent_blockhash = self.client.get_latest_blockhash().await?;
+        let mut reports = Vec::with_capacity(instruction_batches.len());
+        for instructions in instruction_batches {
+            if instructions.is_empty() {
+                reports.push(SimulationReport {
+                    error: Some(no_instructions().to_string()),
+                    logs: Vec::new(),
+                    units_consumed: None,
+                });
+                continue;
+            }
+            let transaction =
+                self.build_transaction(instructions, signers, recent_blockhash).await?;
+            let simulation = self.client.simulate_transaction(&transaction).await?;
+            reports.push(SimulationReport {
+                error: simulation.err.map(|err| simulation_error(&transaction, &err).to_string()),
+                logs: simulation.logs.unwrap_or_default(),
+                units_consumed: simulation.units_consumed,
+            });
+        }
+        Ok(reports)
+    }
+
+    /// Resubmit a signed transaction until it lands or `deadline` passes.
+    ///
+    /// On congested clusters a single send is often silently dropped. The
+    /// same transaction (and so the same signature) is sent every
+    /// `resend_interval`, honoring `skip_preflight`, and its status is
+    /// polled with `getSignatureStatuses` until it reaches the configured
+    /// commitment. Returns the slot it landed in.
+    ///
+    /// Fails if the first send is rejected (e.g. by preflight), if the
+    /// transaction lands with an error, or with [`ToolkitError::Timeout`]
+    /// once `deadline` passes. The deadline should not outlive the
+    /// blockhash, after which the transaction can no longer land.
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    pub async fn send_persistent(
+        &self,
+        transaction: &Transaction,
+        deadline: Duration,
+    ) -> Result<u64> {
+        let signature = transaction.signatures[0];
+        let send_config = RpcSendTransactionConfig {
+            max_retries: Some(0),
+            ..self.config.send_policy().send_config()
+        };
+        let started = Instant::now();
+
+        for attempt in 0.. {
+            match self.client.send_transaction_with_config(transaction, send_config).await {
+                Ok(_) => {}
+                Err(e) if attempt == 0 => {
+                    return Err(self.explain_funding_error(transaction, e).await)
                 }
+                // Resends of an already processed transaction are rejected
+                Err(e) => debug!(attempt, error = %e, "resend rejected"),
             }
+
+            let statuses = self.client.get_signature_statuses(&[signature]).await?;
+            if let Some(Some(status)) = statuses.first() {
+                if status.satisfies_commitment(self.config.commitment) {
+                    return match &status.err {
+                        Some(err) => Err(ToolkitError::TransactionError(format!(
+                            "Transaction {} failed: {:?}",
+                            signature, err
+                        ))),
+                        None => {
+                            debug!(signature = %signature, attempt, "transaction landed");
+                            Ok(status.slot)
+                        }
+                    };
+                }
+            }
+
+            if started.elapsed() >= deadline {
+                break;
+            }
+            tokio::time::sleep(self.config.resend_interval).await;
         }
 
-        Err(ToolkitError::TransactionError(format!(
-            "Failed after {} retries: {:?}",
-            self.config.max_retries,
-            last_error
+        Err(ToolkitError::Timeout(format!(
+            "Signature {} not confirmed within {:?}",
+            signature, deadline
         )))
     }
 
+    /// Priority fee tiers from the fees recently paid to lock
+    /// `writable_accounts`, for presenting slow/normal/fast choices.
+    pub async fn fee_tiers(&self, writable_accounts: &[Pubkey]) -> Result<FeeTiers> {
+        fee_tiers(&self.client, writable_accounts).await
+    }
+
+    /// Wait for a sent transaction until its blockhash expires.
+    ///
+    /// See [`confirm_until_block_height`]; the status is polled every
+    /// `resend_interval` at the configured commitment.
+    pub async fn confirm_until_block_height(
+        &self,
+        signature: &Signature,
+        last_valid_block_height: u64,
+    ) -> Result<u64> {
+        confirm_until_block_height(
+            &self.client,
+            signature,
+            last_valid_block_height,
+            self.config.commitment,
+            self.config.resend_interval,
+        )
+        .await
+    }
+
+    /// Wait for `signature` to reach `target` and make sure it stays there.
+    ///
+    /// See [`confirm_stable`]; the status is polled every `resend_interval`.
+    pub async fn confirm_stable(
+        &self,
+        signature: &Signature,
+        target: CommitmentConfig,
+    ) -> Result<u64> {
+        confirm_stable(&self.client, signature, target, self.config.resend_interval).await
+    }
+
+    /// Record that a transaction with `instructions` consumed `units`.
+    ///
+    /// Called automatically for confirmed transactions when
+    /// `track_compute_units` is set.
+    pub fn record_compute_units(&self, instructions: &[Instruction], units: u64) {
+        self.record_usage(instruction_set_key(instructions), units);
+    }
+
+    /// Recommended compute unit limit for `instructions`: the highest usage
+    /// observed for the same programs, padded by `compute_unit_margin_bps`
+    /// and capped at [`MAX_COMPUTE_UNITS`].
+    ///
+    /// `None` until a similar transaction has been observed.
+    pub fn recommended_compute_units(&self, instructions: &[Instruction]) -> Option<u32> {
+        let max = *self
+            .compute_usage
+            .read()
+            .unwrap()
+            .get(&instruction_set_key(instructions))?;
+        let padded = max as u128 * (10_000 + self.config.compute_unit_margin_bps as u128) / 10_000;
+        Some(padded.min(MAX_COMPUTE_UNITS as u128) as u32)
+    }
+
+    fn record_usage(&self, key: Vec<Pubkey>, units: u64) {
+        let mut usage = self.compute_usage.write().unwrap();
+        let max = usage.entry(key).or_insert(0);
+        *max = (*max).max(units);
+    }
+
+    /// Replace a funding failure of `transaction` with
+    /// [`ToolkitError::InsufficientBalance`], comparing the payer's balance
+    /// with the estimated fee plus the lamports the transaction moves out of
+    /// the payer. Other errors, and failures those figures don't explain, are
+    /// returned unchanged.
+    async fn explain_funding_error(
+        &self,
+        transaction: &Transaction,
+        error: ToolkitError,
+    ) -> ToolkitError {
+        let Some(payer) = transaction.message.account_keys.first() else {
+            return error;
+        };
+        if !is_insufficient_funds(&error) {
+            return error;
+        }
+        let Ok(available) = self.client.get_balance(payer).await else {
+            return error;
+        };
+
+        let needed = estimate_fee(transaction).saturating_add(lamports_debited(transaction, payer));
+        if needed <= available {
+            return error;
+        }
+        ToolkitError::InsufficientBalance { needed, available }
+    }
+
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    async fn track_units_consumed(&self, key: Vec<Pubkey>, signature: &Signature) {
+        match self.client.get_transaction_units_consumed(signature).await {
+            Ok(Some(units)) => {
+                debug!(signature = %signature, units, "compute units consumed");
+                self.record_usage(key, units);
+            }
+            Ok(None) => debug!(signature = %signature, "compute usage not reported"),
+            Err(e) => warn!(signature = %signature, error = %e, "failed to fetch compute usage"),
+        }
+    }
+
+    /// Wait for `signature` to reach `commitment` via `signatureSubscribe`.
+    ///
+    /// Resolves with the slot and execution error (if any) as soon as the
+    /// node reports the commitment, or fails with [`ToolkitError::Timeout`].
+    pub async fn confirm_via_ws(
+        &self,
+        ws_url: &str,
+        signature: &Signature,
+        commitment: CommitmentConfig,
+        timeout: Duration,
+    ) -> Result<WsConfirmation> {
+        confirm_signature_via_ws(ws_url, signature, commitment, timeout, async { Ok(()) }).await
+    }
+
     /// Split instructions into batches based on account limits.
     pub fn split_into_batches(&self, instructions: Vec<Instruction>, max_per_batch: usize) -> Vec<Vec<Instruction>> {
         let effective_max = max_per_batch.min(MAX_ACCOUNTS_PER_TX / 4); // Conservative estimate
@@ -313,30 +1458,49 @@ impl BatchExecutor {
     }
 
     /// Execute multiple instruction batches sequentially.
+    ///
+    /// Empty batches are recorded as failed without sending. Fails if
+    /// every batch is empty.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(batches = instruction_batches.len()))
+    )]
     pub async fn execute_batches(
         &self,
         instruction_batches: Vec<Vec<Instruction>>,
         signers: &[&Keypair],
     ) -> Result<BatchResult> {
-        let mut result = BatchResult {
-            successful: Vec::new(),
-            failed: Vec::new(),
-            instructions_processed: 0,
-        };
+        if instruction_batches.iter().all(Vec::is_empty) {
+            return Err(no_instructions());
+        }
+        let mut result = BatchResult::for_batch_sizes(instruction_batches.iter().map(Vec::len));
 
         for (batch_idx, instructions) in instruction_batches.into_iter().enumerate() {
             let ix_count = instructions.len();
             match self.execute_transaction(instructions, signers).await {
                 Ok(sig) => {
+                    debug!(
+                        batch = batch_idx,
+                        signature = %sig,
+                        instructions = ix_count,
+                        "batch sent"
+                    );
                     result.successful.push(sig);
                     result.instructions_processed += ix_count;
                 }
                 Err(e) => {
+                    warn!(batch = batch_idx, error = %e, "batch failed");
                     result.failed.push((batch_idx, e.to_string()));
                 }
             }
         }
 
+        info!(
+            successful = result.successful.len(),
+            failed = result.failed.len(),
+            instructions = result.instructions_processed,
+            "batch execution finished"
+        );
         Ok(result)
     }
 
@@ -353,8 +1517,8 @@ impl BatchExecutor {
 }
 
 /// Parallel batch executor for concurrent transaction processing.
-pub struct ParallelBatchExecutor {
-    client: RpcClient,
+pub struct ParallelBatchExecutor<R = RpcClient> {
+    client: TimeoutRpc<R>,
     config: TransactionConfig,
     max_concurrent: usize,
 }
@@ -362,26 +1526,52 @@ pub struct ParallelBatchExecutor {
 impl ParallelBatchExecutor {
     /// Create a new parallel batch executor.
     pub fn new(rpc_url: &str, max_concurrent: usize) -> Self {
-        Self {
-            client: RpcClient::new_with_commitment(
-                rpc_url.to_string(),
-                CommitmentConfig::confirmed(),
-            ),
-            config: TransactionConfig::default(),
-            max_concurrent,
-        }
+        Self::with_config(rpc_url, TransactionConfig::default(), max_concurrent)
+    }
+
+    /// Create for a well-known network.
+    pub fn new_on(network: Network, max_concurrent: usize) -> Self {
+        Self::new(network.rpc_url(), max_concurrent)
     }
 
     /// Create with custom configuration.
     pub fn with_config(rpc_url: &str, config: TransactionConfig, max_concurrent: usize) -> Self {
+        let client = RpcClient::new_with_commitment(rpc_url.to_string(), config.commitment);
+        Self::from_client(client, config, max_concurrent)
+    }
+}
+
+impl<R: SolanaRpc> ParallelBatchExecutor<R> {
+    /// Create from an existing RPC backend.
+    pub fn from_client(client: R, config: TransactionConfig, max_concurrent: usize) -> Self {
         Self {
-            client: RpcClient::new_with_commitment(rpc_url.to_string(), config.commitment),
+            client: TimeoutRpc::new(client, DEFAULT_RPC_TIMEOUT),
             config,
             max_concurrent,
         }
     }
 
+    /// Fail RPC calls that take longer than `timeout` (30s by default)
+    /// with [`ToolkitError::Timeout`].
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client.set_timeout(timeout);
+        self
+    }
+
     /// Execute multiple independent transactions in parallel.
+    ///
+    /// Up to `max_concurrent` are in flight at once. `successful` and
+    /// `failed` are in the order the transactions were given, not the order
+    /// they completed in, so repeated runs give comparable results. Empty
+    /// transactions are recorded as failed without sending, and the call
+    /// fails if every one is empty.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(transactions = transaction_instructions.len(), concurrency = self.max_concurrent)
+        )
+    )]
     pub async fn execute_parallel(
         &self,
         transaction_instructions: Vec<Vec<Instruction>>,
@@ -393,34 +1583,49 @@ impl ParallelBatchExecutor {
             return Err(ToolkitError::SigningError("No signers provided".to_string()));
         }
 
+        if transaction_instructions.iter().all(Vec::is_empty) {
+            return Err(no_instructions());
+        }
+
         let payer = signers[0];
+        let all: Vec<Instruction> = transaction_instructions.concat();
         let recent_blockhash = self.client.get_latest_blockhash().await?;
+        let priority_fee =
+            resolve_priority_fee(&self.client, &self.config, &payer.pubkey(), &all).await?;
 
         let transactions: Vec<_> = transaction_instructions
             .iter()
             .map(|instructions| {
+                if instructions.is_empty() {
+                    return None;
+                }
                 let mut all_instructions = Vec::new();
 
                 if let Some(units) = self.config.compute_units {
                     all_instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(units));
                 }
 
-                if let Some(fee) = self.config.priority_fee_micro_lamports {
+                if let Some(fee) = priority_fee {
                     all_instructions.push(ComputeBudgetInstruction::set_compute_unit_price(fee));
                 }
 
                 all_instructions.extend(instructions.clone());
 
                 let message = Message::new(&all_instructions, Some(&payer.pubkey()));
-                Transaction::new(signers, message, recent_blockhash)
+                Some(Transaction::new(signers, message, recent_blockhash))
             })
             .collect();
 
-        let results: Vec<_> = stream::iter(transactions.into_iter().enumerate())
+        let policy = self.config.send_policy();
+        let mut results: Vec<_> = stream::iter(transactions.into_iter().enumerate())
             .map(|(idx, tx)| {
                 let client = &self.client;
+                let policy = &policy;
                 async move {
-                    match client.send_and_confirm_transaction(&tx).await {
+                    let Some(tx) = tx else {
+                        return (idx, Err(no_instructions().to_string()));
+                    };
+                    match send_and_confirm(client, &tx, &[], policy).await {
                         Ok(sig) => (idx, Ok(sig)),
                         Err(e) => (idx, Err(e.to_string())),
                     }
@@ -429,29 +1634,432 @@ impl ParallelBatchExecutor {
             .buffer_unordered(self.max_concurrent)
             .collect()
             .await;
+        // Report in submission order, whatever order they completed in
+        results.sort_by_key(|(idx, _)| *idx);
 
-        let mut batch_result = BatchResult {
-            successful: Vec::new(),
-            failed: Vec::new(),
-            instructions_processed: 0,
-        };
+        let mut batch_result =
+            BatchResult::for_batch_sizes(transaction_instructions.iter().map(Vec::len));
 
         for (idx, result) in results {
             match result {
                 Ok(sig) => {
+                    debug!(transaction = idx, signature = %sig, "transaction sent");
                     batch_result.successful.push(sig);
                     batch_result.instructions_processed += transaction_instructions[idx].len();
                 }
                 Err(e) => {
+                    warn!(transaction = idx, error = %e, "transaction failed");
                     batch_result.failed.push((idx, e));
                 }
             }
         }
 
+        info!(
+            successful = batch_result.successful.len(),
+            failed = batch_result.failed.len(),
+            "parallel execution finished"
+        );
         Ok(batch_result)
     }
 }
 
+/// Sign `instructions` with `fee_payer` paying the fee and `authority`
+/// signing the instructions themselves.
+///
+/// `fee_payer` is the message's fee payer and signs first; when it is also
+/// the authority it signs once. Performs no network I/O; the caller supplies
+/// the recent blockhash.
+pub fn fee_paid_transaction(
+    instructions: &[Instruction],
+    fee_payer: &Keypair,
+    authority: &Keypair,
+    recent_blockhash: Hash,
+) -> Transaction {
+    let message = Message::new(instructions, Some(&fee_payer.pubkey()));
+    if fee_payer.pubkey() == authority.pubkey() {
+        return Transaction::new(&[fee_payer], message, recent_blockhash);
+    }
+    Transaction::new(&[fee_payer, authority], message, recent_blockhash)
+}
+
+/// Build a native SOL transfer instruction.
+pub fn transfer_sol(from: &Pubkey, to: &Pubkey, lamports: impl Into<Lamports>) -> Instruction {
+    system_instruction::transfer(from, to, lamports.into().0)
+}
+
+/// Wait for `signature` to reach `commitment` until the block height
+/// passes `last_valid_block_height`. Returns the slot it landed in.
+///
+/// The status is polled every `poll_interval`. Past the height limit a
+/// transaction that has not landed never will, so this fails with
+/// [`ToolkitError::TransactionExpired`] rather than waiting on; one already
+/// seen landing is waited on regardless. A transaction that lands with an
+/// error fails with [`ToolkitError::TransactionError`].
+pub async fn confirm_until_block_height<R: SolanaRpc + ?Sized>(
+    rpc: &R,
+    signature: &Signature,
+    last_valid_block_height: u64,
+    commitment: CommitmentConfig,
+    poll_interval: Duration,
+) -> Result<u64> {
+    loop {
+        // Read the height before the status, so a transaction landing
+        // between the two calls is not reported as expired
+        let block_height = rpc.get_block_height().await?;
+        let statuses = rpc.get_signature_statuses(&[*signature]).await?;
+
+        match statuses.into_iter().next().flatten() {
+            Some(status) => {
+                if let Some(err) = status.err {
+                    return Err(ToolkitError::TransactionError(format!(
+                        "Transaction {} failed: {:?}",
+                        signature, err
+                    )));
+                }
+                if status.satisfies_commitment(commitment) {
+                    return Ok(status.slot);
+                }
+            }
+            None if block_height > last_valid_block_height => {
+                return Err(ToolkitError::TransactionExpired {
+                    signature: signature.to_string(),
+                    last_valid_block_height,
+                });
+            }
+            None => {}
+        }
+        tokio::time::sleep(poll_interval).await;
+    }
+}
+
+/// Wait for `signature` to reach `target`, then check again that it is
+/// still there. Returns the slot it is settled in.
+///
+/// A transaction at `processed` (or even `confirmed`) can vanish when its
+/// fork is abandoned. If the signature is seen and then disappears, or no
+/// longer meets `target` when re-checked one `poll_interval` after first
+/// meeting it, this fails with [`ToolkitError::TransactionDropped`]. A
+/// signature never seen within [`DEFAULT_CONFIRM_TIMEOUT`] fails with
+/// [`ToolkitError::Timeout`], and one that lands with an error with
+/// [`ToolkitError::TransactionError`].
+pub async fn confirm_stable<R: SolanaRpc + ?Sized>(
+    rpc: &R,
+    signature: &Signature,
+    target: CommitmentConfig,
+    poll_interval: Duration,
+) -> Result<u64> {
+    let started = Instant::now();
+    let mut seen: Option<TransactionConfirmationStatus> = None;
+    let mut reached = false;
+    loop {
+        let statuses = rpc.get_signature_statuses(&[*signature]).await?;
+        match statuses.into_iter().next().flatten() {
+            Some(status) => {
+                if let Some(err) = status.err {
+                    return Err(ToolkitError::TransactionError(format!(
+                        "Transaction {} failed: {:?}",
+                        signature, err
+                    )));
+                }
+                let satisfied = status.satisfies_commitment(target);
+                if reached {
+                    if satisfied {
+                        return Ok(status.slot);
+                    }
+                    return Err(dropped(signature, seen, target));
+                }
+                let level = status.confirmation_status();
+                if seen.as_ref().map_or(true, |seen| rank(&level) > rank(seen)) {
+                    seen = Some(level);
+                }
+                reached = satisfied;
+            }
+            None if seen.is_some() => return Err(dropped(signature, seen, target)),
+            None if started.elapsed() > DEFAULT_CONFIRM_TIMEOUT => {
+                return Err(ToolkitError::Timeout(format!(
+                    "Transaction {} was not seen within {:?}",
+                    signature, DEFAULT_CONFIRM_TIMEOUT
+                )));
+            }
+            None => {}
+        }
+        tokio::time::sleep(poll_interval).await;
+    }
+}
+
+/// Order confirmation levels from processed to finalized.
+fn rank(status: &TransactionConfirmationStatus) -> u8 {
+    match status {
+        TransactionConfirmationStatus::Processed => 0,
+        TransactionConfirmationStatus::Confirmed => 1,
+        TransactionConfirmationStatus::Finalized => 2,
+    }
+}
+
+/// A [`ToolkitError::TransactionDropped`] for [`confirm_stable`].
+fn dropped(
+    signature: &Signature,
+    seen: Option<TransactionConfirmationStatus>,
+    target: CommitmentConfig,
+) -> ToolkitError {
+    ToolkitError::TransactionDropped {
+        signature: signature.to_string(),
+        seen: format!("{:?}", seen.unwrap_or(TransactionConfirmationStatus::Processed))
+            .to_lowercase(),
+        expected: format!("{:?}", target.commitment).to_lowercase(),
+    }
+}
+
+/// Base fee charged per transaction signature, in lamports.
+pub const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+/// Estimate the fee `transaction` will be charged, in lamports.
+///
+/// This is [`LAMPORTS_PER_SIGNATURE`] for each required signature plus the
+/// priority fee its compute budget instructions request. Without a compute
+/// unit limit the runtime's [`DEFAULT_COMPUTE_UNITS`] per instruction is
+/// assumed.
+pub fn estimate_fee(transaction: &Transaction) -> u64 {
+    let message = &transaction.message;
+    let mut limit = None;
+    let mut price = 0u64;
+    let mut other_instructions = 0u64;
+    for ix in (0..message.instructions.len()).filter_map(|i| message_instruction(message, i)) {
+        match compute_budget_tag(&ix) {
+            Some(COMPUTE_UNIT_LIMIT_TAG) => {
+                limit = ix.data.get(1..5).map(|b| u32::from_le_bytes(b.try_into().unwrap()));
+            }
+            Some(COMPUTE_UNIT_PRICE_TAG) => {
+                price = ix.data.get(1..9).map_or(0, |b| u64::from_le_bytes(b.try_into().unwrap()));
+            }
+            Some(_) => {}
+            None => other_instructions += 1,
+        }
+    }
+
+    let units = limit.map_or(other_instructions * DEFAULT_COMPUTE_UNITS as u64, u64::from);
+    let units = units.min(MAX_COMPUTE_UNITS as u64);
+    let priority_fee = (price as u128 * units as u128).div_ceil(1_000_000);
+    let signature_fee = message.header.num_required_signatures as u64 * LAMPORTS_PER_SIGNATURE;
+    signature_fee.saturating_add(priority_fee.min(u64::MAX as u128) as u64)
+}
+
+/// Serialize `transaction` to base64, as wallets and `sendTransaction`
+/// expect it.
+///
+/// The transaction may be unsigned, for handing off to a browser wallet to
+/// sign. Fails if it is larger than [`MAX_TRANSACTION_SIZE`].
+pub fn serialize_base64(transaction: &Transaction) -> Result<String> {
+    encode_base64(transaction)
+}
+
+/// Serialize a versioned `transaction` to base64; see [`serialize_base64`].
+pub fn serialize_versioned_base64(transaction: &VersionedTransaction) -> Result<String> {
+    encode_base64(transaction)
+}
+
+/// Decode a base64 transaction, legacy or versioned, such as one returned
+/// signed by a wallet or built by Jupiter.
+pub fn deserialize_base64<T: DeserializeOwned>(encoded: &str) -> Result<T> {
+    let bytes = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, encoded)
+        .map_err(|e| ToolkitError::ParseError(format!("Invalid base64 transaction: {}", e)))?;
+    bincode::deserialize(&bytes)
+        .map_err(|e| ToolkitError::Deserialization(format!("Invalid transaction: {}", e)))
+}
+
+fn encode_base64<T: Serialize>(transaction: &T) -> Result<String> {
+    let bytes = bincode::serialize(transaction)
+        .map_err(|e| ToolkitError::Custom(format!("Failed to encode transaction: {}", e)))?;
+    if bytes.len() > MAX_TRANSACTION_SIZE {
+        return Err(ToolkitError::TransactionError(format!(
+            "Transaction is {} bytes, over the {} byte limit",
+            bytes.len(),
+            MAX_TRANSACTION_SIZE
+        )));
+    }
+    Ok(base64::Engine::encode(&base64::engine::general_purpose::STANDARD, bytes))
+}
+
+/// Lamports the system instructions of `transaction` move out of `payer`.
+fn lamports_debited(transaction: &Transaction, payer: &Pubkey) -> u64 {
+    let message = &transaction.message;
+    (0..message.instructions.len())
+        .filter_map(|i| message_instruction(message, i))
+        .filter(|ix| solana_sdk::system_program::check_id(&ix.program_id))
+        .filter(|ix| ix.accounts.first().map(|meta| &meta.pubkey) == Some(payer))
+        .filter_map(|ix| match bincode::deserialize(&ix.data).ok()? {
+            SystemInstruction::Transfer { lamports }
+            | SystemInstruction::CreateAccount { lamports, .. }
+            | SystemInstruction::CreateAccountWithSeed { lamports, .. } => Some(lamports),
+            _ => None,
+        })
+        .fold(0, u64::saturating_add)
+}
+
+/// Whether `error` reports that an account could not pay for the
+/// transaction, as returned by preflight, simulation or the runtime.
+fn is_insufficient_funds(error: &ToolkitError) -> bool {
+    let message = error.to_string().to_lowercase();
+    [
+        "insufficientfundsforfee",
+        "insufficient funds",
+        "insufficient lamports",
+        "accountnotfound",
+        "no record of a prior credit",
+    ]
+    .iter()
+    .any(|pattern| message.contains(pattern))
+}
+
+/// Convert a total priority-fee budget into a compute unit price.
+///
+/// Returns the micro-lamports per compute unit that spends at most
+/// `target_lamports` when `compute_units` are requested, rounding down.
+/// Zero compute units gives a price of zero.
+pub fn priority_fee_for_budget(target_lamports: u64, compute_units: u32) -> u64 {
+    if compute_units == 0 {
+        return 0;
+    }
+    let price = target_lamports as u128 * 1_000_000 / compute_units as u128;
+    price.min(u64::MAX as u128) as u64
+}
+
+/// Outcome of a [`SafeTransfer`].
+#[derive(Debug, Clone)]
+pub struct SafeTransferOutcome {
+    /// Safety report for the transfer.
+    pub report: SafetyReport,
+    /// Signature, if the transfer was sent.
+    pub signature: Option<Signature>,
+}
+
+impl SafeTransferOutcome {
+    /// Whether the transfer was sent.
+    pub fn was_sent(&self) -> bool {
+        self.signature.is_some()
+    }
+}
+
+/// SOL transfers gated by [`SafetyProtocol::validate_transfer`].
+pub struct SafeTransfer<R = RpcClient> {
+    client: TimeoutRpc<R>,
+    protocol: SafetyProtocol,
+    send_policy: SendPolicy,
+}
+
+impl SafeTransfer {
+    /// Create with the default safety protocol.
+    pub fn new(rpc_url: &str) -> Self {
+        Self::from_client(
+            RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed()),
+            SafetyProtocol::new(),
+        )
+    }
+
+    /// Create for a well-known network with the default safety protocol.
+    pub fn new_on(network: Network) -> Self {
+        Self::new(network.rpc_url())
+    }
+}
+
+impl<R: SolanaRpc> SafeTransfer<R> {
+    /// Create from an existing RPC backend and safety protocol.
+    pub fn from_client(client: R, protocol: SafetyProtocol) -> Self {
+        Self {
+            client: TimeoutRpc::new(client, DEFAULT_RPC_TIMEOUT),
+            protocol,
+            send_policy: SendPolicy::default(),
+        }
+    }
+
+    /// Fail RPC calls that take longer than `timeout` (30s by default)
+    /// with [`ToolkitError::Timeout`].
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.client.set_timeout(timeout);
+        self
+    }
+
+    /// Send and confirm transfers per `policy`.
+    pub fn with_send_policy(mut self, policy: SendPolicy) -> Self {
+        self.send_policy = policy;
+        self
+    }
+
+    /// Validate and send a transfer of `lamports`, which may also be given
+    /// as [`Lamports`]. Convert a [`Sol`](crate::safety::Sol) amount with
+    /// `Lamports::try_from` first.
+    ///
+    /// Sends only if the report is approved and does not require
+    /// confirmation; otherwise returns the report without sending.
+    pub async fn send(
+        &self,
+        from: &Keypair,
+        to: &Pubkey,
+        lamports: impl Into<Lamports>,
+    ) -> Result<SafeTransferOutcome> {
+        self.send_with_confirmation(from, to, lamports, |_| false).await
+    }
+
+    /// Validate and send `percent` of the sender's balance.
+    ///
+    /// The percentage applies to the balance after reserving rent and the
+    /// fee (see [`AmountValidator::amount_from_percentage`]), so even 100%
+    /// leaves the sender rent exempt. Like [`send`](Self::send), reports
+    /// requiring confirmation are returned without sending.
+    pub async fn send_percentage(
+        &self,
+        from: &Keypair,
+        to: &Pubkey,
+        percent: f64,
+    ) -> Result<SafeTransferOutcome> {
+        let balance = self.client.get_balance(&from.pubkey()).await?;
+        let lamports =
+            AmountValidator::amount_from_percentage(balance, percent, 9, TokenKind::NativeSol)?;
+        self.send(from, to, lamports).await
+    }
+
+    /// Validate and send a transfer, asking `confirm` when the report
+    /// requires confirmation.
+    ///
+    /// Blocked transfers are never sent, regardless of `confirm`.
+    pub async fn send_with_confirmation<F>(
+        &self,
+        from: &Keypair,
+        to: &Pubkey,
+        lamports: impl Into<Lamports>,
+        confirm: F,
+    ) -> Result<SafeTransferOutcome>
+    where
+        F: FnOnce(&SafetyReport) -> bool,
+    {
+        let lamports = lamports.into().0;
+        let report = self
+            .protocol
+            .validate_transfer_of(&self.client, &from.pubkey(), to, lamports, TokenKind::NativeSol)
+            .await?;
+
+        let proceed = report.approved && (!report.requires_confirmation || confirm(&report));
+        if !proceed {
+            return Ok(SafeTransferOutcome {
+                report,
+                signature: None,
+            });
+        }
+
+        let recent_blockhash = self.client.get_latest_blockhash().await?;
+        let transaction = TransactionBuilder::new()
+            .add_instruction(transfer_sol(&from.pubkey(), to, lamports))
+            .build_signed(&[from], recent_blockhash)?;
+        let signature =
+            send_and_confirm(&self.client, &transaction, &[], &self.send_policy).await?;
+
+        Ok(SafeTransferOutcome {
+            report,
+            signature: Some(signature),
+        })
+    }
+}
+
 /// Estimate transaction size for a set of instructions.
 pub fn estimate_transaction_size(instructions: &[Instruction], num_signers: usize) -> usize {
     let mut size = 0;
@@ -490,6 +2098,225 @@ pub fn estimate_transaction_size(instructions: &[Instruction], num_signers: usiz
     size
 }
 
+/// Best-effort description of an instruction, from [`decode_instruction`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedInstruction {
+    /// Program invoked.
+    pub program_id: Pubkey,
+    /// Program name, or `None` for programs that are not recognized.
+    pub program: Option<&'static str>,
+    /// Instruction variant, or `None` if the data could not be decoded.
+    pub name: Option<String>,
+    /// Key accounts with their role. Instructions that could not be decoded
+    /// list every account by position.
+    pub accounts: Vec<(String, Pubkey)>,
+    /// Raw instruction data.
+    pub data: Vec<u8>,
+}
+
+impl std::fmt::Display for DecodedInstruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.program, &self.name) {
+            (Some(program), Some(name)) => write!(f, "{} {}", program, name)?,
+            (Some(program), None) => {
+                write!(f, "{} (undecoded, {} bytes)", program, self.data.len())?
+            }
+            (None, _) => write!(f, "program {} ({} bytes)", self.program_id, self.data.len())?,
+        }
+        if self.name.is_some() && !self.accounts.is_empty() {
+            let accounts: Vec<String> = self
+                .accounts
+                .iter()
+                .map(|(role, pubkey)| format!("{}: {}", role, pubkey))
+                .collect();
+            write!(f, " [{}]", accounts.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Decode the variant and key accounts of a System, SPL Token, Associated
+/// Token Account or Compute Budget instruction.
+///
+/// Other programs, and data that does not parse, fall back to the raw
+/// accounts and data.
+pub fn decode_instruction(ix: &Instruction) -> DecodedInstruction {
+    let decoded = if ix.program_id == solana_sdk::system_program::id() {
+        Some(("System", decode_system(ix)))
+    } else if ix.program_id == spl_token::id() {
+        Some(("SPL Token", decode_token(ix)))
+    } else if ix.program_id == spl_associated_token_account::id() {
+        Some(("Associated Token Account", decode_associated_token(ix)))
+    } else if ix.program_id == solana_sdk::compute_budget::id() {
+        Some(("Compute Budget", decode_compute_budget(ix)))
+    } else {
+        None
+    };
+
+    let (program, variant) = match decoded {
+        Some((program, variant)) => (Some(program), variant),
+        None => (None, None),
+    };
+    let (name, accounts) = match variant {
+        Some((name, roles)) => {
+            let accounts = roles
+                .iter()
+                .filter_map(|(role, index)| {
+                    ix.accounts.get(*index).map(|meta| (role.to_string(), meta.pubkey))
+                })
+                .collect();
+            (Some(name), accounts)
+        }
+        None => {
+            let accounts = ix
+                .accounts
+                .iter()
+                .enumerate()
+                .map(|(index, meta)| (format!("account {}", index), meta.pubkey))
+                .collect();
+            (None, accounts)
+        }
+    };
+
+    DecodedInstruction {
+        program_id: ix.program_id,
+        program,
+        name,
+        accounts,
+        data: ix.data.clone(),
+    }
+}
+
+/// Variant name and key account roles (by account index) of a decoded
+/// instruction.
+type DecodedVariant = Option<(String, &'static [(&'static str, usize)])>;
+
+/// Variant name from a `Debug` rendering such as `Transfer { amount: 5 }`.
+fn variant_name(debug: String) -> String {
+    match debug.find([' ', '{', '(']) {
+        Some(end) => debug[..end].to_string(),
+        None => debug,
+    }
+}
+
+fn decode_system(ix: &Instruction) -> DecodedVariant {
+    let instruction: SystemInstruction = bincode::deserialize(&ix.data).ok()?;
+    let roles: &'static [(&'static str, usize)] = match instruction {
+        SystemInstruction::Transfer { .. } => &[("from", 0), ("to", 1)],
+        SystemInstruction::CreateAccount { .. } => &[("from", 0), ("new_account", 1)],
+        SystemInstruction::Assign { .. } | SystemInstruction::Allocate { .. } => {
+            &[("account", 0)]
+        }
+        _ => &[],
+    };
+    Some((variant_name(format!("{:?}", instruction)), roles))
+}
+
+fn decode_token(ix: &Instruction) -> DecodedVariant {
+    let instruction = TokenInstruction::unpack(&ix.data).ok()?;
+    let roles: &'static [(&'static str, usize)] = match instruction {
+        TokenInstruction::Transfer { .. } => {
+            &[("source", 0), ("destination", 1), ("authority", 2)]
+        }
+        TokenInstruction::TransferChecked { .. } => {
+            &[("source", 0), ("mint", 1), ("destination", 2), ("authority", 3)]
+        }
+        TokenInstruction::CloseAccount => &[("account", 0), ("destination", 1), ("authority", 2)],
+        TokenInstruction::Burn { .. } | TokenInstruction::BurnChecked { .. } => {
+            &[("account", 0), ("mint", 1), ("authority", 2)]
+        }
+        TokenInstruction::MintTo { .. } | TokenInstruction::MintToChecked { .. } => {
+            &[("mint", 0), ("account", 1), ("authority", 2)]
+        }
+        TokenInstruction::InitializeAccount
+        | TokenInstruction::InitializeAccount2 { .. }
+        | TokenInstruction::InitializeAccount3 { .. } => &[("account", 0), ("mint", 1)],
+        TokenInstruction::Approve { .. } => &[("source", 0), ("delegate", 1), ("owner", 2)],
+        TokenInstruction::SyncNative => &[("account", 0)],
+        _ => &[],
+    };
+    Some((variant_name(format!("{:?}", instruction)), roles))
+}
+
+fn decode_associated_token(ix: &Instruction) -> DecodedVariant {
+    const ROLES: &[(&str, usize)] = &[("payer", 0), ("account", 1), ("wallet", 2), ("mint", 3)];
+    let name = match ix.data.first() {
+        None | Some(0) => "Create",
+        Some(1) => "CreateIdempotent",
+        Some(2) => return Some(("RecoverNested".to_string(), &[("destination", 5)])),
+        Some(_) => return None,
+    };
+    Some((name.to_string(), ROLES))
+}
+
+fn decode_compute_budget(ix: &Instruction) -> DecodedVariant {
+    let name = match ix.data.first()? {
+        0 => "RequestUnitsDeprecated",
+        1 => "RequestHeapFrame",
+        2 => "SetComputeUnitLimit",
+        3 => "SetComputeUnitPrice",
+        4 => "SetLoadedAccountsDataSizeLimit",
+        _ => return None,
+    };
+    Some((name.to_string(), &[]))
+}
+
+/// Rebuild instruction `index` of a compiled message.
+pub(crate) fn message_instruction(message: &Message, index: usize) -> Option<Instruction> {
+    let compiled = message.instructions.get(index)?;
+    let program_id = *message.account_keys.get(compiled.program_id_index as usize)?;
+    let accounts = compiled
+        .accounts
+        .iter()
+        .map(|&i| {
+            let i = i as usize;
+            Some(AccountMeta {
+                pubkey: *message.account_keys.get(i)?,
+                is_signer: message.is_signer(i),
+                is_writable: message.is_writable(i),
+            })
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(Instruction {
+        program_id,
+        accounts,
+        data: compiled.data.clone(),
+    })
+}
+
+/// Error for an attempt to send no instructions.
+pub(crate) fn no_instructions() -> ToolkitError {
+    ToolkitError::Custom("no instructions to execute".to_string())
+}
+
+/// Fail with [`no_instructions`] if `instructions` is empty.
+pub(crate) fn ensure_instructions(instructions: &[Instruction]) -> Result<()> {
+    if instructions.is_empty() {
+        return Err(no_instructions());
+    }
+    Ok(())
+}
+
+/// Error for a failed simulation of `transaction`, naming the decoded
+/// instruction that failed when the error points at one.
+pub(crate) fn simulation_error(transaction: &Transaction, err: &TransactionError) -> ToolkitError {
+    let failed = match err {
+        TransactionError::InstructionError(index, _) => {
+            message_instruction(&transaction.message, *index as usize)
+                .map(|ix| (*index, decode_instruction(&ix)))
+        }
+        _ => None,
+    };
+
+    match failed {
+        Some((index, decoded)) => ToolkitError::TransactionError(format!(
+            "Simulation failed: {:?} at instruction {} ({})",
+            err, index, decoded
+        )),
+        None => ToolkitError::TransactionError(format!("Simulation failed: {:?}", err)),
+    }
+}
+
 /// Check if a set of instructions will fit in a single transaction.
 pub fn will_fit_in_transaction(instructions: &[Instruction], num_signers: usize) -> bool {
     estimate_transaction_size(instructions, num_signers) <= MAX_TRANSACTION_SIZE
@@ -515,6 +2342,33 @@ mod tests {
         assert!(config.skip_preflight);
     }
 
+    #[test]
+    fn test_priority_fee_for_budget() {
+        // Fee the runtime charges for a price: ceil(price * units / 1e6)
+        let charged = |price: u64, units: u32| {
+            (price as u128 * units as u128).div_ceil(1_000_000) as u64
+        };
+
+        // 0.0001 SOL over 200k units is 500k micro-lamports per unit
+        assert_eq!(priority_fee_for_budget(100_000, 200_000), 500_000);
+        for (budget, units) in [(100_000, 200_000), (12_345, 300_000), (1, 1_400_000), (7, 3)] {
+            let price = priority_fee_for_budget(budget, units);
+            assert!(charged(price, units) <= budget);
+            assert!(charged(price + 1, units) >= budget);
+        }
+
+        assert_eq!(priority_fee_for_budget(100_000, 0), 0);
+        assert_eq!(priority_fee_for_budget(0, 200_000), 0);
+        assert_eq!(priority_fee_for_budget(u64::MAX, 1), u64::MAX);
+
+        let config = TransactionConfig::default().with_priority_budget(100_000);
+        assert_eq!(config.priority_fee_micro_lamports, Some(500_000));
+        let config = TransactionConfig::default()
+            .with_compute_units(50_000)
+            .with_priority_budget(100_000);
+        assert_eq!(config.priority_fee_micro_lamports, Some(2_000_000));
+    }
+
     #[test]
     fn test_transaction_builder() {
         let payer = Pubkey::new_unique();
@@ -528,16 +2382,1028 @@ mod tests {
         assert_eq!(builder.instruction_count(), 1);
     }
 
+    #[test]
+    fn test_build_signed_offline() {
+        let payer = Keypair::new();
+        let to = Pubkey::new_unique();
+        let blockhash = Hash::new_unique();
+
+        let tx = TransactionBuilder::new()
+            .add_instruction(system_instruction::transfer(&payer.pubkey(), &to, 1000))
+            .compute_units(100_000)
+            .build_signed(&[&payer], blockhash)
+            .unwrap();
+
+        assert_eq!(tx.message.recent_blockhash, blockhash);
+        assert_eq!(tx.message.instructions.len(), 2);
+        assert!(tx.verify().is_ok());
+    }
+
+    #[test]
+    fn test_dedup_compute_budget_keeps_last() {
+        let payer = Pubkey::new_unique();
+        let to = Pubkey::new_unique();
+
+        let instructions = TransactionBuilder::new()
+            .compute_units(100_000)
+            .add_instruction(ComputeBudgetInstruction::set_compute_unit_limit(200_000))
+            .add_instruction(system_instruction::transfer(&payer, &to, 1000))
+            .add_instruction(ComputeBudgetInstruction::set_compute_unit_limit(300_000))
+            .add_instruction(ComputeBudgetInstruction::set_compute_unit_price(5))
+            .dedup_compute_budget()
+            .build_instructions();
+
+        assert_eq!(
+            instructions,
+            vec![
+                system_instruction::transfer(&payer, &to, 1000),
+                ComputeBudgetInstruction::set_compute_unit_limit(300_000),
+                ComputeBudgetInstruction::set_compute_unit_price(5),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_auto_compute_units_scales_with_token_transfers() {
+        let (source, destination, owner) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let transfer = || {
+            spl_token::instruction::transfer(
+                &spl_token::id(),
+                &source,
+                &destination,
+                &owner,
+                &[],
+                1,
+            )
+            .unwrap()
+        };
+        let limit = |builder: TransactionBuilder| builder.build_instructions()[0].clone();
+
+        // Instructions added after the call are still counted
+        let one = TransactionBuilder::new().auto_compute_units().add_instruction(transfer());
+        let four = TransactionBuilder::new()
+            .auto_compute_units()
+            .add_instructions((0..4).map(|_| transfer()).collect());
+        assert_eq!(one.estimate_compute_units(), 4_400);
+        assert_eq!(four.estimate_compute_units(), 4 * 4_400);
+        assert_eq!(limit(one), ComputeBudgetInstruction::set_compute_unit_limit(4_400));
+        assert_eq!(
+            limit(four.clone()),
+            ComputeBudgetInstruction::set_compute_unit_limit(17_600)
+        );
+
+        // Explicit limits override the estimate
+        assert_eq!(
+            limit(four.clone().compute_units(50_000)),
+            ComputeBudgetInstruction::set_compute_unit_limit(50_000)
+        );
+        let manual = four
+            .add_instruction(ComputeBudgetInstruction::set_compute_unit_limit(30_000))
+            .build_instructions();
+        assert_eq!(manual.len(), 5);
+        assert_eq!(manual[0], transfer());
+
+        // Unknown programs get the runtime default and the total is capped
+        let heavy = TransactionBuilder::new().add_instructions(
+            (0..10)
+                .map(|_| Instruction::new_with_bytes(Pubkey::new_unique(), &[], vec![]))
+                .collect(),
+        );
+        assert_eq!(heavy.estimate_compute_units(), MAX_COMPUTE_UNITS);
+    }
+
+    #[test]
+    fn test_remove_duplicate_instructions() {
+        let payer = Pubkey::new_unique();
+        let to = Pubkey::new_unique();
+        let transfer = system_instruction::transfer(&payer, &to, 1000);
+
+        let builder = TransactionBuilder::new()
+            .add_instruction(transfer.clone())
+            .add_instruction(system_instruction::transfer(&payer, &to, 2000))
+            .add_instruction(transfer.clone());
+
+        // Dedup is opt-in
+        assert_eq!(builder.instruction_count(), 3);
+
+        let builder = builder.remove_duplicate_instructions();
+        assert_eq!(builder.instruction_count(), 2);
+        assert_eq!(builder.build_instructions()[0], transfer);
+    }
+
+    #[test]
+    fn test_build_signed_requires_signer() {
+        let result = TransactionBuilder::new().build_signed(&[], Hash::new_unique());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_signature_notification_result() {
+        use solana_client::rpc_response::{
+            ProcessedSignatureResult, ReceivedSignatureResult, RpcResponseContext,
+        };
+
+        let notification = |value| Response {
+            context: RpcResponseContext { slot: 42, api_version: None },
+            value,
+        };
+
+        let received = notification(RpcSignatureResult::ReceivedSignature(
+            ReceivedSignatureResult::ReceivedSignature,
+        ));
+        assert_eq!(signature_notification_result(&received), None);
+
+        let processed = notification(RpcSignatureResult::ProcessedSignature(
+            ProcessedSignatureResult { err: None },
+        ));
+        assert_eq!(
+            signature_notification_result(&processed),
+            Some(WsConfirmation { slot: 42, err: None })
+        );
+
+        let failed = notification(RpcSignatureResult::ProcessedSignature(
+            ProcessedSignatureResult { err: Some(TransactionError::AccountNotFound) },
+        ));
+        assert_eq!(
+            signature_notification_result(&failed).unwrap().err,
+            Some(TransactionError::AccountNotFound)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_safe_transfer_blocked_does_not_send() {
+        use crate::testing::MockRpc;
+
+        let from = Keypair::new();
+        let rpc = MockRpc::new().with_balance(from.pubkey(), 1_000);
+        let transfer = SafeTransfer::from_client(rpc, SafetyProtocol::new());
+
+        let outcome = transfer
+            .send_with_confirmation(&from, &Pubkey::new_unique(), 5_000, |_| true)
+            .await
+            .unwrap();
+
+        assert!(!outcome.report.approved);
+        assert!(!outcome.was_sent());
+        assert!(transfer.client.sent_transactions().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_safe_transfer_approved_sends() {
+        use crate::testing::MockRpc;
+
+        let from = Keypair::new();
+        let to = Pubkey::new_unique();
+        let rpc = MockRpc::new().with_balance(from.pubkey(), 10_000_000_000);
+        let transfer = SafeTransfer::from_client(rpc, SafetyProtocol::new());
+
+        let outcome = transfer.send(&from, &to, 1_000_000).await.unwrap();
+
+        assert!(outcome.report.approved);
+        assert!(outcome.was_sent());
+        let sent = transfer.client.sent_transactions();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].message.instructions.len(), 1);
+        assert_eq!(outcome.signature, Some(sent[0].signatures[0]));
+    }
+
+    #[tokio::test]
+    async fn test_safe_transfer_send_percentage() {
+        use crate::safety::FEE_RESERVE_LAMPORTS;
+        use crate::testing::MockRpc;
+
+        let from = Keypair::new();
+        let to = Pubkey::new_unique();
+        let balance = 10_000_000_000;
+        let rpc = MockRpc::new().with_balance(from.pubkey(), balance);
+        let transfer = SafeTransfer::from_client(rpc, SafetyProtocol::new());
+
+        let outcome = transfer.send_percentage(&from, &to, 25.0).await.unwrap();
+        assert!(outcome.was_sent());
+        let sent = transfer.client.sent_transactions();
+        let expected = system_instruction::transfer(
+            &from.pubkey(),
+            &to,
+            (balance - FEE_RESERVE_LAMPORTS) / 4,
+        );
+        assert_eq!(sent[0].message.instructions[0].data, expected.data);
+
+        // Nearly the whole balance needs confirmation, so isn't sent
+        let outcome = transfer.send_percentage(&from, &to, 100.0).await.unwrap();
+        assert!(outcome.report.approved);
+        assert!(!outcome.was_sent());
+
+        assert!(transfer.send_percentage(&from, &to, 150.0).await.is_err());
+        assert_eq!(transfer.client.sent_transactions().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_safe_transfer_requires_confirmation() {
+        use crate::testing::MockRpc;
+
+        let from = Keypair::new();
+        let to = Pubkey::new_unique();
+        let rpc = MockRpc::new().with_balance(from.pubkey(), 1_000_000);
+        let transfer = SafeTransfer::from_client(rpc, SafetyProtocol::new());
+
+        // Sending all but the fee needs confirmation; `send` declines it
+        let amount = 1_000_000 - LAMPORTS_PER_SIGNATURE;
+        let outcome = transfer.send(&from, &to, amount).await.unwrap();
+        assert!(outcome.report.requires_confirmation);
+        assert!(!outcome.was_sent());
+
+        let outcome = transfer
+            .send_with_confirmation(&from, &to, amount, |report| {
+                report.warnings.iter().any(|w| w.contains("entire balance"))
+            })
+            .await
+            .unwrap();
+        assert!(outcome.was_sent());
+        assert_eq!(transfer.client.sent_transactions().len(), 1);
+    }
+
+    #[cfg(feature = "tracing")]
+    #[tokio::test]
+    async fn test_execute_batches_emits_tracing_events() {
+        use crate::testing::MockRpc;
+        use std::sync::{Arc, Mutex};
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata, Subscriber};
+
+        /// Records span names and event messages.
+        #[derive(Clone, Default)]
+        struct Capture(Arc<Mutex<Vec<String>>>);
+
+        struct MessageVisitor(Option<String>);
+
+        impl Visit for MessageVisitor {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                if field.name() == "message" {
+                    self.0 = Some(format!("{:?}", value));
+                }
+            }
+        }
+
+        impl Subscriber for Capture {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+
+            fn new_span(&self, span: &Attributes<'_>) -> Id {
+                self.0.lock().unwrap().push(format!("span:{}", span.metadata().name()));
+                Id::from_u64(1)
+            }
+
+            fn record(&self, _: &Id, _: &Record<'_>) {}
+
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+            fn event(&self, event: &Event<'_>) {
+                let mut visitor = MessageVisitor(None);
+                event.record(&mut visitor);
+                if let Some(message) = visitor.0 {
+                    self.0.lock().unwrap().push(message);
+                }
+            }
+
+            fn enter(&self, _: &Id) {}
+
+            fn exit(&self, _: &Id) {}
+        }
+
+        let capture = Capture::default();
+        let _guard = tracing::subscriber::set_default(capture.clone());
+
+        let payer = Keypair::new();
+        let to = Pubkey::new_unique();
+        let rpc = MockRpc::new().with_balance(payer.pubkey(), 1_000_000_000);
+        let executor = BatchExecutor::from_client(rpc, TransactionConfig::default());
+        let batches = vec![
+            vec![system_instruction::transfer(&payer.pubkey(), &to, 1)],
+            vec![system_instruction::transfer(&payer.pubkey(), &to, 2)],
+        ];
+
+        let result = executor.execute_batches(batches, &[&payer]).await.unwrap();
+        assert_eq!(result.successful.len(), 2);
+
+        let events = capture.0.lock().unwrap().clone();
+        assert_eq!(events.first().map(String::as_str), Some("span:execute_batches"));
+        assert_eq!(events.iter().filter(|e| *e == "batch sent").count(), 2);
+        assert_eq!(events.last().map(String::as_str), Some("batch execution finished"));
+    }
+
     #[test]
     fn test_batch_result() {
         let result = BatchResult {
             successful: vec![Signature::default()],
             failed: vec![],
             instructions_processed: 5,
+            batch_items: vec![vec![0, 1, 2, 3, 4]],
         };
 
         assert!(result.all_succeeded());
         assert_eq!(result.success_rate(), 100.0);
+        assert!(result.failed_items().is_empty());
+    }
+
+    #[test]
+    fn test_decode_instruction() {
+        let (source, destination, owner) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+        let transfer = spl_token::instruction::transfer(
+            &spl_token::id(),
+            &source,
+            &destination,
+            &owner,
+            &[],
+            5,
+        )
+        .unwrap();
+        let decoded = decode_instruction(&transfer);
+        assert_eq!(decoded.program, Some("SPL Token"));
+        assert_eq!(decoded.name.as_deref(), Some("Transfer"));
+        assert_eq!(
+            decoded.accounts,
+            vec![
+                ("source".to_string(), source),
+                ("destination".to_string(), destination),
+                ("authority".to_string(), owner),
+            ]
+        );
+
+        let close = spl_token::instruction::close_account(
+            &spl_token::id(),
+            &source,
+            &destination,
+            &owner,
+            &[],
+        )
+        .unwrap();
+        let decoded = decode_instruction(&close);
+        assert_eq!(decoded.name.as_deref(), Some("CloseAccount"));
+        assert_eq!(decoded.accounts[0], ("account".to_string(), source));
+        assert!(decoded.to_string().starts_with("SPL Token CloseAccount [account: "));
+
+        let decoded = decode_instruction(&system_instruction::transfer(&source, &destination, 1));
+        assert_eq!((decoded.program, decoded.name.as_deref()), (Some("System"), Some("Transfer")));
+        let decoded = decode_instruction(&ComputeBudgetInstruction::set_compute_unit_limit(1));
+        assert_eq!(decoded.name.as_deref(), Some("SetComputeUnitLimit"));
+
+        // Unknown programs fall back to the raw accounts and data
+        let program = Pubkey::new_unique();
+        let raw = Instruction::new_with_bytes(
+            program,
+            &[1, 2, 3],
+            vec![AccountMeta::new(source, false)],
+        );
+        let decoded = decode_instruction(&raw);
+        assert_eq!((decoded.program, decoded.name), (None, None));
+        assert_eq!(decoded.accounts, vec![("account 0".to_string(), source)]);
+        assert_eq!(decoded.data, vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_simulation_failure_names_instruction() {
+        use crate::testing::MockRpc;
+        use solana_sdk::instruction::InstructionError;
+
+        let payer = Keypair::new();
+        let account = Pubkey::new_unique();
+        let rpc = MockRpc::new();
+        rpc.fail_simulations(TransactionError::InstructionError(1, InstructionError::Custom(11)));
+        let executor = BatchExecutor::from_client(rpc, TransactionConfig::default());
+        let instructions = vec![
+            ComputeBudgetInstruction::set_compute_unit_limit(10_000),
+            spl_token::instruction::close_account(
+                &spl_token::id(),
+                &account,
+                &payer.pubkey(),
+                &payer.pubkey(),
+                &[],
+            )
+            .unwrap(),
+        ];
+
+        let err = executor.execute_transaction(instructions, &[&payer]).await.unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("Simulation failed"));
+        assert!(message.contains("at instruction 1 (SPL Token CloseAccount"));
+        assert!(message.contains(&account.to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_fee_tiers_from_recent_fees() {
+        use crate::testing::MockRpc;
+
+        let payer = Keypair::new();
+        let rpc = MockRpc::new().with_balance(payer.pubkey(), 1_000_000_000);
+        // 100..=2000 in steps of 100, out of order
+        let mut fees: Vec<u64> = (1..=20).map(|i| i * 100).collect();
+        fees.reverse();
+        fees.swap(3, 11);
+        rpc.set_prioritization_fees(fees);
+
+        let executor = BatchExecutor::from_client(rpc, TransactionConfig::fast());
+        let tiers = executor.fee_tiers(&[payer.pubkey()]).await.unwrap();
+        assert_eq!(
+            tiers,
+            FeeTiers { slow: 500, normal: 1_000, fast: 1_500, urgent: 1_900 }
+        );
+        assert_eq!(tiers.get(FeeTier::Fast), 1_500);
+        assert_eq!(FeeTiers::from_fees(&[]), FeeTiers::default());
+        assert_eq!(FeeTiers::from_fees(&[7]).urgent, 7);
+
+        // A configured tier is resolved when sending
+        let executor = BatchExecutor::from_client(
+            executor.client.into_inner(),
+            TransactionConfig::fast().with_priority_fee(1).with_fee_tier(FeeTier::Fast),
+        );
+        let transfer = transfer_sol(&payer.pubkey(), &Pubkey::new_unique(), 1);
+        executor.execute_transaction(vec![transfer], &[&payer]).await.unwrap();
+        let sent = executor.client.sent_transactions();
+        let price = &sent[0].message.instructions[0].data;
+        assert_eq!(price[0], COMPUTE_UNIT_PRICE_TAG);
+        assert_eq!(price[1..9], 1_500u64.to_le_bytes());
+    }
+
+    #[tokio::test]
+    async fn test_execute_parallel_orders_results_by_index() {
+        use crate::testing::MockRpc;
+
+        let payer = Keypair::new();
+        let recipients: Vec<Pubkey> = (0..8).map(|_| Pubkey::new_unique()).collect();
+        let rpc = MockRpc::new().with_balance(payer.pubkey(), 1_000_000_000);
+        for recipient in recipients.iter().skip(1).step_by(3) {
+            rpc.fail_sends_touching(*recipient, "rejected");
+        }
+        rpc.set_latency_jitter(Duration::from_millis(30));
+        let executor = ParallelBatchExecutor::from_client(rpc, TransactionConfig::fast(), 8);
+        let batches: Vec<Vec<Instruction>> = recipients
+            .iter()
+            .map(|to| vec![transfer_sol(&payer.pubkey(), to, 1)])
+            .collect();
+
+        let result = executor.execute_parallel(batches.clone(), &[&payer]).await.unwrap();
+        let failed: Vec<usize> = result.failed.iter().map(|(idx, _)| *idx).collect();
+        assert_eq!(failed, vec![1, 4, 7]);
+        // Signatures follow the order the transactions were given in
+        let sent = executor.client.sent_transactions();
+        let mut expected: Vec<(Pubkey, Signature)> = sent
+            .iter()
+            .map(|tx| (tx.message.account_keys[1], tx.signatures[0]))
+            .collect();
+        expected.sort_by_key(|(to, _)| recipients.iter().position(|r| r == to));
+        let expected: Vec<Signature> = expected.into_iter().map(|(_, sig)| sig).collect();
+        assert_eq!(result.successful, expected);
+
+        // Completion order differs between runs; the result doesn't
+        let again = executor.execute_parallel(batches, &[&payer]).await.unwrap();
+        assert_eq!(again.failed, result.failed);
+        assert_eq!(again.successful, result.successful);
+    }
+
+    #[test]
+    fn test_base64_round_trip() {
+        use solana_sdk::message::{v0, VersionedMessage};
+
+        let payer = Keypair::new();
+        let transfer = transfer_sol(&payer.pubkey(), &Pubkey::new_unique(), 1);
+
+        // Unsigned legacy transactions are handed off for the wallet to sign
+        let legacy = TransactionBuilder::new()
+            .add_instruction(transfer.clone())
+            .build(&payer.pubkey(), Hash::new_unique());
+        let encoded = serialize_base64(&legacy).unwrap();
+        assert_eq!(deserialize_base64::<Transaction>(&encoded).unwrap(), legacy);
+
+        let message =
+            v0::Message::try_compile(&payer.pubkey(), &[transfer], &[], Hash::new_unique())
+                .unwrap();
+        let versioned = VersionedTransaction::try_new(VersionedMessage::V0(message), &[&payer])
+            .unwrap();
+        let encoded = serialize_versioned_base64(&versioned).unwrap();
+        assert_eq!(deserialize_base64::<VersionedTransaction>(&encoded).unwrap(), versioned);
+
+        assert!(matches!(
+            deserialize_base64::<Transaction>("not base64!"),
+            Err(ToolkitError::ParseError(_))
+        ));
+
+        // Wallets reject transactions over the size limit
+        let memo = Instruction::new_with_bytes(Pubkey::new_unique(), &[0; 1_300], vec![]);
+        let oversized = TransactionBuilder::new()
+            .add_instruction(memo)
+            .build(&payer.pubkey(), Hash::new_unique());
+        assert!(serialize_base64(&oversized).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fee_shortfall_reports_insufficient_balance() {
+        use crate::testing::MockRpc;
+
+        let payer = Keypair::new();
+        let recipient = Pubkey::new_unique();
+        let config = |base: TransactionConfig| {
+            base.with_compute_units(10_000).with_priority_fee(1_000_000)
+        };
+        let transfer = || vec![system_instruction::transfer(&payer.pubkey(), &recipient, 1_000)];
+        // One signature, 10k units at 1 lamport each, and the transfer itself
+        let needed = LAMPORTS_PER_SIGNATURE + 10_000 + 1_000;
+
+        let rpc = MockRpc::new().with_balance(payer.pubkey(), 3_000);
+        rpc.fail_sends("Transaction simulation failed: Insufficient funds for fee");
+        let executor = BatchExecutor::from_client(rpc, config(TransactionConfig::fast()));
+        let err = executor.execute_transaction(transfer(), &[&payer]).await.unwrap_err();
+        assert!(
+            matches!(
+                err,
+                ToolkitError::InsufficientBalance { needed: n, available: 3_000 } if n == needed
+            ),
+            "{}",
+            err
+        );
+
+        let rpc = MockRpc::new().with_balance(payer.pubkey(), 3_000);
+        rpc.fail_simulations(TransactionError::InsufficientFundsForFee);
+        let executor = BatchExecutor::from_client(rpc, config(TransactionConfig::default()));
+        let err = executor.execute_transaction(transfer(), &[&payer]).await.unwrap_err();
+        assert!(matches!(err, ToolkitError::InsufficientBalance { available: 3_000, .. }));
+        assert!(executor.client.sent_transactions().is_empty());
+
+        // A balance that covers the estimate leaves the RPC's error as is
+        let rpc = MockRpc::new().with_balance(payer.pubkey(), needed);
+        rpc.fail_sends("Transaction simulation failed: Insufficient funds for fee");
+        let executor = BatchExecutor::from_client(rpc, config(TransactionConfig::fast()));
+        let err = executor.execute_transaction(transfer(), &[&payer]).await.unwrap_err();
+        assert!(matches!(err, ToolkitError::TransactionError(_)));
+    }
+
+    #[tokio::test]
+    async fn test_execution_policy_enforced_before_send() {
+        use crate::testing::MockRpc;
+
+        let payer = Keypair::new();
+        let recipient = Pubkey::new_unique();
+        let rpc = MockRpc::new().with_balance(payer.pubkey(), 1_000_000_000);
+        rpc.fail_simulations(TransactionError::InsufficientFundsForFee);
+        // The fast config skips its own simulation; the policy still requires one
+        let executor = BatchExecutor::from_client(rpc, TransactionConfig::fast())
+            .with_execution_policy(ExecutionPolicy::strict());
+        let transfer = || vec![system_instruction::transfer(&payer.pubkey(), &recipient, 1_000)];
+
+        let err = executor.execute_transaction(transfer(), &[&payer]).await.unwrap_err();
+        assert!(err.to_string().contains("Simulation failed"));
+        assert!(executor.client.sent_transactions().is_empty());
+
+        let rpc = MockRpc::new().with_balance(payer.pubkey(), 1_000_000_000);
+        let mut drained = rpc.get_account(&payer.pubkey()).await.unwrap().unwrap();
+        drained.lamports -= 600_000_000;
+        rpc.set_simulated_account(payer.pubkey(), Some(drained));
+        let capped = ExecutionPolicy::default().with_max_lamports_out(500_000_000);
+        let executor = BatchExecutor::from_client(rpc, TransactionConfig::fast())
+            .with_execution_policy(capped.clone());
+        let err = executor.execute_transaction(transfer(), &[&payer]).await.unwrap_err();
+        assert!(err.to_string().contains("600000000 lamports"));
+        assert!(executor.client.sent_transactions().is_empty());
+
+        let executor = executor.with_execution_policy(capped.with_max_lamports_out(600_000_000));
+        executor.execute_transaction(transfer(), &[&payer]).await.unwrap();
+        assert_eq!(executor.client.sent_transactions().len(), 1);
+
+        let executor = executor.with_execution_policy(ExecutionPolicy::strict().with_dry_run(true));
+        let signature = executor.execute_transaction(transfer(), &[&payer]).await.unwrap();
+        assert_ne!(signature, Signature::default());
+        assert_eq!(executor.client.sent_transactions().len(), 1);
+    }
+
+    #[test]
+    fn test_builder_reuse_from_shared_base() {
+        let payer = Pubkey::new_unique();
+        let blockhash = Hash::new_unique();
+        let setup = system_instruction::transfer(&payer, &Pubkey::new_unique(), 1);
+        let base = TransactionBuilder::new()
+            .compute_units(100_000)
+            .add_instruction(setup.clone());
+
+        let first = base
+            .clone()
+            .add_instruction(system_instruction::transfer(&payer, &Pubkey::new_unique(), 2))
+            .build(&payer, blockhash);
+        let second = base
+            .clone()
+            .add_instruction(system_instruction::transfer(&payer, &Pubkey::new_unique(), 3))
+            .build(&payer, blockhash);
+        for tx in [&first, &second] {
+            assert_eq!(tx.message.instructions.len(), 3);
+            assert_eq!(tx.message.instructions[1].data, setup.data);
+        }
+        assert_ne!(first.message, second.message);
+
+        let instructions = base.build_instructions_ref();
+        assert_eq!(instructions.len(), 2);
+        assert_eq!(instructions[1], setup);
+        assert_eq!(base.build_ref(&payer, blockhash), base.build_ref(&payer, blockhash));
+        assert_eq!(base.build_ref(&payer, blockhash), base.build(&payer, blockhash));
+    }
+
+    #[tokio::test]
+    async fn test_send_persistent_resends_until_landed() {
+        use crate::testing::MockRpc;
+
+        let payer = Keypair::new();
+        let rpc = MockRpc::new().with_balance(payer.pubkey(), 1_000_000_000);
+        let config = TransactionConfig::fast().with_resend_interval(Duration::from_millis(5));
+        let executor = BatchExecutor::from_client(rpc, config);
+        let transaction = TransactionBuilder::new()
+            .add_instruction(transfer_sol(&payer.pubkey(), &Pubkey::new_unique(), 1))
+            .build_signed(&[&payer], executor.client.blockhash())
+            .unwrap();
+
+        // The first two sends are dropped
+        executor.client.land_after_sends(3);
+        let slot = executor
+            .send_persistent(&transaction, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(slot, 3);
+        let sent = executor.client.sent_transactions();
+        assert_eq!(sent.len(), 3);
+        assert!(sent.iter().all(|tx| tx.signatures == transaction.signatures));
+
+        // Never lands
+        executor.client.land_after_sends(usize::MAX);
+        let err = executor
+            .send_persistent(&transaction, Duration::from_millis(20))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolkitError::Timeout(_)), "{}", err);
+
+        // A rejected first send is not retried
+        let count = executor.client.sent_transactions().len();
+        executor.client.fail_sends("preflight failed");
+        assert!(executor.send_persistent(&transaction, Duration::from_secs(5)).await.is_err());
+        assert_eq!(executor.client.sent_transactions().len(), count);
+    }
+
+    #[tokio::test]
+    async fn test_confirm_until_block_height_expires() {
+        use crate::testing::MockRpc;
+        use solana_transaction_status::{TransactionConfirmationStatus, TransactionStatus};
+
+        let config = TransactionConfig::fast().with_resend_interval(Duration::from_millis(1));
+        let executor = BatchExecutor::from_client(MockRpc::new(), config);
+        let signature = Signature::new_unique();
+
+        // Heights 100..=102 are still valid; at 103 it can no longer land
+        executor.client.set_block_height(100);
+        let err = executor.confirm_until_block_height(&signature, 102).await.unwrap_err();
+        assert!(
+            matches!(err, ToolkitError::TransactionExpired { last_valid_block_height: 102, .. }),
+            "{}",
+            err
+        );
+        assert!(err.to_string().contains("expired"), "{}", err);
+        assert!(!err.is_retryable());
+
+        // Landing before the limit returns its slot
+        let status = |err: Option<TransactionError>| TransactionStatus {
+            slot: 7,
+            confirmations: None,
+            status: err.clone().map_or(Ok(()), Err),
+            err,
+            confirmation_status: Some(TransactionConfirmationStatus::Confirmed),
+        };
+        executor.client.set_block_height(100);
+        executor.client.set_signature_status(signature, status(None));
+        assert_eq!(executor.confirm_until_block_height(&signature, 102).await.unwrap(), 7);
+
+        // As does a failure, as an error
+        let failed = status(Some(TransactionError::InsufficientFundsForFee));
+        executor.client.set_signature_status(signature, failed);
+        let err = executor.confirm_until_block_height(&signature, 102).await.unwrap_err();
+        assert!(matches!(err, ToolkitError::TransactionError(_)), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_confirm_stable_detects_dropped_transactions() {
+        use crate::testing::MockRpc;
+        use solana_transaction_status::{TransactionConfirmationStatus, TransactionStatus};
+
+        let config = TransactionConfig::fast().with_resend_interval(Duration::from_millis(1));
+        let executor = BatchExecutor::from_client(MockRpc::new(), config);
+        let status = |level| {
+            Some(TransactionStatus {
+                slot: 7,
+                confirmations: Some(1),
+                status: Ok(()),
+                err: None,
+                confirmation_status: Some(level),
+            })
+        };
+        let processed = status(TransactionConfirmationStatus::Processed);
+        let confirmed = status(TransactionConfirmationStatus::Confirmed);
+
+        // Seen at processed, then gone before it was confirmed
+        let signature = Signature::new_unique();
+        executor
+            .client
+            .set_signature_status_sequence(signature, vec![None, processed.clone(), None]);
+        let err = executor
+            .confirm_stable(&signature, CommitmentConfig::confirmed())
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, ToolkitError::TransactionDropped { seen, .. } if seen == "processed"),
+            "{}",
+            err
+        );
+        assert!(err.to_string().contains("dropped"), "{}", err);
+
+        // Reached the target, but gone when re-checked
+        let signature = Signature::new_unique();
+        let sequence = vec![processed.clone(), confirmed.clone(), None];
+        executor.client.set_signature_status_sequence(signature, sequence);
+        let err = executor
+            .confirm_stable(&signature, CommitmentConfig::confirmed())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolkitError::TransactionDropped { .. }), "{}", err);
+
+        // Fell back below the target on the re-check
+        let signature = Signature::new_unique();
+        let sequence = vec![confirmed.clone(), processed.clone()];
+        executor.client.set_signature_status_sequence(signature, sequence);
+        let err = executor
+            .confirm_stable(&signature, CommitmentConfig::confirmed())
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, ToolkitError::TransactionDropped { seen, .. } if seen == "confirmed"),
+            "{}",
+            err
+        );
+
+        // Stays confirmed: settled
+        let signature = Signature::new_unique();
+        executor.client.set_signature_status_sequence(signature, vec![processed, confirmed]);
+        let slot = executor
+            .confirm_stable(&signature, CommitmentConfig::confirmed())
+            .await
+            .unwrap();
+        assert_eq!(slot, 7);
+    }
+
+    #[tokio::test]
+    async fn test_recommended_compute_units() {
+        use crate::testing::MockRpc;
+
+        let payer = Keypair::new();
+        let rpc = MockRpc::new().with_balance(payer.pubkey(), 1_000_000_000);
+        let config = TransactionConfig::fast()
+            .with_compute_unit_tracking(true)
+            .with_compute_unit_margin_bps(2_000);
+        let executor = BatchExecutor::from_client(rpc, config);
+        let recipient = Pubkey::new_unique();
+        let transfer = vec![system_instruction::transfer(&payer.pubkey(), &recipient, 1)];
+        assert_eq!(executor.recommended_compute_units(&transfer), None);
+
+        for units in [1_200, 4_000, 2_500] {
+            executor.record_compute_units(&transfer, units);
+        }
+        // 20% over the highest observed usage
+        assert_eq!(executor.recommended_compute_units(&transfer), Some(4_800));
+
+        // Compute budget instructions don't change the instruction set
+        let mut budgeted = transfer.clone();
+        budgeted.insert(0, ComputeBudgetInstruction::set_compute_unit_limit(10_000));
+        assert_eq!(executor.recommended_compute_units(&budgeted), Some(4_800));
+        let memo = Instruction::new_with_bytes(Pubkey::new_unique(), b"memo", vec![]);
+        assert_eq!(executor.recommended_compute_units(&[memo]), None);
+
+        // Usage of confirmed transactions is fetched after sending
+        executor.client.set_units_consumed(9_000);
+        executor.execute_transaction(transfer.clone(), &[&payer]).await.unwrap();
+        assert_eq!(executor.recommended_compute_units(&transfer), Some(10_800));
+
+        executor.record_compute_units(&transfer, u64::MAX);
+        assert_eq!(executor.recommended_compute_units(&transfer), Some(MAX_COMPUTE_UNITS));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_send_and_confirm_honors_policy() {
+        use crate::testing::MockRpc;
+        use solana_sdk::commitment_config::CommitmentLevel;
+        use tokio::time::Instant;
+        use solana_transaction_status::{TransactionConfirmationStatus, TransactionStatus};
+
+        let payer = Keypair::new();
+        let rpc = MockRpc::new();
+        let message = Message::new(
+            &[system_instruction::transfer(&payer.pubkey(), &Pubkey::new_unique(), 1)],
+            Some(&payer.pubkey()),
+        );
+        let unsigned = Transaction::new_unsigned(message);
+        let quick = SendPolicy::default()
+            .with_timeout(Duration::from_millis(1))
+            .with_backoff(Duration::from_millis(1));
+
+        // Unsigned transactions need signers
+        let err = send_and_confirm(&rpc, &unsigned, &[], &quick).await.unwrap_err();
+        assert!(matches!(err, ToolkitError::SigningError(_)));
+        assert!(rpc.sent_transactions().is_empty());
+
+        // Preflight settings reach the node
+        let policy = quick.clone().with_skip_preflight(true);
+        let signature = send_and_confirm(&rpc, &unsigned, &[&payer], &policy).await.unwrap();
+        let config = rpc.send_configs()[0];
+        assert!(config.skip_preflight);
+        assert_eq!(config.preflight_commitment, Some(CommitmentLevel::Confirmed));
+        assert_eq!(rpc.sent_transactions()[0].signatures[0], signature);
+
+        // Confirmed but not yet finalized: a landed transaction is waited on
+        // past the policy timeout rather than reported as failed
+        rpc.set_signature_status(
+            signature,
+            TransactionStatus {
+                slot: 1,
+                confirmations: Some(1),
+                status: Ok(()),
+                err: None,
+                confirmation_status: Some(TransactionConfirmationStatus::Confirmed),
+            },
+        );
+        send_and_confirm(&rpc, &unsigned, &[&payer], &quick).await.unwrap();
+        let policy = quick.clone().with_commitment(CommitmentConfig::finalized());
+        let signers = [&payer];
+        let send = send_and_confirm(&rpc, &unsigned, &signers, &policy);
+        assert!(tokio::time::timeout(Duration::from_secs(600), send).await.is_err());
+        assert_eq!(rpc.send_configs()[2].preflight_commitment, Some(CommitmentLevel::Finalized));
+
+        // Unconfirmed sends are resent after the timeout, and the last one is
+        // waited on until its blockhash expires
+        let rpc = MockRpc::new();
+        rpc.land_after_sends(3);
+        let err = send_and_confirm(&rpc, &unsigned, &[&payer], &quick.clone().with_max_retries(1))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolkitError::TransactionExpired { .. }), "{}", err);
+        assert_eq!(rpc.sent_transactions().len(), 2);
+        rpc.land_after_sends(4);
+        let policy = quick.clone().with_max_retries(5).with_backoff(Duration::from_millis(40));
+        let started = Instant::now();
+        send_and_confirm(&rpc, &unsigned, &[&payer], &policy).await.unwrap();
+        // Lands on the first resend, after one backoff
+        assert_eq!(rpc.sent_transactions().len(), 4);
+        assert!(started.elapsed() >= Duration::from_millis(40));
+        assert!(started.elapsed() < Duration::from_millis(120));
+
+        // A payer that can't pay is not retried
+        let rpc = MockRpc::new();
+        rpc.fail_sends("Transaction simulation failed: Insufficient funds for fee");
+        let policy = quick.clone().with_max_retries(3);
+        let err = send_and_confirm(&rpc, &unsigned, &[&payer], &policy).await.unwrap_err();
+        assert!(err.to_string().contains("Insufficient funds"), "{}", err);
+        assert_eq!(rpc.send_configs().len(), 1);
+
+        // Websocket confirmation subscribes before sending
+        let rpc = MockRpc::new();
+        let policy = quick.with_confirm_strategy(ConfirmStrategy::Websocket {
+            ws_url: "ws://127.0.0.1:1".to_string(),
+        });
+        let err = send_and_confirm(&rpc, &unsigned, &[&payer], &policy).await.unwrap_err();
+        assert!(err.to_string().contains("Websocket connect failed"), "{}", err);
+        assert!(rpc.send_configs().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_simulate_all_reports_each_batch() {
+        use crate::testing::MockRpc;
+        use solana_sdk::instruction::InstructionError;
+
+        let payer = Keypair::new();
+        let rpc = MockRpc::new().with_balance(payer.pubkey(), 1_000_000_000);
+        let config = TransactionConfig::fast().with_compute_units(50_000);
+        let executor = BatchExecutor::from_client(rpc, config);
+        let recipients = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        let failure = TransactionError::InstructionError(1, InstructionError::Custom(1));
+        executor.client.fail_simulations_touching(recipients[1], failure);
+        executor.client.set_units_consumed(450);
+
+        let batches = recipients
+            .iter()
+            .map(|to| vec![transfer_sol(&payer.pubkey(), to, 1)])
+            .collect();
+        let reports = executor.simulate_all(batches, &[&payer]).await.unwrap();
+
+        assert_eq!(reports.len(), 3);
+        assert!(reports[0].is_success() && reports[2].is_success());
+        let error = reports[1].error.as_deref().unwrap();
+        assert!(error.contains("at instruction 1 (System Transfer"), "{}", error);
+        for report in &reports {
+            // The compute budget instruction runs ahead of the transfer
+            assert_eq!(report.logs.len(), 2);
+            assert!(report.logs[0].contains(&solana_sdk::compute_budget::id().to_string()));
+            assert_eq!(report.units_consumed, Some(450));
+        }
+        assert!(executor.client.sent_transactions().is_empty());
+
+        assert!(executor.simulate_all(vec![], &[]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_empty_instruction_lists_are_rejected() {
+        use crate::testing::MockRpc;
+
+        let payer = Keypair::new();
+        let rpc = MockRpc::new().with_balance(payer.pubkey(), 1_000_000_000);
+        let config = TransactionConfig::fast().with_compute_units(50_000);
+        let executor = BatchExecutor::from_client(rpc, config.clone());
+        let is_empty_error = |err: &ToolkitError| {
+            matches!(err, ToolkitError::Custom(message) if message == "no instructions to execute")
+        };
+
+        let err = executor.execute_transaction(vec![], &[&payer]).await.unwrap_err();
+        assert!(is_empty_error(&err), "{}", err);
+        let err = executor.execute_batches(vec![vec![], vec![]], &[&payer]).await.unwrap_err();
+        assert!(is_empty_error(&err), "{}", err);
+        let err = executor.execute_all(vec![], &[&payer], 5).await.unwrap_err();
+        assert!(is_empty_error(&err), "{}", err);
+        let err = executor.simulate_all(vec![], &[&payer]).await.unwrap_err();
+        assert!(is_empty_error(&err), "{}", err);
+
+        // An empty batch among others fails on its own
+        let transfer = transfer_sol(&payer.pubkey(), &Pubkey::new_unique(), 1);
+        let batches = vec![vec![transfer.clone()], vec![]];
+        let result = executor.execute_batches(batches.clone(), &[&payer]).await.unwrap();
+        assert_eq!(result.successful.len(), 1);
+        assert_eq!(result.failed[0].0, 1);
+        let reports = executor.simulate_all(batches.clone(), &[&payer]).await.unwrap();
+        assert!(reports[0].is_success() && !reports[1].is_success());
+        assert_eq!(executor.client.sent_transactions().len(), 1);
+
+        let parallel = ParallelBatchExecutor::from_client(MockRpc::new(), config.clone(), 2);
+        let err = parallel.execute_parallel(vec![vec![]], &[&payer]).await.unwrap_err();
+        assert!(is_empty_error(&err), "{}", err);
+        let result = parallel.execute_parallel(batches, &[&payer]).await.unwrap();
+        assert_eq!(result.failed, vec![(1, "Custom error: no instructions to execute".into())]);
+        assert_eq!(parallel.client.sent_transactions().len(), 1);
+
+        // The builder adds no budget to nothing, and won't sign it
+        let builder = TransactionBuilder::with_config(config);
+        assert!(builder.build_instructions_ref().is_empty());
+        let err = builder.build_signed(&[&payer], Hash::default()).unwrap_err();
+        assert!(is_empty_error(&err), "{}", err);
+
+        // A transaction holding only a compute budget is not sent
+        let budget_only = Transaction::new_signed_with_payer(
+            &[ComputeBudgetInstruction::set_compute_unit_limit(1_000)],
+            Some(&payer.pubkey()),
+            &[&payer],
+            Hash::default(),
+        );
+        let policy = SendPolicy::default();
+        let err = send_and_confirm(&executor.client, &budget_only, &[], &policy).await.unwrap_err();
+        assert!(is_empty_error(&err), "{}", err);
+        assert_eq!(executor.client.sent_transactions().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_failed_items_maps_to_original_indices() {
+        use crate::testing::MockRpc;
+
+        let payer = Keypair::new();
+        let bad_recipient = Pubkey::new_unique();
+        let rpc = MockRpc::new().with_balance(payer.pubkey(), 1_000_000_000);
+        rpc.fail_sends_touching(bad_recipient, "recipient rejected");
+        let config = TransactionConfig {
+            max_retries: 0,
+            ..Default::default()
+        };
+        let executor = BatchExecutor::from_client(rpc, config);
+
+        let mut recipients: Vec<Pubkey> = (0..7).map(|_| Pubkey::new_unique()).collect();
+        recipients[3] = bad_recipient;
+        let instructions: Vec<Instruction> = recipients
+            .iter()
+            .map(|to| system_instruction::transfer(&payer.pubkey(), to, 1))
+            .collect();
+
+        // Batches: [0, 1], [2, 3, 4], [5, 6]
+        let batches = vec![
+            instructions[0..2].to_vec(),
+            instructions[2..5].to_vec(),
+            instructions[5..7].to_vec(),
+        ];
+        let result = executor.execute_batches(batches, &[&payer]).await.unwrap();
+
+        assert_eq!(result.successful.len(), 2);
+        assert_eq!(result.failed.len(), 1);
+        assert_eq!(result.failed[0].0, 1);
+        assert_eq!(result.failed_items(), vec![2, 3, 4]);
+        assert_eq!(result.instructions_processed, 4);
     }
 
     #[test]
@@ -0,0 +1,256 @@
+//! One wallet, every helper.
+//!
+//! [`Wallet`] holds a keypair and a shared RPC client and hands out the
+//! crate's helpers already wired to them, with the same timeout, priority
+//! fee and execution policy:
+//!
+//! ```rust,ignore
+//! let wallet = Wallet::new_on(Network::Devnet, payer)
+//!     .with_timeout(Duration::from_secs(10))
+//!     .with_priority_fee(5_000);
+//!
+//! let balance = wallet.accounts().get_balance(&wallet.pubkey()).await?;
+//! let recovered = wallet.rent().execute_cleanup().await?;
+//! ```
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+use crate::account_utils::AccountUtils;
+use crate::rent_cleaner::{AdvancedCleanupConfig, AdvancedRentCleaner};
+use crate::rpc::{Network, SolanaRpc, DEFAULT_RPC_TIMEOUT};
+use crate::token_utils::TokenClient;
+use crate::transaction::{BatchExecutor, ExecutionPolicy, TransactionConfig};
+
+#[cfg(feature = "jupiter")]
+use crate::jupiter::{JupiterClient, SwapConfig};
+
+/// A keypair and RPC client shared by the crate's helpers.
+///
+/// Each accessor builds a fresh helper around the same client, so they
+/// share its connection and commitment. The keypair is copied into helpers
+/// that sign.
+pub struct Wallet<R = RpcClient> {
+    client: Arc<R>,
+    payer: Keypair,
+    network: Option<Network>,
+    timeout: Duration,
+    priority_fee_micro_lamports: Option<u64>,
+    policy: ExecutionPolicy,
+}
+
+impl Wallet {
+    /// Create a wallet on `rpc_url` at confirmed commitment.
+    pub fn new(rpc_url: &str, payer: Keypair) -> Self {
+        Self::from_client(
+            RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed()),
+            payer,
+        )
+    }
+
+    /// Create for a well-known network.
+    pub fn new_on(network: Network, payer: Keypair) -> Self {
+        Self::new(network.rpc_url(), payer).with_network(network)
+    }
+
+    /// Jupiter client for swaps, sending through the wallet's client.
+    ///
+    /// Sign swaps with [`keypair`](Self::keypair) and pass
+    /// [`swap_config`](Self::swap_config) for the wallet's priority fee.
+    #[cfg(feature = "jupiter")]
+    pub fn swap(&self) -> JupiterClient {
+        JupiterClient::new(&self.client.url())
+            .with_rpc_client(Arc::clone(&self.client))
+            .with_timeout(self.timeout)
+    }
+}
+
+impl<R: SolanaRpc> Wallet<R> {
+    /// Create from an existing RPC backend.
+    pub fn from_client(client: R, payer: Keypair) -> Self {
+        Self::from_shared(Arc::new(client), payer)
+    }
+
+    /// Create from a backend that is already shared elsewhere.
+    pub fn from_shared(client: Arc<R>, payer: Keypair) -> Self {
+        Self {
+            client,
+            payer,
+            network: None,
+            timeout: DEFAULT_RPC_TIMEOUT,
+            priority_fee_micro_lamports: None,
+            policy: ExecutionPolicy::default(),
+        }
+    }
+
+    /// Record the cluster the client targets, for the rent cleaner's
+    /// `test_networks_only` check.
+    pub fn with_network(mut self, network: Network) -> Self {
+        self.network = Some(network);
+        self
+    }
+
+    /// Fail RPC calls that take longer than `timeout` (30s by default)
+    /// with [`ToolkitError::Timeout`](crate::ToolkitError::Timeout).
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the priority fee in micro-lamports per compute unit for helpers
+    /// that build their own transactions.
+    pub fn with_priority_fee(mut self, micro_lamports: u64) -> Self {
+        self.priority_fee_micro_lamports = Some(micro_lamports);
+        self
+    }
+
+    /// Enforce `policy` before every send of the token client, rent cleaner
+    /// and batch executor.
+    pub fn with_execution_policy(mut self, policy: ExecutionPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// The wallet's address.
+    pub fn pubkey(&self) -> Pubkey {
+        self.payer.pubkey()
+    }
+
+    /// The wallet's keypair.
+    pub fn keypair(&self) -> &Keypair {
+        &self.payer
+    }
+
+    /// The shared RPC client.
+    pub fn client(&self) -> &Arc<R> {
+        &self.client
+    }
+
+    /// Token client paying from and signing with the wallet, with the
+    /// wallet's priority fee.
+    pub fn tokens(&self) -> TokenClient<Arc<R>> {
+        let tokens =
+            TokenClient::from_client(Arc::clone(&self.client), self.payer.insecure_clone())
+                .with_execution_policy(self.policy.clone())
+                .with_timeout(self.timeout);
+        match self.priority_fee_micro_lamports {
+            Some(fee) => tokens.with_priority_fee(fee),
+            None => tokens,
+        }
+    }
+
+    /// Rent cleaner for the wallet's accounts with the default
+    /// [`AdvancedCleanupConfig`] and the wallet's priority fee.
+    pub fn rent(&self) -> AdvancedRentCleaner<Arc<R>> {
+        self.rent_with_config(AdvancedCleanupConfig::default())
+    }
+
+    /// Rent cleaner with `config`, applying the wallet's priority fee
+    /// unless `config` sets its own.
+    pub fn rent_with_config(
+        &self,
+        mut config: AdvancedCleanupConfig,
+    ) -> AdvancedRentCleaner<Arc<R>> {
+        config.priority_fee_micro_lamports =
+            config.priority_fee_micro_lamports.or(self.priority_fee_micro_lamports);
+        let payer = self.payer.insecure_clone();
+        let cleaner = AdvancedRentCleaner::from_client(Arc::clone(&self.client), payer, config)
+            .with_execution_policy(self.policy.clone())
+            .with_timeout(self.timeout);
+        match self.network {
+            Some(network) => cleaner.with_network(network),
+            None => cleaner,
+        }
+    }
+
+    /// Account reader on the wallet's client.
+    pub fn accounts(&self) -> AccountUtils<Arc<R>> {
+        AccountUtils::from_client(Arc::clone(&self.client)).with_timeout(self.timeout)
+    }
+
+    /// Batch executor with the wallet's priority fee. Pass
+    /// [`keypair`](Self::keypair) as the signer.
+    pub fn transactions(&self) -> BatchExecutor<Arc<R>> {
+        let config = TransactionConfig {
+            priority_fee_micro_lamports: self.priority_fee_micro_lamports,
+            ..TransactionConfig::default()
+        };
+        BatchExecutor::from_client(Arc::clone(&self.client), config)
+            .with_execution_policy(self.policy.clone())
+            .with_timeout(self.timeout)
+    }
+
+    /// Swap settings with the wallet's priority fee.
+    #[cfg(feature = "jupiter")]
+    pub fn swap_config(&self) -> SwapConfig {
+        SwapConfig {
+            priority_fee_micro_lamports: self.priority_fee_micro_lamports,
+            ..SwapConfig::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockRpc;
+    use crate::ToolkitError;
+    use solana_sdk::{account::Account, hash::Hash};
+
+    #[tokio::test]
+    async fn test_helpers_share_client_and_config() {
+        let payer = Keypair::new();
+        let pubkey = payer.pubkey();
+        let wallet = Wallet::from_client(MockRpc::new().with_balance(pubkey, 42), payer)
+            .with_timeout(Duration::from_millis(50))
+            .with_priority_fee(7);
+
+        let (tokens, rent, accounts) = (wallet.tokens(), wallet.rent(), wallet.accounts());
+        let transactions = wallet.transactions();
+        assert_eq!(Arc::strong_count(wallet.client()), 5);
+
+        // The priority fee reaches the token client's transactions
+        let tx = tokens.build_transaction(&[], Hash::default());
+        assert_eq!(tx.message.instructions[0].data[1..9], 7u64.to_le_bytes());
+        drop((tokens, transactions));
+        assert_eq!(Arc::strong_count(wallet.client()), 3);
+
+        // Writes through the wallet's client are seen by the helpers
+        let other = Pubkey::new_unique();
+        wallet.client().set_account(other, Account::new(9, 0, &Pubkey::new_unique()));
+        assert_eq!(accounts.get_balance(&other).await.unwrap(), 9);
+        assert_eq!(accounts.get_balance(&pubkey).await.unwrap(), 42);
+
+        // The priority fee reaches the rent cleaner's transactions
+        let tx = rent.build_cleanup_transaction(&[], Hash::default()).unwrap();
+        let price = tx.message.instructions[0].data.clone();
+        assert_eq!(price[1..9], 7u64.to_le_bytes());
+        assert_eq!(tx.message.account_keys[0], pubkey);
+
+        // So does the timeout
+        wallet.client().set_latency(Duration::from_millis(200));
+        let err = accounts.get_balance(&pubkey).await.unwrap_err();
+        assert!(matches!(err, ToolkitError::Timeout(_)), "{}", err);
+        let err = rent.scan_accounts().await.unwrap_err();
+        assert!(matches!(err, ToolkitError::Timeout(_)), "{}", err);
+    }
+
+    #[cfg(feature = "jupiter")]
+    #[test]
+    fn test_swap_shares_client_and_priority_fee() {
+        let wallet = Wallet::new("http://127.0.0.1:1", Keypair::new()).with_priority_fee(9);
+
+        let jupiter = wallet.swap();
+        assert_eq!(Arc::strong_count(wallet.client()), 2);
+        drop(jupiter);
+        assert_eq!(Arc::strong_count(wallet.client()), 1);
+        assert_eq!(wallet.swap_config().priority_fee_micro_lamports, Some(9));
+    }
+}
@@ -0,0 +1,45 @@
+//! Websocket confirmation against a local validator.
+//!
+//! Run with `solana-test-validator` listening on the default ports:
+//! `cargo test --features integration-tests --test ws_confirm`
+
+#![cfg(feature = "integration-tests")]
+
+use std::time::Duration;
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_pipkit::transaction::{BatchExecutor, ConfirmStrategy, TransactionConfig};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction,
+};
+
+const RPC_URL: &str = "http://127.0.0.1:8899";
+const WS_URL: &str = "ws://127.0.0.1:8900";
+
+#[tokio::test]
+async fn test_confirm_transfer_via_websocket() {
+    let payer = Keypair::new();
+    let rpc = RpcClient::new_with_commitment(RPC_URL.to_string(), CommitmentConfig::confirmed());
+    let airdrop = rpc.request_airdrop(&payer.pubkey(), LAMPORTS_PER_SOL).await.unwrap();
+
+    let config = TransactionConfig::default()
+        .with_confirm_strategy(ConfirmStrategy::Websocket { ws_url: WS_URL.to_string() });
+    let executor = BatchExecutor::from_client(rpc, config);
+
+    let airdrop_confirmation = executor
+        .confirm_via_ws(WS_URL, &airdrop, CommitmentConfig::confirmed(), Duration::from_secs(30))
+        .await
+        .unwrap();
+    assert!(airdrop_confirmation.err.is_none());
+    assert!(airdrop_confirmation.slot > 0);
+
+    let recipient = Pubkey::new_unique();
+    let transfer = system_instruction::transfer(&payer.pubkey(), &recipient, LAMPORTS_PER_SOL / 10);
+    let signature = executor.execute_transaction(vec![transfer], &[&payer]).await.unwrap();
+
+    assert_ne!(signature, Default::default());
+}